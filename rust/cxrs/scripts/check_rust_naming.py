@@ -5,12 +5,12 @@ import re
 import sys
 
 PATTERNS = {
-    "fn": re.compile(r"^\s*(?:pub\s+)?(?:async\s+)?fn\s+([A-Za-z_][A-Za-z0-9_]*)\s*\("),
+    "fn": re.compile(r"^\s*(?:pub\s+)?(?:const\s+)?(?:async\s+)?fn\s+([A-Za-z_][A-Za-z0-9_]*)\s*\("),
     "struct": re.compile(r"^\s*(?:pub\s+)?struct\s+([A-Za-z_][A-Za-z0-9_]*)\b"),
     "enum": re.compile(r"^\s*(?:pub\s+)?enum\s+([A-Za-z_][A-Za-z0-9_]*)\b"),
     "trait": re.compile(r"^\s*(?:pub\s+)?trait\s+([A-Za-z_][A-Za-z0-9_]*)\b"),
     "type": re.compile(r"^\s*(?:pub\s+)?type\s+([A-Za-z_][A-Za-z0-9_]*)\b"),
-    "const": re.compile(r"^\s*(?:pub\s+)?const\s+([A-Za-z_][A-Za-z0-9_]*)\b"),
+    "const": re.compile(r"^\s*(?:pub\s+)?const\s+(?!fn\b)([A-Za-z_][A-Za-z0-9_]*)\b"),
 }
 
 PAT_SNAKE = re.compile(r"^[a-z][a-z0-9_]*$")
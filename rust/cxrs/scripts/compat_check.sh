@@ -163,6 +163,7 @@ extract_worklog_json() {
     BEGIN { by=0; tl=0; runs=""; }
     /^- Runs analyzed: / { sub(/^- Runs analyzed: /, "", $0); runs=$0 }
     /^##+[[:space:]]+By Tool/ { by=1; tl=0; next }
+    /^##+[[:space:]]+By (Day|Task)/ { by=0; next }
     /^##+[[:space:]]+Chronological Runs/ { by=0; tl=1; next }
     /^\| Tool \| Runs \| Avg Duration \(ms\) \| Avg Effective Tokens \|/ { next }
     /^\|---\|---:\|---:\|---:\|/ { next }
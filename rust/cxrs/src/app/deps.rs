@@ -2,8 +2,7 @@ use super::*;
 
 pub(super) fn compat_deps() -> compat_cmd::CompatDeps {
     compat_cmd::CompatDeps {
-        print_help,
-        print_task_help,
+        cmd_help,
         print_version: compat_print_version,
         cmd_doctor: compat_cmd_doctor,
         cmd_where: compat_cmd_where,
@@ -19,10 +18,12 @@ pub(super) fn compat_deps() -> compat_cmd::CompatDeps {
         cmd_prompt_stats,
         print_profile,
         print_trace,
+        print_trace_by_id,
+        print_trace_remediations,
         print_alert,
         parse_optimize_args,
         print_optimize,
-        print_worklog,
+        cmd_worklog,
         cmd_cx,
         cmd_cxj,
         cmd_cxo,
@@ -35,6 +36,7 @@ pub(super) fn compat_deps() -> compat_cmd::CompatDeps {
         cmd_state_set,
         cmd_llm: compat_cmd_llm,
         cmd_bench,
+        cmd_bench_llm,
         cmd_prompt,
         cmd_roles,
         cmd_fanout,
@@ -58,17 +60,24 @@ pub(super) fn compat_deps() -> compat_cmd::CompatDeps {
         cmd_replay,
         cmd_quarantine_list,
         cmd_quarantine_show,
+        cmd_quarantine_resolve,
+        cmd_quarantine_rm,
+        cmd_quarantine_purge,
+        cmd_quarantine_stats,
     }
 }
 
 pub(super) fn native_deps() -> native_cmd::NativeDeps {
     native_cmd::NativeDeps {
         print_help,
-        print_task_help,
+        cmd_help,
         print_version: native_print_version,
         cmd_schema: native_cmd_schema,
+        cmd_exit_codes,
+        cmd_redact_test,
         cmd_logs: native_cmd_logs,
         cmd_ci: native_cmd_ci,
+        cmd_hook: native_cmd_hook,
         cmd_core: native_cmd_core,
         cmd_task,
         cmd_where: native_cmd_where,
@@ -83,9 +92,18 @@ pub(super) fn native_deps() -> native_cmd::NativeDeps {
         cmd_state_get,
         cmd_state_set,
         cmd_llm: native_cmd_llm,
+        cmd_model,
+        cmd_workspace,
+        cmd_init,
+        cmd_gc,
+        cmd_annotate,
+        cmd_secret,
+        cmd_changelog,
+        cmd_config: native_cmd_config,
         cmd_policy: native_cmd_policy,
         cmd_broker: native_cmd_broker,
         cmd_bench,
+        cmd_bench_llm,
         print_metrics,
         cmd_quota,
         cmd_prompt_stats,
@@ -100,9 +118,15 @@ pub(super) fn native_deps() -> native_cmd::NativeDeps {
         cmd_cxol,
         cmd_cxcopy,
         cmd_fix,
+        cmd_followup,
+        cmd_explain,
+        cmd_pipeline,
+        cmd_custom,
+        cmd_session,
         cmd_budget,
         cmd_log_tail,
         cmd_health: native_cmd_health,
+        cmd_selftest,
         cmd_capture_status,
         cmd_log_on,
         cmd_log_off,
@@ -110,19 +134,34 @@ pub(super) fn native_deps() -> native_cmd::NativeDeps {
         cmd_alert_on,
         cmd_alert_off,
         cmd_chunk,
+        cmd_pack,
         print_profile,
         print_alert,
         parse_optimize_args,
         print_optimize,
-        print_worklog,
+        cmd_worklog,
         print_trace,
+        print_trace_by_id,
+        print_trace_remediations,
+        cmd_transcript: native_cmd_transcript,
+        cmd_diff_runs,
+        cmd_trends,
+        cmd_digest,
+        cmd_watch,
         cmd_next,
         cmd_diffsum,
         cmd_fix_run,
         cmd_commitjson,
         cmd_commitmsg,
+        cmd_stage,
         cmd_replay,
+        cmd_rerun,
         cmd_quarantine_list,
         cmd_quarantine_show,
+        cmd_quarantine_resolve,
+        cmd_quarantine_rm,
+        cmd_quarantine_purge,
+        cmd_quarantine_stats,
+        cmd_quarantine_diff,
     }
 }
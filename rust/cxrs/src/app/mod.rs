@@ -1,16 +1,20 @@
 use std::env;
 use std::io::Read;
+use std::time::Instant;
 
 mod deps;
 
 use crate::agentcmds;
 use crate::analytics::{
-    cmd_prompt_stats, cmd_quota, print_alert, print_metrics, print_profile, print_trace,
-    print_worklog,
+    cmd_diff_runs, cmd_digest, cmd_prompt_stats, cmd_quota, cmd_trends, cmd_worklog, print_alert,
+    print_metrics, print_profile, print_trace, print_trace_by_id, print_trace_remediations,
 };
+use crate::bench_llm::cmd_bench_llm;
 use crate::bench_parity;
 use crate::broker::cmd_broker as broker_cmd;
 use crate::capture::{chunk_text_by_budget, run_system_command_capture};
+use crate::changelog::cmd_changelog;
+use crate::cli::apply_global_flags;
 use crate::cmdctx::CmdCtx;
 use crate::command_names::{is_compat_name, is_native_name};
 use crate::compat_cmd;
@@ -20,32 +24,50 @@ use crate::config::{
 };
 use crate::diagnostics::{cmd_diag, cmd_scheduler};
 use crate::doctor;
+use crate::error::{EXIT_OK, EXIT_USAGE, format_error, print_usage_error};
 use crate::execmeta::utc_now_iso;
-use crate::help::{render_help, render_task_help};
+use crate::exit_codes::cmd_exit_codes;
+use crate::gc::cmd_gc;
+use crate::help::{
+    render_command_help, render_help, render_reference, render_task_help, resolve_help_topic,
+};
+use crate::init::cmd_init;
 use crate::introspect::{
     cmd_core as introspect_cmd_core, print_version as introspect_print_version,
 };
+use crate::llm_cmds::cmd_llm;
 use crate::logs::cmd_logs;
 use crate::logview::{cmd_budget, cmd_log_tail};
+use crate::model_bench::cmd_model;
 use crate::native_cmd;
+use crate::notes::cmd_annotate;
 use crate::optimize::{parse_optimize_args, print_optimize};
+use crate::pack::cmd_pack;
 use crate::policy::cmd_policy;
-use crate::prompting::{cmd_fanout, cmd_prompt, cmd_promptlint, cmd_roles};
+use crate::prompting::{cmd_prompt, cmd_promptlint, cmd_roles};
 use crate::quarantine::{cmd_quarantine_list, cmd_quarantine_show};
+use crate::quarantine_diff::cmd_quarantine_diff;
+use crate::quarantine_ops::{
+    cmd_quarantine_purge, cmd_quarantine_resolve, cmd_quarantine_rm, cmd_quarantine_stats,
+};
+use crate::redact_test::cmd_redact_test;
 use crate::routing::{cmd_routes, print_where};
 use crate::runtime_controls::{
     cmd_alert_off, cmd_alert_on, cmd_alert_show, cmd_capture_status, cmd_log_off, cmd_log_on,
 };
 use crate::schema_ops::{cmd_ci, cmd_schema};
-use crate::settings_cmds::{cmd_llm, cmd_state_get, cmd_state_set, cmd_state_show};
+use crate::secrets::cmd_secret;
+use crate::selftest::cmd_selftest;
+use crate::settings_cmds::{cmd_config, cmd_state_get, cmd_state_set, cmd_state_show};
 use crate::state::{current_task_id, current_task_parent_id, set_state_path};
 use crate::structured_cmds;
 use crate::task_cmds;
 use crate::taskrun::{TaskRunner, run_task_by_id};
-use crate::tasks::{
-    cmd_task_add, cmd_task_fanout, cmd_task_list, cmd_task_show, read_tasks, write_tasks,
-};
+use crate::tasks::{cmd_task_add, cmd_task_list, cmd_task_show, read_tasks, update_task};
+use crate::transcripts::cmd_transcript;
 use crate::types::{ExecutionResult, TaskSpec};
+use crate::verbosity::{Verbosity, diag};
+use crate::workspace::cmd_workspace;
 
 fn print_help() {
     print!(
@@ -63,6 +85,64 @@ fn print_task_help() {
     print!("{}", render_task_help());
 }
 
+const HELP_USAGE: &str = "help [<command> | task [<op>] | --all [--format text|md]]";
+
+fn cmd_help(args: &[String]) -> i32 {
+    if args.iter().any(|a| a == "--all") {
+        let mut markdown = false;
+        let mut i = 0usize;
+        while i < args.len() {
+            match (args[i].as_str(), args.get(i + 1).map(String::as_str)) {
+                ("--all", _) => {}
+                ("--format", Some("md" | "markdown")) => {
+                    markdown = true;
+                    i += 1;
+                }
+                ("--format", Some("text")) => {
+                    markdown = false;
+                    i += 1;
+                }
+                _ => return print_usage_error("help", HELP_USAGE),
+            }
+            i += 1;
+        }
+        print!(
+            "{}",
+            render_reference(
+                APP_NAME,
+                APP_DESC,
+                markdown,
+                DEFAULT_RUN_WINDOW,
+                DEFAULT_QUARANTINE_LIST
+            )
+        );
+        return EXIT_OK;
+    }
+    match args {
+        [] => print_help(),
+        [topic] if topic == "task" => print_task_help(),
+        _ => {
+            let name = args.join(" ");
+            let Some(text) = resolve_help_topic(&name).and_then(|topic| {
+                render_command_help(
+                    APP_NAME,
+                    &topic,
+                    DEFAULT_RUN_WINDOW,
+                    DEFAULT_QUARANTINE_LIST,
+                )
+            }) else {
+                crate::cx_eprintln!(
+                    "{}",
+                    format_error("help", &format!("unknown command '{name}'"))
+                );
+                return EXIT_USAGE;
+            };
+            print!("{text}");
+        }
+    }
+    EXIT_OK
+}
+
 // path resolution moved to `paths.rs`
 // schema helpers moved to `schema.rs`
 // ensure_parent_dir moved to `paths.rs`
@@ -72,7 +152,7 @@ fn print_task_help() {
 fn task_runner() -> TaskRunner {
     TaskRunner {
         read_tasks,
-        write_tasks,
+        update_task,
         current_task_id,
         current_task_parent_id,
         set_state_path,
@@ -121,8 +201,8 @@ fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
     crate::execution::execute_task(spec)
 }
 
-fn cmd_bench(runs: usize, command: &[String]) -> i32 {
-    bench_parity::cmd_bench(APP_NAME, runs, command)
+fn cmd_bench(runs: usize, command: &[String], json_out: bool) -> i32 {
+    bench_parity::cmd_bench(APP_NAME, runs, command, json_out)
 }
 
 fn cmd_cx(command: &[String]) -> i32 {
@@ -149,6 +229,39 @@ fn cmd_fix(command: &[String]) -> i32 {
     agentcmds::cmd_fix(command, run_system_command_capture, execute_task)
 }
 
+fn cmd_followup(args: &[String]) -> i32 {
+    crate::session::cmd_followup(APP_NAME, args, execute_task)
+}
+
+fn cmd_fanout(args: &[String]) -> i32 {
+    crate::prompting::cmd_fanout(args, execute_task)
+}
+
+fn cmd_task_fanout(app_name: &str, objective: &str, from: Option<&str>, llm: bool) -> i32 {
+    let llm = llm.then_some(execute_task as structured_cmds::ExecuteTaskFn);
+    crate::tasks::cmd_task_fanout(app_name, objective, from, llm)
+}
+
+fn cmd_explain(args: &[String]) -> i32 {
+    crate::explain::cmd_explain(args, execute_task)
+}
+
+fn cmd_pipeline(args: &[String]) -> i32 {
+    crate::pipeline::cmd_pipeline(args, execute_task)
+}
+
+fn cmd_custom(args: &[String]) -> i32 {
+    crate::custom_cmds::cmd_custom(args, execute_task)
+}
+
+fn cmd_session(args: &[String]) -> i32 {
+    crate::session::cmd_session(APP_NAME, args)
+}
+
+fn cmd_watch(args: &[String]) -> i32 {
+    crate::watch::cmd_watch(APP_NAME, args)
+}
+
 fn cmd_parity() -> i32 {
     bench_parity::cmd_parity()
 }
@@ -180,20 +293,28 @@ fn cmd_fix_run(command: &[String]) -> i32 {
     structured_cmds::cmd_fix_run(APP_NAME, command, execute_task)
 }
 
-fn cmd_diffsum(staged: bool) -> i32 {
-    structured_cmds::cmd_diffsum(staged, execute_task)
+fn cmd_diffsum(staged: bool, args: &[String]) -> i32 {
+    structured_cmds::cmd_diffsum(staged, args, execute_task)
+}
+
+fn cmd_commitjson(args: &[String]) -> i32 {
+    structured_cmds::cmd_commitjson(args, execute_task)
+}
+
+fn cmd_commitmsg(args: &[String]) -> i32 {
+    structured_cmds::cmd_commitmsg(args, execute_task)
 }
 
-fn cmd_commitjson() -> i32 {
-    structured_cmds::cmd_commitjson(execute_task)
+fn cmd_stage(args: &[String]) -> i32 {
+    crate::stage::cmd_stage(args, execute_task)
 }
 
-fn cmd_commitmsg() -> i32 {
-    structured_cmds::cmd_commitmsg(execute_task)
+fn cmd_replay(args: &[String]) -> i32 {
+    structured_cmds::cmd_replay(args, crate::execution::run_llm_jsonl)
 }
 
-fn cmd_replay(id: &str) -> i32 {
-    structured_cmds::cmd_replay(id, crate::execution::run_llm_jsonl)
+fn cmd_rerun(args: &[String]) -> i32 {
+    crate::rerun::cmd_rerun(args, execute_task)
 }
 
 fn compat_print_version() {
@@ -232,12 +353,12 @@ fn compat_cmd_llm(args: &[String]) -> i32 {
     cmd_llm(APP_NAME, args)
 }
 
-fn compat_cmd_doctor() -> i32 {
-    doctor::print_doctor(crate::execution::run_llm_jsonl)
+fn compat_cmd_doctor(args: &[String]) -> i32 {
+    doctor::cmd_doctor(args, crate::execution::run_llm_jsonl)
 }
 
-fn compat_cmd_health() -> i32 {
-    doctor::cmd_health(crate::execution::run_llm_jsonl, cmd_cxo)
+fn compat_cmd_health(args: &[String]) -> i32 {
+    crate::health::cmd_health(args, crate::execution::run_llm_jsonl, execute_task)
 }
 
 fn cmd_cx_compat(args: &[String]) -> i32 {
@@ -260,6 +381,10 @@ fn native_cmd_ci(args: &[String]) -> i32 {
     cmd_ci(APP_NAME, args)
 }
 
+fn native_cmd_hook(args: &[String]) -> i32 {
+    crate::hooks::cmd_hook(APP_NAME, args)
+}
+
 fn native_cmd_where(args: &[String]) -> i32 {
     print_where(args, APP_VERSION)
 }
@@ -280,26 +405,53 @@ fn native_cmd_llm(args: &[String]) -> i32 {
     cmd_llm(APP_NAME, args)
 }
 
+fn native_cmd_config(args: &[String]) -> i32 {
+    cmd_config(APP_NAME, args)
+}
+
 fn native_cmd_policy(args: &[String]) -> i32 {
     cmd_policy(args, APP_NAME)
 }
 
+fn native_cmd_transcript(args: &[String]) -> i32 {
+    cmd_transcript(APP_NAME, args)
+}
+
 fn native_cmd_broker(args: &[String]) -> i32 {
     broker_cmd(APP_NAME, args)
 }
 
-fn native_cmd_doctor() -> i32 {
-    doctor::print_doctor(crate::execution::run_llm_jsonl)
+fn native_cmd_doctor(args: &[String]) -> i32 {
+    doctor::cmd_doctor(args, crate::execution::run_llm_jsonl)
 }
 
-fn native_cmd_health() -> i32 {
-    doctor::cmd_health(crate::execution::run_llm_jsonl, cmd_cxo)
+fn native_cmd_health(args: &[String]) -> i32 {
+    crate::health::cmd_health(args, crate::execution::run_llm_jsonl, execute_task)
 }
 
 pub fn run() -> i32 {
+    let argv: Vec<String> = env::args().collect();
+    let args = match apply_global_flags(&argv) {
+        Ok(args) => args,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("run", &e));
+            return EXIT_USAGE;
+        }
+    };
     init_app_config();
-    let args: Vec<String> = env::args().collect();
-    native_cmd::handler(&cmd_ctx(), &args, &deps::native_deps())
+    let started = Instant::now();
+    let code = native_cmd::handler(&cmd_ctx(), &args, &deps::native_deps());
+    crate::progress::clear_line();
+    diag(
+        Verbosity::Verbose,
+        "timing",
+        &[
+            ("cmd", args.get(1).cloned().unwrap_or_default()),
+            ("exit", code.to_string()),
+            ("duration_ms", started.elapsed().as_millis().to_string()),
+        ],
+    );
+    code
 }
 
 #[cfg(test)]
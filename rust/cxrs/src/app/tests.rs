@@ -26,8 +26,11 @@ fn clip_text_respects_line_and_char_budget() {
     let cfg = BudgetConfig {
         budget_chars: 12,
         budget_lines: 2,
+        budget_tokens: None,
+        tokenizer: crate::token_estimate::TokenizerKind::Codex,
         clip_mode: "head".to_string(),
         clip_footer: false,
+        error_regex: crate::config::DEFAULT_ERROR_REGEX.to_string(),
     };
     let (out, stats) = clip_text_with_config("line1\nline2\nline3\n", &cfg);
     assert!(out.starts_with("line1\nline2"));
@@ -36,6 +39,55 @@ fn clip_text_respects_line_and_char_budget() {
     assert_eq!(stats.clipped, Some(true));
 }
 
+#[test]
+fn clip_text_respects_token_budget() {
+    let cfg = BudgetConfig {
+        budget_chars: 1_000,
+        budget_lines: 100,
+        budget_tokens: Some(4),
+        tokenizer: crate::token_estimate::TokenizerKind::Codex,
+        clip_mode: "head".to_string(),
+        clip_footer: false,
+        error_regex: crate::config::DEFAULT_ERROR_REGEX.to_string(),
+    };
+    let (out, stats) = clip_text_with_config("alpha beta gamma delta epsilon zeta", &cfg);
+    assert_eq!(out, "alpha beta gamma delta");
+    assert_eq!(stats.system_output_tokens_raw, Some(7));
+    assert_eq!(stats.system_output_tokens_clipped, Some(4));
+    assert_eq!(stats.budget_tokens, Some(4));
+    assert_eq!(stats.clipped, Some(true));
+}
+
+#[test]
+fn error_window_centres_on_first_error_line() {
+    let cfg = BudgetConfig {
+        budget_chars: 1_000,
+        budget_lines: 8,
+        budget_tokens: None,
+        tokenizer: crate::token_estimate::TokenizerKind::Codex,
+        clip_mode: "error-window".to_string(),
+        clip_footer: false,
+        error_regex: crate::config::DEFAULT_ERROR_REGEX.to_string(),
+    };
+    let input: String = (1..=30)
+        .map(|i| match i {
+            12 => "error[E0425]: cannot find value `x`\n".to_string(),
+            25 => "test result: FAILED\n".to_string(),
+            _ => format!("l{i}\n"),
+        })
+        .collect();
+    let (out, stats) = clip_text_with_config(&input, &cfg);
+    assert_eq!(
+        out,
+        "l10\nl11\nerror[E0425]: cannot find value `x`\nl13\nl14\nl15\nl16\nl17"
+    );
+    assert_eq!(stats.clip_mode.as_deref(), Some("error-window"));
+
+    let (out, stats) = clip_text_with_config("a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n", &cfg);
+    assert!(out.ends_with("i\nj"), "{out}");
+    assert_eq!(stats.clip_mode.as_deref(), Some("tail"));
+}
+
 #[test]
 fn jsonl_append_integrity() {
     let dir = tempdir().expect("tempdir");
@@ -1,12 +1,28 @@
 #[path = "modules/agentcmds.rs"]
 mod agentcmds;
+#[path = "modules/alert_sinks.rs"]
+mod alert_sinks;
 #[path = "modules/analytics.rs"]
 mod analytics;
+#[path = "modules/analytics_diff.rs"]
+mod analytics_diff;
+#[path = "modules/analytics_digest.rs"]
+mod analytics_digest;
 #[path = "modules/analytics_trace.rs"]
 mod analytics_trace;
+#[path = "modules/analytics_trends.rs"]
+mod analytics_trends;
 #[path = "modules/analytics_worklog.rs"]
 mod analytics_worklog;
+#[path = "modules/analytics_worklog_html.rs"]
+mod analytics_worklog_html;
 mod app;
+#[path = "modules/bench_history.rs"]
+mod bench_history;
+#[path = "modules/bench_llm.rs"]
+mod bench_llm;
+#[path = "modules/bench_llm_args.rs"]
+mod bench_llm_args;
 #[path = "modules/bench_parity.rs"]
 mod bench_parity;
 #[path = "modules/bench_parity_mocks.rs"]
@@ -15,22 +31,44 @@ mod bench_parity_mocks;
 mod bench_parity_support;
 #[path = "modules/broker.rs"]
 mod broker;
+#[path = "modules/budget_suggest.rs"]
+mod budget_suggest;
 #[path = "modules/capture.rs"]
 mod capture;
+#[path = "modules/changelog.rs"]
+mod changelog;
+#[path = "modules/cli.rs"]
+mod cli;
+#[path = "modules/clipboard.rs"]
+mod clipboard;
 #[path = "modules/cmdctx.rs"]
 mod cmdctx;
+#[path = "modules/codex_args.rs"]
+mod codex_args;
 #[path = "modules/command_names.rs"]
 mod command_names;
+#[path = "modules/commit_context.rs"]
+mod commit_context;
+#[path = "modules/commit_lint.rs"]
+mod commit_lint;
 #[path = "modules/compat_cmd.rs"]
 mod compat_cmd;
 #[path = "modules/config.rs"]
 mod config;
+#[path = "modules/config_layers.rs"]
+mod config_layers;
+#[path = "modules/config_settings.rs"]
+mod config_settings;
 #[path = "modules/contract_versions.rs"]
 mod contract_versions;
+#[path = "modules/custom_cmds.rs"]
+mod custom_cmds;
 #[path = "modules/diagnostics.rs"]
 mod diagnostics;
 #[path = "modules/doctor.rs"]
 mod doctor;
+#[path = "modules/doctor_fix.rs"]
+mod doctor_fix;
 #[path = "modules/error.rs"]
 mod error;
 #[path = "modules/execmeta.rs"]
@@ -39,22 +77,58 @@ mod execmeta;
 mod execution;
 #[path = "modules/execution_logging.rs"]
 mod execution_logging;
+#[path = "modules/exit_codes.rs"]
+mod exit_codes;
+#[path = "modules/explain.rs"]
+mod explain;
+#[path = "modules/fanout_llm.rs"]
+mod fanout_llm;
+#[path = "modules/gc.rs"]
+mod gc;
+#[path = "modules/health.rs"]
+mod health;
 #[path = "modules/help.rs"]
 mod help;
+#[path = "modules/hooks.rs"]
+mod hooks;
+#[path = "modules/init.rs"]
+mod init;
 #[path = "modules/introspect.rs"]
 mod introspect;
 #[path = "modules/llm.rs"]
 mod llm;
+#[path = "modules/llm_cmds.rs"]
+mod llm_cmds;
 #[path = "modules/log_contract.rs"]
 mod log_contract;
 #[path = "modules/logs.rs"]
 mod logs;
+#[path = "modules/logs_failures.rs"]
+mod logs_failures;
+#[path = "modules/logs_query.rs"]
+mod logs_query;
 #[path = "modules/logs_stats.rs"]
 mod logs_stats;
 #[path = "modules/logview.rs"]
 mod logview;
+#[path = "modules/markdown_render.rs"]
+mod markdown_render;
+#[path = "modules/mock_responses.rs"]
+mod mock_responses;
+#[path = "modules/model_bench.rs"]
+mod model_bench;
+#[path = "modules/model_context.rs"]
+mod model_context;
 #[path = "modules/native_cmd.rs"]
 mod native_cmd;
+#[path = "modules/nesting.rs"]
+mod nesting;
+#[path = "modules/notes.rs"]
+mod notes;
+#[path = "modules/offline.rs"]
+mod offline;
+#[path = "modules/ollama_api.rs"]
+mod ollama_api;
 #[path = "modules/optimize.rs"]
 mod optimize;
 #[path = "modules/optimize_print.rs"]
@@ -63,54 +137,124 @@ mod optimize_print;
 mod optimize_report;
 #[path = "modules/optimize_rules.rs"]
 mod optimize_rules;
+#[path = "modules/optimize_scoring.rs"]
+mod optimize_scoring;
+#[path = "modules/pack.rs"]
+mod pack;
 #[path = "modules/paths.rs"]
 mod paths;
+#[path = "modules/percentiles.rs"]
+mod percentiles;
+#[path = "modules/pipeline.rs"]
+mod pipeline;
+#[path = "modules/platform.rs"]
+mod platform;
 #[path = "modules/policy.rs"]
 mod policy;
 #[path = "modules/process.rs"]
 mod process;
+#[path = "modules/progress.rs"]
+mod progress;
 #[path = "modules/prompt_filter.rs"]
 mod prompt_filter;
+#[path = "modules/prompt_templates.rs"]
+mod prompt_templates;
 #[path = "modules/prompting.rs"]
 mod prompting;
 #[path = "modules/provider_adapter.rs"]
 mod provider_adapter;
 #[path = "modules/quarantine.rs"]
 mod quarantine;
+#[path = "modules/quarantine_audit.rs"]
+mod quarantine_audit;
+#[path = "modules/quarantine_diff.rs"]
+mod quarantine_diff;
+#[path = "modules/quarantine_ops.rs"]
+mod quarantine_ops;
+#[path = "modules/redact_test.rs"]
+mod redact_test;
+#[path = "modules/repo_registry.rs"]
+mod repo_registry;
+#[path = "modules/rerun.rs"]
+mod rerun;
+#[path = "modules/roles.rs"]
+mod roles;
 #[path = "modules/routing.rs"]
 mod routing;
+#[path = "modules/run_filter.rs"]
+mod run_filter;
+#[path = "modules/run_index.rs"]
+mod run_index;
 #[path = "modules/runlog.rs"]
 mod runlog;
 #[path = "modules/runtime.rs"]
 mod runtime;
 #[path = "modules/runtime_controls.rs"]
 mod runtime_controls;
+#[path = "modules/sandbox.rs"]
+mod sandbox;
 #[path = "modules/schema.rs"]
 mod schema;
 #[path = "modules/schema_ops.rs"]
 mod schema_ops;
+#[path = "modules/schema_registry.rs"]
+mod schema_registry;
+#[path = "modules/secrets.rs"]
+mod secrets;
+#[path = "modules/selftest.rs"]
+mod selftest;
+#[path = "modules/session.rs"]
+mod session;
 #[path = "modules/settings_cmds.rs"]
 mod settings_cmds;
+#[path = "modules/signals.rs"]
+mod signals;
+#[path = "modules/stage.rs"]
+mod stage;
 #[path = "modules/state.rs"]
 mod state;
+#[path = "modules/stream.rs"]
+mod stream;
 #[path = "modules/structured_cmds.rs"]
 mod structured_cmds;
 #[path = "modules/structured_fixrun.rs"]
 mod structured_fixrun;
+#[path = "modules/structured_fixrun_exec.rs"]
+mod structured_fixrun_exec;
+#[path = "modules/structured_next_run.rs"]
+mod structured_next_run;
 #[path = "modules/structured_replay.rs"]
 mod structured_replay;
 #[path = "modules/task_cmds.rs"]
 mod task_cmds;
+#[path = "modules/task_results.rs"]
+mod task_results;
+#[path = "modules/task_watch.rs"]
+mod task_watch;
 #[path = "modules/taskrun.rs"]
 mod taskrun;
 #[path = "modules/tasks.rs"]
 mod tasks;
 #[path = "modules/tasks_plan.rs"]
 mod tasks_plan;
+#[path = "modules/token_estimate.rs"]
+mod token_estimate;
+#[path = "modules/trace_links.rs"]
+mod trace_links;
+#[path = "modules/transcripts.rs"]
+mod transcripts;
 #[path = "modules/types.rs"]
 mod types;
 #[path = "modules/util.rs"]
 mod util;
+#[path = "modules/vcs.rs"]
+mod vcs;
+#[path = "modules/verbosity.rs"]
+mod verbosity;
+#[path = "modules/watch.rs"]
+mod watch;
+#[path = "modules/workspace.rs"]
+mod workspace;
 
 fn main() {
     std::process::exit(app::run());
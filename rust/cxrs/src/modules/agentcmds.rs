@@ -1,11 +1,21 @@
-use std::process::Command;
+use crate::config::app_config;
+use crate::error::{EXIT_OK, format_error, print_runtime_error, print_usage_error};
+use crate::llm::extract_agent_text;
+use crate::markdown_render::{render_markdown, should_render};
+use crate::prompt_templates::render_prompt;
+use crate::runlog::{RunLogInput, log_codex_run};
+use crate::schema::load_schema_for_tool;
+use crate::structured_cmds::{parse_schema_json, schema_text};
+use crate::types::{
+    CaptureStats, ExecutionResult, LlmOutputKind, LoadedSchema, TaskInput, TaskSpec,
+};
+use crate::verbosity::warn;
 
-use crate::error::{EXIT_OK, format_error, print_runtime_error};
-use crate::process::run_command_with_stdin_output_with_timeout;
-use crate::types::{CaptureStats, ExecutionResult, LlmOutputKind, TaskInput, TaskSpec};
+pub const CXCOPY_USAGE: &str = "cxcopy [--raw|--jsonl|--both] <command> [args...]";
+pub const FIX_USAGE: &str = "fix [--json] <command> [args...]";
 
 type TaskRunner = fn(TaskSpec) -> Result<ExecutionResult, String>;
-type CaptureRunner = fn(&[String]) -> Result<(String, i32, CaptureStats), String>;
+type CaptureRunner = fn(&str, &[String]) -> Result<(String, i32, CaptureStats), String>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)]
@@ -16,32 +26,6 @@ pub enum LlmMode {
     SchemaJson,
 }
 
-struct ClipboardBackend {
-    bin: &'static str,
-    args: &'static [&'static str],
-    label: &'static str,
-}
-
-fn clipboard_backends() -> &'static [ClipboardBackend] {
-    &[
-        ClipboardBackend {
-            bin: "pbcopy",
-            args: &[],
-            label: "pbcopy",
-        },
-        ClipboardBackend {
-            bin: "wl-copy",
-            args: &[],
-            label: "wl-copy",
-        },
-        ClipboardBackend {
-            bin: "xclip",
-            args: &["-selection", "clipboard"],
-            label: "xclip",
-        },
-    ]
-}
-
 fn mode_to_task_spec(command: &[String], mode: LlmMode) -> Result<TaskSpec, String> {
     let (command_name, output_kind) = match mode {
         LlmMode::Plain => ("cx", LlmOutputKind::Plain),
@@ -62,6 +46,8 @@ fn mode_to_task_spec(command: &[String], mode: LlmMode) -> Result<TaskSpec, Stri
         schema_task_input: None,
         logging_enabled: true,
         capture_override: None,
+        prompt_template: None,
+        stream: app_config().stream,
     })
 }
 
@@ -80,22 +66,36 @@ fn run_and_print(
     run_task: TaskRunner,
     with_newline: bool,
 ) -> i32 {
+    let name = match mode {
+        LlmMode::Plain => "cx",
+        LlmMode::Jsonl => "cxj",
+        LlmMode::AgentText => "cxo",
+        LlmMode::SchemaJson => "cx-schema",
+    };
     let result = match execute_llm_command(command, mode, run_task) {
         Ok(v) => v,
         Err(e) => {
-            let name = match mode {
-                LlmMode::Plain => "cx",
-                LlmMode::Jsonl => "cxj",
-                LlmMode::AgentText => "cxo",
-                LlmMode::SchemaJson => "cx-schema",
-            };
             return print_runtime_error(name, &e);
         }
     };
-    if with_newline {
-        println!("{}", result.stdout);
-    } else {
-        print!("{}", result.stdout);
+    if matches!(mode, LlmMode::Plain | LlmMode::AgentText)
+        && let Err(e) = crate::session::record_session_turn(name, Some(command), &result)
+    {
+        warn(&format!(
+            "cxrs {name}: warning: failed to record session turn: {e}"
+        ));
+    }
+    // Streamed runs already printed the output as it arrived.
+    if !app_config().stream {
+        let rendered =
+            (mode != LlmMode::Jsonl && should_render()).then(|| render_markdown(&result.stdout));
+        if let Some(text) = rendered {
+            println!("{}", text.trim_end_matches('\n'));
+        } else if with_newline {
+            println!("{}", result.stdout);
+        } else {
+            print!("{}", result.stdout);
+        }
     }
     result.system_status.unwrap_or(0)
 }
@@ -116,74 +116,240 @@ pub fn cmd_cxol(command: &[String], run_task: TaskRunner) -> i32 {
     run_and_print(command, LlmMode::Plain, run_task, false)
 }
 
-pub fn cmd_cxcopy(command: &[String], run_task: TaskRunner) -> i32 {
+/// What `cxcopy` puts on the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CopyWhat {
+    /// The final agent text (default).
+    Agent,
+    /// The clipped command output sent to the model (`--raw`).
+    Raw,
+    /// The full backend event stream (`--jsonl`).
+    Jsonl,
+    /// Markdown with the command, captured output, response and events (`--both`).
+    Both,
+}
+
+impl CopyWhat {
+    fn label(self) -> &'static str {
+        match self {
+            CopyWhat::Agent => "response",
+            CopyWhat::Raw => "captured output",
+            CopyWhat::Jsonl => "JSONL transcript",
+            CopyWhat::Both => "Markdown context",
+        }
+    }
+}
+
+/// Leading `--raw`/`--jsonl`/`--both` flags, then the wrapped command.
+fn parse_cxcopy_args(args: &[String]) -> (CopyWhat, &[String]) {
+    let (mut raw, mut jsonl) = (false, false);
+    let mut i = 0;
+    while let Some(flag) = args.get(i) {
+        match flag.as_str() {
+            "--raw" => raw = true,
+            "--jsonl" => jsonl = true,
+            "--both" => (raw, jsonl) = (true, true),
+            _ => break,
+        }
+        i += 1;
+    }
+    let what = match (raw, jsonl) {
+        (true, true) => CopyWhat::Both,
+        (true, false) => CopyWhat::Raw,
+        (false, true) => CopyWhat::Jsonl,
+        (false, false) => CopyWhat::Agent,
+    };
+    (what, &args[i..])
+}
+
+/// A fenced block whose fence is longer than any backtick run in `body`.
+fn fenced(lang: &str, body: &str) -> String {
+    let longest = body.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    let body = body.trim_end_matches('\n');
+    format!("{fence}{lang}\n{body}\n{fence}\n")
+}
+
+fn copy_markdown(command: &[String], captured: &str, response: &str, jsonl: &str) -> String {
+    format!(
+        "## Command\n\n{}\n## Captured output\n\n{}\n## Response\n\n{}\n\n## JSONL transcript\n\n{}",
+        fenced("sh", &command.join(" ")),
+        fenced("text", captured),
+        response.trim_end(),
+        fenced("jsonl", jsonl)
+    )
+}
+
+/// The cxcopy row is logged after the copy so it names the provider that was
+/// actually used, after any fallback.
+fn log_cxcopy_run(result: &ExecutionResult, provider: Option<&str>) {
+    let _ = log_codex_run(RunLogInput {
+        tool: "cxcopy",
+        prompt: &result.prompt,
+        prompt_raw: None,
+        prompt_filtered: None,
+        response: Some(&result.stdout),
+        schema_prompt: None,
+        schema_raw: None,
+        schema_attempt: None,
+        timed_out: None,
+        timeout_secs: None,
+        command_label: None,
+        duration_ms: result.duration_ms,
+        usage: Some(&result.usage),
+        capture: Some(&result.capture_stats),
+        schema_ok: true,
+        schema_reason: None,
+        schema_name: None,
+        quarantine_id: None,
+        schema_repair: None,
+        policy_blocked: None,
+        policy_reason: None,
+        fix_decisions: None,
+        prompt_template: None,
+        clipboard_provider: provider,
+    });
+}
+
+pub fn cmd_cxcopy(args: &[String], run_task: TaskRunner) -> i32 {
+    let (what, command) = parse_cxcopy_args(args);
+    if command.is_empty() {
+        return print_usage_error("cxcopy", CXCOPY_USAGE);
+    }
+    // The event stream is only kept when the backend output is requested as JSONL.
+    let output_kind = if matches!(what, CopyWhat::Jsonl | CopyWhat::Both) {
+        LlmOutputKind::Jsonl
+    } else {
+        LlmOutputKind::AgentText
+    };
     let result = match run_task(TaskSpec {
         command_name: "cxcopy".to_string(),
         input: TaskInput::SystemCommand(command.to_vec()),
-        output_kind: LlmOutputKind::AgentText,
+        output_kind,
         schema: None,
         schema_task_input: None,
-        logging_enabled: true,
+        logging_enabled: false,
         capture_override: None,
+        prompt_template: None,
+        stream: false,
     }) {
         Ok(v) => v,
         Err(e) => {
             return print_runtime_error("cxcopy", &e);
         }
     };
-    let text = result.stdout;
-    if text.trim().is_empty() {
-        return print_runtime_error("cxcopy", "nothing to copy");
-    }
-    let mut failures: Vec<String> = Vec::new();
-    for backend in clipboard_backends() {
-        let mut cmd = Command::new(backend.bin);
-        if !backend.args.is_empty() {
-            cmd.args(backend.args);
+    let text = match what {
+        CopyWhat::Agent | CopyWhat::Jsonl => result.stdout.clone(),
+        CopyWhat::Raw => result.prompt.clone(),
+        CopyWhat::Both => {
+            let response = extract_agent_text(&result.stdout).unwrap_or_default();
+            copy_markdown(command, &result.prompt, &response, &result.stdout)
         }
-        match run_command_with_stdin_output_with_timeout(cmd, &text, backend.label) {
-            Ok(out) if out.status.success() => {
-                println!("Copied to clipboard ({})", backend.bin);
-                return result.system_status.unwrap_or(0);
+    };
+    let copied = if text.trim().is_empty() {
+        Err("nothing to copy".to_string())
+    } else {
+        crate::clipboard::copy_text(&text)
+    };
+    log_cxcopy_run(&result, copied.as_ref().ok().copied());
+    match copied {
+        Ok(provider) => {
+            if what == CopyWhat::Agent {
+                println!("Copied to clipboard ({provider})");
+            } else {
+                println!("Copied {} to clipboard ({provider})", what.label());
             }
-            Ok(out) => failures.push(format!("{} exited with status {}", backend.bin, out.status)),
-            Err(e) => failures.push(format!("{} unavailable/failed: {}", backend.bin, e)),
+            result.system_status.unwrap_or(0)
         }
+        Err(e) => print_runtime_error("cxcopy", &e),
     }
-    print_runtime_error(
-        "cxcopy",
-        &format!("all clipboard backends failed: {}", failures.join("; ")),
-    )
 }
 
-pub fn cmd_fix(command: &[String], run_capture: CaptureRunner, run_task: TaskRunner) -> i32 {
-    let (captured, status, capture_stats) = match run_capture(command) {
+/// Print the structured `fix` answer: pretty JSON, or a non-zero exit when the
+/// model's object fails the schema.
+fn print_fix_json(result: &ExecutionResult) -> Result<(), String> {
+    let value = parse_schema_json(result)?;
+    let s = serde_json::to_string_pretty(&value).map_err(|e| format!("render failure: {e}"))?;
+    println!("{s}");
+    Ok(())
+}
+
+/// The `fix` task for a captured command: prose, or the strict `cxrs_fix`
+/// object when `schema` is given.
+pub(crate) fn fix_task_spec(
+    command: &[String],
+    captured: &str,
+    status: i32,
+    capture_stats: CaptureStats,
+    schema: Option<LoadedSchema>,
+) -> Result<TaskSpec, String> {
+    let schema_text = schema.as_ref().map(schema_text).unwrap_or_default();
+    let (prompt, template) = render_prompt(
+        "fix",
+        &[
+            ("command", &command.join(" ")),
+            ("status", &status.to_string()),
+            ("output", captured),
+            ("schema", &schema_text),
+        ],
+    )?;
+    let (command_name, output_kind) = if schema.is_some() {
+        ("cxrs_fix", LlmOutputKind::SchemaJson)
+    } else {
+        ("cxfix", LlmOutputKind::AgentText)
+    };
+    Ok(TaskSpec {
+        command_name: command_name.to_string(),
+        input: TaskInput::Prompt(prompt.clone()),
+        output_kind,
+        schema_task_input: schema.is_some().then_some(prompt),
+        schema,
+        logging_enabled: true,
+        capture_override: Some(capture_stats),
+        prompt_template: Some(template),
+        stream: false,
+    })
+}
+
+/// `fix [--json] <command>`: run the command and have the model diagnose it.
+/// `--json` switches to the strict `fix` schema (`cxrs_fix`) instead of prose.
+pub fn cmd_fix(args: &[String], run_capture: CaptureRunner, run_task: TaskRunner) -> i32 {
+    let json_out = args.first().is_some_and(|a| a == "--json");
+    let command = if json_out { &args[1..] } else { args };
+    if command.is_empty() {
+        return print_usage_error("fix", FIX_USAGE);
+    }
+    let schema = if json_out {
+        match load_schema_for_tool("cxrs_fix") {
+            Ok(s) => Some(s),
+            Err(e) => return print_runtime_error("fix", &e),
+        }
+    } else {
+        None
+    };
+    let (captured, status, capture_stats) = match run_capture("cxfix", command) {
         Ok(v) => v,
         Err(e) => {
             return print_runtime_error("fix", &e);
         }
     };
-    let prompt = format!(
-        "You are my terminal debugging assistant.\nTask:\n1) Explain what happened (brief).\n2) If the command failed, diagnose likely cause(s).\n3) Propose the next 3 commands to run to confirm/fix.\n4) If it is a configuration issue, point to exact file/line patterns to check.\n\nCommand:\n{}\n\nExit status: {}\n\nOutput:\n{}",
-        command.join(" "),
-        status,
-        captured
-    );
-    let result = match run_task(TaskSpec {
-        command_name: "cxfix".to_string(),
-        input: TaskInput::Prompt(prompt),
-        output_kind: LlmOutputKind::AgentText,
-        schema: None,
-        schema_task_input: None,
-        logging_enabled: true,
-        capture_override: Some(capture_stats),
-    }) {
+    let spec = match fix_task_spec(command, &captured, status, capture_stats, schema) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("fix", &e),
+    };
+    let result = match run_task(spec) {
         Ok(v) => v,
         Err(e) => {
             crate::cx_eprintln!("{}", format_error("fix", &e));
             return status;
         }
     };
-    println!("{}", result.stdout);
+    if json_out {
+        if let Err(e) = print_fix_json(&result) {
+            return print_runtime_error("fix", &e);
+        }
+    } else {
+        println!("{}", result.stdout);
+    }
     if status == 0 { EXIT_OK } else { status }
 }
@@ -0,0 +1,425 @@
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::config_layers::config_env;
+use crate::execmeta::utc_now_iso;
+use crate::logs::append_jsonl;
+use crate::offline::is_offline;
+use crate::paths::resolve_alerts_file;
+use crate::process::{run_command_output_with_timeout, run_command_with_stdin_output_with_timeout};
+use crate::state::{read_state_layers, read_state_value, value_at_path};
+use crate::verbosity::warn;
+
+const DEFAULT_ALERT_MAX_MS: u64 = 12000;
+const DEFAULT_ALERT_MAX_EFF_IN: u64 = 8000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlertSink {
+    File(PathBuf),
+    Webhook(String),
+    Desktop,
+}
+
+impl AlertSink {
+    pub fn label(&self) -> String {
+        match self {
+            AlertSink::File(p) => format!("file({})", p.display()),
+            AlertSink::Webhook(url) => format!("webhook({url})"),
+            AlertSink::Desktop => "desktop".to_string(),
+        }
+    }
+}
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    config_env(name)
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .unwrap_or(default)
+}
+
+pub fn alerts_enabled() -> bool {
+    config_env("CXALERT_ENABLED").is_none_or(|v| v != "0")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlertThresholds {
+    pub max_ms: u64,
+    pub max_eff_in: u64,
+}
+
+/// Global thresholds plus per-tool overrides from state `alert_overrides`
+/// (e.g. `alert_overrides.cxdiffsum.max_ms = 30000`), keyed by run-log `tool`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertPolicy {
+    pub base: AlertThresholds,
+    pub overrides: BTreeMap<String, AlertThresholds>,
+}
+
+impl AlertPolicy {
+    pub fn for_tool(&self, tool: &str) -> AlertThresholds {
+        self.overrides.get(tool).copied().unwrap_or(self.base)
+    }
+
+    pub fn for_row(&self, row: &Value) -> AlertThresholds {
+        self.for_tool(row.get("tool").and_then(Value::as_str).unwrap_or(""))
+    }
+
+    /// `tool(max_ms=N,max_eff_in=N), ...`, or `none`.
+    pub fn overrides_label(&self) -> String {
+        if self.overrides.is_empty() {
+            return "none".to_string();
+        }
+        self.overrides
+            .iter()
+            .map(|(tool, t)| format!("{tool}(max_ms={},max_eff_in={})", t.max_ms, t.max_eff_in))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn overrides_json(&self) -> Value {
+        self.overrides
+            .iter()
+            .map(|(tool, t)| {
+                (
+                    tool.clone(),
+                    json!({"max_ms": t.max_ms, "max_eff_in": t.max_eff_in}),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+/// Global thresholds: `CXALERT_MAX_MS` and `CXALERT_MAX_EFF_IN`.
+pub fn base_alert_thresholds() -> AlertThresholds {
+    AlertThresholds {
+        max_ms: env_u64("CXALERT_MAX_MS", DEFAULT_ALERT_MAX_MS),
+        max_eff_in: env_u64("CXALERT_MAX_EFF_IN", DEFAULT_ALERT_MAX_EFF_IN),
+    }
+}
+
+/// Merge an `alert_overrides` object over `base`; each tool may set any of
+/// `max_ms` / `max_eff_in` and inherits the rest.
+pub fn parse_alert_overrides(
+    base: AlertThresholds,
+    value: &Value,
+) -> Result<BTreeMap<String, AlertThresholds>, String> {
+    let Some(tools) = value.as_object() else {
+        return match value {
+            Value::Null => Ok(BTreeMap::new()),
+            _ => Err("alert_overrides must be an object keyed by tool".to_string()),
+        };
+    };
+    let mut out = BTreeMap::new();
+    for (tool, entry) in tools {
+        let Some(fields) = entry.as_object() else {
+            return Err(format!("alert_overrides.{tool} must be an object"));
+        };
+        let mut t = base;
+        for (key, v) in fields {
+            let n = v.as_u64().ok_or_else(|| {
+                format!("alert_overrides.{tool}.{key} must be a non-negative integer")
+            })?;
+            match key.as_str() {
+                "max_ms" => t.max_ms = n,
+                "max_eff_in" => t.max_eff_in = n,
+                other => {
+                    return Err(format!(
+                        "alert_overrides.{tool}: unknown key '{other}' (expected max_ms|max_eff_in)"
+                    ));
+                }
+            }
+        }
+        out.insert(tool.clone(), t);
+    }
+    Ok(out)
+}
+
+/// State `alert_overrides` merged over the global thresholds, or an error
+/// describing why the overrides are unusable.
+pub fn load_alert_policy() -> Result<AlertPolicy, String> {
+    let base = base_alert_thresholds();
+    let overrides = match read_state_value() {
+        Some(state) => match value_at_path(&state, "alert_overrides") {
+            Some(v) => parse_alert_overrides(base, v)?,
+            None => BTreeMap::new(),
+        },
+        None => BTreeMap::new(),
+    };
+    Ok(AlertPolicy { base, overrides })
+}
+
+/// Thresholds shared by `alert`, `optimize`, `watch` and run-time dispatch.
+/// Invalid overrides are reported once and ignored.
+pub fn alert_policy() -> AlertPolicy {
+    load_alert_policy().unwrap_or_else(|e| {
+        warn(&format!("cxrs alert: warning: ignoring {e}"));
+        AlertPolicy {
+            base: base_alert_thresholds(),
+            overrides: BTreeMap::new(),
+        }
+    })
+}
+
+fn parse_sink(entry: &Value) -> Result<AlertSink, String> {
+    let kind = entry
+        .as_str()
+        .or_else(|| entry.get("type").and_then(Value::as_str))
+        .ok_or("alerts.sinks entries must be a string or an object with 'type'")?;
+    match kind {
+        "file" => match entry.get("path").and_then(Value::as_str) {
+            Some(p) => Ok(AlertSink::File(PathBuf::from(p))),
+            None => resolve_alerts_file()
+                .map(AlertSink::File)
+                .ok_or_else(|| "unable to resolve alerts file".to_string()),
+        },
+        "webhook" => entry
+            .get("url")
+            .and_then(Value::as_str)
+            .filter(|u| !u.trim().is_empty())
+            .map(|u| AlertSink::Webhook(u.to_string()))
+            .ok_or_else(|| "webhook sink requires 'url'".to_string()),
+        "desktop" => Ok(AlertSink::Desktop),
+        other => Err(format!(
+            "unknown alert sink type '{other}' (expected file|webhook|desktop)"
+        )),
+    }
+}
+
+pub fn parse_alert_sinks(value: &Value) -> Result<Vec<AlertSink>, String> {
+    match value {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(items) => items.iter().map(parse_sink).collect(),
+        other => parse_sink(other).map(|s| vec![s]),
+    }
+}
+
+/// Sinks configured under state key `alerts.sinks` in the global state only:
+/// a cloned repo's `.codex/state.json` must not choose where alerts are sent
+/// or which file they are appended to.
+pub fn configured_alert_sinks() -> Result<Vec<AlertSink>, String> {
+    let mut sinks = Ok(Vec::new());
+    for (label, state) in read_state_layers() {
+        let Some(v) = value_at_path(&state, "alerts.sinks") else {
+            continue;
+        };
+        if label == "global" {
+            sinks = parse_alert_sinks(v);
+        } else {
+            warn(
+                "cxrs alert: warning: ignoring alerts.sinks in repo .codex/state.json; set it with `state set alerts.sinks '<json>' --global`",
+            );
+        }
+    }
+    sinks
+}
+
+fn alert_event(row: &Value, rule: &str, value: u64, threshold: u64) -> Value {
+    let tool = row.get("tool").and_then(Value::as_str).unwrap_or("unknown");
+    let unit = if rule == "max_ms" { "ms" } else { " tokens" };
+    json!({
+        "ts": utc_now_iso(),
+        "rule": rule,
+        "tool": tool,
+        "execution_id": row.get("execution_id").cloned().unwrap_or(Value::Null),
+        "value": value,
+        "threshold": threshold,
+        "message": format!("cx alert: {tool} {rule} exceeded ({value}{unit} > {threshold}{unit})"),
+    })
+}
+
+/// Threshold violations for a single run-log row.
+pub fn alert_events_for_row(row: &Value, max_ms: u64, max_eff: u64) -> Vec<Value> {
+    let mut out = Vec::new();
+    if let Some(d) = row.get("duration_ms").and_then(Value::as_u64)
+        && d > max_ms
+    {
+        out.push(alert_event(row, "max_ms", d, max_ms));
+    }
+    if let Some(e) = row.get("effective_input_tokens").and_then(Value::as_u64)
+        && e > max_eff
+    {
+        out.push(alert_event(row, "max_eff_in", e, max_eff));
+    }
+    out
+}
+
+fn send_webhook(url: &str, event: &Value) -> Result<(), String> {
+    let message = event.get("message").and_then(Value::as_str).unwrap_or("");
+    let payload = json!({"text": message, "cx_alert": event});
+    post_json(url, &payload, "alert webhook curl")
+}
+
+/// Only http(s) webhooks: curl would also follow `file:`, `ftp:` and friends.
+fn check_webhook_url(url: &str) -> Result<(), String> {
+    let scheme = url.split_once("://").map(|(s, _)| s.to_ascii_lowercase());
+    match scheme.as_deref() {
+        Some("http" | "https") => Ok(()),
+        _ => Err(format!("webhook url must be http:// or https://: {url}")),
+    }
+}
+
+/// POST `payload` to `url` with curl (also used for `digest` webhooks).
+pub fn post_json(url: &str, payload: &Value, label: &str) -> Result<(), String> {
+    check_webhook_url(url)?;
+    let payload = payload.to_string();
+    let mut cmd = Command::new("curl");
+    cmd.args([
+        "-sS",
+        "-f",
+        "-X",
+        "POST",
+        "-H",
+        "Content-Type: application/json",
+        "--data-binary",
+        "@-",
+        "--url",
+        url,
+    ]);
+    let out = run_command_with_stdin_output_with_timeout(cmd, &payload, label)?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "webhook post failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ))
+    }
+}
+
+fn send_desktop(event: &Value) -> Result<(), String> {
+    let message = event.get("message").and_then(Value::as_str).unwrap_or("");
+    let cmd = if cfg!(target_os = "macos") {
+        let mut c = Command::new("osascript");
+        c.arg("-e").arg(format!(
+            "display notification {:?} with title \"cx alert\"",
+            message
+        ));
+        c
+    } else if cfg!(windows) {
+        return Err("desktop alerts are not supported on Windows".to_string());
+    } else {
+        let mut c = Command::new("notify-send");
+        c.args(["cx alert", message]);
+        c
+    };
+    let out = run_command_output_with_timeout(cmd, "alert desktop notify")?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(format!("desktop notify exited with status {}", out.status))
+    }
+}
+
+fn send_to_sink(sink: &AlertSink, event: &Value) -> Result<(), String> {
+    match sink {
+        AlertSink::File(path) => append_jsonl(path, event),
+        AlertSink::Webhook(_) if is_offline() => Err("not sent (offline mode)".to_string()),
+        AlertSink::Webhook(url) => send_webhook(url, event),
+        AlertSink::Desktop => send_desktop(event),
+    }
+}
+
+/// Send each event to every sink; returns one message per failed delivery.
+pub fn dispatch_alerts(sinks: &[AlertSink], events: &[Value]) -> Vec<String> {
+    let mut failures = Vec::new();
+    for event in events {
+        for sink in sinks {
+            if let Err(e) = send_to_sink(sink, event) {
+                failures.push(format!("{}: {e}", sink.label()));
+            }
+        }
+    }
+    failures
+}
+
+/// Post-run hook: evaluate thresholds for a freshly logged row and dispatch.
+/// Delivery problems are reported as warnings and never fail the run.
+pub fn dispatch_run_alerts(row: &Value) {
+    if !alerts_enabled() {
+        return;
+    }
+    let sinks = match configured_alert_sinks() {
+        Ok(v) if !v.is_empty() => v,
+        Ok(_) => return,
+        Err(e) => {
+            warn(&format!("cxrs alert: warning: {e}"));
+            return;
+        }
+    };
+    let t = alert_policy().for_row(row);
+    let events = alert_events_for_row(row, t.max_ms, t.max_eff_in);
+    for failure in dispatch_alerts(&sinks, &events) {
+        warn(&format!("cxrs alert: warning: sink {failure}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhook_urls_must_be_http() {
+        assert!(check_webhook_url("https://hooks.example.com/x").is_ok());
+        assert!(check_webhook_url("HTTP://localhost:8080").is_ok());
+        for bad in [
+            "-K/etc/passwd",
+            "file:///etc/passwd",
+            "ftp://h/x",
+            "hooks.example.com",
+        ] {
+            assert!(check_webhook_url(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn parses_sink_shapes() {
+        let sinks = parse_alert_sinks(&json!([
+            {"type": "file", "path": "/tmp/a.jsonl"},
+            {"type": "webhook", "url": "https://hooks.example/x"},
+            "desktop"
+        ]))
+        .expect("sinks");
+        assert_eq!(
+            sinks,
+            vec![
+                AlertSink::File(PathBuf::from("/tmp/a.jsonl")),
+                AlertSink::Webhook("https://hooks.example/x".to_string()),
+                AlertSink::Desktop,
+            ]
+        );
+        assert!(parse_alert_sinks(&json!([{"type": "webhook"}])).is_err());
+        assert!(parse_alert_sinks(&json!(["pager"])).is_err());
+    }
+
+    #[test]
+    fn row_events_follow_thresholds() {
+        let row = json!({"tool": "cxo", "duration_ms": 500, "effective_input_tokens": 9000});
+        let events = alert_events_for_row(&row, 1000, 8000);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["rule"], "max_eff_in");
+        assert!(alert_events_for_row(&row, 100, 10000)[0]["rule"] == "max_ms");
+    }
+
+    #[test]
+    fn overrides_merge_per_tool_over_base() {
+        let base = AlertThresholds {
+            max_ms: 12000,
+            max_eff_in: 8000,
+        };
+        let overrides =
+            parse_alert_overrides(base, &json!({"cxdiffsum": {"max_ms": 30000}})).expect("parse");
+        let policy = AlertPolicy { base, overrides };
+        assert_eq!(
+            policy.for_tool("cxdiffsum"),
+            AlertThresholds {
+                max_ms: 30000,
+                max_eff_in: 8000
+            }
+        );
+        assert_eq!(policy.for_row(&json!({"tool": "cxo"})), base);
+        assert!(parse_alert_overrides(base, &json!({"cxo": {"max_out": 1}})).is_err());
+        assert!(parse_alert_overrides(base, &json!({"cxo": {"max_ms": "fast"}})).is_err());
+    }
+}
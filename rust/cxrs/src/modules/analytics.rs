@@ -8,10 +8,15 @@ mod analytics_quota;
 #[path = "analytics_shared.rs"]
 mod analytics_shared;
 
-pub use crate::analytics_trace::print_trace;
-pub use crate::analytics_worklog::print_worklog;
+pub use crate::analytics_diff::cmd_diff_runs;
+pub use crate::analytics_digest::cmd_digest;
+pub use crate::analytics_trace::{
+    print_trace, print_trace_by_id, print_trace_remediations, trace_id_arg,
+};
+pub use crate::analytics_trends::cmd_trends;
+pub use crate::analytics_worklog::cmd_worklog;
 pub use analytics_alert::print_alert;
 pub use analytics_profile_metrics::{print_metrics, print_profile};
 pub use analytics_prompt_stats::cmd_prompt_stats;
 pub use analytics_quota::{cmd_quota, quota_probe_for_backend_days};
-pub use analytics_shared::parse_ts_epoch;
+pub use analytics_shared::{parse_ts_epoch, print_json_value};
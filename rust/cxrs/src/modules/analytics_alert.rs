@@ -1,11 +1,22 @@
+use serde_json::{Value, json};
 use std::path::Path;
 
+use crate::contract_versions::ALERT_JSON_CONTRACT_VERSION;
+use crate::run_filter::RunFilter;
 use crate::types::RunEntry;
 
-use super::analytics_shared::{env_u64, load_runs_for};
+use super::analytics_shared::{load_runs_for, print_json_value};
+use crate::alert_sinks::{AlertPolicy, alert_policy};
 
-fn print_alert_empty(n: usize, log_file: &Path) {
+fn print_filter_line(filter: &RunFilter) {
+    if !filter.is_empty() {
+        println!("Filter: {}", filter.label());
+    }
+}
+
+fn print_alert_empty(n: usize, filter: &RunFilter, log_file: &Path) {
     println!("== cxrs alert (last {n} runs) ==");
+    print_filter_line(filter);
     println!("Runs: 0");
     println!("Slow threshold violations: 0");
     println!("Token threshold violations: 0");
@@ -28,7 +39,7 @@ fn top_slowest(runs: &[RunEntry]) -> Vec<(u64, String, String)> {
             })
         })
         .collect();
-    slowest.sort_by(|a, b| b.0.cmp(&a.0));
+    slowest.sort_by_key(|b| std::cmp::Reverse(b.0));
     slowest.truncate(5);
     slowest
 }
@@ -57,7 +68,7 @@ fn top_heaviest(runs: &[RunEntry]) -> Vec<(u64, String, String)> {
             })
         })
         .collect();
-    heaviest.sort_by(|a, b| b.0.cmp(&a.0));
+    heaviest.sort_by_key(|b| std::cmp::Reverse(b.0));
     heaviest.truncate(5);
     heaviest
 }
@@ -65,8 +76,7 @@ fn top_heaviest(runs: &[RunEntry]) -> Vec<(u64, String, String)> {
 struct AlertHeaderStats {
     n: usize,
     runs_len: usize,
-    max_ms: u64,
-    max_eff: u64,
+    policy: AlertPolicy,
     slow_violations: usize,
     token_violations: usize,
     sum_in: u64,
@@ -76,7 +86,11 @@ struct AlertHeaderStats {
 fn print_alert_header(s: &AlertHeaderStats) {
     println!("== cxrs alert (last {} runs) ==", s.n);
     println!("Runs: {}", s.runs_len);
-    println!("Thresholds: max_ms={}, max_eff_in={}", s.max_ms, s.max_eff);
+    println!(
+        "Thresholds: max_ms={}, max_eff_in={}",
+        s.policy.base.max_ms, s.policy.base.max_eff_in
+    );
+    println!("Overrides: {}", s.policy.overrides_label());
     println!("Slow threshold violations: {}", s.slow_violations);
     println!("Token threshold violations: {}", s.token_violations);
     match (s.sum_in > 0).then_some((s.sum_cached as f64 / s.sum_in as f64) * 100.0) {
@@ -85,14 +99,15 @@ fn print_alert_header(s: &AlertHeaderStats) {
     }
 }
 
-fn collect_alert_stats(runs: &[RunEntry], max_ms: u64, max_eff: u64) -> (usize, usize, u64, u64) {
+fn collect_alert_stats(runs: &[RunEntry], policy: &AlertPolicy) -> (usize, usize, u64, u64) {
+    let limits = |r: &RunEntry| policy.for_tool(r.tool.as_deref().unwrap_or(""));
     let slow_violations = runs
         .iter()
-        .filter(|r| r.duration_ms.unwrap_or(0) > max_ms)
+        .filter(|r| r.duration_ms.unwrap_or(0) > limits(r).max_ms)
         .count();
     let token_violations = runs
         .iter()
-        .filter(|r| r.effective_input_tokens.unwrap_or(0) > max_eff)
+        .filter(|r| r.effective_input_tokens.unwrap_or(0) > limits(r).max_eff_in)
         .count();
     let sum_in: u64 = runs.iter().map(|r| r.input_tokens.unwrap_or(0)).sum();
     let sum_cached: u64 = runs
@@ -102,32 +117,63 @@ fn collect_alert_stats(runs: &[RunEntry], max_ms: u64, max_eff: u64) -> (usize,
     (slow_violations, token_violations, sum_in, sum_cached)
 }
 
-pub fn print_alert(n: usize) -> i32 {
-    let (log_file, runs) = match load_runs_for("alert", n) {
+fn top_runs_json(rows: Vec<(u64, String, String)>, key: &str) -> Vec<Value> {
+    rows.into_iter()
+        .map(|(value, tool, ts)| json!({ key: value, "tool": tool, "ts": ts }))
+        .collect()
+}
+
+fn alert_json(s: &AlertHeaderStats, runs: &[RunEntry], log_file: &Path) -> Value {
+    json!({
+        "contract_version": ALERT_JSON_CONTRACT_VERSION,
+        "window": s.n,
+        "runs": s.runs_len,
+        "thresholds": {
+            "max_ms": s.policy.base.max_ms,
+            "max_eff_in": s.policy.base.max_eff_in,
+            "overrides": s.policy.overrides_json(),
+        },
+        "slow_violations": s.slow_violations,
+        "token_violations": s.token_violations,
+        "cache_hit_rate": (s.sum_in > 0).then_some(s.sum_cached as f64 / s.sum_in as f64),
+        "top_slowest": top_runs_json(top_slowest(runs), "duration_ms"),
+        "top_heaviest": top_runs_json(top_heaviest(runs), "effective_input_tokens"),
+        "log_file": log_file.display().to_string(),
+    })
+}
+
+pub fn print_alert(n: usize, json_out: bool, filter: &RunFilter) -> i32 {
+    let (log_file, runs) = match load_runs_for("alert", n, filter) {
         Ok(v) => v,
         Err(code) => return code,
     };
-    if runs.is_empty() {
-        print_alert_empty(n, &log_file);
+    if runs.is_empty() && !json_out {
+        print_alert_empty(n, filter, &log_file);
         return 0;
     }
 
-    let max_ms = env_u64("CXALERT_MAX_MS", 12000);
-    let max_eff = env_u64("CXALERT_MAX_EFF_IN", 8000);
+    let policy = alert_policy();
     let (slow_violations, token_violations, sum_in, sum_cached) =
-        collect_alert_stats(&runs, max_ms, max_eff);
+        collect_alert_stats(&runs, &policy);
 
     let header = AlertHeaderStats {
         n,
         runs_len: runs.len(),
-        max_ms,
-        max_eff,
+        policy,
         slow_violations,
         token_violations,
         sum_in,
         sum_cached,
     };
+    if json_out {
+        let mut out = alert_json(&header, &runs, &log_file);
+        if let Some(f) = filter.to_json() {
+            out["filter"] = f;
+        }
+        return print_json_value("cxrs alert", &out);
+    }
     print_alert_header(&header);
+    print_filter_line(filter);
 
     print_top_runs(
         "Top 5 slowest:",
@@ -0,0 +1,233 @@
+use serde_json::{Value, json};
+use std::path::Path;
+
+use crate::contract_versions::DIFF_RUNS_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, print_runtime_error, print_usage_error};
+use crate::paths::resolve_log_file;
+use crate::run_index::find_indexed_run;
+use crate::transcripts::load_transcript;
+
+const USAGE: &str = "diff-runs <exec_id_a> <exec_id_b> [--json] [--changed]";
+
+/// Run-log fields compared by `diff-runs`, in display order.
+const FIELDS: &[&str] = &[
+    "tool",
+    "llm_backend",
+    "llm_model",
+    "execution_mode",
+    "duration_ms",
+    "input_tokens",
+    "cached_input_tokens",
+    "effective_input_tokens",
+    "output_tokens",
+    "prompt_tokens_est",
+    "prompt_len_raw",
+    "prompt_len_filtered",
+    "capture_provider",
+    "reducer",
+    "clip_mode",
+    "clipped",
+    "chunk_strategy",
+    "chunk_count",
+    "redactions_applied",
+    "system_output_len_raw",
+    "system_output_len_clipped",
+    "system_output_lines_raw",
+    "system_output_lines_clipped",
+    "stdout_len_raw",
+    "stderr_len_raw",
+    "stderr_len_clipped",
+    "ansi_sequences_stripped",
+    "binary_output",
+    "system_output_tokens_raw",
+    "system_output_tokens_clipped",
+    "schema_valid",
+    "schema_reason",
+];
+
+/// The logged row for `execution_id` (or a unique prefix of one).
+pub fn find_run(log_file: &Path, execution_id: &str) -> Result<Value, String> {
+    find_indexed_run(log_file, execution_id).map(|found| found.row)
+}
+
+fn delta(a: &Value, b: &Value) -> Option<String> {
+    let (a, b) = (a.as_f64()?, b.as_f64()?);
+    let d = b - a;
+    if d == 0.0 {
+        return None;
+    }
+    let pct = if a == 0.0 {
+        String::new()
+    } else {
+        format!(" ({:+.1}%)", d / a * 100.0)
+    };
+    Some(format!("{d:+}{pct}"))
+}
+
+fn field_rows(a: &Value, b: &Value) -> Vec<Value> {
+    FIELDS
+        .iter()
+        .filter_map(|field| {
+            let va = a.get(*field).cloned().unwrap_or(Value::Null);
+            let vb = b.get(*field).cloned().unwrap_or(Value::Null);
+            if va.is_null() && vb.is_null() {
+                return None;
+            }
+            Some(json!({
+                "field": field,
+                "a": va,
+                "b": vb,
+                "changed": va != vb,
+                "delta": delta(&va, &vb),
+            }))
+        })
+        .collect()
+}
+
+fn text_len(v: &Value, key: &str) -> Option<usize> {
+    v.get(key)
+        .and_then(Value::as_str)
+        .map(|s| s.chars().count())
+}
+
+/// Compare archived transcripts when both runs have one; `null` otherwise.
+fn transcript_diff(a_id: &str, b_id: &str) -> Value {
+    let (Ok(a), Ok(b)) = (load_transcript(a_id), load_transcript(b_id)) else {
+        return Value::Null;
+    };
+    json!({
+        "prompt_equal": a.get("prompt") == b.get("prompt"),
+        "response_equal": a.get("response") == b.get("response"),
+        "prompt_chars": [text_len(&a, "prompt"), text_len(&b, "prompt")],
+        "response_chars": [text_len(&a, "response"), text_len(&b, "response")],
+    })
+}
+
+fn cell(v: &Value) -> String {
+    match v {
+        Value::Null => "n/a".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn print_text(report: &Value, changed_only: bool) {
+    println!(
+        "== cxrs diff-runs {} -> {} ==",
+        cell(&report["a"]),
+        cell(&report["b"])
+    );
+    println!("  {:<30} {:<24} {:<24} delta", "field", "a", "b");
+    let rows = report["fields"].as_array().cloned().unwrap_or_default();
+    for row in &rows {
+        let changed = row["changed"].as_bool().unwrap_or(false);
+        if changed_only && !changed {
+            continue;
+        }
+        println!(
+            "{} {:<30} {:<24} {:<24} {}",
+            if changed { "*" } else { " " },
+            cell(&row["field"]),
+            cell(&row["a"]),
+            cell(&row["b"]),
+            row["delta"].as_str().unwrap_or("")
+        );
+    }
+    let hash = match report["prompt_hash_equal"].as_bool() {
+        Some(true) => "same",
+        Some(false) => "differs",
+        None => "n/a",
+    };
+    println!("prompt_sha256: {hash}");
+    let t = &report["transcripts"];
+    if t.is_null() {
+        println!("transcripts: n/a (need both archived)");
+    } else {
+        let same = |k: &str| if t[k] == true { "same" } else { "differs" };
+        println!(
+            "transcripts: prompt {} ({} -> {} chars), response {} ({} -> {} chars)",
+            same("prompt_equal"),
+            cell(&t["prompt_chars"][0]),
+            cell(&t["prompt_chars"][1]),
+            same("response_equal"),
+            cell(&t["response_chars"][0]),
+            cell(&t["response_chars"][1])
+        );
+    }
+    println!("changed: {} of {} fields", report["changed"], rows.len());
+}
+
+/// `diff-runs <a> <b> [--json] [--changed]`: field-by-field comparison of two
+/// run-log rows (plus their transcripts when both are archived).
+pub fn cmd_diff_runs(args: &[String]) -> i32 {
+    let mut ids = Vec::new();
+    let mut json_out = false;
+    let mut changed_only = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json_out = true,
+            "--changed" => changed_only = true,
+            a if a.starts_with('-') => return print_usage_error("diff-runs", USAGE),
+            a => ids.push(a),
+        }
+    }
+    let [a_id, b_id] = ids[..] else {
+        return print_usage_error("diff-runs", USAGE);
+    };
+    let Some(log_file) = resolve_log_file() else {
+        return print_runtime_error("diff-runs", "unable to resolve log file");
+    };
+    let (a, b) = match (find_run(&log_file, a_id), find_run(&log_file, b_id)) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(e), _) | (_, Err(e)) => return print_runtime_error("diff-runs", &e),
+    };
+    let resolved = |row: &Value, query: &str| {
+        row.get("execution_id")
+            .and_then(Value::as_str)
+            .unwrap_or(query)
+            .to_string()
+    };
+    let (a_id, b_id) = (resolved(&a, a_id), resolved(&b, b_id));
+    let fields = field_rows(&a, &b);
+    let changed = fields.iter().filter(|r| r["changed"] == true).count();
+    let hash_equal = match (a.get("prompt_sha256"), b.get("prompt_sha256")) {
+        (Some(Value::String(x)), Some(Value::String(y))) => Value::Bool(x == y),
+        _ => Value::Null,
+    };
+    let report = json!({
+        "contract_version": DIFF_RUNS_JSON_CONTRACT_VERSION,
+        "a": a_id,
+        "b": b_id,
+        "fields": fields,
+        "changed": changed,
+        "prompt_hash_equal": hash_equal,
+        "transcripts": transcript_diff(&a_id, &b_id),
+    });
+    if json_out {
+        match serde_json::to_string_pretty(&report) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return print_runtime_error("diff-runs", &e.to_string()),
+        }
+    } else {
+        print_text(&report, changed_only);
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_skip_absent_fields_and_compute_deltas() {
+        let a = json!({"tool": "cxo", "duration_ms": 200, "output_tokens": 0});
+        let b = json!({"tool": "cxo", "duration_ms": 300, "output_tokens": 5, "clipped": true});
+        let rows = field_rows(&a, &b);
+        let get = |f: &str| rows.iter().find(|r| r["field"] == f).cloned();
+        assert_eq!(get("tool").expect("tool")["changed"], false);
+        assert_eq!(get("duration_ms").expect("ms")["delta"], "+100 (+50.0%)");
+        assert_eq!(get("output_tokens").expect("out")["delta"], "+5");
+        assert_eq!(get("clipped").expect("clipped")["a"], Value::Null);
+        assert!(get("llm_model").is_none());
+    }
+}
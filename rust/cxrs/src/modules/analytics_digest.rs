@@ -0,0 +1,538 @@
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::process::Command;
+
+use crate::alert_sinks::post_json;
+use crate::analytics::parse_ts_epoch;
+use crate::config_layers::config_env;
+use crate::contract_versions::DIGEST_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, EXIT_RUNTIME, format_error, print_runtime_error, print_usage_error};
+use crate::logs::load_runs;
+use crate::logs_failures::failure_cluster_rows;
+use crate::offline::is_offline;
+use crate::optimize_report::optimize_report;
+use crate::paths::{repo_root, resolve_log_file, resolve_quarantine_dir};
+use crate::process::run_command_with_stdin_output_with_timeout;
+use crate::prompting::promptlint_report;
+use crate::quarantine::scan_quarantine_dir;
+use crate::run_filter::{RunFilter, TimeBound};
+use crate::types::RunEntry;
+
+const DIGEST_USAGE: &str = "digest [--since 7d] [--until WHEN] [--json] [--send] [filters]";
+const DEFAULT_SINCE: &str = "7d";
+/// A tool regresses when its average grows by at least this much.
+const REGRESSION_PCT: i64 = 20;
+const TOP_N: usize = 5;
+
+#[derive(Debug)]
+struct DigestOpts {
+    json: bool,
+    send: bool,
+    filter: RunFilter,
+}
+
+fn parse_digest_args(args: &[String]) -> Result<DigestOpts, String> {
+    let mut opts = DigestOpts {
+        json: false,
+        send: false,
+        filter: RunFilter::default(),
+    };
+    let mut it = args.iter().peekable();
+    while let Some(arg) = it.next() {
+        if opts.filter.parse_flag(arg, it.peek().copied())? {
+            it.next();
+            continue;
+        }
+        match arg.as_str() {
+            "--json" => opts.json = true,
+            "--send" => opts.send = true,
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+    }
+    if opts.filter.since.is_none() {
+        opts.filter
+            .parse_flag("--since", Some(&DEFAULT_SINCE.to_string()))?;
+    }
+    Ok(opts)
+}
+
+/// This period and the one of equal length right before it.
+fn periods(filter: &RunFilter, now: i64) -> (RunFilter, RunFilter) {
+    let since = filter.since.as_ref().map_or(now, |b| b.epoch);
+    let until = filter.until.as_ref().map_or(now, |b| b.epoch);
+    let bound = |epoch: i64| TimeBound {
+        raw: day_label(epoch),
+        epoch,
+    };
+    let current = RunFilter {
+        until: Some(filter.until.clone().unwrap_or_else(|| bound(until))),
+        ..filter.clone()
+    };
+    let previous = RunFilter {
+        since: Some(bound(since - (until - since))),
+        until: Some(bound(since - 1)),
+        ..filter.clone()
+    };
+    (current, previous)
+}
+
+fn day_label(epoch: i64) -> String {
+    DateTime::<Utc>::from_timestamp(epoch, 0)
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| epoch.to_string())
+}
+
+#[derive(Debug, Default)]
+struct Totals {
+    runs: u64,
+    effective_input_tokens: u64,
+    output_tokens: u64,
+    duration_sum: u64,
+    duration_n: u64,
+    schema_failures: u64,
+    timeouts: u64,
+}
+
+impl Totals {
+    fn of(runs: &[RunEntry]) -> Self {
+        let mut t = Totals::default();
+        for r in runs {
+            t.runs += 1;
+            t.effective_input_tokens += r.effective_input_tokens.unwrap_or(0);
+            t.output_tokens += r.output_tokens.unwrap_or(0);
+            if let Some(d) = r.duration_ms {
+                t.duration_sum += d;
+                t.duration_n += 1;
+            }
+            t.schema_failures += u64::from(r.schema_valid == Some(false));
+            t.timeouts += u64::from(r.timed_out == Some(true));
+        }
+        t
+    }
+
+    fn metrics(&self) -> [(&'static str, u64); 6] {
+        [
+            ("runs", self.runs),
+            ("effective_input_tokens", self.effective_input_tokens),
+            ("output_tokens", self.output_tokens),
+            (
+                "avg_duration_ms",
+                self.duration_sum.checked_div(self.duration_n).unwrap_or(0),
+            ),
+            ("schema_failures", self.schema_failures),
+            ("timeouts", self.timeouts),
+        ]
+    }
+}
+
+/// Whole-percent change from `prev` to `cur`; `None` without a baseline.
+fn change_pct(prev: u64, cur: u64) -> Option<i64> {
+    (prev > 0).then(|| ((cur as f64 - prev as f64) / prev as f64 * 100.0).round() as i64)
+}
+
+fn totals_json(cur: &Totals, prev: &Totals) -> Vec<Value> {
+    cur.metrics()
+        .iter()
+        .zip(prev.metrics())
+        .map(|((metric, c), (_, p))| {
+            json!({"metric": metric, "current": c, "previous": p, "change_pct": change_pct(p, *c)})
+        })
+        .collect()
+}
+
+fn tool_averages(runs: &[RunEntry]) -> BTreeMap<String, (u64, u64)> {
+    let mut sums: BTreeMap<String, (u64, u64, u64)> = BTreeMap::new();
+    for r in runs {
+        let tool = r.tool.clone().unwrap_or_else(|| "unknown".to_string());
+        let e = sums.entry(tool).or_default();
+        e.0 += r.duration_ms.unwrap_or(0);
+        e.1 += r.effective_input_tokens.unwrap_or(0);
+        e.2 += 1;
+    }
+    sums.into_iter()
+        .map(|(tool, (d, eff, n))| (tool, (d / n, eff / n)))
+        .collect()
+}
+
+/// Tools whose average latency or prompt size grew by [`REGRESSION_PCT`] or
+/// more against the previous period, worst first.
+fn regressions(cur: &[RunEntry], prev: &[RunEntry]) -> Vec<Value> {
+    let before = tool_averages(prev);
+    let mut out: Vec<(i64, Value)> = Vec::new();
+    for (tool, (dur, eff)) in tool_averages(cur) {
+        let Some((p_dur, p_eff)) = before.get(&tool) else {
+            continue;
+        };
+        for (metric, p, c) in [
+            ("avg_duration_ms", *p_dur, dur),
+            ("avg_effective_input_tokens", *p_eff, eff),
+        ] {
+            if let Some(pct) = change_pct(p, c).filter(|pct| *pct >= REGRESSION_PCT) {
+                out.push((
+                    pct,
+                    json!({"tool": tool, "metric": metric, "previous": p, "current": c, "change_pct": pct}),
+                ));
+            }
+        }
+    }
+    out.sort_by_key(|(pct, _)| std::cmp::Reverse(*pct));
+    out.into_iter().take(TOP_N).map(|(_, v)| v).collect()
+}
+
+/// `optimize` then `promptlint` recommendations for the period, deduplicated.
+fn recommendations(filter: &RunFilter, runs: &[RunEntry]) -> Vec<String> {
+    if runs.is_empty() {
+        return Vec::new();
+    }
+    let mut out: Vec<String> = optimize_report(0, filter)
+        .ok()
+        .and_then(|r| r.get("recommendations").and_then(Value::as_array).cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|v| v.as_str().map(str::to_string))
+        .collect();
+    out.extend(promptlint_report(runs).recommendations);
+    let mut seen = Vec::new();
+    out.retain(|r| {
+        let fresh = !seen.contains(r);
+        seen.push(r.clone());
+        fresh
+    });
+    out.truncate(TOP_N);
+    out
+}
+
+fn quarantines(filter: &RunFilter) -> Value {
+    let in_period = |ts: &str| {
+        parse_ts_epoch(ts).is_some_and(|t| {
+            filter.since.as_ref().is_none_or(|s| t >= s.epoch)
+                && filter.until.as_ref().is_none_or(|u| t <= u.epoch)
+        })
+    };
+    let mut recs: Vec<_> = resolve_quarantine_dir()
+        .map(|dir| scan_quarantine_dir(&dir))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(_, rec)| rec)
+        .filter(|rec| in_period(&rec.ts))
+        .collect();
+    recs.sort_by(|a, b| b.ts.cmp(&a.ts));
+    let total = recs.len();
+    let open: Vec<Value> = recs
+        .iter()
+        .filter(|rec| rec.resolved_ts.is_none())
+        .map(|rec| json!({"id": rec.id, "ts": rec.ts, "tool": rec.tool, "reason": rec.reason}))
+        .collect();
+    json!({
+        "total": total,
+        "unresolved": open.len(),
+        "notable": open.into_iter().take(TOP_N).collect::<Vec<_>>(),
+    })
+}
+
+fn digest_json(all: &[RunEntry], filter: &RunFilter, now: i64) -> Value {
+    let (current, previous) = periods(filter, now);
+    let cur: Vec<RunEntry> = current.apply(all.to_vec(), 0);
+    let prev: Vec<RunEntry> = previous.apply(all.to_vec(), 0);
+    let span = |f: &RunFilter| {
+        json!({
+            "since": f.since.as_ref().map(|b| day_label(b.epoch)),
+            "until": f.until.as_ref().map(|b| day_label(b.epoch)),
+        })
+    };
+    let mut clusters = failure_cluster_rows(&cur);
+    clusters.truncate(TOP_N);
+    let mut out = json!({
+        "contract_version": DIGEST_JSON_CONTRACT_VERSION,
+        "period": span(&current),
+        "previous_period": span(&previous),
+        "repo_root": repo_root().map(|p| p.display().to_string()),
+        "totals": totals_json(&Totals::of(&cur), &Totals::of(&prev)),
+        "regressions": regressions(&cur, &prev),
+        "recommendations": recommendations(&current, &cur),
+        "failure_clusters": clusters,
+        "quarantines": quarantines(&current),
+    });
+    if let Some(f) = row_filter(filter).to_json() {
+        out["filter"] = f;
+    }
+    out
+}
+
+/// The filter minus the period bounds, which the report states on its own.
+fn row_filter(filter: &RunFilter) -> RunFilter {
+    RunFilter {
+        since: None,
+        until: None,
+        ..filter.clone()
+    }
+}
+
+fn change_label(v: &Value) -> String {
+    match v.as_i64() {
+        Some(p) if p > 0 => format!("+{p}%"),
+        Some(p) => format!("{p}%"),
+        None => "n/a".to_string(),
+    }
+}
+
+fn str_of<'a>(v: &'a Value, key: &str) -> &'a str {
+    v.get(key).and_then(Value::as_str).unwrap_or("")
+}
+
+fn list_or_none(out: &mut String, lines: Vec<String>) {
+    if lines.is_empty() {
+        out.push_str("- none\n");
+    }
+    for line in lines {
+        out.push_str(&format!("- {line}\n"));
+    }
+}
+
+fn digest_markdown(doc: &Value, filter: &RunFilter) -> String {
+    let items = |key: &str| doc[key].as_array().cloned().unwrap_or_default();
+    let mut out = format!(
+        "# cx digest: {} to {}\n\n",
+        str_of(&doc["period"], "since"),
+        str_of(&doc["period"], "until")
+    );
+    out.push_str(&format!(
+        "Project: {}\n",
+        doc["repo_root"].as_str().unwrap_or("(global)")
+    ));
+    let filter = row_filter(filter);
+    if !filter.is_empty() {
+        out.push_str(&format!("Filter: {}\n", filter.label()));
+    }
+    out.push_str(&format!(
+        "\n## Totals\n\nCompared with {} to {}.\n\n| metric | this period | previous | change |\n|---|---:|---:|---:|\n",
+        str_of(&doc["previous_period"], "since"),
+        str_of(&doc["previous_period"], "until")
+    ));
+    for row in items("totals") {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            str_of(&row, "metric"),
+            row["current"],
+            row["previous"],
+            change_label(&row["change_pct"])
+        ));
+    }
+    out.push_str("\n## Top regressions\n\n");
+    list_or_none(
+        &mut out,
+        items("regressions")
+            .iter()
+            .map(|r| {
+                format!(
+                    "{} {}: {} -> {} ({})",
+                    str_of(r, "tool"),
+                    str_of(r, "metric"),
+                    r["previous"],
+                    r["current"],
+                    change_label(&r["change_pct"])
+                )
+            })
+            .collect(),
+    );
+    out.push_str("\n## Top recommendations\n\n");
+    list_or_none(
+        &mut out,
+        items("recommendations")
+            .iter()
+            .filter_map(|r| r.as_str().map(str::to_string))
+            .collect(),
+    );
+    out.push_str("\n## Schema failure clusters\n\n");
+    list_or_none(
+        &mut out,
+        items("failure_clusters")
+            .iter()
+            .map(|c| {
+                format!(
+                    "{}x {} | {} | {} (trend: {})",
+                    c["count"],
+                    str_of(c, "tool"),
+                    str_of(c, "reason"),
+                    str_of(c, "fingerprint"),
+                    str_of(c, "trend")
+                )
+            })
+            .collect(),
+    );
+    let q = &doc["quarantines"];
+    out.push_str(&format!(
+        "\n## Notable quarantines\n\n{} quarantined, {} unresolved.\n\n",
+        q["total"], q["unresolved"]
+    ));
+    list_or_none(
+        &mut out,
+        q["notable"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .map(|r| {
+                format!(
+                    "`{}` {} {}: {}",
+                    str_of(r, "id"),
+                    str_of(r, "ts"),
+                    str_of(r, "tool"),
+                    str_of(r, "reason")
+                )
+            })
+            .collect(),
+    );
+    out
+}
+
+fn send_email(to: &str, subject: &str, body: &str) -> Result<(), String> {
+    let sendmail = config_env("CX_DIGEST_SENDMAIL").unwrap_or_else(|| "sendmail".to_string());
+    let mut words = sendmail.split_whitespace();
+    let program = words.next().ok_or("CX_DIGEST_SENDMAIL is empty")?;
+    let mut cmd = Command::new(program);
+    cmd.args(words).arg("-t");
+    let message =
+        format!("To: {to}\nSubject: {subject}\nContent-Type: text/plain; charset=utf-8\n\n{body}");
+    let out = run_command_with_stdin_output_with_timeout(cmd, &message, "digest sendmail")?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(format!("{program} exited with status {}", out.status))
+    }
+}
+
+/// Deliver to `digest.webhook` and `digest.email`; one message per failure.
+fn send_digest(doc: &Value, markdown: &str) -> Result<usize, Vec<String>> {
+    let webhook = config_env("CX_DIGEST_WEBHOOK").filter(|v| !v.trim().is_empty());
+    let email = config_env("CX_DIGEST_EMAIL").filter(|v| !v.trim().is_empty());
+    if webhook.is_none() && email.is_none() {
+        return Err(vec![
+            "no digest sink configured; set digest.webhook (CX_DIGEST_WEBHOOK) or digest.email (CX_DIGEST_EMAIL)".to_string(),
+        ]);
+    }
+    let mut sent = 0;
+    let mut failures = Vec::new();
+    if let Some(url) = webhook {
+        let result = if is_offline() {
+            Err("not sent (offline mode)".to_string())
+        } else {
+            post_json(
+                &url,
+                &json!({"text": markdown, "cx_digest": doc}),
+                "digest webhook curl",
+            )
+        };
+        match result {
+            Ok(()) => sent += 1,
+            Err(e) => failures.push(format!("webhook({url}): {e}")),
+        }
+    }
+    if let Some(to) = email {
+        let subject = markdown.lines().next().unwrap_or("cx digest");
+        match send_email(&to, subject.trim_start_matches("# "), markdown) {
+            Ok(()) => sent += 1,
+            Err(e) => failures.push(format!("email({to}): {e}")),
+        }
+    }
+    if failures.is_empty() {
+        Ok(sent)
+    } else {
+        Err(failures)
+    }
+}
+
+/// `digest`: a period summary for status updates (totals against the previous
+/// period, regressions, recommendations, failure clusters and quarantines) as
+/// Markdown or JSON, optionally sent to the configured webhook/email.
+pub fn cmd_digest(args: &[String]) -> i32 {
+    let opts = match parse_digest_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("digest", &e));
+            return print_usage_error("digest", DIGEST_USAGE);
+        }
+    };
+    let Some(log_file) = resolve_log_file() else {
+        return print_runtime_error("digest", "unable to resolve log file");
+    };
+    let runs = if log_file.exists() {
+        match load_runs(&log_file, 0) {
+            Ok(v) => v,
+            Err(e) => return print_runtime_error("digest", &e),
+        }
+    } else {
+        Vec::new()
+    };
+    let doc = digest_json(&runs, &opts.filter, Utc::now().timestamp());
+    let markdown = digest_markdown(&doc, &opts.filter);
+    if opts.json {
+        match serde_json::to_string_pretty(&doc) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return print_runtime_error("digest", &e.to_string()),
+        }
+    } else {
+        print!("{markdown}");
+    }
+    if opts.send {
+        match send_digest(&doc, &markdown) {
+            Ok(n) => crate::cx_eprintln!("cxrs digest: sent to {n} sink(s)"),
+            Err(failures) => {
+                for f in &failures {
+                    crate::cx_eprintln!("{}", format_error("digest", f));
+                }
+                return EXIT_RUNTIME;
+            }
+        }
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(tool: &str, ts: &str, duration_ms: u64) -> RunEntry {
+        RunEntry {
+            tool: Some(tool.to_string()),
+            ts: Some(ts.to_string()),
+            duration_ms: Some(duration_ms),
+            effective_input_tokens: Some(100),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn previous_period_mirrors_the_current_one() {
+        let opts =
+            parse_digest_args(&["--since".to_string(), "2024-06-08".to_string()]).expect("parse");
+        let now = parse_ts_epoch("2024-06-15T00:00:00Z").expect("now");
+        let (cur, prev) = periods(&opts.filter, now);
+        assert_eq!(cur.until.as_ref().map(|b| b.epoch), Some(now));
+        assert_eq!(
+            day_label(prev.since.as_ref().expect("since").epoch),
+            "2024-06-01"
+        );
+        assert_eq!(
+            day_label(prev.until.as_ref().expect("until").epoch),
+            "2024-06-07"
+        );
+        assert!(parse_digest_args(&["--weekly".to_string()]).is_err());
+    }
+
+    #[test]
+    fn regressions_need_a_baseline_and_a_real_increase() {
+        let prev = vec![run("cxo", "t", 100), run("cxdiffsum", "t", 1000)];
+        let cur = vec![
+            run("cxo", "t", 150),
+            run("cxdiffsum", "t", 1100),
+            run("cxnew", "t", 9000),
+        ];
+        let rows = regressions(&cur, &prev);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["tool"], "cxo");
+        assert_eq!(rows[0]["change_pct"], 50);
+        assert_eq!(change_pct(0, 10), None);
+    }
+}
@@ -2,12 +2,25 @@ use serde_json::{Value, json};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::contract_versions::PROFILE_JSON_CONTRACT_VERSION;
+use crate::percentiles::{Percentiles, percentiles_by_tool, percentiles_json};
+use crate::repo_registry::{per_repo_json, print_per_repo};
+use crate::run_filter::{RunFilter, ScopeNote};
 use crate::types::RunEntry;
 
-use super::analytics_shared::{load_runs_for, print_json_value};
+use super::analytics_shared::{load_scoped_runs_for, print_json_value};
 
-fn print_profile_empty(n: usize, log_file: &Path) {
-    println!("== cxrs profile (last {n} runs) ==");
+fn print_profile_header(n: usize, filter: &RunFilter, note: ScopeNote, included: usize) {
+    let scope = if filter.all_repos { ", all repos" } else { "" };
+    println!("== cxrs profile (last {n} runs{scope}) ==");
+    println!("Scope: {}", note.label(included));
+    if !filter.is_empty() {
+        println!("Filter: {}", filter.label());
+    }
+}
+
+fn print_profile_empty(n: usize, filter: &RunFilter, note: ScopeNote, log_file: &Path) {
+    print_profile_header(n, filter, note, 0);
     println!("Runs: 0");
     println!("Avg duration: 0ms");
     println!("Avg effective tokens: 0");
@@ -36,16 +49,17 @@ fn max_eff_tool(runs: &[RunEntry]) -> Option<(u64, String)> {
         .max_by_key(|(e, _)| *e)
 }
 
-pub fn print_profile(n: usize) -> i32 {
-    let (log_file, runs) = match load_runs_for("profile", n) {
-        Ok(v) => v,
-        Err(code) => return code,
-    };
-    if runs.is_empty() {
-        print_profile_empty(n, &log_file);
-        return 0;
-    }
+struct ProfileStats {
+    runs: usize,
+    avg_duration_ms: u64,
+    avg_effective_input_tokens: u64,
+    cache_hit_rate: Option<f64>,
+    output_input_ratio: Option<f64>,
+    slowest: Option<(u64, String)>,
+    heaviest: Option<(u64, String)>,
+}
 
+fn profile_stats(runs: &[RunEntry]) -> ProfileStats {
     let total = runs.len() as u64;
     let sum_dur: u64 = runs.iter().map(|r| r.duration_ms.unwrap_or(0)).sum();
     let sum_eff: u64 = runs
@@ -58,27 +72,165 @@ pub fn print_profile(n: usize) -> i32 {
         .map(|r| r.cached_input_tokens.unwrap_or(0))
         .sum();
     let sum_out: u64 = runs.iter().map(|r| r.output_tokens.unwrap_or(0)).sum();
+    ProfileStats {
+        runs: runs.len(),
+        avg_duration_ms: sum_dur.checked_div(total).unwrap_or(0),
+        avg_effective_input_tokens: sum_eff.checked_div(total).unwrap_or(0),
+        cache_hit_rate: (sum_in > 0).then_some(sum_cached as f64 / sum_in as f64),
+        output_input_ratio: (sum_eff > 0).then_some(sum_out as f64 / sum_eff as f64),
+        slowest: max_duration_tool(runs),
+        heaviest: max_eff_tool(runs),
+    }
+}
+
+fn tool_samples(runs: &[RunEntry], field: fn(&RunEntry) -> Option<u64>) -> Vec<(String, u64)> {
+    runs.iter()
+        .filter_map(|r| {
+            let tool = r.tool.clone().unwrap_or_else(|| "unknown".to_string());
+            field(r).map(|v| (tool, v))
+        })
+        .collect()
+}
+
+struct LatencyPercentiles {
+    duration: Option<Percentiles>,
+    tokens: Option<Percentiles>,
+    by_tool: Vec<(String, Option<Percentiles>, Option<Percentiles>)>,
+}
+
+fn latency_percentiles(runs: &[RunEntry]) -> LatencyPercentiles {
+    let durations = tool_samples(runs, |r| r.duration_ms);
+    let tokens = tool_samples(runs, |r| r.effective_input_tokens);
+    let overall = |s: &[(String, u64)]| Percentiles::of(s.iter().map(|(_, v)| *v).collect());
+    let (duration, tokens_all) = (overall(&durations), overall(&tokens));
+    let dur_by_tool = percentiles_by_tool(durations);
+    let mut eff_by_tool = percentiles_by_tool(tokens);
+    let mut by_tool: Vec<_> = dur_by_tool
+        .into_iter()
+        .map(|(tool, d)| {
+            let e = eff_by_tool.remove(&tool);
+            (tool, Some(d), e)
+        })
+        .collect();
+    by_tool.extend(
+        eff_by_tool
+            .into_iter()
+            .map(|(tool, e)| (tool, None, Some(e))),
+    );
+    by_tool.sort_by(|a, b| a.0.cmp(&b.0));
+    LatencyPercentiles {
+        duration,
+        tokens: tokens_all,
+        by_tool,
+    }
+}
 
-    println!("== cxrs profile (last {n} runs) ==");
-    println!("Runs: {}", runs.len());
-    println!("Avg duration: {}ms", sum_dur / total);
-    println!("Avg effective tokens: {}", sum_eff / total);
-    match (sum_in > 0).then_some(sum_cached as f64 / sum_in as f64) {
+fn percentiles_value(p: &LatencyPercentiles) -> Value {
+    let by_tool: serde_json::Map<String, Value> = p
+        .by_tool
+        .iter()
+        .map(|(tool, d, e)| {
+            (
+                tool.clone(),
+                json!({
+                    "duration_ms": percentiles_json(*d),
+                    "effective_input_tokens": percentiles_json(*e),
+                }),
+            )
+        })
+        .collect();
+    json!({
+        "duration_ms": percentiles_json(p.duration),
+        "effective_input_tokens": percentiles_json(p.tokens),
+        "by_tool": by_tool,
+    })
+}
+
+fn triple(p: Option<Percentiles>) -> String {
+    p.map_or_else(
+        || "n/a".to_string(),
+        |p| format!("{}/{}/{}", p.p50, p.p90, p.p99),
+    )
+}
+
+fn print_percentiles(p: &LatencyPercentiles) {
+    println!("Duration p50/p90/p99: {}ms", triple(p.duration));
+    println!("Effective tokens p50/p90/p99: {}", triple(p.tokens));
+    println!("Per tool (duration ms | effective tokens, p50/p90/p99):");
+    for (tool, d, e) in &p.by_tool {
+        println!("  {tool}: {} | {}", triple(*d), triple(*e));
+    }
+}
+
+fn profile_json(n: usize, log_file: &Path, stats: &ProfileStats) -> Value {
+    json!({
+        "contract_version": PROFILE_JSON_CONTRACT_VERSION,
+        "window": n,
+        "runs": stats.runs,
+        "avg_duration_ms": stats.avg_duration_ms,
+        "avg_effective_input_tokens": stats.avg_effective_input_tokens,
+        "cache_hit_rate": stats.cache_hit_rate,
+        "output_input_ratio": stats.output_input_ratio,
+        "slowest_run": stats.slowest.as_ref().map(|(d, t)| json!({"duration_ms": d, "tool": t})),
+        "heaviest_context": stats
+            .heaviest
+            .as_ref()
+            .map(|(e, t)| json!({"effective_input_tokens": e, "tool": t})),
+        "log_file": log_file.display().to_string(),
+    })
+}
+
+pub fn print_profile(n: usize, json_out: bool, percentiles: bool, filter: &RunFilter) -> i32 {
+    let (log_file, runs, note) = match load_scoped_runs_for("profile", n, filter) {
+        Ok(v) => v,
+        Err(code) => return code,
+    };
+    let stats = profile_stats(&runs);
+    if json_out {
+        let mut out = profile_json(n, &log_file, &stats);
+        out["scope"] = note.to_json(runs.len());
+        if let Some(f) = filter.to_json() {
+            out["filter"] = f;
+        }
+        if percentiles {
+            out["percentiles"] = percentiles_value(&latency_percentiles(&runs));
+        }
+        if filter.all_repos {
+            out["by_repo"] = per_repo_json(&runs);
+        }
+        return print_json_value("cxrs profile", &out);
+    }
+    if runs.is_empty() {
+        print_profile_empty(n, filter, note, &log_file);
+        return 0;
+    }
+
+    print_profile_header(n, filter, note, runs.len());
+    println!("Runs: {}", stats.runs);
+    println!("Avg duration: {}ms", stats.avg_duration_ms);
+    println!("Avg effective tokens: {}", stats.avg_effective_input_tokens);
+    match stats.cache_hit_rate {
         Some(v) => println!("Cache hit rate: {}%", (v * 100.0).round() as i64),
         None => println!("Cache hit rate: n/a"),
     }
-    match (sum_eff > 0).then_some(sum_out as f64 / sum_eff as f64) {
+    match stats.output_input_ratio {
         Some(v) => println!("Output/input ratio: {:.2}", v),
         None => println!("Output/input ratio: n/a"),
     }
-    match max_duration_tool(&runs) {
+    match &stats.slowest {
         Some((d, t)) => println!("Slowest run: {d}ms ({t})"),
         None => println!("Slowest run: n/a"),
     }
-    match max_eff_tool(&runs) {
+    match &stats.heaviest {
         Some((e, t)) => println!("Heaviest context: {e} effective tokens ({t})"),
         None => println!("Heaviest context: n/a"),
     }
+    if percentiles {
+        print_percentiles(&latency_percentiles(&runs));
+    }
+    if filter.all_repos {
+        print_per_repo(&runs);
+    }
     println!("log_file: {}", log_file.display());
     0
 }
@@ -140,13 +292,21 @@ fn group_metrics_by_tool(runs: &[RunEntry]) -> Vec<Value> {
     by_tool
 }
 
-pub fn print_metrics(n: usize) -> i32 {
-    let (log_file, runs) = match load_runs_for("metrics", n) {
+pub fn print_metrics(n: usize, percentiles: bool, filter: &RunFilter) -> i32 {
+    let (log_file, runs, note) = match load_scoped_runs_for("metrics", n, filter) {
         Ok(v) => v,
         Err(code) => return code,
     };
     if runs.is_empty() {
-        return print_json_value("cxrs metrics", &metrics_empty_json(&log_file));
+        let mut out = metrics_empty_json(&log_file);
+        out["scope"] = note.to_json(0);
+        if let Some(f) = filter.to_json() {
+            out["filter"] = f;
+        }
+        if percentiles {
+            out["percentiles"] = percentiles_value(&latency_percentiles(&runs));
+        }
+        return print_json_value("cxrs metrics", &out);
     }
 
     let total = runs.len() as f64;
@@ -168,7 +328,7 @@ pub fn print_metrics(n: usize) -> i32 {
         .map(|r| r.output_tokens.unwrap_or(0) as f64)
         .sum();
 
-    let out = json!({
+    let mut out = json!({
       "log_file": log_file.display().to_string(),
       "runs": runs.len(),
       "avg_duration_ms": sum_dur / total,
@@ -178,5 +338,15 @@ pub fn print_metrics(n: usize) -> i32 {
       "avg_output_tokens": sum_out / total,
       "by_tool": group_metrics_by_tool(&runs)
     });
+    out["scope"] = note.to_json(runs.len());
+    if let Some(f) = filter.to_json() {
+        out["filter"] = f;
+    }
+    if percentiles {
+        out["percentiles"] = percentiles_value(&latency_percentiles(&runs));
+    }
+    if filter.all_repos {
+        out["by_repo"] = per_repo_json(&runs);
+    }
     print_json_value("cxrs metrics", &out)
 }
@@ -64,8 +64,8 @@ pub(super) fn top_commands(rows: &[Value]) -> Vec<Value> {
             json!({
                 "command": cmd,
                 "runs": runs,
-                "avg_effective_tokens": if runs == 0 { 0 } else { tokens / runs },
-                "avg_duration_ms": if runs == 0 { 0 } else { duration_ms / runs }
+                "avg_effective_tokens": tokens.checked_div(runs).unwrap_or(0),
+                "avg_duration_ms": duration_ms.checked_div(runs).unwrap_or(0)
             })
         })
         .collect();
@@ -1,7 +1,7 @@
 use serde_json::Value;
 
-use crate::logs::load_runs;
 use crate::paths::resolve_log_file;
+use crate::run_filter::{RunFilter, ScopeNote, load_filtered_runs, load_scoped_runs};
 use crate::types::RunEntry;
 
 pub fn parse_ts_epoch(ts: &str) -> Option<i64> {
@@ -10,14 +10,7 @@ pub fn parse_ts_epoch(ts: &str) -> Option<i64> {
         .map(|dt| dt.timestamp())
 }
 
-pub(super) fn env_u64(name: &str, default: u64) -> u64 {
-    std::env::var(name)
-        .ok()
-        .and_then(|s| s.parse::<u64>().ok())
-        .unwrap_or(default)
-}
-
-pub(super) fn print_json_value(prefix: &str, v: &Value) -> i32 {
+pub fn print_json_value(prefix: &str, v: &Value) -> i32 {
     match serde_json::to_string_pretty(v) {
         Ok(s) => {
             println!("{s}");
@@ -30,18 +23,20 @@ pub(super) fn print_json_value(prefix: &str, v: &Value) -> i32 {
     }
 }
 
+/// Last `n` runs matching `filter` (the filter is applied before the window).
 pub(super) fn load_runs_for(
     command: &str,
     n: usize,
+    filter: &RunFilter,
 ) -> Result<(std::path::PathBuf, Vec<RunEntry>), i32> {
     let Some(log_file) = resolve_log_file() else {
         crate::cx_eprintln!("cxrs: unable to resolve log file");
         return Err(1);
     };
-    if !log_file.exists() {
+    if !log_file.exists() && !filter.all_repos {
         return Ok((log_file, Vec::new()));
     }
-    match load_runs(&log_file, n) {
+    match load_filtered_runs(&log_file, n, filter) {
         Ok(v) => Ok((log_file, v)),
         Err(e) => {
             crate::cx_eprintln!("cxrs {command}: {e}");
@@ -49,3 +44,29 @@ pub(super) fn load_runs_for(
         }
     }
 }
+
+/// [`load_runs_for`] for reports that default to the current scope.
+pub(super) fn load_scoped_runs_for(
+    command: &str,
+    n: usize,
+    filter: &RunFilter,
+) -> Result<(std::path::PathBuf, Vec<RunEntry>, ScopeNote), i32> {
+    let Some(log_file) = resolve_log_file() else {
+        crate::cx_eprintln!("cxrs: unable to resolve log file");
+        return Err(1);
+    };
+    if !log_file.exists() && !filter.all_repos {
+        let note = ScopeNote {
+            scope: filter.report_scope(),
+            excluded: 0,
+        };
+        return Ok((log_file, Vec::new(), note));
+    }
+    match load_scoped_runs(&log_file, n, filter) {
+        Ok((runs, note)) => Ok((log_file, runs, note)),
+        Err(e) => {
+            crate::cx_eprintln!("cxrs {command}: {e}");
+            Err(1)
+        }
+    }
+}
@@ -1,5 +1,14 @@
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+
+use crate::analytics::print_json_value;
+use crate::contract_versions::{REMEDIATIONS_JSON_CONTRACT_VERSION, TRACE_JSON_CONTRACT_VERSION};
 use crate::logs::load_runs;
+use crate::notes::{notes_for, notes_json, read_notes};
 use crate::paths::resolve_log_file;
+use crate::run_index::find_indexed_run;
+use crate::trace_links::TraceLinks;
+use crate::types::RunEntry;
 
 fn show_field<T: ToString>(label: &str, value: Option<T>) {
     match value {
@@ -8,22 +17,67 @@ fn show_field<T: ToString>(label: &str, value: Option<T>) {
     }
 }
 
-pub fn print_trace(n: usize) -> i32 {
+/// Notes attached to `run` with `annotate run <execution_id>`.
+fn run_notes(run: &RunEntry) -> Vec<crate::notes::Note> {
+    match run.execution_id.as_deref() {
+        Some(id) => notes_for(&read_notes(), "run", id),
+        None => Vec::new(),
+    }
+}
+
+fn print_trace_json(n: usize, available: usize, run: &RunEntry, log_file: &Path) -> i32 {
+    let out = json!({
+        "contract_version": TRACE_JSON_CONTRACT_VERSION,
+        "index": n,
+        "available": available,
+        "run": {
+            "execution_id": run.execution_id,
+            "ts": run.ts,
+            "tool": run.tool,
+            "cwd": run.cwd,
+            "duration_ms": run.duration_ms,
+            "input_tokens": run.input_tokens,
+            "cached_input_tokens": run.cached_input_tokens,
+            "effective_input_tokens": run.effective_input_tokens,
+            "output_tokens": run.output_tokens,
+            "scope": run.scope,
+            "repo_root": run.repo_root,
+            "git_branch": run.git_branch,
+            "git_head": run.git_head,
+            "llm_backend": run.llm_backend,
+            "llm_model": run.llm_model,
+            "prompt_sha256": run.prompt_sha256,
+            "prompt_preview": run.prompt_preview
+        },
+        "links": TraceLinks::resolve(run).to_json(),
+        "notes": notes_json(&run_notes(run)),
+        "log_file": log_file.display().to_string(),
+    });
+    print_json_value("cxrs trace", &out)
+}
+
+fn load_trace_runs() -> Result<(PathBuf, Vec<RunEntry>), i32> {
     let Some(log_file) = resolve_log_file() else {
         crate::cx_eprintln!("cxrs: unable to resolve log file");
-        return 1;
+        return Err(1);
     };
     if !log_file.exists() {
         crate::cx_eprintln!("cxrs trace: no log file at {}", log_file.display());
-        return 1;
+        return Err(1);
     }
-
-    let runs = match load_runs(&log_file, usize::MAX) {
-        Ok(v) => v,
+    match load_runs(&log_file, usize::MAX) {
+        Ok(runs) => Ok((log_file, runs)),
         Err(e) => {
             crate::cx_eprintln!("cxrs trace: {e}");
-            return 1;
+            Err(1)
         }
+    }
+}
+
+pub fn print_trace(n: usize, json_out: bool) -> i32 {
+    let (log_file, runs) = match load_trace_runs() {
+        Ok(v) => v,
+        Err(code) => return code,
     };
     if runs.is_empty() {
         crate::cx_eprintln!("cxrs trace: no runs in {}", log_file.display());
@@ -39,8 +93,55 @@ pub fn print_trace(n: usize) -> i32 {
     }
     let idx = runs.len() - n;
     let run = runs.get(idx).cloned().unwrap_or_default();
+    render_trace(n, runs.len(), run, &log_file, json_out)
+}
 
+/// The execution id argument of `trace`, when the first positional argument
+/// is not a recency index.
+pub fn trace_id_arg(args: &[String]) -> Option<&str> {
+    args.iter()
+        .map(String::as_str)
+        .find(|a| !a.starts_with('-'))
+        .filter(|a| a.parse::<usize>().is_err())
+}
+
+/// `trace <execution_id|prefix>`: look the run up through the run-log index
+/// so the answer does not shift as new runs land.
+pub fn print_trace_by_id(query: &str, json_out: bool) -> i32 {
+    let Some(log_file) = resolve_log_file() else {
+        crate::cx_eprintln!("cxrs: unable to resolve log file");
+        return 1;
+    };
+    if !log_file.exists() {
+        crate::cx_eprintln!("cxrs trace: no log file at {}", log_file.display());
+        return 1;
+    }
+    let found = match find_indexed_run(&log_file, query) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs trace: {e}");
+            return 2;
+        }
+    };
+    let run: RunEntry = match serde_json::from_value(found.row) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs trace: unreadable run row: {e}");
+            return 1;
+        }
+    };
+    render_trace(found.recency, found.available, run, &log_file, json_out)
+}
+
+fn render_trace(n: usize, available: usize, run: RunEntry, log_file: &Path, json_out: bool) -> i32 {
+    if json_out {
+        return print_trace_json(n, available, &run, log_file);
+    }
+
+    let notes = run_notes(&run);
+    let links = TraceLinks::resolve(&run);
     println!("== cxrs trace (run #{n} most recent) ==");
+    show_field("execution_id", run.execution_id);
     show_field("ts", run.ts);
     show_field("tool", run.tool);
     show_field("cwd", run.cwd);
@@ -51,10 +152,105 @@ pub fn print_trace(n: usize) -> i32 {
     show_field("output_tokens", run.output_tokens);
     show_field("scope", run.scope);
     show_field("repo_root", run.repo_root);
+    show_field("git_branch", run.git_branch);
+    show_field("git_head", run.git_head);
     show_field("llm_backend", run.llm_backend);
     show_field("llm_model", run.llm_model);
     show_field("prompt_sha256", run.prompt_sha256);
     show_field("prompt_preview", run.prompt_preview);
+    links.print();
+    for note in &notes {
+        println!("note: [{}] {}", note.ts, note.note);
+    }
+    println!("log_file: {}", log_file.display());
+    0
+}
+
+fn decision_str<'a>(d: &'a Value, key: &str) -> &'a str {
+    d.get(key).and_then(Value::as_str).unwrap_or("n/a")
+}
+
+fn decision_num(d: &Value, key: &str) -> String {
+    d.get(key)
+        .filter(|v| v.is_number())
+        .map(ToString::to_string)
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Show the remediation commands recorded by the Nth most-recent fix-run.
+pub fn print_trace_remediations(n: usize, json_out: bool) -> i32 {
+    let (log_file, runs) = match load_trace_runs() {
+        Ok(v) => v,
+        Err(code) => return code,
+    };
+    let fix_runs: Vec<&RunEntry> = runs
+        .iter()
+        .filter(|r| r.parent_execution_id.is_none())
+        .filter(|r| r.fix_decisions.as_ref().is_some_and(Value::is_array))
+        .collect();
+    if fix_runs.is_empty() {
+        crate::cx_eprintln!(
+            "cxrs trace: no fix-run remediations in {}",
+            log_file.display()
+        );
+        return 1;
+    }
+    if n == 0 || n > fix_runs.len() {
+        crate::cx_eprintln!(
+            "cxrs trace: remediation index out of range (requested {}, available {})",
+            n,
+            fix_runs.len()
+        );
+        return 2;
+    }
+    let run = fix_runs[fix_runs.len() - n];
+    let decisions = run
+        .fix_decisions
+        .as_ref()
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    if json_out {
+        let out = json!({
+            "contract_version": REMEDIATIONS_JSON_CONTRACT_VERSION,
+            "index": n,
+            "available": fix_runs.len(),
+            "run": {
+                "ts": run.ts,
+                "cwd": run.cwd,
+                "duration_ms": run.duration_ms,
+                "policy_blocked": run.policy_blocked,
+                "policy_reason": run.policy_reason,
+            },
+            "remediations": decisions,
+            "log_file": log_file.display().to_string(),
+        });
+        return print_json_value("cxrs trace", &out);
+    }
+
+    println!("== cxrs trace remediations (fix-run #{n} most recent) ==");
+    show_field("ts", run.ts.as_deref());
+    show_field("cwd", run.cwd.as_deref());
+    show_field("policy_blocked", run.policy_blocked);
+    show_field("policy_reason", run.policy_reason.as_deref());
+    if decisions.is_empty() {
+        println!("remediations: none suggested");
+    }
+    for d in &decisions {
+        let rule = d
+            .get("rule")
+            .and_then(Value::as_str)
+            .map(|r| format!(" rule={r}"))
+            .unwrap_or_default();
+        println!(
+            "- {} ({}{rule}) exit={} duration_ms={}: {}",
+            decision_str(d, "decision"),
+            decision_str(d, "policy"),
+            decision_num(d, "exit_code"),
+            decision_num(d, "duration_ms"),
+            decision_str(d, "command")
+        );
+    }
     println!("log_file: {}", log_file.display());
     0
 }
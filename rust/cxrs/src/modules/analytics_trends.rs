@@ -0,0 +1,395 @@
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+use crate::analytics::parse_ts_epoch;
+use crate::contract_versions::TRENDS_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, format_error, print_runtime_error, print_usage_error};
+use crate::logs::load_runs;
+use crate::paths::resolve_log_file;
+use crate::quarantine_ops::parse_age_secs;
+use crate::repo_registry::{load_all_repo_runs, per_repo_json, print_per_repo};
+use crate::run_filter::RunFilter;
+use crate::types::RunEntry;
+
+const TRENDS_USAGE: &str =
+    "trends [--bucket day|hour] [--last 30d] [--all-repos] [--json] [filters]";
+const SPARK: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bucket {
+    Day,
+    Hour,
+}
+
+impl Bucket {
+    fn secs(self) -> i64 {
+        match self {
+            Bucket::Day => 86_400,
+            Bucket::Hour => 3_600,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Bucket::Day => "day",
+            Bucket::Hour => "hour",
+        }
+    }
+
+    /// `--last` when not given: a month of days or two days of hours.
+    fn default_last(self) -> &'static str {
+        match self {
+            Bucket::Day => "30d",
+            Bucket::Hour => "48h",
+        }
+    }
+
+    fn label(self, start: i64) -> String {
+        let fmt = match self {
+            Bucket::Day => "%Y-%m-%d",
+            Bucket::Hour => "%Y-%m-%dT%H:00Z",
+        };
+        DateTime::<Utc>::from_timestamp(start, 0)
+            .map(|dt| dt.format(fmt).to_string())
+            .unwrap_or_else(|| start.to_string())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct TrendsOpts {
+    bucket: Bucket,
+    last: String,
+    last_secs: i64,
+    json: bool,
+    filter: RunFilter,
+}
+
+fn parse_trends_args(args: &[String]) -> Result<TrendsOpts, String> {
+    let mut bucket = Bucket::Day;
+    let mut last: Option<String> = None;
+    let mut json = false;
+    let mut filter = RunFilter::default();
+    let mut it = args.iter().peekable();
+    while let Some(arg) = it.next() {
+        if filter.parse_flag(arg, it.peek().copied())? {
+            it.next();
+            continue;
+        }
+        match arg.as_str() {
+            "--json" => json = true,
+            "--all-repos" => filter.all_repos = true,
+            "--bucket" => {
+                bucket = match it.next().map(String::as_str) {
+                    Some("day") => Bucket::Day,
+                    Some("hour") => Bucket::Hour,
+                    other => {
+                        return Err(format!(
+                            "invalid --bucket '{}' (expected day|hour)",
+                            other.unwrap_or("")
+                        ));
+                    }
+                }
+            }
+            "--last" => last = Some(it.next().cloned().ok_or("--last requires a value")?),
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+    }
+    let last = last.unwrap_or_else(|| bucket.default_last().to_string());
+    let last_secs = parse_age_secs(&last)
+        .ok_or_else(|| format!("invalid --last '{last}' (expected e.g. 30d, 12h)"))?;
+    Ok(TrendsOpts {
+        bucket,
+        last,
+        last_secs,
+        json,
+        filter,
+    })
+}
+
+#[derive(Debug, Default, Clone)]
+struct BucketStats {
+    runs: u64,
+    input_tokens: u64,
+    cached_input_tokens: u64,
+    effective_input_tokens: u64,
+    output_tokens: u64,
+    duration_sum: u64,
+    duration_n: u64,
+    schema_checked: u64,
+    schema_failed: u64,
+}
+
+impl BucketStats {
+    fn push(&mut self, r: &RunEntry) {
+        self.runs += 1;
+        self.input_tokens += r.input_tokens.unwrap_or(0);
+        self.cached_input_tokens += r.cached_input_tokens.unwrap_or(0);
+        self.effective_input_tokens += r.effective_input_tokens.unwrap_or(0);
+        self.output_tokens += r.output_tokens.unwrap_or(0);
+        if let Some(d) = r.duration_ms {
+            self.duration_sum += d;
+            self.duration_n += 1;
+        }
+        if let Some(valid) = r.schema_valid {
+            self.schema_checked += 1;
+            self.schema_failed += u64::from(!valid);
+        }
+    }
+
+    fn avg_duration_ms(&self) -> Option<u64> {
+        self.duration_sum.checked_div(self.duration_n)
+    }
+
+    fn cache_hit_rate(&self) -> Option<f64> {
+        (self.input_tokens > 0).then(|| self.cached_input_tokens as f64 / self.input_tokens as f64)
+    }
+
+    fn schema_failure_rate(&self) -> Option<f64> {
+        (self.schema_checked > 0).then(|| self.schema_failed as f64 / self.schema_checked as f64)
+    }
+}
+
+/// Every bucket from the one containing `now - last` through the current one,
+/// so gaps show up as empty rows instead of disappearing.
+fn bucket_runs(runs: &[RunEntry], opts: &TrendsOpts, now: i64) -> BTreeMap<i64, BucketStats> {
+    let size = opts.bucket.secs();
+    let floor = |t: i64| t - t.rem_euclid(size);
+    let (first, last) = (floor(now - opts.last_secs), floor(now));
+    let mut out: BTreeMap<i64, BucketStats> = (0..)
+        .map(|i| first + i * size)
+        .take_while(|s| *s <= last)
+        .map(|s| (s, BucketStats::default()))
+        .collect();
+    for r in runs.iter().filter(|r| opts.filter.matches(r)) {
+        let Some(t) = r.ts.as_deref().and_then(parse_ts_epoch) else {
+            continue;
+        };
+        if let Some(stats) = out.get_mut(&floor(t)) {
+            stats.push(r);
+        }
+    }
+    out
+}
+
+fn sparkline(values: &[Option<f64>]) -> String {
+    let max = values.iter().flatten().fold(0.0_f64, |a, b| a.max(*b));
+    values
+        .iter()
+        .map(|v| match v {
+            None => ' ',
+            Some(_) if max <= 0.0 => SPARK[0],
+            Some(x) => SPARK[((x / max) * 7.0).round().clamp(0.0, 7.0) as usize],
+        })
+        .collect()
+}
+
+fn pct(v: Option<f64>) -> String {
+    v.map_or_else(
+        || "n/a".to_string(),
+        |r| format!("{}%", (r * 100.0).round() as i64),
+    )
+}
+
+/// Runs inside the bucketed window, for the `--all-repos` per-repo breakdown.
+fn window_runs(runs: &[RunEntry], opts: &TrendsOpts, first_bucket: i64) -> Vec<RunEntry> {
+    runs.iter()
+        .filter(|r| opts.filter.matches(r))
+        .filter(|r| {
+            r.ts.as_deref()
+                .and_then(parse_ts_epoch)
+                .is_some_and(|t| t >= first_bucket)
+        })
+        .cloned()
+        .collect()
+}
+
+fn print_trends_text(opts: &TrendsOpts, buckets: &BTreeMap<i64, BucketStats>) {
+    println!(
+        "== cxrs trends ({} buckets, last {}{}) ==",
+        opts.bucket.name(),
+        opts.last,
+        if opts.filter.all_repos {
+            ", all repos"
+        } else {
+            ""
+        }
+    );
+    if !opts.filter.is_empty() {
+        println!("filter: {}", opts.filter.label());
+    }
+    println!(
+        "{:<17} {:>5} {:>11} {:>10} {:>8} {:>6} {:>11}",
+        "bucket", "runs", "eff_tokens", "out_tokens", "avg_ms", "cache", "schema_fail"
+    );
+    for (start, s) in buckets.iter().filter(|(_, s)| s.runs > 0) {
+        println!(
+            "{:<17} {:>5} {:>11} {:>10} {:>8} {:>6} {:>11}",
+            opts.bucket.label(*start),
+            s.runs,
+            s.effective_input_tokens,
+            s.output_tokens,
+            s.avg_duration_ms()
+                .map_or_else(|| "n/a".to_string(), |v| v.to_string()),
+            pct(s.cache_hit_rate()),
+            pct(s.schema_failure_rate())
+        );
+    }
+    let stats: Vec<&BucketStats> = buckets.values().collect();
+    let series = |f: &dyn Fn(&BucketStats) -> Option<f64>| {
+        sparkline(&stats.iter().map(|s| f(s)).collect::<Vec<_>>())
+    };
+    println!("sparklines (oldest -> newest, blank = no data):");
+    println!("  runs         {}", series(&|s| Some(s.runs as f64)));
+    println!(
+        "  eff_tokens   {}",
+        series(&|s| Some(s.effective_input_tokens as f64))
+    );
+    println!(
+        "  avg_ms       {}",
+        series(&|s| s.avg_duration_ms().map(|v| v as f64))
+    );
+    println!("  cache_hit    {}", series(&|s| s.cache_hit_rate()));
+    println!("  schema_fail  {}", series(&|s| s.schema_failure_rate()));
+    let total: u64 = stats.iter().map(|s| s.runs).sum();
+    println!("total runs: {total}");
+}
+
+fn trends_json(opts: &TrendsOpts, buckets: &BTreeMap<i64, BucketStats>, log_file: &str) -> Value {
+    let rows: Vec<Value> = buckets
+        .iter()
+        .map(|(start, s)| {
+            json!({
+                "bucket": opts.bucket.label(*start),
+                "start_epoch": start,
+                "runs": s.runs,
+                "input_tokens": s.input_tokens,
+                "cached_input_tokens": s.cached_input_tokens,
+                "effective_input_tokens": s.effective_input_tokens,
+                "output_tokens": s.output_tokens,
+                "avg_duration_ms": s.avg_duration_ms(),
+                "cache_hit_rate": s.cache_hit_rate(),
+                "schema_failure_rate": s.schema_failure_rate(),
+            })
+        })
+        .collect();
+    let mut out = json!({
+        "contract_version": TRENDS_JSON_CONTRACT_VERSION,
+        "bucket": opts.bucket.name(),
+        "last": opts.last,
+        "runs": buckets.values().map(|s| s.runs).sum::<u64>(),
+        "buckets": rows,
+        "log_file": log_file,
+    });
+    if let Some(f) = opts.filter.to_json() {
+        out["filter"] = f;
+    }
+    out
+}
+
+/// `trends`: per-hour or per-day runs, tokens, latency, cache hit and schema
+/// failure rates over a time window, as a table with sparklines or JSON.
+pub fn cmd_trends(args: &[String]) -> i32 {
+    let opts = match parse_trends_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("trends", &e));
+            return print_usage_error("trends", TRENDS_USAGE);
+        }
+    };
+    let Some(log_file) = resolve_log_file() else {
+        return print_runtime_error("trends", "unable to resolve log file");
+    };
+    let runs = if opts.filter.all_repos {
+        load_all_repo_runs(0, &opts.filter)
+    } else if log_file.exists() {
+        match load_runs(&log_file, 0) {
+            Ok(v) => v,
+            Err(e) => return print_runtime_error("trends", &e),
+        }
+    } else {
+        Vec::new()
+    };
+    let buckets = bucket_runs(&runs, &opts, Utc::now().timestamp());
+    let in_window = opts.filter.all_repos.then(|| {
+        let first = buckets.keys().next().copied().unwrap_or(i64::MAX);
+        window_runs(&runs, &opts, first)
+    });
+    if opts.json {
+        let mut v = trends_json(&opts, &buckets, &log_file.display().to_string());
+        if let Some(w) = &in_window {
+            v["by_repo"] = per_repo_json(w);
+        }
+        match serde_json::to_string_pretty(&v) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return print_runtime_error("trends", &e.to_string()),
+        }
+    } else {
+        print_trends_text(&opts, &buckets);
+        if let Some(w) = &in_window {
+            print_per_repo(w);
+        }
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn run(ts: &str, valid: Option<bool>) -> RunEntry {
+        RunEntry {
+            ts: Some(ts.to_string()),
+            input_tokens: Some(100),
+            cached_input_tokens: Some(25),
+            schema_valid: valid,
+            ..RunEntry::default()
+        }
+    }
+
+    #[test]
+    fn args_default_last_per_bucket() {
+        let day = parse_trends_args(&[]).expect("day");
+        assert_eq!((day.bucket, day.last_secs), (Bucket::Day, 30 * 86_400));
+        let hour = parse_trends_args(&args(&["--bucket", "hour", "--json"])).expect("hour");
+        assert_eq!(
+            (hour.bucket, hour.last_secs, hour.json),
+            (Bucket::Hour, 48 * 3_600, true)
+        );
+        assert!(parse_trends_args(&args(&["--bucket", "week"])).is_err());
+        assert!(parse_trends_args(&args(&["--last", "soon"])).is_err());
+    }
+
+    #[test]
+    fn buckets_cover_window_and_drop_older_runs() {
+        let opts = parse_trends_args(&args(&["--last", "3d"])).expect("opts");
+        let now = parse_ts_epoch("2026-03-10T12:00:00Z").expect("now");
+        let runs = [
+            run("2026-03-10T01:00:00Z", Some(false)),
+            run("2026-03-10T02:00:00Z", Some(true)),
+            run("2026-03-08T23:59:59Z", None),
+            run("2026-03-01T00:00:00Z", None),
+        ];
+        let buckets = bucket_runs(&runs, &opts, now);
+        let labels: Vec<String> = buckets.keys().map(|s| Bucket::Day.label(*s)).collect();
+        assert_eq!(
+            labels,
+            ["2026-03-07", "2026-03-08", "2026-03-09", "2026-03-10"]
+        );
+        let today = buckets.values().last().expect("today");
+        assert_eq!(today.runs, 2);
+        assert_eq!(today.schema_failure_rate(), Some(0.5));
+        assert_eq!(today.cache_hit_rate(), Some(0.25));
+        assert_eq!(buckets.values().map(|s| s.runs).sum::<u64>(), 3);
+    }
+
+    #[test]
+    fn sparkline_scales_to_max_and_blanks_missing() {
+        assert_eq!(sparkline(&[Some(0.0), Some(4.0), None, Some(8.0)]), "▁▅ █");
+        assert_eq!(sparkline(&[Some(0.0), Some(0.0)]), "▁▁");
+    }
+}
@@ -1,20 +1,123 @@
-use std::collections::HashMap;
+use chrono::NaiveDate;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
 
+use crate::analytics::print_json_value;
+use crate::analytics_worklog_html::worklog_html;
+use crate::config::DEFAULT_RUN_WINDOW;
+use crate::contract_versions::WORKLOG_JSON_CONTRACT_VERSION;
+use crate::error::{format_error, print_runtime_error, print_usage_error};
 use crate::logs::load_runs;
+use crate::notes::{Note, read_notes};
 use crate::paths::resolve_log_file;
+use crate::run_filter::RunFilter;
+use crate::task_results::{task_result_ref, task_result_summary};
+use crate::tasks::read_tasks;
 use crate::types::RunEntry;
 
-fn print_worklog_empty(n: usize, log_file: &std::path::Path) {
-    println!("# cxrs Worklog");
-    println!();
-    println!("Window: last {n} runs");
-    println!();
-    println!("No runs found.");
-    println!();
-    println!("_log_file: {}_", log_file.display());
+pub const WORKLOG_USAGE: &str =
+    "worklog [N] [--format md|html|json] [--json] [--include-failures] [filters]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WorklogFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct WorklogOpts {
+    n: Option<usize>,
+    format: WorklogFormat,
+    pub include_failures: bool,
+    filter: RunFilter,
+}
+
+fn parse_worklog_args(args: &[String]) -> Result<WorklogOpts, String> {
+    let mut opts = WorklogOpts {
+        n: None,
+        format: WorklogFormat::Markdown,
+        include_failures: false,
+        filter: RunFilter::default(),
+    };
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => opts.format = WorklogFormat::Json,
+            "--include-failures" => opts.include_failures = true,
+            "--format" => {
+                opts.format = match args.get(i + 1).map(String::as_str) {
+                    Some("md") | Some("markdown") => WorklogFormat::Markdown,
+                    Some("html") => WorklogFormat::Html,
+                    Some("json") => WorklogFormat::Json,
+                    other => {
+                        return Err(format!(
+                            "--format expects md|html|json, got '{}'",
+                            other.unwrap_or("")
+                        ));
+                    }
+                };
+                i += 1;
+            }
+            v if opts.filter.parse_flag(v, args.get(i + 1))? => i += 1,
+            v => match v.parse::<usize>() {
+                Ok(n) if n > 0 => opts.n = Some(n),
+                _ => return Err(format!("unknown argument '{v}'")),
+            },
+        }
+        i += 1;
+    }
+    Ok(opts)
+}
+
+fn run_day(r: &RunEntry) -> Option<NaiveDate> {
+    let ts = r.ts.as_deref()?;
+    NaiveDate::parse_from_str(ts.get(..10)?, "%Y-%m-%d").ok()
 }
 
-fn grouped_rows(runs: &[RunEntry]) -> Vec<(String, u64, u64, u64)> {
+/// Explicit N, else the default window unless a time range is given (then unbounded).
+fn window_limit(opts: &WorklogOpts) -> Option<usize> {
+    opts.n
+        .or((!opts.filter.is_dated()).then_some(DEFAULT_RUN_WINDOW))
+}
+
+/// Keep runs matching the shared filter (inclusive time range, tool, task,
+/// branch, path), then the last N.
+fn select_runs(runs: Vec<RunEntry>, opts: &WorklogOpts) -> Vec<RunEntry> {
+    opts.filter.apply(runs, window_limit(opts).unwrap_or(0))
+}
+
+pub fn window_label(opts: &WorklogOpts) -> String {
+    let f = &opts.filter;
+    let range = f.is_dated().then(|| {
+        format!(
+            "{} .. {}",
+            f.since.as_ref().map_or("start", |b| b.raw.as_str()),
+            f.until.as_ref().map_or("now", |b| b.raw.as_str())
+        )
+    });
+    let label = match (range, opts.n) {
+        (Some(r), Some(n)) => format!("{r} (last {n} runs)"),
+        (Some(r), None) => r,
+        (None, n) => format!("last {} runs", n.unwrap_or(DEFAULT_RUN_WINDOW)),
+    };
+    let rest = RunFilter {
+        since: None,
+        until: None,
+        ..f.clone()
+    };
+    if rest.is_empty() {
+        label
+    } else {
+        format!("{label} [{}]", rest.label())
+    }
+}
+
+pub fn is_schema_failure(r: &RunEntry) -> bool {
+    r.schema_valid == Some(false)
+}
+
+pub fn grouped_rows(runs: &[RunEntry]) -> Vec<(String, u64, u64, u64)> {
     let mut by_tool: HashMap<String, (u64, u64, u64)> = HashMap::new();
     for r in runs {
         let tool = r.tool.clone().unwrap_or_else(|| "unknown".to_string());
@@ -26,8 +129,8 @@ fn grouped_rows(runs: &[RunEntry]) -> Vec<(String, u64, u64, u64)> {
     let mut grouped: Vec<(String, u64, u64, u64)> = by_tool
         .into_iter()
         .map(|(tool, (count, sum_dur, sum_eff))| {
-            let avg_dur = if count == 0 { 0 } else { sum_dur / count };
-            let avg_eff = if count == 0 { 0 } else { sum_eff / count };
+            let avg_dur = sum_dur.checked_div(count).unwrap_or(0);
+            let avg_eff = sum_eff.checked_div(count).unwrap_or(0);
             (tool, count, avg_dur, avg_eff)
         })
         .collect();
@@ -35,6 +138,95 @@ fn grouped_rows(runs: &[RunEntry]) -> Vec<(String, u64, u64, u64)> {
     grouped
 }
 
+/// Totals keyed by day or task id: (runs, duration ms, effective tokens, schema failures).
+pub type Rollup = BTreeMap<String, (u64, u64, u64, u64)>;
+
+fn rollup_by(runs: &[RunEntry], key: impl Fn(&RunEntry) -> Option<String>) -> Rollup {
+    let mut out = Rollup::new();
+    for r in runs {
+        let Some(k) = key(r) else { continue };
+        let entry = out.entry(k).or_insert((0, 0, 0, 0));
+        entry.0 += 1;
+        entry.1 += r.duration_ms.unwrap_or(0);
+        entry.2 += r.effective_input_tokens.unwrap_or(0);
+        entry.3 += u64::from(is_schema_failure(r));
+    }
+    out
+}
+
+pub fn by_day(runs: &[RunEntry]) -> Rollup {
+    rollup_by(runs, |r| {
+        Some(
+            run_day(r)
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+        )
+    })
+}
+
+pub fn by_task(runs: &[RunEntry]) -> Rollup {
+    rollup_by(runs, |r| r.task_id.clone().filter(|t| !t.is_empty()))
+}
+
+/// (task id, status, result summary) for tasks in the window with a saved result.
+pub fn task_results(runs: &[RunEntry]) -> Vec<(String, String, String)> {
+    let statuses: HashMap<String, String> = read_tasks()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| (t.id, t.status))
+        .collect();
+    by_task(runs)
+        .into_keys()
+        .filter_map(|id| {
+            let summary = task_result_summary(&id)?;
+            let status = statuses
+                .get(&id)
+                .cloned()
+                .unwrap_or_else(|| "unknown".to_string());
+            Some((id, status, summary))
+        })
+        .collect()
+}
+
+/// Notes on runs in the window and on tasks those runs belong to.
+pub fn window_notes(runs: &[RunEntry]) -> Vec<Note> {
+    let tasks = by_task(runs);
+    read_notes()
+        .into_iter()
+        .filter(|n| match n.kind.as_str() {
+            "run" => runs
+                .iter()
+                .any(|r| r.execution_id.as_deref() == Some(n.target.as_str())),
+            "task" => tasks.contains_key(&n.target),
+            _ => false,
+        })
+        .collect()
+}
+
+fn print_notes(notes: &[Note]) {
+    if notes.is_empty() {
+        return;
+    }
+    println!("## Notes");
+    println!();
+    for n in notes {
+        println!("- {} {} ({}): {}", n.kind, n.target, n.ts, n.note);
+    }
+    println!();
+}
+
+fn print_task_results(results: &[(String, String, String)]) {
+    if results.is_empty() {
+        return;
+    }
+    println!("## Task Results");
+    println!();
+    for (id, status, summary) in results {
+        println!("- {id} ({status}): {summary}");
+    }
+    println!();
+}
+
 fn print_grouped_table(grouped: Vec<(String, u64, u64, u64)>) {
     println!("## By Tool");
     println!();
@@ -46,42 +238,267 @@ fn print_grouped_table(grouped: Vec<(String, u64, u64, u64)>) {
     println!();
 }
 
+fn print_rollup_table(title: &str, key: &str, rollup: &Rollup) {
+    if rollup.is_empty() {
+        return;
+    }
+    println!("## {title}");
+    println!();
+    println!("| {key} | Runs | Total Duration (ms) | Effective Tokens | Schema Failures |");
+    println!("|---|---:|---:|---:|---:|");
+    for (k, (runs, dur, eff, fails)) in rollup {
+        println!("| {k} | {runs} | {dur} | {eff} | {fails} |");
+    }
+    println!();
+}
+
+fn run_line(r: &RunEntry) -> String {
+    let ts = r.ts.clone().unwrap_or_else(|| "n/a".to_string());
+    let tool = r.tool.clone().unwrap_or_else(|| "unknown".to_string());
+    let dur = r.duration_ms.unwrap_or(0);
+    let eff = r.effective_input_tokens.unwrap_or(0);
+    format!("{ts} | {tool} | {dur}ms | {eff} effective tokens")
+}
+
+/// Runs grouped under a heading per day, oldest first.
 fn print_runs(runs: &[RunEntry]) {
     println!("## Chronological Runs");
     println!();
+    let mut current: Option<String> = None;
     for r in runs {
-        let ts = r.ts.clone().unwrap_or_else(|| "n/a".to_string());
-        let tool = r.tool.clone().unwrap_or_else(|| "unknown".to_string());
-        let dur = r.duration_ms.unwrap_or(0);
-        let eff = r.effective_input_tokens.unwrap_or(0);
-        println!("- {ts} | {tool} | {dur}ms | {eff} effective tokens");
+        let day = run_day(r)
+            .map(|d| d.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        if current.as_deref() != Some(day.as_str()) {
+            println!("### {day}");
+            println!();
+            current = Some(day);
+        }
+        println!("- {}", run_line(r));
     }
     println!();
 }
 
-pub fn print_worklog(n: usize) -> i32 {
-    let Some(log_file) = resolve_log_file() else {
-        crate::cx_eprintln!("cxrs: unable to resolve log file");
-        return 1;
-    };
-    if !log_file.exists() {
-        print_worklog_empty(n, &log_file);
-        return 0;
+fn failure_line(r: &RunEntry) -> String {
+    format!(
+        "{} | {} | quarantine={} | {}",
+        r.ts.as_deref().unwrap_or("n/a"),
+        r.tool.as_deref().unwrap_or("unknown"),
+        r.quarantine_id.as_deref().unwrap_or("none"),
+        r.schema_reason
+            .as_deref()
+            .unwrap_or("schema validation failed")
+    )
+}
+
+fn print_failures(runs: &[RunEntry]) {
+    println!("## Schema Failures");
+    println!();
+    let failures: Vec<&RunEntry> = runs.iter().filter(|r| is_schema_failure(r)).collect();
+    if failures.is_empty() {
+        println!("None.");
     }
-    let runs = match load_runs(&log_file, n) {
-        Ok(v) => v,
-        Err(e) => {
-            crate::cx_eprintln!("cxrs worklog: {e}");
-            return 1;
-        }
-    };
+    for r in failures {
+        println!("- {}", failure_line(r));
+    }
+    println!();
+}
 
+fn print_worklog_markdown(opts: &WorklogOpts, runs: &[RunEntry], log_file: &std::path::Path) {
     println!("# cxrs Worklog");
     println!();
-    println!("Window: last {n} runs");
+    println!("Window: {}", window_label(opts));
     println!();
-    print_grouped_table(grouped_rows(&runs));
-    print_runs(&runs);
+    if runs.is_empty() {
+        println!("No runs found.");
+        println!();
+    } else {
+        print_grouped_table(grouped_rows(runs));
+        print_rollup_table("By Day", "Day", &by_day(runs));
+        print_rollup_table("By Task", "Task", &by_task(runs));
+        print_task_results(&task_results(runs));
+        print_notes(&window_notes(runs));
+        print_runs(runs);
+        if opts.include_failures {
+            print_failures(runs);
+        }
+    }
     println!("_log_file: {}_", log_file.display());
-    0
+}
+
+fn rollup_json(rollup: &Rollup, key: &str) -> Vec<Value> {
+    rollup
+        .iter()
+        .map(|(k, (runs, dur, eff, fails))| {
+            json!({
+                key: k,
+                "runs": runs,
+                "duration_ms": dur,
+                "effective_input_tokens": eff,
+                "schema_failures": fails
+            })
+        })
+        .collect()
+}
+
+fn worklog_json(opts: &WorklogOpts, runs: &[RunEntry], log_file: &std::path::Path) -> Value {
+    let by_tool: Vec<Value> = grouped_rows(runs)
+        .into_iter()
+        .map(|(tool, count, avg_dur, avg_eff)| {
+            json!({
+                "tool": tool,
+                "runs": count,
+                "avg_duration_ms": avg_dur,
+                "avg_effective_input_tokens": avg_eff
+            })
+        })
+        .collect();
+    let entries: Vec<Value> = runs
+        .iter()
+        .map(|r| {
+            json!({
+                "ts": r.ts,
+                "tool": r.tool.clone().unwrap_or_else(|| "unknown".to_string()),
+                "duration_ms": r.duration_ms.unwrap_or(0),
+                "effective_input_tokens": r.effective_input_tokens.unwrap_or(0),
+                "task_id": r.task_id
+            })
+        })
+        .collect();
+    let mut out = json!({
+        "contract_version": WORKLOG_JSON_CONTRACT_VERSION,
+        "window": window_limit(opts),
+        "since": opts.filter.since.as_ref().map(|b| &b.raw),
+        "until": opts.filter.until.as_ref().map(|b| &b.raw),
+        "runs": runs.len(),
+        "by_tool": by_tool,
+        "by_day": rollup_json(&by_day(runs), "day"),
+        "by_task": rollup_json(&by_task(runs), "task_id"),
+        "task_results": task_results(runs)
+            .into_iter()
+            .map(|(id, status, summary)| json!({
+                "task_id": id,
+                "status": status,
+                "result_ref": task_result_ref(&id),
+                "summary": summary
+            }))
+            .collect::<Vec<_>>(),
+        "notes": window_notes(runs),
+        "entries": entries,
+        "log_file": log_file.display().to_string(),
+    });
+    if let Some(f) = opts.filter.to_json() {
+        out["filter"] = f;
+    }
+    if opts.include_failures {
+        out["schema_failures"] = runs
+            .iter()
+            .filter(|r| is_schema_failure(r))
+            .map(|r| {
+                json!({
+                    "ts": r.ts,
+                    "tool": r.tool,
+                    "quarantine_id": r.quarantine_id,
+                    "schema_reason": r.schema_reason
+                })
+            })
+            .collect();
+    }
+    out
+}
+
+/// `worklog`: Markdown, HTML or JSON report of the last N runs or a date range,
+/// with per-tool, per-day and per-task rollups.
+pub fn cmd_worklog(args: &[String]) -> i32 {
+    let opts = match parse_worklog_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("worklog", &e));
+            return print_usage_error("worklog", WORKLOG_USAGE);
+        }
+    };
+    let Some(log_file) = resolve_log_file() else {
+        return print_runtime_error("worklog", "unable to resolve log file");
+    };
+    let runs = if log_file.exists() {
+        match load_runs(&log_file, 0) {
+            Ok(v) => select_runs(v, &opts),
+            Err(e) => return print_runtime_error("worklog", &e),
+        }
+    } else {
+        Vec::new()
+    };
+    match opts.format {
+        WorklogFormat::Json => {
+            print_json_value("cxrs worklog", &worklog_json(&opts, &runs, &log_file))
+        }
+        WorklogFormat::Html => {
+            println!("{}", worklog_html(&opts, &runs, &log_file));
+            0
+        }
+        WorklogFormat::Markdown => {
+            print_worklog_markdown(&opts, &runs, &log_file);
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn run(ts: &str, tool: &str) -> RunEntry {
+        RunEntry {
+            ts: Some(ts.to_string()),
+            tool: Some(tool.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn worklog_args_parse_ranges_and_formats() {
+        let opts = parse_worklog_args(&args(&[
+            "5",
+            "--since",
+            "2024-06-01",
+            "--format",
+            "html",
+            "--include-failures",
+        ]))
+        .unwrap();
+        assert_eq!(opts.n, Some(5));
+        assert_eq!(
+            opts.filter.since.map(|b| b.raw).as_deref(),
+            Some("2024-06-01")
+        );
+        assert_eq!(opts.format, WorklogFormat::Html);
+        assert!(opts.include_failures);
+        assert!(parse_worklog_args(&args(&["--since", "06/01/2024"])).is_err());
+        assert!(parse_worklog_args(&args(&["--format", "pdf"])).is_err());
+        assert!(
+            parse_worklog_args(&args(&["--since", "2024-06-02", "--until", "2024-06-01"])).is_err()
+        );
+    }
+
+    #[test]
+    fn date_range_selection_is_inclusive() {
+        let runs = vec![
+            run("2024-05-31T23:59:59Z", "a"),
+            run("2024-06-01T00:00:00Z", "b"),
+            run("2024-06-07T12:00:00Z", "c"),
+            run("2024-06-08T00:00:00Z", "d"),
+        ];
+        let opts =
+            parse_worklog_args(&args(&["--since", "2024-06-01", "--until", "2024-06-07"])).unwrap();
+        let picked: Vec<String> = select_runs(runs, &opts)
+            .into_iter()
+            .filter_map(|r| r.tool)
+            .collect();
+        assert_eq!(picked, vec!["b", "c"]);
+        assert_eq!(window_label(&opts), "2024-06-01 .. 2024-06-07");
+    }
 }
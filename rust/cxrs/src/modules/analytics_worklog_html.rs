@@ -0,0 +1,148 @@
+use std::path::Path;
+
+use crate::analytics_worklog::{
+    Rollup, WorklogOpts, by_day, by_task, grouped_rows, is_schema_failure, task_results,
+    window_label, window_notes,
+};
+use crate::types::RunEntry;
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_table(out: &mut String, title: &str, head: &[&str], rows: Vec<Vec<String>>) {
+    if rows.is_empty() {
+        return;
+    }
+    out.push_str(&format!("<h2>{}</h2>\n<table>\n<tr>", html_escape(title)));
+    for h in head {
+        out.push_str(&format!("<th>{}</th>", html_escape(h)));
+    }
+    out.push_str("</tr>\n");
+    for row in rows {
+        out.push_str("<tr>");
+        for cell in row {
+            out.push_str(&format!("<td>{}</td>", html_escape(&cell)));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+}
+
+fn rollup_rows(rollup: &Rollup) -> Vec<Vec<String>> {
+    rollup
+        .iter()
+        .map(|(k, (runs, dur, eff, fails))| {
+            vec![
+                k.clone(),
+                runs.to_string(),
+                dur.to_string(),
+                eff.to_string(),
+                fails.to_string(),
+            ]
+        })
+        .collect()
+}
+
+/// Standalone HTML page of the worklog (`--format html`).
+pub fn worklog_html(opts: &WorklogOpts, runs: &[RunEntry], log_file: &Path) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>cxrs Worklog</title>\n\
+<style>body{font-family:sans-serif}table{border-collapse:collapse}td,th{border:1px solid #ccc;padding:2px 8px}</style>\n\
+</head>\n<body>\n<h1>cxrs Worklog</h1>\n",
+    );
+    out.push_str(&format!(
+        "<p>Window: {} &middot; {} runs</p>\n",
+        html_escape(&window_label(opts)),
+        runs.len()
+    ));
+    let tools = grouped_rows(runs)
+        .into_iter()
+        .map(|(t, c, d, e)| vec![t, c.to_string(), d.to_string(), e.to_string()])
+        .collect();
+    html_table(
+        &mut out,
+        "By Tool",
+        &["Tool", "Runs", "Avg Duration (ms)", "Avg Effective Tokens"],
+        tools,
+    );
+    let rollup_head = [
+        "Runs",
+        "Total Duration (ms)",
+        "Effective Tokens",
+        "Schema Failures",
+    ];
+    let mut day_head = vec!["Day"];
+    day_head.extend(rollup_head);
+    html_table(&mut out, "By Day", &day_head, rollup_rows(&by_day(runs)));
+    let mut task_head = vec!["Task"];
+    task_head.extend(rollup_head);
+    html_table(&mut out, "By Task", &task_head, rollup_rows(&by_task(runs)));
+    let results: Vec<Vec<String>> = task_results(runs)
+        .into_iter()
+        .map(|(id, status, summary)| vec![id, status, summary])
+        .collect();
+    if !results.is_empty() {
+        html_table(
+            &mut out,
+            "Task Results",
+            &["Task", "Status", "Result"],
+            results,
+        );
+    }
+    let notes: Vec<Vec<String>> = window_notes(runs)
+        .into_iter()
+        .map(|n| vec![n.kind, n.target, n.ts, n.note])
+        .collect();
+    html_table(
+        &mut out,
+        "Notes",
+        &["Kind", "Target", "Timestamp", "Note"],
+        notes,
+    );
+    let entries = runs
+        .iter()
+        .map(|r| {
+            vec![
+                r.ts.clone().unwrap_or_else(|| "n/a".to_string()),
+                r.tool.clone().unwrap_or_else(|| "unknown".to_string()),
+                r.duration_ms.unwrap_or(0).to_string(),
+                r.effective_input_tokens.unwrap_or(0).to_string(),
+            ]
+        })
+        .collect();
+    html_table(
+        &mut out,
+        "Runs",
+        &["Timestamp", "Tool", "Duration (ms)", "Effective Tokens"],
+        entries,
+    );
+    if opts.include_failures {
+        let failures = runs
+            .iter()
+            .filter(|r| is_schema_failure(r))
+            .map(|r| {
+                vec![
+                    r.ts.clone().unwrap_or_else(|| "n/a".to_string()),
+                    r.tool.clone().unwrap_or_else(|| "unknown".to_string()),
+                    r.quarantine_id.clone().unwrap_or_default(),
+                    r.schema_reason.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        html_table(
+            &mut out,
+            "Schema Failures",
+            &["Timestamp", "Tool", "Quarantine", "Reason"],
+            failures,
+        );
+    }
+    out.push_str(&format!(
+        "<p><small>log_file: {}</small></p>\n</body>\n</html>",
+        html_escape(&log_file.display().to_string())
+    ));
+    out
+}
@@ -0,0 +1,371 @@
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fs;
+
+use crate::analytics::parse_ts_epoch;
+use crate::bench_llm::distribution_json;
+use crate::bench_parity_support::BenchStats;
+use crate::execmeta::utc_now_iso;
+use crate::logs::append_jsonl;
+use crate::paths::resolve_bench_history_file;
+use crate::types::RunEntry;
+use crate::verbosity::warn;
+
+/// A bench p90 must be at least this much slower than the week-old one to count.
+const REGRESSION_PCT: f64 = 20.0;
+const WEEK_SECS: i64 = 7 * 86_400;
+
+/// What one `bench`, `bench --llm` variant or `model bench` model measured.
+pub struct BenchRecord<'a> {
+    /// `bench`, `bench_llm` or `model_bench`.
+    pub source: &'a str,
+    pub label: &'a str,
+    pub backend: Option<&'a str>,
+    pub model: Option<&'a str>,
+    pub suite: Option<&'a str>,
+    pub schema_pass_rate: Option<f64>,
+    pub stats: &'a BenchStats,
+}
+
+/// Append `records` to `.codex/bench.jsonl` so `optimize` can compare them over
+/// time. History is best effort: a write failure only warns.
+pub fn record_bench_history(records: &[BenchRecord<'_>]) {
+    let Some(path) = resolve_bench_history_file() else {
+        return;
+    };
+    let ts = utc_now_iso();
+    for r in records {
+        let row = json!({
+            "ts": ts,
+            "source": r.source,
+            "label": r.label,
+            "backend": r.backend,
+            "model": r.model,
+            "suite": r.suite,
+            "runs": r.stats.durations.len(),
+            "failures": r.stats.failures,
+            "schema_pass_rate": r.schema_pass_rate,
+            "duration_ms": distribution_json(&r.stats.durations),
+            "effective_input_tokens": distribution_json(&r.stats.eff_totals),
+            "output_tokens": distribution_json(&r.stats.out_totals),
+        });
+        if let Err(e) = append_jsonl(&path, &row) {
+            warn(&format!(
+                "cxrs bench: warning: bench history not saved: {e}"
+            ));
+            return;
+        }
+    }
+}
+
+/// Every bench record, oldest first; unparsable lines are skipped.
+pub fn read_bench_history() -> Vec<Value> {
+    let Some(path) = resolve_bench_history_file() else {
+        return Vec::new();
+    };
+    let Ok(text) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+        .filter(|v| v.get("ts").and_then(Value::as_str).is_some())
+        .collect()
+}
+
+fn text<'a>(row: &'a Value, key: &str) -> Option<&'a str> {
+    row.get(key).and_then(Value::as_str)
+}
+
+fn ts_of(row: &Value) -> i64 {
+    text(row, "ts").and_then(parse_ts_epoch).unwrap_or_default()
+}
+
+fn stat(row: &Value, key: &str, stat: &str) -> Option<f64> {
+    row.get(key)?.get(stat)?.as_f64()
+}
+
+fn display_label(row: &Value) -> String {
+    let label = text(row, "label").unwrap_or("bench");
+    match (
+        text(row, "source"),
+        text(row, "suite"),
+        text(row, "backend"),
+    ) {
+        (Some("model_bench"), Some(suite), _) => format!("model bench {suite} {label}"),
+        (_, _, Some(backend)) => format!("{label} [{backend}]"),
+        _ => label.to_string(),
+    }
+}
+
+/// The command that re-runs the benchmark behind `row`.
+fn rerun_command(row: &Value) -> String {
+    let label = text(row, "label").unwrap_or("");
+    match (text(row, "source"), text(row, "backend")) {
+        (Some("model_bench"), _) => format!(
+            "cx model bench {} --models {label}",
+            text(row, "suite").unwrap_or(".")
+        ),
+        (Some("bench_llm"), Some(backend)) => {
+            format!("cx bench 5 --llm --backends {backend} -- {label}")
+        }
+        (Some("bench_llm"), None) => format!("cx bench 5 --llm -- {label}"),
+        _ => format!("cx bench 5 -- {label}"),
+    }
+}
+
+/// Benchmarks whose latest p90 is [`REGRESSION_PCT`] slower than the newest
+/// run of the same benchmark from at least a week earlier.
+pub fn latency_regressions(history: &[Value]) -> Vec<Value> {
+    let mut groups: HashMap<String, Vec<&Value>> = HashMap::new();
+    for row in history {
+        let key = ["source", "label", "backend", "model", "suite"]
+            .iter()
+            .map(|k| text(row, k).unwrap_or(""))
+            .collect::<Vec<_>>()
+            .join("\u{1f}");
+        groups.entry(key).or_default().push(row);
+    }
+    let mut out = Vec::new();
+    for rows in groups.values() {
+        let Some(latest) = rows.iter().max_by_key(|r| ts_of(r)) else {
+            continue;
+        };
+        let cutoff = ts_of(latest) - WEEK_SECS;
+        let Some(previous) = rows
+            .iter()
+            .filter(|r| ts_of(r) <= cutoff)
+            .max_by_key(|r| ts_of(r))
+        else {
+            continue;
+        };
+        let (Some(before), Some(after)) = (
+            stat(previous, "duration_ms", "p90"),
+            stat(latest, "duration_ms", "p90"),
+        ) else {
+            continue;
+        };
+        if before <= 0.0 {
+            continue;
+        }
+        let delta_pct = ((after - before) / before * 100.0).round();
+        if delta_pct >= REGRESSION_PCT {
+            out.push(json!({
+                "label": display_label(latest),
+                "previous_ts": text(previous, "ts"),
+                "latest_ts": text(latest, "ts"),
+                "previous_p90_ms": before as u64,
+                "latest_p90_ms": after as u64,
+                "delta_pct": delta_pct,
+                "days": (ts_of(latest) - ts_of(previous)) / 86_400,
+                "command": rerun_command(latest),
+            }));
+        }
+    }
+    out.sort_by(|a, b| {
+        b["delta_pct"]
+            .as_f64()
+            .unwrap_or(0.0)
+            .total_cmp(&a["delta_pct"].as_f64().unwrap_or(0.0))
+    });
+    out
+}
+
+/// Effective input tokens per day by tool for runs on `backend`, over the span
+/// the window covers (at least one day).
+fn daily_tokens_by_tool(runs: &[RunEntry], backend: &str) -> Vec<(String, f64)> {
+    let stamps: Vec<i64> = runs
+        .iter()
+        .filter_map(|r| r.ts.as_deref().and_then(parse_ts_epoch))
+        .collect();
+    let span = match (stamps.iter().min(), stamps.iter().max()) {
+        (Some(a), Some(b)) => ((b - a) as f64 / 86_400.0).max(1.0),
+        _ => 1.0,
+    };
+    let mut totals: HashMap<String, u64> = HashMap::new();
+    for r in runs {
+        if r.llm_backend.as_deref().is_some_and(|b| b != backend) {
+            continue;
+        }
+        let (Some(tool), Some(eff)) = (r.tool.as_ref(), r.effective_input_tokens) else {
+            continue;
+        };
+        *totals.entry(tool.clone()).or_insert(0) += eff;
+    }
+    let mut out: Vec<(String, f64)> = totals
+        .into_iter()
+        .filter(|(_, t)| *t > 0)
+        .map(|(tool, t)| (tool, t as f64 / span))
+        .collect();
+    out.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    out
+}
+
+/// From the latest `model bench` of each suite: when a model on another
+/// backend passed every prompt (and at least as many schema checks) with fewer
+/// effective input tokens than `backend`, estimate what moving the busiest
+/// tool in `runs` to it would save per day.
+pub fn backend_savings(history: &[Value], runs: &[RunEntry], backend: &str) -> Vec<Value> {
+    let mut latest: HashMap<(String, String), &Value> = HashMap::new();
+    for row in history
+        .iter()
+        .filter(|r| text(r, "source") == Some("model_bench"))
+    {
+        let key = (
+            text(row, "suite").unwrap_or("").to_string(),
+            text(row, "label").unwrap_or("").to_string(),
+        );
+        if latest
+            .get(&key)
+            .is_none_or(|prev| ts_of(prev) <= ts_of(row))
+        {
+            latest.insert(key, row);
+        }
+    }
+    let mut suites: HashMap<&str, Vec<&Value>> = HashMap::new();
+    for ((suite, _), row) in &latest {
+        suites.entry(suite.as_str()).or_default().push(row);
+    }
+    let Some((tool, per_day)) = daily_tokens_by_tool(runs, backend).into_iter().next() else {
+        return Vec::new();
+    };
+    let eff = |r: &Value| stat(r, "effective_input_tokens", "avg").filter(|v| *v > 0.0);
+    let pass = |r: &Value| {
+        r.get("schema_pass_rate")
+            .and_then(Value::as_f64)
+            .unwrap_or(1.0)
+    };
+    let mut out = Vec::new();
+    for (suite, rows) in suites {
+        let Some(current) = rows
+            .iter()
+            .filter(|r| text(r, "backend") == Some(backend) && eff(r).is_some())
+            .min_by(|a, b| eff(a).unwrap_or(0.0).total_cmp(&eff(b).unwrap_or(0.0)))
+        else {
+            continue;
+        };
+        let cur_eff = eff(current).unwrap_or(0.0);
+        let Some(best) = rows
+            .iter()
+            .filter(|r| text(r, "backend").is_some_and(|b| b != backend))
+            .filter(|r| r.get("failures").and_then(Value::as_u64) == Some(0))
+            .filter(|r| pass(r) >= pass(current))
+            .filter(|r| eff(r).is_some_and(|e| e < cur_eff))
+            .min_by(|a, b| eff(a).unwrap_or(0.0).total_cmp(&eff(b).unwrap_or(0.0)))
+        else {
+            continue;
+        };
+        let alt_eff = eff(best).unwrap_or(0.0);
+        let saved = (per_day * (1.0 - alt_eff / cur_eff)).round() as u64;
+        if saved == 0 {
+            continue;
+        }
+        out.push(json!({
+            "suite": suite,
+            "tool": tool,
+            "from_backend": backend,
+            "to_backend": text(best, "backend"),
+            "to_model": text(best, "model"),
+            "command": format!(
+                "cx model bench {suite} --models {},{}",
+                text(current, "label").unwrap_or(backend),
+                text(best, "label").unwrap_or("")
+            ),
+            "bench_effective_input_tokens": {"current": cur_eff.round(), "candidate": alt_eff.round()},
+            "tokens_per_day_saved": saved,
+        }));
+    }
+    out.sort_by_key(|v| std::cmp::Reverse(v["tokens_per_day_saved"].as_u64().unwrap_or(0)));
+    out
+}
+
+/// `optimize` recommendation lines for the findings above.
+pub fn bench_recommendations(regressions: &[Value], savings: &[Value]) -> Vec<String> {
+    let mut out = Vec::new();
+    for r in regressions {
+        out.push(format!(
+            "{} p90 latency regressed {}% since {} days ago (bench: {}ms -> {}ms).",
+            r["label"].as_str().unwrap_or("bench"),
+            r["delta_pct"].as_f64().unwrap_or(0.0),
+            r["days"],
+            r["previous_p90_ms"],
+            r["latest_p90_ms"]
+        ));
+    }
+    for s in savings {
+        let target = match s["to_model"].as_str() {
+            Some(m) => format!("{} ({m})", s["to_backend"].as_str().unwrap_or("")),
+            None => s["to_backend"].as_str().unwrap_or("").to_string(),
+        };
+        out.push(format!(
+            "Switching backend to {target} for {} would save ~{} tokens/day based on model bench '{}'.",
+            s["tool"].as_str().unwrap_or(""),
+            s["tokens_per_day_saved"],
+            s["suite"].as_str().unwrap_or("")
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(ts: &str, label: &str, p90: u64) -> Value {
+        json!({"ts": ts, "source": "bench", "label": label, "duration_ms": {"p90": p90}})
+    }
+
+    fn model_row(ts: &str, label: &str, backend: &str, eff: f64, failures: u64) -> Value {
+        json!({
+            "ts": ts, "source": "model_bench", "suite": "prompts", "label": label,
+            "backend": backend, "model": label, "failures": failures,
+            "effective_input_tokens": {"avg": eff},
+        })
+    }
+
+    #[test]
+    fn regressions_compare_against_a_week_old_run() {
+        let history = vec![
+            row("2026-01-01T00:00:00Z", "cxdiffsum", 1000),
+            row("2026-01-03T00:00:00Z", "cxdiffsum", 1100),
+            row("2026-01-09T00:00:00Z", "cxdiffsum", 1400),
+            row("2026-01-09T00:00:00Z", "cxnext", 900),
+            row("2026-01-08T00:00:00Z", "cxcommitjson", 500),
+            row("2026-01-01T00:00:00Z", "cxcommitjson", 480),
+        ];
+        let out = latency_regressions(&history);
+        assert_eq!(out.len(), 1, "{out:?}");
+        assert_eq!(out[0]["label"], "cxdiffsum");
+        assert_eq!(out[0]["delta_pct"], 40.0);
+        assert_eq!(out[0]["days"], 8);
+        let lines = bench_recommendations(&out, &[]);
+        assert!(lines[0].starts_with("cxdiffsum p90 latency regressed 40%"));
+    }
+
+    #[test]
+    fn savings_scale_busiest_tool_by_bench_token_ratio() {
+        let history = vec![
+            model_row("2026-01-01T00:00:00Z", "codex", "codex", 1000.0, 0),
+            model_row("2026-01-01T00:00:00Z", "llama3", "ollama", 600.0, 0),
+            model_row("2026-01-01T00:00:00Z", "tiny", "ollama", 100.0, 2),
+        ];
+        let runs: Vec<RunEntry> = [
+            ("2026-01-01T00:00:00Z", "cxnext", 4000),
+            ("2026-01-02T00:00:00Z", "cxnext", 4000),
+            ("2026-01-02T00:00:00Z", "cxo", 100),
+        ]
+        .iter()
+        .map(|(ts, tool, eff)| {
+            serde_json::from_value(json!({
+                "ts": ts, "tool": tool, "effective_input_tokens": eff, "llm_backend": "codex"
+            }))
+            .unwrap()
+        })
+        .collect();
+        let out = backend_savings(&history, &runs, "codex");
+        assert_eq!(out.len(), 1, "{out:?}");
+        assert_eq!(out[0]["tool"], "cxnext");
+        assert_eq!(out[0]["to_model"], "llama3");
+        assert_eq!(out[0]["tokens_per_day_saved"], 3200);
+        assert!(backend_savings(&history, &runs, "ollama").is_empty());
+    }
+}
@@ -0,0 +1,459 @@
+use chrono::Utc;
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::time::Instant;
+
+use crate::bench_history::{BenchRecord, record_bench_history};
+use crate::bench_llm_args::{Variant, parse_bench_llm_args};
+use crate::bench_parity_support::{BenchStats, maybe_collect_tokens};
+use crate::config::app_config;
+use crate::contract_versions::BENCH_LLM_JSON_CONTRACT_VERSION;
+use crate::error::{format_error, print_usage_error};
+use crate::logs::file_len;
+use crate::paths::resolve_log_file;
+use crate::percentiles::Percentiles;
+use crate::process::run_command_output_with_timeout;
+use crate::state::write_json_atomic;
+
+pub const BENCH_LLM_USAGE: &str = "bench <N> --llm [--warmup K] [--backends a,b | --compare | --suite FILE] [--baseline FILE] [--max-regression PCT] [--write-baseline FILE] [--json] [-- <cmd...> [-- <cmd2...>]]";
+
+/// Flags that select the `--llm` engine even without `--llm`: the baseline
+/// gate and command suites only exist there.
+pub const BENCH_GATE_FLAGS: &[&str] = &[
+    "--baseline",
+    "--max-regress",
+    "--max-regression",
+    "--write-baseline",
+    "--suite",
+];
+
+fn run_variant_once(v: &Variant, disable_cx_log: bool, passthru: bool) -> Result<i32, String> {
+    let mut cmd = Command::new(&v.command[0]);
+    cmd.args(&v.command[1..]);
+    if disable_cx_log {
+        cmd.env("CXLOG_ENABLED", "0");
+    }
+    if let Some(b) = &v.backend {
+        cmd.env("CX_LLM_BACKEND", b);
+    }
+    let output = run_command_output_with_timeout(cmd, &format!("bench '{}'", v.command[0]))?;
+    if passthru {
+        let _ = std::io::stdout().write_all(&output.stdout);
+        let _ = std::io::stderr().write_all(&output.stderr);
+    }
+    Ok(output.status.code().unwrap_or(1))
+}
+
+fn mean(values: &[u64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<u64>() as f64 / values.len() as f64)
+}
+
+/// Sample standard deviation (`n - 1`); 0 for a single sample.
+fn stddev(values: &[u64]) -> Option<f64> {
+    let m = mean(values)?;
+    if values.len() < 2 {
+        return Some(0.0);
+    }
+    let var =
+        values.iter().map(|v| (*v as f64 - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(var.sqrt())
+}
+
+fn round1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+/// avg/min/max/stddev/p50/p90/p99 of one metric; `null` with no samples.
+pub(crate) fn distribution_json(values: &[u64]) -> Value {
+    let (Some(avg), Some(sd), Some(p)) = (
+        mean(values),
+        stddev(values),
+        Percentiles::of(values.to_vec()),
+    ) else {
+        return Value::Null;
+    };
+    json!({
+        "avg": round1(avg),
+        "min": values.iter().min(),
+        "max": values.iter().max(),
+        "stddev": round1(sd),
+        "p50": p.p50,
+        "p90": p.p90,
+        "p99": p.p99,
+    })
+}
+
+fn delta_pct(base: Option<f64>, value: Option<f64>) -> Option<f64> {
+    match (base, value) {
+        (Some(b), Some(v)) if b > 0.0 => Some(round1((v - b) / b * 100.0)),
+        _ => None,
+    }
+}
+
+fn variant_json(v: &Variant, stats: &BenchStats) -> Value {
+    json!({
+        "label": v.label,
+        "command": v.command,
+        "backend": v.backend,
+        "runs": stats.durations.len(),
+        "failures": stats.failures,
+        "duration_ms": distribution_json(&stats.durations),
+        "effective_input_tokens": distribution_json(&stats.eff_totals),
+        "output_tokens": distribution_json(&stats.out_totals),
+    })
+}
+
+fn metric(v: &Value, key: &str, stat: &str) -> Option<f64> {
+    v.get(key)?.get(stat)?.as_f64()
+}
+
+/// Deltas of `candidate` against `base` (negative = faster / fewer tokens).
+fn compare_json(base: &Value, candidate: &Value) -> Value {
+    json!({
+        "label": candidate["label"],
+        "against": base["label"],
+        "duration_p50_delta_pct": delta_pct(metric(base, "duration_ms", "p50"), metric(candidate, "duration_ms", "p50")),
+        "duration_avg_delta_pct": delta_pct(metric(base, "duration_ms", "avg"), metric(candidate, "duration_ms", "avg")),
+        "effective_input_tokens_avg_delta_pct": delta_pct(
+            metric(base, "effective_input_tokens", "avg"),
+            metric(candidate, "effective_input_tokens", "avg"),
+        ),
+        "output_tokens_avg_delta_pct": delta_pct(
+            metric(base, "output_tokens", "avg"),
+            metric(candidate, "output_tokens", "avg"),
+        ),
+    })
+}
+
+/// Deltas the `--max-regression` gate checks: p50 latency and average tokens.
+const GATED_DELTAS: &[(&str, &str)] = &[
+    ("duration_p50_delta_pct", "duration_ms.p50"),
+    (
+        "effective_input_tokens_avg_delta_pct",
+        "effective_input_tokens.avg",
+    ),
+    ("output_tokens_avg_delta_pct", "output_tokens.avg"),
+];
+
+/// Gated deltas of one baseline comparison row above `limit` percent.
+fn row_regressions(row: &Value, limit: f64) -> Vec<Value> {
+    GATED_DELTAS
+        .iter()
+        .filter_map(|(key, metric)| {
+            let d = row[*key].as_f64().filter(|d| *d > limit)?;
+            Some(json!({"label": row["label"], "metric": metric, "delta_pct": d}))
+        })
+        .collect()
+}
+
+/// Compare each variant with the same label in a previous `bench --llm --json`
+/// report; returns the comparisons and every gated metric (p50 duration,
+/// average effective input and output tokens) that regressed beyond
+/// `max_regress`.
+fn baseline_json(
+    path: &str,
+    variants: &[Value],
+    max_regress: Option<f64>,
+) -> Result<(Value, Vec<Value>), String> {
+    let raw = fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+    let doc: Value =
+        serde_json::from_str(&raw).map_err(|e| format!("invalid JSON in {path}: {e}"))?;
+    let previous = doc
+        .get("variants")
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("{path}: not a bench --llm report (missing variants)"))?;
+    let mut rows = Vec::new();
+    let mut regressions = Vec::new();
+    for v in variants {
+        let Some(base) = previous.iter().find(|p| p["label"] == v["label"]) else {
+            rows.push(json!({"label": v["label"], "against": null}));
+            continue;
+        };
+        let row = compare_json(base, v);
+        if let Some(limit) = max_regress {
+            regressions.extend(row_regressions(&row, limit));
+        }
+        rows.push(row);
+    }
+    Ok((
+        json!({"file": path, "max_regress_pct": max_regress, "variants": rows}),
+        regressions,
+    ))
+}
+
+fn fmt_dist(v: &Value, key: &str) -> String {
+    match v.get(key).filter(|d| !d.is_null()) {
+        Some(d) => format!(
+            "{}/{}/{}/{} sd={}",
+            d["avg"], d["p50"], d["p90"], d["p99"], d["stddev"]
+        ),
+        None => "n/a".to_string(),
+    }
+}
+
+fn fmt_delta(v: &Value) -> String {
+    v.as_f64()
+        .map_or_else(|| "n/a".to_string(), |d| format!("{d:+.1}%"))
+}
+
+fn print_report(doc: &Value) {
+    println!("== cxrs bench --llm ==");
+    println!(
+        "runs: {} per variant (+{} warm-up, excluded)",
+        doc["runs"], doc["warmup"]
+    );
+    for v in doc["variants"].as_array().into_iter().flatten() {
+        println!();
+        println!("[{}]", v["label"].as_str().unwrap_or_default());
+        println!("  failures: {}", v["failures"]);
+        println!(
+            "  duration_ms avg/p50/p90/p99: {}",
+            fmt_dist(v, "duration_ms")
+        );
+        println!(
+            "  effective_input_tokens avg/p50/p90/p99: {}",
+            fmt_dist(v, "effective_input_tokens")
+        );
+        println!(
+            "  output_tokens avg/p50/p90/p99: {}",
+            fmt_dist(v, "output_tokens")
+        );
+    }
+    let print_rows = |title: String, rows: &Value| {
+        println!();
+        println!("{title}");
+        for r in rows.as_array().into_iter().flatten() {
+            if r["against"].is_null() {
+                println!(
+                    "  {}: no baseline entry",
+                    r["label"].as_str().unwrap_or_default()
+                );
+                continue;
+            }
+            println!(
+                "  {}: duration p50 {}, avg {}; effective tokens {}",
+                r["label"].as_str().unwrap_or_default(),
+                fmt_delta(&r["duration_p50_delta_pct"]),
+                fmt_delta(&r["duration_avg_delta_pct"]),
+                fmt_delta(&r["effective_input_tokens_avg_delta_pct"]),
+            );
+        }
+    };
+    if let Some(rows) = doc.get("comparison").filter(|c| !c.is_null()) {
+        let against = rows[0]["against"].as_str().unwrap_or_default();
+        print_rows(format!("comparison vs {against}:"), rows);
+    }
+    if let Some(b) = doc.get("baseline").filter(|b| !b.is_null()) {
+        print_rows(
+            format!("baseline {}:", b["file"].as_str().unwrap_or_default()),
+            &b["variants"],
+        );
+    }
+    if let Some(regs) = doc["regression_details"]
+        .as_array()
+        .filter(|r| !r.is_empty())
+    {
+        println!();
+        println!(
+            "regressions (> +{}% vs baseline):",
+            doc["baseline"]["max_regress_pct"]
+        );
+        for r in regs {
+            println!(
+                "  {}: {} {}",
+                r["label"].as_str().unwrap_or_default(),
+                r["metric"].as_str().unwrap_or_default(),
+                fmt_delta(&r["delta_pct"])
+            );
+        }
+    }
+    if let Some(path) = doc["baseline_written"].as_str() {
+        println!();
+        println!("baseline written: {path}");
+    }
+}
+
+/// `bench <N> --llm`: interleaved runs per variant after warm-up, with
+/// variance/percentile stats, variant comparison and an optional baseline
+/// check. Exits 1 on command failures or baseline regressions.
+pub fn cmd_bench_llm(args: &[String]) -> i32 {
+    let opts = match parse_bench_llm_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("bench", &e));
+            return print_usage_error("bench", BENCH_LLM_USAGE);
+        }
+    };
+    let cfg = app_config();
+    let disable_cx_log = !cfg.cxbench_log;
+    let passthru = cfg.cxbench_passthru && !opts.json_out;
+    let log_file = resolve_log_file();
+    let mut stats: Vec<BenchStats> = opts
+        .variants
+        .iter()
+        .map(|_| BenchStats::default())
+        .collect();
+
+    // Round-robin so drift (cache warm-up, load) hits every variant alike.
+    for round in 0..opts.warmup + opts.runs {
+        let measured = round >= opts.warmup;
+        for (v, st) in opts.variants.iter().zip(stats.iter_mut()) {
+            let before_offset = log_file
+                .as_ref()
+                .map(|p| if p.exists() { file_len(p) } else { 0 })
+                .unwrap_or(0);
+            let started = Instant::now();
+            let started_epoch = Utc::now().timestamp();
+            let code = match run_variant_once(v, disable_cx_log, passthru) {
+                Ok(c) => c,
+                Err(e) => {
+                    crate::cx_eprintln!("{}", format_error("bench", &e));
+                    return 1;
+                }
+            };
+            if !measured {
+                continue;
+            }
+            st.durations.push(started.elapsed().as_millis() as u64);
+            if code != 0 {
+                st.failures += 1;
+            }
+            maybe_collect_tokens(
+                st,
+                &log_file,
+                before_offset,
+                started_epoch,
+                Utc::now().timestamp(),
+                disable_cx_log,
+            );
+        }
+    }
+
+    let labels: Vec<String> = opts.variants.iter().map(|v| v.command.join(" ")).collect();
+    let records: Vec<BenchRecord<'_>> = opts
+        .variants
+        .iter()
+        .zip(&labels)
+        .zip(&stats)
+        .map(|((v, label), st)| BenchRecord {
+            source: "bench_llm",
+            label,
+            backend: v.backend.as_deref(),
+            model: None,
+            suite: None,
+            schema_pass_rate: None,
+            stats: st,
+        })
+        .collect();
+    record_bench_history(&records);
+
+    let variants: Vec<Value> = opts
+        .variants
+        .iter()
+        .zip(&stats)
+        .map(|(v, st)| variant_json(v, st))
+        .collect();
+    let comparison = (opts.compare_variants && variants.len() > 1).then(|| {
+        variants[1..]
+            .iter()
+            .map(|v| compare_json(&variants[0], v))
+            .collect::<Vec<_>>()
+    });
+    let (baseline, regressions) = match opts.baseline.as_deref() {
+        Some(path) => match baseline_json(path, &variants, opts.max_regress) {
+            Ok((b, r)) => (Some(b), r),
+            Err(e) => {
+                crate::cx_eprintln!("{}", format_error("bench", &e));
+                return 1;
+            }
+        },
+        None => (None, Vec::new()),
+    };
+    let mut regressed: Vec<&str> = Vec::new();
+    for label in regressions.iter().filter_map(|r| r["label"].as_str()) {
+        if !regressed.contains(&label) {
+            regressed.push(label);
+        }
+    }
+    let mut doc = json!({
+        "contract_version": BENCH_LLM_JSON_CONTRACT_VERSION,
+        "runs": opts.runs,
+        "warmup": opts.warmup,
+        "cxbench_log": !disable_cx_log,
+        "variants": variants,
+        "comparison": comparison,
+        "baseline": baseline,
+        "regressions": regressed,
+        "regression_details": regressions,
+        "baseline_written": null,
+    });
+    if let Some(path) = opts.write_baseline.as_deref() {
+        // The report itself is the next baseline: `--baseline` reads `variants`.
+        if let Err(e) = write_json_atomic(Path::new(path), &doc) {
+            crate::cx_eprintln!("{}", format_error("bench", &e));
+            return 1;
+        }
+        doc["baseline_written"] = json!(path);
+    }
+    if opts.json_out {
+        match serde_json::to_string_pretty(&doc) {
+            Ok(s) => println!("{s}"),
+            Err(e) => {
+                crate::cx_eprintln!("{}", format_error("bench", &format!("render failed: {e}")));
+                return 1;
+            }
+        }
+    } else {
+        print_report(&doc);
+    }
+    let failed = stats.iter().any(|s| s.failures > 0);
+    if failed || !regressed.is_empty() {
+        1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn gate_flags_every_regressed_metric() {
+        let row = json!({
+            "label": "next",
+            "duration_p50_delta_pct": 35.0,
+            "duration_avg_delta_pct": 90.0,
+            "effective_input_tokens_avg_delta_pct": 12.5,
+            "output_tokens_avg_delta_pct": null,
+        });
+        let regs = row_regressions(&row, 20.0);
+        assert_eq!(regs.len(), 1);
+        assert_eq!(regs[0]["metric"], "duration_ms.p50");
+        assert_eq!(row_regressions(&row, 10.0).len(), 2);
+
+        let o = parse_bench_llm_args(&args(&["3", "--max-regression", "20%", "--", "a"])).unwrap();
+        assert_eq!(o.max_regress, Some(20.0));
+        assert!(!o.compare_variants);
+        assert!(parse_bench_llm_args(&args(&["3", "--json"])).is_err());
+    }
+
+    #[test]
+    fn distribution_reports_spread_and_deltas() {
+        let d = distribution_json(&[100, 200, 300, 400]);
+        assert_eq!(d["avg"], 250.0);
+        assert_eq!(d["stddev"], 129.1);
+        assert_eq!(d["p50"], 200);
+        assert_eq!(d["p99"], 400);
+        assert!(distribution_json(&[]).is_null());
+        assert_eq!(delta_pct(Some(200.0), Some(250.0)), Some(25.0));
+        assert_eq!(delta_pct(Some(0.0), Some(1.0)), None);
+    }
+}
@@ -0,0 +1,245 @@
+use serde_json::Value;
+use std::fs;
+
+/// One command/backend combination being measured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    pub label: String,
+    pub command: Vec<String>,
+    pub backend: Option<String>,
+}
+
+/// Parsed `bench --llm` arguments.
+#[derive(Debug, PartialEq)]
+pub struct BenchLlmOpts {
+    pub runs: usize,
+    pub warmup: usize,
+    pub variants: Vec<Variant>,
+    pub baseline: Option<String>,
+    pub max_regress: Option<f64>,
+    pub write_baseline: Option<String>,
+    pub compare_variants: bool,
+    pub json_out: bool,
+}
+
+fn flag_value<'a>(head: &'a [String], i: usize, flag: &str) -> Result<&'a str, String> {
+    head.get(i + 1)
+        .map(String::as_str)
+        .filter(|v| !v.starts_with("--"))
+        .ok_or_else(|| format!("{flag} requires a value"))
+}
+
+/// Commands of a `--suite` file: `{"commands": [{"label": "...", "command":
+/// ["cxrs", "cxo", "git", "status"]}, ...]}`; the label defaults to the command.
+fn load_suite(path: &str) -> Result<Vec<Variant>, String> {
+    let raw = fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+    let doc: Value =
+        serde_json::from_str(&raw).map_err(|e| format!("invalid JSON in {path}: {e}"))?;
+    let entries = doc
+        .get("commands")
+        .and_then(Value::as_array)
+        .filter(|c| !c.is_empty())
+        .ok_or_else(|| format!("{path}: expected a non-empty \"commands\" array"))?;
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            let command: Vec<String> = e
+                .get("command")
+                .and_then(Value::as_array)
+                .map(|a| {
+                    a.iter()
+                        .filter_map(Value::as_str)
+                        .map(String::from)
+                        .collect()
+                })
+                .filter(|c: &Vec<String>| !c.is_empty())
+                .ok_or_else(|| format!("{path}: commands[{i}] needs a non-empty command array"))?;
+            let label = e
+                .get("label")
+                .and_then(Value::as_str)
+                .map_or_else(|| command.join(" "), String::from);
+            Ok(Variant {
+                label,
+                command,
+                backend: None,
+            })
+        })
+        .collect()
+}
+
+/// `args` starts after `bench`; the head before the first `--` holds flags, the
+/// tail one command (or two with `--compare`, split on the next `--`). With
+/// `--suite` the commands come from the file and there is no tail.
+pub fn parse_bench_llm_args(args: &[String]) -> Result<BenchLlmOpts, String> {
+    let split = args.iter().position(|a| a == "--").unwrap_or(args.len());
+    let (head, tail) = (&args[..split], args.get(split + 1..).unwrap_or_default());
+    let mut runs: Option<usize> = None;
+    let mut warmup = 1usize;
+    let mut backends: Vec<String> = Vec::new();
+    let mut compare = false;
+    let mut baseline: Option<String> = None;
+    let mut max_regress: Option<f64> = None;
+    let mut write_baseline: Option<String> = None;
+    let mut suite: Option<String> = None;
+    let mut json_out = false;
+    let mut i = 0usize;
+    while i < head.len() {
+        match head[i].as_str() {
+            "--llm" => {}
+            "--json" => json_out = true,
+            "--compare" => compare = true,
+            "--warmup" => {
+                let v = flag_value(head, i, "--warmup")?;
+                warmup = v
+                    .parse()
+                    .map_err(|_| format!("--warmup: invalid count '{v}'"))?;
+                i += 1;
+            }
+            "--backends" => {
+                backends = flag_value(head, i, "--backends")?
+                    .split(',')
+                    .map(|b| b.trim().to_lowercase())
+                    .filter(|b| !b.is_empty())
+                    .collect();
+                if let Some(bad) = backends
+                    .iter()
+                    .find(|b| !matches!(b.as_str(), "codex" | "ollama"))
+                {
+                    return Err(format!("--backends: unknown backend '{bad}'"));
+                }
+                i += 1;
+            }
+            "--baseline" => {
+                baseline = Some(flag_value(head, i, "--baseline")?.to_string());
+                i += 1;
+            }
+            flag @ ("--max-regress" | "--max-regression") => {
+                let v = flag_value(head, i, flag)?;
+                let pct = v
+                    .trim_end_matches('%')
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|p| *p >= 0.0)
+                    .ok_or_else(|| format!("{flag}: invalid percent '{v}'"))?;
+                max_regress = Some(pct);
+                i += 1;
+            }
+            "--write-baseline" => {
+                write_baseline = Some(flag_value(head, i, "--write-baseline")?.to_string());
+                i += 1;
+            }
+            "--suite" => {
+                suite = Some(flag_value(head, i, "--suite")?.to_string());
+                i += 1;
+            }
+            v if runs.is_none() && v.parse::<usize>().is_ok_and(|n| n > 0) => {
+                runs = v.parse().ok();
+            }
+            other => return Err(format!("unknown flag '{other}'")),
+        }
+        i += 1;
+    }
+    let runs = runs.ok_or_else(|| "runs must be > 0".to_string())?;
+    if compare && !backends.is_empty() {
+        return Err("use either --compare or --backends, not both".to_string());
+    }
+    if suite.is_some() && (compare || !backends.is_empty() || split < args.len()) {
+        return Err("--suite takes its commands from the file; drop -- <command...>, --compare and --backends".to_string());
+    }
+    let compare_variants = compare || !backends.is_empty();
+    let variants = if let Some(path) = &suite {
+        load_suite(path)?
+    } else if compare {
+        let mid = tail.iter().position(|a| a == "--").ok_or_else(|| {
+            "--compare needs two commands: -- <cmd A...> -- <cmd B...>".to_string()
+        })?;
+        let (a, b) = (&tail[..mid], &tail[mid + 1..]);
+        if a.is_empty() || b.is_empty() {
+            return Err("--compare needs two non-empty commands".to_string());
+        }
+        [a, b]
+            .into_iter()
+            .map(|c| Variant {
+                label: c.join(" "),
+                command: c.to_vec(),
+                backend: None,
+            })
+            .collect()
+    } else if split == args.len() {
+        return Err("missing -- <command...>".to_string());
+    } else if tail.is_empty() {
+        return Err("missing command after --".to_string());
+    } else if backends.is_empty() {
+        vec![Variant {
+            label: tail.join(" "),
+            command: tail.to_vec(),
+            backend: None,
+        }]
+    } else {
+        backends
+            .into_iter()
+            .map(|b| Variant {
+                label: format!("backend={b}"),
+                command: tail.to_vec(),
+                backend: Some(b),
+            })
+            .collect()
+    };
+    Ok(BenchLlmOpts {
+        runs,
+        warmup,
+        variants,
+        baseline,
+        max_regress,
+        write_baseline,
+        compare_variants,
+        json_out,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_compare_and_backend_variants() {
+        let o = parse_bench_llm_args(&args(&[
+            "3",
+            "--llm",
+            "--warmup",
+            "0",
+            "--compare",
+            "--",
+            "a",
+            "x",
+            "--",
+            "b",
+        ]))
+        .unwrap();
+        assert_eq!((o.runs, o.warmup), (3, 0));
+        assert_eq!(o.variants[0].command, vec!["a", "x"]);
+        assert_eq!(o.variants[1].label, "b");
+
+        let o = parse_bench_llm_args(&args(&[
+            "2",
+            "--llm",
+            "--backends",
+            "codex,ollama",
+            "--",
+            "cxo",
+            "hi",
+        ]))
+        .unwrap();
+        assert_eq!(o.variants[1].backend.as_deref(), Some("ollama"));
+        assert_eq!(o.variants[1].label, "backend=ollama");
+        assert_eq!(o.warmup, 1);
+
+        assert!(parse_bench_llm_args(&args(&["2", "--compare", "--", "a"])).is_err());
+        assert!(parse_bench_llm_args(&args(&["0", "--", "a"])).is_err());
+    }
+}
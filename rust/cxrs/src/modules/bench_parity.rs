@@ -6,10 +6,12 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::Instant;
 
+use crate::analytics::print_json_value;
+use crate::bench_history::{BenchRecord, record_bench_history};
 use crate::bench_parity_mocks::{setup_parity_mocks, with_parity_env};
 use crate::bench_parity_support::{
-    BenchStats, ParityRow, maybe_collect_tokens, print_bench_summary, print_parity_table,
-    run_parity_path, setup_temp_repo,
+    BenchStats, ParityRow, bench_summary_json, maybe_collect_tokens, print_bench_summary,
+    print_parity_table, run_parity_path, setup_temp_repo,
 };
 use crate::config::app_config;
 use crate::logs::file_len;
@@ -57,13 +59,14 @@ fn validate_bench_args(app_name: &str, runs: usize, command: &[String]) -> Resul
     Ok(())
 }
 
-pub fn cmd_bench(app_name: &str, runs: usize, command: &[String]) -> i32 {
+pub fn cmd_bench(app_name: &str, runs: usize, command: &[String], json_out: bool) -> i32 {
     if let Err(code) = validate_bench_args(app_name, runs, command) {
         return code;
     }
     let cfg = app_config();
     let disable_cx_log = !cfg.cxbench_log;
-    let passthru = cfg.cxbench_passthru;
+    // Passthru output would interleave with the JSON document on stdout.
+    let passthru = cfg.cxbench_passthru && !json_out;
     let log_file = resolve_log_file();
     let mut stats = BenchStats {
         durations: Vec::with_capacity(runs),
@@ -98,8 +101,24 @@ pub fn cmd_bench(app_name: &str, runs: usize, command: &[String]) -> i32 {
             disable_cx_log,
         );
     }
+    record_bench_history(&[BenchRecord {
+        source: "bench",
+        label: &command.join(" "),
+        backend: None,
+        model: None,
+        suite: None,
+        schema_pass_rate: None,
+        stats: &stats,
+    }]);
 
-    print_bench_summary(runs, command, disable_cx_log, passthru, &stats);
+    if json_out {
+        let doc = bench_summary_json(runs, command, disable_cx_log, passthru, &stats);
+        if print_json_value("cxrs bench", &doc) != 0 {
+            return 1;
+        }
+    } else {
+        print_bench_summary(runs, command, disable_cx_log, passthru, &stats);
+    }
     if stats.failures > 0 { 1 } else { 0 }
 }
 
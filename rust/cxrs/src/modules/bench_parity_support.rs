@@ -1,10 +1,11 @@
-use serde_json::Value;
+use serde_json::{Value, json};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::analytics::parse_ts_epoch;
+use crate::contract_versions::BENCH_JSON_CONTRACT_VERSION;
 use crate::diagnostics::{has_required_log_fields, last_appended_json_value};
 use crate::logs::load_runs_appended;
 use crate::process::run_command_status_with_timeout;
@@ -118,6 +119,37 @@ fn avg_opt(values: &[u64]) -> Option<u64> {
     }
 }
 
+fn duration_summary(stats: &BenchStats) -> (u64, u64, u64) {
+    let min = stats.durations.iter().min().copied().unwrap_or(0);
+    let max = stats.durations.iter().max().copied().unwrap_or(0);
+    (avg_opt(&stats.durations).unwrap_or(0), min, max)
+}
+
+pub fn bench_summary_json(
+    runs: usize,
+    command: &[String],
+    disable_cx_log: bool,
+    passthru: bool,
+    stats: &BenchStats,
+) -> Value {
+    let (avg, min, max) = duration_summary(stats);
+    json!({
+        "contract_version": BENCH_JSON_CONTRACT_VERSION,
+        "runs": runs,
+        "command": command,
+        "duration_ms": {"avg": avg, "min": min, "max": max},
+        "failures": stats.failures,
+        "avg_effective_input_tokens": avg_opt(&stats.eff_totals),
+        "avg_output_tokens": avg_opt(&stats.out_totals),
+        "cxbench_log": !disable_cx_log,
+        "cxbench_passthru": passthru,
+        "correlation": (!disable_cx_log).then(|| json!({
+            "prompt_hash_matches": stats.prompt_hash_matched,
+            "appended_rows": stats.appended_row_total
+        })),
+    })
+}
+
 pub fn print_bench_summary(
     runs: usize,
     command: &[String],
@@ -125,14 +157,7 @@ pub fn print_bench_summary(
     passthru: bool,
     stats: &BenchStats,
 ) {
-    let min = stats.durations.iter().min().copied().unwrap_or(0);
-    let max = stats.durations.iter().max().copied().unwrap_or(0);
-    let sum: u64 = stats.durations.iter().sum();
-    let avg = if stats.durations.is_empty() {
-        0
-    } else {
-        sum / (stats.durations.len() as u64)
-    };
+    let (avg, min, max) = duration_summary(stats);
     println!("== cxrs bench ==");
     println!("runs: {runs}");
     println!("command: {}", command.join(" "));
@@ -1,11 +1,10 @@
-use std::process::Command;
-
 use serde_json::{Value, json};
 
 use crate::config::app_config;
 use crate::contract_versions::BROKER_BENCHMARK_JSON_CONTRACT_VERSION;
 use crate::logs::load_values;
 use crate::paths::resolve_log_file;
+use crate::platform::bin_in_path;
 use crate::runtime::{llm_backend, llm_model};
 use crate::state::set_state_path;
 
@@ -55,11 +54,7 @@ fn backend_available(name: &str) -> bool {
     if disabled {
         return false;
     }
-    Command::new("bash")
-        .args(["-lc", &format!("command -v {name} >/dev/null 2>&1")])
-        .status()
-        .map(|s| s.success())
-        .unwrap_or(false)
+    bin_in_path(name)
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,353 @@
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+use crate::alert_sinks::alert_policy;
+use crate::capture::budget_config_for_tool;
+use crate::config::DEFAULT_OPTIMIZE_WINDOW;
+use crate::contract_versions::BUDGET_SUGGEST_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, format_error, print_runtime_error, print_usage_error};
+use crate::logs::load_runs;
+use crate::paths::resolve_log_file;
+use crate::percentiles::percentile;
+use crate::state::set_state_path;
+use crate::types::RunEntry;
+
+const USAGE: &str = "budget suggest [N] [--coverage PCT] [--apply] [--json]";
+const DEFAULT_COVERAGE: u64 = 95;
+/// Tools with fewer captured runs than this get no suggestion.
+const MIN_SAMPLES: usize = 3;
+/// Fallback when the log has no token estimates for a tool.
+const DEFAULT_CHARS_PER_TOKEN: f64 = 4.0;
+const CHARS_STEP: u64 = 500;
+const LINES_STEP: u64 = 10;
+
+#[derive(Debug, PartialEq)]
+struct SuggestOpts {
+    n: usize,
+    coverage: u64,
+    apply: bool,
+    json: bool,
+}
+
+fn parse_suggest_args(args: &[String]) -> Result<SuggestOpts, String> {
+    let mut opts = SuggestOpts {
+        n: DEFAULT_OPTIMIZE_WINDOW,
+        coverage: DEFAULT_COVERAGE,
+        apply: false,
+        json: false,
+    };
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--apply" => opts.apply = true,
+            "--json" => opts.json = true,
+            "--coverage" => {
+                let raw = it.next().ok_or("--coverage requires a value")?;
+                opts.coverage = raw
+                    .trim_end_matches('%')
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|v| (50..=100).contains(v))
+                    .ok_or_else(|| format!("invalid --coverage '{raw}' (expected 50-100)"))?;
+            }
+            other => {
+                opts.n = other
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|v| *v > 0)
+                    .ok_or_else(|| format!("unknown argument '{other}'"))?;
+            }
+        }
+    }
+    Ok(opts)
+}
+
+#[derive(Debug, Default)]
+struct ToolSamples {
+    chars: Vec<u64>,
+    lines: Vec<u64>,
+    tokens: u64,
+    token_chars: u64,
+    clipped: usize,
+}
+
+/// Pre-clip output sizes per tool; runs without capture stats are skipped.
+fn collect_samples(runs: &[RunEntry]) -> BTreeMap<String, ToolSamples> {
+    let mut out: BTreeMap<String, ToolSamples> = BTreeMap::new();
+    for r in runs {
+        let (Some(tool), Some(chars)) = (r.tool.as_ref(), r.system_output_len_raw) else {
+            continue;
+        };
+        let s = out.entry(tool.clone()).or_default();
+        s.chars.push(chars);
+        s.lines.push(r.system_output_lines_raw.unwrap_or(0));
+        if let Some(tokens) = r.system_output_tokens_raw.filter(|t| *t > 0) {
+            s.tokens += tokens;
+            s.token_chars += chars;
+        }
+        s.clipped += usize::from(r.clipped == Some(true));
+    }
+    out
+}
+
+fn round_up(v: u64, step: u64) -> u64 {
+    v.div_ceil(step).max(1) * step
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Suggestion {
+    tool: String,
+    runs: usize,
+    clipped: usize,
+    current: (usize, usize),
+    /// `(budget_chars, budget_lines)`, or `None` with too few samples.
+    suggested: Option<(u64, u64)>,
+    /// The char budget was lowered to keep captured output under `max_eff_in`.
+    token_capped: bool,
+    /// Share of sampled runs that fit the suggestion without clipping.
+    covered: Option<f64>,
+}
+
+fn suggest_for(
+    tool: &str,
+    s: &ToolSamples,
+    coverage: u64,
+    max_eff_in: u64,
+    current: (usize, usize),
+) -> Suggestion {
+    let mut out = Suggestion {
+        tool: tool.to_string(),
+        runs: s.chars.len(),
+        clipped: s.clipped,
+        current,
+        suggested: None,
+        token_capped: false,
+        covered: None,
+    };
+    if s.chars.len() < MIN_SAMPLES {
+        return out;
+    }
+    let mut chars = round_up(
+        percentile(s.chars.clone(), coverage).unwrap_or(0),
+        CHARS_STEP,
+    );
+    let lines = round_up(
+        percentile(s.lines.clone(), coverage).unwrap_or(0),
+        LINES_STEP,
+    );
+    let chars_per_token = if s.tokens > 0 {
+        s.token_chars as f64 / s.tokens as f64
+    } else {
+        DEFAULT_CHARS_PER_TOKEN
+    };
+    let cap =
+        ((max_eff_in as f64 * chars_per_token) as u64 / CHARS_STEP * CHARS_STEP).max(CHARS_STEP);
+    if chars > cap {
+        chars = cap;
+        out.token_capped = true;
+    }
+    let fits = s
+        .chars
+        .iter()
+        .zip(&s.lines)
+        .filter(|(c, l)| **c <= chars && **l <= lines)
+        .count();
+    out.suggested = Some((chars, lines));
+    out.covered = Some(fits as f64 / s.chars.len() as f64);
+    out
+}
+
+fn build_suggestions(runs: &[RunEntry], coverage: u64) -> Vec<Suggestion> {
+    let policy = alert_policy();
+    collect_samples(runs)
+        .iter()
+        .map(|(tool, s)| {
+            let budget = budget_config_for_tool(tool);
+            suggest_for(
+                tool,
+                s,
+                coverage,
+                policy.for_tool(tool).max_eff_in,
+                (budget.budget_chars, budget.budget_lines),
+            )
+        })
+        .collect()
+}
+
+fn apply_suggestions(suggestions: &[Suggestion]) -> Result<usize, String> {
+    let mut applied = 0;
+    for s in suggestions {
+        let Some((chars, lines)) = s.suggested else {
+            continue;
+        };
+        let base = format!("budget_overrides.{}", s.tool);
+        set_state_path(&format!("{base}.budget_chars"), json!(chars))?;
+        set_state_path(&format!("{base}.budget_lines"), json!(lines))?;
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+fn pct(v: Option<f64>) -> String {
+    v.map_or_else(
+        || "n/a".to_string(),
+        |r| format!("{}%", (r * 100.0).round() as i64),
+    )
+}
+
+fn print_text(opts: &SuggestOpts, suggestions: &[Suggestion]) {
+    println!(
+        "== cxrs budget suggest (last {} runs, coverage {}%) ==",
+        opts.n, opts.coverage
+    );
+    if suggestions.is_empty() {
+        println!("No runs with capture stats in this window.");
+        return;
+    }
+    println!(
+        "{:<18} {:>5} {:>8} {:>16} {:>12} {:>8}  note",
+        "tool", "runs", "clipped", "chars now->new", "lines", "covered"
+    );
+    for s in suggestions {
+        let (chars, lines, note) = match s.suggested {
+            Some((c, l)) => (
+                format!("{}->{c}", s.current.0),
+                format!("{}->{l}", s.current.1),
+                if s.token_capped {
+                    "capped by max_eff_in"
+                } else {
+                    ""
+                },
+            ),
+            None => (
+                s.current.0.to_string(),
+                s.current.1.to_string(),
+                "insufficient data",
+            ),
+        };
+        println!(
+            "{:<18} {:>5} {:>8} {:>16} {:>12} {:>8}  {note}",
+            s.tool,
+            s.runs,
+            s.clipped,
+            chars,
+            lines,
+            pct(s.covered)
+        );
+    }
+}
+
+fn suggestions_json(opts: &SuggestOpts, suggestions: &[Suggestion], applied: usize) -> Value {
+    let tools: Vec<Value> = suggestions
+        .iter()
+        .map(|s| {
+            json!({
+                "tool": s.tool,
+                "runs": s.runs,
+                "clipped_runs": s.clipped,
+                "current": {"budget_chars": s.current.0, "budget_lines": s.current.1},
+                "suggested": s.suggested.map(|(c, l)| json!({"budget_chars": c, "budget_lines": l})),
+                "token_capped": s.token_capped,
+                "covered": s.covered,
+            })
+        })
+        .collect();
+    json!({
+        "contract_version": BUDGET_SUGGEST_JSON_CONTRACT_VERSION,
+        "window": opts.n,
+        "coverage": opts.coverage,
+        "tools": tools,
+        "applied": opts.apply.then_some(applied),
+    })
+}
+
+/// `budget suggest`: per-tool `budget_chars` / `budget_lines` that would have
+/// fit `--coverage`% of recent captures unclipped, capped so the captured text
+/// stays under the tool's `max_eff_in` alert threshold. `--apply` stores them
+/// under state `budget_overrides.<tool>`, which capture then uses.
+pub fn cmd_budget_suggest(args: &[String]) -> i32 {
+    let opts = match parse_suggest_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("budget suggest", &e));
+            return print_usage_error("budget suggest", USAGE);
+        }
+    };
+    let Some(log_file) = resolve_log_file() else {
+        return print_runtime_error("budget suggest", "unable to resolve log file");
+    };
+    let runs = if log_file.exists() {
+        match load_runs(&log_file, opts.n) {
+            Ok(v) => v,
+            Err(e) => return print_runtime_error("budget suggest", &e),
+        }
+    } else {
+        Vec::new()
+    };
+    let suggestions = build_suggestions(&runs, opts.coverage);
+    let applied = if opts.apply {
+        match apply_suggestions(&suggestions) {
+            Ok(n) => n,
+            Err(e) => return print_runtime_error("budget suggest", &e),
+        }
+    } else {
+        0
+    };
+    if opts.json {
+        match serde_json::to_string_pretty(&suggestions_json(&opts, &suggestions, applied)) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return print_runtime_error("budget suggest", &e.to_string()),
+        }
+        return EXIT_OK;
+    }
+    print_text(&opts, &suggestions);
+    if opts.apply {
+        println!("applied: {applied} tool(s) -> state budget_overrides");
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(chars: &[u64], lines: u64) -> ToolSamples {
+        ToolSamples {
+            chars: chars.to_vec(),
+            lines: vec![lines; chars.len()],
+            tokens: chars.iter().sum::<u64>() / 4,
+            token_chars: chars.iter().sum(),
+            clipped: 0,
+        }
+    }
+
+    #[test]
+    fn suggestion_covers_percentile_and_respects_token_cap() {
+        let mut chars: Vec<u64> = (1..=19).map(|i| i * 1000).collect();
+        chars.push(90_000);
+        let s = samples(&chars, 120);
+        let got = suggest_for("cxo", &s, 95, 100_000, (12_000, 300));
+        assert_eq!(got.suggested, Some((19_000, 120)));
+        assert_eq!(got.covered, Some(0.95));
+        assert!(!got.token_capped);
+
+        let capped = suggest_for("cxo", &s, 95, 2_000, (12_000, 300));
+        assert_eq!(capped.suggested, Some((8_000, 120)));
+        assert!(capped.token_capped);
+
+        let few = suggest_for("cxo", &samples(&[10], 1), 95, 8_000, (12_000, 300));
+        assert_eq!(few.suggested, None);
+    }
+
+    #[test]
+    fn args_accept_window_coverage_and_flags() {
+        let args: Vec<String> = ["100", "--coverage", "90%", "--apply"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let opts = parse_suggest_args(&args).expect("opts");
+        assert_eq!((opts.n, opts.coverage, opts.apply), (100, 90, true));
+        assert!(parse_suggest_args(&["--coverage".to_string(), "20".to_string()]).is_err());
+        assert!(parse_suggest_args(&["--bogus".to_string()]).is_err());
+    }
+}
@@ -1,5 +1,17 @@
 #[path = "capture_budget.rs"]
 mod capture_budget;
+#[path = "capture_cargo.rs"]
+mod capture_cargo;
+#[path = "capture_diff.rs"]
+mod capture_diff;
+#[path = "capture_mapreduce.rs"]
+mod capture_mapreduce;
+#[path = "capture_normalize.rs"]
+mod capture_normalize;
+#[path = "capture_pty.rs"]
+mod capture_pty;
+#[path = "capture_redact.rs"]
+mod capture_redact;
 #[path = "capture_reduce.rs"]
 mod capture_reduce;
 #[path = "capture_system.rs"]
@@ -7,7 +19,10 @@ mod capture_system;
 
 #[allow(unused_imports)]
 pub use capture_budget::{
-    BudgetConfig, budget_config_from_env, choose_clip_mode, chunk_text_by_budget,
-    clip_text_with_config,
+    BudgetConfig, budget_config_for_tool, budget_config_from_env, choose_clip_mode,
+    chunk_text_by_budget, clip_text_with_config, tool_budget_override,
 };
+pub use capture_diff::add_diff_focus;
+pub use capture_redact::{Redaction, load_redactor};
+pub use capture_reduce::{REDUCERS, reducer_override_env};
 pub use capture_system::run_system_command_capture;
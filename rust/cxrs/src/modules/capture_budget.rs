@@ -1,12 +1,23 @@
-use crate::config::app_config;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::config::{DEFAULT_ERROR_REGEX, app_config};
+use crate::ollama_api::model_prompt_budget_tokens;
+use crate::state::{read_state_value, value_at_path};
+use crate::token_estimate::{TokenizerKind, clip_to_token_budget, estimate_tokens};
 use crate::types::CaptureStats;
 
 #[derive(Debug, Clone)]
 pub struct BudgetConfig {
     pub budget_chars: usize,
     pub budget_lines: usize,
+    /// `None` disables token-driven clipping.
+    pub budget_tokens: Option<usize>,
+    pub tokenizer: TokenizerKind,
     pub clip_mode: String,
     pub clip_footer: bool,
+    /// First line matching this anchors `error-window` clipping.
+    pub error_regex: String,
 }
 
 pub fn budget_config_from_env() -> BudgetConfig {
@@ -14,15 +25,46 @@ pub fn budget_config_from_env() -> BudgetConfig {
     BudgetConfig {
         budget_chars: cfg.budget_chars,
         budget_lines: cfg.budget_lines,
+        // Unset: size from the active Ollama model's context window, if known.
+        budget_tokens: Some(cfg.budget_tokens)
+            .filter(|v| *v > 0)
+            .or_else(model_prompt_budget_tokens),
+        tokenizer: TokenizerKind::for_backend(&cfg.llm_backend),
         clip_mode: cfg.clip_mode.clone(),
         clip_footer: cfg.clip_footer,
+        error_regex: cfg.error_regex.clone(),
     }
 }
 
+/// Per-tool `budget_chars` / `budget_lines` from state
+/// `budget_overrides.<tool>` (written by `budget suggest --apply`).
+/// Missing or non-positive values fall back to the global budget.
+pub fn tool_budget_override(tool: &str) -> (Option<usize>, Option<usize>) {
+    let Some(state) = read_state_value() else {
+        return (None, None);
+    };
+    let field = |key: &str| {
+        value_at_path(&state, &format!("budget_overrides.{tool}.{key}"))
+            .and_then(Value::as_u64)
+            .filter(|v| *v > 0)
+            .map(|v| v as usize)
+    };
+    (field("budget_chars"), field("budget_lines"))
+}
+
+pub fn budget_config_for_tool(tool: &str) -> BudgetConfig {
+    let mut cfg = budget_config_from_env();
+    let (chars, lines) = tool_budget_override(tool);
+    cfg.budget_chars = chars.unwrap_or(cfg.budget_chars);
+    cfg.budget_lines = lines.unwrap_or(cfg.budget_lines);
+    cfg
+}
+
 pub fn choose_clip_mode(input: &str, configured_mode: &str) -> String {
     match configured_mode {
         "head" => "head".to_string(),
         "tail" => "tail".to_string(),
+        "error-window" => "error-window".to_string(),
         _ => {
             let lower = input.to_lowercase();
             if lower.contains("error") || lower.contains("fail") || lower.contains("warning") {
@@ -46,12 +88,50 @@ fn last_n_chars(s: &str, n: usize) -> String {
     s.chars().skip(total - n).collect()
 }
 
+/// Index of the first line matching `pattern` (the default pattern when it
+/// does not compile; `ci validate` reports that).
+fn first_error_line(lines: &[&str], pattern: &str) -> Option<usize> {
+    let re = Regex::new(pattern)
+        .or_else(|_| Regex::new(DEFAULT_ERROR_REGEX))
+        .ok()?;
+    lines.iter().position(|l| re.is_match(l))
+}
+
+/// Keep `budget_lines` lines starting a quarter-window before line `at`, then
+/// `budget_chars` chars starting a quarter-budget before that line.
+fn error_window(lines: &[&str], at: usize, cfg: &BudgetConfig) -> String {
+    let end = (at.saturating_sub(cfg.budget_lines / 4) + cfg.budget_lines).min(lines.len());
+    let start = end.saturating_sub(cfg.budget_lines);
+    let window = lines[start..end].join("\n");
+    let total = window.chars().count();
+    if total <= cfg.budget_chars {
+        return window;
+    }
+    let anchor: usize = lines[start..at].iter().map(|l| l.chars().count() + 1).sum();
+    let from = anchor
+        .saturating_sub(cfg.budget_chars / 4)
+        .min(total - cfg.budget_chars);
+    window.chars().skip(from).take(cfg.budget_chars).collect()
+}
+
 pub fn clip_text_with_config(input: &str, cfg: &BudgetConfig) -> (String, CaptureStats) {
     let original_chars = input.chars().count();
     let original_lines = input.lines().count();
-    let mode_used = choose_clip_mode(input, &cfg.clip_mode);
+    let mut mode_used = choose_clip_mode(input, &cfg.clip_mode);
     let lines: Vec<&str> = input.lines().collect();
-    let line_limited = if lines.len() <= cfg.budget_lines {
+    let fits = lines.len() <= cfg.budget_lines && original_chars <= cfg.budget_chars;
+    let anchor = if mode_used == "error-window" && !fits {
+        first_error_line(&lines, &cfg.error_regex)
+    } else {
+        None
+    };
+    // Without an error line to anchor on, the end of the output is the next best guess.
+    if mode_used == "error-window" && anchor.is_none() && !fits {
+        mode_used = "tail".to_string();
+    }
+    let line_limited = if let Some(at) = anchor {
+        error_window(&lines, at, cfg)
+    } else if lines.len() <= cfg.budget_lines {
         input.to_string()
     } else if mode_used == "tail" {
         lines[lines.len().saturating_sub(cfg.budget_lines)..].join("\n")
@@ -65,8 +145,15 @@ pub fn clip_text_with_config(input: &str, cfg: &BudgetConfig) -> (String, Captur
     } else {
         first_n_chars(&line_limited, cfg.budget_chars)
     };
+    let char_limited = match cfg.budget_tokens {
+        Some(budget) => {
+            clip_to_token_budget(&char_limited, budget, cfg.tokenizer, mode_used == "tail")
+        }
+        None => char_limited,
+    };
     let kept_chars = char_limited.chars().count();
     let kept_lines = char_limited.lines().count();
+    let kept_tokens = estimate_tokens(&char_limited, cfg.tokenizer);
     let clipped = kept_chars < original_chars || kept_lines < original_lines;
     let final_text = if clipped && cfg.clip_footer {
         format!(
@@ -90,8 +177,26 @@ pub fn clip_text_with_config(input: &str, cfg: &BudgetConfig) -> (String, Captur
             budget_lines: Some(cfg.budget_lines as u64),
             clip_mode: Some(mode_used),
             clip_footer: Some(cfg.clip_footer),
+            system_output_tokens_raw: Some(estimate_tokens(input, cfg.tokenizer) as u64),
+            system_output_tokens_clipped: Some(kept_tokens as u64),
+            budget_tokens: cfg.budget_tokens.map(|v| v as u64),
             rtk_used: None,
             capture_provider: None,
+            reducer: None,
+            chunk_strategy: None,
+            chunk_count: None,
+            redactions_applied: None,
+            stdout_len_raw: None,
+            stdout_len_clipped: None,
+            stdout_lines_raw: None,
+            stdout_lines_clipped: None,
+            stderr_len_raw: None,
+            stderr_len_clipped: None,
+            stderr_lines_raw: None,
+            stderr_lines_clipped: None,
+            ansi_sequences_stripped: None,
+            binary_output: None,
+            chunk_calls: Vec::new(),
         },
     )
 }
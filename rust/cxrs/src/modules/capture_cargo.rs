@@ -0,0 +1,250 @@
+use std::collections::BTreeSet;
+
+/// Lines kept per compiler diagnostic (`error[E0308]: ...` plus its snippet).
+const DIAGNOSTIC_LINE_LIMIT: usize = 40;
+/// Lines kept from each panic message / backtrace.
+const PANIC_HEAD_LINES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    None,
+    Diagnostic(usize),
+    Warning,
+    Panic(usize),
+    FailureList,
+}
+
+fn error_code(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("error[")?;
+    rest.split_once(']').map(|(code, _)| code)
+}
+
+fn is_top_level(line: &str) -> bool {
+    !line.is_empty() && !line.starts_with(char::is_whitespace)
+}
+
+/// Snippet lines such as `12 |     let x = 1;` are flush-left but belong to the diagnostic.
+fn is_snippet_line(line: &str) -> bool {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && line[digits..].trim_start().starts_with('|')
+}
+
+/// Keep what matters from `cargo build`/`cargo test` output.
+///
+/// Compiler errors keep their full snippet, failing tests and panic heads are
+/// kept, progress lines and warning bodies are dropped, and a one-line tally of
+/// error codes, failed tests and omitted warnings is appended.
+pub fn reduce_cargo_output(input: &str) -> String {
+    let mut out: Vec<&str> = Vec::new();
+    let mut block = Block::None;
+    let mut codes: BTreeSet<&str> = BTreeSet::new();
+    let (mut errors, mut warnings, mut failed) = (0usize, 0usize, 0usize);
+
+    for line in input.lines() {
+        let trimmed = line.trim_start();
+        if line.trim().is_empty() {
+            if block != Block::None {
+                block = Block::None;
+                if out.last().is_some_and(|l| !l.is_empty()) {
+                    out.push("");
+                }
+            }
+            continue;
+        }
+        if line.starts_with("error") {
+            if let Some(code) = error_code(line) {
+                codes.insert(code);
+            }
+            if !line.starts_with("error: test failed") && !line.starts_with("error: could not") {
+                errors += 1;
+            }
+            block = Block::Diagnostic(1);
+            out.push(line);
+            continue;
+        }
+        if line.starts_with("warning") {
+            // `warning: `crate` (lib) generated N warnings` is a summary, not a diagnostic.
+            if line.contains(" generated ") {
+                out.push(line);
+                block = Block::None;
+            } else {
+                warnings += 1;
+                block = Block::Warning;
+            }
+            continue;
+        }
+        if line.starts_with("thread '") && line.contains("panicked at") {
+            block = Block::Panic(1);
+            out.push(line);
+            continue;
+        }
+        if trimmed.starts_with("test ") && line.ends_with("FAILED") {
+            failed += 1;
+            out.push(line);
+            continue;
+        }
+        if line == "failures:" {
+            block = Block::FailureList;
+            out.push(line);
+            continue;
+        }
+        if (line.starts_with("---- ") && line.ends_with(" ----"))
+            || trimmed.starts_with("test result:")
+            || trimmed.starts_with("Running ")
+            || trimmed.starts_with("Doc-tests ")
+            || trimmed.starts_with("Finished ")
+            || line.starts_with("For more information about")
+        {
+            block = Block::None;
+            out.push(line);
+            continue;
+        }
+        match block {
+            Block::Diagnostic(n) if !is_top_level(line) || is_snippet_line(line) => {
+                if n < DIAGNOSTIC_LINE_LIMIT {
+                    out.push(line);
+                }
+                block = Block::Diagnostic(n + 1);
+            }
+            Block::Panic(n) => {
+                if n < PANIC_HEAD_LINES {
+                    out.push(line);
+                }
+                block = Block::Panic(n + 1);
+            }
+            Block::FailureList if line.starts_with("    ") => out.push(line),
+            Block::Warning if !is_top_level(line) || is_snippet_line(line) => {}
+            _ => block = Block::None,
+        }
+    }
+    while out.last().is_some_and(|l| l.is_empty()) {
+        out.pop();
+    }
+    if out.is_empty() {
+        return input
+            .lines()
+            .rev()
+            .take(20)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    let mut text = out.join("\n");
+    if errors + failed + warnings > 0 {
+        let codes = if codes.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", codes.into_iter().collect::<Vec<_>>().join(", "))
+        };
+        text.push_str(&format!(
+            "\n[cx] cargo: {errors} error(s){codes}, {failed} failed test(s), {warnings} warning(s) omitted"
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILD: &str = "   Compiling foo v0.1.0 (/w/foo)
+warning: unused variable: `x`
+ --> src/lib.rs:2:9
+  |
+2 |     let x = 1;
+  |         ^ help: prefix it with an underscore: `_x`
+  |
+  = note: `#[warn(unused_variables)]` on by default
+
+error[E0308]: mismatched types
+ --> src/lib.rs:5:5
+  |
+4 | fn f() -> u32 {
+  |           --- expected `u32` because of return type
+5 |     \"no\"
+  |     ^^^^ expected `u32`, found `&str`
+
+For more information about this error, try `rustc --explain E0308`.
+warning: `foo` (lib) generated 1 warning
+error: could not compile `foo` (lib) due to 1 previous error; 1 warning emitted
+";
+
+    const TEST: &str = "   Compiling foo v0.1.0 (/w/foo)
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.50s
+     Running unittests src/lib.rs (target/debug/deps/foo-abc)
+
+running 3 tests
+test tests::ok_one ... ok
+test tests::bad ... FAILED
+test tests::ok_two ... ok
+
+failures:
+
+---- tests::bad stdout ----
+
+thread 'tests::bad' panicked at src/lib.rs:9:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+stack backtrace:
+   0: rust_begin_unwind
+   1: core::panicking::panic_fmt
+   2: core::panicking::assert_failed_inner
+   3: core::panicking::assert_failed
+   4: foo::tests::bad
+   5: foo::tests::bad::{{closure}}
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.
+
+failures:
+    tests::bad
+
+test result: FAILED. 2 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+error: test failed, to rerun pass `--lib`
+";
+
+    #[test]
+    fn build_errors_keep_snippet_and_drop_warning_bodies() {
+        let out = reduce_cargo_output(BUILD);
+        assert!(out.contains("error[E0308]: mismatched types"));
+        assert!(out.contains("expected `u32`, found `&str`"));
+        assert!(out.contains("For more information about this error"));
+        assert!(out.contains("error: could not compile `foo`"));
+        assert!(!out.contains("Compiling"));
+        assert!(!out.contains("unused variable"));
+        assert!(!out.contains("prefix it with an underscore"));
+        assert!(
+            out.ends_with("[cx] cargo: 1 error(s) [E0308], 0 failed test(s), 1 warning(s) omitted")
+        );
+    }
+
+    #[test]
+    fn test_failures_keep_names_panic_head_and_summary() {
+        let out = reduce_cargo_output(TEST);
+        assert!(out.contains("test tests::bad ... FAILED"));
+        assert!(!out.contains("tests::ok_one"));
+        assert!(out.contains("thread 'tests::bad' panicked at src/lib.rs:9:9:"));
+        assert!(out.contains("   2: core::panicking::assert_failed_inner"));
+        assert!(!out.contains("foo::tests::bad::{{closure}}"));
+        assert!(out.contains("failures:\n    tests::bad"));
+        assert!(out.contains("test result: FAILED. 2 passed; 1 failed"));
+        assert!(out.contains("error: test failed, to rerun pass `--lib`"));
+        assert!(out.ends_with("[cx] cargo: 0 error(s), 1 failed test(s), 0 warning(s) omitted"));
+    }
+
+    #[test]
+    fn clean_output_falls_back_to_tail() {
+        let input = "   Compiling a v0.1.0\n   Compiling b v0.1.0\n";
+        assert_eq!(
+            reduce_cargo_output(input),
+            "   Compiling a v0.1.0\n   Compiling b v0.1.0"
+        );
+        let ok = "   Compiling a v0.1.0\n    Finished `dev` profile in 1.0s\n";
+        assert_eq!(
+            reduce_cargo_output(ok),
+            "    Finished `dev` profile in 1.0s"
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use regex::Regex;
+use std::env;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::paths::repo_root;
+use crate::policy::glob_to_regex;
+
+/// Hunk body lines (context included) kept before the remaining hunks are summarized.
+pub const DIFF_HUNK_LINE_BUDGET: usize = 300;
+
+#[derive(Debug, Default)]
+struct DiffHunk {
+    lines: Vec<String>,
+    added: usize,
+    removed: usize,
+}
+
+#[derive(Debug, Default)]
+struct DiffFile {
+    path: String,
+    header: Vec<String>,
+    hunks: Vec<DiffHunk>,
+}
+
+fn hunk_counts(hunks: &[&DiffHunk]) -> (usize, usize) {
+    hunks
+        .iter()
+        .fold((0, 0), |(a, r), h| (a + h.added, r + h.removed))
+}
+
+/// `--focus` globs of the current command, merged with `CX_DIFF_FOCUS`.
+static FLAG_FOCUS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Add `--focus` globs for the diffs this process reduces.
+pub fn add_diff_focus(globs: Vec<String>) {
+    if let Ok(mut focus) = FLAG_FOCUS.lock() {
+        focus.extend(globs);
+    }
+}
+
+/// Focus globs from `CX_DIFF_FOCUS` (comma-separated) and `--focus`; invalid
+/// globs are ignored.
+pub fn diff_focus_globs() -> Vec<Regex> {
+    let flags = FLAG_FOCUS.lock().map(|f| f.clone()).unwrap_or_default();
+    env::var("CX_DIFF_FOCUS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::to_string)
+        .chain(flags)
+        .filter(|g| !g.trim().is_empty())
+        .filter_map(|g| glob_to_regex(g.trim()).ok())
+        .collect()
+}
+
+fn header_path(line: &str) -> Option<String> {
+    if let Some(rest) = line.strip_prefix("+++ ") {
+        let p = rest.split('\t').next().unwrap_or(rest).trim();
+        if p == "/dev/null" {
+            return None;
+        }
+        return Some(p.strip_prefix("b/").unwrap_or(p).to_string());
+    }
+    let rest = line.strip_prefix("diff --git ")?;
+    rest.rsplit_once(" b/").map(|(_, p)| p.to_string())
+}
+
+fn parse_unified_diff(input: &str) -> Vec<DiffFile> {
+    let mut files: Vec<DiffFile> = Vec::new();
+    let mut lines = input.lines().peekable();
+    while let Some(line) = lines.next() {
+        // `--- ` only opens a file when `+++ ` follows; inside a hunk it may be a removed `-- ` line.
+        let plain_header = line.starts_with("--- ")
+            && lines.peek().is_some_and(|n| n.starts_with("+++ "))
+            && files.last().is_none_or(|f| !f.hunks.is_empty());
+        let starts_file = line.starts_with("diff ") || plain_header;
+        if starts_file {
+            files.push(DiffFile::default());
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("@@") {
+            file.hunks.push(DiffHunk {
+                lines: vec![line.to_string()],
+                ..Default::default()
+            });
+            continue;
+        }
+        match file.hunks.last_mut() {
+            Some(hunk) => {
+                if line.starts_with('+') {
+                    hunk.added += 1;
+                } else if line.starts_with('-') {
+                    hunk.removed += 1;
+                }
+                hunk.lines.push(line.to_string());
+            }
+            None => {
+                if let Some(p) = header_path(line) {
+                    file.path = p;
+                }
+                file.header.push(line.to_string());
+            }
+        }
+    }
+    files
+}
+
+fn matches_focus(path: &str, focus: &[Regex]) -> bool {
+    let name = Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path);
+    focus
+        .iter()
+        .any(|re| re.is_match(path) || re.is_match(name))
+}
+
+fn modified_at(root: Option<&Path>, path: &str) -> Option<SystemTime> {
+    std::fs::metadata(root?.join(path)).ok()?.modified().ok()
+}
+
+/// Keep whole hunks for the most important files until the budget runs out.
+///
+/// Files matching a focus glob come first, then the most recently modified
+/// working-tree files; whatever does not fit is reduced to per-file
+/// added/removed counts.
+pub fn reduce_diff_smart(input: &str, focus: &[Regex], budget: usize) -> Option<String> {
+    let files = parse_unified_diff(input);
+    if files.iter().all(|f| f.hunks.is_empty()) {
+        return None;
+    }
+    let root = repo_root();
+    let mut order: Vec<(usize, bool, Option<SystemTime>)> = files
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let focused = matches_focus(&f.path, focus);
+            (i, focused, modified_at(root.as_deref(), &f.path))
+        })
+        .collect();
+    order.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+
+    let mut used = 0usize;
+    let mut out: Vec<String> = Vec::new();
+    let mut summary: Vec<String> = Vec::new();
+    for (idx, _, _) in order {
+        let file = &files[idx];
+        let (kept, omitted): (Vec<&DiffHunk>, Vec<&DiffHunk>) = file.hunks.iter().partition(|h| {
+            let fits = used + h.lines.len() <= budget;
+            if fits {
+                used += h.lines.len();
+            }
+            fits
+        });
+        let (added, removed) = hunk_counts(&omitted);
+        if kept.is_empty() && !file.hunks.is_empty() {
+            summary.push(format!(
+                "- {}: +{added} -{removed} ({} hunk(s))",
+                file.path,
+                omitted.len()
+            ));
+            continue;
+        }
+        out.extend(file.header.iter().cloned());
+        for hunk in kept {
+            out.extend(hunk.lines.iter().cloned());
+        }
+        if !omitted.is_empty() {
+            out.push(format!(
+                "[cx] omitted {} hunk(s) in {}: +{added} -{removed}",
+                omitted.len(),
+                file.path
+            ));
+        }
+    }
+    if !summary.is_empty() {
+        out.push(format!(
+            "[cx] diff budget reached; {} file(s) summarized:",
+            summary.len()
+        ));
+        out.extend(summary);
+    }
+    Some(out.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_diff() -> String {
+        let mut s = String::new();
+        for (name, n) in [("src/big.rs", 6), ("docs/notes.md", 2), ("src/focus.rs", 2)] {
+            s.push_str(&format!(
+                "diff --git a/{name} b/{name}\nindex 1..2 100644\n--- a/{name}\n+++ b/{name}\n"
+            ));
+            s.push_str("@@ -1,1 +1,1 @@\n");
+            for i in 0..n {
+                s.push_str(&format!("+added {i}\n-removed {i}\n"));
+            }
+        }
+        s
+    }
+
+    #[test]
+    fn focus_files_are_kept_first_and_rest_summarized() {
+        let focus = vec![glob_to_regex("focus.rs").unwrap()];
+        let out = reduce_diff_smart(&sample_diff(), &focus, 10).expect("unified diff");
+        let focus_at = out.find("+++ b/src/focus.rs").expect("focus kept");
+        let notes_at = out.find("+++ b/docs/notes.md").expect("notes kept");
+        assert!(focus_at < notes_at);
+        assert!(out.contains("+added 1"));
+        assert!(out.contains("[cx] diff budget reached; 1 file(s) summarized:"));
+        assert!(out.contains("- src/big.rs: +6 -6 (1 hunk(s))"));
+        assert!(!out.contains("+++ b/src/big.rs"));
+    }
+
+    #[test]
+    fn everything_fits_within_budget() {
+        let out = reduce_diff_smart(&sample_diff(), &[], DIFF_HUNK_LINE_BUDGET).expect("diff");
+        assert!(!out.contains("[cx]"));
+        assert_eq!(out.matches("diff --git").count(), 3);
+    }
+
+    #[test]
+    fn plain_unified_diff_and_non_diff_input() {
+        let plain = "--- a.txt\t2026-01-01\n+++ b.txt\t2026-01-02\n@@ -1 +1 @@\n-x\n+y\n";
+        let out = reduce_diff_smart(plain, &[], 1).expect("unified diff");
+        assert!(out.contains("- b.txt: +1 -1 (1 hunk(s))"));
+        let dashes = "--- a.sql\n+++ b.sql\n@@ -1,2 +1 @@\n--- old comment\n keep\n";
+        let out = reduce_diff_smart(dashes, &[], 10).expect("unified diff");
+        assert!(out.contains("--- old comment"));
+        assert!(!out.contains("[cx]"));
+        assert_eq!(reduce_diff_smart("1c1\n< x\n---\n> y\n", &[], 10), None);
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::Instant;
+
+use crate::llm::{extract_agent_text, usage_from_jsonl};
+use crate::progress::llm_started;
+use crate::provider_adapter::run_jsonl_with_current_adapter;
+use crate::types::ChunkCall;
+
+use super::capture_budget::{BudgetConfig, chunk_text_by_budget};
+
+pub struct MapReduceOutcome {
+    /// Concatenated chunk summaries; `None` when any sub-call failed.
+    pub text: Option<String>,
+    pub calls: Vec<ChunkCall>,
+}
+
+pub fn exceeds_budget(input: &str, cfg: &BudgetConfig) -> bool {
+    input.chars().count() > cfg.budget_chars || input.lines().count() > cfg.budget_lines
+}
+
+/// Chunk size that keeps the number of summarization calls at or below `max_chunks`.
+fn chunk_chars_for(input: &str, budget_chars: usize, max_chunks: usize) -> usize {
+    let total = input.chars().count();
+    // Chunks break on line boundaries, so allow one extra line of slack per chunk.
+    let longest_line = input
+        .lines()
+        .map(|l| l.chars().count() + 1)
+        .max()
+        .unwrap_or(0);
+    let capped = total.div_ceil(max_chunks.max(1)) + longest_line;
+    budget_chars.max(1).max(capped)
+}
+
+fn chunk_prompt(cmd_label: &str, index: usize, total: usize, chunk: &str) -> String {
+    format!(
+        "Summarize chunk {index}/{total} of the output of `{cmd_label}`.\n\
+Reply with at most 12 terse bullet lines. Keep error messages, error codes, file paths, \
+line numbers, failing test names and counts verbatim; drop progress and repeated noise.\n\n\
+--- chunk {index}/{total} ---\n{chunk}"
+    )
+}
+
+/// Split `input` into budget-sized chunks, summarize each with one LLM call and
+/// join the summaries in order.
+pub fn map_reduce_summarize(
+    cmd_label: &str,
+    input: &str,
+    cfg: &BudgetConfig,
+    max_chunks: usize,
+) -> MapReduceOutcome {
+    let chunks = chunk_text_by_budget(input, chunk_chars_for(input, cfg.budget_chars, max_chunks));
+    let total = chunks.len();
+    let mut calls: Vec<ChunkCall> = Vec::new();
+    let mut summaries: Vec<String> = Vec::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let prompt = chunk_prompt(cmd_label, i + 1, total, chunk);
+        let started = Instant::now();
+        let span = llm_started(&format!("mapreduce {}/{total}", i + 1), &prompt);
+        let result = run_jsonl_with_current_adapter(&prompt);
+        drop(span);
+        let mut call = ChunkCall {
+            index: (i + 1) as u64,
+            duration_ms: started.elapsed().as_millis() as u64,
+            ..Default::default()
+        };
+        match result {
+            Ok(jsonl) => {
+                call.usage = usage_from_jsonl(&jsonl);
+                let summary = extract_agent_text(&jsonl).unwrap_or_default();
+                if summary.trim().is_empty() {
+                    call.error = Some("empty_agent_message".to_string());
+                } else {
+                    summaries.push(format!("## chunk {}/{total}\n{}", i + 1, summary.trim()));
+                }
+            }
+            Err(e) => call.error = Some(e.message),
+        }
+        call.prompt = prompt;
+        let failed = call.error.is_some();
+        calls.push(call);
+        if failed {
+            return MapReduceOutcome { text: None, calls };
+        }
+    }
+    let header = format!(
+        "[cx] mapreduce: {total} chunk summaries of {} chars from `{cmd_label}`",
+        input.chars().count()
+    );
+    MapReduceOutcome {
+        text: Some(
+            std::iter::once(header)
+                .chain(summaries)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        calls,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_size_caps_call_count() {
+        let input = "x".repeat(1_000);
+        assert_eq!(chunk_chars_for(&input, 100, 8), 1_126);
+        let lines = "abcd\n".repeat(200);
+        assert_eq!(chunk_chars_for(&lines, 100, 8), 130);
+        assert_eq!(chunk_chars_for(&lines, 400, 8), 400);
+        assert!(chunk_text_by_budget(&lines, chunk_chars_for(&lines, 10, 8)).len() <= 8);
+        assert_eq!(chunk_chars_for("", 0, 0), 1);
+        let prompt = chunk_prompt("cargo test", 2, 3, "body");
+        assert!(prompt.contains("chunk 2/3 of the output of `cargo test`"));
+        assert!(prompt.ends_with("--- chunk 2/3 ---\nbody"));
+    }
+}
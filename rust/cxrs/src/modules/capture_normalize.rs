@@ -0,0 +1,129 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Bytes inspected by the binary heuristic.
+const SAMPLE_BYTES: usize = 8192;
+/// Share of control bytes / invalid UTF-8 above which output counts as binary.
+const BINARY_PCT: usize = 30;
+
+/// CSI/OSC sequences and two-byte escapes emitted by colorizing tools.
+fn ansi_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]")
+            .expect("ansi escape pattern")
+    })
+}
+
+/// Text with escape sequences removed, and how many were removed.
+pub fn strip_ansi(text: &str) -> (String, u64) {
+    let re = ansi_re();
+    let n = re.find_iter(text).count() as u64;
+    if n == 0 {
+        return (text.to_string(), 0);
+    }
+    (re.replace_all(text, "").into_owned(), n)
+}
+
+/// NUL bytes, or too many control bytes / invalid UTF-8 sequences in the
+/// leading sample, mark output as binary.
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(SAMPLE_BYTES)];
+    if sample.is_empty() {
+        return false;
+    }
+    if sample.contains(&0) {
+        return true;
+    }
+    let controls = sample
+        .iter()
+        .filter(|b| {
+            b.is_ascii_control() && !matches!(b, b'\t' | b'\n' | b'\r' | 0x08 | 0x0c | 0x1b)
+        })
+        .count();
+    let invalid = String::from_utf8_lossy(sample)
+        .chars()
+        .filter(|c| *c == char::REPLACEMENT_CHARACTER)
+        .count();
+    (controls + invalid) * 100 > sample.len() * BINARY_PCT
+}
+
+fn binary_kind(bytes: &[u8]) -> &'static str {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x89PNG", "PNG image"),
+        (b"\xff\xd8\xff", "JPEG image"),
+        (b"GIF8", "GIF image"),
+        (b"%PDF", "PDF document"),
+        (b"PK\x03\x04", "zip archive"),
+        (b"\x1f\x8b", "gzip data"),
+        (b"\x7fELF", "ELF executable"),
+        (b"\xcf\xfa\xed\xfe", "Mach-O executable"),
+        (b"MZ", "Windows executable"),
+    ];
+    MAGIC
+        .iter()
+        .find(|(magic, _)| bytes.starts_with(magic))
+        .map(|(_, kind)| *kind)
+        .unwrap_or("unrecognized binary data")
+}
+
+/// Prompt-side stand-in for output that is not worth sending as text.
+pub fn binary_notice(bytes: &[u8]) -> String {
+    format!(
+        "[cx] binary output omitted: {} bytes ({})\n",
+        bytes.len(),
+        binary_kind(bytes)
+    )
+}
+
+/// One captured stream made prompt-safe.
+pub struct Normalized {
+    pub text: String,
+    pub ansi_sequences: u64,
+    pub binary: bool,
+}
+
+/// Replace binary output with a notice; otherwise decode and strip escapes.
+pub fn normalize_output(bytes: &[u8]) -> Normalized {
+    if looks_binary(bytes) {
+        return Normalized {
+            text: binary_notice(bytes),
+            ansi_sequences: 0,
+            binary: true,
+        };
+    }
+    let (text, ansi_sequences) = strip_ansi(&String::from_utf8_lossy(bytes));
+    Normalized {
+        text,
+        ansi_sequences,
+        binary: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_are_counted_and_binary_is_summarized() {
+        let out =
+            normalize_output(b"\x1b[1;31merror\x1b[0m: \x1b]8;;https://x\x07link\x1b]8;;\x07\n");
+        assert_eq!(out.text, "error: link\n");
+        assert_eq!(out.ansi_sequences, 4);
+        assert!(!out.binary);
+
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+        let out = normalize_output(png);
+        assert!(out.binary);
+        assert_eq!(
+            out.text,
+            "[cx] binary output omitted: 16 bytes (PNG image)\n"
+        );
+        // Latin-1 text without NULs still decodes; dense garbage does not.
+        assert!(!looks_binary(
+            "caf\u{e9} na\u{ef}ve r\u{e9}sum\u{e9}\n".as_bytes()
+        ));
+        assert!(looks_binary(&[0xfe, 0x81, 0x02, 0x03, b'a', 0x90, 0x91]));
+        assert!(!looks_binary(b""));
+    }
+}
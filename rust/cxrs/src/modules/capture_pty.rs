@@ -0,0 +1,52 @@
+use super::capture_normalize::strip_ansi;
+#[cfg(unix)]
+use crate::nesting::IN_FLIGHT_ENV;
+
+/// Terminal output as it would read on screen: escapes stripped, CRLF
+/// normalized and `\r`-redrawn progress lines reduced to their final state.
+pub fn clean_terminal_output(raw: &str) -> String {
+    let (plain, _) = strip_ansi(raw);
+    let mut out = String::with_capacity(plain.len());
+    for line in plain.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        out.push_str(line.rsplit('\r').next().unwrap_or(line));
+        out.push('\n');
+    }
+    out.pop();
+    out
+}
+
+/// Run `cmd` on a PTY with [`IN_FLIGHT_ENV`] set to `marker`; returns the raw
+/// merged output and exit status.
+#[cfg(unix)]
+pub fn run_pty_capture(cmd: &[String], tee: bool, marker: &str) -> Result<(Vec<u8>, i32), String> {
+    let label = format!("system command '{}' (pty)", cmd[0]);
+    let envs = [(IN_FLIGHT_ENV, marker)];
+    let output = crate::process::run_command_in_pty_with_timeout(cmd, &label, tee, &envs)?;
+    Ok((output.stdout, output.status.code().unwrap_or(1)))
+}
+
+#[cfg(not(unix))]
+pub fn run_pty_capture(
+    _cmd: &[String],
+    _tee: bool,
+    _marker: &str,
+) -> Result<(Vec<u8>, i32), String> {
+    Err("CX_CAPTURE_PROVIDER=pty is only supported on unix".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminal_output_loses_colors_and_progress_redraws() {
+        let raw = "\x1b[1m\x1b[32m   Compiling\x1b[0m cxrs v0.1.0\r\n\
+                   Building [=>  ] 1/3\rBuilding [==> ] 2/3\rBuilding [===] 3/3\r\n\
+                   \x1b]0;title\x07done\r\n";
+        assert_eq!(
+            clean_terminal_output(raw),
+            "   Compiling cxrs v0.1.0\nBuilding [===] 3/3\ndone\n"
+        );
+    }
+}
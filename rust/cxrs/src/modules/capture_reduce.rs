@@ -1,5 +1,11 @@
+use serde_json::Value;
 use std::env;
 
+use crate::state::{read_state_value, value_at_path};
+
+use super::capture_cargo::reduce_cargo_output;
+use super::capture_diff::{DIFF_HUNK_LINE_BUDGET, diff_focus_globs, reduce_diff_smart};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ReduceProfile {
     Fast,
@@ -154,21 +160,293 @@ fn reduce_tree_or_ls(input: &str) -> String {
         .join("\n")
 }
 
-pub fn native_reduce_output(cmd: &[String], input: &str) -> String {
-    let profile = reduce_profile_from_env();
+fn reduce_pytest(input: &str) -> String {
+    let mut in_failure = false;
+    input
+        .lines()
+        .filter(|line| {
+            let t = line.trim_start();
+            if t.starts_with("___") || t.starts_with("=== FAILURES") || t.starts_with("=== ERRORS")
+            {
+                in_failure = true;
+                return true;
+            }
+            if t.starts_with("===") {
+                in_failure = false;
+                return true;
+            }
+            in_failure
+                && (t.starts_with("E ")
+                    || t.starts_with('>')
+                    || t.contains(".py:")
+                    || t.starts_with("Traceback"))
+                || t.starts_with("FAILED ")
+                || t.starts_with("ERROR ")
+        })
+        .take(400)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn reduce_npm(input: &str) -> String {
+    input
+        .lines()
+        .filter(|line| {
+            let t = line.trim_start();
+            let lower = t.to_ascii_lowercase();
+            t.starts_with("npm ERR!")
+                || t.starts_with("npm WARN")
+                || lower.starts_with("error")
+                || lower.contains(" error ")
+                || lower.starts_with("added ")
+                || lower.starts_with("removed ")
+                || lower.contains("vulnerabilit")
+                || t.starts_with("Tests:")
+                || t.starts_with("Test Suites:")
+                || t.starts_with('●')
+                || t.starts_with('✕')
+        })
+        .take(300)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn reduce_kubectl(input: &str) -> String {
+    const TROUBLE: [&str; 8] = [
+        "Error",
+        "CrashLoopBackOff",
+        "BackOff",
+        "Pending",
+        "Failed",
+        "Evicted",
+        "OOMKilled",
+        "Warning",
+    ];
+    let mut lines = input.lines();
+    let header = lines.next().unwrap_or_default();
+    let trouble: Vec<&str> = lines
+        .filter(|line| TROUBLE.iter().any(|t| line.contains(t)))
+        .take(300)
+        .collect();
+    if trouble.is_empty() {
+        return input.lines().take(100).collect::<Vec<_>>().join("\n");
+    }
+    std::iter::once(header)
+        .chain(trouble)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Collapse repeated log lines and keep errors plus the most recent tail.
+fn reduce_container_logs(input: &str) -> String {
+    let mut deduped: Vec<(String, usize)> = Vec::new();
+    for line in input.lines() {
+        match deduped.last_mut() {
+            Some((prev, n)) if prev == line => *n += 1,
+            _ => deduped.push((line.to_string(), 1)),
+        }
+    }
+    let render = |(line, n): &(String, usize)| {
+        if *n > 1 {
+            format!("{line} [x{n}]")
+        } else {
+            line.clone()
+        }
+    };
+    let tail_start = deduped.len().saturating_sub(100);
+    let mut out: Vec<String> = deduped[..tail_start]
+        .iter()
+        .filter(|(line, _)| {
+            let lower = line.to_ascii_lowercase();
+            lower.contains("error") || lower.contains("warn") || lower.contains("panic")
+        })
+        .take(200)
+        .map(render)
+        .collect();
+    out.extend(deduped[tail_start..].iter().map(render));
+    out.join("\n")
+}
+
+fn reduce_diff(input: &str) -> String {
+    reduce_diff_smart(input, &diff_focus_globs(), DIFF_HUNK_LINE_BUDGET)
+        .unwrap_or_else(|| reduce_diff_like(input))
+}
+
+/// A named native reducer and the command prefixes it claims.
+pub struct ReducerSpec {
+    pub name: &'static str,
+    pub prefixes: &'static [&'static [&'static str]],
+    reduce: fn(&str) -> String,
+}
+
+/// Ordered registry; the first reducer whose prefix matches the command wins.
+pub const REDUCERS: &[ReducerSpec] = &[
+    ReducerSpec {
+        name: "git-status",
+        prefixes: &[&["git", "status"]],
+        reduce: reduce_git_status,
+    },
+    ReducerSpec {
+        name: "diff",
+        prefixes: &[
+            &["git", "diff"],
+            &["jj", "diff"],
+            &["hg", "diff"],
+            &["diff"],
+        ],
+        reduce: reduce_diff,
+    },
+    ReducerSpec {
+        name: "git-log",
+        prefixes: &[&["git", "log"], &["log"]],
+        reduce: reduce_git_log,
+    },
+    ReducerSpec {
+        name: "grep",
+        prefixes: &[&["grep"], &["rg"]],
+        reduce: reduce_grep_like,
+    },
+    ReducerSpec {
+        name: "listing",
+        prefixes: &[&["tree"], &["ls"]],
+        reduce: reduce_tree_or_ls,
+    },
+    ReducerSpec {
+        name: "pytest",
+        prefixes: &[
+            &["pytest"],
+            &["python", "-m", "pytest"],
+            &["python3", "-m", "pytest"],
+        ],
+        reduce: reduce_pytest,
+    },
+    ReducerSpec {
+        name: "npm",
+        prefixes: &[&["npm"], &["pnpm"], &["yarn"]],
+        reduce: reduce_npm,
+    },
+    ReducerSpec {
+        name: "kubectl",
+        prefixes: &[&["kubectl"]],
+        reduce: reduce_kubectl,
+    },
+    ReducerSpec {
+        name: "container-logs",
+        prefixes: &[
+            &["docker", "logs"],
+            &["docker", "compose", "logs"],
+            &["docker-compose", "logs"],
+            &["kubectl", "logs"],
+        ],
+        reduce: reduce_container_logs,
+    },
+    ReducerSpec {
+        name: "cargo",
+        prefixes: &[&["cargo"], &["rustc"]],
+        reduce: reduce_cargo_output,
+    },
+    ReducerSpec {
+        name: "test-output",
+        prefixes: &[&["test"]],
+        reduce: reduce_test_output,
+    },
+];
+
+pub fn reducer_by_name(name: &str) -> Option<&'static ReducerSpec> {
+    REDUCERS.iter().find(|r| r.name == name)
+}
+
+fn command_matches(cmd: &[String], prefix: &[&str]) -> bool {
+    cmd.len() >= prefix.len()
+        && cmd.iter().zip(prefix).enumerate().all(|(i, (c, p))| {
+            if i == 0 {
+                command_base(c) == *p
+            } else {
+                c == p
+            }
+        })
+}
+
+fn command_base(cmd0: &str) -> &str {
+    cmd0.rsplit('/').next().unwrap_or(cmd0)
+}
+
+/// Most specific prefix wins, so `kubectl logs` beats `kubectl`.
+fn reducer_for_command(cmd: &[String]) -> Option<&'static ReducerSpec> {
+    REDUCERS
+        .iter()
+        .filter_map(|r| {
+            r.prefixes
+                .iter()
+                .filter(|p| command_matches(cmd, p))
+                .map(|p| p.len())
+                .max()
+                .map(|len| (len, r))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, r)| r)
+}
+
+/// `CX_REDUCER_<CMD>` env name for a command's first word (`docker-compose` -> `DOCKER_COMPOSE`).
+pub fn reducer_override_env(cmd0: &str) -> String {
+    let key: String = command_base(cmd0)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("CX_REDUCER_{key}")
+}
+
+/// Env (`CX_REDUCER_<CMD>`) first, then state `preferences.reducers.<cmd>`.
+fn reducer_override(cmd0: &str) -> Option<String> {
+    env::var(reducer_override_env(cmd0))
+        .ok()
+        .or_else(|| {
+            let state = read_state_value()?;
+            let path = format!("preferences.reducers.{}", command_base(cmd0));
+            value_at_path(&state, &path)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .map(|v| v.trim().to_ascii_lowercase())
+        .filter(|v| !v.is_empty() && v != "auto")
+}
+
+fn auto_reducer(cmd: &[String]) -> Option<&'static ReducerSpec> {
+    reducer_for_command(cmd).or_else(|| {
+        (reduce_profile_from_env() == ReduceProfile::Deep)
+            .then(|| reducer_by_name("test-output"))
+            .flatten()
+    })
+}
+
+/// Reduce captured output and report which reducer ran.
+///
+/// `CX_REDUCER_<CMD>=off` disables reduction for that command (`off`), a
+/// registry name forces that reducer, and anything else falls back to prefix
+/// matching (`none` when nothing matched).
+pub fn native_reduce_output(cmd: &[String], input: &str) -> (String, String) {
     let cmd0 = cmd.first().map(String::as_str).unwrap_or("");
-    let cmd1 = cmd.get(1).map(String::as_str).unwrap_or("");
-    let reduced = match (cmd0, cmd1, profile) {
-        ("git", "status", _) => reduce_git_status(input),
-        ("git", "diff", _) | ("diff", _, _) => reduce_diff_like(input),
-        ("git", "log", _) | ("log", _, _) => reduce_git_log(input),
-        ("grep", _, _) => reduce_grep_like(input),
-        ("tree", _, _) | ("ls", _, _) => reduce_tree_or_ls(input),
-        ("test", _, _) => reduce_test_output(input),
-        (_, _, ReduceProfile::Deep) => reduce_test_output(input),
-        _ => input.to_string(),
+    let selected = match reducer_override(cmd0).as_deref() {
+        Some("off") => return (normalize_generic(input), "off".to_string()),
+        Some(name) => reducer_by_name(name).or_else(|| {
+            crate::cx_eprintln!(
+                "cxrs capture: unknown reducer '{name}' in {}; using automatic selection",
+                reducer_override_env(cmd0)
+            );
+            auto_reducer(cmd)
+        }),
+        None => auto_reducer(cmd),
     };
-    normalize_generic(&reduced)
+    match selected {
+        Some(r) => (normalize_generic(&(r.reduce)(input)), r.name.to_string()),
+        None => (normalize_generic(input), "none".to_string()),
+    }
 }
 
 #[cfg(test)]
@@ -178,7 +456,8 @@ mod tests {
     #[test]
     fn reduce_git_status_keeps_semantic_lines() {
         let input = "On branch main\n  modified: src/main.rs\nrandom noise\n";
-        let out = native_reduce_output(&["git".into(), "status".into()], input);
+        let (out, reducer) = native_reduce_output(&["git".into(), "status".into()], input);
+        assert_eq!(reducer, "git-status");
         assert!(out.contains("On branch main"));
         assert!(out.contains("modified: src/main.rs"));
         assert!(!out.contains("random noise"));
@@ -187,8 +466,38 @@ mod tests {
     #[test]
     fn reduce_test_output_surfaces_failures() {
         let input = "line 1\nFAIL test_x\nwarning: foo\nline 2\n";
-        let out = native_reduce_output(&["test".into()], input);
+        let (out, _) = native_reduce_output(&["test".into()], input);
         assert!(out.contains("FAIL test_x"));
         assert!(out.contains("warning: foo"));
     }
+
+    fn words(cmd: &str) -> Vec<String> {
+        cmd.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn registry_prefers_most_specific_prefix() {
+        let name = |cmd: &str| reducer_for_command(&words(cmd)).map(|r| r.name);
+        assert_eq!(name("kubectl get pods"), Some("kubectl"));
+        assert_eq!(name("kubectl logs api-0"), Some("container-logs"));
+        assert_eq!(name("/usr/bin/git status -s"), Some("git-status"));
+        assert_eq!(name("python3 -m pytest -q"), Some("pytest"));
+        assert_eq!(name("cargo test --workspace"), Some("cargo"));
+        assert_eq!(name("python3 script.py"), None);
+        assert_eq!(name("git"), None);
+        assert_eq!(
+            reducer_override_env("docker-compose"),
+            "CX_REDUCER_DOCKER_COMPOSE"
+        );
+        assert_eq!(reducer_override_env("/usr/bin/npm"), "CX_REDUCER_NPM");
+    }
+
+    #[test]
+    fn container_logs_collapse_repeats() {
+        let input = "boot\nretrying\nretrying\nretrying\nready\n";
+        let (out, reducer) = native_reduce_output(&words("docker logs web"), input);
+        assert_eq!(reducer, "container-logs");
+        assert!(out.contains("retrying [x3]"));
+        assert_eq!(out.matches("retrying").count(), 1);
+    }
 }
@@ -1,13 +1,38 @@
 use std::env;
+use std::path::PathBuf;
 use std::process::Command;
+use std::time::Instant;
 
-use crate::process::run_command_output_with_timeout;
+use serde_json::json;
+
+use crate::config::app_config;
+use crate::nesting::{IN_FLIGHT_ENV, guard_wrapped};
+use crate::paths::repo_root;
+use crate::process::{run_command_output_with_timeout, run_command_tee_with_timeout};
+use crate::progress;
+use crate::token_estimate::estimate_tokens;
 use crate::types::CaptureStats;
+use crate::verbosity::{Verbosity, diag, warn};
 
-use super::capture_budget::{budget_config_from_env, clip_text_with_config};
+use super::capture_budget::{BudgetConfig, budget_config_for_tool, clip_text_with_config};
+use super::capture_mapreduce::{exceeds_budget, map_reduce_summarize};
+use super::capture_normalize::normalize_output;
+use super::capture_pty::{clean_terminal_output, run_pty_capture};
+use super::capture_redact::load_redactor;
 use super::capture_reduce::native_reduce_output;
 
-fn run_capture(command: &[String]) -> Result<(String, i32), String> {
+/// One captured stream after redaction and reduction.
+struct Stream {
+    label: &'static str,
+    text: String,
+}
+
+/// Run `command` with [`IN_FLIGHT_ENV`] set to `marker` in its environment.
+fn run_capture(
+    command: &[String],
+    tee: bool,
+    marker: &str,
+) -> Result<(Vec<u8>, Vec<u8>, i32), String> {
     if command.is_empty() {
         return Err("missing command".to_string());
     }
@@ -15,37 +40,360 @@ fn run_capture(command: &[String]) -> Result<(String, i32), String> {
     if command.len() > 1 {
         c.args(&command[1..]);
     }
-    let output = run_command_output_with_timeout(c, &format!("system command '{}'", command[0]))?;
-    let status = output.status.code().unwrap_or(1);
-    let mut combined = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    if !stderr.trim().is_empty() {
-        if !combined.is_empty() && !combined.ends_with('\n') {
-            combined.push('\n');
-        }
-        combined.push_str(&stderr);
+    c.env(IN_FLIGHT_ENV, marker);
+    let label = format!("system command '{}'", command[0]);
+    let output = if tee {
+        run_command_tee_with_timeout(c, &label)?
+    } else {
+        run_command_output_with_timeout(c, &label)?
+    };
+    Ok((
+        output.stdout,
+        output.stderr,
+        output.status.code().unwrap_or(1),
+    ))
+}
+
+/// Sections as they appear in the prompt; labels only once stderr has content.
+fn join_streams(stdout: &str, stderr: &str) -> String {
+    if stderr.trim().is_empty() {
+        return stdout.to_string();
+    }
+    if stdout.trim().is_empty() {
+        return format!("[stderr]\n{stderr}");
+    }
+    let sep = if stdout.ends_with('\n') { "" } else { "\n" };
+    format!("[stdout]\n{stdout}{sep}[stderr]\n{stderr}")
+}
+
+fn scale(v: usize, pct: usize) -> usize {
+    (v.saturating_mul(pct) / 100).max(1)
+}
+
+/// Budget for stderr: the configured share, or all of it when stdout is empty.
+fn stderr_budget(budget: &BudgetConfig, pct: usize, stdout_empty: bool) -> BudgetConfig {
+    let pct = if stdout_empty { 100 } else { pct.min(100) };
+    BudgetConfig {
+        budget_chars: scale(budget.budget_chars, pct),
+        budget_lines: scale(budget.budget_lines, pct),
+        budget_tokens: budget.budget_tokens.map(|t| scale(t, pct)),
+        ..budget.clone()
+    }
+}
+
+/// What stderr left unused goes to stdout.
+fn stdout_budget(budget: &BudgetConfig, used: &CaptureStats) -> BudgetConfig {
+    let left = |total: usize, used: Option<u64>| total.saturating_sub(used.unwrap_or(0) as usize);
+    BudgetConfig {
+        budget_chars: left(budget.budget_chars, used.system_output_len_clipped),
+        budget_lines: left(budget.budget_lines, used.system_output_lines_clipped),
+        budget_tokens: budget
+            .budget_tokens
+            .map(|t| left(t, used.system_output_tokens_clipped)),
+        ..budget.clone()
+    }
+}
+
+/// Clip stderr first under its own budget, then stdout with the remainder, and
+/// fold the per-stream figures into one set of stats.
+fn clip_streams(
+    stdout: &str,
+    stderr: &str,
+    budget: &BudgetConfig,
+    stderr_pct: usize,
+) -> (String, CaptureStats) {
+    let (err_text, err) = if stderr.trim().is_empty() {
+        (String::new(), CaptureStats::default())
+    } else {
+        clip_text_with_config(
+            stderr,
+            &stderr_budget(budget, stderr_pct, stdout.trim().is_empty()),
+        )
+    };
+    let (out_text, out) = clip_text_with_config(stdout, &stdout_budget(budget, &err));
+    let sum = |a: Option<u64>, b: Option<u64>| Some(a.unwrap_or(0) + b.unwrap_or(0));
+    let mut stats = out.clone();
+    stats.system_output_len_raw = sum(out.system_output_len_raw, err.system_output_len_raw);
+    stats.system_output_len_processed = stats.system_output_len_raw;
+    stats.system_output_len_clipped =
+        sum(out.system_output_len_clipped, err.system_output_len_clipped);
+    stats.system_output_lines_raw = sum(out.system_output_lines_raw, err.system_output_lines_raw);
+    stats.system_output_lines_processed = stats.system_output_lines_raw;
+    stats.system_output_lines_clipped = sum(
+        out.system_output_lines_clipped,
+        err.system_output_lines_clipped,
+    );
+    stats.system_output_tokens_raw =
+        sum(out.system_output_tokens_raw, err.system_output_tokens_raw);
+    stats.system_output_tokens_clipped = sum(
+        out.system_output_tokens_clipped,
+        err.system_output_tokens_clipped,
+    );
+    stats.clipped = Some(out.clipped == Some(true) || err.clipped == Some(true));
+    stats.budget_chars = Some(budget.budget_chars as u64);
+    stats.budget_lines = Some(budget.budget_lines as u64);
+    stats.budget_tokens = budget.budget_tokens.map(|v| v as u64);
+    if stdout.trim().is_empty() && err.clip_mode.is_some() {
+        stats.clip_mode = err.clip_mode.clone();
     }
-    Ok((combined, status))
+    stats.stdout_len_raw = out.system_output_len_raw;
+    stats.stdout_len_clipped = out.system_output_len_clipped;
+    stats.stdout_lines_raw = out.system_output_lines_raw;
+    stats.stdout_lines_clipped = out.system_output_lines_clipped;
+    stats.stderr_len_raw = Some(err.system_output_len_raw.unwrap_or(0));
+    stats.stderr_len_clipped = Some(err.system_output_len_clipped.unwrap_or(0));
+    stats.stderr_lines_raw = Some(err.system_output_lines_raw.unwrap_or(0));
+    stats.stderr_lines_clipped = Some(err.system_output_lines_clipped.unwrap_or(0));
+    (join_streams(&out_text, &err_text), stats)
 }
 
-pub fn run_system_command_capture(cmd: &[String]) -> Result<(String, i32, CaptureStats), String> {
+/// Run `cmd` and prepare its output for a `tool` prompt: redact, reduce, then
+/// summarize or clip to the tool's budget.
+pub fn run_system_command_capture(
+    tool: &str,
+    cmd: &[String],
+) -> Result<(String, i32, CaptureStats), String> {
     if cmd.is_empty() {
         return Err("missing command".to_string());
     }
-    let (raw_out, status) = run_capture(cmd)?;
+    let cfg = app_config();
+    // A PTY merges both streams, so everything arrives as stdout.
+    let pty = cfg.capture_provider == "pty";
+    let cmd_label = cmd.join(" ");
+    progress::emit("capture_started", &[("cmd", json!(cmd_label))]);
+    let marker = guard_wrapped(tool, cmd)?;
+    let capture_started = Instant::now();
+    let (raw_stdout, raw_stderr, status) = if pty {
+        let (out, status) = run_pty_capture(cmd, cfg.tee, &marker)?;
+        (out, Vec::new(), status)
+    } else {
+        run_capture(cmd, cfg.tee, &marker)?
+    };
+    progress::emit(
+        "capture_finished",
+        &[
+            ("cmd", json!(cmd_label)),
+            ("status", json!(status)),
+            ("stdout_bytes", json!(raw_stdout.len())),
+            ("stderr_bytes", json!(raw_stderr.len())),
+            (
+                "duration_ms",
+                json!(capture_started.elapsed().as_millis() as u64),
+            ),
+        ],
+    );
+    // Escapes and binary blobs cost tokens without telling the model anything.
+    let mut ansi_sequences = 0;
+    let mut binary_output = false;
+    let [raw_stdout, raw_stderr] = [raw_stdout, raw_stderr].map(|bytes| {
+        let n = normalize_output(&bytes);
+        ansi_sequences += n.ansi_sequences;
+        binary_output |= n.binary;
+        if pty && !n.binary {
+            clean_terminal_output(&n.text)
+        } else {
+            n.text
+        }
+    });
     let native_reduce = env::var("CX_NATIVE_REDUCE")
         .ok()
         .and_then(|v| v.parse::<u8>().ok())
         .unwrap_or(1)
         == 1;
-    let processed = raw_out.clone();
-    let reduced = if native_reduce {
-        native_reduce_output(cmd, &processed)
+    // Redact first so budgets, reducers and summarization only ever see masked text.
+    let redactor = if cfg.redact {
+        let root = repo_root().unwrap_or_else(|| PathBuf::from("."));
+        Some(load_redactor(&root)?)
     } else {
-        processed
+        None
+    };
+    let mut redactions_applied = redactor.as_ref().map(|_| 0);
+    let mut reducer = "off".to_string();
+    let (mut reduce_before, mut reduce_after) = (0, 0);
+    let mut streams = [
+        Stream {
+            label: "stdout",
+            text: raw_stdout,
+        },
+        Stream {
+            label: "stderr",
+            text: raw_stderr,
+        },
+    ];
+    for s in &mut streams {
+        if let Some(r) = &redactor {
+            let redaction = r.redact(&s.text);
+            redactions_applied = redactions_applied.map(|n| n + redaction.total());
+            s.text = redaction.text;
+        }
+        // Both streams go through the command's reducer; stdout names it.
+        if native_reduce && (s.label == "stdout" || !s.text.trim().is_empty()) {
+            let (text, name) = native_reduce_output(cmd, &s.text);
+            reduce_before += s.text.chars().count();
+            reduce_after += text.chars().count();
+            s.text = text;
+            if s.label == "stdout" {
+                reducer = name;
+            }
+        }
+    }
+    let [stdout, stderr] = streams.map(|s| s.text);
+    let reduced = join_streams(&stdout, &stderr);
+    let reduced_chars = reduced.chars().count();
+    if reduce_after != reduce_before {
+        progress::emit(
+            "reduce_applied",
+            &[
+                ("reducer", json!(reducer)),
+                ("chars_before", json!(reduce_before)),
+                ("chars_after", json!(reduce_after)),
+            ],
+        );
+    }
+    let mut budget = budget_config_for_tool(tool);
+    // A failing command's key lines sit around its first error, not at either end.
+    if status != 0 && budget.clip_mode == "smart" {
+        budget.clip_mode = "error-window".to_string();
+    }
+    let mut chunk_calls = Vec::new();
+    let mut chunk_strategy = "clip";
+    let mut to_clip = None;
+    if cfg.chunk_strategy == "mapreduce" && exceeds_budget(&reduced, &budget) {
+        let outcome = map_reduce_summarize(&cmd.join(" "), &reduced, &budget, cfg.chunk_max);
+        match outcome.text {
+            Some(text) => {
+                chunk_strategy = "mapreduce";
+                to_clip = Some(text);
+            }
+            None => {
+                chunk_strategy = "mapreduce_fallback";
+                warn("cxrs capture: map-reduce summarization failed; falling back to clipping");
+            }
+        }
+        chunk_calls = outcome.calls;
+    }
+    let (clipped_text, mut stats) = match &to_clip {
+        // Summaries already mix both streams; clip them as one text.
+        Some(summary) => {
+            let (text, mut stats) = clip_text_with_config(summary, &budget);
+            // Raw figures describe the captured output, not the summaries fed to clipping.
+            stats.system_output_len_raw = Some(reduced.chars().count() as u64);
+            stats.system_output_lines_raw = Some(reduced.lines().count() as u64);
+            stats.system_output_tokens_raw =
+                Some(estimate_tokens(&reduced, budget.tokenizer) as u64);
+            stats.stdout_len_raw = Some(stdout.chars().count() as u64);
+            stats.stdout_lines_raw = Some(stdout.lines().count() as u64);
+            stats.stderr_len_raw = Some(stderr.chars().count() as u64);
+            stats.stderr_lines_raw = Some(stderr.lines().count() as u64);
+            (text, stats)
+        }
+        None => clip_streams(&stdout, &stderr, &budget, cfg.stderr_budget_pct),
     };
-    let (clipped_text, mut stats) = clip_text_with_config(&reduced, &budget_config_from_env());
+    let clipped_chars = clipped_text.chars().count();
+    if clipped_chars < reduced_chars || to_clip.is_some() {
+        progress::emit(
+            "clip_applied",
+            &[
+                ("strategy", json!(chunk_strategy)),
+                ("mode", json!(budget.clip_mode)),
+                ("chars_before", json!(reduced_chars)),
+                ("chars_after", json!(clipped_chars)),
+                ("chunks", json!(chunk_calls.len())),
+            ],
+        );
+    }
+    stats.chunk_strategy = Some(chunk_strategy.to_string());
+    stats.chunk_count = Some(chunk_calls.len() as u64).filter(|n| *n > 0);
+    stats.chunk_calls = chunk_calls;
+    stats.reducer = Some(reducer);
+    stats.redactions_applied = redactions_applied;
     stats.rtk_used = Some(false);
-    stats.capture_provider = Some("native".to_string());
+    stats.capture_provider = Some(cfg.capture_provider.clone());
+    stats.ansi_sequences_stripped = Some(ansi_sequences);
+    stats.binary_output = Some(binary_output);
+    log_capture_diag(cmd, &stats);
     Ok((clipped_text, status, stats))
 }
+
+fn opt_field<T: ToString>(v: &Option<T>) -> String {
+    v.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+fn log_capture_diag(cmd: &[String], stats: &CaptureStats) {
+    diag(
+        Verbosity::Verbose,
+        "capture",
+        &[
+            ("cmd", cmd.join(" ")),
+            ("provider", opt_field(&stats.capture_provider)),
+            ("reducer", opt_field(&stats.reducer)),
+            ("chunk_strategy", opt_field(&stats.chunk_strategy)),
+            ("chunk_count", stats.chunk_count.unwrap_or(0).to_string()),
+            ("redactions", opt_field(&stats.redactions_applied)),
+            ("ansi_stripped", opt_field(&stats.ansi_sequences_stripped)),
+            ("binary", opt_field(&stats.binary_output)),
+        ],
+    );
+    diag(
+        Verbosity::Verbose,
+        "clip",
+        &[
+            ("mode", opt_field(&stats.clip_mode)),
+            ("clipped", opt_field(&stats.clipped)),
+            ("chars_raw", opt_field(&stats.system_output_len_raw)),
+            ("chars_clipped", opt_field(&stats.system_output_len_clipped)),
+            ("lines_raw", opt_field(&stats.system_output_lines_raw)),
+            ("stdout_chars_raw", opt_field(&stats.stdout_len_raw)),
+            ("stderr_chars_raw", opt_field(&stats.stderr_len_raw)),
+            ("stderr_chars_clipped", opt_field(&stats.stderr_len_clipped)),
+            (
+                "lines_clipped",
+                opt_field(&stats.system_output_lines_clipped),
+            ),
+            ("tokens_raw", opt_field(&stats.system_output_tokens_raw)),
+            (
+                "tokens_clipped",
+                opt_field(&stats.system_output_tokens_clipped),
+            ),
+        ],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token_estimate::TokenizerKind;
+
+    fn budget(chars: usize, lines: usize) -> BudgetConfig {
+        BudgetConfig {
+            budget_chars: chars,
+            budget_lines: lines,
+            budget_tokens: None,
+            tokenizer: TokenizerKind::for_backend("codex"),
+            clip_mode: "head".to_string(),
+            clip_footer: false,
+            error_regex: crate::config::DEFAULT_ERROR_REGEX.to_string(),
+        }
+    }
+
+    #[test]
+    fn noisy_stdout_cannot_clip_away_stderr() {
+        let stdout: String = (0..100).map(|i| format!("progress {i}\n")).collect();
+        let stderr = "error: build failed\n";
+        let (text, stats) = clip_streams(&stdout, stderr, &budget(10_000, 20), 50);
+        assert!(text.starts_with("[stdout]\nprogress 0\n"), "{text}");
+        assert!(text.ends_with("[stderr]\nerror: build failed\n"), "{text}");
+        // stderr used one line of its ten, so stdout gets the other nineteen.
+        assert_eq!(stats.stderr_lines_clipped, Some(1));
+        assert_eq!(stats.stdout_lines_raw, Some(100));
+        assert_eq!(stats.stdout_lines_clipped, Some(19));
+        assert_eq!(stats.system_output_lines_clipped, Some(20));
+        assert_eq!(stats.clipped, Some(true));
+
+        let (text, stats) = clip_streams("ok\n", "", &budget(100, 10), 50);
+        assert_eq!(text, "ok\n");
+        assert_eq!(stats.stderr_len_raw, Some(0));
+        let (text, _) = clip_streams("", "boom\n", &budget(100, 10), 50);
+        assert_eq!(text, "[stderr]\nboom\n");
+    }
+}
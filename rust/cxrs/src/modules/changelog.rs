@@ -0,0 +1,399 @@
+use chrono::Utc;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::contract_versions::CHANGELOG_JSON_CONTRACT_VERSION;
+use crate::error::{print_runtime_error, print_usage_error};
+use crate::logs::append_jsonl;
+use crate::paths::resolve_commitjson_log_file;
+use crate::process::run_command_output_with_timeout;
+use crate::schema::validate_schema_instance;
+use crate::types::LoadedSchema;
+
+const CHANGELOG_USAGE: &str = "changelog --from <rev> [--to <rev>] [--json]";
+
+/// Conventional-commit types in release-note order; anything else is `other`.
+pub(crate) const SECTIONS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build"),
+    ("ci", "CI"),
+    ("chore", "Chores"),
+    ("revert", "Reverts"),
+    ("other", "Other Changes"),
+];
+
+pub(crate) fn git_output(args: &[&str]) -> Result<String, String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args);
+    let label = format!("git {}", args.first().copied().unwrap_or_default());
+    let out = run_command_output_with_timeout(cmd, &label)?;
+    if !out.status.success() {
+        return Err(format!(
+            "{label} failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+/// Remember a generated commit object under the staged tree hash, so
+/// `changelog` can reuse it once the commit exists. Best-effort.
+pub fn record_commitjson(value: &Value) {
+    let (Ok(tree), Some(path)) = (git_output(&["write-tree"]), resolve_commitjson_log_file())
+    else {
+        return;
+    };
+    let row = json!({
+        "ts": Utc::now().to_rfc3339(),
+        "tree": tree.trim(),
+        "commit": value,
+    });
+    let _ = append_jsonl(&path, &row);
+}
+
+/// Recorded commitjson objects by tree hash; later rows win.
+fn recorded_commitjson() -> HashMap<String, Value> {
+    let Some(raw) = resolve_commitjson_log_file().and_then(|p| fs::read_to_string(p).ok()) else {
+        return HashMap::new();
+    };
+    raw.lines()
+        .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+        .filter_map(|row| {
+            let tree = row.get("tree")?.as_str()?.to_string();
+            Some((tree, row.get("commit")?.clone()))
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Conventional {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+}
+
+/// Split `type(scope)!: description`; non-conventional subjects are `other`.
+pub(crate) fn parse_subject(subject: &str) -> Conventional {
+    let plain = || Conventional {
+        kind: "other".to_string(),
+        scope: None,
+        breaking: false,
+        description: subject.trim().to_string(),
+    };
+    let Some((head, desc)) = subject.split_once(": ") else {
+        return plain();
+    };
+    let (head, breaking) = match head.strip_suffix('!') {
+        Some(h) => (h, true),
+        None => (head, false),
+    };
+    let (kind, scope) = match head.split_once('(') {
+        Some((k, rest)) => match rest.strip_suffix(')') {
+            Some(s) => (k, Some(s.trim().to_string()).filter(|s| !s.is_empty())),
+            None => return plain(),
+        },
+        None => (head, None),
+    };
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphabetic()) {
+        return plain();
+    }
+    let kind = kind.to_ascii_lowercase();
+    Conventional {
+        kind: if SECTIONS.iter().any(|(k, _)| *k == kind) {
+            kind
+        } else {
+            "other".to_string()
+        },
+        scope,
+        breaking,
+        description: desc.trim().to_string(),
+    }
+}
+
+struct RawCommit {
+    hash: String,
+    tree: String,
+    subject: String,
+    body: String,
+}
+
+fn collect_commits(from: &str, to: &str) -> Result<Vec<RawCommit>, String> {
+    let range = format!("{from}..{to}");
+    let raw = git_output(&[
+        "log",
+        "--no-merges",
+        "--format=%H%x1f%T%x1f%s%x1f%b%x1e",
+        &range,
+    ])?;
+    Ok(raw
+        .split('\x1e')
+        .filter_map(|rec| {
+            let mut f = rec.trim_start_matches('\n').splitn(4, '\x1f');
+            Some(RawCommit {
+                hash: f.next().filter(|h| !h.is_empty())?.to_string(),
+                tree: f.next()?.to_string(),
+                subject: f.next()?.to_string(),
+                body: f.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+fn string_list(v: Option<&Value>) -> Vec<String> {
+    v.and_then(Value::as_array)
+        .map(|a| {
+            a.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// One changelog entry, preferring the recorded commitjson object whose
+/// tree matches the commit over the git subject/body.
+fn build_entry(c: &RawCommit, recorded: &HashMap<String, Value>) -> Value {
+    let conv = parse_subject(&c.subject);
+    let git_breaking = conv.breaking || c.body.contains("BREAKING CHANGE");
+    let (kind, scope, breaking, subject, body, source) = match recorded.get(&c.tree) {
+        Some(rec) => {
+            let rec_conv = parse_subject(rec.get("subject").and_then(Value::as_str).unwrap_or(""));
+            let kind = if conv.kind == "other" {
+                rec_conv.kind
+            } else {
+                conv.kind
+            };
+            let scope = rec
+                .get("scope")
+                .and_then(Value::as_str)
+                .filter(|s| !s.trim().is_empty())
+                .map(str::to_string)
+                .or(rec_conv.scope)
+                .or(conv.scope);
+            let breaking = git_breaking
+                || rec_conv.breaking
+                || rec.get("breaking").and_then(Value::as_bool) == Some(true);
+            let body = string_list(rec.get("body"));
+            (
+                kind,
+                scope,
+                breaking,
+                rec_conv.description,
+                body,
+                "commitjson",
+            )
+        }
+        None => {
+            let body = c
+                .body
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(str::to_string)
+                .collect();
+            (
+                conv.kind,
+                conv.scope,
+                git_breaking,
+                conv.description,
+                body,
+                "git",
+            )
+        }
+    };
+    json!({
+        "hash": c.hash,
+        "type": kind,
+        "scope": scope,
+        "subject": subject,
+        "body": body,
+        "breaking": breaking,
+        "source": source,
+    })
+}
+
+fn short_hash(entry: &Value) -> &str {
+    let h = entry["hash"].as_str().unwrap_or_default();
+    &h[..h.len().min(7)]
+}
+
+fn markdown_line(entry: &Value) -> String {
+    let scope = entry["scope"]
+        .as_str()
+        .map(|s| format!("**{s}:** "))
+        .unwrap_or_default();
+    format!(
+        "- {scope}{} ({})\n",
+        entry["subject"].as_str().unwrap_or_default(),
+        short_hash(entry)
+    )
+}
+
+fn render_markdown(from: &str, to: &str, entries: &[Value], sections: &[Value]) -> String {
+    let mut out = format!("## Changes {from}..{to}\n");
+    let breaking: Vec<&Value> = entries
+        .iter()
+        .filter(|e| e["breaking"].as_bool() == Some(true))
+        .collect();
+    if !breaking.is_empty() {
+        out.push_str("\n### Breaking Changes\n\n");
+        for e in breaking {
+            out.push_str(&markdown_line(e));
+        }
+    }
+    for section in sections {
+        out.push_str(&format!(
+            "\n### {}\n\n",
+            section["title"].as_str().unwrap_or_default()
+        ));
+        for e in section["entries"].as_array().into_iter().flatten() {
+            out.push_str(&markdown_line(e));
+        }
+    }
+    if entries.is_empty() {
+        out.push_str("\n_No changes._\n");
+    }
+    out
+}
+
+fn changelog_schema() -> LoadedSchema {
+    let entry = json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["hash", "type", "scope", "subject", "body", "breaking", "source"],
+        "properties": {
+            "hash": { "type": "string", "minLength": 7 },
+            "type": { "enum": SECTIONS.iter().map(|(k, _)| *k).collect::<Vec<_>>() },
+            "scope": { "type": ["string", "null"] },
+            "subject": { "type": "string" },
+            "body": { "type": "array", "items": { "type": "string" } },
+            "breaking": { "type": "boolean" },
+            "source": { "enum": ["commitjson", "git"] }
+        }
+    });
+    LoadedSchema {
+        name: "changelog.schema.json".to_string(),
+        path: PathBuf::from("<inline>"),
+        value: json!({
+            "$id": "cx://schemas/changelog.v1",
+            "type": "object",
+            "additionalProperties": false,
+            "required": ["contract_version", "from", "to", "commits", "sections", "markdown"],
+            "properties": {
+                "contract_version": { "const": CHANGELOG_JSON_CONTRACT_VERSION },
+                "from": { "type": "string", "minLength": 1 },
+                "to": { "type": "string", "minLength": 1 },
+                "commits": { "type": "integer", "minimum": 0 },
+                "sections": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "additionalProperties": false,
+                        "required": ["type", "title", "entries"],
+                        "properties": {
+                            "type": { "type": "string" },
+                            "title": { "type": "string" },
+                            "entries": { "type": "array", "items": entry }
+                        }
+                    }
+                },
+                "markdown": { "type": "string" }
+            }
+        }),
+        id: Some("cx://schemas/changelog.v1".to_string()),
+    }
+}
+
+fn build_changelog(from: &str, to: &str) -> Result<Value, String> {
+    let recorded = recorded_commitjson();
+    let entries: Vec<Value> = collect_commits(from, to)?
+        .iter()
+        .map(|c| build_entry(c, &recorded))
+        .collect();
+    let sections: Vec<Value> = SECTIONS
+        .iter()
+        .filter_map(|(kind, title)| {
+            let items: Vec<Value> = entries
+                .iter()
+                .filter(|e| e["type"] == *kind)
+                .cloned()
+                .collect();
+            (!items.is_empty()).then(|| json!({"type": kind, "title": title, "entries": items}))
+        })
+        .collect();
+    let markdown = render_markdown(from, to, &entries, &sections);
+    let doc = json!({
+        "contract_version": CHANGELOG_JSON_CONTRACT_VERSION,
+        "from": from,
+        "to": to,
+        "commits": entries.len(),
+        "sections": sections,
+        "markdown": markdown,
+    });
+    validate_schema_instance(&changelog_schema(), &doc.to_string())
+}
+
+pub fn cmd_changelog(args: &[String]) -> i32 {
+    let mut from: Option<&str> = None;
+    let mut to = "HEAD";
+    let mut json_out = false;
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--from" => match it.next() {
+                Some(v) => from = Some(v),
+                None => return print_usage_error("changelog", CHANGELOG_USAGE),
+            },
+            "--to" => match it.next() {
+                Some(v) => to = v,
+                None => return print_usage_error("changelog", CHANGELOG_USAGE),
+            },
+            "--json" => json_out = true,
+            _ => return print_usage_error("changelog", CHANGELOG_USAGE),
+        }
+    }
+    let Some(from) = from else {
+        return print_usage_error("changelog", CHANGELOG_USAGE);
+    };
+    let doc = match build_changelog(from, to) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("changelog", &e),
+    };
+    if json_out {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&doc).unwrap_or_else(|_| doc.to_string())
+        );
+    } else {
+        print!("{}", doc["markdown"].as_str().unwrap_or_default());
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subjects_split_into_type_scope_and_breaking() {
+        let c = parse_subject("feat(api)!: drop v1 routes");
+        assert_eq!(c.kind, "feat");
+        assert_eq!(c.scope.as_deref(), Some("api"));
+        assert!(c.breaking);
+        assert_eq!(c.description, "drop v1 routes");
+        assert_eq!(parse_subject("Fix: typo").kind, "fix");
+        assert_eq!(parse_subject("wip: stuff").kind, "other");
+        let plain = parse_subject("Update README: wording");
+        assert_eq!(plain.kind, "other");
+        assert_eq!(plain.description, "Update README: wording");
+    }
+}
@@ -0,0 +1,495 @@
+use std::env;
+
+use crate::codex_args::parse_codex_args;
+use crate::command_names::{canonical_name, is_compat_name};
+use crate::config_layers::{setting_by_key, setting_env_value};
+
+/// How a global flag takes its value.
+#[derive(Clone, Copy)]
+pub enum GlobalArg {
+    /// `--flag VALUE` or `--flag=VALUE`; the str is the metavar shown in help.
+    Value(&'static str),
+    /// `--flag` alone; the str is the value stored for the setting.
+    Switch(&'static str),
+    /// Like `Value`, but may be given more than once; values are joined with spaces.
+    Repeat(&'static str),
+}
+
+/// What a global flag overrides.
+#[derive(Clone, Copy)]
+pub enum FlagTarget {
+    /// A known config setting; the value is validated like `config set`.
+    Setting(&'static str),
+    /// A plain env var read by the runtime (e.g. `CX_VERBOSITY`).
+    Env(&'static str),
+}
+
+/// A flag accepted before the command name. Each one exports an env var for
+/// this process, so it wins over the environment, state preferences and config
+/// files.
+pub struct GlobalFlag {
+    pub long: &'static str,
+    pub short: Option<&'static str>,
+    pub target: FlagTarget,
+    pub arg: GlobalArg,
+    pub help: &'static str,
+}
+
+pub const GLOBAL_FLAGS: &[GlobalFlag] = &[
+    GlobalFlag {
+        long: "--backend",
+        short: None,
+        target: FlagTarget::Setting("llm.backend"),
+        arg: GlobalArg::Value("NAME"),
+        help: "LLM backend for this run (codex|ollama|mock)",
+    },
+    GlobalFlag {
+        long: "--model",
+        short: None,
+        target: FlagTarget::Setting("llm.model"),
+        arg: GlobalArg::Value("NAME"),
+        help: "Codex model for this run",
+    },
+    GlobalFlag {
+        long: "--codex-arg",
+        short: None,
+        target: FlagTarget::Env("CX_CODEX_EXTRA_ARGS"),
+        arg: GlobalArg::Repeat("ARG"),
+        help: "Extra allowlisted `codex exec` option, repeatable (e.g. --codex-arg='--profile work')",
+    },
+    GlobalFlag {
+        long: "--mode",
+        short: None,
+        target: FlagTarget::Setting("runtime.mode"),
+        arg: GlobalArg::Value("MODE"),
+        help: "Execution mode (lean|deterministic|verbose)",
+    },
+    GlobalFlag {
+        long: "--timeout",
+        short: None,
+        target: FlagTarget::Setting("runtime.cmd_timeout_secs"),
+        arg: GlobalArg::Value("SECS"),
+        help: "External command timeout",
+    },
+    GlobalFlag {
+        long: "--relaxed",
+        short: None,
+        target: FlagTarget::Setting("runtime.schema_relaxed"),
+        arg: GlobalArg::Switch("true"),
+        help: "Relax strict schema enforcement",
+    },
+    GlobalFlag {
+        long: "--offline",
+        short: None,
+        target: FlagTarget::Setting("runtime.offline"),
+        arg: GlobalArg::Switch("true"),
+        help: "Refuse LLM and network calls; local commands still work",
+    },
+    GlobalFlag {
+        long: "--allow-nested",
+        short: None,
+        target: FlagTarget::Setting("runtime.nested"),
+        arg: GlobalArg::Switch("allow"),
+        help: "Let cx wrap its own LLM commands; nested runs log their parent_execution_id",
+    },
+    GlobalFlag {
+        long: "--quiet",
+        short: Some("-q"),
+        target: FlagTarget::Env("CX_VERBOSITY"),
+        arg: GlobalArg::Switch("0"),
+        help: "Suppress non-essential stderr (warnings, notes)",
+    },
+    GlobalFlag {
+        long: "--verbose",
+        short: Some("-v"),
+        target: FlagTarget::Env("CX_VERBOSITY"),
+        arg: GlobalArg::Switch("2"),
+        help: "Print timing, backend, capture and clip diagnostics to stderr",
+    },
+    GlobalFlag {
+        long: "--debug",
+        short: Some("-vv"),
+        target: FlagTarget::Env("CX_VERBOSITY"),
+        arg: GlobalArg::Switch("3"),
+        help: "Like --verbose, plus config-file resolution",
+    },
+];
+
+impl GlobalFlag {
+    pub fn usage(&self) -> String {
+        let names = match self.short {
+            Some(short) => format!("{short}, {}", self.long),
+            None => self.long.to_string(),
+        };
+        match self.arg {
+            GlobalArg::Value(meta) => format!("{names} {meta}"),
+            GlobalArg::Repeat(meta) => format!("{names} {meta}..."),
+            GlobalArg::Switch(_) => names,
+        }
+    }
+
+    /// Config setting key or env var name, for help output.
+    pub fn target_name(&self) -> &'static str {
+        match self.target {
+            FlagTarget::Setting(key) | FlagTarget::Env(key) => key,
+        }
+    }
+}
+
+/// `(env var, value)` pairs exported for the parsed global flags.
+pub type GlobalOverrides = Vec<(&'static str, String)>;
+
+fn global_flag(name: &str) -> Option<&'static GlobalFlag> {
+    GLOBAL_FLAGS
+        .iter()
+        .find(|f| f.long == name || f.short == Some(name))
+}
+
+fn flag_override(
+    long: &str,
+    target: FlagTarget,
+    raw: &str,
+) -> Result<(&'static str, String), String> {
+    match target {
+        FlagTarget::Setting(key) => {
+            let spec =
+                setting_by_key(key).ok_or_else(|| format!("{long}: unknown setting '{key}'"))?;
+            let value = setting_env_value(spec, raw).map_err(|e| format!("{long}: {e}"))?;
+            Ok((spec.env, value))
+        }
+        FlagTarget::Env(name) => Ok((name, raw.to_string())),
+    }
+}
+
+/// Split the global flags that precede the command name off `args` (argv,
+/// program name included). Returns the `(env var, value)` overrides and the
+/// remaining argv. Parsing stops at the first argument that is not a global
+/// flag, so command arguments are never reinterpreted.
+pub fn split_global_flags(args: &[String]) -> Result<(GlobalOverrides, Vec<String>), String> {
+    let mut overrides = Vec::new();
+    let mut i = 1usize;
+    while let Some(arg) = args.get(i) {
+        let (name, inline) = match arg.split_once('=') {
+            Some((n, v)) => (n, Some(v.to_string())),
+            None => (arg.as_str(), None),
+        };
+        let Some(flag) = global_flag(name) else {
+            break;
+        };
+        let raw = match (flag.arg, inline) {
+            (GlobalArg::Value(_) | GlobalArg::Repeat(_), Some(v)) => v,
+            (GlobalArg::Value(meta) | GlobalArg::Repeat(meta), None) => {
+                i += 1;
+                args.get(i)
+                    .cloned()
+                    .ok_or_else(|| format!("{} requires {meta}", flag.long))?
+            }
+            (GlobalArg::Switch(v), None) => v.to_string(),
+            (GlobalArg::Switch(_), Some(_)) => {
+                return Err(format!("{} does not take a value", flag.long));
+            }
+        };
+        if let GlobalArg::Repeat(_) = flag.arg {
+            let (name, value) = flag_override(flag.long, flag.target, &raw)?;
+            match overrides.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, prev)) => *prev = format!("{prev} {value}"),
+                None => overrides.push((name, value)),
+            }
+        } else {
+            overrides.push(flag_override(flag.long, flag.target, &raw)?);
+        }
+        i += 1;
+    }
+    if let Some((_, raw)) = overrides.iter().find(|(n, _)| *n == "CX_CODEX_EXTRA_ARGS") {
+        parse_codex_args(raw).map_err(|e| format!("--codex-arg: {e}"))?;
+    }
+    let mut rest = args[..1.min(args.len())].to_vec();
+    rest.extend_from_slice(args.get(i..).unwrap_or_default());
+    Ok((overrides, rest))
+}
+
+/// Commands that call an LLM backend; they accept `--timeout SECS` directly
+/// after their name (`cxrs next --timeout 30 cargo test`).
+pub const LLM_TIMEOUT_COMMANDS: &[&str] = &[
+    "cx",
+    "cxj",
+    "cxo",
+    "cxol",
+    "cxcopy",
+    "fix",
+    "followup",
+    "explain",
+    "next",
+    "fix-run",
+    "pipeline",
+    "diffsum",
+    "diffsum-staged",
+    "commitjson",
+    "commitmsg",
+    "replay",
+    "rerun",
+];
+
+/// Commands that print backend output and can stream it (`--stream`).
+pub const STREAM_COMMANDS: &[&str] = &["cx", "cxj", "cxo", "cxol"];
+
+/// Commands that capture a wrapped command and can pass it through (`--tee`).
+pub const TEE_COMMANDS: &[&str] = &["cx", "cxj", "cxo", "cxol", "fix"];
+
+/// Commands whose agent Markdown can be rendered on a terminal (`--plain` opts out).
+pub const RENDER_COMMANDS: &[&str] = &["cx", "cxo", "cxol"];
+
+/// A flag accepted directly after the name of one of `commands`, in any order
+/// with the other command flags. Like [`GlobalFlag`], it exports an env var for
+/// this process.
+pub struct CommandFlag {
+    pub long: &'static str,
+    pub commands: &'static [&'static str],
+    pub target: FlagTarget,
+    pub arg: GlobalArg,
+    pub help: &'static str,
+}
+
+pub const COMMAND_FLAGS: &[CommandFlag] = &[
+    CommandFlag {
+        long: "--timeout",
+        commands: LLM_TIMEOUT_COMMANDS,
+        target: FlagTarget::Setting("llm.timeout_secs"),
+        arg: GlobalArg::Value("SECS"),
+        help: "LLM backend timeout for this run (overrides CX_LLM_TIMEOUT_SECS)",
+    },
+    CommandFlag {
+        long: "--stream",
+        commands: STREAM_COMMANDS,
+        target: FlagTarget::Setting("runtime.stream"),
+        arg: GlobalArg::Switch("true"),
+        help: "Print model output as it arrives (CX_STREAM=1)",
+    },
+    CommandFlag {
+        long: "--tee",
+        commands: TEE_COMMANDS,
+        target: FlagTarget::Setting("runtime.tee"),
+        arg: GlobalArg::Switch("true"),
+        help: "Show the command's output live on stderr while capturing it (CX_TEE=1)",
+    },
+    CommandFlag {
+        long: "--plain",
+        commands: RENDER_COMMANDS,
+        target: FlagTarget::Setting("render.markdown"),
+        arg: GlobalArg::Switch("false"),
+        help: "Print the model's Markdown as-is even when CX_RENDER_MARKDOWN=1",
+    },
+];
+
+impl CommandFlag {
+    pub fn usage(&self) -> String {
+        match self.arg {
+            GlobalArg::Value(meta) => format!("{} {meta}", self.long),
+            GlobalArg::Repeat(meta) => format!("{} {meta}...", self.long),
+            GlobalArg::Switch(_) => self.long.to_string(),
+        }
+    }
+}
+
+/// Command flags that apply to the native command `name`.
+pub fn command_flags(name: &str) -> impl Iterator<Item = &'static CommandFlag> + '_ {
+    COMMAND_FLAGS
+        .iter()
+        .filter(move |f| f.commands.contains(&name))
+}
+
+/// Split the command flags that directly follow the command name off `args`
+/// (argv after the global flags). The command may be native (`next`) or compat
+/// (`cx cxnext`); parsing stops at the first argument that is not one of its
+/// flags.
+pub fn split_command_flags(args: &[String]) -> Result<(GlobalOverrides, Vec<String>), String> {
+    let name_idx = match args.get(2) {
+        Some(compat) if args[1] == "cx" && is_compat_name(compat) => 2,
+        _ => 1,
+    };
+    let Some(name) = args.get(name_idx).and_then(|n| canonical_name(n)) else {
+        return Ok((Vec::new(), args.to_vec()));
+    };
+    let mut overrides = Vec::new();
+    let mut i = name_idx + 1;
+    while let Some(arg) = args.get(i) {
+        let (flag_name, inline) = match arg.split_once('=') {
+            Some((n, v)) => (n, Some(v.to_string())),
+            None => (arg.as_str(), None),
+        };
+        let Some(flag) = command_flags(name).find(|f| f.long == flag_name) else {
+            break;
+        };
+        let raw = match (flag.arg, inline) {
+            (GlobalArg::Value(_) | GlobalArg::Repeat(_), Some(v)) => v,
+            (GlobalArg::Value(meta) | GlobalArg::Repeat(meta), None) => {
+                i += 1;
+                args.get(i)
+                    .cloned()
+                    .ok_or_else(|| format!("{} requires {meta}", flag.long))?
+            }
+            (GlobalArg::Switch(v), None) => v.to_string(),
+            (GlobalArg::Switch(_), Some(_)) => {
+                return Err(format!("{} does not take a value", flag.long));
+            }
+        };
+        overrides.push(flag_override(flag.long, flag.target, &raw)?);
+        i += 1;
+    }
+    let mut rest = args[..=name_idx].to_vec();
+    rest.extend_from_slice(&args[i..]);
+    Ok((overrides, rest))
+}
+
+/// Export the overrides from [`split_global_flags`] and [`split_command_flags`].
+/// Must run before `init_app_config` snapshots the environment.
+pub fn apply_global_flags(args: &[String]) -> Result<Vec<String>, String> {
+    let (global, rest) = split_global_flags(args)?;
+    let (command, rest) = split_command_flags(&rest)?;
+    for (name, value) in global.into_iter().chain(command) {
+        unsafe { env::set_var(name, value) };
+    }
+    Ok(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn leading_flags_become_env_overrides() {
+        let (ov, rest) = split_global_flags(&argv(&[
+            "cxrs",
+            "--backend=ollama",
+            "--relaxed",
+            "--timeout",
+            "30",
+            "next",
+            "--backend",
+            "x",
+        ]))
+        .expect("parse");
+        assert_eq!(
+            ov,
+            vec![
+                ("CX_LLM_BACKEND", "ollama".to_string()),
+                ("CX_SCHEMA_RELAXED", "1".to_string()),
+                ("CX_CMD_TIMEOUT_SECS", "30".to_string()),
+            ]
+        );
+        assert_eq!(rest, argv(&["cxrs", "next", "--backend", "x"]));
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let err = split_global_flags(&argv(&["cxrs", "--backend", "gpt", "next"]))
+            .expect_err("bad backend");
+        assert!(err.starts_with("--backend: llm.backend: expected one of codex|ollama|mock"));
+        assert!(split_global_flags(&argv(&["cxrs", "--timeout"])).is_err());
+        assert!(split_global_flags(&argv(&["cxrs", "--relaxed=1", "next"])).is_err());
+    }
+
+    #[test]
+    fn every_flag_targets_a_known_setting() {
+        for f in GLOBAL_FLAGS {
+            if let FlagTarget::Setting(key) = f.target {
+                assert!(setting_by_key(key).is_some(), "{}", f.long);
+            }
+        }
+        for f in COMMAND_FLAGS {
+            if let FlagTarget::Setting(key) = f.target {
+                assert!(setting_by_key(key).is_some(), "{}", f.long);
+            }
+        }
+    }
+
+    #[test]
+    fn command_flags_are_split_after_their_commands_only() {
+        let (ov, rest) = split_command_flags(&argv(&[
+            "cxrs",
+            "cxo",
+            "--stream",
+            "--timeout",
+            "30",
+            "cargo",
+            "test",
+        ]))
+        .expect("parse");
+        assert_eq!(
+            ov,
+            vec![
+                ("CX_STREAM", "1".to_string()),
+                ("CX_LLM_TIMEOUT_SECS", "30".to_string()),
+            ]
+        );
+        assert_eq!(rest, argv(&["cxrs", "cxo", "cargo", "test"]));
+
+        let (ov, rest) = split_command_flags(&argv(&["cxrs", "cx", "cxnext", "--timeout=5", "ls"]))
+            .expect("parse");
+        assert_eq!(ov, vec![("CX_LLM_TIMEOUT_SECS", "5".to_string())]);
+        assert_eq!(rest, argv(&["cxrs", "cx", "cxnext", "ls"]));
+
+        let (ov, rest) =
+            split_command_flags(&argv(&["cxrs", "fix", "--tee", "cargo", "build"])).expect("parse");
+        assert_eq!(ov, vec![("CX_TEE", "1".to_string())]);
+        assert_eq!(rest, argv(&["cxrs", "fix", "cargo", "build"]));
+
+        let args = argv(&["cxrs", "next", "--stream", "ls"]);
+        assert_eq!(
+            split_command_flags(&args).expect("parse"),
+            (Vec::new(), args)
+        );
+        assert!(split_command_flags(&argv(&["cxrs", "cxo", "--timeout", "x"])).is_err());
+    }
+
+    #[test]
+    fn every_command_flag_names_known_commands() {
+        for f in COMMAND_FLAGS {
+            for c in f.commands {
+                assert_eq!(canonical_name(c), Some(*c), "{} {c}", f.long);
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_codex_args_accumulate_and_are_validated() {
+        let (ov, rest) = split_global_flags(&argv(&[
+            "cxrs",
+            "--codex-arg=--profile work",
+            "--codex-arg",
+            "-m",
+            "--codex-arg=o4-mini",
+            "next",
+        ]))
+        .expect("parse");
+        assert_eq!(
+            ov,
+            vec![(
+                "CX_CODEX_EXTRA_ARGS",
+                "--profile work -m o4-mini".to_string()
+            )]
+        );
+        assert_eq!(rest, argv(&["cxrs", "next"]));
+        let err = split_global_flags(&argv(&["cxrs", "--codex-arg", "--full-auto", "next"]))
+            .expect_err("not allowlisted");
+        assert!(err.starts_with("--codex-arg: '--full-auto' is not an allowed codex argument"));
+    }
+
+    #[test]
+    fn short_verbosity_flags_set_levels() {
+        let (ov, rest) =
+            split_global_flags(&argv(&["cxrs", "-q", "-vv", "budget"])).expect("parse");
+        assert_eq!(
+            ov,
+            vec![
+                ("CX_VERBOSITY", "0".to_string()),
+                ("CX_VERBOSITY", "3".to_string())
+            ]
+        );
+        assert_eq!(rest, argv(&["cxrs", "budget"]));
+    }
+}
@@ -0,0 +1,207 @@
+use std::env;
+use std::io::{IsTerminal, Write};
+use std::process::Command;
+
+use crate::platform::bin_in_path;
+use crate::process::run_command_with_stdin_output_with_timeout;
+
+pub struct ClipboardProvider {
+    pub name: &'static str,
+    pub bin: Option<&'static str>,
+    pub args: &'static [&'static str],
+}
+
+const PROVIDERS: &[ClipboardProvider] = &[
+    ClipboardProvider {
+        name: "pbcopy",
+        bin: Some("pbcopy"),
+        args: &[],
+    },
+    ClipboardProvider {
+        name: "wl-copy",
+        bin: Some("wl-copy"),
+        args: &[],
+    },
+    ClipboardProvider {
+        name: "xclip",
+        bin: Some("xclip"),
+        args: &["-selection", "clipboard"],
+    },
+    ClipboardProvider {
+        name: "xsel",
+        bin: Some("xsel"),
+        args: &["--clipboard", "--input"],
+    },
+    ClipboardProvider {
+        name: "clip.exe",
+        bin: Some("clip.exe"),
+        args: &[],
+    },
+    ClipboardProvider {
+        name: "osc52",
+        bin: None,
+        args: &[],
+    },
+];
+
+pub fn provider_names() -> Vec<&'static str> {
+    PROVIDERS.iter().map(|p| p.name).collect()
+}
+
+fn provider_by_name(name: &str) -> Option<&'static ClipboardProvider> {
+    PROVIDERS.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+pub fn forced_provider_name() -> Option<String> {
+    env::var("CX_CLIPBOARD_PROVIDER")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty() && v != "auto")
+}
+
+fn env_set(name: &str) -> bool {
+    env::var_os(name).is_some_and(|v| !v.is_empty())
+}
+
+/// Preference order for autodetection: session-native tools first, then any
+/// other installed tool; OSC52 only when stdout is a terminal.
+fn preferred_order() -> Vec<&'static str> {
+    let mut order: Vec<&'static str> = Vec::new();
+    if cfg!(windows) {
+        order.push("clip.exe");
+    } else if cfg!(target_os = "macos") {
+        order.push("pbcopy");
+    } else {
+        if env_set("WAYLAND_DISPLAY") {
+            order.push("wl-copy");
+        }
+        if env_set("DISPLAY") {
+            order.extend(["xclip", "xsel"]);
+        }
+    }
+    for p in PROVIDERS {
+        if p.bin.is_some() && !order.contains(&p.name) {
+            order.push(p.name);
+        }
+    }
+    order
+}
+
+fn provider_available(p: &ClipboardProvider) -> bool {
+    match p.bin {
+        Some(bin) => bin_in_path(bin),
+        None => std::io::stdout().is_terminal(),
+    }
+}
+
+/// Providers to try for this process, honouring `CX_CLIPBOARD_PROVIDER`.
+pub fn detect_providers() -> Result<Vec<&'static ClipboardProvider>, String> {
+    if let Some(name) = forced_provider_name() {
+        let p = provider_by_name(&name).ok_or_else(|| {
+            format!(
+                "unknown CX_CLIPBOARD_PROVIDER '{name}' (expected one of: {})",
+                provider_names().join(", ")
+            )
+        })?;
+        if let Some(bin) = p.bin
+            && !bin_in_path(bin)
+        {
+            return Err(format!("clipboard provider '{name}' not found in PATH"));
+        }
+        return Ok(vec![p]);
+    }
+    let mut out: Vec<&'static ClipboardProvider> = preferred_order()
+        .into_iter()
+        .filter_map(provider_by_name)
+        .filter(|p| provider_available(p))
+        .collect();
+    if let Some(osc) = provider_by_name("osc52")
+        && provider_available(osc)
+    {
+        out.push(osc);
+    }
+    Ok(out)
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+pub fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))
+}
+
+fn copy_with(p: &ClipboardProvider, text: &str) -> Result<(), String> {
+    let Some(bin) = p.bin else {
+        let mut out = std::io::stdout();
+        return out
+            .write_all(osc52_sequence(text).as_bytes())
+            .and_then(|_| out.flush())
+            .map_err(|e| format!("osc52 write failed: {e}"));
+    };
+    let mut cmd = Command::new(bin);
+    cmd.args(p.args);
+    match run_command_with_stdin_output_with_timeout(cmd, text, p.name) {
+        Ok(out) if out.status.success() => Ok(()),
+        Ok(out) => Err(format!("{bin} exited with status {}", out.status)),
+        Err(e) => Err(format!("{bin} unavailable/failed: {e}")),
+    }
+}
+
+/// Copy `text` using the first working provider; returns the provider name.
+pub fn copy_text(text: &str) -> Result<&'static str, String> {
+    let providers = detect_providers()?;
+    if providers.is_empty() {
+        return Err(format!(
+            "no clipboard provider found (tried: {})",
+            provider_names().join(", ")
+        ));
+    }
+    let mut failures: Vec<String> = Vec::new();
+    for p in providers {
+        match copy_with(p, text) {
+            Ok(()) => return Ok(p.name),
+            Err(e) => failures.push(e),
+        }
+    }
+    Err(format!(
+        "all clipboard backends failed: {}",
+        failures.join("; ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_matches_rfc4648_vectors() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn osc52_sequence_wraps_payload() {
+        assert_eq!(osc52_sequence("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
@@ -0,0 +1,200 @@
+use crate::config::app_config;
+
+/// `codex exec` options cxrs passes through: the ones that pick a profile,
+/// model or provider. Sandboxing, approvals, the working directory and the
+/// output format stay under cxrs' control. `(long, short)`.
+const VALUE_FLAGS: &[(&str, Option<&str>)] = &[
+    ("--profile", Some("-p")),
+    ("--model", Some("-m")),
+    ("--local-provider", None),
+];
+const SWITCH_FLAGS: &[&str] = &["--oss", "--skip-git-repo-check"];
+/// Keys `-c key=value` may override.
+const CONFIG_KEYS: &[&str] = &[
+    "model_reasoning_effort",
+    "model_reasoning_summary",
+    "model_verbosity",
+];
+
+/// One validated option, normalized to its long name (`-c` stays `-c`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodexArg {
+    pub flag: &'static str,
+    pub value: Option<String>,
+}
+
+impl CodexArg {
+    /// Options with the same key replace each other; `-c` is keyed per setting.
+    fn key(&self) -> String {
+        match (self.flag, &self.value) {
+            ("-c", Some(v)) => format!("-c {}", v.split_once('=').map_or(v.as_str(), |p| p.0)),
+            (flag, _) => flag.to_string(),
+        }
+    }
+}
+
+fn allowed_list() -> String {
+    let mut names: Vec<&str> = VALUE_FLAGS.iter().map(|(long, _)| *long).collect();
+    names.extend(SWITCH_FLAGS);
+    names.push("-c");
+    names.join(", ")
+}
+
+fn value_flag(name: &str) -> Option<&'static str> {
+    VALUE_FLAGS
+        .iter()
+        .find(|(long, short)| *long == name || *short == Some(name))
+        .map(|(long, _)| *long)
+}
+
+fn config_override(value: &str) -> Result<String, String> {
+    let key = value.split_once('=').map(|p| p.0.trim()).unwrap_or("");
+    if CONFIG_KEYS.contains(&key) {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "-c {value}: only {} may be overridden",
+            CONFIG_KEYS.join(", ")
+        ))
+    }
+}
+
+/// Parse codex args from whitespace- or comma-separated words
+/// (`--profile work -m o4-mini`, `--model=o4-mini`), rejecting anything off
+/// the allowlist.
+pub fn parse_codex_args(raw: &str) -> Result<Vec<CodexArg>, String> {
+    let mut words = raw
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty());
+    let mut out = Vec::new();
+    while let Some(word) = words.next() {
+        let (name, inline) = match word.split_once('=') {
+            Some((n, v)) if word.starts_with("--") => (n, Some(v.to_string())),
+            _ => (word, None),
+        };
+        let mut value = |flag: &str| {
+            inline
+                .clone()
+                .or_else(|| words.next().map(str::to_string))
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| format!("{flag} requires a value"))
+        };
+        let arg = if let Some(flag) = value_flag(name) {
+            CodexArg {
+                flag,
+                value: Some(value(flag)?),
+            }
+        } else if matches!(name, "-c" | "--config") {
+            CodexArg {
+                flag: "-c",
+                value: Some(config_override(&value("-c")?)?),
+            }
+        } else if let Some(flag) = SWITCH_FLAGS.iter().find(|f| **f == name) {
+            if inline.is_some() {
+                return Err(format!("{flag} does not take a value"));
+            }
+            CodexArg { flag, value: None }
+        } else {
+            return Err(format!(
+                "'{word}' is not an allowed codex argument (allowed: {})",
+                allowed_list()
+            ));
+        };
+        out.push(arg);
+    }
+    Ok(out)
+}
+
+/// Append `extra` to `base`; a later option replaces an earlier one with the
+/// same key so codex never sees it twice.
+fn merge(base: Vec<CodexArg>, extra: Vec<CodexArg>) -> Vec<CodexArg> {
+    let mut out: Vec<CodexArg> = Vec::new();
+    for arg in base.into_iter().chain(extra) {
+        let key = arg.key();
+        out.retain(|a| a.key() != key);
+        out.push(arg);
+    }
+    out
+}
+
+/// Configured args (`CX_CODEX_ARGS`, state `preferences.codex_args`, config
+/// `llm.codex_args`) followed by the `--codex-arg` values of this run.
+pub fn resolved_codex_args() -> Result<Vec<CodexArg>, String> {
+    let cfg = app_config();
+    let base = parse_codex_args(&cfg.codex_args).map_err(|e| format!("CX_CODEX_ARGS: {e}"))?;
+    let extra = parse_codex_args(&cfg.codex_extra_args).map_err(|e| format!("--codex-arg: {e}"))?;
+    Ok(merge(base, extra))
+}
+
+/// Argv words for `args`, inserted right after `codex exec`.
+pub fn render_codex_args(args: &[CodexArg]) -> Vec<String> {
+    let mut out = Vec::new();
+    for arg in args {
+        out.push(arg.flag.to_string());
+        if let Some(v) = &arg.value {
+            out.push(v.clone());
+        }
+    }
+    out
+}
+
+/// Value of `flag` (long name) in `args`, e.g. the profile or model a run used.
+pub fn codex_arg_value<'a>(args: &'a [CodexArg], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .find(|a| a.flag == flag)
+        .and_then(|a| a.value.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowlisted_args_are_normalized_and_others_rejected() {
+        let args =
+            parse_codex_args("--profile work, -m o4-mini --oss -c model_reasoning_effort=high")
+                .expect("parse");
+        assert_eq!(
+            render_codex_args(&args),
+            [
+                "--profile",
+                "work",
+                "--model",
+                "o4-mini",
+                "--oss",
+                "-c",
+                "model_reasoning_effort=high"
+            ]
+        );
+        assert_eq!(codex_arg_value(&args, "--profile"), Some("work"));
+        assert!(
+            parse_codex_args("--sandbox danger-full-access")
+                .expect_err("sandbox")
+                .contains("not an allowed codex argument")
+        );
+        assert!(parse_codex_args("-c sandbox_mode=danger-full-access").is_err());
+        assert!(parse_codex_args("--model").is_err());
+        assert!(parse_codex_args("--oss=1").is_err());
+    }
+
+    #[test]
+    fn later_args_replace_earlier_ones_with_the_same_key() {
+        let base =
+            parse_codex_args("--profile work -m gpt-5 -c model_verbosity=low").expect("base");
+        let extra =
+            parse_codex_args("--model=o4-mini -c model_reasoning_effort=high").expect("extra");
+        assert_eq!(
+            render_codex_args(&merge(base, extra)),
+            [
+                "--profile",
+                "work",
+                "-c",
+                "model_verbosity=low",
+                "--model",
+                "o4-mini",
+                "-c",
+                "model_reasoning_effort=high"
+            ]
+        );
+    }
+}
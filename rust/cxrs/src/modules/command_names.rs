@@ -1,174 +1,137 @@
-const COMPAT_NAMES: &[&str] = &[
-    "help",
-    "cxversion",
-    "version",
-    "cxdoctor",
-    "doctor",
-    "cxwhere",
-    "where",
-    "cxroutes",
-    "routes",
-    "cxdiag",
-    "diag",
-    "cxscheduler",
-    "scheduler",
-    "cxparity",
-    "parity",
-    "cxcore",
-    "core",
-    "cxlogs",
-    "logs",
-    "cxtelemetry",
-    "telemetry",
-    "cxtask",
-    "task",
-    "cxmetrics",
-    "metrics",
-    "cxquota",
-    "quota",
-    "cxprompt_stats",
-    "prompt-stats",
-    "cxprofile",
-    "profile",
-    "cxtrace",
-    "trace",
-    "cxalert",
-    "alert",
-    "cxoptimize",
-    "optimize",
-    "cxworklog",
-    "worklog",
-    "cx",
-    "cxj",
-    "cxo",
-    "cxol",
-    "cxcopy",
-    "cxpolicy",
-    "policy",
-    "cxbroker",
-    "broker",
-    "cxstate",
-    "state",
-    "cxllm",
-    "llm",
-    "cxbench",
-    "bench",
-    "cxprompt",
-    "prompt",
-    "cxroles",
-    "roles",
-    "cxfanout",
-    "fanout",
-    "cxpromptlint",
-    "promptlint",
-    "cxnext",
-    "next",
-    "cxfix",
-    "fix",
-    "cxdiffsum",
-    "diffsum",
-    "cxdiffsum_staged",
-    "diffsum-staged",
-    "cxcommitjson",
-    "commitjson",
-    "cxcommitmsg",
-    "commitmsg",
-    "cxbudget",
-    "budget",
-    "cxlog_tail",
-    "log-tail",
-    "cxhealth",
-    "health",
-    "capture-status",
-    "cxlog_on",
-    "log-on",
-    "cxlog_off",
-    "log-off",
-    "cxalert_show",
-    "alert-show",
-    "cxalert_on",
-    "alert-on",
-    "cxalert_off",
-    "alert-off",
-    "cxchunk",
-    "chunk",
-    "cxfix_run",
-    "fix-run",
-    "cxreplay",
-    "replay",
-    "cxquarantine",
-    "quarantine",
-    "schema",
-];
+/// One top-level command. `compat` marks names the `cx` compat dispatcher also
+/// accepts; `alias` is the legacy bash-style spelling (`cxdiffsum_staged`).
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub alias: Option<&'static str>,
+    pub compat: bool,
+}
+
+const fn cmd(name: &'static str) -> CommandSpec {
+    CommandSpec {
+        name,
+        alias: None,
+        compat: true,
+    }
+}
+
+const fn with_alias(name: &'static str, alias: &'static str) -> CommandSpec {
+    CommandSpec {
+        name,
+        alias: Some(alias),
+        compat: true,
+    }
+}
 
-const NATIVE_NAMES: &[&str] = &[
-    "help",
-    "-h",
-    "--help",
-    "version",
-    "-V",
-    "--version",
-    "where",
-    "routes",
-    "diag",
-    "scheduler",
-    "parity",
-    "core",
-    "logs",
-    "telemetry",
-    "ci",
-    "task",
-    "doctor",
-    "state",
-    "llm",
-    "policy",
-    "broker",
-    "bench",
-    "metrics",
-    "quota",
-    "prompt-stats",
-    "prompt",
-    "roles",
-    "fanout",
-    "promptlint",
-    "cx",
-    "cxj",
-    "cxo",
-    "cxol",
-    "cxcopy",
-    "fix",
-    "budget",
-    "log-tail",
-    "health",
-    "capture-status",
-    "log-on",
-    "log-off",
-    "alert-show",
-    "alert-on",
-    "alert-off",
-    "chunk",
-    "cx-compat",
-    "profile",
-    "alert",
-    "optimize",
-    "worklog",
-    "trace",
-    "next",
-    "fix-run",
-    "diffsum",
-    "diffsum-staged",
-    "commitjson",
-    "commitmsg",
-    "replay",
-    "quarantine",
-    "supports",
-    "schema",
+const fn native_only(name: &'static str) -> CommandSpec {
+    CommandSpec {
+        name,
+        alias: None,
+        compat: false,
+    }
+}
+
+/// Flag spellings of `help`/`version` accepted as the native command name.
+const NATIVE_FLAG_NAMES: &[&str] = &["-h", "--help", "-V", "--version"];
+
+pub const COMMANDS: &[CommandSpec] = &[
+    cmd("help"),
+    with_alias("version", "cxversion"),
+    with_alias("where", "cxwhere"),
+    with_alias("routes", "cxroutes"),
+    with_alias("diag", "cxdiag"),
+    with_alias("scheduler", "cxscheduler"),
+    with_alias("parity", "cxparity"),
+    with_alias("core", "cxcore"),
+    with_alias("logs", "cxlogs"),
+    with_alias("telemetry", "cxtelemetry"),
+    native_only("ci"),
+    native_only("hook"),
+    with_alias("task", "cxtask"),
+    with_alias("doctor", "cxdoctor"),
+    with_alias("state", "cxstate"),
+    with_alias("llm", "cxllm"),
+    native_only("model"),
+    native_only("workspace"),
+    native_only("init"),
+    native_only("gc"),
+    native_only("annotate"),
+    native_only("secret"),
+    native_only("changelog"),
+    native_only("config"),
+    with_alias("policy", "cxpolicy"),
+    with_alias("broker", "cxbroker"),
+    with_alias("bench", "cxbench"),
+    with_alias("metrics", "cxmetrics"),
+    with_alias("quota", "cxquota"),
+    with_alias("prompt-stats", "cxprompt_stats"),
+    with_alias("prompt", "cxprompt"),
+    with_alias("roles", "cxroles"),
+    with_alias("fanout", "cxfanout"),
+    with_alias("promptlint", "cxpromptlint"),
+    cmd("cx"),
+    cmd("cxj"),
+    cmd("cxo"),
+    cmd("cxol"),
+    cmd("cxcopy"),
+    with_alias("fix", "cxfix"),
+    native_only("followup"),
+    native_only("explain"),
+    native_only("custom"),
+    native_only("session"),
+    with_alias("budget", "cxbudget"),
+    with_alias("log-tail", "cxlog_tail"),
+    with_alias("health", "cxhealth"),
+    native_only("selftest"),
+    cmd("capture-status"),
+    with_alias("log-on", "cxlog_on"),
+    with_alias("log-off", "cxlog_off"),
+    with_alias("alert-show", "cxalert_show"),
+    with_alias("alert-on", "cxalert_on"),
+    with_alias("alert-off", "cxalert_off"),
+    with_alias("chunk", "cxchunk"),
+    native_only("pack"),
+    native_only("cx-compat"),
+    with_alias("profile", "cxprofile"),
+    with_alias("alert", "cxalert"),
+    with_alias("optimize", "cxoptimize"),
+    with_alias("worklog", "cxworklog"),
+    with_alias("trace", "cxtrace"),
+    native_only("transcript"),
+    native_only("diff-runs"),
+    native_only("trends"),
+    native_only("digest"),
+    native_only("watch"),
+    with_alias("next", "cxnext"),
+    with_alias("fix-run", "cxfix_run"),
+    native_only("pipeline"),
+    with_alias("diffsum", "cxdiffsum"),
+    with_alias("diffsum-staged", "cxdiffsum_staged"),
+    with_alias("commitjson", "cxcommitjson"),
+    with_alias("commitmsg", "cxcommitmsg"),
+    native_only("stage"),
+    with_alias("replay", "cxreplay"),
+    native_only("rerun"),
+    with_alias("quarantine", "cxquarantine"),
+    native_only("supports"),
+    cmd("schema"),
+    native_only("exit-codes"),
+    native_only("redact-test"),
 ];
 
 pub fn is_compat_name(name: &str) -> bool {
-    COMPAT_NAMES.contains(&name)
+    COMMANDS
+        .iter()
+        .any(|c| (c.compat && c.name == name) || c.alias == Some(name))
+}
+
+/// Native name for a native name or compat alias (`cxdiffsum_staged` -> `diffsum-staged`).
+pub fn canonical_name(name: &str) -> Option<&'static str> {
+    COMMANDS
+        .iter()
+        .find(|c| c.name == name || c.alias == Some(name))
+        .map(|c| c.name)
 }
 
 pub fn is_native_name(name: &str) -> bool {
-    NATIVE_NAMES.contains(&name)
+    NATIVE_FLAG_NAMES.contains(&name) || COMMANDS.iter().any(|c| c.name == name)
 }
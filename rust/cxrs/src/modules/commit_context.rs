@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::process::run_command_output_with_timeout;
+
+/// Directory names that say nothing about what changed.
+const GENERIC_DIRS: &[&str] = &[
+    "src", "lib", "app", "pkg", "internal", "modules", "crates", "packages", "rust", "cmd",
+];
+
+/// One staged file from `git diff --numstat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    pub path: String,
+    /// Previous path when git detected a rename.
+    pub renamed_from: Option<String>,
+    /// `None` for binary files.
+    pub added: Option<u64>,
+    pub removed: Option<u64>,
+}
+
+impl FileStat {
+    pub fn language(&self) -> &'static str {
+        language_for(&self.path)
+    }
+}
+
+pub fn language_for(path: &str) -> &'static str {
+    let p = Path::new(path);
+    let name = p.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+    match name {
+        "Dockerfile" => return "Docker",
+        "Makefile" => return "Make",
+        "Cargo.toml" | "Cargo.lock" => return "Cargo",
+        "package.json" | "package-lock.json" => return "npm",
+        _ => {}
+    }
+    match p.extension().and_then(|s| s.to_str()).unwrap_or_default() {
+        "rs" => "Rust",
+        "sh" | "bash" => "Shell",
+        "py" => "Python",
+        "js" | "mjs" | "cjs" => "JavaScript",
+        "ts" | "tsx" => "TypeScript",
+        "go" => "Go",
+        "java" => "Java",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "hpp" => "C++",
+        "rb" => "Ruby",
+        "md" => "Markdown",
+        "json" | "jsonl" => "JSON",
+        "toml" => "TOML",
+        "yml" | "yaml" => "YAML",
+        "html" | "css" => "Web",
+        "sql" => "SQL",
+        _ => "Other",
+    }
+}
+
+fn parse_count(s: &str) -> Option<u64> {
+    s.parse().ok()
+}
+
+/// Parse `git diff --numstat -z` output; renames arrive as
+/// `add\tdel\t\0old\0new\0`, other files as `add\tdel\tpath\0`.
+pub fn parse_numstat_z(raw: &str) -> Vec<FileStat> {
+    let mut out = Vec::new();
+    let mut fields = raw.split('\0');
+    while let Some(head) = fields.next() {
+        let mut parts = head.splitn(3, '\t');
+        let (Some(a), Some(d), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let (path, renamed_from) = if path.is_empty() {
+            let old = fields.next().unwrap_or_default().to_string();
+            let new = fields.next().unwrap_or_default().to_string();
+            (new, Some(old))
+        } else {
+            (path.to_string(), None)
+        };
+        out.push(FileStat {
+            path,
+            renamed_from,
+            added: parse_count(a),
+            removed: parse_count(d),
+        });
+    }
+    out
+}
+
+pub fn staged_file_stats() -> Result<Vec<FileStat>, String> {
+    let mut cmd = Command::new("git");
+    cmd.args(["diff", "--staged", "--numstat", "-M", "-z"]);
+    let out = run_command_output_with_timeout(cmd, "git diff --staged --numstat")?;
+    if !out.status.success() {
+        return Err(format!(
+            "git diff --numstat failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(parse_numstat_z(&String::from_utf8_lossy(&out.stdout)))
+}
+
+fn file_stem(path: &str) -> Option<String> {
+    Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(|s| s.trim_end_matches("_tests").trim_end_matches("_test"))
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Conventional-commit scope from the touched paths: `docs`/`ci` for
+/// doc- or workflow-only changes, else the deepest meaningful directory the
+/// files share, else a shared file stem (e.g. `src/health.rs` + `tests/health_tests.rs`).
+pub fn infer_scope(files: &[FileStat]) -> Option<String> {
+    if files.is_empty() {
+        return None;
+    }
+    if files.iter().all(|f| f.language() == "Markdown") {
+        return Some("docs".to_string());
+    }
+    if files.iter().all(|f| f.path.starts_with(".github/")) {
+        return Some("ci".to_string());
+    }
+    let dirs: Vec<Vec<&str>> = files
+        .iter()
+        .map(|f| {
+            let mut parts: Vec<&str> = f.path.split('/').collect();
+            parts.pop();
+            parts
+        })
+        .collect();
+    let mut common: Vec<&str> = dirs[0].clone();
+    for d in &dirs[1..] {
+        let n = common.iter().zip(d).take_while(|(a, b)| a == b).count();
+        common.truncate(n);
+    }
+    let stems: Vec<Option<String>> = files.iter().map(|f| file_stem(&f.path)).collect();
+    let shared_stem = stems[0]
+        .clone()
+        .filter(|s| stems.iter().all(|t| t.as_deref() == Some(s.as_str())));
+    // The common dir names the area only when it is specific and files live in it.
+    let deepest_specific = common
+        .iter()
+        .rev()
+        .find(|c| !GENERIC_DIRS.contains(c) && !c.starts_with('.'));
+    let files_directly_under_generic = common.last().is_none_or(|c| GENERIC_DIRS.contains(c));
+    match (shared_stem, deepest_specific) {
+        (Some(stem), _) if files_directly_under_generic || files.len() > 1 => Some(stem),
+        (_, Some(dir)) => Some((*dir).to_string()),
+        (stem, None) => stem,
+    }
+    .map(|s| s.to_ascii_lowercase())
+}
+
+/// Plain-text summary fed to the commitjson prompt.
+pub fn render_stats(files: &[FileStat]) -> String {
+    let total = |f: fn(&FileStat) -> Option<u64>| files.iter().filter_map(f).sum::<u64>();
+    let mut out = format!(
+        "Files changed: {} (+{} -{})\n",
+        files.len(),
+        total(|f| f.added),
+        total(|f| f.removed)
+    );
+    let mut langs: BTreeMap<&str, usize> = BTreeMap::new();
+    for f in files {
+        let counts = match (f.added, f.removed) {
+            (Some(a), Some(d)) => format!("+{a} -{d}"),
+            _ => "binary".to_string(),
+        };
+        match &f.renamed_from {
+            Some(old) => out.push_str(&format!(
+                "- {old} -> {} [{}] {counts} (renamed)\n",
+                f.path,
+                f.language()
+            )),
+            None => out.push_str(&format!("- {} [{}] {counts}\n", f.path, f.language())),
+        }
+        *langs.entry(f.language()).or_default() += 1;
+    }
+    let langs: Vec<String> = langs.iter().map(|(l, n)| format!("{l} ({n})")).collect();
+    out.push_str(&format!("Languages: {}", langs.join(", ")));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(path: &str) -> FileStat {
+        FileStat {
+            path: path.to_string(),
+            renamed_from: None,
+            added: Some(1),
+            removed: Some(0),
+        }
+    }
+
+    #[test]
+    fn numstat_parses_renames_and_binaries() {
+        let raw = "3\t1\tsrc/a.rs\0-\t-\tlogo.png\x000\t0\t\0old/b.rs\0new/b.rs\0";
+        let files = parse_numstat_z(raw);
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].added, Some(3));
+        assert_eq!(files[1].added, None);
+        assert_eq!(files[2].path, "new/b.rs");
+        assert_eq!(files[2].renamed_from.as_deref(), Some("old/b.rs"));
+        let text = render_stats(&files);
+        assert!(text.starts_with("Files changed: 3 (+3 -1)\n"), "{text}");
+        assert!(text.contains("- old/b.rs -> new/b.rs [Rust] +0 -0 (renamed)"));
+        assert!(text.contains("logo.png [Other] binary"));
+    }
+
+    #[test]
+    fn scope_comes_from_shared_area() {
+        let scope =
+            |paths: &[&str]| infer_scope(&paths.iter().map(|p| stat(p)).collect::<Vec<_>>());
+        assert_eq!(
+            scope(&["rust/cxrs/src/modules/health.rs"]),
+            Some("health".into())
+        );
+        assert_eq!(
+            scope(&["src/modules/health.rs", "tests/health_tests.rs"]),
+            Some("health".into())
+        );
+        assert_eq!(
+            scope(&["services/billing/api.go", "services/billing/db.go"]),
+            Some("billing".into())
+        );
+        assert_eq!(scope(&["README.md", "docs/x.md"]), Some("docs".into()));
+        assert_eq!(scope(&[".github/workflows/ci.yml"]), Some("ci".into()));
+        assert_eq!(scope(&["src/a.rs", "src/b.rs"]), None);
+        assert_eq!(scope(&[]), None);
+    }
+}
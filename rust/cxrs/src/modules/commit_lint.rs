@@ -0,0 +1,248 @@
+use serde_json::Value;
+
+use crate::changelog::{SECTIONS, parse_subject};
+
+pub const SUBJECT_MAX_CHARS: usize = 72;
+pub const BODY_WRAP_COLUMNS: usize = 72;
+
+/// Common non-imperative openers and their imperative form.
+const MOOD_FIXES: &[(&str, &str)] = &[
+    ("added", "add"),
+    ("adds", "add"),
+    ("adding", "add"),
+    ("fixed", "fix"),
+    ("fixes", "fix"),
+    ("fixing", "fix"),
+    ("updated", "update"),
+    ("updates", "update"),
+    ("updating", "update"),
+    ("removed", "remove"),
+    ("removes", "remove"),
+    ("removing", "remove"),
+    ("changed", "change"),
+    ("changes", "change"),
+    ("implemented", "implement"),
+    ("implements", "implement"),
+    ("improved", "improve"),
+    ("improves", "improve"),
+    ("refactored", "refactor"),
+    ("refactors", "refactor"),
+    ("renamed", "rename"),
+    ("renames", "rename"),
+    ("moved", "move"),
+    ("moves", "move"),
+    ("created", "create"),
+    ("creates", "create"),
+    ("introduced", "introduce"),
+    ("introduces", "introduce"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct LintReport {
+    /// Rules fixed in place, in the order applied.
+    pub fixed: Vec<&'static str>,
+    /// Violations left after auto-fixing.
+    pub issues: Vec<LintIssue>,
+}
+
+fn issue(rule: &'static str, message: String) -> LintIssue {
+    LintIssue { rule, message }
+}
+
+/// Rewrite a known non-imperative first word, keeping its capitalisation.
+fn fix_mood(text: &str) -> Option<String> {
+    let (word, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let lower = word.to_ascii_lowercase();
+    let (_, fixed) = MOOD_FIXES.iter().find(|(from, _)| *from == lower)?;
+    let mut out = if word.starts_with(|c: char| c.is_ascii_uppercase()) {
+        let mut c = fixed.chars();
+        c.next()
+            .map(|f| f.to_ascii_uppercase().to_string() + c.as_str())
+            .unwrap_or_default()
+    } else {
+        fixed.to_string()
+    };
+    if !rest.is_empty() {
+        out.push(' ');
+        out.push_str(rest);
+    }
+    Some(out)
+}
+
+/// `-ed`/`-ing` openers that are not in the fix table are only reported.
+fn mood_issue(text: &str) -> Option<LintIssue> {
+    let word = text.split_whitespace().next()?.to_ascii_lowercase();
+    let suspicious = word.len() > 4 && (word.ends_with("ed") || word.ends_with("ing"));
+    suspicious.then(|| {
+        issue(
+            "imperative_mood",
+            format!("start the subject with an imperative verb, not '{word}'"),
+        )
+    })
+}
+
+/// Lowercase a leading capital unless it starts an acronym (`API`, `CI`).
+fn lower_first(text: &str) -> Option<String> {
+    let mut chars = text.chars();
+    let first = chars.next()?;
+    let second = chars.next();
+    if !first.is_ascii_uppercase() || second.is_some_and(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    Some(first.to_ascii_lowercase().to_string() + &text[first.len_utf8()..])
+}
+
+fn lint_subject(subject: &str, breaking: bool, conventional: bool) -> (String, LintReport) {
+    let mut report = LintReport::default();
+    let mut subject = subject.trim().to_string();
+    if subject.ends_with('.') && !subject.ends_with("..") {
+        subject.pop();
+        report.fixed.push("trailing_period");
+    }
+    let (mut head, mut desc) = match subject.split_once(": ") {
+        Some((h, d)) if conventional => (format!("{h}: "), d.to_string()),
+        _ => (String::new(), subject.clone()),
+    };
+    if conventional {
+        let parsed = parse_subject(&subject);
+        if parsed.kind == "other" {
+            let kinds: Vec<&str> = SECTIONS
+                .iter()
+                .map(|(k, _)| *k)
+                .filter(|k| *k != "other")
+                .collect();
+            report.issues.push(issue(
+                "conventional_format",
+                format!(
+                    "subject is not `type(scope): description` (types: {})",
+                    kinds.join(", ")
+                ),
+            ));
+            head.clear();
+            desc = subject.clone();
+        } else {
+            if breaking && !parsed.breaking {
+                head = head.replacen(": ", "!: ", 1);
+                report.fixed.push("breaking_marker");
+            }
+            if let Some(lowered) = lower_first(&desc) {
+                desc = lowered;
+                report.fixed.push("description_case");
+            }
+        }
+    }
+    if let Some(fixed) = fix_mood(&desc) {
+        desc = fixed;
+        report.fixed.push("imperative_mood");
+    } else if let Some(i) = mood_issue(&desc) {
+        report.issues.push(i);
+    }
+    let subject = format!("{head}{desc}");
+    let len = subject.chars().count();
+    if len > SUBJECT_MAX_CHARS {
+        report.issues.push(issue(
+            "subject_length",
+            format!("subject is {len} chars (max {SUBJECT_MAX_CHARS})"),
+        ));
+    }
+    (subject, report)
+}
+
+/// Lint a commitjson object and apply the safe fixes in place: trailing
+/// period, `!` for breaking changes, description case and common
+/// non-imperative openers. Length, type grammar and unknown moods are reported.
+pub fn lint_commit(v: &mut Value, conventional: bool) -> LintReport {
+    let subject = v.get("subject").and_then(Value::as_str).unwrap_or_default();
+    let breaking = v.get("breaking").and_then(Value::as_bool).unwrap_or(false);
+    let (fixed_subject, report) = lint_subject(subject, breaking, conventional);
+    if !report.fixed.is_empty()
+        && let Some(obj) = v.as_object_mut()
+    {
+        obj.insert("subject".to_string(), Value::String(fixed_subject));
+    }
+    report
+}
+
+/// Re-prompt feedback listing the violations the model must fix.
+pub fn lint_feedback(issues: &[LintIssue]) -> String {
+    let mut out = String::from(
+        "\n\nThe previous commit message broke these rules; return a corrected one:\n",
+    );
+    for i in issues {
+        out.push_str(&format!("- {}: {}\n", i.rule, i.message));
+    }
+    out
+}
+
+/// `- item` wrapped at [`BODY_WRAP_COLUMNS`] with a two-space hanging indent.
+pub fn wrap_bullet(item: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::from("-");
+    for word in item.split_whitespace() {
+        if line.len() > 2 && line.chars().count() + 1 + word.chars().count() > BODY_WRAP_COLUMNS {
+            lines.push(std::mem::replace(&mut line, " ".to_string()));
+        }
+        line.push(' ');
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn fixes_safe_violations_and_reports_the_rest() {
+        let mut v = json!({"subject": "feat(api): Added retries.", "breaking": true});
+        let report = lint_commit(&mut v, true);
+        assert_eq!(v["subject"], "feat(api)!: add retries");
+        assert_eq!(
+            report.fixed,
+            vec![
+                "trailing_period",
+                "breaking_marker",
+                "description_case",
+                "imperative_mood"
+            ]
+        );
+        assert!(report.issues.is_empty());
+
+        let mut v = json!({"subject": "feat: API tokens rotated"});
+        assert!(lint_commit(&mut v, true).issues.is_empty());
+        assert_eq!(v["subject"], "feat: API tokens rotated");
+
+        let long = format!("fix: {}", "x".repeat(80));
+        let mut v = json!({"subject": "Streamlined the parser"});
+        let rules: Vec<&str> = lint_commit(&mut v, true)
+            .issues
+            .iter()
+            .map(|i| i.rule)
+            .collect();
+        assert_eq!(rules, vec!["conventional_format", "imperative_mood"]);
+        let mut v = json!({"subject": long});
+        assert_eq!(lint_commit(&mut v, true).issues[0].rule, "subject_length");
+        let mut v = json!({"subject": "Fixes login"});
+        assert!(lint_commit(&mut v, false).issues.is_empty());
+        assert_eq!(v["subject"], "Fix login");
+    }
+
+    #[test]
+    fn bullets_wrap_with_hanging_indent() {
+        let item = "word ".repeat(30);
+        let lines = wrap_bullet(item.trim());
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| l.chars().count() <= BODY_WRAP_COLUMNS));
+        assert!(lines[0].starts_with("- word"));
+        assert!(lines[1].starts_with("  word"));
+        assert_eq!(wrap_bullet("short"), vec!["- short"]);
+    }
+}
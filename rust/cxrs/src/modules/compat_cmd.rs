@@ -2,10 +2,9 @@
 mod compat_dispatch;
 
 pub struct CompatDeps {
-    pub print_help: fn(),
-    pub print_task_help: fn(),
+    pub cmd_help: fn(&[String]) -> i32,
     pub print_version: fn(),
-    pub cmd_doctor: fn() -> i32,
+    pub cmd_doctor: fn(&[String]) -> i32,
     pub cmd_where: fn(&[String]) -> i32,
     pub cmd_routes: fn(&[String]) -> i32,
     pub cmd_diag: fn(&[String]) -> i32,
@@ -14,15 +13,17 @@ pub struct CompatDeps {
     pub cmd_core: fn() -> i32,
     pub cmd_logs: fn(&[String]) -> i32,
     pub cmd_task: fn(&[String]) -> i32,
-    pub print_metrics: fn(usize) -> i32,
+    pub print_metrics: fn(usize, bool, &crate::run_filter::RunFilter) -> i32,
     pub cmd_quota: fn(&[String]) -> i32,
     pub cmd_prompt_stats: fn(&[String]) -> i32,
-    pub print_profile: fn(usize) -> i32,
-    pub print_trace: fn(usize) -> i32,
-    pub print_alert: fn(usize) -> i32,
+    pub print_profile: fn(usize, bool, bool, &crate::run_filter::RunFilter) -> i32,
+    pub print_trace: fn(usize, bool) -> i32,
+    pub print_trace_by_id: fn(&str, bool) -> i32,
+    pub print_trace_remediations: fn(usize, bool) -> i32,
+    pub print_alert: fn(usize, bool, &crate::run_filter::RunFilter) -> i32,
     pub parse_optimize_args: ParseOptimizeArgsFn,
     pub print_optimize: fn(crate::optimize_report::OptimizeArgs) -> i32,
-    pub print_worklog: fn(usize) -> i32,
+    pub cmd_worklog: fn(&[String]) -> i32,
     pub cmd_cx: fn(&[String]) -> i32,
     pub cmd_cxj: fn(&[String]) -> i32,
     pub cmd_cxo: fn(&[String]) -> i32,
@@ -30,23 +31,24 @@ pub struct CompatDeps {
     pub cmd_cxcopy: fn(&[String]) -> i32,
     pub cmd_policy: fn(&[String]) -> i32,
     pub cmd_broker: fn(&[String]) -> i32,
-    pub cmd_state_show: fn() -> i32,
-    pub cmd_state_get: fn(&str) -> i32,
-    pub cmd_state_set: fn(&str, &str) -> i32,
+    pub cmd_state_show: fn(&[String]) -> i32,
+    pub cmd_state_get: fn(&[String]) -> i32,
+    pub cmd_state_set: fn(&[String]) -> i32,
     pub cmd_llm: fn(&[String]) -> i32,
-    pub cmd_bench: fn(usize, &[String]) -> i32,
-    pub cmd_prompt: fn(&str, &str) -> i32,
+    pub cmd_bench: fn(usize, &[String], bool) -> i32,
+    pub cmd_bench_llm: fn(&[String]) -> i32,
+    pub cmd_prompt: fn(&str, Option<&str>, &str) -> i32,
     pub cmd_roles: fn(Option<&str>) -> i32,
-    pub cmd_fanout: fn(&str) -> i32,
-    pub cmd_promptlint: fn(usize) -> i32,
+    pub cmd_fanout: fn(&[String]) -> i32,
+    pub cmd_promptlint: fn(usize, bool, &crate::run_filter::RunFilter) -> i32,
     pub cmd_next: fn(&[String]) -> i32,
     pub cmd_fix: fn(&[String]) -> i32,
-    pub cmd_diffsum: fn(bool) -> i32,
-    pub cmd_commitjson: fn() -> i32,
-    pub cmd_commitmsg: fn() -> i32,
-    pub cmd_budget: fn() -> i32,
+    pub cmd_diffsum: fn(bool, &[String]) -> i32,
+    pub cmd_commitjson: fn(&[String]) -> i32,
+    pub cmd_commitmsg: fn(&[String]) -> i32,
+    pub cmd_budget: fn(&[String]) -> i32,
     pub cmd_log_tail: fn(usize) -> i32,
-    pub cmd_health: fn() -> i32,
+    pub cmd_health: fn(&[String]) -> i32,
     pub cmd_capture_status: fn() -> i32,
     pub cmd_log_on: fn() -> i32,
     pub cmd_log_off: fn() -> i32,
@@ -55,9 +57,13 @@ pub struct CompatDeps {
     pub cmd_alert_off: fn() -> i32,
     pub cmd_chunk: fn() -> i32,
     pub cmd_fix_run: fn(&[String]) -> i32,
-    pub cmd_replay: fn(&str) -> i32,
+    pub cmd_replay: fn(&[String]) -> i32,
     pub cmd_quarantine_list: fn(usize) -> i32,
     pub cmd_quarantine_show: fn(&str) -> i32,
+    pub cmd_quarantine_resolve: fn(&[String]) -> i32,
+    pub cmd_quarantine_rm: fn(&str) -> i32,
+    pub cmd_quarantine_purge: fn(&[String]) -> i32,
+    pub cmd_quarantine_stats: fn(bool) -> i32,
 }
 
 type ParseOptimizeArgsFn =
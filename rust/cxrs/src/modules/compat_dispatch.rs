@@ -1,6 +1,13 @@
+use crate::agentcmds::FIX_USAGE;
+use crate::analytics::trace_id_arg;
+use crate::bench_llm::BENCH_GATE_FLAGS;
 use crate::cmdctx::CmdCtx;
 use crate::config::{DEFAULT_OPTIMIZE_WINDOW, DEFAULT_QUARANTINE_LIST, DEFAULT_RUN_WINDOW};
 use crate::error::{EXIT_OK, EXIT_USAGE, format_error, print_usage_error};
+use crate::help::resolve_help_topic;
+use crate::roles::split_role_flag;
+use crate::run_filter::{RunFilter, parse_window_json, split_run_filter};
+use crate::structured_replay::REPLAY_USAGE;
 
 use super::CompatDeps;
 
@@ -11,6 +18,26 @@ fn parse_n(args: &[String], idx: usize, default: usize) -> usize {
         .unwrap_or(default)
 }
 
+/// Strip the analytics `--branch`/`--path` filter flags from `args[idx..]`.
+fn split_filter(
+    cmd: &str,
+    usage: &str,
+    args: &[String],
+    idx: usize,
+) -> Result<(RunFilter, Vec<String>), i32> {
+    split_run_filter(args.get(idx..).unwrap_or_default()).map_err(|e| {
+        crate::cx_eprintln!("{}", format_error(cmd, &e));
+        print_usage_error(cmd, usage)
+    })
+}
+
+fn has_percentiles(args: &[String], idx: usize) -> bool {
+    args.get(idx..)
+        .unwrap_or_default()
+        .iter()
+        .any(|v| v == "--percentiles")
+}
+
 fn require_prefixed_arg(args: &[String], usage: &str) -> Result<(), i32> {
     if args.len() < 2 {
         return Err(print_usage_error("cx", usage));
@@ -26,16 +53,11 @@ fn run_prefixed_cmd(args: &[String], usage: &str, f: fn(&[String]) -> i32) -> i3
 }
 
 fn handle_state(app_name: &str, args: &[String], deps: &CompatDeps) -> i32 {
+    let rest = args.get(2..).unwrap_or(&[]);
     match args.get(1).map(String::as_str).unwrap_or("show") {
-        "show" => (deps.cmd_state_show)(),
-        "get" => match args.get(2) {
-            Some(key) => (deps.cmd_state_get)(key),
-            None => print_usage_error("state", &format!("{app_name} cx state get <key>")),
-        },
-        "set" => match (args.get(2), args.get(3)) {
-            (Some(key), Some(value)) => (deps.cmd_state_set)(key, value),
-            _ => print_usage_error("state", &format!("{app_name} cx state set <key> <value>")),
-        },
+        "show" => (deps.cmd_state_show)(rest),
+        "get" => (deps.cmd_state_get)(rest),
+        "set" => (deps.cmd_state_set)(rest),
         other => {
             crate::cx_eprintln!("{app_name} cx state: unknown subcommand '{other}'");
             EXIT_USAGE
@@ -52,39 +74,37 @@ fn handle_telemetry(args: &[String], deps: &CompatDeps) -> i32 {
 }
 
 fn handle_bench(app_name: &str, args: &[String], deps: &CompatDeps) -> i32 {
-    let Some(runs) = args
-        .get(1)
-        .and_then(|v| v.parse::<usize>().ok())
-        .filter(|v| *v > 0)
-    else {
-        return print_usage_error(
-            "bench",
-            &format!("{app_name} cx bench <runs> -- <command...>"),
-        );
-    };
-    let Some(i) = args.iter().position(|v| v == "--") else {
-        return print_usage_error(
-            "bench",
-            &format!("{app_name} cx bench <runs> -- <command...>"),
-        );
-    };
-    if i + 1 >= args.len() {
-        return print_usage_error(
-            "bench",
-            &format!("{app_name} cx bench <runs> -- <command...>"),
-        );
+    let usage = format!("{app_name} cx bench <runs> [--json] -- <command...>");
+    let i = args.iter().position(|v| v == "--").unwrap_or(args.len());
+    let head = &args[..i];
+    if head
+        .iter()
+        .any(|v| v == "--llm" || BENCH_GATE_FLAGS.contains(&v.as_str()))
+    {
+        return (deps.cmd_bench_llm)(&args[1..]);
+    }
+    if i == args.len() {
+        return print_usage_error("bench", &usage);
+    }
+    let (runs, json_out) = parse_window_json(&args[..i], 1, 0);
+    if runs == 0 || i + 1 >= args.len() {
+        return print_usage_error("bench", &usage);
     }
-    (deps.cmd_bench)(runs, &args[i + 1..])
+    (deps.cmd_bench)(runs, &args[i + 1..], json_out)
 }
 
 fn handle_prompt(app_name: &str, args: &[String], deps: &CompatDeps) -> i32 {
+    let usage = format!("{app_name} cx prompt <mode> [--role NAME] <request>");
     let Some(mode) = args.get(1) else {
-        return print_usage_error("prompt", &format!("{app_name} cx prompt <mode> <request>"));
+        return print_usage_error("prompt", &usage);
     };
-    if args.len() < 3 {
-        return print_usage_error("prompt", &format!("{app_name} cx prompt <mode> <request>"));
+    let Some((role, request)) = split_role_flag(&args[2..]) else {
+        return print_usage_error("prompt", &usage);
+    };
+    if request.is_empty() {
+        return print_usage_error("prompt", &usage);
     }
-    (deps.cmd_prompt)(mode, &args[2..].join(" "))
+    (deps.cmd_prompt)(mode, role, &request.join(" "))
 }
 
 fn handle_optimize(args: &[String], deps: &CompatDeps) -> i32 {
@@ -99,10 +119,10 @@ fn handle_optimize(args: &[String], deps: &CompatDeps) -> i32 {
 }
 
 fn handle_replay(app_name: &str, args: &[String], deps: &CompatDeps) -> i32 {
-    match args.get(1) {
-        Some(id) => (deps.cmd_replay)(id),
-        None => print_usage_error("replay", &format!("{app_name} cx replay <quarantine_id>")),
+    if args.len() < 2 {
+        return print_usage_error("replay", &format!("{app_name} cx {REPLAY_USAGE}"));
     }
+    (deps.cmd_replay)(&args[1..])
 }
 
 fn handle_quarantine(app_name: &str, args: &[String], deps: &CompatDeps) -> i32 {
@@ -115,6 +135,16 @@ fn handle_quarantine(app_name: &str, args: &[String], deps: &CompatDeps) -> i32
                 &format!("{app_name} cx quarantine show <quarantine_id>"),
             ),
         },
+        "resolve" => (deps.cmd_quarantine_resolve)(&args[2..]),
+        "rm" => match args.get(2) {
+            Some(id) => (deps.cmd_quarantine_rm)(id),
+            None => print_usage_error(
+                "quarantine",
+                &format!("{app_name} cx quarantine rm <quarantine_id>"),
+            ),
+        },
+        "purge" => (deps.cmd_quarantine_purge)(&args[2..]),
+        "stats" => (deps.cmd_quarantine_stats)(args[2..].iter().any(|a| a == "--json")),
         other => {
             crate::cx_eprintln!("{app_name} cx quarantine: unknown subcommand '{other}'");
             EXIT_USAGE
@@ -129,19 +159,12 @@ fn dispatch_meta_commands(
     deps: &CompatDeps,
 ) -> Option<i32> {
     let out = match sub {
-        "help" => {
-            if args.get(1).map(String::as_str) == Some("task") {
-                (deps.print_task_help)();
-            } else {
-                (deps.print_help)();
-            }
-            EXIT_OK
-        }
+        "help" => (deps.cmd_help)(&args[1..]),
         "cxversion" | "version" => {
             (deps.print_version)();
             EXIT_OK
         }
-        "cxdoctor" | "doctor" => (deps.cmd_doctor)(),
+        "cxdoctor" | "doctor" => (deps.cmd_doctor)(&args[1..]),
         "cxwhere" | "where" => (deps.cmd_where)(&args[1..]),
         "cxroutes" | "routes" => (deps.cmd_routes)(&args[1..]),
         "cxdiag" | "diag" => (deps.cmd_diag)(&args[1..]),
@@ -162,13 +185,46 @@ fn dispatch_meta_commands(
 
 fn dispatch_analytics_commands(sub: &str, args: &[String], deps: &CompatDeps) -> Option<i32> {
     let out = match sub {
-        "cxmetrics" | "metrics" => (deps.print_metrics)(parse_n(args, 1, DEFAULT_RUN_WINDOW)),
+        "cxmetrics" | "metrics" => {
+            let usage = "metrics [N] [--percentiles] [--all-repos] [filters]";
+            let (filter, rest) = match split_filter("metrics", usage, args, 1) {
+                Ok(v) => v,
+                Err(code) => return Some(code),
+            };
+            let (n, _) = parse_window_json(&rest, 0, DEFAULT_RUN_WINDOW);
+            (deps.print_metrics)(n, has_percentiles(&rest, 0), &filter)
+        }
         "cxquota" | "quota" => (deps.cmd_quota)(&args[1..]),
         "cxprompt_stats" | "prompt-stats" => (deps.cmd_prompt_stats)(&args[1..]),
-        "cxprofile" | "profile" => (deps.print_profile)(parse_n(args, 1, DEFAULT_RUN_WINDOW)),
-        "cxtrace" | "trace" => (deps.print_trace)(parse_n(args, 1, 1)),
-        "cxalert" | "alert" => (deps.print_alert)(parse_n(args, 1, DEFAULT_RUN_WINDOW)),
-        "cxworklog" | "worklog" => (deps.print_worklog)(parse_n(args, 1, DEFAULT_RUN_WINDOW)),
+        "cxprofile" | "profile" => {
+            let usage = "profile [N] [--json] [--percentiles] [--all-repos] [filters]";
+            let (filter, rest) = match split_filter("profile", usage, args, 1) {
+                Ok(v) => v,
+                Err(code) => return Some(code),
+            };
+            let (n, json_out) = parse_window_json(&rest, 0, DEFAULT_RUN_WINDOW);
+            (deps.print_profile)(n, json_out, has_percentiles(&rest, 0), &filter)
+        }
+        "cxtrace" | "trace" => {
+            let (n, json_out) = parse_window_json(args, 1, 1);
+            if args[1..].iter().any(|a| a == "--remediations") {
+                (deps.print_trace_remediations)(n, json_out)
+            } else if let Some(id) = trace_id_arg(&args[1..]) {
+                (deps.print_trace_by_id)(id, json_out)
+            } else {
+                (deps.print_trace)(n, json_out)
+            }
+        }
+        "cxalert" | "alert" => {
+            let (filter, rest) =
+                match split_filter("alert", "alert [N] [--json] [filters]", args, 1) {
+                    Ok(v) => v,
+                    Err(code) => return Some(code),
+                };
+            let (n, json_out) = parse_window_json(&rest, 0, DEFAULT_RUN_WINDOW);
+            (deps.print_alert)(n, json_out, &filter)
+        }
+        "cxworklog" | "worklog" => (deps.cmd_worklog)(&args[1..]),
         "cxoptimize" | "optimize" => handle_optimize(args, deps),
         _ => return None,
     };
@@ -189,12 +245,20 @@ fn dispatch_prompt_commands(
             if args.len() < 2 {
                 return Some(print_usage_error(
                     "fanout",
-                    &format!("{app_name} cx fanout <objective>"),
+                    &format!("{app_name} cx fanout [--llm | --role NAME...] <objective>"),
                 ));
             }
-            (deps.cmd_fanout)(&args[1..].join(" "))
+            (deps.cmd_fanout)(&args[1..])
+        }
+        "cxpromptlint" | "promptlint" => {
+            let (filter, rest) =
+                match split_filter("promptlint", "promptlint [N] [--json] [filters]", args, 1) {
+                    Ok(v) => v,
+                    Err(code) => return Some(code),
+                };
+            let (n, json_out) = parse_window_json(&rest, 0, 200);
+            (deps.cmd_promptlint)(n, json_out, &filter)
         }
-        "cxpromptlint" | "promptlint" => (deps.cmd_promptlint)(parse_n(args, 1, 200)),
         _ => return None,
     };
     Some(out)
@@ -229,7 +293,7 @@ fn dispatch_agent_prefixed_shortcuts(
         ),
         "cxcopy" => run_prefixed_cmd(
             args,
-            &format!("{app_name} cxcopy <command> [args...]"),
+            &format!("{app_name} cxcopy [--raw|--jsonl|--both] <command> [args...]"),
             deps.cmd_cxcopy,
         ),
         _ => return None,
@@ -249,14 +313,12 @@ fn dispatch_agent_commands(
     let out = match sub {
         "cxnext" | "next" => run_prefixed_cmd(
             args,
-            &format!("{app_name} cx next <command> [args...]"),
+            &format!("{app_name} cx next [--run [--unsafe]] <command> [args...]"),
             deps.cmd_next,
         ),
-        "cxfix" | "fix" => run_prefixed_cmd(
-            args,
-            &format!("{app_name} cx fix <command> [args...]"),
-            deps.cmd_fix,
-        ),
+        "cxfix" | "fix" => {
+            run_prefixed_cmd(args, &format!("{app_name} cx {FIX_USAGE}"), deps.cmd_fix)
+        }
         "cxfix_run" | "fix-run" => run_prefixed_cmd(
             args,
             &format!("{app_name} cx fix-run <command> [args...]"),
@@ -274,9 +336,9 @@ fn dispatch_runtime_commands(
     deps: &CompatDeps,
 ) -> Option<i32> {
     let out = match sub {
-        "cxbudget" | "budget" => (deps.cmd_budget)(),
+        "cxbudget" | "budget" => (deps.cmd_budget)(&args[1..]),
         "cxlog_tail" | "log-tail" => (deps.cmd_log_tail)(parse_n(args, 1, 10)),
-        "cxhealth" | "health" => (deps.cmd_health)(),
+        "cxhealth" | "health" => (deps.cmd_health)(&args[1..]),
         "capture-status" => (deps.cmd_capture_status)(),
         "cxlog_on" | "log-on" => (deps.cmd_log_on)(),
         "cxlog_off" | "log-off" => (deps.cmd_log_off)(),
@@ -284,10 +346,10 @@ fn dispatch_runtime_commands(
         "cxalert_on" | "alert-on" => (deps.cmd_alert_on)(),
         "cxalert_off" | "alert-off" => (deps.cmd_alert_off)(),
         "cxchunk" | "chunk" => (deps.cmd_chunk)(),
-        "cxdiffsum" | "diffsum" => (deps.cmd_diffsum)(false),
-        "cxdiffsum_staged" | "diffsum-staged" => (deps.cmd_diffsum)(true),
-        "cxcommitjson" | "commitjson" => (deps.cmd_commitjson)(),
-        "cxcommitmsg" | "commitmsg" => (deps.cmd_commitmsg)(),
+        "cxdiffsum" | "diffsum" => (deps.cmd_diffsum)(false, &args[1..]),
+        "cxdiffsum_staged" | "diffsum-staged" => (deps.cmd_diffsum)(true, &args[1..]),
+        "cxcommitjson" | "commitjson" => (deps.cmd_commitjson)(&args[1..]),
+        "cxcommitmsg" | "commitmsg" => (deps.cmd_commitmsg)(&args[1..]),
         "cxreplay" | "replay" => handle_replay(app_name, args, deps),
         "cxquarantine" | "quarantine" => handle_quarantine(app_name, args, deps),
         _ => return None,
@@ -295,12 +357,22 @@ fn dispatch_runtime_commands(
     Some(out)
 }
 
+fn help_flag_topic(sub: &str, next: Option<&String>) -> Option<String> {
+    if sub == "help" || !matches!(next.map(String::as_str), Some("--help" | "-h")) {
+        return None;
+    }
+    resolve_help_topic(sub)
+}
+
 pub fn handler(ctx: &CmdCtx, args: &[String], deps: &CompatDeps) -> i32 {
     let app_name = ctx.app_name;
     if args.is_empty() {
         return print_usage_error("cx", &format!("{app_name} cx <command> [args...]"));
     }
     let sub = args[0].as_str();
+    if let Some(topic) = help_flag_topic(sub, args.get(1)) {
+        return (deps.cmd_help)(&[topic]);
+    }
 
     dispatch_meta_commands(sub, app_name, args, deps)
         .or_else(|| dispatch_analytics_commands(sub, args, deps))
@@ -1,8 +1,8 @@
 use serde_json::Value;
-use std::env;
 use std::sync::OnceLock;
 
-use crate::state::{read_state_value, value_at_path};
+use crate::config_layers::{config_env, config_env_or_state};
+use crate::state::read_state_value;
 
 /// Canonical application identity (used by routing/help/version surfaces).
 pub const APP_NAME: &str = "cxrs";
@@ -12,10 +12,36 @@ pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// Canonical runtime defaults.
 pub const DEFAULT_CONTEXT_BUDGET_CHARS: usize = 12_000;
 pub const DEFAULT_CONTEXT_BUDGET_LINES: usize = 300;
+/// Token budget is opt-in; `0` leaves clipping to the char/line budgets.
+pub const DEFAULT_CONTEXT_BUDGET_TOKENS: usize = 0;
+/// Share of the capture budget reserved for stderr when a command also wrote stdout.
+pub const DEFAULT_STDERR_BUDGET_PCT: usize = 50;
+/// Lines that anchor `error-window` clipping.
+pub const DEFAULT_ERROR_REGEX: &str =
+    r"(?i)\b(error|fatal|panic(ked)?|failed|failure|exception|traceback)\b";
+/// Upper bound on map-reduce summarization calls per capture.
+pub const DEFAULT_CHUNK_MAX: usize = 8;
 pub const DEFAULT_RUN_WINDOW: usize = 50;
 pub const DEFAULT_OPTIMIZE_WINDOW: usize = 200;
 pub const DEFAULT_QUARANTINE_LIST: usize = 20;
 pub const DEFAULT_CMD_TIMEOUT_SECS: usize = 120;
+pub const DEFAULT_SESSION_NAME: &str = "default";
+pub const DEFAULT_SESSION_TURNS: usize = 3;
+/// Transcript retention: age in days (`0` keeps forever) and total size cap.
+pub const DEFAULT_TRANSCRIPT_KEEP_DAYS: usize = 14;
+pub const DEFAULT_TRANSCRIPT_MAX_BYTES: usize = 50 * 1024 * 1024;
+/// `hook run pre-push` blocks above this schema failure rate (`0` disables).
+pub const DEFAULT_HOOK_MAX_SCHEMA_FAIL_PCT: usize = 20;
+/// `gc` retention for rotated logs, cache, sessions and resolved quarantine records.
+pub const DEFAULT_GC_LOGS_KEEP_DAYS: usize = 30;
+pub const DEFAULT_GC_LOGS_MAX_BYTES: usize = 100 * 1024 * 1024;
+pub const DEFAULT_GC_CACHE_KEEP_DAYS: usize = 30;
+pub const DEFAULT_GC_CACHE_MAX_BYTES: usize = 256 * 1024 * 1024;
+pub const DEFAULT_GC_SESSIONS_KEEP_DAYS: usize = 30;
+pub const DEFAULT_GC_SESSIONS_MAX_BYTES: usize = 50 * 1024 * 1024;
+pub const DEFAULT_GC_QUARANTINE_KEEP_DAYS: usize = 30;
+pub const DEFAULT_GC_QUARANTINE_MAX_BYTES: usize = 50 * 1024 * 1024;
+pub const DEFAULT_SANDBOX_IMAGE: &str = "debian:stable-slim";
 
 /// Process-level configuration snapshot.
 ///
@@ -25,11 +51,26 @@ pub const DEFAULT_CMD_TIMEOUT_SECS: usize = 120;
 pub struct AppConfig {
     pub budget_chars: usize,
     pub budget_lines: usize,
+    pub budget_tokens: usize,
     pub clip_mode: String,
     pub clip_footer: bool,
+    pub error_regex: String,
+    /// Percent of the budget stderr may use before stdout gets the rest.
+    pub stderr_budget_pct: usize,
+    /// `clip` (default) or `mapreduce`.
+    pub chunk_strategy: String,
+    pub chunk_max: usize,
+    /// Mask secrets in captured output before it reaches a prompt.
+    pub redact: bool,
     pub llm_backend: String,
+    /// Backends to fail over through, in order (`codex`/`ollama`); empty disables failover.
+    pub backend_chain: Vec<String>,
     pub ollama_model: String,
     pub codex_model: String,
+    /// Extra `codex exec` options, unvalidated (see `codex_args`).
+    pub codex_args: String,
+    /// `--codex-arg` values of this run, appended after `codex_args`.
+    pub codex_extra_args: String,
     pub cxbench_log: bool,
     pub cxbench_passthru: bool,
     pub cxfix_run: bool,
@@ -37,98 +78,223 @@ pub struct AppConfig {
     pub cx_unsafe: bool,
     pub cx_mode: String,
     pub schema_relaxed: bool,
+    pub stream: bool,
+    /// Pass captured command output through to stderr while it runs.
+    pub tee: bool,
+    /// Refuse every LLM and network call (`CX_OFFLINE=1` / `--offline`).
+    pub offline: bool,
+    /// cx running its own LLM commands: `block`, `warn` or `allow` (`--allow-nested`).
+    pub nested: String,
     pub cxlog_enabled: bool,
     pub capture_provider: String,
     pub broker_policy: String,
     pub cmd_timeout_secs: usize,
     pub task_halt_on_critical: bool,
+    /// Record cx/cxo/cxol turns to `.codex/sessions` (opt-in).
+    pub session_enabled: bool,
+    pub session_name: String,
+    pub session_turns: usize,
+    /// Archive full prompt/response pairs under `.codex/transcripts` (opt-in).
+    pub transcripts: bool,
+    pub transcripts_keep_days: usize,
+    pub transcripts_max_bytes: usize,
+    pub hook_max_schema_fail_pct: usize,
+    /// Prune stores over their retention limits after logged runs.
+    pub gc_auto: bool,
+    pub gc_logs_keep_days: usize,
+    pub gc_logs_max_bytes: usize,
+    pub gc_cache_keep_days: usize,
+    pub gc_cache_max_bytes: usize,
+    pub gc_sessions_keep_days: usize,
+    pub gc_sessions_max_bytes: usize,
+    pub gc_quarantine_keep_days: usize,
+    pub gc_quarantine_max_bytes: usize,
+    /// Pipeline progress on stderr: `auto` (terminal only), `human`, `json` or `off`.
+    pub progress: String,
+    /// Render agent Markdown for cx/cxo/cxol on a terminal (opt-in; `--plain` disables).
+    pub render_markdown: bool,
+    /// Where fix-run/next --run execute suggestions: `none`, `container` or `nsjail`.
+    pub sandbox: String,
+    /// Paths (repo-relative or absolute) mounted writable inside the sandbox.
+    pub sandbox_writable: Vec<String>,
+    pub sandbox_image: String,
+    /// Built-in dangerous-command rules; only the user can turn them off.
+    pub policy_builtin: bool,
+    /// Globs for write targets outside the repo that the built-in rule allows.
+    pub policy_write_allow: Vec<String>,
 }
 
 static APP_CONFIG: OnceLock<AppConfig> = OnceLock::new();
 
 fn env_bool(name: &str, default: bool) -> bool {
-    env::var(name)
-        .ok()
+    config_env(name)
         .and_then(|v| v.parse::<u8>().ok())
         .map(|v| v == 1)
         .unwrap_or(default)
 }
 
 fn env_usize(name: &str, default: usize) -> usize {
-    env::var(name)
-        .ok()
+    config_env(name)
         .and_then(|v| v.parse::<usize>().ok())
         .unwrap_or(default)
 }
 
-fn state_pref_str(state: &Option<Value>, path: &str) -> Option<String> {
-    state
-        .as_ref()
-        .and_then(|v| value_at_path(v, path))
-        .and_then(Value::as_str)
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-}
-
 fn resolve_backend(state: &Option<Value>) -> String {
-    let raw = env::var("CX_LLM_BACKEND")
-        .ok()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .or_else(|| state_pref_str(state, "preferences.llm_backend"))
-        .unwrap_or_else(|| "codex".to_string());
+    let raw = config_env_or_state("CX_LLM_BACKEND", state).unwrap_or_else(|| "codex".to_string());
     if raw.eq_ignore_ascii_case("ollama") {
         "ollama".to_string()
+    } else if raw.eq_ignore_ascii_case("mock") {
+        "mock".to_string()
     } else {
         "codex".to_string()
     }
 }
 
+fn resolve_backend_chain(state: &Option<Value>) -> Vec<String> {
+    let raw = config_env_or_state("CX_BACKEND_CHAIN", state).unwrap_or_default();
+    let mut chain: Vec<String> = Vec::new();
+    for name in raw.split(',').map(|s| s.trim().to_ascii_lowercase()) {
+        if matches!(name.as_str(), "codex" | "ollama") && !chain.contains(&name) {
+            chain.push(name);
+        }
+    }
+    chain
+}
+
 fn resolve_ollama_model(state: &Option<Value>) -> String {
-    env::var("CX_OLLAMA_MODEL")
-        .ok()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .or_else(|| state_pref_str(state, "preferences.ollama_model"))
-        .unwrap_or_default()
+    config_env_or_state("CX_OLLAMA_MODEL", state).unwrap_or_default()
 }
 
 fn resolve_broker_policy(state: &Option<Value>) -> String {
-    let raw = env::var("CX_BROKER_POLICY")
-        .ok()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .or_else(|| state_pref_str(state, "preferences.broker_policy"))
-        .unwrap_or_else(|| "balanced".to_string());
+    let raw =
+        config_env_or_state("CX_BROKER_POLICY", state).unwrap_or_else(|| "balanced".to_string());
     match raw.as_str() {
         "latency" | "quality" | "cost" | "balanced" | "quota_saver" => raw,
         _ => "balanced".to_string(),
     }
 }
 
+fn resolve_chunk_strategy() -> String {
+    match config_env("CX_CHUNK_STRATEGY")
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "mapreduce" | "map-reduce" => "mapreduce".to_string(),
+        _ => "clip".to_string(),
+    }
+}
+
+/// `native` (pipes) or `pty` (pseudo-terminal, unix only).
+fn resolve_capture_provider() -> String {
+    match config_env("CX_CAPTURE_PROVIDER")
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "pty" if cfg!(unix) => "pty".to_string(),
+        _ => "native".to_string(),
+    }
+}
+
 impl AppConfig {
     pub fn from_env() -> Self {
         let state = read_state_value();
         Self {
             budget_chars: env_usize("CX_CONTEXT_BUDGET_CHARS", DEFAULT_CONTEXT_BUDGET_CHARS),
             budget_lines: env_usize("CX_CONTEXT_BUDGET_LINES", DEFAULT_CONTEXT_BUDGET_LINES),
-            clip_mode: env::var("CX_CONTEXT_CLIP_MODE").unwrap_or_else(|_| "smart".to_string()),
+            budget_tokens: env_usize("CX_CONTEXT_BUDGET_TOKENS", DEFAULT_CONTEXT_BUDGET_TOKENS),
+            clip_mode: config_env("CX_CONTEXT_CLIP_MODE").unwrap_or_else(|| "smart".to_string()),
             clip_footer: env_bool("CX_CONTEXT_CLIP_FOOTER", true),
+            error_regex: config_env("CX_CONTEXT_ERROR_REGEX")
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_ERROR_REGEX.to_string()),
+            stderr_budget_pct: env_usize("CX_CONTEXT_STDERR_BUDGET_PCT", DEFAULT_STDERR_BUDGET_PCT)
+                .min(100),
+            chunk_strategy: resolve_chunk_strategy(),
+            chunk_max: env_usize("CX_CHUNK_MAX", DEFAULT_CHUNK_MAX).max(1),
+            redact: env_bool("CX_REDACT", true),
             llm_backend: resolve_backend(&state),
+            backend_chain: resolve_backend_chain(&state),
             ollama_model: resolve_ollama_model(&state),
-            codex_model: env::var("CX_MODEL").unwrap_or_default(),
+            codex_model: config_env("CX_MODEL").unwrap_or_default(),
+            codex_args: config_env_or_state("CX_CODEX_ARGS", &state).unwrap_or_default(),
+            codex_extra_args: config_env("CX_CODEX_EXTRA_ARGS").unwrap_or_default(),
             cxbench_log: env_bool("CXBENCH_LOG", true),
             cxbench_passthru: env_bool("CXBENCH_PASSTHRU", false),
             cxfix_run: env_bool("CXFIX_RUN", false),
             cxfix_force: env_bool("CXFIX_FORCE", false),
             cx_unsafe: env_bool("CX_UNSAFE", false),
-            cx_mode: env::var("CX_MODE").unwrap_or_else(|_| "lean".to_string()),
+            cx_mode: config_env("CX_MODE").unwrap_or_else(|| "lean".to_string()),
             schema_relaxed: env_bool("CX_SCHEMA_RELAXED", false),
+            stream: env_bool("CX_STREAM", false),
+            tee: env_bool("CX_TEE", false),
+            offline: env_bool("CX_OFFLINE", false),
+            nested: config_env("CX_NESTED").unwrap_or_else(|| "block".to_string()),
             cxlog_enabled: env_bool("CXLOG_ENABLED", true),
-            capture_provider: "native".to_string(),
+            capture_provider: resolve_capture_provider(),
             broker_policy: resolve_broker_policy(&state),
             cmd_timeout_secs: env_usize("CX_CMD_TIMEOUT_SECS", DEFAULT_CMD_TIMEOUT_SECS).max(1),
             task_halt_on_critical: env_bool("CX_TASK_HALT_ON_CRITICAL", false),
+            session_enabled: env_bool("CX_SESSION_ENABLED", false),
+            session_name: config_env("CX_SESSION")
+                .unwrap_or_else(|| DEFAULT_SESSION_NAME.to_string()),
+            session_turns: env_usize("CX_SESSION_TURNS", DEFAULT_SESSION_TURNS).max(1),
+            transcripts: env_bool("CX_TRANSCRIPTS", false),
+            transcripts_keep_days: env_usize(
+                "CX_TRANSCRIPTS_KEEP_DAYS",
+                DEFAULT_TRANSCRIPT_KEEP_DAYS,
+            ),
+            transcripts_max_bytes: env_usize(
+                "CX_TRANSCRIPTS_MAX_BYTES",
+                DEFAULT_TRANSCRIPT_MAX_BYTES,
+            ),
+            hook_max_schema_fail_pct: env_usize(
+                "CX_HOOK_MAX_SCHEMA_FAIL_PCT",
+                DEFAULT_HOOK_MAX_SCHEMA_FAIL_PCT,
+            ),
+            gc_auto: env_bool("CX_GC_AUTO", true),
+            gc_logs_keep_days: env_usize("CX_GC_LOGS_KEEP_DAYS", DEFAULT_GC_LOGS_KEEP_DAYS),
+            gc_logs_max_bytes: env_usize("CX_GC_LOGS_MAX_BYTES", DEFAULT_GC_LOGS_MAX_BYTES),
+            gc_cache_keep_days: env_usize("CX_GC_CACHE_KEEP_DAYS", DEFAULT_GC_CACHE_KEEP_DAYS),
+            gc_cache_max_bytes: env_usize("CX_GC_CACHE_MAX_BYTES", DEFAULT_GC_CACHE_MAX_BYTES),
+            gc_sessions_keep_days: env_usize(
+                "CX_GC_SESSIONS_KEEP_DAYS",
+                DEFAULT_GC_SESSIONS_KEEP_DAYS,
+            ),
+            gc_sessions_max_bytes: env_usize(
+                "CX_GC_SESSIONS_MAX_BYTES",
+                DEFAULT_GC_SESSIONS_MAX_BYTES,
+            ),
+            gc_quarantine_keep_days: env_usize(
+                "CX_GC_QUARANTINE_KEEP_DAYS",
+                DEFAULT_GC_QUARANTINE_KEEP_DAYS,
+            ),
+            gc_quarantine_max_bytes: env_usize(
+                "CX_GC_QUARANTINE_MAX_BYTES",
+                DEFAULT_GC_QUARANTINE_MAX_BYTES,
+            ),
+            progress: config_env("CX_PROGRESS").unwrap_or_else(|| "auto".to_string()),
+            render_markdown: env_bool("CX_RENDER_MARKDOWN", false),
+            sandbox: config_env("CX_SANDBOX")
+                .map(|v| v.trim().to_ascii_lowercase())
+                .unwrap_or_else(|| "none".to_string()),
+            sandbox_writable: config_env("CX_SANDBOX_WRITABLE")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            sandbox_image: config_env("CX_SANDBOX_IMAGE")
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_SANDBOX_IMAGE.to_string()),
+            policy_builtin: env_bool("CX_POLICY_BUILTIN", true),
+            policy_write_allow: config_env("CX_POLICY_WRITE_ALLOW")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
         }
     }
 }
@@ -0,0 +1,427 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config_settings::setting_by_env;
+pub use crate::config_settings::{SETTINGS, SettingKind, SettingSpec, setting_by_key};
+use crate::paths::{GlobalKind, ensure_parent_dir, global_dir, repo_root};
+use crate::state::{read_state_value, value_at_path};
+use crate::verbosity::{Verbosity, diag, warn};
+
+pub fn global_config_file() -> Option<PathBuf> {
+    global_dir(GlobalKind::Config).map(|d| d.join("config.toml"))
+}
+
+pub fn repo_config_file() -> Option<PathBuf> {
+    repo_root().map(|r| r.join(".codex").join("config.toml"))
+}
+
+/// One parsed config file: `(key, env-style value)` pairs for known settings.
+#[derive(Debug, Default)]
+struct ConfigLayer {
+    path: String,
+    values: Vec<(&'static str, String)>,
+    /// Global-only keys a repo file tried to set; dropped from `values`.
+    refused: Vec<&'static str>,
+    repo: bool,
+}
+
+/// Convert a TOML scalar into the env-style string the runtime parses
+/// (`1`/`0` for booleans) after checking it against the setting kind.
+fn normalize_value(spec: &SettingSpec, v: &toml::Value) -> Result<String, String> {
+    match (spec.kind, v) {
+        (SettingKind::Usize, toml::Value::Integer(n)) if *n >= 0 => Ok(n.to_string()),
+        (SettingKind::Bool, toml::Value::Boolean(b)) => Ok(if *b { "1" } else { "0" }.to_string()),
+        (SettingKind::Bool, toml::Value::Integer(n @ (0 | 1))) => Ok(n.to_string()),
+        (SettingKind::Text(allowed), toml::Value::String(s)) => {
+            let s = s.trim();
+            if allowed.is_empty() || allowed.contains(&s) {
+                Ok(s.to_string())
+            } else {
+                Err(format!("expected one of {}", allowed.join("|")))
+            }
+        }
+        (SettingKind::Usize, _) => Err("expected a non-negative integer".to_string()),
+        (SettingKind::Bool, _) => Err("expected true or false".to_string()),
+        (SettingKind::Text(_), _) => Err("expected a string".to_string()),
+    }
+}
+
+fn parse_table(raw: &str, path: &str) -> Result<toml::Table, String> {
+    raw.parse()
+        .map_err(|e: toml::de::Error| format!("{path}: invalid TOML: {}", e.message()))
+}
+
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let sub = prev[j] + usize::from(ca != *cb);
+            cur.push(sub.min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// "unknown setting" message with up to three close known keys.
+pub fn unknown_setting(key: &str) -> String {
+    let name = key.rsplit('.').next().unwrap_or(key);
+    let mut close: Vec<(usize, &str)> = SETTINGS
+        .iter()
+        .filter_map(|s| {
+            let d = edit_distance(key, s.key);
+            let same_name = s.key.rsplit('.').next() == Some(name);
+            (d <= 4 || same_name || s.key.starts_with(key)).then_some((d, s.key))
+        })
+        .collect();
+    close.sort();
+    let names: Vec<&str> = close.into_iter().take(3).map(|(_, k)| k).collect();
+    if names.is_empty() {
+        format!("unknown setting '{key}' (run `config show` for known settings)")
+    } else {
+        format!(
+            "unknown setting '{key}' (did you mean: {}?)",
+            names.join(", ")
+        )
+    }
+}
+
+/// Why a repo config cannot set `key`, naming where it can be set instead.
+fn global_only(key: &str) -> String {
+    let env = setting_by_key(key).map_or("its env var", |s| s.env);
+    format!("{key} can only be set in the global config or {env}; ignoring the repo value")
+}
+
+/// Parse a CLI value for `spec` into the TOML type written to config files.
+pub fn parse_setting_value(spec: &SettingSpec, raw: &str) -> Result<toml::Value, String> {
+    let raw = raw.trim();
+    let value = match spec.kind {
+        SettingKind::Usize => raw
+            .parse::<u32>()
+            .map(|n| toml::Value::Integer(i64::from(n)))
+            .map_err(|_| "expected a non-negative integer".to_string()),
+        SettingKind::Bool => match raw.to_ascii_lowercase().as_str() {
+            "true" | "1" | "on" | "yes" => Ok(toml::Value::Boolean(true)),
+            "false" | "0" | "off" | "no" => Ok(toml::Value::Boolean(false)),
+            _ => Err("expected true or false".to_string()),
+        },
+        SettingKind::Text(_) => Ok(toml::Value::String(raw.to_string())),
+    };
+    let value = value.map_err(|e| format!("{}: {e}", spec.key))?;
+    normalize_value(spec, &value).map_err(|e| format!("{}: {e}", spec.key))?;
+    Ok(value)
+}
+
+/// Validate a CLI value for `spec` and return the env-style string the runtime reads.
+pub fn setting_env_value(spec: &SettingSpec, raw: &str) -> Result<String, String> {
+    let value = parse_setting_value(spec, raw)?;
+    normalize_value(spec, &value).map_err(|e| format!("{}: {e}", spec.key))
+}
+
+/// Value of `spec` stored in one config file, if any.
+pub fn file_setting(path: &Path, spec: &SettingSpec) -> Result<Option<String>, String> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let shown = path.display().to_string();
+    let raw = fs::read_to_string(path).map_err(|e| format!("cannot read {shown}: {e}"))?;
+    let layer = parse_layer(&raw, &shown, false)?;
+    Ok(layer
+        .values
+        .into_iter()
+        .find(|(k, _)| *k == spec.key)
+        .map(|(_, v)| v))
+}
+
+/// Set (`Some`) or remove (`None`) one setting in a config file, dropping
+/// sections left empty. Returns false when an unset found nothing to remove.
+/// The file is rewritten, so comments are not preserved.
+pub fn write_file_setting(
+    path: &Path,
+    spec: &SettingSpec,
+    value: Option<toml::Value>,
+) -> Result<bool, String> {
+    let shown = path.display().to_string();
+    let mut table = if path.is_file() {
+        let raw = fs::read_to_string(path).map_err(|e| format!("cannot read {shown}: {e}"))?;
+        parse_table(&raw, &shown)?
+    } else {
+        toml::Table::new()
+    };
+    let (section, name) = spec.key.split_once('.').unwrap_or(("", spec.key));
+    let changed = match value {
+        Some(v) => {
+            let body = table
+                .entry(section)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            let Some(body) = body.as_table_mut() else {
+                return Err(format!("{shown}: '{section}' must be a [section] table"));
+            };
+            body.insert(name.to_string(), v);
+            true
+        }
+        None => {
+            let removed = table
+                .get_mut(section)
+                .and_then(toml::Value::as_table_mut)
+                .and_then(|body| body.remove(name))
+                .is_some();
+            if table
+                .get(section)
+                .and_then(toml::Value::as_table)
+                .is_some_and(toml::Table::is_empty)
+            {
+                table.remove(section);
+            }
+            removed
+        }
+    };
+    if !changed {
+        return Ok(false);
+    }
+    ensure_parent_dir(path)?;
+    let rendered = toml::to_string(&table).map_err(|e| format!("failed to render {shown}: {e}"))?;
+    let tmp = path.with_extension(format!("tmp.{}", std::process::id()));
+    fs::write(&tmp, rendered).map_err(|e| format!("failed to write {}: {e}", tmp.display()))?;
+    fs::rename(&tmp, path).map_err(|e| format!("failed to replace {shown}: {e}"))?;
+    Ok(true)
+}
+
+/// Parse `[section] name = value` tables into known settings; unknown keys and
+/// ill-typed values are reported with the file path. A `repo` layer keeps only
+/// repo-overridable settings and lists the others in `refused`.
+fn parse_layer(raw: &str, path: &str, repo: bool) -> Result<ConfigLayer, String> {
+    let table = parse_table(raw, path)?;
+    let mut layer = ConfigLayer {
+        path: path.to_string(),
+        repo,
+        ..ConfigLayer::default()
+    };
+    for (section, body) in &table {
+        let Some(body) = body.as_table() else {
+            return Err(format!("{path}: '{section}' must be a [section] table"));
+        };
+        for (name, value) in body {
+            let key = format!("{section}.{name}");
+            let spec =
+                setting_by_key(&key).ok_or_else(|| format!("{path}: {}", unknown_setting(&key)))?;
+            let v = normalize_value(spec, value).map_err(|e| format!("{path}: {key}: {e}"))?;
+            if repo && !spec.repo_overridable {
+                layer.refused.push(spec.key);
+                continue;
+            }
+            layer.values.push((spec.key, v));
+        }
+    }
+    Ok(layer)
+}
+
+fn load_layer(path: Option<PathBuf>, repo: bool) -> Option<ConfigLayer> {
+    let path = path.filter(|p| p.is_file())?;
+    let shown = path.display().to_string();
+    let parsed = fs::read_to_string(&path)
+        .map_err(|e| format!("cannot read {shown}: {e}"))
+        .and_then(|raw| parse_layer(&raw, &shown, repo));
+    match parsed {
+        Ok(layer) => {
+            for key in &layer.refused {
+                warn(&format!("cxrs: warning: {shown}: {}", global_only(key)));
+            }
+            diag(
+                Verbosity::Debug,
+                "config_file",
+                &[("path", shown), ("keys", layer.values.len().to_string())],
+            );
+            Some(layer)
+        }
+        Err(e) => {
+            warn(&format!("cxrs: warning: ignoring config file: {e}"));
+            None
+        }
+    }
+}
+
+/// Global then repo layer (later wins); the repo file is skipped when it is the
+/// global file (repo rooted at `$HOME`).
+fn load_layers() -> Vec<ConfigLayer> {
+    let global = global_config_file();
+    let repo = repo_config_file().filter(|p| Some(p) != global.as_ref());
+    [load_layer(global, false), load_layer(repo, true)]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn layers() -> &'static [ConfigLayer] {
+    static LAYERS: OnceLock<Vec<ConfigLayer>> = OnceLock::new();
+    LAYERS.get_or_init(load_layers)
+}
+
+fn file_value(spec: &SettingSpec) -> Option<(String, String)> {
+    layers().iter().rev().find_map(|l| {
+        l.values
+            .iter()
+            .find(|(k, _)| *k == spec.key)
+            .map(|(_, v)| (v.clone(), l.path.clone()))
+    })
+}
+
+fn env_value(name: &str) -> Option<String> {
+    env::var(name)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Env var `env_name`, falling back to the config files for the matching
+/// setting. Use in place of `env::var` for settings listed in [`SETTINGS`].
+pub fn config_env(env_name: &str) -> Option<String> {
+    env_value(env_name)
+        .or_else(|| setting_by_env(env_name).and_then(|s| file_value(s).map(|v| v.0)))
+}
+
+/// True when the effective value of `env_name` comes from a repo config file,
+/// i.e. from the checkout rather than the user.
+pub fn set_by_repo_config(env_name: &str) -> bool {
+    if env_value(env_name).is_some() {
+        return false;
+    }
+    let Some(spec) = setting_by_env(env_name) else {
+        return false;
+    };
+    layers()
+        .iter()
+        .rev()
+        .find(|l| l.values.iter().any(|(k, _)| *k == spec.key))
+        .is_some_and(|l| l.repo)
+}
+
+/// A state preference as text; string lists (e.g. `backend_chain`) join with `,`.
+fn state_text(v: &serde_json::Value) -> Option<String> {
+    let text = match v {
+        serde_json::Value::String(s) => s.trim().to_string(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter_map(serde_json::Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => return None,
+    };
+    Some(text).filter(|s| !s.is_empty())
+}
+
+/// Like [`config_env`] but with the legacy state preference between the env and
+/// the config files.
+pub fn config_env_or_state(env_name: &str, state: &Option<serde_json::Value>) -> Option<String> {
+    let spec = setting_by_env(env_name);
+    env_value(env_name)
+        .or_else(|| {
+            let path = spec?.state_path?;
+            state
+                .as_ref()
+                .and_then(|v| value_at_path(v, path))
+                .and_then(state_text)
+        })
+        .or_else(|| spec.and_then(|s| file_value(s).map(|v| v.0)))
+}
+
+/// Effective value of one setting and where it came from
+/// (`default`, a config file path, `state:<path>` or `env:<NAME>`).
+pub fn resolve_setting(spec: &SettingSpec) -> (String, String) {
+    if let Some(v) = env_value(spec.env) {
+        return (v, format!("env:{}", spec.env));
+    }
+    if let Some(path) = spec.state_path
+        && let Some(v) = read_state_value()
+            .as_ref()
+            .and_then(|s| value_at_path(s, path))
+            .and_then(state_text)
+    {
+        return (v, format!("state:{path}"));
+    }
+    if let Some((v, path)) = file_value(spec) {
+        return (v, path);
+    }
+    (spec.default.to_string(), "default".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layer_parses_known_settings_and_normalizes_values() {
+        let layer = parse_layer(
+            "[capture]\nbudget_chars = 20000\nclip_footer = false\n[llm]\nbackend = \"ollama\"\n",
+            "c.toml",
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            layer.values,
+            vec![
+                ("capture.budget_chars", "20000".to_string()),
+                ("capture.clip_footer", "0".to_string()),
+                ("llm.backend", "ollama".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn layer_rejects_unknown_keys_and_bad_types() {
+        let err = parse_layer("[capture]\nbudget = 1\n", "c.toml", false).unwrap_err();
+        assert!(err.starts_with("c.toml: unknown setting 'capture.budget' (did you mean: "));
+        assert!(err.contains("capture.budget_chars"));
+        let err = parse_layer("[capture]\nbudget_chars = \"big\"\n", "c.toml", false).unwrap_err();
+        assert!(err.contains("capture.budget_chars: expected a non-negative integer"));
+        let err = parse_layer("[llm]\nbackend = \"gpt\"\n", "c.toml", false).unwrap_err();
+        assert!(err.contains("expected one of codex|ollama|mock"));
+        assert!(parse_layer("budget_chars = 1\n", "c.toml", false).is_err());
+        assert!(SETTINGS.iter().all(|s| s.key.contains('.')));
+    }
+
+    #[test]
+    fn repo_layer_refuses_global_only_settings() {
+        let raw = "[digest]\nsendmail = \"evil\"\n[sandbox]\nmode = \"none\"\n[capture]\nbudget_chars = 1\n";
+        let layer = parse_layer(raw, "c.toml", true).unwrap();
+        assert_eq!(
+            layer.values,
+            vec![("capture.budget_chars", "1".to_string())]
+        );
+        assert_eq!(layer.refused, vec!["digest.sendmail", "sandbox.mode"]);
+        let layer = parse_layer(raw, "c.toml", false).unwrap();
+        assert!(layer.refused.is_empty());
+        assert_eq!(layer.values.len(), 3);
+    }
+
+    #[test]
+    fn unknown_keys_suggest_close_settings() {
+        assert!(unknown_setting("alert.max_msec").contains("did you mean: alert.max_ms"));
+        assert!(unknown_setting("budget_chars").contains("capture.budget_chars"));
+        assert!(unknown_setting("zzz.qqq").ends_with("(run `config show` for known settings)"));
+    }
+
+    #[test]
+    fn cli_values_are_typed_per_setting() {
+        let spec = setting_by_key("capture.clip_footer").unwrap();
+        assert_eq!(
+            parse_setting_value(spec, "off").unwrap(),
+            toml::Value::Boolean(false)
+        );
+        let spec = setting_by_key("capture.budget_chars").unwrap();
+        assert_eq!(
+            parse_setting_value(spec, "20000").unwrap(),
+            toml::Value::Integer(20000)
+        );
+        assert!(parse_setting_value(spec, "-1").is_err());
+        let spec = setting_by_key("llm.backend").unwrap();
+        let err = parse_setting_value(spec, "gpt").unwrap_err();
+        assert_eq!(err, "llm.backend: expected one of codex|ollama|mock");
+    }
+}
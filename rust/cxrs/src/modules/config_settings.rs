@@ -0,0 +1,513 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    Usize,
+    Bool,
+    /// Free-form string, or one of the listed values when non-empty.
+    Text(&'static [&'static str]),
+}
+
+/// One user-facing setting: its config-file key, the env var that overrides it,
+/// and (for legacy preferences) the state path that sits between the two.
+pub struct SettingSpec {
+    pub key: &'static str,
+    pub env: &'static str,
+    pub state_path: Option<&'static str>,
+    pub kind: SettingKind,
+    pub default: &'static str,
+    /// False for settings that name a program, a destination or a security
+    /// control: a cloned repo's `.codex/config.toml` must not pick those.
+    pub repo_overridable: bool,
+}
+
+/// Every layered setting. Keys with `repo_overridable: false` are read from the
+/// env and the global config only; a repo config setting them is warned about.
+pub const SETTINGS: &[SettingSpec] = &[
+    SettingSpec {
+        key: "capture.budget_chars",
+        env: "CX_CONTEXT_BUDGET_CHARS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "12000",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "capture.budget_lines",
+        env: "CX_CONTEXT_BUDGET_LINES",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "300",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "capture.budget_tokens",
+        env: "CX_CONTEXT_BUDGET_TOKENS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "0",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "capture.clip_mode",
+        env: "CX_CONTEXT_CLIP_MODE",
+        state_path: None,
+        kind: SettingKind::Text(&["smart", "head", "tail", "error-window"]),
+        default: "smart",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "capture.error_regex",
+        env: "CX_CONTEXT_ERROR_REGEX",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "capture.clip_footer",
+        env: "CX_CONTEXT_CLIP_FOOTER",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "1",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "capture.stderr_budget_pct",
+        env: "CX_CONTEXT_STDERR_BUDGET_PCT",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "50",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "capture.provider",
+        env: "CX_CAPTURE_PROVIDER",
+        state_path: None,
+        kind: SettingKind::Text(&["native", "pty"]),
+        default: "native",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "capture.chunk_strategy",
+        env: "CX_CHUNK_STRATEGY",
+        state_path: None,
+        kind: SettingKind::Text(&["clip", "mapreduce"]),
+        default: "clip",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "capture.chunk_max",
+        env: "CX_CHUNK_MAX",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "8",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "capture.redact",
+        env: "CX_REDACT",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "1",
+        repo_overridable: false,
+    },
+    SettingSpec {
+        key: "digest.webhook",
+        env: "CX_DIGEST_WEBHOOK",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: false,
+    },
+    SettingSpec {
+        key: "digest.email",
+        env: "CX_DIGEST_EMAIL",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: false,
+    },
+    SettingSpec {
+        key: "digest.sendmail",
+        env: "CX_DIGEST_SENDMAIL",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "sendmail",
+        repo_overridable: false,
+    },
+    SettingSpec {
+        key: "alert.enabled",
+        env: "CXALERT_ENABLED",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "1",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "alert.max_ms",
+        env: "CXALERT_MAX_MS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "12000",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "alert.max_eff_in",
+        env: "CXALERT_MAX_EFF_IN",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "8000",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "alert.max_out",
+        env: "CXALERT_MAX_OUT",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "500",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "llm.backend",
+        env: "CX_LLM_BACKEND",
+        state_path: Some("preferences.llm_backend"),
+        kind: SettingKind::Text(&["codex", "ollama", "mock"]),
+        default: "codex",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "llm.model",
+        env: "CX_MODEL",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "llm.codex_args",
+        env: "CX_CODEX_ARGS",
+        state_path: Some("preferences.codex_args"),
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "llm.ollama_model",
+        env: "CX_OLLAMA_MODEL",
+        state_path: Some("preferences.ollama_model"),
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "llm.backend_chain",
+        env: "CX_BACKEND_CHAIN",
+        state_path: Some("preferences.backend_chain"),
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "llm.broker_policy",
+        env: "CX_BROKER_POLICY",
+        state_path: Some("preferences.broker_policy"),
+        kind: SettingKind::Text(&["latency", "quality", "cost", "balanced", "quota_saver"]),
+        default: "balanced",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "llm.context_windows",
+        env: "CX_CONTEXT_WINDOWS",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "llm.context_guard",
+        env: "CX_CONTEXT_GUARD",
+        state_path: None,
+        kind: SettingKind::Text(&["shrink", "fail", "off"]),
+        default: "shrink",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "llm.timeout_secs",
+        env: "CX_LLM_TIMEOUT_SECS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "0",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "http.token",
+        env: "CX_HTTP_PROVIDER_TOKEN",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: false,
+    },
+    SettingSpec {
+        key: "runtime.mode",
+        env: "CX_MODE",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "lean",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "runtime.cmd_timeout_secs",
+        env: "CX_CMD_TIMEOUT_SECS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "120",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "runtime.schema_relaxed",
+        env: "CX_SCHEMA_RELAXED",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "0",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "runtime.stream",
+        env: "CX_STREAM",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "0",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "runtime.offline",
+        env: "CX_OFFLINE",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "0",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "runtime.nested",
+        env: "CX_NESTED",
+        state_path: None,
+        kind: SettingKind::Text(&["block", "warn", "allow"]),
+        default: "block",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "runtime.codex_root",
+        env: "CX_CODEX_ROOT",
+        state_path: None,
+        kind: SettingKind::Text(&["nearest", "shared"]),
+        default: "nearest",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "runtime.tee",
+        env: "CX_TEE",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "0",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "log.enabled",
+        env: "CXLOG_ENABLED",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "1",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "session.enabled",
+        env: "CX_SESSION_ENABLED",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "0",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "session.name",
+        env: "CX_SESSION",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "default",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "session.turns",
+        env: "CX_SESSION_TURNS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "3",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "transcripts.enabled",
+        env: "CX_TRANSCRIPTS",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "0",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "transcripts.keep_days",
+        env: "CX_TRANSCRIPTS_KEEP_DAYS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "14",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "transcripts.max_bytes",
+        env: "CX_TRANSCRIPTS_MAX_BYTES",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "52428800",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "hooks.max_schema_fail_pct",
+        env: "CX_HOOK_MAX_SCHEMA_FAIL_PCT",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "20",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "gc.auto",
+        env: "CX_GC_AUTO",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "1",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "gc.logs_keep_days",
+        env: "CX_GC_LOGS_KEEP_DAYS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "30",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "gc.logs_max_bytes",
+        env: "CX_GC_LOGS_MAX_BYTES",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "104857600",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "gc.cache_keep_days",
+        env: "CX_GC_CACHE_KEEP_DAYS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "30",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "gc.cache_max_bytes",
+        env: "CX_GC_CACHE_MAX_BYTES",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "268435456",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "gc.sessions_keep_days",
+        env: "CX_GC_SESSIONS_KEEP_DAYS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "30",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "gc.sessions_max_bytes",
+        env: "CX_GC_SESSIONS_MAX_BYTES",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "52428800",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "gc.quarantine_keep_days",
+        env: "CX_GC_QUARANTINE_KEEP_DAYS",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "30",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "gc.quarantine_max_bytes",
+        env: "CX_GC_QUARANTINE_MAX_BYTES",
+        state_path: None,
+        kind: SettingKind::Usize,
+        default: "52428800",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "policy.builtin",
+        env: "CX_POLICY_BUILTIN",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "1",
+        repo_overridable: false,
+    },
+    SettingSpec {
+        key: "policy.write_allow",
+        env: "CX_POLICY_WRITE_ALLOW",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: false,
+    },
+    SettingSpec {
+        key: "progress.format",
+        env: "CX_PROGRESS",
+        state_path: None,
+        kind: SettingKind::Text(&["auto", "human", "json", "off"]),
+        default: "auto",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "render.markdown",
+        env: "CX_RENDER_MARKDOWN",
+        state_path: None,
+        kind: SettingKind::Bool,
+        default: "0",
+        repo_overridable: true,
+    },
+    SettingSpec {
+        key: "sandbox.mode",
+        env: "CX_SANDBOX",
+        state_path: None,
+        kind: SettingKind::Text(&["none", "container", "nsjail"]),
+        default: "none",
+        repo_overridable: false,
+    },
+    SettingSpec {
+        key: "sandbox.writable",
+        env: "CX_SANDBOX_WRITABLE",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "",
+        repo_overridable: false,
+    },
+    SettingSpec {
+        key: "sandbox.image",
+        env: "CX_SANDBOX_IMAGE",
+        state_path: None,
+        kind: SettingKind::Text(&[]),
+        default: "debian:stable-slim",
+        repo_overridable: false,
+    },
+];
+
+pub fn setting_by_key(key: &str) -> Option<&'static SettingSpec> {
+    SETTINGS.iter().find(|s| s.key == key)
+}
+
+pub fn setting_by_env(env_name: &str) -> Option<&'static SettingSpec> {
+    SETTINGS.iter().find(|s| s.env == env_name)
+}
@@ -4,3 +4,29 @@ pub const OPTIMIZE_JSON_CONTRACT_VERSION: &str = "optimize.v1";
 pub const TELEMETRY_JSON_CONTRACT_VERSION: &str = "telemetry.v1";
 pub const BROKER_BENCHMARK_JSON_CONTRACT_VERSION: &str = "broker-benchmark.v1";
 pub const ACTIONS_JSON_CONTRACT_VERSION: &str = "actions.v1";
+pub const PROFILE_JSON_CONTRACT_VERSION: &str = "profile.v1";
+pub const ALERT_JSON_CONTRACT_VERSION: &str = "alert.v1";
+pub const WORKLOG_JSON_CONTRACT_VERSION: &str = "worklog.v1";
+pub const PROMPTLINT_JSON_CONTRACT_VERSION: &str = "promptlint.v1";
+pub const BENCH_JSON_CONTRACT_VERSION: &str = "bench.v1";
+pub const BENCH_LLM_JSON_CONTRACT_VERSION: &str = "bench-llm.v1";
+pub const MODEL_BENCH_JSON_CONTRACT_VERSION: &str = "model-bench.v1";
+pub const HEALTH_JSON_CONTRACT_VERSION: &str = "health.v1";
+pub const WHERE_JSON_CONTRACT_VERSION: &str = "where.v1";
+pub const CHANGELOG_JSON_CONTRACT_VERSION: &str = "changelog.v1";
+pub const TRACE_JSON_CONTRACT_VERSION: &str = "trace.v1";
+pub const DIFF_RUNS_JSON_CONTRACT_VERSION: &str = "diff-runs.v1";
+pub const TRENDS_JSON_CONTRACT_VERSION: &str = "trends.v1";
+pub const DIGEST_JSON_CONTRACT_VERSION: &str = "digest.v1";
+pub const PIPELINE_JSON_CONTRACT_VERSION: &str = "pipeline.v1";
+pub const BUDGET_SUGGEST_JSON_CONTRACT_VERSION: &str = "budget-suggest.v1";
+pub const REMEDIATIONS_JSON_CONTRACT_VERSION: &str = "remediations.v1";
+pub const QUARANTINE_STATS_JSON_CONTRACT_VERSION: &str = "quarantine-stats.v1";
+pub const QUARANTINE_DIFF_JSON_CONTRACT_VERSION: &str = "quarantine-diff.v1";
+pub const REPLAY_JSON_CONTRACT_VERSION: &str = "replay.v1";
+pub const RERUN_JSON_CONTRACT_VERSION: &str = "rerun.v1";
+pub const LOGS_QUERY_JSON_CONTRACT_VERSION: &str = "logs-query.v1";
+pub const LOGS_FAILURES_JSON_CONTRACT_VERSION: &str = "logs-failures.v1";
+pub const STAGE_JSON_CONTRACT_VERSION: &str = "stage.v1";
+pub const GC_JSON_CONTRACT_VERSION: &str = "gc.v1";
+pub const POLICY_CHECK_JSON_CONTRACT_VERSION: &str = "policy-check.v1";
@@ -0,0 +1,323 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Component, Path};
+
+use crate::capture::run_system_command_capture;
+use crate::error::{EXIT_OK, format_error, print_runtime_error, print_usage_error};
+use crate::paths::{resolve_custom_commands_file, resolve_prompt_template_dir};
+use crate::prompt_templates::{check_placeholders, fill};
+use crate::schema::{load_schema_for_tool, normalize_schema_name};
+use crate::structured_cmds::{ExecuteTaskFn, parse_schema_json};
+use crate::types::{LlmOutputKind, PromptTemplateStamp, TaskInput, TaskSpec};
+use crate::util::sha256_hex;
+
+/// Run-log `tool` for `custom <name>` is `cxrs_custom_<name>`.
+pub const CUSTOM_TOOL_PREFIX: &str = "cxrs_custom_";
+const USAGE: &str = "custom <name> [-- <command> [args...]] | custom list [--json]";
+const PLACEHOLDERS: &[&str] = &["command", "status", "output", "schema"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomCommand {
+    pub name: String,
+    pub description: String,
+    /// Schema file name in the registry (`<name>.schema.json`).
+    pub schema: String,
+    /// Inline template, or `None` when `prompt_file` is used.
+    pub prompt: Option<String>,
+    /// Template path relative to `.codex/prompts/`.
+    pub prompt_file: Option<String>,
+    /// Default capture command; `-- <cmd...>` on the command line replaces it.
+    pub command: Vec<String>,
+}
+
+fn valid_command_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+fn opt_string(def: &Value, key: &str, origin: &str, name: &str) -> Result<Option<String>, String> {
+    match def.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(format!("{origin}: command '{name}' {key} must be a string")),
+    }
+}
+
+/// Parse `{"commands": {"<name>": {"schema", "prompt"|"prompt_file", "command"?, "description"?}}}`.
+fn parse_command_map(value: &Value, origin: &str) -> Result<Vec<CustomCommand>, String> {
+    let obj = value
+        .get("commands")
+        .and_then(Value::as_object)
+        .ok_or_else(|| format!("{origin}: expected an object at \"commands\""))?;
+    let mut out = Vec::new();
+    for (name, def) in obj {
+        if !valid_command_name(name) || name == "list" {
+            return Err(format!(
+                "{origin}: invalid command name '{name}' (use lowercase letters, digits, '-' or '_'; 'list' is reserved)"
+            ));
+        }
+        let schema = opt_string(def, "schema", origin, name)?
+            .filter(|s| !s.trim().is_empty())
+            .ok_or_else(|| format!("{origin}: command '{name}' needs a 'schema'"))?;
+        let prompt = opt_string(def, "prompt", origin, name)?;
+        let prompt_file = opt_string(def, "prompt_file", origin, name)?;
+        if prompt.is_some() == prompt_file.is_some() {
+            return Err(format!(
+                "{origin}: command '{name}' needs exactly one of 'prompt' or 'prompt_file'"
+            ));
+        }
+        if let Some(rel) = prompt_file.as_deref().filter(|r| !plain_relative_path(r)) {
+            return Err(format!(
+                "{origin}: command '{name}' prompt_file must be a path inside .codex/prompts: {rel}"
+            ));
+        }
+        let command = match def.get("command") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|v| v.as_str().map(ToOwned::to_owned))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| format!("{origin}: command '{name}' command must be strings"))?,
+            Some(_) => {
+                return Err(format!(
+                    "{origin}: command '{name}' command must be an array of strings"
+                ));
+            }
+        };
+        out.push(CustomCommand {
+            name: name.clone(),
+            description: opt_string(def, "description", origin, name)?.unwrap_or_default(),
+            schema: normalize_schema_name(&schema),
+            prompt,
+            prompt_file,
+            command,
+        });
+    }
+    Ok(out)
+}
+
+/// `prompt_file` stays under the prompt dir: no root, `.` or `..` components.
+fn plain_relative_path(rel: &str) -> bool {
+    let mut comps = Path::new(rel).components().peekable();
+    comps.peek().is_some() && comps.all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Definitions from `.codex/commands.json`; empty when the file is absent.
+pub fn load_custom_commands() -> Result<Vec<CustomCommand>, String> {
+    let Some(path) = resolve_custom_commands_file().filter(|p| p.is_file()) else {
+        return Ok(Vec::new());
+    };
+    let origin = path.display().to_string();
+    let raw = fs::read_to_string(&path).map_err(|e| format!("cannot read {origin}: {e}"))?;
+    let value: Value =
+        serde_json::from_str(&raw).map_err(|e| format!("{origin}: invalid JSON: {e}"))?;
+    parse_command_map(&value, &origin)
+}
+
+/// Schema a `cxrs_custom_<name>` tool enforces, per its definition.
+pub fn custom_schema_for_tool(tool: &str) -> Option<String> {
+    let name = tool.strip_prefix(CUSTOM_TOOL_PREFIX)?;
+    load_custom_commands()
+        .ok()?
+        .into_iter()
+        .find(|c| c.name == name)
+        .map(|c| c.schema)
+}
+
+/// Template text plus where it came from, for the run-log stamp.
+fn load_template(def: &CustomCommand) -> Result<(String, String), String> {
+    if let Some(p) = &def.prompt {
+        let origin = resolve_custom_commands_file()
+            .map(|f| f.display().to_string())
+            .unwrap_or_else(|| "commands.json".to_string());
+        return Ok((p.clone(), format!("{origin}#{}", def.name)));
+    }
+    let rel = def.prompt_file.as_deref().unwrap_or_default();
+    let path = resolve_prompt_template_dir()
+        .ok_or_else(|| "unable to resolve prompt template dir".to_string())?
+        .join(rel);
+    let raw =
+        fs::read_to_string(&path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    Ok((raw, path.display().to_string()))
+}
+
+fn run_custom(
+    def: &CustomCommand,
+    command: &[String],
+    execute_task: ExecuteTaskFn,
+) -> Result<Value, String> {
+    let tool = format!("{CUSTOM_TOOL_PREFIX}{}", def.name);
+    let (template, source) = load_template(def)?;
+    let required: &[&str] = if command.is_empty() { &[] } else { &["output"] };
+    check_placeholders(&template, required, PLACEHOLDERS)
+        .map_err(|e| format!("prompt template {source}: {e}"))?;
+    let schema = load_schema_for_tool(&tool)?;
+    let (output, status, capture) = if command.is_empty() {
+        (String::new(), 0, None)
+    } else {
+        let (out, status, stats) = run_system_command_capture(&tool, command)?;
+        (out, status, Some(stats))
+    };
+    let schema_text = serde_json::to_string_pretty(&schema.value).unwrap_or_default();
+    let task_input = fill(
+        &template,
+        &[
+            ("command", &command.join(" ")),
+            ("status", &status.to_string()),
+            ("output", &output),
+            ("schema", &schema_text),
+        ],
+    );
+    let stamp = PromptTemplateStamp {
+        name: format!("custom:{}", def.name),
+        source,
+        sha256: sha256_hex(&template),
+    };
+    let result = execute_task(TaskSpec {
+        command_name: tool,
+        input: TaskInput::Prompt(task_input.clone()),
+        output_kind: LlmOutputKind::SchemaJson,
+        schema: Some(schema),
+        schema_task_input: Some(task_input),
+        logging_enabled: true,
+        capture_override: capture,
+        prompt_template: Some(stamp),
+        stream: false,
+    })?;
+    parse_schema_json(&result)
+}
+
+fn print_list(json_out: bool) -> i32 {
+    let defs = match load_custom_commands() {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("custom", &e),
+    };
+    if json_out {
+        let rows: Vec<Value> = defs
+            .iter()
+            .map(|d| {
+                json!({
+                    "name": d.name,
+                    "description": d.description,
+                    "schema": d.schema,
+                    "command": d.command,
+                })
+            })
+            .collect();
+        println!("{}", json!({ "commands": rows }));
+        return EXIT_OK;
+    }
+    println!("== cxrs custom ==");
+    if defs.is_empty() {
+        println!("No custom commands (define them in .codex/commands.json).");
+    }
+    for d in &defs {
+        let cmd = if d.command.is_empty() {
+            String::new()
+        } else {
+            format!(" [command: {}]", d.command.join(" "))
+        };
+        println!("- {} -> {}{cmd}", d.name, d.schema);
+        if !d.description.is_empty() {
+            println!("    {}", d.description);
+        }
+    }
+    EXIT_OK
+}
+
+/// `custom <name> [-- cmd...]`: capture the command (or the definition's
+/// default), render the template, enforce the schema and print the JSON.
+/// Failures quarantine and log like the built-in structured commands.
+pub fn cmd_custom(args: &[String], execute_task: ExecuteTaskFn) -> i32 {
+    let (name, rest) = match args.split_first() {
+        None => return print_list(false),
+        Some((n, rest)) if n == "list" => {
+            return match rest {
+                [] => print_list(false),
+                [f] if f == "--json" => print_list(true),
+                _ => print_usage_error("custom", USAGE),
+            };
+        }
+        Some((n, rest)) => (n, rest),
+    };
+    let cli_command: &[String] = match rest.split_first() {
+        None => &[],
+        Some((sep, cmd)) if sep == "--" => cmd,
+        Some(_) => return print_usage_error("custom", USAGE),
+    };
+    let defs = match load_custom_commands() {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("custom", &e),
+    };
+    let Some(def) = defs.iter().find(|d| &d.name == name) else {
+        let known: Vec<&str> = defs.iter().map(|d| d.name.as_str()).collect();
+        crate::cx_eprintln!(
+            "{}",
+            format_error(
+                "custom",
+                &format!(
+                    "unknown command '{name}' (known: {})",
+                    if known.is_empty() {
+                        "none".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                )
+            )
+        );
+        return print_usage_error("custom", USAGE);
+    };
+    let command = if cli_command.is_empty() {
+        &def.command[..]
+    } else {
+        cli_command
+    };
+    let value = match run_custom(def, command, execute_task) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("custom", &e),
+    };
+    match serde_json::to_string_pretty(&value) {
+        Ok(s) => println!("{s}"),
+        Err(e) => return print_runtime_error("custom", &format!("render failure: {e}")),
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_map_parses_and_rejects_bad_definitions() {
+        let v = json!({"commands": {"lint-summary": {
+            "schema": "lint", "prompt": "{{output}}", "command": ["cargo", "clippy"]}}});
+        let defs = parse_command_map(&v, "cfg").expect("parsed");
+        assert_eq!(defs[0].schema, "lint.schema.json");
+        assert_eq!(defs[0].command, vec!["cargo", "clippy"]);
+
+        let both = json!({"commands": {"x": {"schema": "s", "prompt": "a", "prompt_file": "b"}}});
+        assert!(
+            parse_command_map(&both, "cfg")
+                .unwrap_err()
+                .contains("exactly one")
+        );
+        let no_schema = json!({"commands": {"x": {"prompt": "a"}}});
+        assert!(parse_command_map(&no_schema, "cfg").is_err());
+        let reserved = json!({"commands": {"list": {"schema": "s", "prompt": "a"}}});
+        assert!(parse_command_map(&reserved, "cfg").is_err());
+        for bad in [
+            "/etc/passwd",
+            "../secrets.md",
+            "sub/../../x.md",
+            "./x.md",
+            "",
+        ] {
+            let v = json!({"commands": {"x": {"schema": "s", "prompt_file": bad}}});
+            assert!(parse_command_map(&v, "cfg").unwrap_err().contains("inside"));
+        }
+        let nested = json!({"commands": {"x": {"schema": "s", "prompt_file": "team/x.md"}}});
+        assert!(parse_command_map(&nested, "cfg").is_ok());
+    }
+}
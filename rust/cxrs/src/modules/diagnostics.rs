@@ -17,8 +17,11 @@ use crate::routing::{bash_type_of_function, route_handler_for};
 use crate::runtime::{llm_backend, llm_model};
 
 fn resolved_provider(cfg_provider: &str) -> &'static str {
-    let _ = cfg_provider;
-    "native"
+    if cfg_provider == "pty" {
+        "pty"
+    } else {
+        "native"
+    }
 }
 
 fn schema_count(schema_dir: &Path) -> usize {
@@ -0,0 +1,186 @@
+use chrono::Utc;
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::command_names::COMMANDS;
+use crate::config_layers::repo_config_file;
+use crate::paths::{
+    ensure_parent_dir, home_dir, legacy_migration_hint, resolve_log_file, resolve_quarantine_dir,
+    resolve_state_file, resolved_paths,
+};
+use crate::state::{default_state_value, write_json_atomic};
+
+const DEFAULT_CONFIG: &str = "# cxrs repo config; overrides ~/.codex/config.toml, overridden by env.\n# See settings with `cxrs config show --origin`; set one with `cxrs config set <key> <value> --repo`.\n";
+
+/// Changes made (or failed) by `doctor --fix`, printed in order.
+#[derive(Default)]
+struct FixLog {
+    changes: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl FixLog {
+    fn record(&mut self, res: Result<Option<String>, String>) {
+        match res {
+            Ok(Some(change)) => self.changes.push(change),
+            Ok(None) => {}
+            Err(e) => self.errors.push(e),
+        }
+    }
+}
+
+pub(crate) fn ensure_dir(dir: &Path) -> Result<Option<String>, String> {
+    if dir.is_dir() {
+        return Ok(None);
+    }
+    fs::create_dir_all(dir).map_err(|e| format!("failed to create {}: {e}", dir.display()))?;
+    Ok(Some(format!("created directory {}", dir.display())))
+}
+
+/// Write the default state when missing; back up and regenerate it when it
+/// does not parse as a JSON object.
+pub(crate) fn fix_state_file(path: &Path) -> Result<Option<String>, String> {
+    if !path.exists() {
+        write_json_atomic(path, &default_state_value())?;
+        return Ok(Some(format!("initialized {}", path.display())));
+    }
+    let raw =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    if serde_json::from_str::<Value>(&raw).is_ok_and(|v| v.is_object()) {
+        return Ok(None);
+    }
+    let backup = path.with_extension(format!(
+        "json.corrupt-{}",
+        Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    fs::rename(path, &backup).map_err(|e| {
+        format!(
+            "failed to back up {} -> {}: {e}",
+            path.display(),
+            backup.display()
+        )
+    })?;
+    write_json_atomic(path, &default_state_value())?;
+    Ok(Some(format!(
+        "regenerated corrupt {} (backup: {})",
+        path.display(),
+        backup.display()
+    )))
+}
+
+pub(crate) fn fix_config_file(path: &Path) -> Result<Option<String>, String> {
+    if path.exists() {
+        return Ok(None);
+    }
+    ensure_parent_dir(path)?;
+    fs::write(path, DEFAULT_CONFIG)
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    Ok(Some(format!("initialized {}", path.display())))
+}
+
+fn completions_file() -> Option<PathBuf> {
+    let data = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| home_dir().map(|h| h.join(".local").join("share")))?;
+    Some(
+        data.join("bash-completion")
+            .join("completions")
+            .join("cxrs"),
+    )
+}
+
+fn bash_completion_script() -> String {
+    let names: Vec<&str> = COMMANDS.iter().map(|c| c.name).collect();
+    format!(
+        "# bash completion for cxrs (installed by `cxrs doctor --fix`)\n_cxrs() {{\n  if [ \"$COMP_CWORD\" -eq 1 ]; then\n    COMPREPLY=($(compgen -W \"{}\" -- \"${{COMP_WORDS[1]}}\"))\n  fi\n}}\ncomplete -o default -F _cxrs cxrs\n",
+        names.join(" ")
+    )
+}
+
+fn fix_completions() -> Result<Option<String>, String> {
+    let Some(path) = completions_file() else {
+        return Err("cannot resolve a completions dir (HOME/XDG_DATA_HOME unset)".to_string());
+    };
+    if path.exists() {
+        return Ok(None);
+    }
+    ensure_parent_dir(&path)?;
+    fs::write(&path, bash_completion_script())
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    Ok(Some(format!(
+        "installed bash completions {}",
+        path.display()
+    )))
+}
+
+/// `doctor --fix`: repair what `doctor` can only report, printing each change.
+pub fn cmd_doctor_fix() -> i32 {
+    let mut log = FixLog::default();
+    let dirs = [
+        resolve_log_file().and_then(|p| p.parent().map(Path::to_path_buf)),
+        resolve_quarantine_dir(),
+    ];
+    for dir in dirs.into_iter().flatten() {
+        log.record(ensure_dir(&dir));
+    }
+    match resolve_state_file() {
+        Some(p) => log.record(fix_state_file(&p)),
+        None => log.errors.push("unable to resolve state file".to_string()),
+    }
+    if let Some(p) = repo_config_file() {
+        log.record(fix_config_file(&p));
+    }
+    log.record(fix_completions());
+    // Permissions are reported rather than changed; see `where --json`.
+    for p in resolved_paths() {
+        if let Some(path) = p.path.as_ref().filter(|_| !p.writable()) {
+            log.errors
+                .push(format!("{} is not writable: {}", p.name, path.display()));
+        }
+    }
+
+    println!("== cxrs doctor --fix ==");
+    if log.changes.is_empty() && log.errors.is_empty() {
+        println!("nothing to fix");
+    }
+    for change in &log.changes {
+        println!("fixed: {change}");
+    }
+    for e in &log.errors {
+        println!("FAIL: {e}");
+    }
+    // Moving data between layouts is left to the user; print the commands.
+    if let Some(hint) = legacy_migration_hint() {
+        println!("migrate: {hint}");
+    }
+    if log.errors.is_empty() { 0 } else { 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupt_state_is_backed_up_and_regenerated() {
+        let dir = env::temp_dir().join(format!("cxdoctorfix-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let state = dir.join("state.json");
+        fs::write(&state, "{not json").unwrap();
+        let change = fix_state_file(&state).unwrap().unwrap();
+        assert!(change.contains("regenerated corrupt"), "{change}");
+        let fixed: Value = serde_json::from_str(&fs::read_to_string(&state).unwrap()).unwrap();
+        assert!(fixed.get("preferences").is_some());
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().contains("corrupt"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(backups[0].path()).unwrap(), "{not json");
+        assert_eq!(fix_state_file(&state).unwrap(), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
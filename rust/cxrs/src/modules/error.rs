@@ -1,15 +1,120 @@
 use std::fmt;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicI32, Ordering};
 
 pub type CxResult<T> = Result<T, CxError>;
 pub const EXIT_OK: i32 = 0;
 pub const EXIT_RUNTIME: i32 = 1;
 pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_POLICY: i32 = 3;
+pub const EXIT_SCHEMA: i32 = 4;
+pub const EXIT_BACKEND: i32 = 5;
+pub const EXIT_OFFLINE: i32 = 6;
+pub const EXIT_TIMEOUT: i32 = 124;
+pub const EXIT_INTERRUPTED: i32 = 130;
 
+/// Exit-code contract, printed by `cxrs exit-codes`. Scripts may branch on
+/// these; codes are never reused for a different meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitKind {
+    Ok,
+    Runtime,
+    Usage,
+    Policy,
+    Schema,
+    Backend,
+    Offline,
+    Timeout,
+    Interrupted,
+    PassThrough,
+}
+
+impl ExitKind {
+    pub const ALL: [ExitKind; 10] = [
+        ExitKind::Ok,
+        ExitKind::Runtime,
+        ExitKind::Usage,
+        ExitKind::Policy,
+        ExitKind::Schema,
+        ExitKind::Backend,
+        ExitKind::Offline,
+        ExitKind::Timeout,
+        ExitKind::Interrupted,
+        ExitKind::PassThrough,
+    ];
+
+    /// `None` for pass-through: the wrapped command's own status is returned.
+    pub const fn code(self) -> Option<i32> {
+        match self {
+            ExitKind::Ok => Some(EXIT_OK),
+            ExitKind::Runtime => Some(EXIT_RUNTIME),
+            ExitKind::Usage => Some(EXIT_USAGE),
+            ExitKind::Policy => Some(EXIT_POLICY),
+            ExitKind::Schema => Some(EXIT_SCHEMA),
+            ExitKind::Backend => Some(EXIT_BACKEND),
+            ExitKind::Offline => Some(EXIT_OFFLINE),
+            ExitKind::Timeout => Some(EXIT_TIMEOUT),
+            ExitKind::Interrupted => Some(EXIT_INTERRUPTED),
+            ExitKind::PassThrough => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExitKind::Ok => "ok",
+            ExitKind::Runtime => "runtime_error",
+            ExitKind::Usage => "usage_error",
+            ExitKind::Policy => "policy_blocked",
+            ExitKind::Schema => "schema_failure",
+            ExitKind::Backend => "backend_failure",
+            ExitKind::Offline => "offline_blocked",
+            ExitKind::Timeout => "timeout",
+            ExitKind::Interrupted => "interrupted",
+            ExitKind::PassThrough => "command_status",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            ExitKind::Ok => "Success",
+            ExitKind::Runtime => "Generic runtime failure (I/O, missing input, bad state)",
+            ExitKind::Usage => "Invalid arguments or unknown command",
+            ExitKind::Policy => "A selected command was blocked by the safety policy",
+            ExitKind::Schema => {
+                "LLM output failed schema validation (entry quarantined; see `quarantine list`)"
+            }
+            ExitKind::Backend => "LLM backend failed (spawn error, non-zero exit, bad output)",
+            ExitKind::Offline => "LLM call refused: offline mode is on (CX_OFFLINE=1 / --offline)",
+            ExitKind::Timeout => "LLM backend call exceeded CX_LLM_TIMEOUT_SECS / --timeout",
+            ExitKind::Interrupted => "LLM backend call stopped by Ctrl-C or SIGTERM",
+            ExitKind::PassThrough => {
+                "cx/cxj/cxo/cxol/cxcopy/fix/fix-run return the wrapped command's own status"
+            }
+        }
+    }
+}
+
+static FAILURE_EXIT: AtomicI32 = AtomicI32::new(EXIT_RUNTIME);
+
+/// Record the class of a failure at its source (backend call, schema check) so
+/// the command reporting it later exits with the specific code.
+pub fn note_failure(kind: ExitKind) {
+    if let Some(code) = kind.code() {
+        FAILURE_EXIT.store(code, Ordering::Relaxed);
+    }
+}
+
+/// Exit code for the failure being reported: the last noted class, else runtime.
+pub fn failure_exit() -> i32 {
+    FAILURE_EXIT.load(Ordering::Relaxed)
+}
+
+/// `eprintln!` that first clears a pending progress status line.
 #[macro_export]
 macro_rules! cx_eprintln {
     ($($arg:tt)*) => {
         {
+            $crate::progress::clear_line();
             let mut stderr_lock = std::io::stderr().lock();
             let _ = std::io::Write::write_fmt(&mut stderr_lock, format_args!($($arg)*));
             let _ = std::io::Write::write_all(&mut stderr_lock, b"\n");
@@ -100,7 +205,7 @@ pub fn format_error(command: &str, error: &str) -> String {
 
 pub fn print_runtime_error(command: &str, error: &str) -> i32 {
     crate::cx_eprintln!("{}", format_error(command, error));
-    EXIT_RUNTIME
+    failure_exit()
 }
 
 pub fn print_usage_error(command: &str, usage: &str) -> i32 {
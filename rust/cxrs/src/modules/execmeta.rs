@@ -2,8 +2,10 @@ use chrono::Utc;
 use std::fs;
 use std::process::Command;
 
+use crate::custom_cmds::CUSTOM_TOOL_PREFIX;
 use crate::paths::repo_root_hint;
 use crate::process::run_command_output_with_timeout;
+use crate::schema::is_registry_mapped_tool;
 
 pub fn prompt_preview(s: &str, max: usize) -> String {
     s.chars().take(max).collect()
@@ -52,6 +54,8 @@ pub fn utc_now_iso() -> String {
     Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
 }
 
+/// Built-in structured tools, `custom` commands and any tool mapped in the
+/// schema registry.
 pub fn is_schema_tool(tool: &str) -> bool {
     matches!(
         tool,
@@ -66,11 +70,16 @@ pub fn is_schema_tool(tool: &str) -> bool {
             | "cxrs_diffsum_staged"
             | "cxrs_next"
             | "cxrs_fix_run"
+            | "cxrs_explain"
+            | "cxrs_fix"
+            | "cxrs_fanout"
             | "commitjson"
             | "commitmsg"
             | "diffsum"
             | "diffsum-staged"
             | "next"
             | "fix-run"
-    )
+            | "explain"
+    ) || tool.starts_with(CUSTOM_TOOL_PREFIX)
+        || is_registry_mapped_tool(tool)
 }
@@ -2,13 +2,19 @@ use serde_json::Value;
 use std::time::Instant;
 
 use crate::config::app_config;
+use crate::error::{ExitKind, note_failure};
 use crate::execmeta::make_execution_id;
 use crate::execution_logging::{LogExecutionErrorInput, log_execution_error};
 use crate::llm::{LlmRunError, extract_agent_text, usage_from_jsonl};
+use crate::model_context::{clear_context_outcome, fit_prompt};
+use crate::progress;
 use crate::prompt_filter::process_prompt;
-use crate::provider_adapter::{resolve_provider_adapter, run_jsonl_with_current_adapter};
+use crate::provider_adapter::{
+    clear_failover_outcome, resolve_provider_adapter, run_jsonl_with_current_adapter,
+};
 use crate::runlog::log_schema_failure;
-use crate::schema::{build_schema_prompt_envelope, validate_schema_instance};
+use crate::schema::{build_schema_prompt_envelope, repair_json_text, validate_schema_instance};
+use crate::stream::{StreamMode, StreamPrinter};
 use crate::types::{
     CaptureStats, ExecutionResult, LlmOutputKind, QuarantineAttempt, TaskInput, TaskSpec,
     UsageStats,
@@ -19,6 +25,17 @@ pub fn run_llm_jsonl(prompt: &str) -> Result<String, String> {
     run_jsonl_with_current_adapter(prompt).map_err(|e| e.message)
 }
 
+/// Tell the model up front when the captured command failed.
+pub(crate) fn frame_failed_capture(cmd: &[String], status: i32, captured: String) -> String {
+    if status == 0 {
+        return captured;
+    }
+    format!(
+        "[cx] command `{}` exited with status {status}; focus on why it failed.\n{captured}",
+        cmd.join(" ")
+    )
+}
+
 pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
     let started = Instant::now();
     let execution_id = make_execution_id(&spec.command_name);
@@ -26,8 +43,13 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
     let (prompt, capture_stats, system_status) = match &spec.input {
         TaskInput::Prompt(p) => (p.clone(), CaptureStats::default(), None),
         TaskInput::SystemCommand(cmd) => {
-            let (captured, status, stats) = crate::capture::run_system_command_capture(cmd)?;
-            (captured, stats, Some(status))
+            let (captured, status, stats) =
+                crate::capture::run_system_command_capture(&spec.command_name, cmd)?;
+            (
+                frame_failed_capture(cmd, status, captured),
+                stats,
+                Some(status),
+            )
         }
     };
     let capture_stats = spec
@@ -38,15 +60,43 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
     let prompt_raw = prompt.clone();
     let prompt_tx = process_prompt(&prompt_raw, spec.output_kind == LlmOutputKind::SchemaJson);
     let prompt = prompt_tx.filtered.clone();
+    clear_context_outcome();
+    // Schema runs fit the task input inside the envelope instead.
+    let prompt = if spec.output_kind == LlmOutputKind::SchemaJson {
+        prompt
+    } else {
+        match fit_prompt(prompt, "") {
+            Ok(v) => v,
+            Err(e) => {
+                log_execution_error(LogExecutionErrorInput {
+                    spec: &spec,
+                    prompt: &prompt_tx.filtered,
+                    prompt_raw: &prompt_raw,
+                    prompt_filtered: &prompt_tx.filtered,
+                    capture_stats: &capture_stats,
+                    usage: &UsageStats::default(),
+                    schema_name: None,
+                    schema_prompt: None,
+                    schema_raw: None,
+                    schema_attempt: None,
+                    err: &e,
+                    started: &started,
+                });
+                return Err(e.message);
+            }
+        }
+    };
 
     let mut schema_valid: Option<bool> = None;
     let mut quarantine_id: Option<String> = None;
+    let mut repair_steps: Vec<&'static str> = Vec::new();
     let mut schema_prompt_for_log: Option<String> = None;
     let mut schema_raw_for_log: Option<String> = None;
     let mut schema_attempt_for_log: Option<u64> = None;
     let mut usage = UsageStats::default();
     let stdout: String;
     let stderr = String::new();
+    clear_failover_outcome();
     let adapter = match resolve_provider_adapter() {
         Ok(v) => v,
         Err(e) => {
@@ -68,9 +118,24 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
         }
     };
 
+    // Without `--stream`, a counting printer still feeds progress token events.
+    let mut printer = StreamMode::for_kind(spec.output_kind).and_then(|mode| {
+        if spec.stream {
+            Some(StreamPrinter::new(mode))
+        } else {
+            progress::enabled().then(|| StreamPrinter::counting(mode))
+        }
+    });
+    // Schema runs open one span per attempt instead.
+    let llm_span = (spec.output_kind != LlmOutputKind::SchemaJson)
+        .then(|| progress::llm_started(&spec.command_name, &prompt));
     match spec.output_kind {
         LlmOutputKind::Plain => {
-            stdout = match adapter.run_plain(&prompt) {
+            let res = match printer.as_mut() {
+                Some(out) => adapter.run_plain_streaming(&prompt, out),
+                None => adapter.run_plain(&prompt),
+            };
+            stdout = match res {
                 Ok(v) => v,
                 Err(e) => {
                     log_execution_error(LogExecutionErrorInput {
@@ -92,7 +157,11 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
             };
         }
         LlmOutputKind::Jsonl => {
-            let jsonl = match adapter.run_jsonl(&prompt) {
+            let res = match printer.as_mut() {
+                Some(out) => adapter.run_jsonl_streaming(&prompt, out),
+                None => adapter.run_jsonl(&prompt),
+            };
+            let jsonl = match res {
                 Ok(v) => v,
                 Err(e) => {
                     log_execution_error(LogExecutionErrorInput {
@@ -116,7 +185,11 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
             stdout = jsonl;
         }
         LlmOutputKind::AgentText => {
-            let jsonl = match adapter.run_jsonl(&prompt) {
+            let res = match printer.as_mut() {
+                Some(out) => adapter.run_jsonl_streaming(&prompt, out),
+                None => adapter.run_jsonl(&prompt),
+            };
+            let jsonl = match res {
                 Ok(v) => v,
                 Err(e) => {
                     log_execution_error(LogExecutionErrorInput {
@@ -151,6 +224,27 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
                 .to_string();
             let schema_pretty = serde_json::to_string_pretty(&schema.value)
                 .unwrap_or_else(|_| schema.value.to_string());
+            let envelope_frame = build_schema_prompt_envelope(&schema_pretty, "", None).full_prompt;
+            let task_input = match fit_prompt(task_input, &envelope_frame) {
+                Ok(v) => v,
+                Err(e) => {
+                    log_execution_error(LogExecutionErrorInput {
+                        spec: &spec,
+                        prompt: &prompt,
+                        prompt_raw: &prompt_raw,
+                        prompt_filtered: &prompt,
+                        capture_stats: &capture_stats,
+                        usage: &usage,
+                        schema_name: Some(schema.name.as_str()),
+                        schema_prompt: None,
+                        schema_raw: Some(schema_pretty.as_str()),
+                        schema_attempt: None,
+                        err: &e,
+                        started: &started,
+                    });
+                    return Err(e.message);
+                }
+            };
             let retry_allowed = !app_config().schema_relaxed;
             let mut attempts: Vec<QuarantineAttempt> = Vec::new();
             let mut final_reason: Option<String> = None;
@@ -162,17 +256,34 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
             let run_attempt =
                 |full_prompt: &str| -> Result<(String, UsageStats, String), LlmRunError> {
                     let prompt_tx = process_prompt(full_prompt, true);
-                    let jsonl = adapter.run_jsonl(&prompt_tx.filtered)?;
+                    let _span = progress::llm_started(&spec.command_name, &prompt_tx.filtered);
+                    let jsonl = if progress::enabled() {
+                        let mut counter = StreamPrinter::counting(StreamMode::AgentText);
+                        adapter.run_jsonl_streaming(&prompt_tx.filtered, &mut counter)?
+                    } else {
+                        adapter.run_jsonl(&prompt_tx.filtered)?
+                    };
                     let usage = usage_from_jsonl(&jsonl);
                     let raw = extract_agent_text(&jsonl).unwrap_or_default();
                     Ok((raw, usage, prompt_tx.filtered))
                 };
 
-            let validate_raw = |raw: &str| -> Result<Value, String> {
+            // Fenced or prose-wrapped JSON is repaired locally before it
+            // costs a retry or a quarantine; the original reason is kept.
+            let validate_raw = |raw: &str| -> Result<(Value, Vec<&'static str>), String> {
                 if raw.trim().is_empty() {
                     return Err("empty_agent_message".to_string());
                 }
-                validate_schema_instance(schema, raw)
+                let reason = match validate_schema_instance(schema, raw) {
+                    Ok(v) => return Ok((v, Vec::new())),
+                    Err(e) => e,
+                };
+                let Some((fixed, steps)) = repair_json_text(raw) else {
+                    return Err(reason);
+                };
+                validate_schema_instance(schema, &fixed)
+                    .map(|v| (v, steps))
+                    .map_err(|_| reason)
             };
 
             let (first_raw, first_usage, first_prompt_filtered) =
@@ -202,8 +313,9 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
             usage = first_usage;
 
             match validate_raw(&first_raw) {
-                Ok(valid) => {
+                Ok((valid, steps)) => {
                     schema_valid = Some(true);
+                    repair_steps = steps;
                     stdout = valid.to_string();
                 }
                 Err(reason_first) => {
@@ -248,8 +360,9 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
                         schema_prompt_for_log = Some(retry_prompt_filtered.clone());
                         usage = retry_usage;
                         match validate_raw(&retry_raw) {
-                            Ok(valid) => {
+                            Ok((valid, steps)) => {
                                 schema_valid = Some(true);
+                                repair_steps = steps;
                                 stdout = valid.to_string();
                             }
                             Err(reason_retry) => {
@@ -288,6 +401,9 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
                         quarantine_id = Some(qid);
                         stdout = first_raw;
                     }
+                    if schema_valid == Some(false) {
+                        note_failure(ExitKind::Schema);
+                    }
 
                     if spec.logging_enabled {
                         let _ = crate::runlog::log_codex_run(crate::runlog::RunLogInput {
@@ -295,6 +411,7 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
                             prompt: &last_schema_prompt_filtered,
                             prompt_raw: Some(&last_schema_prompt_raw),
                             prompt_filtered: Some(&last_schema_prompt_filtered),
+                            response: Some(&stdout),
                             schema_prompt: schema_prompt_for_log.as_deref(),
                             schema_raw: schema_raw_for_log.as_deref(),
                             schema_attempt: schema_attempt_for_log,
@@ -308,12 +425,18 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
                             schema_reason: final_reason.as_deref(),
                             schema_name: Some(schema.name.as_str()),
                             quarantine_id: quarantine_id.as_deref(),
+                            schema_repair: (!repair_steps.is_empty())
+                                .then_some(repair_steps.as_slice()),
                             policy_blocked: None,
                             policy_reason: None,
+                            fix_decisions: None,
+                            prompt_template: spec.prompt_template.as_ref(),
+                            clipboard_provider: None,
                         });
                     }
                     return Ok(ExecutionResult {
                         stdout,
+                        prompt: task_input,
                         stderr,
                         duration_ms: started.elapsed().as_millis() as u64,
                         schema_valid,
@@ -327,6 +450,10 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
             }
         }
     }
+    if let Some(out) = printer.as_mut() {
+        out.finish();
+    }
+    drop(llm_span);
 
     if spec.logging_enabled {
         let _ = crate::runlog::log_codex_run(crate::runlog::RunLogInput {
@@ -334,6 +461,7 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
             prompt: &prompt,
             prompt_raw: Some(&prompt_raw),
             prompt_filtered: Some(&prompt),
+            response: Some(&stdout),
             schema_prompt: schema_prompt_for_log.as_deref(),
             schema_raw: schema_raw_for_log.as_deref(),
             schema_attempt: schema_attempt_for_log,
@@ -347,13 +475,18 @@ pub fn execute_task(spec: TaskSpec) -> Result<ExecutionResult, String> {
             schema_reason: None,
             schema_name: spec.schema.as_ref().map(|s| s.name.as_str()),
             quarantine_id: quarantine_id.as_deref(),
+            schema_repair: (!repair_steps.is_empty()).then_some(repair_steps.as_slice()),
             policy_blocked: None,
             policy_reason: None,
+            fix_decisions: None,
+            prompt_template: spec.prompt_template.as_ref(),
+            clipboard_provider: None,
         });
     }
 
     Ok(ExecutionResult {
         stdout,
+        prompt,
         stderr,
         duration_ms: started.elapsed().as_millis() as u64,
         schema_valid,
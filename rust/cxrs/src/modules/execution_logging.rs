@@ -1,5 +1,6 @@
 use std::time::Instant;
 
+use crate::error::{ExitKind, note_failure};
 use crate::llm::LlmRunError;
 use crate::runlog::{RunLogInput, log_codex_run};
 use crate::types::{CaptureStats, TaskSpec, UsageStats};
@@ -34,6 +35,16 @@ pub(crate) fn log_execution_error(input: LogExecutionErrorInput<'_>) {
         err,
         started,
     } = input;
+    let (kind, reason) = if err.timeout.is_some() {
+        (ExitKind::Timeout, "timeout")
+    } else if err.interrupted {
+        (ExitKind::Interrupted, "interrupted")
+    } else if err.offline {
+        (ExitKind::Offline, "offline")
+    } else {
+        (ExitKind::Backend, err.message.as_str())
+    };
+    note_failure(kind);
     if !spec.logging_enabled {
         return;
     }
@@ -45,6 +56,7 @@ pub(crate) fn log_execution_error(input: LogExecutionErrorInput<'_>) {
         prompt,
         prompt_raw: Some(prompt_raw),
         prompt_filtered: Some(prompt_filtered),
+        response: None,
         schema_prompt,
         schema_raw,
         schema_attempt,
@@ -55,10 +67,14 @@ pub(crate) fn log_execution_error(input: LogExecutionErrorInput<'_>) {
         usage: Some(usage),
         capture: Some(capture_stats),
         schema_ok: false,
-        schema_reason: Some(err.message.as_str()),
+        schema_reason: Some(reason),
         schema_name,
         quarantine_id: None,
+        schema_repair: None,
         policy_blocked: None,
         policy_reason: None,
+        fix_decisions: None,
+        prompt_template: spec.prompt_template.as_ref(),
+        clipboard_provider: None,
     });
 }
@@ -0,0 +1,67 @@
+use serde_json::{Value, json};
+
+use crate::error::{EXIT_OK, ExitKind, print_usage_error};
+
+fn code_label(kind: ExitKind) -> String {
+    kind.code()
+        .map(|c| c.to_string())
+        .unwrap_or_else(|| "*".to_string())
+}
+
+fn exit_codes_json() -> Value {
+    Value::Array(
+        ExitKind::ALL
+            .iter()
+            .map(|k| {
+                json!({
+                    "code": k.code(),
+                    "name": k.name(),
+                    "description": k.description(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// `exit-codes [--json]`: print the exit-code contract. Pass-through is shown
+/// as `*` (JSON `null`) since the code is the wrapped command's own status.
+pub fn cmd_exit_codes(args: &[String]) -> i32 {
+    let json_out = match args {
+        [] => false,
+        [flag] if flag == "--json" => true,
+        _ => return print_usage_error("exit-codes", "exit-codes [--json]"),
+    };
+    if json_out {
+        println!("{}", exit_codes_json());
+        return EXIT_OK;
+    }
+    for kind in ExitKind::ALL {
+        println!(
+            "{:<4} {:<16} {}",
+            code_label(kind),
+            kind.name(),
+            kind.description()
+        );
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_unique_and_pass_through_is_null() {
+        let v = exit_codes_json();
+        let rows = v.as_array().expect("array");
+        let mut codes: Vec<i64> = rows.iter().filter_map(|r| r["code"].as_i64()).collect();
+        let n = codes.len();
+        codes.dedup();
+        assert_eq!(codes.len(), n);
+        assert_eq!(rows.len(), n + 1);
+        assert!(
+            rows.iter()
+                .any(|r| r["name"] == "command_status" && r["code"].is_null())
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use crate::capture::{budget_config_from_env, clip_text_with_config};
+use crate::error::{EXIT_OK, format_error, print_runtime_error, print_usage_error};
+use crate::schema::load_schema_for_tool;
+use crate::structured_cmds::{ExecuteTaskFn, parse_schema_json, render_bullets};
+use crate::types::{LlmOutputKind, TaskInput, TaskSpec};
+
+const EXPLAIN_USAGE: &str = "explain <file>[:START[-END]] [--json]";
+/// Sibling file names offered to the model as related-file candidates.
+const SIBLING_LIMIT: usize = 40;
+
+#[derive(Debug, PartialEq, Eq)]
+struct ExplainTarget {
+    path: String,
+    start: usize,
+    end: Option<usize>,
+}
+
+impl ExplainTarget {
+    fn label(&self) -> String {
+        match (self.start, self.end) {
+            (1, None) => self.path.clone(),
+            (s, Some(e)) if s == e => format!("{}:{s}", self.path),
+            (s, Some(e)) => format!("{}:{s}-{e}", self.path),
+            (s, None) => format!("{}:{s}-", self.path),
+        }
+    }
+}
+
+/// Parse `file`, `file:10`, `file:10-40` or `file:10-`; an existing path wins over a range suffix.
+fn parse_target(raw: &str) -> Result<ExplainTarget, String> {
+    let whole = || ExplainTarget {
+        path: raw.to_string(),
+        start: 1,
+        end: None,
+    };
+    if Path::new(raw).exists() {
+        return Ok(whole());
+    }
+    let Some((path, range)) = raw.rsplit_once(':') else {
+        return Ok(whole());
+    };
+    let bad = || format!("invalid line range '{range}' (expected START, START-END or START-)");
+    let parse_line = |s: &str| s.parse::<usize>().ok().filter(|n| *n > 0).ok_or_else(bad);
+    let (start, end) = match range.split_once('-') {
+        Some((s, "")) => (parse_line(s)?, None),
+        Some((s, e)) => (parse_line(s)?, Some(parse_line(e)?)),
+        None => {
+            let n = parse_line(range)?;
+            (n, Some(n))
+        }
+    };
+    if end.is_some_and(|e| e < start) {
+        return Err(bad());
+    }
+    Ok(ExplainTarget {
+        path: path.to_string(),
+        start,
+        end,
+    })
+}
+
+/// Selected lines prefixed with their 1-based line numbers.
+fn numbered_excerpt(text: &str, start: usize, end: Option<usize>) -> Result<String, String> {
+    let total = text.lines().count();
+    if start > total.max(1) {
+        return Err(format!(
+            "line {start} is past the end of the file ({total} lines)"
+        ));
+    }
+    let end = end.unwrap_or(total).min(total);
+    Ok(text
+        .lines()
+        .enumerate()
+        .skip(start - 1)
+        .take(end + 1 - start)
+        .map(|(i, line)| format!("{:>5} | {line}", i + 1))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn sibling_files(path: &Path) -> Vec<String> {
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let own = path.file_name();
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_file() && Some(e.file_name().as_os_str()) != own)
+        .filter_map(|e| e.file_name().to_str().map(str::to_string))
+        .filter(|n| !n.starts_with('.'))
+        .collect();
+    names.sort();
+    names.truncate(SIBLING_LIMIT);
+    names
+}
+
+fn print_explain_human(label: &str, v: &Value) {
+    println!("File: {label}");
+    println!(
+        "Purpose: {}",
+        v.get("purpose").and_then(Value::as_str).unwrap_or("n/a")
+    );
+    println!();
+    println!("Key functions:");
+    let funcs = v
+        .get("key_functions")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    if funcs.is_empty() {
+        println!("- n/a");
+    }
+    for f in funcs {
+        let name = f.get("name").and_then(Value::as_str).unwrap_or("?");
+        let summary = f.get("summary").and_then(Value::as_str).unwrap_or("");
+        println!("- {name}: {summary}");
+    }
+    for (title, key) in [("Risks", "risks"), ("Related files", "related_files")] {
+        println!();
+        println!("{title}:");
+        let items = render_bullets(v.get(key));
+        if items.is_empty() {
+            println!("- n/a");
+        }
+        for s in items {
+            println!("- {s}");
+        }
+    }
+}
+
+pub fn cmd_explain(args: &[String], execute_task: ExecuteTaskFn) -> i32 {
+    let json_out = args.iter().any(|a| a == "--json");
+    let positional: Vec<&String> = args.iter().filter(|a| *a != "--json").collect();
+    let [raw] = positional.as_slice() else {
+        return print_usage_error("explain", EXPLAIN_USAGE);
+    };
+    if raw.starts_with("--") {
+        return print_usage_error("explain", EXPLAIN_USAGE);
+    }
+    let target = match parse_target(raw) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("explain", &e));
+            return print_usage_error("explain", EXPLAIN_USAGE);
+        }
+    };
+    let path = Path::new(&target.path);
+    let text = match fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(e) => {
+            return print_runtime_error("explain", &format!("cannot read {}: {e}", target.path));
+        }
+    };
+    let excerpt = match numbered_excerpt(&text, target.start, target.end) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("explain", &e),
+    };
+    let mut budget = budget_config_from_env();
+    // Source reads top-down; the error-sniffing `smart` mode would keep the tail.
+    if budget.clip_mode == "smart" {
+        budget.clip_mode = "head".to_string();
+    }
+    let (clipped, capture_stats) = clip_text_with_config(&excerpt, &budget);
+    let siblings = sibling_files(path);
+    let schema = match load_schema_for_tool("cxrs_explain") {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("explain", &e),
+    };
+    let label = target.label();
+    let task_input = format!(
+        "Explain this code for a developer who is about to change it.\n\
+Describe its purpose, the key functions/types (name + one-line summary), risks or \
+non-obvious behaviour, and related files worth reading next.\n\
+Sibling files in the same directory: {}\n\nFILE {label}:\n{clipped}",
+        if siblings.is_empty() {
+            "none".to_string()
+        } else {
+            siblings.join(", ")
+        }
+    );
+    let result = execute_task(TaskSpec {
+        command_name: "cxrs_explain".to_string(),
+        input: TaskInput::Prompt(task_input.clone()),
+        output_kind: LlmOutputKind::SchemaJson,
+        schema: Some(schema),
+        schema_task_input: Some(task_input),
+        logging_enabled: true,
+        capture_override: Some(capture_stats),
+        prompt_template: None,
+        stream: false,
+    })
+    .and_then(|r| parse_schema_json(&r));
+    let value = match result {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("explain", &e),
+    };
+    if json_out {
+        match serde_json::to_string_pretty(&value) {
+            Ok(s) => println!("{s}"),
+            Err(e) => {
+                return print_runtime_error("explain", &format!("render failure: {e}"));
+            }
+        }
+    } else {
+        print_explain_human(&label, &value);
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_ranges_parse() {
+        let t = parse_target("no/such/file.rs:10-40").unwrap();
+        assert_eq!(
+            (t.path.as_str(), t.start, t.end),
+            ("no/such/file.rs", 10, Some(40))
+        );
+        assert_eq!(t.label(), "no/such/file.rs:10-40");
+        let t = parse_target("a.rs:7").unwrap();
+        assert_eq!((t.start, t.end), (7, Some(7)));
+        let t = parse_target("a.rs:7-").unwrap();
+        assert_eq!((t.start, t.end), (7, None));
+        let t = parse_target("a.rs").unwrap();
+        assert_eq!((t.start, t.end, t.label()), (1, None, "a.rs".to_string()));
+        assert!(parse_target("a.rs:0").is_err());
+        assert!(parse_target("a.rs:9-3").is_err());
+        assert!(parse_target("a.rs:x").is_err());
+    }
+
+    #[test]
+    fn excerpt_numbers_selected_lines() {
+        let text = "one\ntwo\nthree\nfour\n";
+        assert_eq!(
+            numbered_excerpt(text, 2, Some(3)).unwrap(),
+            "    2 | two\n    3 | three"
+        );
+        assert_eq!(numbered_excerpt(text, 4, Some(99)).unwrap(), "    4 | four");
+        assert!(numbered_excerpt(text, 5, None).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use serde_json::Value;
+
+use crate::capture::{budget_config_from_env, clip_text_with_config};
+use crate::roles::load_roles;
+use crate::schema::load_schema_for_tool;
+use crate::structured_cmds::{ExecuteTaskFn, parse_schema_json};
+use crate::types::{LlmOutputKind, TaskInput, TaskSpec};
+
+/// One model-proposed subtask; `depends_on` holds 1-based indexes of earlier subtasks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmSubtask {
+    pub role: String,
+    pub objective: String,
+    pub depends_on: Vec<usize>,
+    pub estimate: String,
+}
+
+/// Check the schema-valid response against the known roles and require
+/// dependencies to point at earlier subtasks only (so the graph is acyclic).
+fn parse_subtasks(v: &Value, known_roles: &[String]) -> Result<Vec<LlmSubtask>, String> {
+    let items = v
+        .get("subtasks")
+        .and_then(Value::as_array)
+        .ok_or_else(|| "missing required key 'subtasks' array".to_string())?;
+    let mut out = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let n = i + 1;
+        let field = |k: &str| {
+            item.get(k)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| format!("subtask {n}: missing '{k}'"))
+        };
+        let role = field("role")?.to_lowercase();
+        if !known_roles.contains(&role) {
+            return Err(format!(
+                "subtask {n}: unknown role '{role}' (known: {})",
+                known_roles.join(", ")
+            ));
+        }
+        let mut depends_on = Vec::new();
+        for dep in item
+            .get("depends_on")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            match dep.as_u64().map(|d| d as usize) {
+                Some(d) if d >= 1 && d < n => depends_on.push(d),
+                _ => {
+                    return Err(format!(
+                        "subtask {n}: depends_on {dep} must reference an earlier subtask (1-{})",
+                        n - 1
+                    ));
+                }
+            }
+        }
+        depends_on.sort_unstable();
+        depends_on.dedup();
+        out.push(LlmSubtask {
+            role,
+            objective: field("objective")?,
+            depends_on,
+            estimate: field("estimate")?,
+        });
+    }
+    Ok(out)
+}
+
+/// Ask the backend to split `objective` into subtasks (strict `fanout` schema).
+pub fn generate_subtasks(
+    objective: &str,
+    context: Option<&str>,
+    execute_task: ExecuteTaskFn,
+) -> Result<Vec<LlmSubtask>, String> {
+    let roles = load_roles()?;
+    let role_lines = roles
+        .iter()
+        .map(|r| format!("- {}: {}", r.name, r.summary))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let schema = load_schema_for_tool("cxrs_fanout")?;
+    let (context_block, capture_stats) = match context.filter(|c| !c.trim().is_empty()) {
+        Some(c) => {
+            let (clipped, stats) = clip_text_with_config(c, &budget_config_from_env());
+            (format!("\n\nCONTEXT:\n{clipped}"), Some(stats))
+        }
+        None => (String::new(), None),
+    };
+    let task_input = format!(
+        "Split the objective below into 2-8 independently executable subtasks.\n\
+For each give: role (one of the roles listed), objective (one concrete sentence), \
+depends_on (1-based indexes of EARLIER subtasks it needs, or []), and estimate \
+(rough size such as 30m, 2h or 1d).\n\nRoles:\n{role_lines}\n\nOBJECTIVE:\n{objective}{context_block}"
+    );
+    let result = execute_task(TaskSpec {
+        command_name: "cxrs_fanout".to_string(),
+        input: TaskInput::Prompt(task_input.clone()),
+        output_kind: LlmOutputKind::SchemaJson,
+        schema: Some(schema),
+        schema_task_input: Some(task_input),
+        logging_enabled: true,
+        capture_override: capture_stats,
+        prompt_template: None,
+        stream: false,
+    })?;
+    let value = parse_schema_json(&result)?;
+    let names: Vec<String> = roles.iter().map(|r| r.name.clone()).collect();
+    parse_subtasks(&value, &names)
+}
+
+pub fn print_llm_fanout(objective: &str, subtasks: &[LlmSubtask]) {
+    println!("== cxrs fanout (llm) ==");
+    println!("objective: {objective}");
+    println!();
+    for (idx, t) in subtasks.iter().enumerate() {
+        println!("### Subtask {}/{} [{}]", idx + 1, subtasks.len(), t.role);
+        println!("Goal: {}", t.objective);
+        let deps = if t.depends_on.is_empty() {
+            "none".to_string()
+        } else {
+            t.depends_on
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        println!("Depends on: {deps}");
+        println!("Estimate: {}", t.estimate);
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn roles() -> Vec<String> {
+        ["architect", "implementer", "tester"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn subtasks_parse_with_backward_dependencies() {
+        let v = json!({"subtasks": [
+            {"role": "Architect", "objective": "plan", "depends_on": [], "estimate": "1h"},
+            {"role": "implementer", "objective": "build", "depends_on": [1, 1], "estimate": "1d"},
+            {"role": "tester", "objective": "verify", "depends_on": [2, 1], "estimate": "2h"}
+        ]});
+        let out = parse_subtasks(&v, &roles()).unwrap();
+        assert_eq!(out[0].role, "architect");
+        assert_eq!(out[1].depends_on, vec![1]);
+        assert_eq!(out[2].depends_on, vec![1, 2]);
+    }
+
+    #[test]
+    fn subtasks_reject_unknown_roles_and_forward_deps() {
+        let v = json!({"subtasks": [
+            {"role": "wizard", "objective": "x", "depends_on": [], "estimate": "1h"}
+        ]});
+        let err = parse_subtasks(&v, &roles()).unwrap_err();
+        assert!(err.starts_with("subtask 1: unknown role 'wizard'"));
+        let v = json!({"subtasks": [
+            {"role": "tester", "objective": "x", "depends_on": [1], "estimate": "1h"}
+        ]});
+        let err = parse_subtasks(&v, &roles()).unwrap_err();
+        assert!(err.contains("must reference an earlier subtask"));
+    }
+}
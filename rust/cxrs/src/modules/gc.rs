@@ -0,0 +1,368 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::analytics::parse_ts_epoch;
+use crate::config::app_config;
+use crate::contract_versions::GC_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, print_runtime_error, print_usage_error};
+use crate::paths::{
+    resolve_cache_dir, resolve_log_file, resolve_quarantine_dir, resolve_sessions_dir,
+    resolve_transcripts_dir,
+};
+use crate::quarantine::scan_quarantine_dir;
+use crate::verbosity::{Verbosity, diag, warn};
+use crate::workspace::human_bytes;
+
+const GC_USAGE: &str =
+    "gc [--dry-run] [--json] [--store logs|cache|transcripts|sessions|quarantine]";
+const STORES: &[&str] = &["logs", "cache", "transcripts", "sessions", "quarantine"];
+/// Stamp next to the run log; auto-gc scans at most once per interval.
+const AUTO_GC_STAMP: &str = ".gc_stamp";
+const AUTO_GC_INTERVAL: Duration = Duration::from_secs(3_600);
+
+#[derive(Debug, Clone)]
+pub struct GcFile {
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// Age reference: mtime, or `resolved_ts` for quarantine records.
+    pub since: SystemTime,
+}
+
+/// Indexes into `files` (oldest first) to delete, with the reason: files
+/// older than `keep_days` (`0` = no age limit), then the oldest remaining
+/// ones until `total` fits in `max_bytes` (`0` = no cap). `total` is the
+/// whole store's size, which may include files that are never candidates.
+pub fn plan_removals(
+    files: &[GcFile],
+    mut total: u64,
+    keep_days: usize,
+    max_bytes: u64,
+    now: SystemTime,
+) -> Vec<(usize, &'static str)> {
+    let cutoff = (keep_days > 0)
+        .then(|| now.checked_sub(Duration::from_secs(keep_days as u64 * 86_400)))
+        .flatten();
+    let mut out = Vec::new();
+    for (i, f) in files.iter().enumerate() {
+        let reason = if cutoff.is_some_and(|c| f.since < c) {
+            "age"
+        } else if max_bytes > 0 && total > max_bytes {
+            "size"
+        } else {
+            continue;
+        };
+        total = total.saturating_sub(f.bytes);
+        out.push((i, reason));
+    }
+    out
+}
+
+fn gc_file(path: PathBuf, meta: &fs::Metadata) -> Option<GcFile> {
+    Some(GcFile {
+        path,
+        bytes: meta.len(),
+        since: meta.modified().ok()?,
+    })
+}
+
+/// Regular files directly in `dir` whose name passes `keep`.
+pub fn dir_files(dir: &Path, keep: impl Fn(&str) -> bool) -> Vec<GcFile> {
+    let Ok(rd) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    rd.flatten()
+        .filter(|e| keep(&e.file_name().to_string_lossy()))
+        .filter_map(|e| {
+            let meta = e.metadata().ok().filter(fs::Metadata::is_file)?;
+            gc_file(e.path(), &meta)
+        })
+        .collect()
+}
+
+fn walk_files(dir: &Path, out: &mut Vec<GcFile>) {
+    let Ok(rd) = fs::read_dir(dir) else {
+        return;
+    };
+    for e in rd.flatten() {
+        let Ok(meta) = e.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            walk_files(&e.path(), out);
+        } else if let Some(f) = gc_file(e.path(), &meta) {
+            out.push(f);
+        }
+    }
+}
+
+/// Rotated and backup copies beside the live logs (`runs.jsonl.bak.<ts>`,
+/// `runs.jsonl.1`, ...); the live `*.jsonl` files are never touched.
+fn is_rotated_log(name: &str) -> bool {
+    name.contains(".jsonl.")
+}
+
+struct StorePlan {
+    name: &'static str,
+    dir: Option<PathBuf>,
+    keep_days: usize,
+    max_bytes: u64,
+    files: usize,
+    bytes: u64,
+    removals: Vec<(GcFile, &'static str)>,
+}
+
+impl StorePlan {
+    fn freed(&self) -> u64 {
+        self.removals.iter().map(|(f, _)| f.bytes).sum()
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "store": self.name,
+            "dir": self.dir.as_ref().map(|d| d.display().to_string()),
+            "keep_days": self.keep_days,
+            "max_bytes": self.max_bytes,
+            "files": self.files,
+            "bytes": self.bytes,
+            "removed": self.removals.iter().map(|(f, reason)| json!({
+                "path": f.path.display().to_string(),
+                "bytes": f.bytes,
+                "reason": reason,
+            })).collect::<Vec<_>>(),
+            "freed_bytes": self.freed(),
+        })
+    }
+}
+
+/// Candidates (oldest first) and the store's total size.
+fn store_files(name: &str, dir: &Path) -> (Vec<GcFile>, usize, u64) {
+    let (mut candidates, all) = match name {
+        "logs" => {
+            let files = dir_files(dir, is_rotated_log);
+            (files.clone(), files)
+        }
+        "cache" => {
+            let mut files = Vec::new();
+            walk_files(dir, &mut files);
+            (files.clone(), files)
+        }
+        "transcripts" => {
+            let files = dir_files(dir, |n| n.ends_with(crate::transcripts::SUFFIX));
+            (files.clone(), files)
+        }
+        "sessions" => {
+            let files = dir_files(dir, |n| n.ends_with(".jsonl"));
+            (files.clone(), files)
+        }
+        _ => {
+            let all = dir_files(dir, |n| n.ends_with(".json"));
+            let resolved = scan_quarantine_dir(dir)
+                .into_iter()
+                .filter_map(|(path, rec)| {
+                    let epoch = parse_ts_epoch(rec.resolved_ts.as_deref()?)?;
+                    let bytes = all.iter().find(|f| f.path == path)?.bytes;
+                    Some(GcFile {
+                        path,
+                        bytes,
+                        since: UNIX_EPOCH + Duration::from_secs(u64::try_from(epoch).ok()?),
+                    })
+                })
+                .collect();
+            (resolved, all)
+        }
+    };
+    candidates.sort_by(|a, b| a.since.cmp(&b.since).then_with(|| a.path.cmp(&b.path)));
+    (candidates, all.len(), all.iter().map(|f| f.bytes).sum())
+}
+
+fn plan_store(name: &'static str, now: SystemTime) -> StorePlan {
+    let cfg = app_config();
+    let (dir, keep_days, max_bytes) = match name {
+        "logs" => (
+            resolve_log_file().and_then(|p| p.parent().map(Path::to_path_buf)),
+            cfg.gc_logs_keep_days,
+            cfg.gc_logs_max_bytes,
+        ),
+        "cache" => (
+            resolve_cache_dir(),
+            cfg.gc_cache_keep_days,
+            cfg.gc_cache_max_bytes,
+        ),
+        "transcripts" => (
+            resolve_transcripts_dir(),
+            cfg.transcripts_keep_days,
+            cfg.transcripts_max_bytes,
+        ),
+        "sessions" => (
+            resolve_sessions_dir(),
+            cfg.gc_sessions_keep_days,
+            cfg.gc_sessions_max_bytes,
+        ),
+        _ => (
+            resolve_quarantine_dir(),
+            cfg.gc_quarantine_keep_days,
+            cfg.gc_quarantine_max_bytes,
+        ),
+    };
+    let mut plan = StorePlan {
+        name,
+        dir,
+        keep_days,
+        max_bytes: max_bytes as u64,
+        files: 0,
+        bytes: 0,
+        removals: Vec::new(),
+    };
+    let Some(dir) = plan.dir.clone() else {
+        return plan;
+    };
+    let (candidates, files, bytes) = store_files(name, &dir);
+    plan.files = files;
+    plan.bytes = bytes;
+    plan.removals = plan_removals(&candidates, bytes, keep_days, plan.max_bytes, now)
+        .into_iter()
+        .map(|(i, reason)| (candidates[i].clone(), reason))
+        .collect();
+    plan
+}
+
+/// Delete every planned file; returns `(removed, errors)`.
+fn apply(plans: &[StorePlan]) -> (usize, Vec<String>) {
+    let mut removed = 0;
+    let mut errors = Vec::new();
+    for (f, _) in plans.iter().flat_map(|p| &p.removals) {
+        match fs::remove_file(&f.path) {
+            Ok(()) => removed += 1,
+            Err(e) => errors.push(format!("failed to remove {}: {e}", f.path.display())),
+        }
+    }
+    (removed, errors)
+}
+
+fn print_plans(plans: &[StorePlan], dry_run: bool) {
+    println!("== cxrs gc{} ==", if dry_run { " (dry run)" } else { "" });
+    let verb = if dry_run { "would remove" } else { "removed" };
+    for p in plans {
+        let limit = |v: u64, f: &dyn Fn(u64) -> String| {
+            if v == 0 { "none".to_string() } else { f(v) }
+        };
+        println!(
+            "{}: {} file(s), {} (keep {}, max {}) -> {verb} {} ({})",
+            p.name,
+            p.files,
+            human_bytes(p.bytes),
+            limit(p.keep_days as u64, &|d| format!("{d}d")),
+            limit(p.max_bytes, &human_bytes),
+            p.removals.len(),
+            human_bytes(p.freed()),
+        );
+        for (f, reason) in &p.removals {
+            println!("  - {} [{reason}]", f.path.display());
+        }
+    }
+    let total: u64 = plans.iter().map(StorePlan::freed).sum();
+    println!("total: {verb} {}", human_bytes(total));
+}
+
+/// `gc`: apply retention limits to rotated logs, cache, transcripts and
+/// resolved quarantine records.
+pub fn cmd_gc(args: &[String]) -> i32 {
+    let mut dry_run = false;
+    let mut json_out = false;
+    let mut only: Option<&'static str> = None;
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--dry-run" => dry_run = true,
+            "--json" => json_out = true,
+            "--store" => match it.next().and_then(|v| STORES.iter().find(|s| **s == v)) {
+                Some(s) => only = Some(s),
+                None => return print_usage_error("gc", GC_USAGE),
+            },
+            _ => return print_usage_error("gc", GC_USAGE),
+        }
+    }
+    let now = SystemTime::now();
+    let plans: Vec<StorePlan> = STORES
+        .iter()
+        .filter(|s| only.is_none_or(|o| o == **s))
+        .map(|s| plan_store(s, now))
+        .collect();
+    let errors = if dry_run { Vec::new() } else { apply(&plans).1 };
+    if json_out {
+        let v = json!({
+            "contract_version": GC_JSON_CONTRACT_VERSION,
+            "dry_run": dry_run,
+            "stores": plans.iter().map(StorePlan::to_json).collect::<Vec<_>>(),
+            "freed_bytes": plans.iter().map(StorePlan::freed).sum::<u64>(),
+            "errors": errors,
+        });
+        println!("{v}");
+    } else {
+        print_plans(&plans, dry_run);
+    }
+    if let Some(first) = errors.first() {
+        return print_runtime_error("gc", first);
+    }
+    EXIT_OK
+}
+
+/// Lightweight gc after a logged run: at most once per [`AUTO_GC_INTERVAL`]
+/// per log dir, and only deletes when a store is over its limits.
+/// Best-effort: failures are warned about and never fail the run.
+pub fn auto_gc(run_log: &Path) {
+    if !app_config().gc_auto {
+        return;
+    }
+    let Some(stamp) = run_log.parent().map(|d| d.join(AUTO_GC_STAMP)) else {
+        return;
+    };
+    let now = SystemTime::now();
+    let fresh = fs::metadata(&stamp)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| now.duration_since(t).ok())
+        .is_some_and(|age| age < AUTO_GC_INTERVAL);
+    if fresh {
+        return;
+    }
+    if let Err(e) = fs::write(&stamp, b"") {
+        warn(&format!("cxrs: auto-gc skipped: {e}"));
+        return;
+    }
+    let plans: Vec<StorePlan> = STORES.iter().map(|s| plan_store(s, now)).collect();
+    let (removed, errors) = apply(&plans);
+    for e in errors {
+        warn(&format!("cxrs: auto-gc: {e}"));
+    }
+    if removed > 0 {
+        diag(
+            Verbosity::Verbose,
+            "auto_gc",
+            &[("removed", removed.to_string())],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_applies_age_then_size_oldest_first() {
+        let now = SystemTime::now();
+        let file = |name: &str, days: u64| GcFile {
+            path: PathBuf::from(name),
+            bytes: 100,
+            since: now - Duration::from_secs(days * 86_400),
+        };
+        let files = [file("a", 30), file("b", 3), file("c", 2), file("d", 1)];
+        let plan = plan_removals(&files, 460, 14, 250, now);
+        assert_eq!(plan, [(0, "age"), (1, "size"), (2, "size")]);
+        assert!(plan_removals(&files, 460, 0, 0, now).is_empty());
+        assert!(is_rotated_log("runs.jsonl.bak.1700000000"));
+        assert!(!is_rotated_log("runs.jsonl"));
+    }
+}
@@ -0,0 +1,391 @@
+use serde_json::{Value, json};
+use std::process::Command;
+
+use crate::agentcmds::{LlmMode, execute_llm_command};
+use crate::contract_versions::HEALTH_JSON_CONTRACT_VERSION;
+use crate::doctor::count_completed_items;
+use crate::error::{ExitKind, failure_exit, format_error, print_usage_error};
+use crate::logs::validate_runs_jsonl_file;
+use crate::offline::is_offline;
+use crate::paths::{repo_root, resolve_log_file, resolve_schema_dir};
+use crate::platform::bin_in_path;
+use crate::process::run_command_output_with_timeout;
+use crate::runtime::{llm_backend, llm_bin_name};
+use crate::schema_ops::required_schema_problems;
+use crate::types::{ExecutionResult, TaskSpec};
+
+type JsonlRunner = fn(&str) -> Result<String, String>;
+type TaskRunner = fn(TaskSpec) -> Result<ExecutionResult, String>;
+
+const HEALTH_USAGE: &str = "health [--json] [--no-llm]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl CheckStatus {
+    fn name(self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "fail",
+            CheckStatus::Skip => "skip",
+        }
+    }
+}
+
+/// One named health check. `class` decides the exit code when it fails.
+struct Check {
+    name: &'static str,
+    status: CheckStatus,
+    class: ExitKind,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, class: ExitKind, status: CheckStatus, detail: String) -> Self {
+        Check {
+            name,
+            status,
+            class,
+            detail,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "status": self.status.name(),
+            "class": self.class.name(),
+            "detail": self.detail,
+        })
+    }
+}
+
+fn check_binaries(backend: &str, llm_bin: &str, no_llm: bool) -> Check {
+    let missing: Vec<&str> = ["git", "jq"]
+        .into_iter()
+        .filter(|b| !bin_in_path(b))
+        .collect();
+    let llm_ok = bin_in_path(llm_bin);
+    let (status, detail) = if !missing.is_empty() {
+        (
+            CheckStatus::Fail,
+            format!("missing: {}", missing.join(", ")),
+        )
+    } else if !llm_ok && !no_llm {
+        (
+            CheckStatus::Fail,
+            format!("missing: {llm_bin} (selected backend: {backend})"),
+        )
+    } else if !llm_ok {
+        (
+            CheckStatus::Warn,
+            format!("git, jq found; {llm_bin} missing (selected backend: {backend})"),
+        )
+    } else {
+        (CheckStatus::Pass, format!("git, jq, {llm_bin} found"))
+    };
+    Check::new("binaries", ExitKind::Runtime, status, detail)
+}
+
+fn check_paths() -> Check {
+    let Some(log_file) = resolve_log_file() else {
+        return Check::new(
+            "paths",
+            ExitKind::Runtime,
+            CheckStatus::Fail,
+            "unable to resolve the run log path (no repo root or HOME)".to_string(),
+        );
+    };
+    let log_dir = log_file.parent().map(|p| p.display().to_string());
+    match repo_root() {
+        Some(root) => Check::new(
+            "paths",
+            ExitKind::Runtime,
+            CheckStatus::Pass,
+            format!(
+                "repo root {}; logs in {}",
+                root.display(),
+                log_dir.unwrap_or_default()
+            ),
+        ),
+        None => Check::new(
+            "paths",
+            ExitKind::Runtime,
+            CheckStatus::Warn,
+            format!(
+                "not in a git repo; logs fall back to {}",
+                log_dir.unwrap_or_default()
+            ),
+        ),
+    }
+}
+
+fn check_schema_registry() -> Check {
+    let Some(dir) = resolve_schema_dir() else {
+        return Check::new(
+            "schema_registry",
+            ExitKind::Schema,
+            CheckStatus::Fail,
+            "unable to resolve schema dir".to_string(),
+        );
+    };
+    let problems = required_schema_problems(&dir);
+    match problems.first() {
+        None => Check::new(
+            "schema_registry",
+            ExitKind::Schema,
+            CheckStatus::Pass,
+            format!("required schemas compile ({})", dir.display()),
+        ),
+        Some(first) => Check::new(
+            "schema_registry",
+            ExitKind::Schema,
+            CheckStatus::Fail,
+            format!("{} problem(s); first: {first}", problems.len()),
+        ),
+    }
+}
+
+fn check_log_contract() -> Check {
+    let check = |status, detail| Check::new("log_contract", ExitKind::Schema, status, detail);
+    let Some(log_file) = resolve_log_file().filter(|p| p.exists()) else {
+        return check(CheckStatus::Warn, "no run log yet".to_string());
+    };
+    match validate_runs_jsonl_file(&log_file, true) {
+        Ok(o) if o.issues.is_empty() => check(
+            CheckStatus::Pass,
+            format!("{} row(s) valid ({})", o.total, log_file.display()),
+        ),
+        // Legacy rows are tolerated; only unparseable lines break the contract.
+        Ok(o) if o.invalid_json_lines == 0 => check(
+            CheckStatus::Warn,
+            format!(
+                "{} of {} row(s) legacy; run `logs migrate`",
+                o.legacy_lines, o.total
+            ),
+        ),
+        Ok(o) => check(
+            CheckStatus::Fail,
+            format!(
+                "{} invalid JSON line(s) of {}; first: {}",
+                o.invalid_json_lines,
+                o.total,
+                o.issues.first().cloned().unwrap_or_default()
+            ),
+        ),
+        Err(e) => check(CheckStatus::Fail, e),
+    }
+}
+
+/// Exit class for a failed LLM probe: the class the backend call noted
+/// (timeout, interrupt), else a generic backend failure.
+fn llm_failure_class() -> ExitKind {
+    ExitKind::ALL
+        .into_iter()
+        .find(|k| k.code() == Some(failure_exit()))
+        .filter(|k| !matches!(k, ExitKind::Runtime))
+        .unwrap_or(ExitKind::Backend)
+}
+
+fn skipped(name: &'static str, reason: &str) -> Check {
+    Check::new(
+        name,
+        ExitKind::Backend,
+        CheckStatus::Skip,
+        reason.to_string(),
+    )
+}
+
+fn check_llm_version(backend: &str, llm_bin: &str) -> Check {
+    let mut cmd = Command::new(llm_bin);
+    cmd.arg("--version");
+    let (status, detail) =
+        match run_command_output_with_timeout(cmd, &format!("{llm_bin} --version")) {
+            Ok(out) if out.status.success() => (
+                CheckStatus::Pass,
+                format!("{backend}: {}", String::from_utf8_lossy(&out.stdout).trim()),
+            ),
+            Ok(out) => (
+                CheckStatus::Fail,
+                format!(
+                    "{llm_bin} --version exited {}",
+                    out.status.code().unwrap_or(1)
+                ),
+            ),
+            Err(e) => (CheckStatus::Fail, e),
+        };
+    Check::new("llm_version", ExitKind::Backend, status, detail)
+}
+
+fn check_llm_json(backend: &str, run_llm_jsonl: JsonlRunner) -> Check {
+    match run_llm_jsonl("ping") {
+        Ok(jsonl) => {
+            let (agent, reasoning) = count_completed_items(&jsonl);
+            let status = if agent > 0 {
+                CheckStatus::Pass
+            } else {
+                CheckStatus::Fail
+            };
+            Check::new(
+                "llm_json",
+                ExitKind::Backend,
+                status,
+                format!("{backend}: {agent} agent_message, {reasoning} reasoning event(s)"),
+            )
+        }
+        Err(e) => Check::new("llm_json", llm_failure_class(), CheckStatus::Fail, e),
+    }
+}
+
+fn check_cxo(run_task: TaskRunner) -> Check {
+    let command = ["git".to_string(), "status".to_string()];
+    match execute_llm_command(&command, LlmMode::AgentText, run_task) {
+        Ok(r) if r.stdout.trim().is_empty() => Check::new(
+            "cxo",
+            ExitKind::Backend,
+            CheckStatus::Warn,
+            "cxo git status returned empty text".to_string(),
+        ),
+        Ok(r) => Check::new(
+            "cxo",
+            ExitKind::Backend,
+            CheckStatus::Pass,
+            format!("cxo git status ok ({} ms)", r.duration_ms),
+        ),
+        Err(e) => Check::new("cxo", llm_failure_class(), CheckStatus::Fail, e),
+    }
+}
+
+/// Exit code of the first failing check, else 0.
+fn health_exit(checks: &[Check]) -> i32 {
+    checks
+        .iter()
+        .find(|c| c.status == CheckStatus::Fail)
+        .and_then(|c| c.class.code())
+        .unwrap_or(0)
+}
+
+fn overall_status(checks: &[Check]) -> CheckStatus {
+    if checks.iter().any(|c| c.status == CheckStatus::Fail) {
+        CheckStatus::Fail
+    } else if checks.iter().any(|c| c.status == CheckStatus::Warn) {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    }
+}
+
+fn print_health_text(checks: &[Check], offline: bool) {
+    println!("== cxrs health ==");
+    if offline {
+        println!("offline mode: LLM checks skipped (CX_OFFLINE=1 / --offline)");
+    }
+    for c in checks {
+        println!(
+            "{:<5} {:<16} {}",
+            c.status.name().to_uppercase(),
+            c.name,
+            c.detail
+        );
+    }
+    let count = |s: CheckStatus| checks.iter().filter(|c| c.status == s).count();
+    println!();
+    println!(
+        "health: {} pass, {} warn, {} fail, {} skip",
+        count(CheckStatus::Pass),
+        count(CheckStatus::Warn),
+        count(CheckStatus::Fail),
+        count(CheckStatus::Skip)
+    );
+}
+
+pub fn cmd_health(args: &[String], run_llm_jsonl: JsonlRunner, run_task: TaskRunner) -> i32 {
+    let mut json_out = false;
+    let mut no_llm = false;
+    for a in args {
+        match a.as_str() {
+            "--json" => json_out = true,
+            "--no-llm" => no_llm = true,
+            other => {
+                crate::cx_eprintln!(
+                    "{}",
+                    format_error("health", &format!("unknown flag '{other}'"))
+                );
+                return print_usage_error("health", HEALTH_USAGE);
+            }
+        }
+    }
+    let offline = is_offline();
+    let backend = llm_backend();
+    let llm_bin = llm_bin_name();
+    let mut checks = vec![
+        check_binaries(&backend, llm_bin, no_llm || offline),
+        check_paths(),
+        check_schema_registry(),
+        check_log_contract(),
+    ];
+    if no_llm || offline {
+        let reason = if offline { "offline mode" } else { "--no-llm" };
+        checks.extend(["llm_version", "llm_json", "cxo"].map(|n| skipped(n, reason)));
+    } else {
+        checks.push(check_llm_version(&backend, llm_bin));
+        // Later probes would only repeat a missing/broken backend failure.
+        if checks.last().is_some_and(|c| c.status == CheckStatus::Fail) {
+            checks.extend(["llm_json", "cxo"].map(|n| skipped(n, "llm_version failed")));
+        } else {
+            checks.push(check_llm_json(&backend, run_llm_jsonl));
+            checks.push(check_cxo(run_task));
+        }
+    }
+    let code = health_exit(&checks);
+    if json_out {
+        let doc = json!({
+            "contract_version": HEALTH_JSON_CONTRACT_VERSION,
+            "backend": backend,
+            "no_llm": no_llm,
+            "offline": offline,
+            "status": overall_status(&checks).name(),
+            "exit_code": code,
+            "checks": checks.iter().map(Check::to_json).collect::<Vec<_>>(),
+        });
+        match serde_json::to_string_pretty(&doc) {
+            Ok(s) => println!("{s}"),
+            Err(e) => {
+                crate::cx_eprintln!("{}", format_error("health", &format!("render failed: {e}")));
+                return 1;
+            }
+        }
+    } else {
+        print_health_text(&checks, offline);
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_code_follows_first_failing_class() {
+        let c = |status, class| Check::new("x", class, status, String::new());
+        let checks = vec![
+            c(CheckStatus::Warn, ExitKind::Runtime),
+            c(CheckStatus::Fail, ExitKind::Schema),
+            c(CheckStatus::Fail, ExitKind::Backend),
+        ];
+        assert_eq!(health_exit(&checks), 4);
+        assert_eq!(overall_status(&checks), CheckStatus::Fail);
+        let ok = vec![
+            c(CheckStatus::Pass, ExitKind::Runtime),
+            c(CheckStatus::Skip, ExitKind::Backend),
+        ];
+        assert_eq!(health_exit(&ok), 0);
+        assert_eq!(overall_status(&ok), CheckStatus::Pass);
+    }
+}
@@ -4,9 +4,24 @@ pub struct CommandHelp {
     pub description: &'static str,
 }
 
+/// Extra per-command reference material shown by `help <cmd>` and `<cmd> --help`.
+pub struct CommandDetail {
+    pub name: &'static str,
+    pub flags: &'static [(&'static str, &'static str)],
+    pub env: &'static [&'static str],
+    pub examples: &'static [&'static str],
+    pub state_keys: &'static [&'static str],
+}
+
 #[path = "help_data.rs"]
 mod help_data;
+#[path = "help_detail.rs"]
+mod help_detail;
+#[path = "help_detail_ops.rs"]
+mod help_detail_ops;
 #[path = "help_render.rs"]
 mod help_render;
 
-pub use help_render::{render_help, render_task_help};
+pub use help_render::{
+    render_command_help, render_help, render_reference, render_task_help, resolve_help_topic,
+};
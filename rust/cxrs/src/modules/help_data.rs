@@ -8,8 +8,8 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
     },
     CommandHelp {
         name: "where",
-        usage: "where",
-        description: "Show binary/source/log resolution details",
+        usage: "where [--json] [cmd...]",
+        description: "Show binary/source/path resolution details (--json: every path with reason, exists, writable)",
     },
     CommandHelp {
         name: "routes",
@@ -33,8 +33,13 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
     },
     CommandHelp {
         name: "schema",
-        usage: "schema list [--json]",
-        description: "List registered schemas",
+        usage: "schema list [--json] | show <name> | validate <name> [file] | add <name> --from <file> | lint | diff <a> <b> | map [<tool> <schema>]",
+        description: "Manage registry schemas and tool -> schema mappings",
+    },
+    CommandHelp {
+        name: "exit-codes",
+        usage: "exit-codes [--json]",
+        description: "Print the exit-code contract scripts can branch on",
     },
     CommandHelp {
         name: "logs",
@@ -51,6 +56,16 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
         usage: "logs stats [N] [--json] [--strict] [--severity]",
         description: "Telemetry health and contract-drift summary",
     },
+    CommandHelp {
+        name: "logs",
+        usage: "logs query '<filter>' [--last N] [--fields a,b,c] [--json]",
+        description: "Filter run-log rows, e.g. 'tool=cxdiffsum AND duration_ms>10000'",
+    },
+    CommandHelp {
+        name: "logs",
+        usage: "logs failures [N] [--json]",
+        description: "Cluster schema failures by tool, reason and response shape",
+    },
     CommandHelp {
         name: "telemetry",
         usage: "telemetry [N] [--json] [--strict] [--severity]",
@@ -58,9 +73,14 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
     },
     CommandHelp {
         name: "ci",
-        usage: "ci validate [--strict] [--legacy-ok] [--json]",
+        usage: "ci validate [--strict] [--legacy-ok] [--json] [--report FILE]",
         description: "CI-friendly validation gate (no network)",
     },
+    CommandHelp {
+        name: "hook",
+        usage: "hook install --type pre-push [--logs] [--force] | hook uninstall --type pre-push | hook run pre-push [--logs]",
+        description: "Git pre-push hook running 'ci validate --strict' and a schema failure rate gate",
+    },
     CommandHelp {
         name: "core",
         usage: "core",
@@ -74,12 +94,12 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
     CommandHelp {
         name: "task",
         usage: "task <op> [...]",
-        description: "Task graph management (add/list/claim/complete/fail/show/fanout)",
+        description: "Task graph management (add/list/claim/reap/watch/complete/fail/show/fanout/template/export/import)",
     },
     CommandHelp {
         name: "doctor",
-        usage: "doctor",
-        description: "Run non-interactive environment checks",
+        usage: "doctor [--fix]",
+        description: "Run non-interactive environment checks (--fix repairs dirs, state, config, completions)",
     },
     CommandHelp {
         name: "supports",
@@ -89,22 +109,57 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
     CommandHelp {
         name: "llm",
         usage: "llm <op> [...]",
-        description: "Manage LLM backend/model defaults (show|use|unset|set-backend|set-model|clear-model)",
+        description: "Manage LLM backend/model defaults (show|use|unset|set-backend|set-model|clear-model) and local Ollama models (models|pull|inspect)",
+    },
+    CommandHelp {
+        name: "model",
+        usage: "model bench <prompt-file|dir> --models a,b [--runs N] [--schema NAME] [--json]",
+        description: "Run a prompt suite across models/backends and rank latency, tokens and schema pass rate",
+    },
+    CommandHelp {
+        name: "secret",
+        usage: "secret <set <name> [value]|get <name>|rm <name>|list>",
+        description: "Encrypted store for API keys; reference them as secret:<name> in config/env",
+    },
+    CommandHelp {
+        name: "workspace",
+        usage: "workspace <info [--json]|backup [--out FILE.tar.gz]|restore <FILE> [--force]>",
+        description: "Show .codex sizes per subdirectory, or back up/restore the whole workspace",
+    },
+    CommandHelp {
+        name: "init",
+        usage: "init [--gitignore]",
+        description: "Scaffold .codex (logs, quarantine, default schemas, prompts, starter config and state) in this repo",
+    },
+    CommandHelp {
+        name: "gc",
+        usage: "gc [--dry-run] [--json] [--store logs|cache|transcripts|sessions|quarantine]",
+        description: "Prune rotated logs, cache, transcripts and resolved quarantine records past their retention limits",
+    },
+    CommandHelp {
+        name: "annotate",
+        usage: "annotate <run <execution_id>|task <id>> \"<note>\"",
+        description: "Attach a human note to a logged run or a task; shown by trace, task show and worklog",
+    },
+    CommandHelp {
+        name: "config",
+        usage: "config <show [--origin]|get <key>|set <key> <value>|unset <key>> [--repo|--global]",
+        description: "Show or edit layered settings (defaults < ~/.codex/config.toml < .codex/config.toml < env)",
     },
     CommandHelp {
         name: "state",
         usage: "state <op> [...]",
-        description: "Manage repo state JSON (show|get|set)",
+        description: "Manage layered state JSON: repo over ~/.codex (show|get|set)",
     },
     CommandHelp {
         name: "policy",
-        usage: "policy [show|check ...]",
-        description: "Show safety rules or classify a command",
+        usage: "policy [show|check [--json] ...]",
+        description: "Show effective safety rules (built-in + .codex/policy.json) or classify a command (--json: rule, category, severity)",
     },
     CommandHelp {
         name: "bench",
-        usage: "bench <N> -- <cmd...>",
-        description: "Benchmark command runtime and tokens",
+        usage: "bench <N> [--llm ...] [--suite FILE] [--baseline FILE --max-regression PCT] [--write-baseline FILE] [--json] [-- <cmd...>]",
+        description: "Benchmark command runtime and tokens (--llm: warm-up, variance, comparisons; baseline flags gate CI on regressions)",
     },
     CommandHelp {
         name: "cx",
@@ -128,18 +183,43 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
     },
     CommandHelp {
         name: "cxcopy",
-        usage: "cxcopy <cmd...>",
-        description: "Copy cxo output to clipboard (pbcopy/wl-copy/xclip)",
+        usage: "cxcopy [--raw|--jsonl|--both] <cmd...>",
+        description: "Copy cxo output, the captured command output or the JSONL transcript to clipboard (auto-detect or CX_CLIPBOARD_PROVIDER)",
     },
     CommandHelp {
         name: "fix",
-        usage: "fix <cmd...>",
-        description: "Explain failures and suggest next steps (text)",
+        usage: "fix [--json] <cmd...>",
+        description: "Explain failures and suggest next steps (text, or strict JSON with --json)",
+    },
+    CommandHelp {
+        name: "followup",
+        usage: "followup [--session NAME] <question...>",
+        description: "Ask a follow-up using prior session turns as context",
+    },
+    CommandHelp {
+        name: "explain",
+        usage: "explain <file>[:START[-END]] [--json]",
+        description: "Explain a file or line range (purpose, key functions, risks, related files)",
+    },
+    CommandHelp {
+        name: "custom",
+        usage: "custom <name> [-- <command> [args...]] | custom list [--json]",
+        description: "Run a user-defined strict-schema command from .codex/commands.json",
+    },
+    CommandHelp {
+        name: "session",
+        usage: "session <list [--json]|show [NAME] [--json]|clear [NAME]>",
+        description: "Manage conversation sessions (CX_SESSION_ENABLED=1 records, CX_SESSION selects)",
     },
     CommandHelp {
         name: "budget",
-        usage: "budget",
-        description: "Show context budget settings and last clip fields",
+        usage: "budget | budget suggest [N] [--coverage PCT] [--apply] [--json]",
+        description: "Show context budget settings and last clip fields; suggest per-tool budgets from history",
+    },
+    CommandHelp {
+        name: "redact-test",
+        usage: "redact-test [--json] [FILE|-]",
+        description: "Preview which secrets capture redaction would mask",
     },
     CommandHelp {
         name: "log-tail",
@@ -148,8 +228,13 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
     },
     CommandHelp {
         name: "health",
-        usage: "health",
-        description: "Run end-to-end selected-LLM/cx smoke checks",
+        usage: "health [--json] [--no-llm]",
+        description: "Named pass/warn/fail checks for binaries, paths, schemas, log contract and LLM probes",
+    },
+    CommandHelp {
+        name: "selftest",
+        usage: "selftest [--json] [--keep]",
+        description: "Run every structured tool offline against the mock backend in a scratch repo; pass/fail per stage",
     },
     CommandHelp {
         name: "capture-status",
@@ -169,7 +254,7 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
     CommandHelp {
         name: "alert-show",
         usage: "alert-show",
-        description: "Show active alert thresholds/toggles",
+        description: "Show active alert thresholds/toggles and configured alert sinks",
     },
     CommandHelp {
         name: "alert-on",
@@ -186,9 +271,14 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
         usage: "chunk",
         description: "Chunk stdin text by context budget chars",
     },
+    CommandHelp {
+        name: "pack",
+        usage: "pack [--chunk] <path|dir|glob>...",
+        description: "Bundle files into a budgeted Markdown context pack (manifest in run log)",
+    },
     CommandHelp {
         name: "metrics",
-        usage: "metrics [N]",
+        usage: "metrics [N] [--percentiles] [--all-repos] [filters]",
         description: "Token and duration aggregates from last N runs",
     },
     CommandHelp {
@@ -203,22 +293,22 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
     },
     CommandHelp {
         name: "prompt",
-        usage: "prompt <mode> <request>",
+        usage: "prompt <mode> [--role NAME] <request>",
         description: "Generate Codex-ready prompt block",
     },
     CommandHelp {
         name: "roles",
         usage: "roles [role]",
-        description: "List roles or print role-specific prompt header",
+        description: "List built-in and custom roles (.codex/roles.json, state roles.<name>) or print a role header",
     },
     CommandHelp {
         name: "fanout",
-        usage: "fanout <objective>",
-        description: "Generate role-tagged parallelizable subtasks",
+        usage: "fanout [--llm | --role NAME...] <objective>",
+        description: "Generate role-tagged parallelizable subtasks (--llm: backend-planned, schema-validated)",
     },
     CommandHelp {
         name: "promptlint",
-        usage: "promptlint [N]",
+        usage: "promptlint [N] [--json] [filters]",
         description: "Lint prompt/cost patterns from last N runs",
     },
     CommandHelp {
@@ -228,63 +318,113 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
     },
     CommandHelp {
         name: "profile",
-        usage: "profile [N]",
+        usage: "profile [N] [--json] [--percentiles] [--all-repos] [filters]",
         description: "Summarize last N runs from resolved cx log (default {RUN_WINDOW})",
     },
     CommandHelp {
         name: "alert",
-        usage: "alert [N]",
+        usage: "alert [N] [--json] [filters]",
         description: "Report anomalies from last N runs (default {RUN_WINDOW})",
     },
     CommandHelp {
         name: "optimize",
-        usage: "optimize [N] [--json] [--actions] [--strict] [--severity warning|critical]",
+        usage: "optimize [N] [--json] [--actions] [--strict] [--severity warning|critical] [filters]",
         description: "Recommend cost/latency improvements from last N runs",
     },
     CommandHelp {
         name: "worklog",
-        usage: "worklog [N]",
-        description: "Emit Markdown worklog from last N runs (default {RUN_WINDOW})",
+        usage: "worklog [N] [--format md|html|json] [--include-failures] [filters]",
+        description: "Worklog of last N runs (default {RUN_WINDOW}) or a date range, with per-day/per-task rollups",
     },
     CommandHelp {
         name: "trace",
-        usage: "trace [N]",
-        description: "Show Nth most-recent run from resolved cx log (default 1)",
+        usage: "trace [N|execution_id] [--json] [--remediations]",
+        description: "Show Nth most-recent run or the run with an execution id (unique prefix accepted) (or fix-run remediations) from resolved cx log (default 1), with its schema outcome, quarantine, transcript and task chain",
+    },
+    CommandHelp {
+        name: "transcript",
+        usage: "transcript show <execution_id> [--json]",
+        description: "Show the archived full prompt/response of a run (needs CX_TRANSCRIPTS=1)",
+    },
+    CommandHelp {
+        name: "diff-runs",
+        usage: "diff-runs <exec_id_a> <exec_id_b> [--json] [--changed]",
+        description: "Compare two run-log rows field by field (tokens, latency, capture, backend, transcripts)",
+    },
+    CommandHelp {
+        name: "trends",
+        usage: "trends [--bucket day|hour] [--last 30d] [--all-repos] [--json] [filters]",
+        description: "Runs, tokens, latency, cache hit and schema failure rate per hour/day, with sparklines",
+    },
+    CommandHelp {
+        name: "digest",
+        usage: "digest [--since 7d] [--until WHEN] [--json] [--send] [filters]",
+        description: "Periodic Markdown/JSON digest: totals vs the previous period, regressions, recommendations, failures, quarantines",
+    },
+    CommandHelp {
+        name: "watch",
+        usage: "watch [--interval SECS] [--dispatch] [--from-start] [--once]",
+        description: "Tail the run log and raise live alerts with rolling aggregates (Ctrl-C to stop)",
     },
     CommandHelp {
         name: "next",
-        usage: "next <cmd...>",
-        description: "Suggest next shell commands from command output (strict JSON)",
+        usage: "next [--run [--unsafe]] <cmd...>",
+        description: "Suggest next shell commands from command output (strict JSON); --run labels each with its policy and lets you pick which to execute",
+    },
+    CommandHelp {
+        name: "pipeline",
+        usage: "pipeline run <cmd...> --steps STEP[,STEP...] [--json]",
+        description: "Capture a command once and run several steps on it (cxo, diffsum, next, fix, fix-run suggestions), logged under one pipeline_id",
     },
     CommandHelp {
         name: "diffsum",
-        usage: "diffsum",
+        usage: "diffsum [--focus GLOB]...",
         description: "Summarize unstaged diff (strict schema)",
     },
     CommandHelp {
         name: "diffsum-staged",
-        usage: "diffsum-staged",
+        usage: "diffsum-staged [--focus GLOB]...",
         description: "Summarize staged diff (strict schema)",
     },
     CommandHelp {
         name: "fix-run",
-        usage: "fix-run <cmd...>",
+        usage: "fix-run [--unsafe] [--dry-run|--confirm] <cmd...>",
         description: "Suggest remediation commands for a failed command",
     },
     CommandHelp {
         name: "commitjson",
-        usage: "commitjson",
+        usage: "commitjson [--strict]",
         description: "Generate strict JSON commit object from staged diff",
     },
     CommandHelp {
         name: "commitmsg",
-        usage: "commitmsg",
+        usage: "commitmsg [--strict]",
         description: "Generate commit message text from staged diff",
     },
+    CommandHelp {
+        name: "stage",
+        usage: "stage [--json] | stage --apply N [--yes]",
+        description: "Propose commit-sized staging groups from the worktree diff; apply one from the saved plan",
+    },
+    CommandHelp {
+        name: "changelog",
+        usage: "changelog --from <rev> [--to <rev>] [--json]",
+        description: "Release notes for a rev range grouped by conventional-commit type (Markdown, or strict JSON)",
+    },
     CommandHelp {
         name: "replay",
-        usage: "replay <id>",
-        description: "Replay quarantined schema run in strict mode",
+        usage: "replay <id> [--apply]",
+        description: "Replay quarantined schema run in strict mode (--apply resolves it on success)",
+    },
+    CommandHelp {
+        name: "replay",
+        usage: "replay --all [--tool NAME] [--reason REASON] [--include-resolved] [--apply] [--json]",
+        description: "Batch-replay open quarantine entries and summarize how many now pass",
+    },
+    CommandHelp {
+        name: "rerun",
+        usage: "rerun <execution_id|prefix> [--json]",
+        description: "Re-send an archived run's prompt to the current backend and diff the responses",
     },
     CommandHelp {
         name: "quarantine",
@@ -296,6 +436,31 @@ pub const MAIN_COMMANDS: &[CommandHelp] = &[
         usage: "quarantine show <id>",
         description: "Show quarantined entry payload",
     },
+    CommandHelp {
+        name: "quarantine",
+        usage: "quarantine diff <id> [--json]",
+        description: "Validate a quarantined response: violations by JSON Pointer, unexpected/missing keys and a JSON Patch that makes it pass",
+    },
+    CommandHelp {
+        name: "quarantine",
+        usage: "quarantine resolve <id> [note...]",
+        description: "Mark a quarantine entry resolved",
+    },
+    CommandHelp {
+        name: "quarantine",
+        usage: "quarantine rm <id>",
+        description: "Delete a quarantine entry",
+    },
+    CommandHelp {
+        name: "quarantine",
+        usage: "quarantine purge [--older-than 30d] [--resolved] [--dry-run]",
+        description: "Delete aged and/or resolved quarantine entries",
+    },
+    CommandHelp {
+        name: "quarantine",
+        usage: "quarantine stats [--json]",
+        description: "Count quarantine entries by tool, reason and day",
+    },
     CommandHelp {
         name: "help",
         usage: "help",
@@ -311,13 +476,23 @@ pub const TASK_COMMANDS: &[CommandHelp] = &[
     },
     CommandHelp {
         name: "task list",
-        usage: "cx task list [--status pending|in_progress|complete|failed]",
-        description: "List tasks with optional status filter",
+        usage: "cx task list [--status pending|in_progress|complete|failed] [--assignee NAME] [--verbose]",
+        description: "List tasks with optional status/assignee filters (--verbose adds each task's result summary)",
     },
     CommandHelp {
         name: "task claim",
-        usage: "cx task claim <id>",
-        description: "Mark task as in_progress",
+        usage: "cx task claim <id> [--as NAME]",
+        description: "Mark task as in_progress for NAME; refuses a task someone else already holds",
+    },
+    CommandHelp {
+        name: "task reap",
+        usage: "cx task reap --older-than <age> [--dry-run]",
+        description: "Return in_progress tasks claimed longer ago than <age> (30m, 2h, 1d) to pending",
+    },
+    CommandHelp {
+        name: "task watch",
+        usage: "cx task watch [--role ROLE] [--as NAME] [--max-workers N] [--interval SECS] [--once]",
+        description: "Worker loop: claim ready pending tasks (optionally one role) as they appear and run them; Ctrl-C stops claiming and waits for running tasks",
     },
     CommandHelp {
         name: "task complete",
@@ -331,13 +506,28 @@ pub const TASK_COMMANDS: &[CommandHelp] = &[
     },
     CommandHelp {
         name: "task show",
-        usage: "cx task show <id>",
-        description: "Show one task record",
+        usage: "cx task show <id> [--result]",
+        description: "Show one task record (--result prints the saved .codex/task_results/<id>.md)",
     },
     CommandHelp {
         name: "task fanout",
-        usage: "cx task fanout \"<objective>\" [--from staged-diff|worktree|log|file:PATH]",
-        description: "Generate role-tagged subtasks",
+        usage: "cx task fanout \"<objective>\" [--llm] [--from staged-diff|worktree|log|file:PATH]",
+        description: "Generate role-tagged subtasks (--llm: backend-planned with dependencies and estimates)",
+    },
+    CommandHelp {
+        name: "task export",
+        usage: "cx task export [--format md|json]",
+        description: "Print the task graph as a nested Markdown checklist (ids, roles, status) or JSON",
+    },
+    CommandHelp {
+        name: "task import",
+        usage: "cx task import <file.md|file.json> [--format md|json] [--dry-run]",
+        description: "Merge a checklist or JSON task list into tasks.json (keeps ids; untouched tasks stay)",
+    },
+    CommandHelp {
+        name: "task template",
+        usage: "cx task template <list | save <name> --from <parent_id> [--param key=VALUE]... [--force] | apply <name> --param objective=\"...\" [--param key=VALUE]...>",
+        description: "Save a task subtree as a parameterized template under .codex/task_templates/ and re-instantiate it",
     },
     CommandHelp {
         name: "task run-plan",
@@ -347,7 +537,7 @@ pub const TASK_COMMANDS: &[CommandHelp] = &[
     CommandHelp {
         name: "task run",
         usage: "cx task run <id> [--mode lean|deterministic|verbose] [--backend codex|ollama]",
-        description: "Run one task objective",
+        description: "Run one task objective and save its output to .codex/task_results/<id>.md",
     },
     CommandHelp {
         name: "task run-all",
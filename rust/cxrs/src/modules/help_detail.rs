@@ -0,0 +1,340 @@
+use super::CommandDetail;
+use super::help_detail_ops::OPS_DETAILS;
+
+pub const CONTEXT_ENV: &[&str] = &[
+    "CX_CONTEXT_BUDGET_CHARS",
+    "CX_CONTEXT_BUDGET_LINES",
+    "CX_CONTEXT_CLIP_MODE",
+    "CX_CONTEXT_CLIP_FOOTER",
+    "CX_CONTEXT_ERROR_REGEX",
+];
+
+pub const BACKEND_STATE: &[&str] = &["preferences.llm_backend", "preferences.ollama_model"];
+
+/// Every command's detail: the workflow commands below, then settings and
+/// operations from [`OPS_DETAILS`].
+pub fn command_details() -> impl Iterator<Item = &'static CommandDetail> {
+    WORKFLOW_DETAILS.iter().chain(OPS_DETAILS)
+}
+
+const WORKFLOW_DETAILS: &[CommandDetail] = &[
+    CommandDetail {
+        name: "diffsum",
+        flags: &[(
+            "--focus GLOB",
+            "Restrict the diff to matching paths (repeatable; merged with CX_DIFF_FOCUS)",
+        )],
+        env: &[
+            "CX_DIFF_FOCUS",
+            "CX_CONTEXT_BUDGET_CHARS",
+            "CX_SCHEMA_RELAXED",
+            "CX_LLM_BACKEND",
+        ],
+        examples: &[
+            "cxrs diffsum",
+            "cxrs diffsum --focus 'src/**' --focus Cargo.toml",
+        ],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "diffsum-staged",
+        flags: &[(
+            "--focus GLOB",
+            "Restrict the staged diff to matching paths (repeatable)",
+        )],
+        env: &[
+            "CX_DIFF_FOCUS",
+            "CX_CONTEXT_BUDGET_CHARS",
+            "CX_SCHEMA_RELAXED",
+            "CX_LLM_BACKEND",
+        ],
+        examples: &["git add -p && cxrs diffsum-staged"],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "commitjson",
+        flags: &[(
+            "--strict",
+            "Fail when the subject still breaks lint rules after auto-fix and one re-prompt",
+        )],
+        env: &["CX_SCHEMA_RELAXED", "CX_LLM_BACKEND"],
+        examples: &["cxrs commitjson | jq -r .subject"],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "commitmsg",
+        flags: &[(
+            "--strict",
+            "Fail when the subject still breaks lint rules after auto-fix and one re-prompt",
+        )],
+        env: &["CX_SCHEMA_RELAXED", "CX_LLM_BACKEND"],
+        examples: &["git commit -m \"$(cxrs commitmsg)\""],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "stage",
+        flags: &[
+            ("--json", "Print the plan as a stage.v1 JSON document"),
+            (
+                "--apply N",
+                "Stage group N from the saved plan (no model call)",
+            ),
+            ("--yes", "Skip the confirmation prompt for --apply"),
+        ],
+        env: &[
+            "CX_CONTEXT_BUDGET_CHARS",
+            "CX_SCHEMA_RELAXED",
+            "CX_LLM_BACKEND",
+        ],
+        examples: &["cxrs stage", "cxrs stage --apply 1 && cxrs commitmsg"],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "hook",
+        flags: &[
+            (
+                "--type pre-push",
+                "Hook to install or remove (only pre-push for now)",
+            ),
+            ("--logs", "Also run 'logs validate' before each push"),
+            ("--force", "Replace a hook that cxrs did not write"),
+        ],
+        env: &["CX_HOOK_MAX_SCHEMA_FAIL_PCT"],
+        examples: &[
+            "cxrs hook install --type pre-push --logs",
+            "CX_HOOK_MAX_SCHEMA_FAIL_PCT=5 git push",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "changelog",
+        flags: &[
+            (
+                "--from REV",
+                "Exclusive start of the range, e.g. the last release tag",
+            ),
+            ("--to REV", "Inclusive end of the range (default HEAD)"),
+            (
+                "--json",
+                "Strict `changelog.v1` JSON with sections and the rendered Markdown",
+            ),
+        ],
+        env: &[],
+        examples: &[
+            "cxrs changelog --from v1.2.0",
+            "cxrs changelog --from v1.2.0 --to v1.3.0 --json | jq -r .markdown",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "next",
+        flags: &[
+            (
+                "--run",
+                "Label each suggestion with its policy verdict and pick which to execute",
+            ),
+            (
+                "--unsafe",
+                "Allow commands the policy would block (with --run)",
+            ),
+        ],
+        env: &["CX_UNSAFE", "CX_CMD_TIMEOUT_SECS", "CX_SCHEMA_RELAXED"],
+        examples: &["cxrs next cargo test", "cxrs next --run git status"],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "fix",
+        flags: &[(
+            "--json",
+            "Print {diagnosis, probable_causes, next_commands, config_files_to_check} validated against the fix schema",
+        )],
+        env: CONTEXT_ENV,
+        examples: &["cxrs fix cargo build", "cxrs fix --json cargo test"],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "fix-run",
+        flags: &[
+            (
+                "--dry-run",
+                "Print the suggested commands without running them",
+            ),
+            ("--confirm", "Ask before running each suggested command"),
+            ("--unsafe", "Allow commands the policy would block"),
+        ],
+        env: &["CX_UNSAFE", "CX_CMD_TIMEOUT_SECS"],
+        examples: &["cxrs fix-run --dry-run cargo build"],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "pipeline",
+        flags: &[
+            (
+                "--steps STEP[,STEP...]",
+                "Steps to run on the shared capture, in order: cxo, diffsum, next, fix, fix-run",
+            ),
+            (
+                "--json",
+                "Emit the pipeline.v1 document with each step's result",
+            ),
+        ],
+        env: CONTEXT_ENV,
+        examples: &[
+            "cxrs pipeline run 'cargo test' --steps next,fix-run",
+            "cxrs pipeline run 'git diff main' --steps diffsum,next --json",
+        ],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "cxo",
+        flags: &[],
+        env: CONTEXT_ENV,
+        examples: &["cxrs cxo git log --oneline -20"],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "cxcopy",
+        flags: &[
+            (
+                "--raw",
+                "Copy the clipped command output that was sent to the model",
+            ),
+            ("--jsonl", "Copy the full backend JSONL event stream"),
+            (
+                "--both",
+                "Copy Markdown with command, captured output, response and JSONL sections",
+            ),
+        ],
+        env: &["CX_CLIPBOARD_PROVIDER", "CX_CONTEXT_BUDGET_CHARS"],
+        examples: &["cxrs cxcopy cargo test", "cxrs cxcopy --both cargo test"],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "pack",
+        flags: &[(
+            "--chunk",
+            "Split the pack into budget-sized chunks instead of clipping",
+        )],
+        env: &["CX_CONTEXT_BUDGET_CHARS", "CX_CHUNK_MAX"],
+        examples: &["cxrs pack 'src/**/*.rs' README.md"],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "fanout",
+        flags: &[
+            (
+                "--llm",
+                "Ask the backend for subtasks with dependencies and estimates",
+            ),
+            (
+                "--role NAME",
+                "Only emit subtasks for the given role (repeatable)",
+            ),
+        ],
+        env: &["CX_LLM_BACKEND"],
+        examples: &[
+            "cxrs fanout \"add a cache layer\"",
+            "cxrs fanout --llm \"add a cache layer\"",
+        ],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "custom",
+        flags: &[
+            (
+                "-- <command...>",
+                "Capture this command instead of the definition's default",
+            ),
+            ("list [--json]", "Show defined commands and their schemas"),
+        ],
+        env: &["CX_LLM_BACKEND", "CX_CONTEXT_BUDGET_CHARS"],
+        examples: &[
+            "cxrs custom lint-summary -- cargo clippy",
+            "cxrs custom list",
+        ],
+        state_keys: &["budget_overrides"],
+    },
+    CommandDetail {
+        name: "task",
+        flags: &[],
+        env: &["CX_TASK_HALT_ON_CRITICAL", "CX_LLM_BACKEND"],
+        examples: &[
+            "cxrs task add \"write tests\" --role tester",
+            "cxrs task run-all --mode mixed --backend-pool codex,ollama",
+            "cxrs task show task_001 --result",
+            "cxrs task claim task_002 --as agent-a",
+            "cxrs task reap --older-than 2h",
+            "cxrs task watch --role implementer --as agent-a --max-workers 2",
+            "cxrs task export --format md > tasks.md",
+            "cxrs task import tasks.md --dry-run",
+            "cxrs task template save feature --from task_001",
+            "cxrs task template apply feature --param objective=\"Add SSO login\"",
+        ],
+        state_keys: &["runtime.current_task_id", "runtime.current_task_parent_id"],
+    },
+    CommandDetail {
+        name: "worklog",
+        flags: &[
+            ("--format md|html|json", "Output format (default md)"),
+            (
+                "--include-failures",
+                "Append schema failures with quarantine ids",
+            ),
+        ],
+        env: &[],
+        examples: &[
+            "cxrs worklog 100",
+            "cxrs worklog --since 2024-06-01 --format html > week.html",
+            "cxrs worklog --branch feature/login --format json",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "schema",
+        flags: &[
+            ("list [--json]", "Registered schemas with their $id"),
+            ("show <name>", "Print one schema"),
+            (
+                "validate <name> [file]",
+                "Check a JSON instance (file or stdin); exit 4 on mismatch",
+            ),
+            (
+                "add <name> --from <file>",
+                "Register a schema; $id becomes cx://schemas/<name>.v<N> (--force replaces)",
+            ),
+            (
+                "lint [--json]",
+                "Compile and metadata checks for every schema",
+            ),
+            (
+                "diff <a> <b> [--json]",
+                "Required/property changes between two schemas",
+            ),
+            (
+                "map [<tool> <schema>|<tool> --unset]",
+                "List or edit tool -> schema mappings in registry.json",
+            ),
+        ],
+        env: &[],
+        examples: &[
+            "cxrs schema validate next out.json",
+            "cxrs schema add review --from review.json",
+            "cxrs schema diff next ./next-v2.schema.json",
+            "cxrs schema map cxrs_next next-strict",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "exit-codes",
+        flags: &[(
+            "--json",
+            "Emit [{code,name,description}]; code is null for pass-through",
+        )],
+        env: &[],
+        examples: &[
+            "cxrs exit-codes",
+            "cxrs commitjson; [ $? -eq 4 ] && cxrs quarantine list 1",
+        ],
+        state_keys: &[],
+    },
+];
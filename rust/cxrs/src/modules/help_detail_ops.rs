@@ -0,0 +1,550 @@
+use super::CommandDetail;
+use super::help_detail::{BACKEND_STATE, CONTEXT_ENV};
+
+/// Settings, reporting and maintenance commands.
+pub const OPS_DETAILS: &[CommandDetail] = &[
+    CommandDetail {
+        name: "config",
+        flags: &[
+            ("--origin", "Show where each effective value comes from"),
+            (
+                "--repo",
+                "Read or write .codex/config.toml in the repo (default)",
+            ),
+            (
+                "--global",
+                "Read or write the global config.toml (~/.codex, $CX_HOME or $XDG_CONFIG_HOME/cx)",
+            ),
+        ],
+        env: &["HOME", "CX_HOME", "XDG_CONFIG_HOME"],
+        examples: &[
+            "cxrs config show --origin",
+            "cxrs config set capture.budget_chars 20000 --global",
+        ],
+        state_keys: &[
+            "preferences.llm_backend",
+            "preferences.ollama_model",
+            "preferences.broker_policy",
+        ],
+    },
+    CommandDetail {
+        name: "state",
+        flags: &[
+            ("--merged", "Show the merged view (repo state over global)"),
+            (
+                "--origin",
+                "With --merged: print each key and the layer that set it",
+            ),
+            ("--repo", "Read or write .codex/state.json in the repo"),
+            (
+                "--global",
+                "Read or write the global state.json (~/.codex, $CX_HOME or $XDG_STATE_HOME/cx)",
+            ),
+        ],
+        env: &["HOME", "CX_HOME", "XDG_STATE_HOME"],
+        examples: &[
+            "cxrs state show --merged --origin",
+            "cxrs state set preferences.llm_backend ollama --global",
+            "cxrs state get preferences.ollama_model --repo",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "llm",
+        flags: &[(
+            "--json",
+            "Machine-readable output for `models` and `inspect`",
+        )],
+        env: &[
+            "CX_LLM_BACKEND",
+            "CX_MODEL",
+            "CX_OLLAMA_MODEL",
+            "OLLAMA_HOST",
+            "OLLAMA_CONTEXT_LENGTH",
+            "CX_CONTEXT_WINDOWS",
+            "CX_CONTEXT_GUARD",
+            "CX_CODEX_ARGS",
+        ],
+        examples: &[
+            "cxrs llm show",
+            "cxrs llm use ollama llama3.1",
+            "cxrs llm models",
+            "cxrs llm pull qwen2.5:7b",
+            "cxrs llm inspect llama3.1 --json",
+        ],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "budget",
+        flags: &[
+            (
+                "suggest [N]",
+                "Per-tool budgets from the last N runs (default 200)",
+            ),
+            (
+                "--coverage PCT",
+                "Share of runs to fit unclipped (50-100, default 95)",
+            ),
+            (
+                "--apply",
+                "Write suggestions to state budget_overrides.<tool>",
+            ),
+            ("--json", "Emit the budget-suggest.v1 report"),
+        ],
+        env: CONTEXT_ENV,
+        examples: &[
+            "CX_CONTEXT_BUDGET_CHARS=20000 cxrs budget",
+            "cxrs budget suggest 300 --coverage 90",
+            "cxrs budget suggest --apply",
+        ],
+        state_keys: &["budget_overrides"],
+    },
+    CommandDetail {
+        name: "transcript",
+        flags: &[("--json", "Emit the stored transcript object")],
+        env: &[
+            "CX_TRANSCRIPTS",
+            "CX_TRANSCRIPTS_KEEP_DAYS",
+            "CX_TRANSCRIPTS_MAX_BYTES",
+        ],
+        examples: &[
+            "CX_TRANSCRIPTS=1 cxrs cxo cargo test",
+            "cxrs transcript show \"$(tail -n1 .codex/cxlogs/runs.jsonl | jq -r .execution_id)\"",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "diff-runs",
+        flags: &[
+            ("--json", "Emit the diff-runs.v1 report"),
+            ("--changed", "Only list fields whose values differ"),
+        ],
+        env: &[],
+        examples: &[
+            "cxrs diff-runs 20240601T101500Z_cxo_4121 20240602T091200Z_cxo_977",
+            "cxrs diff-runs A B --json | jq '.fields[] | select(.changed)'",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "bench",
+        flags: &[
+            (
+                "--json",
+                "Emit the bench.v1 report (bench-llm.v1 with --llm)",
+            ),
+            (
+                "--llm",
+                "Warm-up runs, stddev/p50/p90/p99 and side-by-side comparisons",
+            ),
+            (
+                "--warmup K",
+                "Unmeasured runs per variant first (--llm, default 1)",
+            ),
+            (
+                "--compare",
+                "Bench two commands: -- <cmd A...> -- <cmd B...> (--llm)",
+            ),
+            (
+                "--backends a,b",
+                "Run the command once per CX_LLM_BACKEND value (--llm)",
+            ),
+            (
+                "--baseline FILE",
+                "Compare with a saved bench --llm --json report",
+            ),
+            (
+                "--max-regression PCT",
+                "Exit 1 when p50 duration or average tokens regressed more than PCT vs --baseline (alias --max-regress)",
+            ),
+            (
+                "--suite FILE",
+                "Bench each command of a JSON suite ({\"commands\": [{\"label\", \"command\": [...]}]}) instead of -- <cmd...>",
+            ),
+            (
+                "--write-baseline FILE",
+                "Save this report as the next --baseline file",
+            ),
+        ],
+        env: &["CXBENCH_LOG", "CXBENCH_PASSTHRU"],
+        examples: &[
+            "cxrs bench 5 -- cxrs cxo git status",
+            "cxrs bench 10 --llm --backends codex,ollama -- cxrs commitjson",
+            "cxrs bench 5 --llm --compare -- cxrs diffsum -- cxrs diffsum --focus 'src/**'",
+            "cxrs bench 10 --llm --json -- cxrs next > bench.json",
+            "cxrs bench 10 --llm --baseline bench.json --max-regress 20 -- cxrs next",
+            "cxrs bench 5 --json --suite bench-suite.json --baseline bench.json --max-regression 20% --write-baseline bench-new.json",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "model",
+        flags: &[
+            (
+                "--models a,b",
+                "codex, codex:<model>, ollama:<model> or a bare Ollama model name",
+            ),
+            ("--runs N", "Runs per prompt and model (default 1)"),
+            (
+                "--schema NAME",
+                "Expected schema for prompts without a leading 'schema: NAME' line",
+            ),
+            ("--json", "Emit the model-bench.v1 report"),
+        ],
+        env: &["CX_LLM_BACKEND", "CX_MODEL", "CX_OLLAMA_MODEL"],
+        examples: &[
+            "cxrs model bench prompts/ --models llama3.1:8b,qwen2.5:7b,codex",
+            "cxrs model bench prompts/next.md --models ollama:llama3.1:8b --runs 3 --schema next",
+            "cxrs model bench prompts/ --models a,b --json | jq '.models[0].label'",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "profile",
+        flags: &[
+            ("--json", "Machine-readable output"),
+            (
+                "--percentiles",
+                "Add duration/effective-token p50/p90/p99, overall and per tool",
+            ),
+            (
+                "--all-repos",
+                "Merge the run logs of every repo in the global repos.json registry and add a per-repo breakdown",
+            ),
+        ],
+        env: &[],
+        examples: &[
+            "cxrs profile 200 --percentiles",
+            "cxrs profile --branch main --path rust/cxrs",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "metrics",
+        flags: &[
+            (
+                "--percentiles",
+                "Add a percentiles object (p50/p90/p99, overall and by_tool)",
+            ),
+            (
+                "--all-repos",
+                "Merge the run logs of every repo in the global repos.json registry and add a per-repo breakdown",
+            ),
+        ],
+        env: &[],
+        examples: &[
+            "cxrs metrics 100 --percentiles | jq .percentiles",
+            "cxrs metrics --branch feature/login",
+            "cxrs metrics --tool cxdiffsum --task task_007 --since 2h",
+            "cxrs metrics 500 --all-repos | jq .by_repo",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "trends",
+        flags: &[
+            ("--bucket day|hour", "Bucket size (default day)"),
+            (
+                "--last DURATION",
+                "Window such as 30d or 12h (default 30d for days, 48h for hours)",
+            ),
+            ("--json", "Emit the trends.v1 report with every bucket"),
+            (
+                "--all-repos",
+                "Merge the run logs of every repo in the global repos.json registry and add a per-repo breakdown",
+            ),
+        ],
+        env: &[],
+        examples: &[
+            "cxrs trends",
+            "cxrs trends --bucket hour --last 12h",
+            "cxrs trends --json | jq '.buckets[] | select(.runs > 0)'",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "digest",
+        flags: &[
+            (
+                "--json",
+                "Emit the digest.v1 report instead of Markdown (--since defaults to 7d; the previous period has the same length)",
+            ),
+            (
+                "--send",
+                "Also post to digest.webhook and/or mail digest.email; exit 1 if a delivery fails",
+            ),
+        ],
+        env: &["CX_DIGEST_WEBHOOK", "CX_DIGEST_EMAIL", "CX_DIGEST_SENDMAIL"],
+        examples: &[
+            "cxrs digest",
+            "cxrs digest --since 30d --json | jq .regressions",
+            "CX_DIGEST_EMAIL=team@example.com cxrs digest --send",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "redact-test",
+        flags: &[(
+            "--json",
+            "Emit {redactions_applied,rules,config,text} instead of the masked text",
+        )],
+        env: &[],
+        examples: &[
+            "cxrs redact-test .env",
+            "git diff | cxrs redact-test --json",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "session",
+        flags: &[("--json", "Machine-readable output")],
+        env: &["CX_SESSION_ENABLED", "CX_SESSION", "CX_SESSION_TURNS"],
+        examples: &[
+            "CX_SESSION_ENABLED=1 CX_SESSION=refactor cxrs cxo git diff",
+            "cxrs session show refactor",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "optimize",
+        flags: &[
+            ("--json", "Machine-readable output"),
+            ("--actions", "Include suggested remediation actions"),
+            ("--strict", "Exit non-zero when findings meet --severity"),
+            (
+                "--severity warning|critical",
+                "Minimum severity for --strict",
+            ),
+        ],
+        env: &["CXALERT_MAX_MS", "CXALERT_MAX_EFF_IN"],
+        examples: &[
+            "cxrs optimize 200 --json",
+            "cxrs optimize --tool cxdiffsum --since 7d",
+        ],
+        state_keys: &["alert_overrides"],
+    },
+    CommandDetail {
+        name: "alert",
+        flags: &[("--json", "Emit the alert.v1 report")],
+        env: &["CXALERT_MAX_MS", "CXALERT_MAX_EFF_IN"],
+        examples: &["cxrs alert 100", "cxrs alert --tool cxj --since 24h --json"],
+        state_keys: &["alert_overrides"],
+    },
+    CommandDetail {
+        name: "promptlint",
+        flags: &[("--json", "Emit the promptlint.v1 report")],
+        env: &[],
+        examples: &[
+            "cxrs promptlint 200",
+            "cxrs promptlint --tool cxcommitjson --json",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "watch",
+        flags: &[
+            ("--interval SECS", "Poll interval"),
+            ("--dispatch", "Send alerts to configured sinks"),
+            ("--from-start", "Replay the whole log before tailing"),
+            ("--once", "Process the current log and exit"),
+        ],
+        env: &["CXALERT_MAX_MS", "CXALERT_MAX_EFF_IN", "CXALERT_MAX_OUT"],
+        examples: &["cxrs watch --interval 5 --dispatch"],
+        state_keys: &["alert_overrides"],
+    },
+    CommandDetail {
+        name: "replay",
+        flags: &[
+            (
+                "--apply",
+                "Resolve the quarantine entry when the replay passes",
+            ),
+            ("--all", "Replay every open quarantine entry"),
+            (
+                "--tool NAME",
+                "Only replay entries from this tool (with --all)",
+            ),
+            (
+                "--reason REASON",
+                "Only replay entries with this reason (with --all)",
+            ),
+            (
+                "--include-resolved",
+                "Also replay resolved entries (with --all)",
+            ),
+            ("--json", "Machine-readable summary (with --all)"),
+        ],
+        env: &["CX_LLM_BACKEND"],
+        examples: &[
+            "cxrs replay q_123 --apply",
+            "cxrs replay --all --tool cxrs_next",
+        ],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "rerun",
+        flags: &[("--json", "Emit the rerun.v1 report with both responses")],
+        env: &["CX_TRANSCRIPTS", "CX_LLM_BACKEND", "CX_MODEL"],
+        examples: &[
+            "CX_TRANSCRIPTS=1 cxrs cxo cargo test",
+            "cxrs rerun \"$(tail -n1 .codex/cxlogs/runs.jsonl | jq -r .execution_id)\"",
+            "cxrs --backend ollama rerun 20240601T101500Z_cxo_4121 --json | jq .identical",
+        ],
+        state_keys: BACKEND_STATE,
+    },
+    CommandDetail {
+        name: "quarantine",
+        flags: &[
+            ("--older-than 30d", "Age threshold for purge"),
+            ("--resolved", "Only purge resolved entries"),
+            ("--dry-run", "List what purge would delete"),
+            (
+                "--json",
+                "Machine-readable stats, or the quarantine-diff.v1 report",
+            ),
+        ],
+        env: &[],
+        examples: &[
+            "cxrs quarantine list",
+            "cxrs quarantine diff 20260301T101500Z_cxrs_next_4242",
+            "cxrs quarantine purge --older-than 14d --dry-run",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "health",
+        flags: &[
+            ("--json", "Emit the health.v1 report"),
+            (
+                "--no-llm",
+                "Skip the backend version, JSON and cxo probes (CI-friendly)",
+            ),
+        ],
+        env: &["CX_LLM_BACKEND"],
+        examples: &[
+            "cxrs health",
+            "cxrs health --no-llm --json | jq '.checks[] | select(.status != \"pass\")'",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "selftest",
+        flags: &[
+            (
+                "--json",
+                "Per-stage status, exit code, duration and problems",
+            ),
+            (
+                "--keep",
+                "Keep the scratch repo (logs, quarantine) for inspection",
+            ),
+        ],
+        env: &["CX_MOCK_FIXTURES"],
+        examples: &[
+            "cxrs selftest",
+            "cxrs selftest --json | jq '.stages[] | select(.status == \"fail\")'",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "where",
+        flags: &[(
+            "--json",
+            "where.v1 document: resolved paths with reason/exists/writable, plus routes",
+        )],
+        env: &["HOME", "CX_REPO_ROOT", "CX_BIN_CX"],
+        examples: &[
+            "cxrs where",
+            "cxrs where --json | jq '.paths[] | select(.writable | not)'",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "secret",
+        flags: &[],
+        env: &["CX_SECRET_PASSPHRASE", "HOME"],
+        examples: &[
+            "printf %s \"$KEY\" | cxrs secret set provider",
+            "cxrs config set http.token secret:provider --global",
+            "cxrs secret list",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "workspace",
+        flags: &[
+            ("--json", "Machine-readable `info` sizes"),
+            (
+                "--out FILE",
+                "Archive path for `backup` (default cx-workspace-<ts>.tar.gz)",
+            ),
+            (
+                "--force",
+                "Let `restore` move a non-empty .codex aside to .codex.bak-<ts>",
+            ),
+        ],
+        env: &[],
+        examples: &[
+            "cxrs workspace info",
+            "cxrs workspace backup --out ~/cx-snapshot.tar.gz",
+            "cxrs workspace restore ~/cx-snapshot.tar.gz --force",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "init",
+        flags: &[(
+            "--gitignore",
+            "Ignore .codex/cxlogs and .codex/quarantine in .gitignore",
+        )],
+        env: &[],
+        examples: &["cxrs init", "cxrs init --gitignore"],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "gc",
+        flags: &[
+            ("--dry-run", "List what would be deleted without deleting"),
+            ("--json", "Print a gc.v1 JSON report"),
+            (
+                "--store NAME",
+                "Only logs, cache, transcripts, sessions or quarantine",
+            ),
+        ],
+        env: &[
+            "CX_GC_AUTO",
+            "CX_GC_LOGS_KEEP_DAYS",
+            "CX_GC_LOGS_MAX_BYTES",
+            "CX_GC_CACHE_KEEP_DAYS",
+            "CX_GC_CACHE_MAX_BYTES",
+            "CX_TRANSCRIPTS_KEEP_DAYS",
+            "CX_TRANSCRIPTS_MAX_BYTES",
+            "CX_GC_SESSIONS_KEEP_DAYS",
+            "CX_GC_SESSIONS_MAX_BYTES",
+            "CX_GC_QUARANTINE_KEEP_DAYS",
+            "CX_GC_QUARANTINE_MAX_BYTES",
+        ],
+        examples: &["cxrs gc --dry-run", "cxrs gc --store cache --json"],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "annotate",
+        flags: &[],
+        env: &[],
+        examples: &[
+            "cxrs annotate run 3f2a9c1e-... \"this was the bad deploy\"",
+            "cxrs annotate task task_004 \"blocked on vendor API\"",
+        ],
+        state_keys: &[],
+    },
+    CommandDetail {
+        name: "doctor",
+        flags: &[(
+            "--fix",
+            "Create missing .codex dirs, init state.json/config.toml, regenerate a corrupt state (backed up), install bash completions",
+        )],
+        env: &["CX_LLM_BACKEND", "CX_DISABLE_CODEX", "CX_DISABLE_OLLAMA"],
+        examples: &["cxrs doctor", "cxrs doctor --fix"],
+        state_keys: BACKEND_STATE,
+    },
+];
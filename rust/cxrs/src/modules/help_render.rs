@@ -1,4 +1,9 @@
+use crate::cli::{GLOBAL_FLAGS, command_flags};
+use crate::run_filter::{RUN_FILTER_COMMANDS, RUN_FILTER_FLAGS};
+
 use super::help_data::{MAIN_COMMANDS, TASK_COMMANDS};
+use super::help_detail::command_details;
+use super::{CommandDetail, CommandHelp};
 
 fn replace_tokens(input: &str, run_window: usize, quarantine_list: usize) -> String {
     input
@@ -15,7 +20,18 @@ pub fn render_help(
     let mut out = String::new();
     out.push_str(&format!("{app_name} - {app_desc}\n\n"));
     out.push_str("Usage:\n");
-    out.push_str(&format!("  {app_name} <command> [args]\n\n"));
+    out.push_str(&format!("  {app_name} [global flags] <command> [args]\n\n"));
+    out.push_str("Global flags (before the command):\n");
+    let flag_width = GLOBAL_FLAGS
+        .iter()
+        .map(|f| f.usage().len())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    for f in GLOBAL_FLAGS {
+        out.push_str(&format!("  {:<flag_width$}{}\n", f.usage(), f.help));
+    }
+    out.push('\n');
     out.push_str("Commands:\n");
     let width = MAIN_COMMANDS
         .iter()
@@ -46,3 +62,224 @@ pub fn render_task_help() -> String {
     }
     out
 }
+
+fn help_entries(name: &str) -> Vec<&'static CommandHelp> {
+    MAIN_COMMANDS
+        .iter()
+        .chain(TASK_COMMANDS.iter())
+        .filter(|c| c.name == name)
+        .collect()
+}
+
+fn detail_for(name: &str) -> Option<&'static CommandDetail> {
+    command_details().find(|d| d.name == name)
+}
+
+/// Registry flags plus the shared command flags (`--timeout`, `--stream`, `--tee`)
+/// and, for analytics commands, the run filter flags.
+fn detail_flags(detail: &CommandDetail) -> Vec<(String, &'static str)> {
+    let mut flags: Vec<(String, &'static str)> = detail
+        .flags
+        .iter()
+        .map(|(f, d)| ((*f).to_string(), *d))
+        .collect();
+    flags.extend(command_flags(detail.name).map(|f| (f.usage(), f.help)));
+    if RUN_FILTER_COMMANDS.contains(&detail.name) {
+        flags.extend(RUN_FILTER_FLAGS.iter().map(|(f, d)| ((*f).to_string(), *d)));
+    }
+    flags
+}
+
+/// Task usages are written as `cx task ...`; normalize them to `<app> task ...`.
+fn full_usage(app_name: &str, usage: &str) -> String {
+    format!("{app_name} {}", usage.strip_prefix("cx ").unwrap_or(usage))
+}
+
+/// Registry names in display order: main commands first, then task subcommands.
+fn command_names() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = Vec::new();
+    for c in MAIN_COMMANDS.iter().chain(TASK_COMMANDS.iter()) {
+        if !names.contains(&c.name) {
+            names.push(c.name);
+        }
+    }
+    names
+}
+
+/// Map a command name (native, `cx`-prefixed compat, or `task <op>`) to its registry entry.
+pub fn resolve_help_topic(name: &str) -> Option<String> {
+    if !help_entries(name).is_empty() {
+        return Some(name.to_string());
+    }
+    let alias = name.strip_prefix("cx")?.replace('_', "-");
+    (!help_entries(&alias).is_empty()).then_some(alias)
+}
+
+pub fn render_command_help(
+    app_name: &str,
+    name: &str,
+    run_window: usize,
+    quarantine_list: usize,
+) -> Option<String> {
+    let entries = help_entries(name);
+    let first = entries.first()?;
+    let mut out = if entries.len() == 1 {
+        let desc = replace_tokens(first.description, run_window, quarantine_list);
+        format!("{app_name} {name} - {desc}\n\nUsage:\n")
+    } else {
+        format!("{app_name} {name}\n\nUsage:\n")
+    };
+    for c in &entries {
+        out.push_str(&format!("  {}\n", full_usage(app_name, c.usage)));
+        if entries.len() > 1 {
+            let desc = replace_tokens(c.description, run_window, quarantine_list);
+            out.push_str(&format!("      {desc}\n"));
+        }
+    }
+    let Some(detail) = detail_for(name) else {
+        return Some(out);
+    };
+    let flags = detail_flags(detail);
+    if !flags.is_empty() {
+        out.push_str("\nFlags:\n");
+        let width = flags.iter().map(|(f, _)| f.len()).max().unwrap_or(0) + 2;
+        for (flag, desc) in flags {
+            out.push_str(&format!("  {flag:<width$}{desc}\n"));
+        }
+    }
+    for (title, items) in [
+        ("Environment", detail.env),
+        ("Examples", detail.examples),
+        ("State keys", detail.state_keys),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n{title}:\n"));
+        for item in items {
+            out.push_str(&format!("  {item}\n"));
+        }
+    }
+    Some(out)
+}
+
+fn render_command_md(
+    app_name: &str,
+    name: &str,
+    run_window: usize,
+    quarantine_list: usize,
+) -> String {
+    let entries = help_entries(name);
+    let mut out = format!("## {name}\n\n");
+    for c in &entries {
+        let desc = replace_tokens(c.description, run_window, quarantine_list);
+        out.push_str(&format!(
+            "{desc}\n\n```\n{}\n```\n\n",
+            full_usage(app_name, c.usage)
+        ));
+    }
+    let Some(detail) = detail_for(name) else {
+        return out;
+    };
+    let flags = detail_flags(detail);
+    if !flags.is_empty() {
+        out.push_str("### Flags\n\n| Flag | Description |\n| --- | --- |\n");
+        for (flag, desc) in flags {
+            out.push_str(&format!("| `{flag}` | {} |\n", desc.replace('|', "\\|")));
+        }
+        out.push('\n');
+    }
+    if !detail.env.is_empty() {
+        out.push_str("### Environment\n\n");
+        for var in detail.env {
+            out.push_str(&format!("- `{var}`\n"));
+        }
+        out.push('\n');
+    }
+    if !detail.examples.is_empty() {
+        out.push_str("### Examples\n\n```sh\n");
+        for ex in detail.examples {
+            out.push_str(&format!("{ex}\n"));
+        }
+        out.push_str("```\n\n");
+    }
+    if !detail.state_keys.is_empty() {
+        out.push_str("### State keys\n\n");
+        for key in detail.state_keys {
+            out.push_str(&format!("- `{key}`\n"));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Full command reference (`help --all`), as plain text or Markdown.
+pub fn render_reference(
+    app_name: &str,
+    app_desc: &str,
+    markdown: bool,
+    run_window: usize,
+    quarantine_list: usize,
+) -> String {
+    let names = command_names();
+    if !markdown {
+        return names
+            .iter()
+            .filter_map(|n| render_command_help(app_name, n, run_window, quarantine_list))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    let mut out = format!("# {app_name} command reference\n\n{app_desc}\n\n");
+    out.push_str("## Global flags\n\nAccepted before the command name; each overrides one config setting for the run.\n\n");
+    out.push_str("| Flag | Overrides | Description |\n| --- | --- | --- |\n");
+    for f in GLOBAL_FLAGS {
+        out.push_str(&format!(
+            "| `{}` | `{}` | {} |\n",
+            f.usage(),
+            f.target_name(),
+            f.help.replace('|', "\\|")
+        ));
+    }
+    out.push('\n');
+    for name in names {
+        out.push_str(&render_command_md(
+            app_name,
+            name,
+            run_window,
+            quarantine_list,
+        ));
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_detail_entry_has_a_registry_command() {
+        for d in command_details() {
+            assert!(
+                !help_entries(d.name).is_empty(),
+                "no help entry for {}",
+                d.name
+            );
+        }
+    }
+
+    #[test]
+    fn compat_names_resolve_to_native_topics() {
+        assert_eq!(
+            resolve_help_topic("cxdiffsum_staged").as_deref(),
+            Some("diffsum-staged")
+        );
+        assert_eq!(resolve_help_topic("cxo").as_deref(), Some("cxo"));
+        assert_eq!(
+            resolve_help_topic("task run-all").as_deref(),
+            Some("task run-all")
+        );
+        assert_eq!(resolve_help_topic("nope"), None);
+    }
+}
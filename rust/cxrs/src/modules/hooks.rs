@@ -0,0 +1,202 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::changelog::git_output;
+use crate::config::app_config;
+use crate::error::{EXIT_OK, EXIT_RUNTIME, print_runtime_error, print_usage_error};
+use crate::logs::{cmd_logs, load_runs};
+use crate::paths::resolve_log_file;
+use crate::schema_ops::cmd_ci;
+
+const HOOK_USAGE: &str = "hook install --type pre-push [--logs] [--force] | hook uninstall --type pre-push | hook run pre-push [--logs]";
+const HOOK_TYPES: &[&str] = &["pre-push"];
+/// Line after the shebang that marks a hook script written by `hook install`.
+const HOOK_MARKER: &str = "# cxrs-managed hook";
+/// Runs considered for the schema failure rate gate.
+const RATE_WINDOW: usize = 50;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct HookArgs {
+    hook_type: Option<String>,
+    logs: bool,
+    force: bool,
+}
+
+fn parse_hook_args(args: &[String]) -> Result<HookArgs, ()> {
+    let mut out = HookArgs::default();
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--type" => out.hook_type = Some(it.next().ok_or(())?.clone()),
+            "--logs" => out.logs = true,
+            "--force" => out.force = true,
+            v if !v.starts_with('-') && out.hook_type.is_none() => {
+                out.hook_type = Some(v.to_string())
+            }
+            _ => return Err(()),
+        }
+    }
+    match &out.hook_type {
+        Some(t) if HOOK_TYPES.contains(&t.as_str()) => Ok(out),
+        _ => Err(()),
+    }
+}
+
+fn hook_path(hook_type: &str) -> Result<PathBuf, String> {
+    let dir = git_output(&["rev-parse", "--git-path", "hooks"])?;
+    Ok(PathBuf::from(dir.trim()).join(hook_type))
+}
+
+fn is_managed(text: &str) -> bool {
+    text.lines().nth(1) == Some(HOOK_MARKER)
+}
+
+/// The hook calls this binary by absolute path when it still exists, so it
+/// works without `cxrs` on the PATH git hooks run with.
+fn hook_script(hook_type: &str, logs: bool) -> String {
+    let exe = env::current_exe()
+        .ok()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "cxrs".to_string());
+    let flags = if logs { " --logs" } else { "" };
+    format!(
+        "#!/bin/sh\n{HOOK_MARKER}\n\
+# Installed by `cxrs hook install --type {hook_type}`; bypass with `git push --no-verify`.\n\
+CXRS='{}'\n\
+[ -x \"$CXRS\" ] || CXRS=cxrs\n\
+exec \"$CXRS\" hook run {hook_type}{flags}\n",
+        exe.replace('\'', r"'\''")
+    )
+}
+
+fn install(args: &HookArgs, hook_type: &str) -> i32 {
+    let path = match hook_path(hook_type) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("hook", &e),
+    };
+    if let Ok(existing) = fs::read_to_string(&path)
+        && !is_managed(&existing)
+        && !args.force
+    {
+        return print_runtime_error(
+            "hook",
+            &format!(
+                "{} exists and was not installed by cxrs (use --force to replace it)",
+                path.display()
+            ),
+        );
+    }
+    if let Some(parent) = path.parent()
+        && let Err(e) = fs::create_dir_all(parent)
+    {
+        return print_runtime_error("hook", &format!("cannot create {}: {e}", parent.display()));
+    }
+    if let Err(e) = fs::write(&path, hook_script(hook_type, args.logs)) {
+        return print_runtime_error("hook", &format!("cannot write {}: {e}", path.display()));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Err(e) = fs::set_permissions(&path, fs::Permissions::from_mode(0o755)) {
+            return print_runtime_error("hook", &format!("cannot chmod {}: {e}", path.display()));
+        }
+    }
+    println!("installed {hook_type} hook: {}", path.display());
+    println!(
+        "checks: ci validate --strict{}, schema failure rate <= {}%",
+        if args.logs { ", logs validate" } else { "" },
+        app_config().hook_max_schema_fail_pct
+    );
+    EXIT_OK
+}
+
+fn uninstall(hook_type: &str) -> i32 {
+    let path = match hook_path(hook_type) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("hook", &e),
+    };
+    match fs::read_to_string(&path) {
+        Err(_) => {
+            println!("no {hook_type} hook installed");
+            EXIT_OK
+        }
+        Ok(text) if !is_managed(&text) => print_runtime_error(
+            "hook",
+            &format!("{} was not installed by cxrs; leaving it", path.display()),
+        ),
+        Ok(_) => match fs::remove_file(&path) {
+            Ok(()) => {
+                println!("removed {hook_type} hook: {}", path.display());
+                EXIT_OK
+            }
+            Err(e) => {
+                print_runtime_error("hook", &format!("cannot remove {}: {e}", path.display()))
+            }
+        },
+    }
+}
+
+/// `(schema failures, schema runs)` over the last [`RATE_WINDOW`] runs.
+fn schema_failure_counts() -> Result<(usize, usize), String> {
+    let Some(log_file) = resolve_log_file().filter(|p| p.exists()) else {
+        return Ok((0, 0));
+    };
+    let runs = load_runs(&log_file, RATE_WINDOW)?;
+    let schema_runs: Vec<bool> = runs.iter().filter_map(|r| r.schema_valid).collect();
+    let failures = schema_runs.iter().filter(|ok| !**ok).count();
+    Ok((failures, schema_runs.len()))
+}
+
+fn run_pre_push(app_name: &str, logs: bool) -> i32 {
+    let mut blocked: Vec<String> = Vec::new();
+    if cmd_ci(app_name, &["validate".to_string(), "--strict".to_string()]) != 0 {
+        blocked.push("ci validate --strict failed".to_string());
+    }
+    if logs && cmd_logs(app_name, &["validate".to_string()]) != 0 {
+        blocked.push("logs validate failed".to_string());
+    }
+    let max_pct = app_config().hook_max_schema_fail_pct;
+    match schema_failure_counts() {
+        Ok((fails, total)) if max_pct > 0 && total > 0 => {
+            let pct = fails as f64 * 100.0 / total as f64;
+            println!(
+                "schema_failure_rate: {pct:.1}% ({fails}/{total} schema runs, max {max_pct}%)"
+            );
+            if pct > max_pct as f64 {
+                blocked.push(format!(
+                    "schema failure rate {pct:.1}% exceeds {max_pct}% (hooks.max_schema_fail_pct)"
+                ));
+            }
+        }
+        Ok(_) => {}
+        Err(e) => blocked.push(format!("cannot read run log: {e}")),
+    }
+    if blocked.is_empty() {
+        println!("pre-push: ok");
+        return EXIT_OK;
+    }
+    for reason in &blocked {
+        crate::cx_eprintln!("cxrs pre-push: {reason}");
+    }
+    crate::cx_eprintln!("cxrs pre-push: push blocked (bypass with `git push --no-verify`)");
+    EXIT_RUNTIME
+}
+
+/// `hook install|uninstall|run`: a git hook that keeps the shared `.codex`
+/// contract healthy before it leaves the machine.
+pub fn cmd_hook(app_name: &str, args: &[String]) -> i32 {
+    let Some(sub) = args.first() else {
+        return print_usage_error("hook", HOOK_USAGE);
+    };
+    let Ok(parsed) = parse_hook_args(&args[1..]) else {
+        return print_usage_error("hook", HOOK_USAGE);
+    };
+    let hook_type = parsed.hook_type.clone().unwrap_or_default();
+    match sub.as_str() {
+        "install" => install(&parsed, &hook_type),
+        "uninstall" if !parsed.logs && !parsed.force => uninstall(&hook_type),
+        "run" if !parsed.force => run_pre_push(app_name, parsed.logs),
+        _ => print_usage_error("hook", HOOK_USAGE),
+    }
+}
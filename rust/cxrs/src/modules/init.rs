@@ -0,0 +1,199 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::doctor_fix::{ensure_dir, fix_config_file, fix_state_file};
+use crate::error::{format_error, print_usage_error};
+use crate::paths::repo_root;
+
+const INIT_USAGE: &str = "init [--gitignore]";
+
+/// The default schema registry, built into the binary so `init` works in any
+/// repo. Must match `.codex/schemas` of the cx checkout (see the test below).
+const BUNDLED_SCHEMAS: &[(&str, &str)] = &[
+    (
+        "commitjson.schema.json",
+        include_str!("../../../../.codex/schemas/commitjson.schema.json"),
+    ),
+    (
+        "diffsum.schema.json",
+        include_str!("../../../../.codex/schemas/diffsum.schema.json"),
+    ),
+    (
+        "explain.schema.json",
+        include_str!("../../../../.codex/schemas/explain.schema.json"),
+    ),
+    (
+        "fanout.schema.json",
+        include_str!("../../../../.codex/schemas/fanout.schema.json"),
+    ),
+    (
+        "fix.schema.json",
+        include_str!("../../../../.codex/schemas/fix.schema.json"),
+    ),
+    (
+        "fixrun.schema.json",
+        include_str!("../../../../.codex/schemas/fixrun.schema.json"),
+    ),
+    (
+        "next.schema.json",
+        include_str!("../../../../.codex/schemas/next.schema.json"),
+    ),
+    (
+        "stage.schema.json",
+        include_str!("../../../../.codex/schemas/stage.schema.json"),
+    ),
+];
+
+/// Local run data stays out of git; schemas, prompts and config are committed.
+const GITIGNORE_ENTRIES: &[&str] = &[".codex/cxlogs/", ".codex/quarantine/"];
+
+const NEXT_STEPS: &[&str] = &[
+    "cxrs doctor                 # check backend, paths and schemas",
+    "cxrs config show --origin   # review settings; edit .codex/config.toml",
+    "cxrs cxo git status         # first logged run",
+    "git add .codex              # commit schemas, prompts and config",
+];
+
+/// Write a bundled schema unless the repo already has one by that name.
+fn write_schema(dir: &Path, name: &str, body: &str) -> Result<Option<String>, String> {
+    let path = dir.join(name);
+    if path.exists() {
+        return Ok(None);
+    }
+    fs::write(&path, body).map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    Ok(Some(format!("wrote {}", path.display())))
+}
+
+/// Entries of `wanted` missing from a `.gitignore` body (trailing `/` optional).
+fn missing_ignore_entries<'a>(existing: &str, wanted: &[&'a str]) -> Vec<&'a str> {
+    let have: Vec<&str> = existing
+        .lines()
+        .map(|l| l.trim().trim_start_matches('/').trim_end_matches('/'))
+        .collect();
+    wanted
+        .iter()
+        .filter(|w| !have.contains(&w.trim_end_matches('/')))
+        .copied()
+        .collect()
+}
+
+fn update_gitignore(root: &Path) -> Result<Option<String>, String> {
+    let path = root.join(".gitignore");
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let missing = missing_ignore_entries(&existing, GITIGNORE_ENTRIES);
+    if missing.is_empty() {
+        return Ok(None);
+    }
+    let mut body = existing;
+    if !body.is_empty() && !body.ends_with('\n') {
+        body.push('\n');
+    }
+    body.push_str("# cx: local run logs and quarantine; schemas are committed\n");
+    for entry in &missing {
+        body.push_str(entry);
+        body.push('\n');
+    }
+    fs::write(&path, body).map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    Ok(Some(format!(
+        "added {} to {}",
+        missing.join(", "),
+        path.display()
+    )))
+}
+
+/// `init [--gitignore]`: scaffold `.codex` at the project root (the current
+/// dir outside any project) with the default schemas, a starter config and
+/// state. Existing files are kept, so it is safe to re-run.
+pub fn cmd_init(args: &[String]) -> i32 {
+    let gitignore = match args {
+        [] => false,
+        [flag] if flag == "--gitignore" => true,
+        _ => return print_usage_error("init", INIT_USAGE),
+    };
+    let root: PathBuf = match repo_root().map_or_else(env::current_dir, Ok) {
+        Ok(r) => r,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("init", &format!("current dir: {e}")));
+            return 1;
+        }
+    };
+    let codex = root.join(".codex");
+    let mut changes: Vec<String> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+    let mut record = |res: Result<Option<String>, String>| match res {
+        Ok(Some(change)) => changes.push(change),
+        Ok(None) => {}
+        Err(e) => errors.push(e),
+    };
+    for sub in ["cxlogs", "quarantine", "schemas", "prompts"] {
+        record(ensure_dir(&codex.join(sub)));
+    }
+    let schemas = codex.join("schemas");
+    if schemas.is_dir() {
+        for (name, body) in BUNDLED_SCHEMAS {
+            record(write_schema(&schemas, name, body));
+        }
+    }
+    record(fix_config_file(&codex.join("config.toml")));
+    record(fix_state_file(&codex.join("state.json")));
+    if gitignore {
+        record(update_gitignore(&root));
+    }
+
+    println!("== cxrs init: {} ==", codex.display());
+    if changes.is_empty() && errors.is_empty() {
+        println!("already initialized; nothing to do");
+    }
+    for change in &changes {
+        println!("{change}");
+    }
+    for e in &errors {
+        crate::cx_eprintln!("{}", format_error("init", e));
+    }
+    if !errors.is_empty() {
+        return 1;
+    }
+    println!();
+    println!("next steps:");
+    for step in NEXT_STEPS {
+        println!("  {step}");
+    }
+    if !gitignore {
+        println!(
+            "  (re-run with --gitignore to keep .codex/cxlogs and .codex/quarantine out of git)"
+        );
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_schemas_match_the_checkout() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../.codex/schemas");
+        let mut on_disk: Vec<String> = fs::read_dir(&dir)
+            .expect("schema dir")
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|n| n.ends_with(".schema.json"))
+            .collect();
+        on_disk.sort();
+        let bundled: Vec<&str> = BUNDLED_SCHEMAS.iter().map(|(n, _)| *n).collect();
+        assert_eq!(on_disk, bundled);
+    }
+
+    #[test]
+    fn gitignore_entries_are_added_once() {
+        assert_eq!(
+            missing_ignore_entries("target/\n/.codex/cxlogs\n", GITIGNORE_ENTRIES),
+            [".codex/quarantine/"]
+        );
+        assert!(
+            missing_ignore_entries(".codex/cxlogs/\n.codex/quarantine/\n", GITIGNORE_ENTRIES)
+                .is_empty()
+        );
+    }
+}
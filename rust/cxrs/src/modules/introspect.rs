@@ -120,6 +120,7 @@ pub fn print_version(app_name: &str, app_version: &str) {
 
     println!("budget_chars: {}", cfg.budget_chars);
     println!("budget_lines: {}", cfg.budget_lines);
+    println!("budget_tokens: {}", cfg.budget_tokens);
     println!("cmd_timeout_secs: {}", cfg.cmd_timeout_secs);
     println!("clip_mode: {}", cfg.clip_mode);
     print_version_preferences();
@@ -167,6 +168,10 @@ pub fn cmd_core(app_version: &str) -> i32 {
     println!("capture_external_dependencies: none");
     println!("budget_chars: {}", budget_cfg.budget_chars);
     println!("budget_lines: {}", budget_cfg.budget_lines);
+    println!(
+        "budget_tokens: {}",
+        budget_cfg.budget_tokens.unwrap_or_default()
+    );
     println!("cmd_timeout_secs: {}", runtime_cfg.cmd_timeout_secs);
     println!("clip_mode: {}", budget_cfg.clip_mode);
     println!("clip_footer: {}", budget_cfg.clip_footer);
@@ -1,21 +1,31 @@
 use serde_json::{Value, json};
-use std::process::Command;
+use std::process::{Command, Output};
 
-use crate::process::{TimeoutInfo, run_command_with_stdin_output_with_timeout_meta};
+use crate::codex_args::{render_codex_args, resolved_codex_args};
+use crate::process::{
+    ProcessError, TimeoutInfo, run_command_with_stdin_output_with_timeout_meta,
+    run_command_with_stdin_streaming_meta,
+};
+use crate::signals::ShutdownGuard;
 use crate::types::UsageStats;
 
 #[derive(Clone, Debug)]
 pub struct LlmRunError {
     pub message: String,
     pub timeout: Option<TimeoutInfo>,
+    /// The backend call was stopped by Ctrl-C/SIGTERM.
+    pub interrupted: bool,
+    /// The call was refused because offline mode is on.
+    pub offline: bool,
 }
 
 impl LlmRunError {
-    fn from_process(err: crate::process::ProcessError) -> Self {
-        let timeout = err.timeout_info().cloned();
+    fn from_process(err: ProcessError) -> Self {
         Self {
             message: err.to_string(),
-            timeout,
+            timeout: err.timeout_info().cloned(),
+            interrupted: matches!(err, ProcessError::Interrupted(_)),
+            offline: false,
         }
     }
 
@@ -23,8 +33,37 @@ impl LlmRunError {
         Self {
             message,
             timeout: None,
+            interrupted: false,
+            offline: false,
         }
     }
+
+    pub(crate) fn offline() -> Self {
+        Self {
+            offline: true,
+            ..Self::message(crate::offline::OFFLINE_MESSAGE.to_string())
+        }
+    }
+}
+
+/// Receives backend stdout as it is read (`--stream`).
+pub type ChunkSink<'a> = Option<&'a mut dyn FnMut(&[u8])>;
+
+/// Run one backend process, forwarding stdout to `sink` when given. Ctrl-C
+/// while it runs terminates the child and surfaces as an interrupted error so
+/// the run is still logged.
+fn run_backend(
+    cmd: Command,
+    prompt: &str,
+    label: &str,
+    sink: ChunkSink<'_>,
+) -> Result<Output, LlmRunError> {
+    let _guard = ShutdownGuard::install();
+    match sink {
+        Some(on_chunk) => run_command_with_stdin_streaming_meta(cmd, prompt, label, on_chunk),
+        None => run_command_with_stdin_output_with_timeout_meta(cmd, prompt, label),
+    }
+    .map_err(LlmRunError::from_process)
 }
 
 impl std::fmt::Display for LlmRunError {
@@ -79,11 +118,20 @@ pub fn extract_agent_text(jsonl: &str) -> Option<String> {
     last
 }
 
-pub fn run_codex_jsonl(prompt: &str) -> Result<String, LlmRunError> {
+/// Allowlisted extra options for `codex exec`; a bad configured value fails
+/// the call before codex is spawned.
+fn codex_exec_args() -> Result<Vec<String>, LlmRunError> {
+    resolved_codex_args()
+        .map(|args| render_codex_args(&args))
+        .map_err(LlmRunError::message)
+}
+
+pub fn run_codex_jsonl(prompt: &str, sink: ChunkSink<'_>) -> Result<String, LlmRunError> {
     let mut cmd = Command::new("codex");
-    cmd.args(["exec", "--json", "-"]);
-    let out = run_command_with_stdin_output_with_timeout_meta(cmd, prompt, "codex exec --json -")
-        .map_err(LlmRunError::from_process)?;
+    cmd.arg("exec")
+        .args(codex_exec_args()?)
+        .args(["--json", "-"]);
+    let out = run_backend(cmd, prompt, "codex exec --json -", sink)?;
 
     if !out.status.success() {
         return Err(LlmRunError::message(format!(
@@ -95,11 +143,10 @@ pub fn run_codex_jsonl(prompt: &str) -> Result<String, LlmRunError> {
     Ok(String::from_utf8_lossy(&out.stdout).to_string())
 }
 
-pub fn run_codex_plain(prompt: &str) -> Result<String, LlmRunError> {
+pub fn run_codex_plain(prompt: &str, sink: ChunkSink<'_>) -> Result<String, LlmRunError> {
     let mut cmd = Command::new("codex");
-    cmd.args(["exec", "-"]);
-    let out = run_command_with_stdin_output_with_timeout_meta(cmd, prompt, "codex exec -")
-        .map_err(LlmRunError::from_process)?;
+    cmd.arg("exec").args(codex_exec_args()?).arg("-");
+    let out = run_backend(cmd, prompt, "codex exec -", sink)?;
     if !out.status.success() {
         return Err(LlmRunError::message(format!(
             "codex exited with status {}",
@@ -109,11 +156,14 @@ pub fn run_codex_plain(prompt: &str) -> Result<String, LlmRunError> {
     Ok(String::from_utf8_lossy(&out.stdout).to_string())
 }
 
-pub fn run_ollama_plain(prompt: &str, model: &str) -> Result<String, LlmRunError> {
+pub fn run_ollama_plain(
+    prompt: &str,
+    model: &str,
+    sink: ChunkSink<'_>,
+) -> Result<String, LlmRunError> {
     let mut cmd = Command::new("ollama");
     cmd.args(["run", model]);
-    let out = run_command_with_stdin_output_with_timeout_meta(cmd, prompt, "ollama run")
-        .map_err(LlmRunError::from_process)?;
+    let out = run_backend(cmd, prompt, "ollama run", sink)?;
     if !out.status.success() {
         return Err(LlmRunError::message(format!(
             "ollama exited with status {}",
@@ -139,8 +189,7 @@ fn run_http_request(prompt: &str, url: &str, token: Option<&str>) -> Result<Stri
     if let Some(t) = token.filter(|v| !v.trim().is_empty()) {
         cmd.args(["-H", &format!("Authorization: Bearer {t}")]);
     }
-    let out = run_command_with_stdin_output_with_timeout_meta(cmd, prompt, "http provider curl")
-        .map_err(LlmRunError::from_process)?;
+    let out = run_backend(cmd, prompt, "http provider curl", None)?;
     if !out.status.success() {
         let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
         let kind = classify_http_curl_error(&stderr);
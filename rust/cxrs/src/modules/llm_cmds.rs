@@ -0,0 +1,380 @@
+use serde_json::{Value, json};
+
+use crate::analytics::quota_probe_for_backend_days;
+use crate::model_context::resolve_context_window;
+use crate::ollama_api::{OllamaModelInfo, inspect_model, list_models, ollama_host, pull_model};
+use crate::runtime::{llm_backend, llm_model, ollama_model_preference};
+use crate::state::{set_state_path, state_cache_clear};
+use crate::workspace::human_bytes;
+
+fn print_llm_usage(app_name: &str) {
+    crate::cx_eprintln!(
+        "Usage: {app_name} llm <show|use <codex|ollama> [model]|unset <backend|model|all>|set-backend <codex|ollama>|set-model <model>|clear-model|models [--json]|pull <model>|inspect [model] [--json]>"
+    );
+}
+
+fn llm_show() -> i32 {
+    let backend = llm_backend();
+    let model = llm_model();
+    let ollama_pref = ollama_model_preference();
+    println!("llm_backend: {backend}");
+    println!(
+        "active_model: {}",
+        if model.is_empty() { "<unset>" } else { &model }
+    );
+    println!(
+        "ollama_model: {}",
+        if ollama_pref.is_empty() {
+            "<unset>"
+        } else {
+            &ollama_pref
+        }
+    );
+    match resolve_context_window(false) {
+        Some(w) => println!("context_window: {} ({})", w.tokens, w.source),
+        None => println!("context_window: <unknown>"),
+    }
+    0
+}
+
+fn emit_quota_probe_notice(backend: &str, model: Option<&str>) {
+    let Ok(payload) = quota_probe_for_backend_days(30, backend, model) else {
+        crate::cx_eprintln!("quota_probe: unavailable");
+        return;
+    };
+    let backend = payload
+        .get("backend")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown");
+    let service_kind = payload
+        .get("service_kind")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown");
+    let source = payload
+        .get("quota_source")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown");
+    let remaining = payload.get("quota_remaining_pct").and_then(Value::as_f64);
+
+    if service_kind == "local_unmetered" {
+        crate::cx_eprintln!(
+            "quota_probe: backend={} service_kind=local_unmetered (provider quota unavailable for local model)",
+            backend
+        );
+        return;
+    }
+
+    if let Some(rem) = remaining {
+        let remaining_pct = format!("{}%", (rem * 100.0).round() as i64);
+        crate::cx_eprintln!(
+            "quota_probe: backend={} remaining={} source={}",
+            backend,
+            remaining_pct,
+            source
+        );
+    } else {
+        crate::cx_eprintln!(
+            "quota_probe: backend={} remaining=unknown source={} (set quota total or refresh catalog)",
+            backend,
+            source
+        );
+    }
+}
+
+fn llm_use(app_name: &str, args: &[String]) -> i32 {
+    let Some(target) = args.get(1).map(|s| s.to_lowercase()) else {
+        print_llm_usage(app_name);
+        return 2;
+    };
+    if target != "codex" && target != "ollama" {
+        print_llm_usage(app_name);
+        return 2;
+    }
+    if let Err(e) = set_state_path("preferences.llm_backend", Value::String(target.clone())) {
+        crate::cx_eprintln!("cxrs llm use: {e}");
+        return 1;
+    }
+    if target == "ollama" {
+        if let Some(model) = args.get(2) {
+            let m = model.trim();
+            if m.is_empty() {
+                print_llm_usage(app_name);
+                return 2;
+            }
+            if let Err(e) = set_state_path("preferences.ollama_model", Value::String(m.to_string()))
+            {
+                crate::cx_eprintln!("cxrs llm use: {e}");
+                return 1;
+            }
+        }
+        println!("ok");
+        println!("llm_backend: ollama");
+        let pref = ollama_model_preference();
+        println!(
+            "ollama_model: {}",
+            if pref.is_empty() { "<unset>" } else { &pref }
+        );
+        state_cache_clear();
+        let model_opt = if pref.is_empty() {
+            None
+        } else {
+            Some(pref.as_str())
+        };
+        emit_quota_probe_notice("ollama", model_opt);
+        return 0;
+    }
+    println!("ok");
+    println!("llm_backend: codex");
+    state_cache_clear();
+    emit_quota_probe_notice("codex", None);
+    0
+}
+
+fn llm_unset(app_name: &str, args: &[String]) -> i32 {
+    let target = args.get(1).map(String::as_str).unwrap_or("all");
+    match target {
+        "backend" => {
+            if let Err(e) = set_state_path("preferences.llm_backend", Value::Null) {
+                crate::cx_eprintln!("cxrs llm unset backend: {e}");
+                return 1;
+            }
+            println!("ok");
+            println!("llm_backend: <unset>");
+            0
+        }
+        "model" => {
+            if let Err(e) = set_state_path("preferences.ollama_model", Value::Null) {
+                crate::cx_eprintln!("cxrs llm unset model: {e}");
+                return 1;
+            }
+            println!("ok");
+            println!("ollama_model: <unset>");
+            0
+        }
+        "all" => {
+            if let Err(e) = set_state_path("preferences.llm_backend", Value::Null) {
+                crate::cx_eprintln!("cxrs llm unset all: {e}");
+                return 1;
+            }
+            if let Err(e) = set_state_path("preferences.ollama_model", Value::Null) {
+                crate::cx_eprintln!("cxrs llm unset all: {e}");
+                return 1;
+            }
+            println!("ok");
+            println!("llm_backend: <unset>");
+            println!("ollama_model: <unset>");
+            0
+        }
+        _ => {
+            print_llm_usage(app_name);
+            2
+        }
+    }
+}
+
+fn llm_set_backend(app_name: &str, args: &[String]) -> i32 {
+    let Some(v) = args.get(1).map(|s| s.to_lowercase()) else {
+        print_llm_usage(app_name);
+        return 2;
+    };
+    if v != "codex" && v != "ollama" {
+        print_llm_usage(app_name);
+        return 2;
+    }
+    if let Err(e) = set_state_path("preferences.llm_backend", Value::String(v.clone())) {
+        crate::cx_eprintln!("cxrs llm set-backend: {e}");
+        return 1;
+    }
+    println!("ok");
+    println!("llm_backend: {v}");
+    state_cache_clear();
+    emit_quota_probe_notice(&v, None);
+    0
+}
+
+fn llm_set_model(app_name: &str, args: &[String]) -> i32 {
+    let Some(model) = args.get(1) else {
+        print_llm_usage(app_name);
+        return 2;
+    };
+    if model.trim().is_empty() {
+        print_llm_usage(app_name);
+        return 2;
+    }
+    if let Err(e) = set_state_path(
+        "preferences.ollama_model",
+        Value::String(model.trim().to_string()),
+    ) {
+        crate::cx_eprintln!("cxrs llm set-model: {e}");
+        return 1;
+    }
+    println!("ok");
+    println!("ollama_model: {}", model.trim());
+    state_cache_clear();
+    emit_quota_probe_notice("ollama", Some(model.trim()));
+    0
+}
+
+/// `llm models [--json]`: local Ollama models from the API; `*` marks the
+/// configured model.
+fn llm_models(app_name: &str, args: &[String]) -> i32 {
+    let json_out = match &args[1..] {
+        [] => false,
+        [flag] if flag == "--json" => true,
+        _ => {
+            print_llm_usage(app_name);
+            return 2;
+        }
+    };
+    let models = match list_models() {
+        Ok(m) => m,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs llm models: {e}");
+            return 1;
+        }
+    };
+    let current = ollama_model_preference();
+    if json_out {
+        let doc = json!({"host": ollama_host(), "current": current, "models": models});
+        match serde_json::to_string_pretty(&doc) {
+            Ok(s) => println!("{s}"),
+            Err(e) => {
+                crate::cx_eprintln!("cxrs llm models: render failed: {e}");
+                return 1;
+            }
+        }
+        return 0;
+    }
+    if models.is_empty() {
+        println!("No local models (pull one with '{app_name} llm pull <model>').");
+        return 0;
+    }
+    let text = |m: &Value, k: &str| m.get(k).and_then(Value::as_str).unwrap_or("-").to_string();
+    println!(
+        "  {:<32} {:>10} {:>8} {:<10}",
+        "NAME", "SIZE", "PARAMS", "QUANT"
+    );
+    for m in &models {
+        let name = text(m, "name");
+        let size = m
+            .get("size_bytes")
+            .and_then(Value::as_u64)
+            .map(human_bytes)
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{} {:<32} {:>10} {:>8} {:<10}",
+            if name == current { "*" } else { " " },
+            name,
+            size,
+            text(m, "parameter_size"),
+            text(m, "quantization")
+        );
+    }
+    0
+}
+
+fn print_model_info(info: &OllamaModelInfo) {
+    let opt = |v: &Option<String>| v.clone().unwrap_or_else(|| "n/a".to_string());
+    println!("model: {}", info.name);
+    println!("family: {}", opt(&info.family));
+    println!("parameter_size: {}", opt(&info.parameter_size));
+    println!("quantization: {}", opt(&info.quantization));
+    println!(
+        "context_length: {}",
+        info.context_length
+            .map_or_else(|| "n/a".to_string(), |v| v.to_string())
+    );
+    println!(
+        "context_window: {} ({})",
+        info.context_window, info.context_source
+    );
+    println!("prompt_budget_tokens: {}", info.prompt_budget_tokens());
+}
+
+/// `llm pull <model>`: download through the Ollama API with progress on stderr.
+fn llm_pull(app_name: &str, args: &[String]) -> i32 {
+    let Some(model) = args.get(1).map(|s| s.trim()).filter(|s| !s.is_empty()) else {
+        print_llm_usage(app_name);
+        return 2;
+    };
+    match pull_model(model) {
+        Ok(info) => {
+            println!("ok");
+            print_model_info(&info);
+            0
+        }
+        Err(e) => {
+            crate::cx_eprintln!("cxrs llm pull: {e}");
+            1
+        }
+    }
+}
+
+/// `llm inspect [model] [--json]`: context window, size and quantization of a
+/// local model (default: the configured one); refreshes the cached window.
+fn llm_inspect(app_name: &str, args: &[String]) -> i32 {
+    let json_out = args.iter().any(|a| a == "--json");
+    let rest: Vec<&String> = args[1..].iter().filter(|a| *a != "--json").collect();
+    let model = match rest.as_slice() {
+        [] => ollama_model_preference(),
+        [m] => m.trim().to_string(),
+        _ => {
+            print_llm_usage(app_name);
+            return 2;
+        }
+    };
+    if model.is_empty() {
+        crate::cx_eprintln!(
+            "cxrs llm inspect: no model given and none configured (set CX_OLLAMA_MODEL)"
+        );
+        return 2;
+    }
+    let info = match inspect_model(&model) {
+        Ok(info) => info,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs llm inspect: {e}");
+            return 1;
+        }
+    };
+    if json_out {
+        match serde_json::to_string_pretty(&info.to_json()) {
+            Ok(s) => println!("{s}"),
+            Err(e) => {
+                crate::cx_eprintln!("cxrs llm inspect: render failed: {e}");
+                return 1;
+            }
+        }
+    } else {
+        print_model_info(&info);
+    }
+    0
+}
+
+fn llm_clear_model() -> i32 {
+    if let Err(e) = set_state_path("preferences.ollama_model", Value::Null) {
+        crate::cx_eprintln!("cxrs llm clear-model: {e}");
+        return 1;
+    }
+    println!("ok");
+    println!("ollama_model: <unset>");
+    0
+}
+
+pub fn cmd_llm(app_name: &str, args: &[String]) -> i32 {
+    match args.first().map(String::as_str).unwrap_or("show") {
+        "show" => llm_show(),
+        "use" => llm_use(app_name, args),
+        "unset" => llm_unset(app_name, args),
+        "set-backend" => llm_set_backend(app_name, args),
+        "set-model" => llm_set_model(app_name, args),
+        "clear-model" => llm_clear_model(),
+        "models" => llm_models(app_name, args),
+        "pull" => llm_pull(app_name, args),
+        "inspect" => llm_inspect(app_name, args),
+        other => {
+            crate::cx_eprintln!("{app_name} llm: unknown subcommand '{other}'");
+            print_llm_usage(app_name);
+            2
+        }
+    }
+}
@@ -182,9 +182,11 @@ pub fn cmd_logs(app_name: &str, args: &[String]) -> i32 {
         "validate" => handle_validate(app_name, args),
         "migrate" => handle_migrate(app_name, args),
         "stats" => crate::logs_stats::handle_stats(app_name, args),
+        "query" => crate::logs_query::handle_query(args),
+        "failures" => crate::logs_failures::handle_failures(args),
         other => {
             crate::cx_eprintln!(
-                "Usage: {app_name} logs <validate|migrate|stats> (unknown subcommand: {other})"
+                "Usage: {app_name} logs <validate|migrate|stats|query|failures> (unknown subcommand: {other})"
             );
             2
         }
@@ -0,0 +1,261 @@
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+use crate::contract_versions::LOGS_FAILURES_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, print_runtime_error, print_usage_error};
+use crate::logs::load_runs;
+use crate::paths::resolve_log_file;
+use crate::quarantine::read_quarantine_record;
+use crate::schema::json_value_end;
+use crate::types::RunEntry;
+
+const USAGE: &str = "logs failures [N] [--json]";
+const DEFAULT_WINDOW: usize = 200;
+const SAMPLE_IDS: usize = 3;
+
+/// Shape of a raw response that failed schema validation.
+fn fingerprint(raw: &str) -> &'static str {
+    let t = raw.trim();
+    if t.is_empty() {
+        return "empty response";
+    }
+    if t.starts_with("```") {
+        return "markdown fence wrapped JSON";
+    }
+    if serde_json::from_str::<Value>(t).is_ok() {
+        return "valid JSON, schema mismatch";
+    }
+    let Some(start) = t.find(['{', '[']) else {
+        return "no JSON";
+    };
+    match json_value_end(&t[start..]) {
+        None => "truncated",
+        Some(_) if start > 0 => "leading prose",
+        Some(end) if !t[start + end..].trim().is_empty() => "trailing prose",
+        Some(_) => "malformed JSON",
+    }
+}
+
+/// `invalid JSON: expected value at line 1` -> `invalid JSON`; the detail
+/// varies per response and would split every cluster.
+fn reason_class(reason: &str) -> String {
+    reason
+        .split(':')
+        .next()
+        .unwrap_or(reason)
+        .trim()
+        .to_string()
+}
+
+#[derive(Default)]
+struct Cluster {
+    count: usize,
+    older: usize,
+    newer: usize,
+    first_ts: Option<String>,
+    last_ts: Option<String>,
+    quarantine_ids: Vec<String>,
+}
+
+impl Cluster {
+    fn trend(&self) -> &'static str {
+        match self.newer.cmp(&self.older) {
+            std::cmp::Ordering::Greater => "rising",
+            std::cmp::Ordering::Less => "falling",
+            std::cmp::Ordering::Equal => "steady",
+        }
+    }
+}
+
+type ClusterKey = (String, String, &'static str);
+
+fn is_schema_failure(r: &RunEntry) -> bool {
+    r.schema_valid == Some(false)
+}
+
+/// Group schema failures by tool, reason class and response fingerprint.
+/// The older/newer split is by position in the window, for the trend.
+fn cluster_failures(runs: &[RunEntry]) -> BTreeMap<ClusterKey, Cluster> {
+    let half = runs.len() / 2;
+    let mut out: BTreeMap<ClusterKey, Cluster> = BTreeMap::new();
+    for (idx, r) in runs.iter().enumerate() {
+        if !is_schema_failure(r) {
+            continue;
+        }
+        let raw = r
+            .quarantine_id
+            .as_deref()
+            .and_then(|id| read_quarantine_record(id).ok())
+            .map(|rec| rec.raw_response);
+        let print = raw
+            .as_deref()
+            .map_or("raw response unavailable", fingerprint);
+        let key = (
+            r.tool.clone().unwrap_or_else(|| "unknown".to_string()),
+            reason_class(r.schema_reason.as_deref().unwrap_or("unknown")),
+            print,
+        );
+        let c = out.entry(key).or_default();
+        c.count += 1;
+        if idx < half {
+            c.older += 1;
+        } else {
+            c.newer += 1;
+        }
+        if let Some(ts) = &r.ts {
+            if c.first_ts.is_none() {
+                c.first_ts = Some(ts.clone());
+            }
+            c.last_ts = Some(ts.clone());
+        }
+        if let Some(id) = &r.quarantine_id {
+            c.quarantine_ids.insert(0, id.clone());
+            c.quarantine_ids.truncate(SAMPLE_IDS);
+        }
+    }
+    out
+}
+
+/// Biggest cluster first; ties go to the one seen most recently.
+fn ranked(clusters: &BTreeMap<ClusterKey, Cluster>) -> Vec<(&ClusterKey, &Cluster)> {
+    let mut v: Vec<_> = clusters.iter().collect();
+    v.sort_by(|a, b| {
+        b.1.count
+            .cmp(&a.1.count)
+            .then_with(|| b.1.last_ts.cmp(&a.1.last_ts))
+            .then_with(|| a.0.cmp(b.0))
+    });
+    v
+}
+
+fn parse_args(args: &[String]) -> Result<(usize, bool), ()> {
+    let mut n = DEFAULT_WINDOW;
+    let mut json_out = false;
+    for a in args {
+        match a.as_str() {
+            "--json" => json_out = true,
+            v => match v.parse::<usize>() {
+                Ok(v) if v > 0 => n = v,
+                _ => return Err(()),
+            },
+        }
+    }
+    Ok((n, json_out))
+}
+
+fn print_report(window: usize, failures: usize, ranked: &[(&ClusterKey, &Cluster)]) {
+    println!("== cxrs logs failures ==");
+    println!("window: {window} run(s)");
+    println!("schema_failures: {failures}");
+    if ranked.is_empty() {
+        println!("clusters: 0");
+        return;
+    }
+    println!("clusters: {}", ranked.len());
+    for ((tool, reason, print), c) in ranked {
+        println!(
+            "- {}x {tool} | {reason} | {print} | trend: {} ({} -> {}) | last: {}",
+            c.count,
+            c.trend(),
+            c.older,
+            c.newer,
+            c.last_ts.as_deref().unwrap_or("n/a")
+        );
+        if !c.quarantine_ids.is_empty() {
+            println!("  quarantine: {}", c.quarantine_ids.join(", "));
+        }
+    }
+    if let Some(((tool, _, print), c)) = ranked.first() {
+        println!("fix_first: {tool} ({print})");
+        if let Some(id) = c.quarantine_ids.first() {
+            println!("next: cxrs quarantine show {id}");
+        }
+    }
+}
+
+/// `logs failures [N]`: cluster the schema failures in the last N runs so the
+/// noisiest prompt/response shape is fixed first.
+pub fn handle_failures(args: &[String]) -> i32 {
+    let Ok((n, json_out)) = parse_args(args.get(1..).unwrap_or_default()) else {
+        return print_usage_error("logs", USAGE);
+    };
+    let Some(log_file) = resolve_log_file() else {
+        return print_runtime_error("logs failures", "unable to resolve log file");
+    };
+    let runs = if log_file.exists() {
+        match load_runs(&log_file, n) {
+            Ok(v) => v,
+            Err(e) => return print_runtime_error("logs failures", &e),
+        }
+    } else {
+        Vec::new()
+    };
+    let clusters = cluster_failures(&runs);
+    let ranked = ranked(&clusters);
+    let failures: usize = clusters.values().map(|c| c.count).sum();
+    if !json_out {
+        print_report(runs.len(), failures, &ranked);
+        return EXIT_OK;
+    }
+    let doc = json!({
+        "contract_version": LOGS_FAILURES_JSON_CONTRACT_VERSION,
+        "log_file": log_file.display().to_string(),
+        "window": runs.len(),
+        "schema_failures": failures,
+        "clusters": cluster_rows(&ranked),
+    });
+    match serde_json::to_string_pretty(&doc) {
+        Ok(s) => println!("{s}"),
+        Err(e) => return print_runtime_error("logs failures", &e.to_string()),
+    }
+    EXIT_OK
+}
+
+fn cluster_rows(ranked: &[(&ClusterKey, &Cluster)]) -> Vec<Value> {
+    ranked
+        .iter()
+        .map(|((tool, reason, print), c)| {
+            json!({
+                "tool": tool,
+                "reason": reason,
+                "fingerprint": print,
+                "count": c.count,
+                "older_half": c.older,
+                "newer_half": c.newer,
+                "trend": c.trend(),
+                "first_ts": c.first_ts,
+                "last_ts": c.last_ts,
+                "quarantine_ids": c.quarantine_ids,
+            })
+        })
+        .collect()
+}
+
+/// Schema-failure clusters of `runs`, biggest first, as `logs failures --json` rows.
+pub fn failure_cluster_rows(runs: &[RunEntry]) -> Vec<Value> {
+    cluster_rows(&ranked(&cluster_failures(runs)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprints_name_common_response_shapes() {
+        assert_eq!(fingerprint("  "), "empty response");
+        assert_eq!(
+            fingerprint("```json\n{\"a\":1}\n```"),
+            "markdown fence wrapped JSON"
+        );
+        assert_eq!(fingerprint("{\"a\":1}"), "valid JSON, schema mismatch");
+        assert_eq!(fingerprint("{\"a\":1}\nHope this helps!"), "trailing prose");
+        assert_eq!(fingerprint("Sure! {\"a\":1}"), "leading prose");
+        assert_eq!(fingerprint("{\"a\":\"x}\", \"b\":["), "truncated");
+        assert_eq!(fingerprint("I cannot do that."), "no JSON");
+        assert_eq!(fingerprint("{a:1}"), "malformed JSON");
+        assert_eq!(
+            reason_class("invalid JSON: expected value at line 1 column 1"),
+            "invalid JSON"
+        );
+    }
+}
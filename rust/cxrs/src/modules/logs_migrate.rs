@@ -1,6 +1,7 @@
 use crate::error::{CxError, CxResult};
 use crate::paths::ensure_parent_dir;
 use crate::provider_adapter::normalize_provider_status;
+use crate::schema::schema_name_for_tool;
 use crate::types::ExecutionLog;
 use crate::util::{IfEmpty, sha256_hex};
 use serde_json::Value;
@@ -105,6 +106,9 @@ fn normalize_execution_log_row(
         ..Default::default()
     };
     fill_optional_fields(obj, &mut row);
+    if row.schema_enforced && row.schema_name.is_none() {
+        row.schema_name = schema_name_for_tool(&row.tool);
+    }
     row
 }
 
@@ -118,12 +122,59 @@ fn fill_optional_fields(obj: &serde_json::Map<String, Value>, row: &mut Executio
     row.http_provider_format = get_opt_str(obj, "http_provider_format");
     row.http_parser_mode = get_opt_str(obj, "http_parser_mode");
     row.capture_provider = get_opt_str(obj, "capture_provider");
+    row.reducer = get_opt_str(obj, "reducer");
+    row.chunk_strategy = get_opt_str(obj, "chunk_strategy");
+    row.chunk_count = get_opt_u64(obj, "chunk_count");
+    row.redactions_applied = get_opt_u64(obj, "redactions_applied");
+    row.stdout_len_raw = get_opt_u64(obj, "stdout_len_raw");
+    row.stdout_len_clipped = get_opt_u64(obj, "stdout_len_clipped");
+    row.stdout_lines_raw = get_opt_u64(obj, "stdout_lines_raw");
+    row.stdout_lines_clipped = get_opt_u64(obj, "stdout_lines_clipped");
+    row.stderr_len_raw = get_opt_u64(obj, "stderr_len_raw");
+    row.stderr_len_clipped = get_opt_u64(obj, "stderr_len_clipped");
+    row.stderr_lines_raw = get_opt_u64(obj, "stderr_lines_raw");
+    row.stderr_lines_clipped = get_opt_u64(obj, "stderr_lines_clipped");
+    row.ansi_sequences_stripped = get_opt_u64(obj, "ansi_sequences_stripped");
+    row.binary_output = get_opt_bool(obj, "binary_output");
+    row.parent_execution_id = get_opt_str(obj, "parent_execution_id");
+    row.chunk_index = get_opt_u64(obj, "chunk_index");
     row.duration_ms = get_opt_u64(obj, "duration_ms");
     row.schema_name = get_opt_str(obj, "schema_name");
     row.schema_reason = get_opt_str(obj, "schema_reason");
     row.quarantine_id = get_opt_str(obj, "quarantine_id");
+    row.repair_applied = get_opt_bool(obj, "repair_applied");
+    row.repair_steps = obj.get("repair_steps").and_then(Value::as_array).map(|v| {
+        v.iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect()
+    });
     row.task_id = get_opt_str(obj, "task_id");
     row.task_parent_id = get_opt_str(obj, "task_parent_id");
+    row.rerun_of = get_opt_str(obj, "rerun_of");
+    row.pipeline_id = get_opt_str(obj, "pipeline_id");
+    row.codex_args = obj.get("codex_args").and_then(Value::as_array).map(|v| {
+        v.iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect()
+    });
+    row.codex_profile = get_opt_str(obj, "codex_profile");
+    row.failover_count = get_opt_u64(obj, "failover_count");
+    row.failover_reasons = obj
+        .get("failover_reasons")
+        .and_then(Value::as_array)
+        .map(|v| {
+            v.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        });
+    row.git_branch = get_opt_str(obj, "git_branch");
+    row.git_head = get_opt_str(obj, "git_head");
+    row.vcs = get_opt_str(obj, "vcs");
+    row.scope_source = get_opt_str(obj, "scope_source");
+    row.codex_root = get_opt_str(obj, "codex_root");
     row.input_tokens = get_opt_u64(obj, "input_tokens");
     row.cached_input_tokens = get_opt_u64(obj, "cached_input_tokens");
     row.effective_input_tokens = get_opt_u64(obj, "effective_input_tokens");
@@ -139,6 +190,13 @@ fn fill_optional_fields(obj: &serde_json::Map<String, Value>, row: &mut Executio
     row.budget_lines = get_opt_u64(obj, "budget_lines");
     row.clip_mode = get_opt_str(obj, "clip_mode");
     row.clip_footer = get_opt_bool(obj, "clip_footer");
+    row.system_output_tokens_raw = get_opt_u64(obj, "system_output_tokens_raw");
+    row.system_output_tokens_clipped = get_opt_u64(obj, "system_output_tokens_clipped");
+    row.budget_tokens = get_opt_u64(obj, "budget_tokens");
+    row.prompt_tokens_est = get_opt_u64(obj, "prompt_tokens_est");
+    row.token_estimator = get_opt_str(obj, "token_estimator");
+    row.context_window = get_opt_u64(obj, "context_window");
+    row.context_guard = get_opt_str(obj, "context_guard");
     row.rtk_used = get_opt_bool(obj, "rtk_used");
     row.prompt_sha256 = get_opt_str(obj, "prompt_sha256");
     row.schema_prompt_sha256 = get_opt_str(obj, "schema_prompt_sha256");
@@ -148,8 +206,13 @@ fn fill_optional_fields(obj: &serde_json::Map<String, Value>, row: &mut Executio
     row.timeout_secs = get_opt_u64(obj, "timeout_secs");
     row.command_label = get_opt_str(obj, "command_label");
     row.prompt_preview = get_opt_str(obj, "prompt_preview");
+    row.prompt_template = get_opt_str(obj, "prompt_template");
+    row.prompt_template_source = get_opt_str(obj, "prompt_template_source");
+    row.prompt_template_sha256 = get_opt_str(obj, "prompt_template_sha256");
     row.policy_blocked = get_opt_bool(obj, "policy_blocked");
     row.policy_reason = get_opt_str(obj, "policy_reason");
+    row.fix_decisions = obj.get("fix_decisions").filter(|v| v.is_array()).cloned();
+    row.pack_manifest = obj.get("pack_manifest").filter(|v| v.is_object()).cloned();
     row.retry_attempt = obj
         .get("retry_attempt")
         .and_then(Value::as_u64)
@@ -0,0 +1,521 @@
+use serde_json::{Map, Value, json};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+use crate::config_layers::edit_distance;
+use crate::contract_versions::LOGS_QUERY_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, format_error, print_runtime_error, print_usage_error};
+use crate::logs::load_values;
+use crate::paths::resolve_log_file;
+use crate::state::value_at_path;
+use crate::types::ExecutionLog;
+
+const USAGE: &str = "logs query '<filter>' [--last N] [--fields a,b,c] [--json]";
+const DEFAULT_LAST: usize = 20;
+const BASE_FIELDS: &[&str] = &["ts", "execution_id", "tool"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+/// Parsed filter; `field` may be a dotted path (`converge_votes.decision_source`).
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Cmp {
+        field: String,
+        op: Op,
+        value: String,
+    },
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+}
+
+fn ends_word(chars: &[char], i: usize) -> bool {
+    let c = chars[i];
+    c.is_whitespace() || "()\"'=<>~".contains(c) || (c == '!' && chars.get(i + 1) == Some(&'='))
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                out.push(Token::Open);
+                i += 1;
+            }
+            ')' => {
+                out.push(Token::Close);
+                i += 1;
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|ch| *ch == c)
+                    .ok_or_else(|| format!("unterminated quote at position {}", i + 1))?;
+                out.push(Token::Quoted(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            '!' if next == Some('=') => {
+                out.push(Token::Op(Op::Ne));
+                i += 2;
+            }
+            '>' | '<' => {
+                let eq = next == Some('=');
+                out.push(Token::Op(match (c, eq) {
+                    ('>', true) => Op::Ge,
+                    ('>', false) => Op::Gt,
+                    (_, true) => Op::Le,
+                    _ => Op::Lt,
+                }));
+                i += if eq { 2 } else { 1 };
+            }
+            '=' => {
+                out.push(Token::Op(Op::Eq));
+                i += if next == Some('=') { 2 } else { 1 };
+            }
+            '~' => {
+                out.push(Token::Op(Op::Contains));
+                i += 1;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && !ends_word(&chars, i) {
+                    i += 1;
+                }
+                out.push(Token::Word(chars[start..i].iter().collect()));
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn keyword(tok: Option<&Token>, kw: &str) -> bool {
+    matches!(tok, Some(Token::Word(w)) if w.eq_ignore_ascii_case(kw))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    fn or_expr(&mut self) -> Result<Filter, String> {
+        let mut left = self.and_expr()?;
+        while keyword(self.peek(), "OR") {
+            self.pos += 1;
+            left = Filter::Or(Box::new(left), Box::new(self.and_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<Filter, String> {
+        let mut left = self.not_expr()?;
+        while keyword(self.peek(), "AND") {
+            self.pos += 1;
+            left = Filter::And(Box::new(left), Box::new(self.not_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn not_expr(&mut self) -> Result<Filter, String> {
+        if keyword(self.peek(), "NOT") {
+            self.pos += 1;
+            return Ok(Filter::Not(Box::new(self.not_expr()?)));
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let inner = self.or_expr()?;
+            if self.next() != Some(Token::Close) {
+                return Err("expected ')'".to_string());
+            }
+            return Ok(inner);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Filter, String> {
+        let field = match self.next() {
+            Some(Token::Word(w)) => w,
+            Some(other) => return Err(format!("expected a field name, got {other:?}")),
+            None => return Err("expected a field name at end of filter".to_string()),
+        };
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            _ => {
+                return Err(format!(
+                    "expected an operator (= != > >= < <= ~) after '{field}'"
+                ));
+            }
+        };
+        let value = match self.next() {
+            Some(Token::Word(w) | Token::Quoted(w)) => w,
+            _ => return Err(format!("expected a value after '{field}'")),
+        };
+        Ok(Filter::Cmp { field, op, value })
+    }
+}
+
+/// Parse `tool=cxdiffsum AND (duration_ms>10000 OR NOT schema_ok=true)`.
+/// `AND` binds tighter than `OR`; keywords are case-insensitive.
+fn parse_filter(src: &str) -> Result<Filter, String> {
+    let mut p = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+    };
+    if p.tokens.is_empty() {
+        return Err("empty filter".to_string());
+    }
+    let filter = p.or_expr()?;
+    if let Some(tok) = p.peek() {
+        return Err(format!("unexpected {tok:?} (join conditions with AND/OR)"));
+    }
+    Ok(filter)
+}
+
+/// `ts` falls back to `timestamp` (and back) so both log generations match.
+fn field_value<'a>(row: &'a Value, field: &str) -> Option<&'a Value> {
+    let alias = match field {
+        "ts" => Some("timestamp"),
+        "timestamp" => Some("ts"),
+        _ => None,
+    };
+    value_at_path(row, field)
+        .filter(|v| !v.is_null())
+        .or_else(|| alias.and_then(|a| row.get(a)).filter(|v| !v.is_null()))
+}
+
+/// Numbers compare numerically, everything else as text; a missing field
+/// equals only `null`.
+fn compare(actual: Option<&Value>, op: Op, want: &str) -> bool {
+    let Some(actual) = actual else {
+        return match op {
+            Op::Eq => want == "null",
+            Op::Ne => want != "null",
+            _ => false,
+        };
+    };
+    let text = match actual {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if op == Op::Contains {
+        return text.contains(want);
+    }
+    let ord = match (actual.as_f64(), want.parse::<f64>().ok()) {
+        (Some(a), Some(b)) => a.partial_cmp(&b),
+        _ => Some(text.as_str().cmp(want)),
+    };
+    let Some(ord) = ord else {
+        return false;
+    };
+    match op {
+        Op::Eq => ord == Ordering::Equal,
+        Op::Ne => ord != Ordering::Equal,
+        Op::Gt => ord == Ordering::Greater,
+        Op::Ge => ord != Ordering::Less,
+        Op::Lt => ord == Ordering::Less,
+        Op::Le => ord != Ordering::Greater,
+        Op::Contains => unreachable!(),
+    }
+}
+
+fn matches(filter: &Filter, row: &Value) -> bool {
+    match filter {
+        Filter::Cmp { field, op, value } => compare(field_value(row, field), *op, value),
+        Filter::And(a, b) => matches(a, row) && matches(b, row),
+        Filter::Or(a, b) => matches(a, row) || matches(b, row),
+        Filter::Not(a) => !matches(a, row),
+    }
+}
+
+fn filter_fields(filter: &Filter, out: &mut Vec<String>) {
+    match filter {
+        Filter::Cmp { field, .. } => {
+            if !out.contains(field) {
+                out.push(field.clone());
+            }
+        }
+        Filter::And(a, b) | Filter::Or(a, b) => {
+            filter_fields(a, out);
+            filter_fields(b, out);
+        }
+        Filter::Not(a) => filter_fields(a, out),
+    }
+}
+
+/// Run-log fields: the current row contract plus any key seen in the log.
+fn known_fields(rows: &[Value]) -> BTreeSet<String> {
+    let mut out: BTreeSet<String> = serde_json::to_value(ExecutionLog::default())
+        .ok()
+        .and_then(|v| v.as_object().map(|o| o.keys().cloned().collect()))
+        .unwrap_or_default();
+    for row in rows {
+        if let Some(obj) = row.as_object() {
+            out.extend(obj.keys().cloned());
+        }
+    }
+    out
+}
+
+fn check_fields(fields: &[String], known: &BTreeSet<String>) -> Result<(), String> {
+    for field in fields {
+        let top = field.split('.').next().unwrap_or_default();
+        if known.contains(top) {
+            continue;
+        }
+        let mut ranked: Vec<(usize, &str)> = known
+            .iter()
+            .map(|k| (edit_distance(top, k), k.as_str()))
+            .filter(|(d, k)| *d <= 2 || k.starts_with(top))
+            .collect();
+        ranked.sort();
+        let close: Vec<&str> = ranked.into_iter().take(3).map(|(_, k)| k).collect();
+        return Err(if close.is_empty() {
+            format!("unknown field '{field}'")
+        } else {
+            format!(
+                "unknown field '{field}' (did you mean: {}?)",
+                close.join(", ")
+            )
+        });
+    }
+    Ok(())
+}
+
+struct QueryArgs {
+    filter: String,
+    last: usize,
+    fields: Option<Vec<String>>,
+    json_out: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<QueryArgs, String> {
+    let mut filter: Option<String> = None;
+    let mut last = DEFAULT_LAST;
+    let mut fields = None;
+    let mut json_out = false;
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--json" => json_out = true,
+            "--last" => {
+                last = it
+                    .next()
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .filter(|n| *n > 0)
+                    .ok_or("--last requires a positive number")?;
+            }
+            "--fields" => {
+                let list: Vec<String> = it
+                    .next()
+                    .ok_or("--fields requires a comma-separated list")?
+                    .split(',')
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty())
+                    .collect();
+                if list.is_empty() {
+                    return Err("--fields requires a comma-separated list".to_string());
+                }
+                fields = Some(list);
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown flag '{flag}'")),
+            expr if filter.is_none() => filter = Some(expr.to_string()),
+            extra => return Err(format!("unexpected argument '{extra}' (quote the filter)")),
+        }
+    }
+    Ok(QueryArgs {
+        filter: filter.ok_or("missing filter")?,
+        last,
+        fields,
+        json_out,
+    })
+}
+
+fn project(row: &Value, fields: &[String]) -> Value {
+    let mut out = Map::new();
+    for f in fields {
+        out.insert(
+            f.clone(),
+            field_value(row, f).cloned().unwrap_or(Value::Null),
+        );
+    }
+    Value::Object(out)
+}
+
+fn cell(v: &Value) -> String {
+    match v {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn print_table(fields: &[String], rows: &[Value]) {
+    let table: Vec<Vec<String>> = rows
+        .iter()
+        .map(|r| fields.iter().map(|f| cell(&r[f.as_str()])).collect())
+        .collect();
+    let widths: Vec<usize> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            table
+                .iter()
+                .map(|r| r[i].chars().count())
+                .fold(f.len(), usize::max)
+        })
+        .collect();
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{c:<w$}"))
+            .collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+    line(fields.iter().map(String::as_str).collect());
+    for r in &table {
+        line(r.iter().map(String::as_str).collect());
+    }
+}
+
+/// `logs query '<filter>'`: filter run-log rows with a small
+/// `field op value [AND|OR ...]` language and print the newest matches.
+pub fn handle_query(args: &[String]) -> i32 {
+    let q = match parse_args(args.get(1..).unwrap_or_default()) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("logs query", &e));
+            return print_usage_error("logs", USAGE);
+        }
+    };
+    let filter = match parse_filter(&q.filter) {
+        Ok(f) => f,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("logs query", &e));
+            return print_usage_error("logs", USAGE);
+        }
+    };
+    let Some(log_file) = resolve_log_file() else {
+        return print_runtime_error("logs query", "unable to resolve log file");
+    };
+    let rows = if log_file.exists() {
+        match load_values(&log_file, 0) {
+            Ok(v) => v,
+            Err(e) => return print_runtime_error("logs query", &e),
+        }
+    } else {
+        Vec::new()
+    };
+    let mut referenced = Vec::new();
+    filter_fields(&filter, &mut referenced);
+    referenced.extend(q.fields.iter().flatten().cloned());
+    if let Err(e) = check_fields(&referenced, &known_fields(&rows)) {
+        crate::cx_eprintln!("{}", format_error("logs query", &e));
+        return print_usage_error("logs", USAGE);
+    }
+    let matched: Vec<&Value> = rows.iter().filter(|r| matches(&filter, r)).collect();
+    let shown = &matched[matched.len().saturating_sub(q.last)..];
+    if q.json_out {
+        let out: Vec<Value> = match &q.fields {
+            Some(f) => shown.iter().map(|r| project(r, f)).collect(),
+            None => shown.iter().map(|r| (*r).clone()).collect(),
+        };
+        let doc = json!({
+            "contract_version": LOGS_QUERY_JSON_CONTRACT_VERSION,
+            "filter": q.filter,
+            "log_file": log_file.display().to_string(),
+            "scanned": rows.len(),
+            "matched": matched.len(),
+            "rows": out,
+        });
+        match serde_json::to_string_pretty(&doc) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return print_runtime_error("logs query", &e.to_string()),
+        }
+        return EXIT_OK;
+    }
+    let fields = q.fields.unwrap_or_else(|| {
+        let mut f: Vec<String> = BASE_FIELDS.iter().map(|s| s.to_string()).collect();
+        for r in referenced {
+            if !f.contains(&r) {
+                f.push(r);
+            }
+        }
+        f
+    });
+    let projected: Vec<Value> = shown.iter().map(|r| project(r, &fields)).collect();
+    if !projected.is_empty() {
+        print_table(&fields, &projected);
+    }
+    println!(
+        "{} of {} row(s) matched; showing {}",
+        matched.len(),
+        rows.len(),
+        projected.len()
+    );
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_handles_precedence_types_and_missing() {
+        let row = json!({
+            "tool": "cxdiffsum", "duration_ms": 12000, "schema_ok": false,
+            "ts": "2026-03-01T10:00:00Z", "llm_model": null,
+            "converge_votes": {"decision_source": "model_judge"}
+        });
+        let hit = |src: &str| matches(&parse_filter(src).expect(src), &row);
+        assert!(hit(
+            "tool=cxdiffsum AND duration_ms>10000 AND schema_ok=false"
+        ));
+        assert!(hit("tool = cxo OR duration_ms >= 12000"));
+        assert!(!hit("tool=cxo OR tool=cxj AND duration_ms>1"));
+        assert!(hit("NOT (tool=cxo OR schema_ok=true)"));
+        assert!(hit("duration_ms<=12000 and ts>2026-02-28"));
+        assert!(hit("llm_model=null AND retry_attempt!=2"));
+        assert!(!hit("retry_attempt<5"));
+        assert!(hit(
+            "tool~diff AND converge_votes.decision_source='model_judge'"
+        ));
+        assert!(hit("timestamp>=\"2026-03-01\""));
+        for bad in ["", "tool", "tool=", "tool=x AND", "(tool=x", "tool=x y=z"] {
+            assert!(parse_filter(bad).is_err(), "{bad}");
+        }
+    }
+}
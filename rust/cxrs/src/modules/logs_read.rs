@@ -1,6 +1,8 @@
 use crate::error::{CxError, CxResult};
 use crate::log_contract::REQUIRED_STRICT_FIELDS;
+use crate::schema::schema_name_for_tool;
 use crate::types::RunEntry;
+use crate::verbosity::warn;
 use serde_json::Value;
 use std::collections::BTreeSet;
 use std::fs::File;
@@ -133,6 +135,28 @@ fn validate_required_fields(
                 .push(format!("line {line_no}: missing required field '{k}'"));
         }
     }
+    check_schema_mapping(obj, line_no, out);
+}
+
+/// Schema-enforced rows should name the schema the registry maps their tool to.
+fn check_schema_mapping(
+    obj: &serde_json::Map<String, Value>,
+    line_no: usize,
+    out: &mut LogValidateOutcome,
+) {
+    if obj.get("schema_enforced").and_then(Value::as_bool) != Some(true)
+        || obj.get("schema_name").is_some_and(|v| !v.is_null())
+    {
+        return;
+    }
+    let Some(tool) = obj.get("tool").and_then(Value::as_str) else {
+        return;
+    };
+    if let Some(expected) = schema_name_for_tool(tool) {
+        out.issues.push(format!(
+            "line {line_no}: schema-enforced '{tool}' row missing schema_name (registry maps it to {expected})"
+        ));
+    }
 }
 
 pub fn load_runs(log_file: &Path, limit: usize) -> Result<Vec<RunEntry>, String> {
@@ -275,10 +299,10 @@ fn maybe_warn_invalid_lines(log_file: &Path, invalid: usize, sample: Option<Stri
     if RUNS_PARSE_WARNED.swap(true, Ordering::SeqCst) {
         return;
     }
-    crate::cx_eprintln!(
+    warn(&format!(
         "cxrs: warning: skipped {} invalid JSON lines in {} (sample: {}). Run 'cx logs validate' for details.",
         invalid,
         log_file.display(),
         sample.unwrap_or_else(|| "n/a".to_string())
-    );
+    ));
 }
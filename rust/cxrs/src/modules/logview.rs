@@ -2,9 +2,13 @@ use serde_json::Value;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+use crate::budget_suggest::cmd_budget_suggest;
 use crate::config::app_config;
+use crate::error::print_usage_error;
 use crate::logs::load_runs;
 use crate::paths::resolve_log_file;
+use crate::state::{read_state_value, value_at_path};
+use crate::token_estimate::TokenizerKind;
 
 fn show_field<T: ToString>(label: &str, value: Option<T>) {
     match value {
@@ -13,7 +17,41 @@ fn show_field<T: ToString>(label: &str, value: Option<T>) {
     }
 }
 
-pub fn cmd_budget() -> i32 {
+fn budget_overrides_label() -> String {
+    let state = read_state_value();
+    let Some(tools) = state
+        .as_ref()
+        .and_then(|s| value_at_path(s, "budget_overrides"))
+        .and_then(Value::as_object)
+        .filter(|m| !m.is_empty())
+    else {
+        return "none".to_string();
+    };
+    tools
+        .iter()
+        .map(|(tool, v)| {
+            let field = |k: &str| v.get(k).map_or("-".to_string(), Value::to_string);
+            format!(
+                "{tool}(chars={},lines={})",
+                field("budget_chars"),
+                field("budget_lines")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn cmd_budget(args: &[String]) -> i32 {
+    match args.first().map(String::as_str) {
+        None => {}
+        Some("suggest") => return cmd_budget_suggest(&args[1..]),
+        Some(_) => {
+            return print_usage_error(
+                "budget",
+                "budget | budget suggest [N] [--coverage PCT] [--apply] [--json]",
+            );
+        }
+    }
     let Some(log_file) = resolve_log_file() else {
         crate::cx_eprintln!("cxrs: unable to resolve log file");
         return 1;
@@ -22,18 +60,28 @@ pub fn cmd_budget() -> i32 {
     println!("== cxbudget ==");
     println!("CX_CONTEXT_BUDGET_CHARS={}", cfg.budget_chars);
     println!("CX_CONTEXT_BUDGET_LINES={}", cfg.budget_lines);
+    println!("CX_CONTEXT_BUDGET_TOKENS={}", cfg.budget_tokens);
+    println!(
+        "token_estimator: {}",
+        TokenizerKind::for_backend(&cfg.llm_backend).as_str()
+    );
     println!("CX_CONTEXT_CLIP_MODE={}", cfg.clip_mode);
     println!(
         "CX_CONTEXT_CLIP_FOOTER={}",
         if cfg.clip_footer { "1" } else { "0" }
     );
+    println!("CX_CHUNK_STRATEGY={}", cfg.chunk_strategy);
+    println!("CX_CHUNK_MAX={}", cfg.chunk_max);
+    println!("CX_REDACT={}", if cfg.redact { "1" } else { "0" });
+    println!("budget_overrides: {}", budget_overrides_label());
     println!("log_file: {}", log_file.display());
 
     if !log_file.exists() {
         return 0;
     }
-    let runs = load_runs(&log_file, 1).unwrap_or_default();
-    if let Some(last) = runs.last() {
+    // Map-reduce child rows trail their parent; report the parent run.
+    let runs = load_runs(&log_file, app_config().chunk_max + 1).unwrap_or_default();
+    if let Some(last) = runs.iter().rev().find(|r| r.parent_execution_id.is_none()) {
         println!();
         println!("Last run clip fields:");
         show_field("system_output_len_raw", last.system_output_len_raw);
@@ -54,10 +102,33 @@ pub fn cmd_budget() -> i32 {
         show_field("clipped", last.clipped);
         show_field("budget_chars", last.budget_chars);
         show_field("budget_lines", last.budget_lines);
+        show_field("system_output_tokens_raw", last.system_output_tokens_raw);
+        show_field(
+            "system_output_tokens_clipped",
+            last.system_output_tokens_clipped,
+        );
+        show_field("budget_tokens", last.budget_tokens);
+        show_field("prompt_tokens_est", last.prompt_tokens_est);
+        show_field("context_window", last.context_window);
+        show_field("context_guard", last.context_guard.clone());
         show_field("clip_mode", last.clip_mode.clone());
         show_field("clip_footer", last.clip_footer);
         show_field("rtk_used", last.rtk_used);
         show_field("capture_provider", last.capture_provider.clone());
+        show_field("reducer", last.reducer.clone());
+        show_field("chunk_strategy", last.chunk_strategy.clone());
+        show_field("chunk_count", last.chunk_count);
+        show_field("redactions_applied", last.redactions_applied);
+        show_field("stdout_len_raw", last.stdout_len_raw);
+        show_field("stdout_len_clipped", last.stdout_len_clipped);
+        show_field("stdout_lines_raw", last.stdout_lines_raw);
+        show_field("stdout_lines_clipped", last.stdout_lines_clipped);
+        show_field("stderr_len_raw", last.stderr_len_raw);
+        show_field("stderr_len_clipped", last.stderr_len_clipped);
+        show_field("stderr_lines_raw", last.stderr_lines_raw);
+        show_field("stderr_lines_clipped", last.stderr_lines_clipped);
+        show_field("ansi_sequences_stripped", last.ansi_sequences_stripped);
+        show_field("binary_output", last.binary_output);
     }
     0
 }
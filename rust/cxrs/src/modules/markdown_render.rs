@@ -0,0 +1,222 @@
+use regex::Regex;
+use std::io::{self, IsTerminal};
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
+use syntect::util::{LinesWithEndings, as_24_bit_terminal_escaped};
+
+use crate::config::app_config;
+
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const UNDERLINE: &str = "\x1b[4m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+const THEME: &str = "base16-ocean.dark";
+
+/// Render agent Markdown only when asked to (`CX_RENDER_MARKDOWN=1`, not
+/// `--plain`) and stdout is a terminal, so piped output stays byte-for-byte.
+pub fn should_render() -> bool {
+    app_config().render_markdown && io::stdout().is_terminal()
+}
+
+fn inline_patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            (r"`([^`]+)`", "\x1b[36m$1\x1b[39m"),
+            (r"\*\*([^*]+)\*\*", "\x1b[1m$1\x1b[22m"),
+            (r"__([^_]+)__", "\x1b[1m$1\x1b[22m"),
+        ]
+        .into_iter()
+        .filter_map(|(re, rep)| Regex::new(re).ok().map(|re| (re, rep)))
+        .collect()
+    })
+}
+
+/// Inline code and bold spans styled; returns the text and its visible width.
+fn inline(text: &str) -> (String, usize) {
+    let mut styled = text.to_string();
+    let mut visible = text.to_string();
+    for (re, rep) in inline_patterns() {
+        styled = re.replace_all(&styled, *rep).into_owned();
+        visible = re.replace_all(&visible, "$1").into_owned();
+    }
+    (styled, visible.chars().count())
+}
+
+fn highlight(lang: &str, code: &str) -> String {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    let syntaxes = SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines);
+    let themes = THEMES.get_or_init(ThemeSet::load_defaults);
+    let Some(theme) = themes.themes.get(THEME) else {
+        return code.to_string();
+    };
+    let syntax = syntaxes
+        .find_syntax_by_token(lang)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut h = HighlightLines::new(syntax, theme);
+    let mut out = String::new();
+    for line in LinesWithEndings::from(code) {
+        match h.highlight_line(line, syntaxes) {
+            Ok(ranges) => out.push_str(&as_24_bit_terminal_escaped(&ranges, false)),
+            Err(_) => out.push_str(line),
+        }
+    }
+    // Reset before the last newline so no stray empty line follows the block.
+    let newline = out.ends_with('\n');
+    if newline {
+        out.pop();
+    }
+    out.push_str(RESET);
+    if newline {
+        out.push('\n');
+    }
+    out
+}
+
+fn split_row(line: &str) -> Vec<String> {
+    let t = line.trim();
+    let t = t.strip_prefix('|').unwrap_or(t);
+    let t = t.strip_suffix('|').unwrap_or(t);
+    t.split('|').map(|c| c.trim().to_string()).collect()
+}
+
+fn is_separator_row(cells: &[String]) -> bool {
+    cells.iter().all(|c| {
+        let c = c.trim_matches(':');
+        !c.is_empty() && c.chars().all(|ch| ch == '-')
+    })
+}
+
+/// A pipe table as aligned box-drawn rows; the first row is the header.
+fn render_table(lines: &[&str]) -> String {
+    let rows: Vec<Vec<(String, usize)>> = lines
+        .iter()
+        .map(|l| split_row(l))
+        .filter(|cells| !is_separator_row(cells))
+        .map(|cells| cells.iter().map(|c| inline(c)).collect())
+        .collect();
+    let cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..cols)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|r| r.get(i))
+                .map(|c| c.1)
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut out = String::new();
+    for (n, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = (0..cols)
+            .map(|i| {
+                let (text, width) = row.get(i).cloned().unwrap_or_default();
+                let pad = " ".repeat(widths[i] - width);
+                if n == 0 {
+                    format!(" {BOLD}{text}{RESET}{pad} ")
+                } else {
+                    format!(" {text}{pad} ")
+                }
+            })
+            .collect();
+        out.push_str(&format!("│{}│\n", cells.join("│")));
+        if n == 0 && rows.len() > 1 {
+            let rule: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+            out.push_str(&format!("├{}┤\n", rule.join("┼")));
+        }
+    }
+    out
+}
+
+fn render_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+        let (text, _) = inline(trimmed[hashes..].trim());
+        let style = if hashes == 1 {
+            format!("{BOLD}{UNDERLINE}")
+        } else {
+            BOLD.to_string()
+        };
+        return format!("{style}{text}{RESET}");
+    }
+    if matches!(trimmed, "---" | "***" | "___") {
+        return format!("{DIM}{}{RESET}", "─".repeat(40));
+    }
+    if let Some(rest) = trimmed.strip_prefix("> ") {
+        return format!("{indent}{DIM}│{RESET} {}", inline(rest).0);
+    }
+    if let Some(rest) = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+    {
+        return format!("{indent}{CYAN}•{RESET} {}", inline(rest).0);
+    }
+    inline(line).0
+}
+
+/// Terminal rendering of Markdown: styled headings, box-drawn pipe tables,
+/// syntax-highlighted fenced code, bullets, quotes and inline bold/code.
+pub fn render_markdown(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            let end = lines[i + 1..]
+                .iter()
+                .position(|l| l.trim_start().starts_with("```"))
+                .map_or(lines.len(), |p| i + 1 + p);
+            let code: String = lines[i + 1..end].iter().map(|l| format!("{l}\n")).collect();
+            out.push_str(&highlight(lang.trim(), &code));
+            i = end + 1;
+            continue;
+        }
+        if trimmed.starts_with('|') {
+            let end = lines[i..]
+                .iter()
+                .position(|l| !l.trim_start().starts_with('|'))
+                .map_or(lines.len(), |p| i + p);
+            out.push_str(&render_table(&lines[i..end]));
+            i = end;
+            continue;
+        }
+        out.push_str(&render_line(line));
+        out.push('\n');
+        i += 1;
+    }
+    if !text.ends_with('\n') && out.ends_with('\n') {
+        out.pop();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_align_on_visible_width() {
+        let out = render_table(&["| name | n |", "|---|--:|", "| `cx` | 10 |"]);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "├──────┼────┤");
+        assert_eq!(lines[2], "│ \x1b[36mcx\x1b[39m   │ 10 │");
+    }
+
+    #[test]
+    fn headings_bullets_and_code_are_styled() {
+        let out = render_markdown("# Title\n- **a** item\n```rust\nfn main() {}\n```\ndone");
+        assert!(out.starts_with("\x1b[1m\x1b[4mTitle\x1b[0m\n"), "{out:?}");
+        assert!(out.contains("\x1b[36m•\x1b[0m \x1b[1ma\x1b[22m item\n"));
+        assert!(out.contains("\x1b[38;2;"), "code is highlighted: {out:?}");
+        assert!(!out.contains("```"));
+        assert!(out.ends_with("done"));
+    }
+}
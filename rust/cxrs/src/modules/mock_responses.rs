@@ -0,0 +1,172 @@
+use serde_json::{Map, Value, json};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Response of the legacy mock adapter for prompts that carry no schema.
+pub const MOCK_DEFAULT_RESPONSE: &str = "{\"commands\":[\"echo mock\"]}";
+
+/// The schema a strict-schema prompt embeds between `Schema:` and `Task input:`.
+fn embedded_schema(prompt: &str) -> Option<Value> {
+    let start = prompt.find("\nSchema:\n")? + "\nSchema:\n".len();
+    let end = start + prompt[start..].find("\n\nTask input:")?;
+    serde_json::from_str(&prompt[start..end]).ok()
+}
+
+/// Fixture name for a schema: the last `$id` segment without its version
+/// (`cx://schemas/next.v1` -> `next`), else the lower-cased title.
+fn fixture_name(schema: &Value) -> Option<String> {
+    if let Some(id) = schema.get("$id").and_then(Value::as_str) {
+        let last = id.rsplit('/').next().unwrap_or(id);
+        let name = match last.rsplit_once(".v") {
+            Some((base, ver)) if ver.chars().all(|c| c.is_ascii_digit()) => base,
+            _ => last,
+        };
+        if !name.is_empty() {
+            return Some(name.to_string());
+        }
+    }
+    schema
+        .get("title")
+        .and_then(Value::as_str)
+        .map(|t| t.trim_start_matches("cx ").replace(' ', "-").to_lowercase())
+}
+
+fn first_type(schema: &Value) -> &str {
+    match schema.get("type") {
+        Some(Value::String(t)) => t,
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null")
+            .unwrap_or("null"),
+        _ if schema.get("properties").is_some() => "object",
+        _ => "string",
+    }
+}
+
+/// Strings are harmless on purpose: anything under a `command*` key is `true`,
+/// so a synthesized suggestion can be executed without side effects.
+fn mock_string(schema: &Value, key: &str) -> String {
+    let mut s = if key.starts_with("command") {
+        "true".to_string()
+    } else {
+        format!("mock {key}")
+    };
+    let min = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+    while s.chars().count() < min {
+        s.push('x');
+    }
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+        s = s.chars().take(max as usize).collect();
+    }
+    s
+}
+
+/// Smallest useful instance of `schema`: every declared property, one array
+/// item (or `minItems`), lower bounds for numbers and the first `enum` value.
+pub fn synthesize_instance(schema: &Value, key: &str) -> Value {
+    if let Some(v) = schema.get("const") {
+        return v.clone();
+    }
+    if let Some(v) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|e| e.first())
+    {
+        return v.clone();
+    }
+    match first_type(schema) {
+        "object" => {
+            let mut out = Map::new();
+            if let Some(props) = schema.get("properties").and_then(Value::as_object) {
+                for (name, sub) in props {
+                    out.insert(name.clone(), synthesize_instance(sub, name));
+                }
+            }
+            Value::Object(out)
+        }
+        "array" => {
+            let min = schema.get("minItems").and_then(Value::as_u64).unwrap_or(1);
+            let max = schema
+                .get("maxItems")
+                .and_then(Value::as_u64)
+                .unwrap_or(u64::MAX);
+            let items = schema.get("items").cloned().unwrap_or(json!({}));
+            let count = min.max(1).min(max);
+            Value::Array(
+                (0..count)
+                    .map(|_| synthesize_instance(&items, key))
+                    .collect(),
+            )
+        }
+        "integer" | "number" => schema.get("minimum").cloned().unwrap_or_else(|| json!(1)),
+        "boolean" => Value::Bool(false),
+        "null" => Value::Null,
+        _ => Value::String(mock_string(schema, key)),
+    }
+}
+
+/// Canned answer for `prompt` when the mock backend has no fixed response:
+/// `<fixtures>/<schema>.json` from `CX_MOCK_FIXTURES` when present, else an
+/// instance synthesized from the embedded schema. `None` for prompts without
+/// a schema.
+pub fn mock_response_for_prompt(prompt: &str) -> Option<String> {
+    let schema = embedded_schema(prompt)?;
+    if let (Ok(dir), Some(name)) = (env::var("CX_MOCK_FIXTURES"), fixture_name(&schema))
+        && let Ok(text) = fs::read_to_string(Path::new(dir.trim()).join(format!("{name}.json")))
+    {
+        return Some(text.trim().to_string());
+    }
+    Some(synthesize_instance(&schema, "").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synthesized_instances_satisfy_their_schema() {
+        let schema = json!({
+            "$id": "cx://schemas/stage.v1",
+            "type": "object",
+            "additionalProperties": false,
+            "required": ["groups", "scope", "breaking"],
+            "properties": {
+                "groups": {
+                    "type": "array",
+                    "minItems": 2,
+                    "maxItems": 3,
+                    "items": {
+                        "type": "object",
+                        "required": ["subject", "depends_on"],
+                        "properties": {
+                            "subject": { "type": "string", "minLength": 1, "maxLength": 5 },
+                            "depends_on": { "type": "array", "items": { "type": "integer", "minimum": 1 } }
+                        }
+                    }
+                },
+                "commands": { "type": "array", "items": { "type": "string", "minLength": 1 } },
+                "scope": { "type": ["string", "null"] },
+                "breaking": { "type": "boolean" }
+            }
+        });
+        let instance = synthesize_instance(&schema, "");
+        let compiled = jsonschema::JSONSchema::compile(&schema).expect("compile");
+        assert!(compiled.is_valid(&instance), "{instance}");
+        assert_eq!(instance["groups"].as_array().map(Vec::len), Some(2));
+        assert_eq!(instance["groups"][0]["subject"], "mock ");
+        assert_eq!(instance["commands"][0], "true");
+        assert_eq!(fixture_name(&schema).as_deref(), Some("stage"));
+    }
+
+    #[test]
+    fn schema_is_read_back_from_strict_prompt() {
+        let prompt = crate::schema::build_strict_schema_prompt(
+            "{\n  \"type\": \"object\"\n}",
+            "Task input:\nnot the schema",
+        );
+        assert_eq!(embedded_schema(&prompt), Some(json!({"type": "object"})));
+        assert_eq!(embedded_schema("plain prompt"), None);
+    }
+}
@@ -0,0 +1,480 @@
+use chrono::Utc;
+use serde_json::{Value, json};
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+use crate::bench_history::{BenchRecord, record_bench_history};
+use crate::bench_llm::distribution_json;
+use crate::bench_parity_support::{BenchStats, maybe_collect_tokens};
+use crate::contract_versions::MODEL_BENCH_JSON_CONTRACT_VERSION;
+use crate::error::{format_error, print_usage_error};
+use crate::logs::file_len;
+use crate::paths::resolve_log_file;
+use crate::process::run_command_output_with_timeout;
+use crate::schema::{load_schema, validate_schema_instance};
+use crate::types::LoadedSchema;
+
+pub const MODEL_USAGE: &str =
+    "model bench <prompt-file|dir> --models a,b [--runs N] [--schema NAME] [--json]";
+
+const PROMPT_EXTENSIONS: &[&str] = &["md", "txt", "prompt"];
+
+/// `codex`, `codex:<model>`, `ollama:<model>`, or a bare Ollama model name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ModelSpec {
+    label: String,
+    backend: &'static str,
+    model: Option<String>,
+}
+
+fn parse_model_spec(raw: &str) -> Result<ModelSpec, String> {
+    let raw = raw.trim();
+    let (backend, model) = match raw.split_once(':') {
+        Some(("codex", m)) => ("codex", Some(m)),
+        Some(("ollama", m)) => ("ollama", Some(m)),
+        _ if raw == "codex" => ("codex", None),
+        _ if raw == "ollama" => ("ollama", None),
+        _ => ("ollama", Some(raw)),
+    };
+    let model = model.map(str::trim).filter(|m| !m.is_empty());
+    if backend == "ollama" && model.is_none() && raw != "ollama" {
+        return Err(format!("invalid model '{raw}'"));
+    }
+    Ok(ModelSpec {
+        label: raw.to_string(),
+        backend,
+        model: model.map(ToOwned::to_owned),
+    })
+}
+
+/// One prompt of the suite; a leading `schema: NAME` line names the schema
+/// its response must validate against and is not sent to the model.
+#[derive(Debug, PartialEq, Eq)]
+struct SuitePrompt {
+    name: String,
+    body: String,
+    schema: Option<String>,
+}
+
+fn parse_prompt(name: &str, raw: &str, default_schema: Option<&str>) -> SuitePrompt {
+    let (schema, body) = match raw.split_once('\n') {
+        Some((first, rest)) if first.trim_start().starts_with("schema:") => (
+            first.trim_start()["schema:".len()..].trim().to_string(),
+            rest,
+        ),
+        _ => (String::new(), raw),
+    };
+    SuitePrompt {
+        name: name.to_string(),
+        body: body.trim().to_string(),
+        schema: Some(schema)
+            .filter(|s| !s.is_empty())
+            .or_else(|| default_schema.map(ToOwned::to_owned)),
+    }
+}
+
+fn load_suite(path: &Path, default_schema: Option<&str>) -> Result<Vec<SuitePrompt>, String> {
+    let files: Vec<PathBuf> = if path.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(path)
+            .map_err(|e| format!("cannot read {}: {e}", path.display()))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.is_file()
+                    && p.extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| PROMPT_EXTENSIONS.contains(&e))
+            })
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let mut prompts = Vec::new();
+    for f in files {
+        let raw =
+            fs::read_to_string(&f).map_err(|e| format!("cannot read {}: {e}", f.display()))?;
+        let name = f
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let prompt = parse_prompt(&name, &raw, default_schema);
+        if !prompt.body.is_empty() {
+            prompts.push(prompt);
+        }
+    }
+    if prompts.is_empty() {
+        return Err(format!("no prompts found in {}", path.display()));
+    }
+    Ok(prompts)
+}
+
+#[derive(Debug, PartialEq)]
+struct ModelBenchArgs {
+    suite: String,
+    models: Vec<ModelSpec>,
+    runs: usize,
+    schema: Option<String>,
+    json_out: bool,
+}
+
+fn parse_model_bench_args(args: &[String]) -> Result<ModelBenchArgs, String> {
+    let mut suite: Option<String> = None;
+    let mut models = Vec::new();
+    let mut runs = 1usize;
+    let mut schema = None;
+    let mut json_out = false;
+    let mut i = 0usize;
+    while i < args.len() {
+        let value = || {
+            args.get(i + 1)
+                .filter(|v| !v.starts_with("--"))
+                .ok_or_else(|| format!("{} requires a value", args[i]))
+        };
+        match args[i].as_str() {
+            "--json" => json_out = true,
+            "--models" => {
+                for m in value()?.split(',').filter(|m| !m.trim().is_empty()) {
+                    models.push(parse_model_spec(m)?);
+                }
+                i += 1;
+            }
+            "--runs" => {
+                let v = value()?;
+                runs = v
+                    .parse()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| format!("--runs: invalid count '{v}'"))?;
+                i += 1;
+            }
+            "--schema" => {
+                schema = Some(value()?.clone());
+                i += 1;
+            }
+            other if other.starts_with("--") => return Err(format!("unknown flag '{other}'")),
+            other if suite.is_none() => suite = Some(other.to_string()),
+            other => return Err(format!("unexpected argument '{other}'")),
+        }
+        i += 1;
+    }
+    let suite = suite.ok_or_else(|| "missing <prompt-file|dir>".to_string())?;
+    if models.is_empty() {
+        return Err("--models requires at least one model".to_string());
+    }
+    Ok(ModelBenchArgs {
+        suite,
+        models,
+        runs,
+        schema,
+        json_out,
+    })
+}
+
+#[derive(Default)]
+struct ModelTally {
+    stats: BenchStats,
+    schema_checked: usize,
+    schema_passed: usize,
+}
+
+impl ModelTally {
+    fn schema_pass_rate(&self) -> Option<f64> {
+        (self.schema_checked > 0).then(|| self.schema_passed as f64 / self.schema_checked as f64)
+    }
+
+    fn success_rate(&self) -> f64 {
+        let n = self.stats.durations.len();
+        if n == 0 {
+            return 0.0;
+        }
+        (n - self.stats.failures) as f64 / n as f64
+    }
+
+    fn p50(&self) -> u64 {
+        crate::percentiles::percentile(self.stats.durations.clone(), 50).unwrap_or(u64::MAX)
+    }
+}
+
+/// Best first: schema pass rate, then success rate, then median latency.
+fn rank_order(a: &ModelTally, b: &ModelTally) -> Ordering {
+    let pass = |t: &ModelTally| t.schema_pass_rate().unwrap_or(1.0);
+    pass(b)
+        .total_cmp(&pass(a))
+        .then(b.success_rate().total_cmp(&a.success_rate()))
+        .then(a.p50().cmp(&b.p50()))
+}
+
+/// Run one prompt through `cxrs cxo` in a child process so the backend/model
+/// override applies to a fresh config; returns (exit code, stdout).
+fn run_prompt_once(
+    exe: &Path,
+    prompt_file: &Path,
+    spec: &ModelSpec,
+) -> Result<(i32, String), String> {
+    let mut cmd = Command::new(exe);
+    cmd.args(["cxo", "cat"]).arg(prompt_file);
+    cmd.env("CX_LLM_BACKEND", spec.backend);
+    if let Some(m) = &spec.model {
+        let var = if spec.backend == "ollama" {
+            "CX_OLLAMA_MODEL"
+        } else {
+            "CX_MODEL"
+        };
+        cmd.env(var, m);
+    }
+    let output = run_command_output_with_timeout(cmd, &format!("model bench '{}'", spec.label))?;
+    Ok((
+        output.status.code().unwrap_or(1),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+    ))
+}
+
+/// Schema check on a response; tolerates a fenced ```json block.
+fn response_passes(schema: &LoadedSchema, stdout: &str) -> bool {
+    let text = stdout.trim();
+    let text = text
+        .strip_prefix("```json")
+        .or_else(|| text.strip_prefix("```"))
+        .and_then(|t| t.strip_suffix("```"))
+        .unwrap_or(text);
+    validate_schema_instance(schema, text.trim()).is_ok()
+}
+
+fn model_json(rank: usize, spec: &ModelSpec, t: &ModelTally) -> Value {
+    let avg = |v: &[u64]| (!v.is_empty()).then(|| v.iter().sum::<u64>() / v.len() as u64);
+    json!({
+        "rank": rank,
+        "label": spec.label,
+        "backend": spec.backend,
+        "model": spec.model,
+        "runs": t.stats.durations.len(),
+        "failures": t.stats.failures,
+        "schema_checked": t.schema_checked,
+        "schema_passed": t.schema_passed,
+        "schema_pass_rate": t.schema_pass_rate().map(|r| (r * 1000.0).round() / 1000.0),
+        "duration_ms": distribution_json(&t.stats.durations),
+        "effective_input_tokens_avg": avg(&t.stats.eff_totals),
+        "output_tokens_avg": avg(&t.stats.out_totals),
+    })
+}
+
+fn print_ranked_table(suite: &str, prompts: usize, runs: usize, rows: &[Value]) {
+    println!("== cxrs model bench ==");
+    println!("suite: {suite} ({prompts} prompt(s) x {runs} run(s) per model)");
+    println!();
+    println!(
+        "{:<4} {:<28} {:>5} {:>5} {:>8} {:>8} {:>8} {:>9} {:>8}",
+        "rank", "model", "runs", "fail", "schema", "p50_ms", "p90_ms", "eff_tok", "out_tok"
+    );
+    let num = |v: &Value| v.as_u64().map_or("-".to_string(), |n| n.to_string());
+    for r in rows {
+        let schema = match r["schema_pass_rate"].as_f64() {
+            Some(rate) => format!("{:.0}%", rate * 100.0),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:<4} {:<28} {:>5} {:>5} {:>8} {:>8} {:>8} {:>9} {:>8}",
+            r["rank"],
+            r["label"].as_str().unwrap_or_default(),
+            r["runs"],
+            r["failures"],
+            schema,
+            num(&r["duration_ms"]["p50"]),
+            num(&r["duration_ms"]["p90"]),
+            num(&r["effective_input_tokens_avg"]),
+            num(&r["output_tokens_avg"]),
+        );
+    }
+}
+
+fn cmd_model_bench(args: &[String]) -> i32 {
+    let opts = match parse_model_bench_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("model bench", &e));
+            return print_usage_error("model", MODEL_USAGE);
+        }
+    };
+    let fail = |e: String| {
+        crate::cx_eprintln!("{}", format_error("model bench", &e));
+        1
+    };
+    let prompts = match load_suite(Path::new(&opts.suite), opts.schema.as_deref()) {
+        Ok(v) => v,
+        Err(e) => return fail(e),
+    };
+    let mut schemas: Vec<(String, LoadedSchema)> = Vec::new();
+    for name in prompts.iter().filter_map(|p| p.schema.as_ref()) {
+        if schemas.iter().any(|(n, _)| n == name) {
+            continue;
+        }
+        match load_schema(name) {
+            Ok(s) => schemas.push((name.clone(), s)),
+            Err(e) => return fail(e),
+        }
+    }
+    let exe = match env::current_exe() {
+        Ok(v) => v,
+        Err(e) => return fail(format!("current_exe failed: {e}")),
+    };
+    let scratch = env::temp_dir().join(format!(
+        "cxmodelbench-{}-{}",
+        std::process::id(),
+        Utc::now().timestamp_millis()
+    ));
+    if let Err(e) = fs::create_dir_all(&scratch) {
+        return fail(format!("cannot create {}: {e}", scratch.display()));
+    }
+    let log_file = resolve_log_file();
+    let mut tallies: Vec<ModelTally> = opts.models.iter().map(|_| ModelTally::default()).collect();
+    let mut result = Ok(());
+    'suite: for (pi, prompt) in prompts.iter().enumerate() {
+        let prompt_file = scratch.join(format!("prompt_{pi}.txt"));
+        if let Err(e) = fs::write(&prompt_file, &prompt.body) {
+            result = Err(format!("cannot write {}: {e}", prompt_file.display()));
+            break;
+        }
+        let schema = prompt
+            .schema
+            .as_ref()
+            .and_then(|n| schemas.iter().find(|(s, _)| s == n).map(|(_, s)| s));
+        for _ in 0..opts.runs {
+            for (spec, tally) in opts.models.iter().zip(tallies.iter_mut()) {
+                let before_offset = log_file
+                    .as_ref()
+                    .map(|p| if p.exists() { file_len(p) } else { 0 })
+                    .unwrap_or(0);
+                let started = Instant::now();
+                let started_epoch = Utc::now().timestamp();
+                let (code, stdout) = match run_prompt_once(&exe, &prompt_file, spec) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        result = Err(e);
+                        break 'suite;
+                    }
+                };
+                tally
+                    .stats
+                    .durations
+                    .push(started.elapsed().as_millis() as u64);
+                if code != 0 {
+                    tally.stats.failures += 1;
+                }
+                if let Some(s) = schema {
+                    tally.schema_checked += 1;
+                    if code == 0 && response_passes(s, &stdout) {
+                        tally.schema_passed += 1;
+                    }
+                }
+                maybe_collect_tokens(
+                    &mut tally.stats,
+                    &log_file,
+                    before_offset,
+                    started_epoch,
+                    Utc::now().timestamp(),
+                    false,
+                );
+            }
+        }
+    }
+    let _ = fs::remove_dir_all(&scratch);
+    if let Err(e) = result {
+        return fail(e);
+    }
+
+    let records: Vec<BenchRecord<'_>> = opts
+        .models
+        .iter()
+        .zip(&tallies)
+        .map(|(spec, t)| BenchRecord {
+            source: "model_bench",
+            label: &spec.label,
+            backend: Some(spec.backend),
+            model: spec.model.as_deref(),
+            suite: Some(&opts.suite),
+            schema_pass_rate: t.schema_pass_rate(),
+            stats: &t.stats,
+        })
+        .collect();
+    record_bench_history(&records);
+
+    let mut order: Vec<usize> = (0..opts.models.len()).collect();
+    order.sort_by(|a, b| rank_order(&tallies[*a], &tallies[*b]));
+    let rows: Vec<Value> = order
+        .iter()
+        .enumerate()
+        .map(|(rank, i)| model_json(rank + 1, &opts.models[*i], &tallies[*i]))
+        .collect();
+    if opts.json_out {
+        let doc = json!({
+            "contract_version": MODEL_BENCH_JSON_CONTRACT_VERSION,
+            "suite": opts.suite,
+            "prompts": prompts.iter().map(|p| json!({"name": p.name, "schema": p.schema})).collect::<Vec<_>>(),
+            "runs_per_prompt": opts.runs,
+            "models": rows,
+        });
+        match serde_json::to_string_pretty(&doc) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return fail(format!("render failed: {e}")),
+        }
+    } else {
+        print_ranked_table(&opts.suite, prompts.len(), opts.runs, &rows);
+    }
+    0
+}
+
+pub fn cmd_model(args: &[String]) -> i32 {
+    match args.first().map(String::as_str) {
+        Some("bench") => cmd_model_bench(&args[1..]),
+        _ => print_usage_error("model", MODEL_USAGE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_specs_and_prompt_schema_lines() {
+        let s = parse_model_spec("llama3:8b").unwrap();
+        assert_eq!(
+            (s.backend, s.model.as_deref()),
+            ("ollama", Some("llama3:8b"))
+        );
+        let s = parse_model_spec("ollama:qwen2.5").unwrap();
+        assert_eq!(s.model.as_deref(), Some("qwen2.5"));
+        let s = parse_model_spec("codex:gpt-5").unwrap();
+        assert_eq!((s.backend, s.model.as_deref()), ("codex", Some("gpt-5")));
+        assert_eq!(parse_model_spec("codex").unwrap().model, None);
+
+        let p = parse_prompt("a.md", "schema: next\nList next commands.\n", None);
+        assert_eq!(p.schema.as_deref(), Some("next"));
+        assert_eq!(p.body, "List next commands.");
+        let p = parse_prompt("b.md", "Explain.\n", Some("explain"));
+        assert_eq!(p.schema.as_deref(), Some("explain"));
+        assert_eq!(p.body, "Explain.");
+    }
+
+    #[test]
+    fn ranking_prefers_schema_passes_then_latency() {
+        let tally = |passed: usize, durations: Vec<u64>| ModelTally {
+            stats: BenchStats {
+                durations,
+                ..Default::default()
+            },
+            schema_checked: 2,
+            schema_passed: passed,
+        };
+        let fast_bad = tally(1, vec![10, 10]);
+        let slow_good = tally(2, vec![90, 90]);
+        let fast_good = tally(2, vec![20, 20]);
+        assert_eq!(rank_order(&slow_good, &fast_bad), Ordering::Less);
+        assert_eq!(rank_order(&fast_good, &slow_good), Ordering::Less);
+    }
+}
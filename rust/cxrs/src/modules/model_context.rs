@@ -0,0 +1,216 @@
+use std::sync::Mutex;
+
+use crate::capture::{budget_config_from_env, clip_text_with_config};
+use crate::config_layers::config_env;
+use crate::llm::LlmRunError;
+use crate::ollama_api::known_model_info;
+use crate::runtime::{llm_backend, llm_model, ollama_model_preference};
+use crate::token_estimate::{TokenizerKind, estimate_tokens};
+use crate::verbosity::{Verbosity, diag, warn};
+
+/// Context windows (tokens) of hosted models, matched by longest name prefix.
+/// Ollama models are sized by inspection instead: what bounds them is the
+/// `num_ctx` they run with, not the trained maximum.
+const BUILTIN_CONTEXT_WINDOWS: &[(&str, u64)] = &[
+    ("gpt-5", 400_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-oss", 131_072),
+    ("o3", 200_000),
+    ("o4-mini", 200_000),
+    ("codex-mini", 200_000),
+];
+
+/// Room left for the clip footer the clipper appends after shrinking.
+const FOOTER_RESERVE_TOKENS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextWindow {
+    pub tokens: u64,
+    /// `config` (`CX_CONTEXT_WINDOWS`), `ollama` (inspected) or `builtin`.
+    pub source: &'static str,
+}
+
+fn builtin_window(model: &str) -> Option<u64> {
+    let model = model.trim().to_ascii_lowercase();
+    BUILTIN_CONTEXT_WINDOWS
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, tokens)| *tokens)
+}
+
+/// `model=tokens` pairs from `CX_CONTEXT_WINDOWS`; an exact model entry wins
+/// over one keyed by backend name.
+fn configured_window(raw: &str, model: &str, backend: &str) -> Option<u64> {
+    let mut by_backend = None;
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry
+            .rsplit_once('=')
+            .and_then(|(k, v)| Some((k.trim(), v.trim().parse::<u64>().ok()?)))
+            .filter(|(_, v)| *v > 0);
+        let Some((key, tokens)) = parsed else {
+            diag(
+                Verbosity::Verbose,
+                "context",
+                &[("ignored_window", entry.to_string())],
+            );
+            continue;
+        };
+        if !model.is_empty() && key == model {
+            return Some(tokens);
+        }
+        if key == backend {
+            by_backend = Some(tokens);
+        }
+    }
+    by_backend
+}
+
+/// Model the active backend will run (`ollama_model` for Ollama).
+pub fn active_model_name() -> String {
+    if llm_backend() == "ollama" {
+        ollama_model_preference()
+    } else {
+        llm_model()
+    }
+}
+
+/// Context window of the active model: config override, then the Ollama
+/// inspection (run once when `inspect` allows it), then the built-in table.
+pub fn resolve_context_window(inspect: bool) -> Option<ContextWindow> {
+    let backend = llm_backend();
+    let model = active_model_name();
+    if let Some(tokens) = config_env("CX_CONTEXT_WINDOWS")
+        .and_then(|raw| configured_window(&raw, model.trim(), &backend))
+    {
+        return Some(ContextWindow {
+            tokens,
+            source: "config",
+        });
+    }
+    if model.trim().is_empty() {
+        return None;
+    }
+    if backend == "ollama" {
+        return known_model_info(&model, inspect).map(|info| ContextWindow {
+            tokens: info.context_window,
+            source: "ollama",
+        });
+    }
+    builtin_window(&model).map(|tokens| ContextWindow {
+        tokens,
+        source: "builtin",
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GuardMode {
+    Shrink,
+    Fail,
+    Off,
+}
+
+fn guard_mode() -> GuardMode {
+    match config_env("CX_CONTEXT_GUARD").as_deref() {
+        Some("fail") => GuardMode::Fail,
+        Some("off") => GuardMode::Off,
+        _ => GuardMode::Shrink,
+    }
+}
+
+/// How the last prompt related to the model context, read when the run row is logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextOutcome {
+    pub window: u64,
+    /// `fit`, `shrunk` or `blocked`.
+    pub guard: &'static str,
+}
+
+static CONTEXT_OUTCOME: Mutex<Option<ContextOutcome>> = Mutex::new(None);
+
+fn set_context_outcome(window: u64, guard: &'static str) {
+    if let Ok(mut slot) = CONTEXT_OUTCOME.lock() {
+        *slot = Some(ContextOutcome { window, guard });
+    }
+}
+
+pub fn context_outcome() -> Option<ContextOutcome> {
+    CONTEXT_OUTCOME.lock().ok().and_then(|slot| *slot)
+}
+
+pub fn clear_context_outcome() {
+    if let Ok(mut slot) = CONTEXT_OUTCOME.lock() {
+        *slot = None;
+    }
+}
+
+/// Make `body` fit the model context next to the fixed `frame` text (e.g. the
+/// schema envelope): clipped with the capture clipper under `shrink`, refused
+/// under `fail` or when the frame alone leaves no room.
+pub fn fit_prompt(body: String, frame: &str) -> Result<String, LlmRunError> {
+    let mode = guard_mode();
+    if mode == GuardMode::Off {
+        return Ok(body);
+    }
+    let Some(window) = resolve_context_window(true) else {
+        return Ok(body);
+    };
+    let tokenizer = TokenizerKind::for_backend(&llm_backend());
+    let frame_tokens = estimate_tokens(frame, tokenizer);
+    let total = frame_tokens + estimate_tokens(&body, tokenizer);
+    let limit = window.tokens as usize;
+    if total <= limit {
+        set_context_outcome(window.tokens, "fit");
+        return Ok(body);
+    }
+    let room = limit.saturating_sub(frame_tokens + FOOTER_RESERVE_TOKENS);
+    if mode == GuardMode::Fail || room == 0 {
+        set_context_outcome(window.tokens, "blocked");
+        return Err(LlmRunError::message(format!(
+            "prompt exceeds model context ({total} > {limit} tokens); shrink the input, raise CX_CONTEXT_WINDOWS or set CX_CONTEXT_GUARD=shrink"
+        )));
+    }
+    let mut cfg = budget_config_from_env();
+    cfg.budget_chars = usize::MAX;
+    cfg.budget_lines = usize::MAX;
+    cfg.budget_tokens = Some(room);
+    cfg.tokenizer = tokenizer;
+    let (clipped, _) = clip_text_with_config(&body, &cfg);
+    set_context_outcome(window.tokens, "shrunk");
+    warn(&format!(
+        "cxrs: prompt exceeds model context ({total} > {limit} tokens); clipped to fit"
+    ));
+    diag(
+        Verbosity::Verbose,
+        "context",
+        &[
+            ("window", limit.to_string()),
+            ("source", window.source.to_string()),
+            ("prompt_tokens", total.to_string()),
+            ("kept_tokens", room.to_string()),
+        ],
+    );
+    Ok(clipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_table_matches_the_longest_prefix() {
+        assert_eq!(builtin_window("gpt-5.1-codex"), Some(400_000));
+        assert_eq!(builtin_window("GPT-4o-mini"), Some(128_000));
+        assert_eq!(builtin_window("o3-mini"), Some(200_000));
+        assert_eq!(builtin_window("llama3.1:8b"), None);
+    }
+
+    #[test]
+    fn configured_windows_prefer_the_model_over_the_backend() {
+        let raw = "ollama=8192, qwen2.5:7b=32768,bogus,bad=x";
+        assert_eq!(configured_window(raw, "qwen2.5:7b", "ollama"), Some(32768));
+        assert_eq!(configured_window(raw, "llama3", "ollama"), Some(8192));
+        assert_eq!(configured_window(raw, "gpt-5", "codex"), None);
+    }
+}
@@ -1,6 +1,15 @@
+use crate::agentcmds::{CXCOPY_USAGE, FIX_USAGE};
+use crate::analytics::trace_id_arg;
+use crate::bench_llm::BENCH_GATE_FLAGS;
 use crate::cmdctx::CmdCtx;
 use crate::config::{DEFAULT_OPTIMIZE_WINDOW, DEFAULT_QUARANTINE_LIST, DEFAULT_RUN_WINDOW};
 use crate::error::{EXIT_OK, EXIT_RUNTIME, EXIT_USAGE, format_error, print_usage_error};
+use crate::help::resolve_help_topic;
+use crate::nesting::nested_block;
+use crate::offline::offline_block;
+use crate::roles::split_role_flag;
+use crate::run_filter::{RunFilter, parse_window_json, split_run_filter};
+use crate::structured_replay::REPLAY_USAGE;
 
 use super::NativeDeps;
 
@@ -11,6 +20,26 @@ fn parse_n(args: &[String], idx: usize, default: usize) -> usize {
         .unwrap_or(default)
 }
 
+/// Strip the analytics `--branch`/`--path` filter flags from `args[idx..]`.
+fn split_filter(
+    cmd: &str,
+    usage: &str,
+    args: &[String],
+    idx: usize,
+) -> Result<(RunFilter, Vec<String>), i32> {
+    split_run_filter(args.get(idx..).unwrap_or_default()).map_err(|e| {
+        crate::cx_eprintln!("{}", format_error(cmd, &e));
+        print_usage_error(cmd, usage)
+    })
+}
+
+fn has_percentiles(args: &[String], idx: usize) -> bool {
+    args.get(idx..)
+        .unwrap_or_default()
+        .iter()
+        .any(|v| v == "--percentiles")
+}
+
 fn require_min_args(args: &[String], min: usize, usage: &str) -> Result<(), i32> {
     if args.len() < min {
         return Err(print_usage_error(usage, usage));
@@ -26,19 +55,16 @@ fn run_agent_cmd(args: &[String], min: usize, usage: &str, f: fn(&[String]) -> i
 }
 
 fn handle_state(app_name: &str, args: &[String], deps: &NativeDeps) -> i32 {
+    let rest = args.get(3..).unwrap_or(&[]);
     match args.get(2).map(String::as_str).unwrap_or("show") {
-        "show" => (deps.cmd_state_show)(),
-        "get" => match args.get(3) {
-            Some(key) => (deps.cmd_state_get)(key),
-            None => print_usage_error("state", &format!("{app_name} state get <key>")),
-        },
-        "set" => match (args.get(3), args.get(4)) {
-            (Some(key), Some(value)) => (deps.cmd_state_set)(key, value),
-            _ => print_usage_error("state", &format!("{app_name} state set <key> <value>")),
-        },
+        "show" => (deps.cmd_state_show)(rest),
+        "get" => (deps.cmd_state_get)(rest),
+        "set" => (deps.cmd_state_set)(rest),
         other => {
             crate::cx_eprintln!("{app_name}: unknown state subcommand '{other}'");
-            crate::cx_eprintln!("Usage: {app_name} state <show|get <key>|set <key> <value>>");
+            crate::cx_eprintln!(
+                "Usage: {app_name} state <show [--merged [--origin]]|get <key>|set <key> <value>> [--repo|--global]"
+            );
             EXIT_USAGE
         }
     }
@@ -66,29 +92,37 @@ fn handle_telemetry(args: &[String], deps: &NativeDeps) -> i32 {
 }
 
 fn handle_bench(app_name: &str, args: &[String], deps: &NativeDeps) -> i32 {
-    let usage = format!("{app_name} bench <runs> -- <command...>");
-    let runs = parse_n(args, 2, 0);
-    if runs == 0 {
-        return print_usage_error("bench", &usage);
+    let usage = format!("{app_name} bench <runs> [--json] -- <command...>");
+    let i = args.iter().position(|v| v == "--").unwrap_or(args.len());
+    let head = &args[..i];
+    if head
+        .iter()
+        .any(|v| v == "--llm" || BENCH_GATE_FLAGS.contains(&v.as_str()))
+    {
+        return (deps.cmd_bench_llm)(&args[2..]);
     }
-    let Some(i) = args.iter().position(|v| v == "--") else {
+    if i == args.len() {
         return print_usage_error("bench", &usage);
-    };
-    if i + 1 >= args.len() {
+    }
+    let (runs, json_out) = parse_window_json(&args[..i], 2, 0);
+    if runs == 0 || i + 1 >= args.len() {
         return print_usage_error("bench", &usage);
     }
-    (deps.cmd_bench)(runs, &args[i + 1..])
+    (deps.cmd_bench)(runs, &args[i + 1..], json_out)
 }
 
 fn handle_prompt(app_name: &str, args: &[String], deps: &NativeDeps) -> i32 {
-    let usage = format!("{app_name} prompt <implement|fix|test|doc|ops> <request>");
+    let usage = format!("{app_name} prompt <implement|fix|test|doc|ops> [--role NAME] <request>");
     let Some(mode) = args.get(2) else {
         return print_usage_error("prompt", &usage);
     };
-    if args.len() < 4 {
+    let Some((role, request)) = split_role_flag(&args[3..]) else {
+        return print_usage_error("prompt", &usage);
+    };
+    if request.is_empty() {
         return print_usage_error("prompt", &usage);
     }
-    (deps.cmd_prompt)(mode, &args[3..].join(" "))
+    (deps.cmd_prompt)(mode, role, &request.join(" "))
 }
 
 fn handle_cx(args: &[String], deps: &NativeDeps) -> i32 {
@@ -114,10 +148,10 @@ fn handle_optimize(args: &[String], deps: &NativeDeps) -> i32 {
 }
 
 fn handle_replay(app_name: &str, args: &[String], deps: &NativeDeps) -> i32 {
-    match args.get(2) {
-        Some(id) => (deps.cmd_replay)(id),
-        None => print_usage_error("replay", &format!("{app_name} replay <quarantine_id>")),
+    if args.len() < 3 {
+        return print_usage_error("replay", &format!("{app_name} {REPLAY_USAGE}"));
     }
+    (deps.cmd_replay)(&args[2..])
 }
 
 fn handle_quarantine(app_name: &str, args: &[String], deps: &NativeDeps) -> i32 {
@@ -130,9 +164,22 @@ fn handle_quarantine(app_name: &str, args: &[String], deps: &NativeDeps) -> i32
                 &format!("{app_name} quarantine show <quarantine_id>"),
             ),
         },
+        "resolve" => (deps.cmd_quarantine_resolve)(&args[3..]),
+        "rm" => match args.get(3) {
+            Some(id) => (deps.cmd_quarantine_rm)(id),
+            None => print_usage_error(
+                "quarantine",
+                &format!("{app_name} quarantine rm <quarantine_id>"),
+            ),
+        },
+        "purge" => (deps.cmd_quarantine_purge)(&args[3..]),
+        "stats" => (deps.cmd_quarantine_stats)(args[3..].iter().any(|a| a == "--json")),
+        "diff" => (deps.cmd_quarantine_diff)(&args[3..]),
         other => {
             crate::cx_eprintln!("{app_name}: unknown quarantine subcommand '{other}'");
-            crate::cx_eprintln!("Usage: {app_name} quarantine <list [N]|show <id>>");
+            crate::cx_eprintln!(
+                "Usage: {app_name} quarantine <list [N]|show <id>|diff <id>|resolve <id>|rm <id>|purge|stats>"
+            );
             EXIT_USAGE
         }
     }
@@ -145,22 +192,18 @@ fn dispatch_meta_commands(
     deps: &NativeDeps,
 ) -> Option<i32> {
     let out = match cmd {
-        "help" | "-h" | "--help" => {
-            if args.get(2).map(String::as_str) == Some("task") {
-                (deps.print_task_help)();
-            } else {
-                (deps.print_help)();
-            }
-            EXIT_OK
-        }
+        "help" | "-h" | "--help" => (deps.cmd_help)(&args[2..]),
         "version" | "-V" | "--version" => {
             (deps.print_version)();
             EXIT_OK
         }
         "schema" => (deps.cmd_schema)(&args[2..]),
+        "exit-codes" => (deps.cmd_exit_codes)(&args[2..]),
+        "redact-test" => (deps.cmd_redact_test)(&args[2..]),
         "logs" => (deps.cmd_logs)(&args[2..]),
         "telemetry" => handle_telemetry(args, deps),
         "ci" => (deps.cmd_ci)(&args[2..]),
+        "hook" => (deps.cmd_hook)(&args[2..]),
         "core" => (deps.cmd_core)(),
         "task" => (deps.cmd_task)(&args[2..]),
         "where" => (deps.cmd_where)(&args[2..]),
@@ -169,11 +212,20 @@ fn dispatch_meta_commands(
         "scheduler" => (deps.cmd_scheduler)(&args[2..]),
         "parity" => (deps.cmd_parity)(),
         "supports" => handle_supports(app_name, args, deps),
-        "doctor" => (deps.cmd_doctor)(),
+        "doctor" => (deps.cmd_doctor)(&args[2..]),
         "state" => handle_state(app_name, args, deps),
         "llm" => (deps.cmd_llm)(&args[2..]),
+        "model" => (deps.cmd_model)(&args[2..]),
+        "workspace" => (deps.cmd_workspace)(&args[2..]),
+        "init" => (deps.cmd_init)(&args[2..]),
+        "gc" => (deps.cmd_gc)(&args[2..]),
+        "annotate" => (deps.cmd_annotate)(&args[2..]),
+        "secret" => (deps.cmd_secret)(&args[2..]),
+        "changelog" => (deps.cmd_changelog)(&args[2..]),
+        "config" => (deps.cmd_config)(&args[2..]),
         "policy" => (deps.cmd_policy)(&args[2..]),
         "broker" => (deps.cmd_broker)(&args[2..]),
+        "session" => (deps.cmd_session)(&args[2..]),
         _ => return None,
     };
     Some(out)
@@ -187,7 +239,15 @@ fn dispatch_prompt_commands(
 ) -> Option<i32> {
     let out = match cmd {
         "bench" => handle_bench(app_name, args, deps),
-        "metrics" => (deps.print_metrics)(parse_n(args, 2, DEFAULT_RUN_WINDOW)),
+        "metrics" => {
+            let usage = "metrics [N] [--percentiles] [--all-repos] [filters]";
+            let (filter, rest) = match split_filter("metrics", usage, args, 2) {
+                Ok(v) => v,
+                Err(code) => return Some(code),
+            };
+            let (n, _) = parse_window_json(&rest, 0, DEFAULT_RUN_WINDOW);
+            (deps.print_metrics)(n, has_percentiles(&rest, 0), &filter)
+        }
         "quota" => (deps.cmd_quota)(&args[2..]),
         "prompt-stats" => (deps.cmd_prompt_stats)(&args[2..]),
         "prompt" => handle_prompt(app_name, args, deps),
@@ -196,12 +256,20 @@ fn dispatch_prompt_commands(
             if args.len() < 3 {
                 return Some(print_usage_error(
                     "fanout",
-                    &format!("{app_name} fanout <objective>"),
+                    &format!("{app_name} fanout [--llm | --role NAME...] <objective>"),
                 ));
             }
-            (deps.cmd_fanout)(&args[2..].join(" "))
+            (deps.cmd_fanout)(&args[2..])
+        }
+        "promptlint" => {
+            let (filter, rest) =
+                match split_filter("promptlint", "promptlint [N] [--json] [filters]", args, 2) {
+                    Ok(v) => v,
+                    Err(code) => return Some(code),
+                };
+            let (n, json_out) = parse_window_json(&rest, 0, DEFAULT_OPTIMIZE_WINDOW);
+            (deps.cmd_promptlint)(n, json_out, &filter)
         }
-        "promptlint" => (deps.cmd_promptlint)(parse_n(args, 2, DEFAULT_OPTIMIZE_WINDOW)),
         _ => return None,
     };
     Some(out)
@@ -213,11 +281,30 @@ fn dispatch_agent_commands(cmd: &str, args: &[String], deps: &NativeDeps) -> Opt
         "cxj" => run_agent_cmd(args, 3, "cxj <command> [args...]", deps.cmd_cxj),
         "cxo" => run_agent_cmd(args, 3, "cxo <command> [args...]", deps.cmd_cxo),
         "cxol" => run_agent_cmd(args, 3, "cxol <command> [args...]", deps.cmd_cxol),
-        "cxcopy" => run_agent_cmd(args, 3, "cxcopy <command> [args...]", deps.cmd_cxcopy),
-        "fix" => run_agent_cmd(args, 3, "fix <command> [args...]", deps.cmd_fix),
+        "cxcopy" => run_agent_cmd(args, 3, CXCOPY_USAGE, deps.cmd_cxcopy),
+        "fix" => run_agent_cmd(args, 3, FIX_USAGE, deps.cmd_fix),
+        "followup" => run_agent_cmd(args, 3, "followup <question...>", deps.cmd_followup),
+        "explain" => run_agent_cmd(
+            args,
+            3,
+            "explain <file>[:START[-END]] [--json]",
+            deps.cmd_explain,
+        ),
+        "custom" => (deps.cmd_custom)(&args[2..]),
         "cx-compat" => (deps.cmd_cx_compat)(&args[2..]),
-        "next" => run_agent_cmd(args, 3, "next <command> [args...]", deps.cmd_next),
-        "fix-run" => run_agent_cmd(args, 3, "fix-run <command> [args...]", deps.cmd_fix_run),
+        "next" => run_agent_cmd(
+            args,
+            3,
+            "next [--run [--unsafe]] <command> [args...]",
+            deps.cmd_next,
+        ),
+        "pipeline" => (deps.cmd_pipeline)(&args[2..]),
+        "fix-run" => run_agent_cmd(
+            args,
+            3,
+            "fix-run [--dry-run|--confirm] <command> [args...]",
+            deps.cmd_fix_run,
+        ),
         _ => return None,
     };
     Some(out)
@@ -225,9 +312,10 @@ fn dispatch_agent_commands(cmd: &str, args: &[String], deps: &NativeDeps) -> Opt
 
 fn dispatch_runtime_commands(cmd: &str, args: &[String], deps: &NativeDeps) -> Option<i32> {
     let out = match cmd {
-        "budget" => (deps.cmd_budget)(),
+        "budget" => (deps.cmd_budget)(&args[2..]),
         "log-tail" => (deps.cmd_log_tail)(parse_n(args, 2, 10)),
-        "health" => (deps.cmd_health)(),
+        "health" => (deps.cmd_health)(&args[2..]),
+        "selftest" => (deps.cmd_selftest)(&args[2..]),
         "capture-status" => (deps.cmd_capture_status)(),
         "log-on" => (deps.cmd_log_on)(),
         "log-off" => (deps.cmd_log_off)(),
@@ -235,11 +323,42 @@ fn dispatch_runtime_commands(cmd: &str, args: &[String], deps: &NativeDeps) -> O
         "alert-on" => (deps.cmd_alert_on)(),
         "alert-off" => (deps.cmd_alert_off)(),
         "chunk" => (deps.cmd_chunk)(),
-        "profile" => (deps.print_profile)(parse_n(args, 2, DEFAULT_RUN_WINDOW)),
-        "alert" => (deps.print_alert)(parse_n(args, 2, DEFAULT_RUN_WINDOW)),
+        "pack" => run_agent_cmd(args, 3, "pack [--chunk] <path|dir|glob>...", deps.cmd_pack),
+        "profile" => {
+            let usage = "profile [N] [--json] [--percentiles] [--all-repos] [filters]";
+            let (filter, rest) = match split_filter("profile", usage, args, 2) {
+                Ok(v) => v,
+                Err(code) => return Some(code),
+            };
+            let (n, json_out) = parse_window_json(&rest, 0, DEFAULT_RUN_WINDOW);
+            (deps.print_profile)(n, json_out, has_percentiles(&rest, 0), &filter)
+        }
+        "alert" => {
+            let (filter, rest) =
+                match split_filter("alert", "alert [N] [--json] [filters]", args, 2) {
+                    Ok(v) => v,
+                    Err(code) => return Some(code),
+                };
+            let (n, json_out) = parse_window_json(&rest, 0, DEFAULT_RUN_WINDOW);
+            (deps.print_alert)(n, json_out, &filter)
+        }
         "optimize" => handle_optimize(args, deps),
-        "worklog" => (deps.print_worklog)(parse_n(args, 2, DEFAULT_RUN_WINDOW)),
-        "trace" => (deps.print_trace)(parse_n(args, 2, 1)),
+        "worklog" => (deps.cmd_worklog)(&args[2..]),
+        "trace" => {
+            let (n, json_out) = parse_window_json(args, 2, 1);
+            if args[2..].iter().any(|a| a == "--remediations") {
+                (deps.print_trace_remediations)(n, json_out)
+            } else if let Some(id) = trace_id_arg(&args[2..]) {
+                (deps.print_trace_by_id)(id, json_out)
+            } else {
+                (deps.print_trace)(n, json_out)
+            }
+        }
+        "transcript" => (deps.cmd_transcript)(&args[2..]),
+        "diff-runs" => (deps.cmd_diff_runs)(&args[2..]),
+        "trends" => (deps.cmd_trends)(&args[2..]),
+        "digest" => (deps.cmd_digest)(&args[2..]),
+        "watch" => (deps.cmd_watch)(&args[2..]),
         _ => return None,
     };
     Some(out)
@@ -252,17 +371,27 @@ fn dispatch_structured_commands(
     deps: &NativeDeps,
 ) -> Option<i32> {
     let out = match cmd {
-        "diffsum" => (deps.cmd_diffsum)(false),
-        "diffsum-staged" => (deps.cmd_diffsum)(true),
-        "commitjson" => (deps.cmd_commitjson)(),
-        "commitmsg" => (deps.cmd_commitmsg)(),
+        "diffsum" => (deps.cmd_diffsum)(false, &args[2..]),
+        "diffsum-staged" => (deps.cmd_diffsum)(true, &args[2..]),
+        "commitjson" => (deps.cmd_commitjson)(&args[2..]),
+        "commitmsg" => (deps.cmd_commitmsg)(&args[2..]),
+        "stage" => (deps.cmd_stage)(&args[2..]),
         "replay" => handle_replay(app_name, args, deps),
+        "rerun" => (deps.cmd_rerun)(&args[2..]),
         "quarantine" => handle_quarantine(app_name, args, deps),
         _ => return None,
     };
     Some(out)
 }
 
+/// `<cmd> --help` / `<cmd> -h` resolves to `help <cmd>` when the command has a help entry.
+fn help_flag_topic(cmd: &str, next: Option<&String>) -> Option<String> {
+    if cmd == "help" || !matches!(next.map(String::as_str), Some("--help" | "-h")) {
+        return None;
+    }
+    resolve_help_topic(cmd)
+}
+
 pub fn handler(ctx: &CmdCtx, args: &[String], deps: &NativeDeps) -> i32 {
     let app_name = ctx.app_name;
     if args.len() < 2 {
@@ -271,6 +400,15 @@ pub fn handler(ctx: &CmdCtx, args: &[String], deps: &NativeDeps) -> i32 {
     }
 
     let cmd = args[1].as_str();
+    if let Some(topic) = help_flag_topic(cmd, args.get(2)) {
+        return (deps.cmd_help)(&[topic]);
+    }
+    if let Some(code) = offline_block(cmd) {
+        return code;
+    }
+    if let Some(code) = nested_block(cmd) {
+        return code;
+    }
 
     dispatch_meta_commands(cmd, app_name, args, deps)
         .or_else(|| dispatch_prompt_commands(cmd, app_name, args, deps))
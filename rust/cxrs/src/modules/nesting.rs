@@ -0,0 +1,156 @@
+use std::env;
+use std::path::{MAIN_SEPARATOR, Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::cli::{LLM_TIMEOUT_COMMANDS, split_global_flags};
+use crate::command_names::canonical_name;
+use crate::config::app_config;
+use crate::error::{EXIT_POLICY, ExitKind, format_error, note_failure};
+use crate::execmeta::make_execution_id;
+use crate::platform::find_in_path;
+use crate::verbosity::warn;
+
+/// Exported to every command cx wraps; holds the execution id of the wrapping
+/// run, so a cx started inside it knows it is nested and who its parent is.
+pub const IN_FLIGHT_ENV: &str = "CX_IN_FLIGHT";
+
+const NESTED_HINT: &str = "pass --allow-nested (or CX_NESTED=allow) to run it anyway";
+
+/// Id handed to the last wrapped command; the next logged row takes it so
+/// nested rows point at a real parent.
+static PENDING_ID: Mutex<Option<String>> = Mutex::new(None);
+
+/// `runtime.nested`: what to do when cx would run an LLM command of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NestedMode {
+    Block,
+    Warn,
+    Allow,
+}
+
+fn nested_mode() -> NestedMode {
+    match app_config().nested.as_str() {
+        "allow" => NestedMode::Allow,
+        "warn" => NestedMode::Warn,
+        _ => NestedMode::Block,
+    }
+}
+
+/// `program` is cx itself: named `cxrs`/`cx`, or resolving to this binary.
+fn is_self(program: &str, me: Option<&Path>) -> bool {
+    let stem = Path::new(program)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(program);
+    if matches!(stem, "cxrs" | "cx") {
+        return true;
+    }
+    let Some(me) = me else {
+        return false;
+    };
+    let resolved = if program.contains(MAIN_SEPARATOR) {
+        Some(PathBuf::from(program))
+    } else {
+        find_in_path(program)
+    };
+    resolved.and_then(|p| p.canonicalize().ok()).as_deref() == Some(me)
+}
+
+/// LLM command a cx argv runs, past its global flags (`cxrs -v cxo ls` -> `cxo`).
+fn llm_command(argv: &[String]) -> Option<&'static str> {
+    let (_, rest) = split_global_flags(argv).ok()?;
+    let name = rest.get(1)?.as_str();
+    let name = canonical_name(name).unwrap_or(name);
+    LLM_TIMEOUT_COMMANDS.iter().find(|c| **c == name).copied()
+}
+
+/// Check `cmd` before cx wraps it and return the marker the child inherits as
+/// [`IN_FLIGHT_ENV`]. Wrapping one of cx's own LLM commands is refused, or only
+/// warned about, per `runtime.nested`.
+pub fn guard_wrapped(tool: &str, cmd: &[String]) -> Result<String, String> {
+    let me = env::current_exe().and_then(|p| p.canonicalize()).ok();
+    if is_self(&cmd[0], me.as_deref())
+        && let Some(inner) = llm_command(cmd)
+    {
+        let msg = format!("`{}` runs cx {inner} inside cx", cmd.join(" "));
+        match nested_mode() {
+            NestedMode::Block => {
+                note_failure(ExitKind::Policy);
+                return Err(format!("{msg}, which logs every run twice; {NESTED_HINT}"));
+            }
+            NestedMode::Warn => warn(&format!("cxrs: {msg}; its runs are logged as nested")),
+            NestedMode::Allow => {}
+        }
+    }
+    let id = make_execution_id(tool);
+    if let Ok(mut pending) = PENDING_ID.lock() {
+        *pending = Some(id.clone());
+    }
+    Ok(id)
+}
+
+/// Execution id for the next run-log row, when a wrapped command was given one.
+pub fn take_pending_execution_id() -> Option<String> {
+    PENDING_ID.lock().ok().and_then(|mut p| p.take())
+}
+
+/// Execution id of the cx run wrapping this process, if any.
+pub fn nested_parent_id() -> Option<String> {
+    env::var(IN_FLIGHT_ENV)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Refuse (or warn about) an LLM command started inside a command cx is
+/// wrapping, e.g. through a script or shell alias the argv check cannot see.
+pub fn nested_block(cmd: &str) -> Option<i32> {
+    let parent = nested_parent_id()?;
+    let name = canonical_name(cmd).unwrap_or(cmd);
+    if !LLM_TIMEOUT_COMMANDS.contains(&name) {
+        return None;
+    }
+    let msg = format!("started inside cx run {parent}");
+    match nested_mode() {
+        NestedMode::Block => {
+            note_failure(ExitKind::Policy);
+            crate::cx_eprintln!(
+                "{}",
+                format_error(
+                    name,
+                    &format!("{msg}, which logs every run twice; {NESTED_HINT}")
+                )
+            );
+            Some(EXIT_POLICY)
+        }
+        NestedMode::Warn => {
+            warn(&format!("cxrs {name}: {msg}; logged as nested"));
+            None
+        }
+        NestedMode::Allow => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn self_invocations_of_llm_commands_are_recognized() {
+        let me = PathBuf::from("/opt/tools/cxrs-dev");
+        assert!(is_self("cxrs", None));
+        assert!(is_self("/usr/local/bin/cx", None));
+        assert!(!is_self("git", Some(&me)));
+
+        assert_eq!(
+            llm_command(&argv(&["cxrs", "-v", "--backend", "mock", "cxo", "ls"])),
+            Some("cxo")
+        );
+        assert_eq!(llm_command(&argv(&["cx", "cxnext", "ls"])), Some("next"));
+        assert_eq!(llm_command(&argv(&["cxrs", "logs", "stats"])), None);
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fs;
+
+use crate::error::{EXIT_OK, print_runtime_error, print_usage_error};
+use crate::execmeta::utc_now_iso;
+use crate::logs::{append_jsonl, load_runs};
+use crate::paths::{resolve_log_file, resolve_notes_file};
+use crate::tasks::read_tasks;
+
+pub const ANNOTATE_USAGE: &str = "annotate <run <execution_id>|task <id>> \"<note>\"";
+
+/// A human note attached to a run (by execution id) or a task (by id).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Note {
+    pub ts: String,
+    /// `run` or `task`.
+    pub kind: String,
+    pub target: String,
+    pub note: String,
+}
+
+/// Every note in the store, oldest first. A missing store is empty; lines that
+/// do not parse are skipped so one bad edit does not hide the rest.
+pub fn read_notes() -> Vec<Note> {
+    let Some(path) = resolve_notes_file() else {
+        return Vec::new();
+    };
+    let Ok(text) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|l| serde_json::from_str::<Note>(l).ok())
+        .collect()
+}
+
+pub fn notes_for(notes: &[Note], kind: &str, target: &str) -> Vec<Note> {
+    notes
+        .iter()
+        .filter(|n| n.kind == kind && n.target == target)
+        .cloned()
+        .collect()
+}
+
+/// `[{"ts", "note"}]` for embedding next to the annotated run or task.
+pub fn notes_json(notes: &[Note]) -> Value {
+    notes
+        .iter()
+        .map(|n| json!({"ts": n.ts, "note": n.note}))
+        .collect()
+}
+
+fn run_exists(execution_id: &str) -> Result<bool, String> {
+    let log_file = resolve_log_file().ok_or("unable to resolve log file")?;
+    if !log_file.exists() {
+        return Ok(false);
+    }
+    let runs = load_runs(&log_file, 0)?;
+    Ok(runs
+        .iter()
+        .any(|r| r.execution_id.as_deref() == Some(execution_id)))
+}
+
+fn task_exists(id: &str) -> Result<bool, String> {
+    Ok(read_tasks()?.iter().any(|t| t.id == id))
+}
+
+/// `annotate run <execution_id> "<note>"` / `annotate task <id> "<note>"`:
+/// append a note for an existing run or task to the notes store.
+pub fn cmd_annotate(args: &[String]) -> i32 {
+    let (Some(kind), Some(target)) = (args.first(), args.get(1)) else {
+        return print_usage_error("annotate", ANNOTATE_USAGE);
+    };
+    let note = args[2..].join(" ");
+    if note.trim().is_empty() {
+        return print_usage_error("annotate", ANNOTATE_USAGE);
+    }
+    let exists = match kind.as_str() {
+        "run" => run_exists(target),
+        "task" => task_exists(target),
+        _ => return print_usage_error("annotate", ANNOTATE_USAGE),
+    };
+    match exists {
+        Ok(true) => {}
+        Ok(false) => {
+            return print_runtime_error("annotate", &format!("{kind} not found: {target}"));
+        }
+        Err(e) => return print_runtime_error("annotate", &e),
+    }
+    let Some(path) = resolve_notes_file() else {
+        return print_runtime_error("annotate", "unable to resolve notes file");
+    };
+    let entry = Note {
+        ts: utc_now_iso(),
+        kind: kind.clone(),
+        target: target.clone(),
+        note: note.trim().to_string(),
+    };
+    let value = match serde_json::to_value(&entry) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("annotate", &format!("failed to encode note: {e}")),
+    };
+    if let Err(e) = append_jsonl(&path, &value) {
+        return print_runtime_error("annotate", &e);
+    }
+    println!("Annotated {kind} {target}");
+    EXIT_OK
+}
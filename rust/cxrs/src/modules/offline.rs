@@ -0,0 +1,24 @@
+use crate::cli::LLM_TIMEOUT_COMMANDS;
+use crate::command_names::canonical_name;
+use crate::config::app_config;
+use crate::error::{EXIT_OFFLINE, ExitKind, format_error, note_failure};
+
+pub const OFFLINE_MESSAGE: &str =
+    "offline mode is on (CX_OFFLINE=1 / --offline); LLM and network calls are blocked";
+
+pub fn is_offline() -> bool {
+    app_config().offline
+}
+
+/// Refuse a command that always calls the LLM before it captures or spawns
+/// anything. Commands that only sometimes do (`task run`, `fanout --llm`) are
+/// stopped when they resolve a backend adapter.
+pub fn offline_block(cmd: &str) -> Option<i32> {
+    let name = canonical_name(cmd).unwrap_or(cmd);
+    if !is_offline() || !LLM_TIMEOUT_COMMANDS.contains(&name) {
+        return None;
+    }
+    note_failure(ExitKind::Offline);
+    crate::cx_eprintln!("{}", format_error(name, OFFLINE_MESSAGE));
+    Some(EXIT_OFFLINE)
+}
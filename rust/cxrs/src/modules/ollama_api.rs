@@ -0,0 +1,453 @@
+use serde_json::{Value, json};
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use crate::execmeta::utc_now_iso;
+use crate::offline::is_offline;
+use crate::paths::resolve_cache_dir;
+use crate::process::{
+    run_command_output_with_timeout, run_command_with_stdin_output_with_timeout,
+    run_command_with_stdin_streaming_meta,
+};
+use crate::runtime::{llm_backend, ollama_model_preference};
+use crate::state::write_json_atomic;
+use crate::verbosity::{Verbosity, diag};
+use crate::workspace::human_bytes;
+
+const DEFAULT_OLLAMA_HOST: &str = "http://127.0.0.1:11434";
+/// `num_ctx` Ollama runs with when neither the Modelfile nor
+/// `OLLAMA_CONTEXT_LENGTH` sets one.
+const OLLAMA_DEFAULT_NUM_CTX: u64 = 4096;
+/// Share of the context window given to the prompt; the rest is left for the
+/// instructions, schema and the answer.
+const PROMPT_SHARE_PCT: u64 = 75;
+const CURL_EXIT_CONNECT_FAILED: i32 = 7;
+
+/// What `/api/show` reports about a local model. `context_window` is what
+/// `ollama run` actually uses; `context_length` is the model's trained maximum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OllamaModelInfo {
+    pub name: String,
+    pub family: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization: Option<String>,
+    pub context_length: Option<u64>,
+    pub context_window: u64,
+    /// `num_ctx` (Modelfile parameter), `OLLAMA_CONTEXT_LENGTH` or `default`.
+    pub context_source: String,
+}
+
+impl OllamaModelInfo {
+    pub fn prompt_budget_tokens(&self) -> u64 {
+        self.context_window * PROMPT_SHARE_PCT / 100
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "family": self.family,
+            "parameter_size": self.parameter_size,
+            "quantization": self.quantization,
+            "context_length": self.context_length,
+            "context_window": self.context_window,
+            "context_source": self.context_source,
+            "prompt_budget_tokens": self.prompt_budget_tokens(),
+        })
+    }
+
+    fn from_json(v: &Value) -> Option<Self> {
+        let text = |k: &str| v.get(k).and_then(Value::as_str).map(str::to_string);
+        Some(Self {
+            name: text("name")?,
+            family: text("family"),
+            parameter_size: text("parameter_size"),
+            quantization: text("quantization"),
+            context_length: v.get("context_length").and_then(Value::as_u64),
+            context_window: v.get("context_window").and_then(Value::as_u64)?,
+            context_source: text("context_source").unwrap_or_else(|| "default".to_string()),
+        })
+    }
+}
+
+/// `OLLAMA_HOST` as a base URL (`host:port` gains `http://`).
+pub fn ollama_host() -> String {
+    let raw = env::var("OLLAMA_HOST")
+        .ok()
+        .map(|v| v.trim().trim_end_matches('/').to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_OLLAMA_HOST.to_string());
+    if raw.contains("://") {
+        raw
+    } else {
+        format!("http://{raw}")
+    }
+}
+
+fn curl(method: &str, path: &str, streaming: bool) -> Command {
+    let mut cmd = Command::new("curl");
+    cmd.args(["-sS", "--connect-timeout", "5", "-X", method]);
+    if streaming {
+        cmd.arg("-N");
+    }
+    if method == "POST" {
+        cmd.args([
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ]);
+    }
+    cmd.arg(format!("{}{path}", ollama_host()));
+    cmd
+}
+
+fn curl_failure(out: &Output) -> String {
+    if out.status.code() == Some(CURL_EXIT_CONNECT_FAILED) {
+        return format!(
+            "ollama API unreachable at {} (is `ollama serve` running?)",
+            ollama_host()
+        );
+    }
+    let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+    format!("ollama API request failed ({}): {stderr}", out.status)
+}
+
+/// Parse an API response body; `{"error": ...}` bodies become errors.
+fn api_body(out: Output) -> Result<Value, String> {
+    if !out.status.success() {
+        return Err(curl_failure(&out));
+    }
+    let body: Value = serde_json::from_slice(&out.stdout)
+        .map_err(|e| format!("ollama API returned invalid JSON: {e}"))?;
+    match body.get("error").and_then(Value::as_str) {
+        Some(err) => Err(format!("ollama API: {err}")),
+        None => Ok(body),
+    }
+}
+
+fn api_get(path: &str) -> Result<Value, String> {
+    let out = run_command_output_with_timeout(curl("GET", path, false), "ollama api")?;
+    api_body(out)
+}
+
+fn api_post(path: &str, body: &Value) -> Result<Value, String> {
+    let out = run_command_with_stdin_output_with_timeout(
+        curl("POST", path, false),
+        &body.to_string(),
+        "ollama api",
+    )?;
+    api_body(out)
+}
+
+/// Local models from `/api/tags`, sorted by name.
+pub fn list_models() -> Result<Vec<Value>, String> {
+    let body = api_get("/api/tags")?;
+    let mut models: Vec<Value> = body
+        .get("models")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| {
+            let details = m.get("details").cloned().unwrap_or(Value::Null);
+            json!({
+                "name": m.get("name").or_else(|| m.get("model")).cloned().unwrap_or(Value::Null),
+                "size_bytes": m.get("size").cloned().unwrap_or(Value::Null),
+                "family": details.get("family").cloned().unwrap_or(Value::Null),
+                "parameter_size": details.get("parameter_size").cloned().unwrap_or(Value::Null),
+                "quantization": details.get("quantization_level").cloned().unwrap_or(Value::Null),
+                "modified_at": m.get("modified_at").cloned().unwrap_or(Value::Null),
+            })
+        })
+        .collect();
+    models.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+    Ok(models)
+}
+
+/// `num_ctx` from the Modelfile `parameters` text (`num_ctx    8192`).
+fn num_ctx_parameter(parameters: &str) -> Option<u64> {
+    parameters.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        (parts.next() == Some("num_ctx"))
+            .then(|| parts.next()?.parse().ok())
+            .flatten()
+    })
+}
+
+/// Build model info from an `/api/show` body. The effective window is the
+/// Modelfile `num_ctx`, else `OLLAMA_CONTEXT_LENGTH`, else Ollama's default,
+/// never above the trained `<arch>.context_length`.
+fn model_info_from_show(name: &str, show: &Value, env_ctx: Option<u64>) -> OllamaModelInfo {
+    let details = show.get("details").cloned().unwrap_or(Value::Null);
+    let text = |k: &str| details.get(k).and_then(Value::as_str).map(str::to_string);
+    let context_length = show
+        .get("model_info")
+        .and_then(Value::as_object)
+        .and_then(|info| {
+            info.iter()
+                .find(|(k, _)| k.ends_with(".context_length"))
+                .and_then(|(_, v)| v.as_u64())
+        });
+    let num_ctx = show
+        .get("parameters")
+        .and_then(Value::as_str)
+        .and_then(num_ctx_parameter);
+    let (window, source) = match (num_ctx, env_ctx) {
+        (Some(n), _) => (n, "num_ctx"),
+        (None, Some(n)) => (n, "OLLAMA_CONTEXT_LENGTH"),
+        (None, None) => (OLLAMA_DEFAULT_NUM_CTX, "default"),
+    };
+    OllamaModelInfo {
+        name: name.to_string(),
+        family: text("family"),
+        parameter_size: text("parameter_size"),
+        quantization: text("quantization_level"),
+        context_length,
+        context_window: context_length.map_or(window, |max| window.min(max)),
+        context_source: source.to_string(),
+    }
+}
+
+fn env_context_length() -> Option<u64> {
+    env::var("OLLAMA_CONTEXT_LENGTH")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|v| *v > 0)
+}
+
+fn cache_file() -> Option<PathBuf> {
+    resolve_cache_dir().map(|d| d.join("ollama_models.json"))
+}
+
+fn read_cache() -> Value {
+    cache_file()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .filter(Value::is_object)
+        .unwrap_or_else(|| json!({"models": {}}))
+}
+
+fn cached_model_info(name: &str) -> Option<OllamaModelInfo> {
+    read_cache()
+        .get("models")
+        .and_then(|m| m.get(name))
+        .and_then(OllamaModelInfo::from_json)
+}
+
+fn store_model_info(info: &OllamaModelInfo) -> Result<(), String> {
+    let Some(path) = cache_file() else {
+        return Err("unable to resolve cache dir".to_string());
+    };
+    let mut cache = read_cache();
+    let mut entry = info.to_json();
+    entry["inspected_at"] = json!(utc_now_iso());
+    if !cache.get("models").is_some_and(Value::is_object) {
+        cache["models"] = json!({});
+    }
+    cache["models"][&info.name] = entry;
+    write_json_atomic(&path, &cache)
+}
+
+/// Query `/api/show` for `name` and refresh its cache entry.
+pub fn inspect_model(name: &str) -> Result<OllamaModelInfo, String> {
+    let show = api_post("/api/show", &json!({ "model": name }))?;
+    let info = model_info_from_show(name, &show, env_context_length());
+    if let Err(e) = store_model_info(&info) {
+        diag(
+            Verbosity::Verbose,
+            "ollama",
+            &[("cache_write_failed", e.to_string())],
+        );
+    }
+    Ok(info)
+}
+
+/// Model info for `model` from the cache, inspected once when missing and
+/// `inspect` allows it (never offline).
+pub fn known_model_info(model: &str, inspect: bool) -> Option<OllamaModelInfo> {
+    if let Some(info) = cached_model_info(model) {
+        return Some(info);
+    }
+    if !inspect || is_offline() {
+        return None;
+    }
+    match inspect_model(model) {
+        Ok(info) => Some(info),
+        Err(e) => {
+            diag(
+                Verbosity::Verbose,
+                "ollama",
+                &[("model", model.to_string()), ("inspect_failed", e)],
+            );
+            None
+        }
+    }
+}
+
+/// Prompt token budget for the active Ollama model: a share of its context
+/// window. `None` for other backends or when the window is unknown.
+pub fn model_prompt_budget_tokens() -> Option<usize> {
+    if llm_backend() != "ollama" {
+        return None;
+    }
+    let model = ollama_model_preference();
+    if model.trim().is_empty() {
+        return None;
+    }
+    known_model_info(&model, true).map(|info| info.prompt_budget_tokens() as usize)
+}
+
+/// Renders `/api/pull` progress events on stderr: redrawn in place on a
+/// terminal, otherwise one line per status change.
+struct PullProgress {
+    tty: bool,
+    last_status: String,
+    pending: String,
+    error: Option<String>,
+    success: bool,
+}
+
+impl PullProgress {
+    fn line(event: &Value) -> String {
+        let status = event.get("status").and_then(Value::as_str).unwrap_or("");
+        let total = event.get("total").and_then(Value::as_u64).unwrap_or(0);
+        let done = event.get("completed").and_then(Value::as_u64).unwrap_or(0);
+        if total == 0 {
+            return status.to_string();
+        }
+        format!(
+            "{status} {}% ({}/{})",
+            done * 100 / total,
+            human_bytes(done),
+            human_bytes(total)
+        )
+    }
+
+    fn event(&mut self, event: &Value) {
+        if let Some(err) = event.get("error").and_then(Value::as_str) {
+            self.error = Some(err.to_string());
+            return;
+        }
+        let status = event.get("status").and_then(Value::as_str).unwrap_or("");
+        if status == "success" {
+            self.success = true;
+        }
+        if self.tty {
+            let _ = write!(io::stderr(), "\r\x1b[2K{}", Self::line(event));
+            let _ = io::stderr().flush();
+        } else if status != self.last_status {
+            crate::cx_eprintln!("{}", Self::line(event));
+        }
+        self.last_status = status.to_string();
+    }
+
+    fn feed(&mut self, bytes: &[u8]) {
+        self.pending.push_str(&String::from_utf8_lossy(bytes));
+        while let Some(pos) = self.pending.find('\n') {
+            let line: String = self.pending.drain(..=pos).collect();
+            if let Ok(v) = serde_json::from_str::<Value>(line.trim()) {
+                self.event(&v);
+            }
+        }
+    }
+}
+
+/// Pull `name` through `/api/pull`, reporting progress on stderr, then cache
+/// its model info.
+pub fn pull_model(name: &str) -> Result<OllamaModelInfo, String> {
+    let mut progress = PullProgress {
+        tty: io::stderr().is_terminal(),
+        last_status: String::new(),
+        pending: String::new(),
+        error: None,
+        success: false,
+    };
+    let body = json!({ "model": name, "stream": true }).to_string();
+    let out = run_command_with_stdin_streaming_meta(
+        curl("POST", "/api/pull", true),
+        &body,
+        "ollama pull",
+        &mut |b| progress.feed(b),
+    )
+    .map_err(|e| e.to_string())?;
+    progress.feed(b"\n");
+    if progress.tty {
+        let _ = writeln!(io::stderr());
+    }
+    if !out.status.success() {
+        return Err(curl_failure(&out));
+    }
+    if let Some(err) = progress.error {
+        return Err(format!("ollama API: {err}"));
+    }
+    if !progress.success {
+        return Err(format!("pull of '{name}' ended without a success status"));
+    }
+    inspect_model(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn show() -> Value {
+        json!({
+            "details": {"family": "llama", "parameter_size": "8.0B", "quantization_level": "Q4_K_M"},
+            "model_info": {"general.architecture": "llama", "llama.context_length": 131072},
+            "parameters": "stop \"<|eot_id|>\"\nnum_ctx 8192"
+        })
+    }
+
+    #[test]
+    fn context_window_prefers_num_ctx_then_env_then_default() {
+        let info = model_info_from_show("llama3.1:8b", &show(), Some(16384));
+        assert_eq!(
+            (info.context_window, info.context_source.as_str()),
+            (8192, "num_ctx")
+        );
+        assert_eq!(info.context_length, Some(131072));
+        assert_eq!(info.quantization.as_deref(), Some("Q4_K_M"));
+        assert_eq!(info.prompt_budget_tokens(), 6144);
+
+        let mut no_param = show();
+        no_param["parameters"] = json!("stop \"<|eot_id|>\"");
+        let info = model_info_from_show("m", &no_param, Some(16384));
+        assert_eq!(info.context_window, 16384);
+        let info = model_info_from_show("m", &no_param, None);
+        assert_eq!(
+            (info.context_window, info.context_source.as_str()),
+            (4096, "default")
+        );
+
+        no_param["model_info"]["llama.context_length"] = json!(2048);
+        let info = model_info_from_show("m", &no_param, None);
+        assert_eq!(info.context_window, 2048);
+    }
+
+    #[test]
+    fn pull_progress_parses_split_lines_and_errors() {
+        let mut p = PullProgress {
+            tty: false,
+            last_status: String::new(),
+            pending: String::new(),
+            error: None,
+            success: false,
+        };
+        p.feed(b"{\"status\":\"pulling manifest\"}\n{\"status\":\"down");
+        assert_eq!(p.last_status, "pulling manifest");
+        p.feed(b"loading\",\"total\":2048,\"completed\":1024}\n{\"status\":\"success\"}\n");
+        assert!(p.success);
+        p.feed(b"{\"error\":\"pull model manifest: file does not exist\"}\n");
+        assert_eq!(
+            p.error.as_deref(),
+            Some("pull model manifest: file does not exist")
+        );
+        assert_eq!(
+            PullProgress::line(&json!({"status": "downloading", "total": 2048, "completed": 1024})),
+            "downloading 50% (1.0 KiB/2.0 KiB)"
+        );
+    }
+}
@@ -116,6 +116,24 @@ fn print_retry_health(sb: &Value) {
     println!("retry_attempt_histogram: {hist}");
 }
 
+fn print_backend_failover(sb: &Value) {
+    let Some(bf) = sb.get("backend_failover") else {
+        println!("backend_failover: n/a");
+        return;
+    };
+    let runs = bf
+        .get("runs_with_failover")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    let failovers = bf.get("failovers").and_then(Value::as_u64).unwrap_or(0);
+    let rate = bf
+        .get("rate")
+        .and_then(Value::as_f64)
+        .map(|v| format!("{}%", (v * 100.0).round() as i64))
+        .unwrap_or_else(|| "n/a".to_string());
+    println!("backend_failover: {rate} ({runs} runs, {failovers} failed backend calls)");
+}
+
 fn print_timeout_frequency(sb: &Value) {
     let Some(tf) = sb.get("timeout_frequency") else {
         println!("timeout_frequency: n/a");
@@ -187,6 +205,17 @@ fn print_scoreboard(sb: &Value) {
             .and_then(Value::as_array),
         "",
     );
+    print_tool_pairs(
+        "top_by_p90_duration_ms:",
+        sb.get("top_p90_duration_ms").and_then(Value::as_array),
+        "ms",
+    );
+    print_tool_pairs(
+        "top_by_p90_effective_tokens:",
+        sb.get("top_p90_effective_input_tokens")
+            .and_then(Value::as_array),
+        "",
+    );
 
     if let Some(c) = sb.get("budget_clipping_frequency") {
         match c.get("rate").and_then(Value::as_f64) {
@@ -195,6 +224,7 @@ fn print_scoreboard(sb: &Value) {
         }
     }
     print_timeout_frequency(sb);
+    print_backend_failover(sb);
     print_retry_health(sb);
     print_capture_compression(sb);
 }
@@ -213,8 +243,8 @@ fn print_list_section(title: &str, arr: Option<&Vec<Value>>, empty: &str) {
 }
 
 pub fn print_optimize(args: OptimizeArgs) -> i32 {
-    let (n, json_out, include_actions, strict, severity_floor) = args;
-    let report = match optimize_report(n) {
+    let (n, json_out, include_actions, strict, severity_floor, filter) = args;
+    let report = match optimize_report(n, &filter) {
         Ok(v) => v,
         Err(e) => {
             crate::cx_eprintln!("cxrs optimize: {e}");
@@ -242,6 +272,9 @@ pub fn print_optimize(args: OptimizeArgs) -> i32 {
     }
 
     println!("== cxrs optimize (last {n} runs) ==");
+    if !filter.is_empty() {
+        println!("Filter: {}", filter.label());
+    }
     let sb = report
         .get("scoreboard")
         .cloned()
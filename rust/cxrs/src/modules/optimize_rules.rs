@@ -7,24 +7,26 @@ pub struct RecommendationInput<'a> {
     pub top_timeout_labels: &'a [(String, u64)],
     pub retry_rows_rate: Option<f64>,
     pub retry_recovery_rate: Option<f64>,
+    /// Findings from `.codex/bench.jsonl` (see `bench_history`).
+    pub bench: &'a [String],
 }
 
 pub fn push_latency_anomaly(anomalies: &mut Vec<String>, top_dur: &[(String, u64)], max_ms: u64) {
-    if let Some((tool, avg)) = top_dur.first()
-        && *avg > max_ms / 2
+    if let Some((tool, p90)) = top_dur.first()
+        && *p90 > max_ms / 2
     {
         anomalies.push(format!(
-            "High latency concentration: {tool} avg_duration_ms={avg}"
+            "High latency concentration: {tool} p90_duration_ms={p90}"
         ));
     }
 }
 
 pub fn push_token_anomaly(anomalies: &mut Vec<String>, top_eff: &[(String, u64)], max_eff: u64) {
-    if let Some((tool, avg)) = top_eff.first()
-        && *avg > max_eff / 2
+    if let Some((tool, p90)) = top_eff.first()
+        && *p90 > max_eff / 2
     {
         anomalies.push(format!(
-            "High token load concentration: {tool} avg_effective_input_tokens={avg}"
+            "High token load concentration: {tool} p90_effective_input_tokens={p90}"
         ));
     }
 }
@@ -67,6 +69,17 @@ pub fn push_clip_anomaly(anomalies: &mut Vec<String>, clip_freq: Option<f64>) {
     }
 }
 
+pub fn push_failover_anomaly(anomalies: &mut Vec<String>, failover_freq: Option<f64>) {
+    if let Some(freq) = failover_freq
+        && freq > 0.10
+    {
+        anomalies.push(format!(
+            "Backend failover frequent: {}% of runs fell back past the primary backend",
+            (freq * 100.0).round() as i64
+        ));
+    }
+}
+
 pub fn push_timeout_anomaly(anomalies: &mut Vec<String>, timeout_freq: Option<f64>) {
     if let Some(freq) = timeout_freq
         && freq > 0.03
@@ -111,11 +124,12 @@ pub fn build_recommendations(input: RecommendationInput<'_>) -> Vec<String> {
         top_timeout_labels,
         retry_rows_rate,
         retry_recovery_rate,
+        bench,
     } = input;
     let mut recommendations: Vec<String> = Vec::new();
-    if let Some((tool, avg_eff)) = top_eff.first() {
+    if let Some((tool, p90_eff)) = top_eff.first() {
         recommendations.push(format!(
-            "{tool} exceeds average token threshold ({avg_eff}); recommend lean mode."
+            "{tool} has the heaviest p90 token load ({p90_eff}); recommend lean mode."
         ));
     }
     if let (Some(a), Some(b)) = (first_cache, second_cache)
@@ -157,6 +171,7 @@ pub fn build_recommendations(input: RecommendationInput<'_>) -> Vec<String> {
             (rate * 100.0).round() as i64
         ));
     }
+    recommendations.extend(bench.iter().cloned());
     if recommendations.is_empty() {
         recommendations.push("No significant anomalies in this window.".to_string());
     }
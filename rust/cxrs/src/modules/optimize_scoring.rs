@@ -0,0 +1,326 @@
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+use crate::alert_sinks::AlertPolicy;
+use crate::optimize_rules::{
+    push_cache_anomaly, push_clip_anomaly, push_failover_anomaly, push_latency_anomaly,
+    push_retry_anomaly, push_schema_anomaly, push_timeout_anomaly, push_token_anomaly,
+};
+use crate::percentiles::percentiles_by_tool;
+use crate::types::RunEntry;
+
+/// Counters collected run by run over the report window.
+#[derive(Default)]
+pub struct Agg {
+    pub tool_eff: HashMap<String, (u64, u64)>,
+    pub tool_dur: HashMap<String, (u64, u64)>,
+    pub timeout_labels: HashMap<String, u64>,
+    pub provider_stats: HashMap<String, (u64, u64, u64, u64)>,
+    pub alerts: u64,
+    pub schema_fails: u64,
+    pub schema_total: u64,
+    pub clipped_count: u64,
+    pub clipped_total: u64,
+    pub timeout_count: u64,
+    pub failover_runs: u64,
+    pub failover_total: u64,
+    pub sum_in: u64,
+    pub sum_cached: u64,
+    pub retry_rows_after_retry: u64,
+    pub retry_rows_after_retry_success: u64,
+    pub retry_task_timeout_seen: HashMap<String, bool>,
+    pub retry_task_recovered: HashMap<String, bool>,
+    pub retry_attempt_histogram: HashMap<u64, u64>,
+}
+
+impl Agg {
+    fn ingest(&mut self, r: &RunEntry, policy: &AlertPolicy) {
+        let tool = r.tool.clone().unwrap_or_else(|| "unknown".to_string());
+        let limits = policy.for_tool(&tool);
+        let eff = r.effective_input_tokens.unwrap_or(0);
+        let dur = r.duration_ms.unwrap_or(0);
+        let eff_entry = self.tool_eff.entry(tool.clone()).or_insert((0, 0));
+        eff_entry.0 += eff;
+        eff_entry.1 += 1;
+        let dur_entry = self.tool_dur.entry(tool).or_insert((0, 0));
+        dur_entry.0 += dur;
+        dur_entry.1 += 1;
+        if dur > limits.max_ms || eff > limits.max_eff_in {
+            self.alerts += 1;
+        }
+        if r.schema_enforced.unwrap_or(false) {
+            self.schema_total += 1;
+            if r.schema_valid == Some(false) {
+                self.schema_fails += 1;
+            }
+        }
+        if r.timed_out.unwrap_or(false) {
+            self.timeout_count += 1;
+            let label = r
+                .command_label
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string());
+            *self.timeout_labels.entry(label).or_insert(0) += 1;
+        }
+        if let Some(n) = r.failover_count.filter(|n| *n > 0) {
+            self.failover_runs += 1;
+            self.failover_total += n;
+        }
+        if let Some(attempt) = r.retry_attempt.map(u64::from) {
+            *self.retry_attempt_histogram.entry(attempt).or_insert(0) += 1;
+            if attempt > 1 {
+                self.retry_rows_after_retry += 1;
+                if !r.timed_out.unwrap_or(false)
+                    && r.policy_blocked != Some(true)
+                    && r.schema_valid != Some(false)
+                {
+                    self.retry_rows_after_retry_success += 1;
+                }
+            }
+            if let Some(task_id) = r.task_id.as_ref()
+                && !task_id.trim().is_empty()
+            {
+                self.retry_task_timeout_seen
+                    .entry(task_id.clone())
+                    .or_insert(false);
+                self.retry_task_recovered
+                    .entry(task_id.clone())
+                    .or_insert(false);
+                if r.timed_out.unwrap_or(false) {
+                    self.retry_task_timeout_seen.insert(task_id.clone(), true);
+                } else if attempt > 1 {
+                    self.retry_task_recovered.insert(task_id.clone(), true);
+                }
+            }
+        }
+        if r.clipped.is_some() {
+            self.clipped_total += 1;
+            if r.clipped == Some(true) {
+                self.clipped_count += 1;
+            }
+        }
+        if let Some(provider) = r.capture_provider.as_ref() {
+            let entry = self
+                .provider_stats
+                .entry(provider.clone())
+                .or_insert((0, 0, 0, 0));
+            entry.0 += r.system_output_len_raw.unwrap_or(0);
+            entry.1 += r.system_output_len_processed.unwrap_or(0);
+            entry.2 += r.system_output_len_clipped.unwrap_or(0);
+            entry.3 += 1;
+        }
+        self.sum_in += r.input_tokens.unwrap_or(0);
+        self.sum_cached += r.cached_input_tokens.unwrap_or(0);
+    }
+}
+
+fn top_avg(map: HashMap<String, (u64, u64)>) -> Vec<(String, u64)> {
+    let mut rows: Vec<(String, u64)> = map
+        .into_iter()
+        .map(|(tool, (sum, count))| (tool, sum.checked_div(count).unwrap_or(0)))
+        .collect();
+    rows.sort_by_key(|b| std::cmp::Reverse(b.1));
+    rows.truncate(5);
+    rows
+}
+
+/// Top 5 tools by p90 of `field`; tail latency drives the recommendations
+/// because a few slow runs vanish in the mean.
+fn top_p90(runs: &[RunEntry], field: fn(&RunEntry) -> Option<u64>) -> Vec<(String, u64)> {
+    let samples = runs.iter().filter_map(|r| {
+        let tool = r.tool.clone().unwrap_or_else(|| "unknown".to_string());
+        field(r).map(|v| (tool, v))
+    });
+    let mut rows: Vec<(String, u64)> = percentiles_by_tool(samples)
+        .into_iter()
+        .map(|(tool, p)| (tool, p.p90))
+        .collect();
+    rows.sort_by_key(|b| std::cmp::Reverse(b.1));
+    rows.truncate(5);
+    rows
+}
+
+fn cache_halves(runs: &[RunEntry]) -> (Option<f64>, Option<f64>) {
+    let mid = runs.len() / 2;
+    let (first, second) = runs.split_at(mid.max(1).min(runs.len()));
+    let first_in: u64 = first.iter().map(|r| r.input_tokens.unwrap_or(0)).sum();
+    let first_cached: u64 = first
+        .iter()
+        .map(|r| r.cached_input_tokens.unwrap_or(0))
+        .sum();
+    let second_in: u64 = second.iter().map(|r| r.input_tokens.unwrap_or(0)).sum();
+    let second_cached: u64 = second
+        .iter()
+        .map(|r| r.cached_input_tokens.unwrap_or(0))
+        .sum();
+    (
+        (first_in > 0).then_some(first_cached as f64 / first_in as f64),
+        (second_in > 0).then_some(second_cached as f64 / second_in as f64),
+    )
+}
+
+fn compression_rows(provider_stats: HashMap<String, (u64, u64, u64, u64)>) -> Vec<Value> {
+    let mut rows: Vec<Value> = provider_stats
+        .into_iter()
+        .map(|(provider, (raw, processed, clipped, count))| {
+            json!({
+                "provider": provider,
+                "runs": count,
+                "raw_sum": raw,
+                "processed_sum": processed,
+                "clipped_sum": clipped,
+                "processed_over_raw": if raw == 0 { Value::Null } else { json!((processed as f64) / (raw as f64)) },
+                "clipped_over_raw": if raw == 0 { Value::Null } else { json!((clipped as f64) / (raw as f64)) }
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        let ar = a
+            .get("processed_over_raw")
+            .and_then(Value::as_f64)
+            .unwrap_or(1.0);
+        let br = b
+            .get("processed_over_raw")
+            .and_then(Value::as_f64)
+            .unwrap_or(1.0);
+        ar.partial_cmp(&br).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    rows
+}
+
+pub struct AnomalyInput<'a> {
+    pub top_dur: &'a [(String, u64)],
+    pub top_eff: &'a [(String, u64)],
+    pub max_ms: u64,
+    pub max_eff: u64,
+    pub first_cache: Option<f64>,
+    pub second_cache: Option<f64>,
+    pub schema_fail_freq: Option<f64>,
+    pub clip_freq: Option<f64>,
+    pub timeout_freq: Option<f64>,
+    pub failover_freq: Option<f64>,
+    pub retry_rows_rate: Option<f64>,
+    pub retry_recovery_rate: Option<f64>,
+}
+
+pub fn build_anomalies(input: AnomalyInput<'_>) -> Vec<String> {
+    let AnomalyInput {
+        top_dur,
+        top_eff,
+        max_ms,
+        max_eff,
+        first_cache,
+        second_cache,
+        schema_fail_freq,
+        clip_freq,
+        timeout_freq,
+        failover_freq,
+        retry_rows_rate,
+        retry_recovery_rate,
+    } = input;
+    let mut anomalies: Vec<String> = Vec::new();
+    push_latency_anomaly(&mut anomalies, top_dur, max_ms);
+    push_token_anomaly(&mut anomalies, top_eff, max_eff);
+    push_cache_anomaly(&mut anomalies, first_cache, second_cache);
+    push_schema_anomaly(&mut anomalies, schema_fail_freq);
+    push_clip_anomaly(&mut anomalies, clip_freq);
+    push_timeout_anomaly(&mut anomalies, timeout_freq);
+    push_failover_anomaly(&mut anomalies, failover_freq);
+    push_retry_anomaly(&mut anomalies, retry_rows_rate, retry_recovery_rate);
+    anomalies
+}
+
+/// Rates and top-5 lists computed from an [`Agg`].
+pub struct Derived {
+    pub top_eff: Vec<(String, u64)>,
+    pub top_dur: Vec<(String, u64)>,
+    pub p90_eff: Vec<(String, u64)>,
+    pub p90_dur: Vec<(String, u64)>,
+    pub top_timeout_labels: Vec<(String, u64)>,
+    pub cache_all: Option<f64>,
+    pub first_cache: Option<f64>,
+    pub second_cache: Option<f64>,
+    pub clip_freq: Option<f64>,
+    pub schema_fail_freq: Option<f64>,
+    pub timeout_freq: Option<f64>,
+    pub failover_freq: Option<f64>,
+    pub compression: Vec<Value>,
+    pub retry_rows_rate: Option<f64>,
+    pub retry_rows_success_rate: Option<f64>,
+    pub retry_tasks_recovery_rate: Option<f64>,
+    pub retry_tasks_with_timeout: u64,
+    pub retry_tasks_recovered: u64,
+    pub retry_attempt_histogram: Vec<(u64, u64)>,
+}
+
+fn derive_metrics(runs: &[RunEntry], agg: Agg) -> (Agg, Derived) {
+    let top_eff = top_avg(agg.tool_eff.clone());
+    let top_dur = top_avg(agg.tool_dur.clone());
+    let p90_eff = top_p90(runs, |r| r.effective_input_tokens);
+    let p90_dur = top_p90(runs, |r| r.duration_ms);
+    let mut top_timeout_labels: Vec<(String, u64)> =
+        agg.timeout_labels.clone().into_iter().collect();
+    top_timeout_labels.sort_by_key(|b| std::cmp::Reverse(b.1));
+    top_timeout_labels.truncate(5);
+    let cache_all = (agg.sum_in > 0).then_some(agg.sum_cached as f64 / agg.sum_in as f64);
+    let (first_cache, second_cache) = cache_halves(runs);
+    let clip_freq =
+        (agg.clipped_total > 0).then_some(agg.clipped_count as f64 / agg.clipped_total as f64);
+    let schema_fail_freq =
+        (agg.schema_total > 0).then_some(agg.schema_fails as f64 / agg.schema_total as f64);
+    let timeout_freq = (!runs.is_empty()).then_some(agg.timeout_count as f64 / runs.len() as f64);
+    let failover_freq = (!runs.is_empty()).then_some(agg.failover_runs as f64 / runs.len() as f64);
+    let retry_rows_rate =
+        (!runs.is_empty()).then_some(agg.retry_rows_after_retry as f64 / runs.len() as f64);
+    let retry_rows_success_rate = (agg.retry_rows_after_retry > 0)
+        .then_some(agg.retry_rows_after_retry_success as f64 / agg.retry_rows_after_retry as f64);
+    let retry_tasks_with_timeout = agg
+        .retry_task_timeout_seen
+        .iter()
+        .filter(|(_, saw)| **saw)
+        .count() as u64;
+    let retry_tasks_recovered = agg
+        .retry_task_timeout_seen
+        .iter()
+        .filter(|(task, saw)| **saw && agg.retry_task_recovered.get(*task) == Some(&true))
+        .count() as u64;
+    let retry_tasks_recovery_rate = (retry_tasks_with_timeout > 0)
+        .then_some(retry_tasks_recovered as f64 / retry_tasks_with_timeout as f64);
+    let mut retry_attempt_histogram: Vec<(u64, u64)> =
+        agg.retry_attempt_histogram.clone().into_iter().collect();
+    retry_attempt_histogram.sort_by_key(|a| a.0);
+    let compression = compression_rows(agg.provider_stats.clone());
+    (
+        agg,
+        Derived {
+            top_eff,
+            top_dur,
+            p90_eff,
+            p90_dur,
+            top_timeout_labels,
+            cache_all,
+            first_cache,
+            second_cache,
+            clip_freq,
+            schema_fail_freq,
+            timeout_freq,
+            failover_freq,
+            compression,
+            retry_rows_rate,
+            retry_rows_success_rate,
+            retry_tasks_recovery_rate,
+            retry_tasks_with_timeout,
+            retry_tasks_recovered,
+            retry_attempt_histogram,
+        },
+    )
+}
+
+/// Score `runs` against the per-tool alert `policy`.
+pub fn analyze_runs(runs: &[RunEntry], policy: &AlertPolicy) -> (Agg, Derived) {
+    let mut agg = Agg::default();
+    for r in runs {
+        agg.ingest(r, policy);
+    }
+    derive_metrics(runs, agg)
+}
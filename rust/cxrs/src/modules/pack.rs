@@ -0,0 +1,339 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::capture::chunk_text_by_budget;
+use crate::config::app_config;
+use crate::error::{EXIT_OK, format_error, print_runtime_error, print_usage_error};
+use crate::policy::glob_to_regex;
+use crate::runlog::log_pack_run;
+use crate::token_estimate::{TokenizerKind, clip_to_token_budget, estimate_tokens};
+use crate::verbosity::warn;
+
+const PACK_USAGE: &str = "pack [--chunk] <path|dir|glob>...";
+/// Hard cap on files in one bundle; globs over large trees stop here.
+const PACK_MAX_FILES: usize = 200;
+const SKIP_DIRS: [&str; 4] = [".git", "target", "node_modules", ".codex"];
+
+#[derive(Debug)]
+struct PackFile {
+    path: String,
+    text: Option<String>,
+    bytes: usize,
+    skipped: Option<&'static str>,
+}
+
+fn is_glob(arg: &str) -> bool {
+    arg.contains('*') || arg.contains('?')
+}
+
+fn display_path(path: &Path) -> String {
+    let s = path.to_string_lossy().replace('\\', "/");
+    s.strip_prefix("./").unwrap_or(&s).to_string()
+}
+
+fn walk_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    entries.sort();
+    for path in entries {
+        if out.len() >= PACK_MAX_FILES {
+            return;
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if path.is_dir() {
+            if !SKIP_DIRS.contains(&name) {
+                walk_files(&path, out);
+            }
+        } else if path.is_file() {
+            out.push(path);
+        }
+    }
+}
+
+/// Expand files, directories (recursively) and globs relative to the cwd, in argument order.
+fn expand_paths(args: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut out: Vec<PathBuf> = Vec::new();
+    let mut tree: Option<Vec<PathBuf>> = None;
+    for arg in args {
+        let found: Vec<PathBuf> = if is_glob(arg) {
+            let re = glob_to_regex(arg.strip_prefix("./").unwrap_or(arg))?;
+            let all = tree.get_or_insert_with(|| {
+                let mut v = Vec::new();
+                walk_files(Path::new("."), &mut v);
+                v
+            });
+            all.iter()
+                .filter(|p| re.is_match(&display_path(p)))
+                .cloned()
+                .collect()
+        } else {
+            let path = PathBuf::from(arg);
+            if path.is_dir() {
+                let mut v = Vec::new();
+                walk_files(&path, &mut v);
+                v
+            } else if path.is_file() {
+                vec![path]
+            } else {
+                return Err(format!("no such file or directory: {arg}"));
+            }
+        };
+        if found.is_empty() {
+            return Err(format!("no files matched '{arg}'"));
+        }
+        for p in found {
+            if !out.contains(&p) {
+                out.push(p);
+            }
+        }
+    }
+    out.truncate(PACK_MAX_FILES);
+    Ok(out)
+}
+
+fn read_pack_file(path: &Path) -> PackFile {
+    let display = display_path(path);
+    match fs::read(path) {
+        Ok(bytes) if bytes.iter().take(8192).any(|b| *b == 0) => PackFile {
+            path: display,
+            text: None,
+            bytes: bytes.len(),
+            skipped: Some("binary"),
+        },
+        Ok(bytes) => PackFile {
+            path: display,
+            bytes: bytes.len(),
+            text: Some(String::from_utf8_lossy(&bytes).into_owned()),
+            skipped: None,
+        },
+        Err(_) => PackFile {
+            path: display,
+            text: None,
+            bytes: 0,
+            skipped: Some("unreadable"),
+        },
+    }
+}
+
+/// Water-fill `budget` across files: small files keep everything and their
+/// unused share is split among the larger ones.
+fn allocate(sizes: &[usize], budget: usize) -> Vec<usize> {
+    let mut alloc = vec![0usize; sizes.len()];
+    let mut open: Vec<usize> = (0..sizes.len()).filter(|i| sizes[*i] > 0).collect();
+    let mut remaining = budget;
+    while !open.is_empty() && remaining > 0 {
+        let share = (remaining / open.len()).max(1);
+        let mut next = Vec::new();
+        for &i in &open {
+            let want = sizes[i] - alloc[i];
+            let give = want.min(share).min(remaining);
+            alloc[i] += give;
+            remaining -= give;
+            if alloc[i] < sizes[i] {
+                next.push(i);
+            }
+        }
+        open = next;
+    }
+    alloc
+}
+
+/// Head of `text` within `limit` chars, backing off to a line boundary when one is close.
+fn truncate_chars(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let head: String = text.chars().take(limit).collect();
+    match head.rfind('\n') {
+        Some(idx) if idx >= head.len() / 2 => head[..idx].to_string(),
+        _ => head,
+    }
+}
+
+fn fence_lang(path: &str) -> &'static str {
+    match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("rs") => "rust",
+        Some("py") => "python",
+        Some("js" | "mjs" | "cjs") => "javascript",
+        Some("ts" | "tsx") => "typescript",
+        Some("sh" | "bash") => "bash",
+        Some("json") => "json",
+        Some("toml") => "toml",
+        Some("yml" | "yaml") => "yaml",
+        Some("md") => "markdown",
+        Some("go") => "go",
+        Some("c" | "h") => "c",
+        Some("cpp" | "hpp" | "cc") => "cpp",
+        Some("java") => "java",
+        Some("rb") => "ruby",
+        _ => "",
+    }
+}
+
+/// A backtick fence longer than any backtick run inside `body`.
+fn fence_for(body: &str) -> String {
+    let mut longest = 0usize;
+    let mut run = 0usize;
+    for c in body.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    "`".repeat(longest.max(2) + 1)
+}
+
+struct PackOutcome {
+    bundle: String,
+    manifest: Vec<Value>,
+    bytes_total: usize,
+    bytes_kept: usize,
+}
+
+/// Per-file char/token limits. A shared bundle water-fills the budget across
+/// files; a chunked bundle caps each file at the budget instead.
+fn file_limits(
+    texts: &[&str],
+    budget: usize,
+    per_file: bool,
+    measure: impl Fn(&str) -> usize,
+) -> Vec<usize> {
+    let sizes: Vec<usize> = texts.iter().map(|t| measure(t)).collect();
+    if per_file {
+        sizes.iter().map(|n| (*n).min(budget)).collect()
+    } else {
+        allocate(&sizes, budget)
+    }
+}
+
+fn build_bundle(
+    files: &[PackFile],
+    budget_chars: usize,
+    budget_tokens: Option<usize>,
+    per_file: bool,
+) -> PackOutcome {
+    let tokenizer = TokenizerKind::for_backend(&app_config().llm_backend);
+    let texts: Vec<&str> = files
+        .iter()
+        .map(|f| f.text.as_deref().unwrap_or(""))
+        .collect();
+    let char_alloc = file_limits(&texts, budget_chars, per_file, |t| t.chars().count());
+    let token_alloc =
+        budget_tokens.map(|b| file_limits(&texts, b, per_file, |t| estimate_tokens(t, tokenizer)));
+    let mut sections = vec![format!("# cx pack ({} files)", files.len())];
+    let mut manifest = Vec::new();
+    let (mut bytes_total, mut bytes_kept) = (0usize, 0usize);
+    for (i, file) in files.iter().enumerate() {
+        bytes_total += file.bytes;
+        if let Some(reason) = file.skipped {
+            sections.push(format!("## {}\n[cx] skipped: {reason}", file.path));
+            manifest.push(json!({
+                "path": file.path, "bytes": file.bytes, "bytes_kept": 0,
+                "truncated": false, "skipped": reason,
+            }));
+            continue;
+        }
+        let mut kept = truncate_chars(texts[i], char_alloc[i]);
+        if let Some(alloc) = &token_alloc {
+            kept = clip_to_token_budget(&kept, alloc[i], tokenizer, false);
+        }
+        let truncated = kept.len() < texts[i].len();
+        bytes_kept += kept.len();
+        let mut body = kept.trim_end_matches('\n').to_string();
+        if truncated {
+            body.push_str(&format!(
+                "\n[cx] truncated {}: kept {}/{} bytes",
+                file.path,
+                kept.len(),
+                file.bytes
+            ));
+        }
+        let fence = fence_for(&body);
+        sections.push(format!(
+            "## {}\n{fence}{}\n{body}\n{fence}",
+            file.path,
+            fence_lang(&file.path)
+        ));
+        manifest.push(json!({
+            "path": file.path, "bytes": file.bytes, "bytes_kept": kept.len(),
+            "truncated": truncated, "skipped": Value::Null,
+        }));
+    }
+    PackOutcome {
+        bundle: sections.join("\n\n") + "\n",
+        manifest,
+        bytes_total,
+        bytes_kept,
+    }
+}
+
+pub fn cmd_pack(args: &[String]) -> i32 {
+    let started = Instant::now();
+    let chunked = args.iter().any(|a| a == "--chunk");
+    let paths: Vec<String> = args.iter().filter(|a| *a != "--chunk").cloned().collect();
+    if paths.is_empty() || paths.iter().any(|p| p.starts_with("--")) {
+        return print_usage_error("pack", PACK_USAGE);
+    }
+    let found = match expand_paths(&paths) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("pack", &e),
+    };
+    let files: Vec<PackFile> = found.iter().map(|p| read_pack_file(p)).collect();
+    let cfg = app_config();
+    let tokens = Some(cfg.budget_tokens).filter(|v| *v > 0);
+    let outcome = build_bundle(&files, cfg.budget_chars, tokens, chunked);
+    if chunked {
+        let chunks = chunk_text_by_budget(&outcome.bundle, cfg.budget_chars);
+        let total = chunks.len();
+        for (i, ch) in chunks.iter().enumerate() {
+            println!("----- cx chunk {}/{} -----", i + 1, total);
+            print!("{ch}");
+            if !ch.ends_with('\n') {
+                println!();
+            }
+        }
+    } else {
+        print!("{}", outcome.bundle);
+    }
+    let manifest = json!({
+        "files": outcome.manifest,
+        "file_count": files.len(),
+        "bytes_total": outcome.bytes_total,
+        "bytes_kept": outcome.bytes_kept,
+        "bytes_dropped": outcome.bytes_total.saturating_sub(outcome.bytes_kept),
+        "chunked": chunked,
+    });
+    if let Err(e) = log_pack_run(
+        &outcome.bundle,
+        &manifest,
+        started.elapsed().as_millis() as u64,
+    ) {
+        warn(&format_error("pack", &format!("warning: {e}")));
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocation_water_fills_unused_share() {
+        assert_eq!(allocate(&[10, 100, 100], 110), vec![10, 50, 50]);
+        assert_eq!(allocate(&[10, 20], 1_000), vec![10, 20]);
+        assert_eq!(allocate(&[0, 50], 10), vec![0, 10]);
+        assert_eq!(allocate(&[5, 5, 5], 2), vec![1, 1, 0]);
+    }
+
+    #[test]
+    fn truncation_prefers_line_boundary_and_fences_escape() {
+        assert_eq!(truncate_chars("aaaa\nbbbb\ncccc", 12), "aaaa\nbbbb");
+        assert_eq!(truncate_chars("abcdefgh", 3), "abc");
+        assert_eq!(fence_for("plain"), "```");
+        assert_eq!(fence_for("has ```` inside"), "`````");
+        assert_eq!(fence_lang("src/main.rs"), "rust");
+        assert_eq!(fence_lang("Makefile"), "");
+    }
+}
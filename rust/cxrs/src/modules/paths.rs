@@ -1,24 +1,43 @@
 use std::env;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-#[cfg(not(test))]
-use std::sync::OnceLock;
 
-use crate::process::run_command_output_with_timeout;
+use crate::config_layers::config_env;
+use crate::vcs::current_project;
 
+/// Root of the current project: a git, jj or hg checkout, a directory with
+/// `.codex`, or `CX_PROJECT_ROOT` (see [`crate::vcs`]).
 pub fn repo_root() -> Option<PathBuf> {
-    #[cfg(test)]
-    {
-        repo_root_uncached()
-    }
-    #[cfg(not(test))]
+    current_project().map(|p| p.root)
+}
+
+/// Nearest dir from `cwd` up to `root` (both included) holding a `.codex` dir.
+pub fn nearest_codex_root(cwd: &Path, root: &Path) -> Option<PathBuf> {
+    cwd.ancestors()
+        .take_while(|d| d.starts_with(root))
+        .find(|d| d.join(".codex").is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// Dir whose `.codex` holds this run's state and logs, and why: `nearest` for
+/// a package `.codex` below the project root (`runtime.codex_root=nearest`, the
+/// default), else `repo_root`. Schemas and repo config always come from the
+/// project root.
+pub fn codex_root_with_reason() -> Option<(PathBuf, &'static str)> {
+    let root = repo_root()?;
+    let shared = config_env("CX_CODEX_ROOT").as_deref() == Some("shared");
+    if !shared
+        && let Some(dir) = env::current_dir()
+            .ok()
+            .and_then(|cwd| nearest_codex_root(&cwd, &root))
+            .filter(|d| *d != root)
     {
-        static CACHED: OnceLock<Option<PathBuf>> = OnceLock::new();
-        if env::var("CX_NO_CACHE").ok().as_deref() == Some("1") {
-            return repo_root_uncached();
-        }
-        CACHED.get_or_init(repo_root_uncached).as_ref().cloned()
+        return Some((dir, "nearest"));
     }
+    Some((root, "repo_root"))
+}
+
+pub fn codex_root() -> Option<PathBuf> {
+    codex_root_with_reason().map(|(d, _)| d)
 }
 
 pub fn repo_root_hint() -> Option<PathBuf> {
@@ -31,78 +50,417 @@ pub fn repo_root_hint() -> Option<PathBuf> {
     repo_root()
 }
 
-fn repo_root_uncached() -> Option<PathBuf> {
-    let mut cmd = Command::new("git");
-    cmd.args(["rev-parse", "--show-toplevel"]);
-    let out = run_command_output_with_timeout(cmd, "git rev-parse --show-toplevel").ok()?;
-    if !out.status.success() {
-        return None;
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+}
+
+/// Which global (non-repo) dir a path belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlobalKind {
+    /// state.json, logs, quarantine, schemas, sessions, ...
+    State,
+    /// config.toml and secrets.
+    Config,
+    Cache,
+}
+
+impl GlobalKind {
+    fn xdg_var(self) -> &'static str {
+        match self {
+            GlobalKind::State => "XDG_STATE_HOME",
+            GlobalKind::Config => "XDG_CONFIG_HOME",
+            GlobalKind::Cache => "XDG_CACHE_HOME",
+        }
+    }
+
+    fn xdg_reason(self) -> &'static str {
+        match self {
+            GlobalKind::State => "env:XDG_STATE_HOME",
+            GlobalKind::Config => "env:XDG_CONFIG_HOME",
+            GlobalKind::Cache => "env:XDG_CACHE_HOME",
+        }
+    }
+}
+
+/// The pre-XDG `~/.codex` dir.
+pub fn legacy_codex_dir() -> Option<PathBuf> {
+    home_dir().map(|h| h.join(".codex"))
+}
+
+/// Global dir for `kind` and why it was chosen: `CX_HOME` wins; an existing
+/// `~/.codex` keeps working; otherwise `$XDG_*_HOME/cx` when that var is set;
+/// else `~/.codex`.
+pub fn global_dir_with_reason(kind: GlobalKind) -> Option<(PathBuf, &'static str)> {
+    let in_codex_dir = |d: PathBuf| match kind {
+        GlobalKind::Cache => d.join("cache"),
+        _ => d,
+    };
+    if let Some(dir) = env_dir("CX_HOME") {
+        return Some((in_codex_dir(dir), "env:CX_HOME"));
+    }
+    let legacy = legacy_codex_dir();
+    if let Some(dir) = legacy.clone().filter(|d| d.is_dir()) {
+        return Some((in_codex_dir(dir), "home"));
     }
-    let s = String::from_utf8_lossy(&out.stdout).trim().to_string();
-    if s.is_empty() {
-        None
+    if let Some(base) = env_dir(kind.xdg_var()) {
+        return Some((base.join("cx"), kind.xdg_reason()));
+    }
+    legacy.map(|d| (in_codex_dir(d), "home"))
+}
+
+pub fn global_dir(kind: GlobalKind) -> Option<PathBuf> {
+    global_dir_with_reason(kind).map(|(d, _)| d)
+}
+
+/// Resolution reason for repo-scoped paths: `repo_root` or `nearest`, else
+/// the global one.
+fn scoped_reason() -> &'static str {
+    if let Some((_, reason)) = codex_root_with_reason() {
+        reason
     } else {
-        Some(PathBuf::from(s))
+        global_dir_with_reason(GlobalKind::State)
+            .map(|(_, r)| r)
+            .unwrap_or("unresolved")
     }
 }
 
-pub fn home_dir() -> Option<PathBuf> {
-    env::var_os("HOME").map(PathBuf::from)
+/// `doctor` hint when a legacy `~/.codex` shadows a configured XDG/CX_HOME layout.
+pub fn legacy_migration_hint() -> Option<String> {
+    let legacy = legacy_codex_dir().filter(|d| d.is_dir())?;
+    if let Some(cx_home) = env_dir("CX_HOME") {
+        if cx_home == legacy {
+            return None;
+        }
+        return Some(format!(
+            "CX_HOME={} is set but {} still exists; move its contents with: mv {}/* {}/",
+            cx_home.display(),
+            legacy.display(),
+            legacy.display(),
+            cx_home.display()
+        ));
+    }
+    let state = env_dir("XDG_STATE_HOME");
+    let config = env_dir("XDG_CONFIG_HOME");
+    if state.is_none() && config.is_none() {
+        return None;
+    }
+    let mut steps = Vec::new();
+    if let Some(c) = config {
+        steps.push(format!(
+            "mkdir -p {0}/cx && mv {1}/config.toml {1}/secrets.json {0}/cx/",
+            c.display(),
+            legacy.display()
+        ));
+    }
+    if let Some(s) = state {
+        steps.push(format!("mv {} {}/cx", legacy.display(), s.display()));
+    }
+    Some(format!(
+        "XDG base dirs are set but {} takes precedence while it exists; to migrate: {}",
+        legacy.display(),
+        steps.join(" && ")
+    ))
+}
+
+/// The `.codex` workspace dir: repo-local (see [`codex_root`]), else the
+/// global state dir.
+pub fn resolve_codex_dir() -> Option<PathBuf> {
+    if let Some(root) = codex_root() {
+        return Some(root.join(".codex"));
+    }
+    global_dir(GlobalKind::State)
 }
 
 pub fn resolve_log_file() -> Option<PathBuf> {
-    if let Some(root) = repo_root() {
+    if let Some(root) = codex_root() {
         return Some(root.join(".codex").join("cxlogs").join("runs.jsonl"));
     }
-    home_dir().map(|h| h.join(".codex").join("cxlogs").join("runs.jsonl"))
+    global_dir(GlobalKind::State).map(|d| d.join("cxlogs").join("runs.jsonl"))
 }
 
 pub fn resolve_schema_fail_log_file() -> Option<PathBuf> {
-    if let Some(root) = repo_root() {
+    if let Some(root) = codex_root() {
         return Some(
             root.join(".codex")
                 .join("cxlogs")
                 .join("schema_failures.jsonl"),
         );
     }
-    home_dir().map(|h| {
-        h.join(".codex")
-            .join("cxlogs")
-            .join("schema_failures.jsonl")
-    })
+    global_dir(GlobalKind::State).map(|d| d.join("cxlogs").join("schema_failures.jsonl"))
+}
+
+/// Generated commitjson objects keyed by staged tree, reused by `changelog`.
+pub fn resolve_commitjson_log_file() -> Option<PathBuf> {
+    if let Some(root) = codex_root() {
+        return Some(root.join(".codex").join("cxlogs").join("commitjson.jsonl"));
+    }
+    global_dir(GlobalKind::State).map(|d| d.join("cxlogs").join("commitjson.jsonl"))
+}
+
+/// Global `repo_root -> log_file` registry read by the `--all-repos` analytics.
+pub fn resolve_repo_registry_file() -> Option<PathBuf> {
+    global_dir(GlobalKind::State).map(|d| d.join("repos.json"))
+}
+
+/// Last `stage` proposal, read back by `stage --apply N`; repo-scoped only.
+pub fn resolve_stage_plan_file() -> Option<PathBuf> {
+    codex_root().map(|root| root.join(".codex").join("stage_plan.json"))
+}
+
+pub fn resolve_alerts_file() -> Option<PathBuf> {
+    if let Some(root) = codex_root() {
+        return Some(root.join(".codex").join("cxlogs").join("alerts.jsonl"));
+    }
+    global_dir(GlobalKind::State).map(|d| d.join("cxlogs").join("alerts.jsonl"))
+}
+
+/// Human notes attached to runs and tasks (`annotate`).
+pub fn resolve_notes_file() -> Option<PathBuf> {
+    if let Some(root) = codex_root() {
+        return Some(root.join(".codex").join("notes.jsonl"));
+    }
+    global_dir(GlobalKind::State).map(|d| d.join("notes.jsonl"))
+}
+
+/// Results of `bench`, `bench --llm` and `model bench`, read by `optimize`.
+pub fn resolve_bench_history_file() -> Option<PathBuf> {
+    if let Some(root) = codex_root() {
+        return Some(root.join(".codex").join("bench.jsonl"));
+    }
+    global_dir(GlobalKind::State).map(|d| d.join("bench.jsonl"))
 }
 
 pub fn resolve_quarantine_dir() -> Option<PathBuf> {
-    if let Some(root) = repo_root() {
+    if let Some(root) = codex_root() {
         return Some(root.join(".codex").join("quarantine"));
     }
-    home_dir().map(|h| h.join(".codex").join("quarantine"))
+    global_dir(GlobalKind::State).map(|d| d.join("quarantine"))
 }
 
-pub fn resolve_state_file() -> Option<PathBuf> {
-    if let Some(root) = repo_root() {
-        return Some(root.join(".codex").join("state.json"));
+pub fn resolve_sessions_dir() -> Option<PathBuf> {
+    if let Some(root) = codex_root() {
+        return Some(root.join(".codex").join("sessions"));
     }
-    home_dir().map(|h| h.join(".codex").join("state.json"))
+    global_dir(GlobalKind::State).map(|d| d.join("sessions"))
+}
+
+pub fn resolve_transcripts_dir() -> Option<PathBuf> {
+    if let Some(root) = codex_root() {
+        return Some(root.join(".codex").join("transcripts"));
+    }
+    global_dir(GlobalKind::State).map(|d| d.join("transcripts"))
+}
+
+pub fn global_state_file() -> Option<PathBuf> {
+    global_dir(GlobalKind::State).map(|d| d.join("state.json"))
+}
+
+pub fn repo_state_file() -> Option<PathBuf> {
+    codex_root().map(|r| r.join(".codex").join("state.json"))
+}
+
+pub fn resolve_cache_dir() -> Option<PathBuf> {
+    global_dir(GlobalKind::Cache)
+}
+
+/// Encrypted secret store; always per-user, never in the repo.
+pub fn resolve_secrets_file() -> Option<PathBuf> {
+    global_dir(GlobalKind::Config).map(|d| d.join("secrets.json"))
+}
+
+/// Default state write target: the repo layer inside a repo, else the global one.
+pub fn resolve_state_file() -> Option<PathBuf> {
+    repo_state_file().or_else(global_state_file)
 }
 
 pub fn resolve_quota_catalog_file() -> Option<PathBuf> {
-    if let Some(root) = repo_root() {
+    if let Some(root) = codex_root() {
         return Some(root.join(".codex").join("quota_catalog.json"));
     }
-    home_dir().map(|h| h.join(".codex").join("quota_catalog.json"))
+    global_dir(GlobalKind::State).map(|d| d.join("quota_catalog.json"))
 }
 
 pub fn resolve_tasks_file() -> Result<PathBuf, String> {
-    let root = repo_root().ok_or_else(|| "cx task: not inside a git repository".to_string())?;
+    let root = codex_root().ok_or_else(|| "cx task: not inside a git repository".to_string())?;
     Ok(root.join(".codex").join("tasks.json"))
 }
 
+pub fn resolve_task_templates_dir() -> Result<PathBuf, String> {
+    let root = codex_root().ok_or_else(|| "cx task: not inside a git repository".to_string())?;
+    Ok(root.join(".codex").join("task_templates"))
+}
+
+pub fn resolve_task_results_dir() -> Result<PathBuf, String> {
+    let root = codex_root().ok_or_else(|| "cx task: not inside a git repository".to_string())?;
+    Ok(root.join(".codex").join("task_results"))
+}
+
 pub fn resolve_schema_dir() -> Option<PathBuf> {
     if let Some(root) = repo_root() {
         return Some(root.join(".codex").join("schemas"));
     }
-    home_dir().map(|h| h.join(".codex").join("schemas"))
+    global_dir(GlobalKind::State).map(|d| d.join("schemas"))
+}
+
+pub fn resolve_schema_registry_file() -> Option<PathBuf> {
+    resolve_schema_dir().map(|d| d.join("registry.json"))
+}
+
+pub fn resolve_custom_commands_file() -> Option<PathBuf> {
+    if let Some(root) = codex_root() {
+        return Some(root.join(".codex").join("commands.json"));
+    }
+    global_dir(GlobalKind::State).map(|d| d.join("commands.json"))
+}
+
+pub fn resolve_roles_file() -> Option<PathBuf> {
+    if let Some(root) = codex_root() {
+        return Some(root.join(".codex").join("roles.json"));
+    }
+    global_dir(GlobalKind::State).map(|d| d.join("roles.json"))
+}
+
+pub fn resolve_prompt_template_dir() -> Option<PathBuf> {
+    if let Some(root) = codex_root() {
+        return Some(root.join(".codex").join("prompts"));
+    }
+    global_dir(GlobalKind::State).map(|d| d.join("prompts"))
+}
+
+/// One resolved path for `where --json` and `doctor --fix`.
+pub struct ResolvedPath {
+    pub name: &'static str,
+    pub kind: &'static str,
+    pub path: Option<PathBuf>,
+    /// `repo_root`, `nearest`, `home`, or `unresolved`.
+    pub reason: &'static str,
+}
+
+impl ResolvedPath {
+    pub fn exists(&self) -> bool {
+        self.path.as_deref().is_some_and(Path::exists)
+    }
+
+    /// Writable in place, or creatable under its nearest existing ancestor.
+    pub fn writable(&self) -> bool {
+        let Some(mut p) = self.path.as_deref() else {
+            return false;
+        };
+        while !p.exists() {
+            match p.parent() {
+                Some(parent) => p = parent,
+                None => return false,
+            }
+        }
+        path_is_writable(p)
+    }
+}
+
+#[cfg(unix)]
+fn path_is_writable(p: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    let Ok(c) = std::ffi::CString::new(p.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `c` is a valid NUL-terminated path for the duration of the call.
+    unsafe { libc::access(c.as_ptr(), libc::W_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+fn path_is_writable(p: &Path) -> bool {
+    std::fs::metadata(p).is_ok_and(|m| !m.permissions().readonly())
+}
+
+/// Every path cxrs reads or writes, with how it was resolved.
+pub fn resolved_paths() -> Vec<ResolvedPath> {
+    let scoped = scoped_reason();
+    let global = |kind| {
+        global_dir_with_reason(kind)
+            .map(|(_, r)| r)
+            .unwrap_or("unresolved")
+    };
+    let entry = |name, kind, path: Option<PathBuf>, reason| ResolvedPath {
+        name,
+        kind,
+        reason: if path.is_some() { reason } else { "unresolved" },
+        path,
+    };
+    vec![
+        entry("codex_dir", "dir", resolve_codex_dir(), scoped),
+        entry("log_file", "file", resolve_log_file(), scoped),
+        entry(
+            "schema_fail_log",
+            "file",
+            resolve_schema_fail_log_file(),
+            scoped,
+        ),
+        entry("alerts_file", "file", resolve_alerts_file(), scoped),
+        entry("notes_file", "file", resolve_notes_file(), scoped),
+        entry(
+            "bench_history_file",
+            "file",
+            resolve_bench_history_file(),
+            scoped,
+        ),
+        entry(
+            "commitjson_log",
+            "file",
+            resolve_commitjson_log_file(),
+            scoped,
+        ),
+        entry("state_file", "file", resolve_state_file(), scoped),
+        entry(
+            "global_state_file",
+            "file",
+            global_state_file(),
+            global(GlobalKind::State),
+        ),
+        entry(
+            "global_config_file",
+            "file",
+            global_dir(GlobalKind::Config).map(|d| d.join("config.toml")),
+            global(GlobalKind::Config),
+        ),
+        entry(
+            "secrets_file",
+            "file",
+            resolve_secrets_file(),
+            global(GlobalKind::Config),
+        ),
+        entry("tasks_file", "file", resolve_tasks_file().ok(), scoped),
+        entry(
+            "schema_dir",
+            "dir",
+            resolve_schema_dir(),
+            if repo_root().is_some() {
+                "repo_root"
+            } else {
+                scoped
+            },
+        ),
+        entry("quarantine_dir", "dir", resolve_quarantine_dir(), scoped),
+        entry("sessions_dir", "dir", resolve_sessions_dir(), scoped),
+        entry("transcripts_dir", "dir", resolve_transcripts_dir(), scoped),
+        entry("prompts_dir", "dir", resolve_prompt_template_dir(), scoped),
+        entry(
+            "quota_catalog_file",
+            "file",
+            resolve_quota_catalog_file(),
+            scoped,
+        ),
+        entry(
+            "cache_dir",
+            "dir",
+            resolve_cache_dir(),
+            global(GlobalKind::Cache),
+        ),
+    ]
 }
 
 pub fn ensure_parent_dir(path: &Path) -> Result<(), String> {
@@ -0,0 +1,80 @@
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// Nearest-rank p50/p90/p99 of a sample; runs missing the field are skipped
+/// by callers rather than counted as zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Percentiles {
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+}
+
+fn nearest_rank(sorted: &[u64], pct: u64) -> u64 {
+    let rank = (sorted.len() as u64 * pct).div_ceil(100).max(1);
+    sorted[rank as usize - 1]
+}
+
+/// Nearest-rank `pct` percentile (`1..=100`) of an unsorted sample.
+pub fn percentile(mut values: Vec<u64>, pct: u64) -> Option<u64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    Some(nearest_rank(&values, pct.clamp(1, 100)))
+}
+
+impl Percentiles {
+    pub fn of(mut values: Vec<u64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+        Some(Self {
+            p50: nearest_rank(&values, 50),
+            p90: nearest_rank(&values, 90),
+            p99: nearest_rank(&values, 99),
+        })
+    }
+
+    pub fn to_json(self) -> Value {
+        json!({"p50": self.p50, "p90": self.p90, "p99": self.p99})
+    }
+}
+
+pub fn percentiles_json(p: Option<Percentiles>) -> Value {
+    p.map_or(Value::Null, Percentiles::to_json)
+}
+
+/// Group `(tool, value)` samples and compute percentiles per tool.
+pub fn percentiles_by_tool<I>(samples: I) -> BTreeMap<String, Percentiles>
+where
+    I: IntoIterator<Item = (String, u64)>,
+{
+    let mut grouped: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+    for (tool, v) in samples {
+        grouped.entry(tool).or_default().push(v);
+    }
+    grouped
+        .into_iter()
+        .filter_map(|(tool, values)| Percentiles::of(values).map(|p| (tool, p)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        assert_eq!(Percentiles::of(Vec::new()), None);
+        let one = Percentiles::of(vec![7]).expect("one");
+        assert_eq!((one.p50, one.p90, one.p99), (7, 7, 7));
+        let p = Percentiles::of((1..=100).rev().collect()).expect("hundred");
+        assert_eq!((p.p50, p.p90, p.p99), (50, 90, 99));
+        let p = Percentiles::of(vec![100, 200, 300, 400, 10_000]).expect("tail");
+        assert_eq!((p.p50, p.p90, p.p99), (300, 10_000, 10_000));
+        assert_eq!(percentile(vec![5, 1, 3], 100), Some(5));
+        assert_eq!(percentile(Vec::new(), 95), None);
+    }
+}
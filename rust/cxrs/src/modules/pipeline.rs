@@ -0,0 +1,297 @@
+use serde_json::{Value, json};
+use std::sync::Mutex;
+
+use crate::agentcmds::fix_task_spec;
+use crate::capture::run_system_command_capture;
+use crate::contract_versions::PIPELINE_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, failure_exit, format_error, print_runtime_error, print_usage_error};
+use crate::execmeta::make_execution_id;
+use crate::execution::frame_failed_capture;
+use crate::structured_cmds::{
+    ExecuteTaskFn, diffsum_from_output, next_from_capture, parse_commands_array,
+    print_diffsum_human,
+};
+use crate::structured_fixrun::{fix_run_suggestions, print_fix_suggestions};
+use crate::types::{CaptureStats, LlmOutputKind, TaskInput, TaskSpec};
+
+pub const PIPELINE_USAGE: &str = "pipeline run <command> [args...] --steps STEP[,STEP...] [--json]";
+/// Set while the steps of a pipeline run; every run-log row records it as
+/// `pipeline_id`.
+static PIPELINE_ID: Mutex<Option<String>> = Mutex::new(None);
+/// Steps that can share one capture. `fix-run` only suggests: its commands are
+/// never executed from a pipeline.
+const STEPS: &[&str] = &["cxo", "diffsum", "next", "fix", "fix-run"];
+
+fn set_pipeline_id(id: Option<&str>) {
+    if let Ok(mut cur) = PIPELINE_ID.lock() {
+        *cur = id.map(str::to_string);
+    }
+}
+
+/// Id of the pipeline whose steps are running, if any.
+pub fn current_pipeline_id() -> Option<String> {
+    PIPELINE_ID.lock().ok().and_then(|cur| cur.clone())
+}
+
+/// The wrapped command's output, captured once and handed to every step.
+struct Capture {
+    command: Vec<String>,
+    output: String,
+    status: i32,
+    stats: CaptureStats,
+}
+
+struct PipelineArgs {
+    command: Vec<String>,
+    steps: Vec<&'static str>,
+    json_out: bool,
+}
+
+fn parse_steps(raw: &str) -> Result<Vec<&'static str>, String> {
+    let mut out: Vec<&'static str> = Vec::new();
+    for name in raw.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let Some(step) = STEPS.iter().find(|s| **s == name) else {
+            return Err(format!(
+                "unknown step '{name}' (steps: {})",
+                STEPS.join(", ")
+            ));
+        };
+        if out.contains(step) {
+            return Err(format!("step '{name}' is listed twice"));
+        }
+        out.push(step);
+    }
+    if out.is_empty() {
+        return Err("--steps needs at least one step".to_string());
+    }
+    Ok(out)
+}
+
+/// Flags may come before or after the command; everything after `--` is the
+/// command. A single quoted word (`'git log -5'`) is split like a shell would.
+fn parse_run_args(args: &[String]) -> Result<PipelineArgs, String> {
+    let (mut words, mut steps, mut json_out) = (Vec::new(), None, false);
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => json_out = true,
+            "--steps" => {
+                let raw = args.get(i + 1).ok_or("--steps requires a value")?;
+                steps = Some(parse_steps(raw)?);
+                i += 1;
+            }
+            "--" => {
+                words.extend_from_slice(&args[i + 1..]);
+                break;
+            }
+            _ => words.push(args[i].clone()),
+        }
+        i += 1;
+    }
+    let steps = steps.ok_or("--steps is required")?;
+    let command = match words.as_slice() {
+        [] => return Err("missing command".to_string()),
+        [one] => shell_words::split(one).map_err(|e| format!("invalid command: {e}"))?,
+        _ => words,
+    };
+    if command.is_empty() {
+        return Err("missing command".to_string());
+    }
+    Ok(PipelineArgs {
+        command,
+        steps,
+        json_out,
+    })
+}
+
+fn run_cxo(cap: &Capture, execute_task: ExecuteTaskFn) -> Result<Value, String> {
+    let prompt = frame_failed_capture(&cap.command, cap.status, cap.output.clone());
+    let result = execute_task(TaskSpec {
+        command_name: "cxo".to_string(),
+        input: TaskInput::Prompt(prompt),
+        output_kind: LlmOutputKind::AgentText,
+        schema: None,
+        schema_task_input: None,
+        logging_enabled: true,
+        capture_override: Some(cap.stats.clone()),
+        prompt_template: None,
+        stream: false,
+    })?;
+    Ok(Value::String(result.stdout))
+}
+
+fn run_step(step: &str, cap: &Capture, execute_task: ExecuteTaskFn) -> Result<Value, String> {
+    match step {
+        "cxo" => run_cxo(cap, execute_task),
+        "diffsum" => {
+            if cap.output.trim().is_empty() {
+                return Err("the captured output is empty; diffsum needs a diff".to_string());
+            }
+            diffsum_from_output(
+                "cxrs_diffsum",
+                "DIFF",
+                &cap.output,
+                cap.stats.clone(),
+                execute_task,
+            )
+        }
+        "next" => next_from_capture(
+            &cap.command,
+            &cap.output,
+            cap.status,
+            cap.stats.clone(),
+            execute_task,
+        )
+        .map(|(v, _)| v),
+        "fix" => {
+            let spec = fix_task_spec(
+                &cap.command,
+                &cap.output,
+                cap.status,
+                cap.stats.clone(),
+                None,
+            )?;
+            Ok(Value::String(execute_task(spec)?.stdout))
+        }
+        "fix-run" => fix_run_suggestions(
+            &cap.command,
+            &cap.output,
+            cap.status,
+            cap.stats.clone(),
+            execute_task,
+        )
+        .map(|(analysis, commands)| json!({"analysis": analysis, "commands": commands}))
+        .map_err(|code| format!("remediation failed (exit {code})")),
+        other => Err(format!("unknown step '{other}'")),
+    }
+}
+
+fn print_step(step: &str, value: &Value) {
+    match step {
+        "diffsum" => print_diffsum_human(value),
+        "next" => {
+            for cmd in parse_commands_array(&value.to_string()).unwrap_or_default() {
+                println!("{cmd}");
+            }
+        }
+        "fix-run" => {
+            let analysis = value.get("analysis").and_then(Value::as_str).unwrap_or("");
+            let commands = parse_commands_array(&value.to_string()).unwrap_or_default();
+            print_fix_suggestions(analysis, &commands);
+        }
+        _ => println!("{}", value.as_str().unwrap_or_default()),
+    }
+}
+
+fn run_pipeline(args: &[String], execute_task: ExecuteTaskFn) -> i32 {
+    let args = match parse_run_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("pipeline", &e));
+            return print_usage_error("pipeline", PIPELINE_USAGE);
+        }
+    };
+    let (output, status, stats) = match run_system_command_capture("cxrs_pipeline", &args.command) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("pipeline", &e),
+    };
+    let cap = Capture {
+        command: args.command,
+        output,
+        status,
+        stats,
+    };
+    let pipeline_id = make_execution_id("pipeline");
+    if !args.json_out {
+        println!(
+            "pipeline {pipeline_id}: `{}` exited {status}; steps: {}",
+            cap.command.join(" "),
+            args.steps.join(", ")
+        );
+    }
+    set_pipeline_id(Some(&pipeline_id));
+    let mut rows: Vec<Value> = Vec::new();
+    let mut failed = false;
+    for step in &args.steps {
+        let outcome = run_step(step, &cap, execute_task);
+        if !args.json_out {
+            println!();
+            println!("== {step} ==");
+        }
+        match outcome {
+            Ok(value) => {
+                if !args.json_out {
+                    print_step(step, &value);
+                }
+                rows.push(json!({"step": step, "ok": true, "result": value, "error": null}));
+            }
+            Err(e) => {
+                failed = true;
+                crate::cx_eprintln!("{}", format_error("pipeline", &format!("{step}: {e}")));
+                rows.push(json!({"step": step, "ok": false, "result": null, "error": e}));
+            }
+        }
+    }
+    set_pipeline_id(None);
+    if args.json_out {
+        let doc = json!({
+            "contract_version": PIPELINE_JSON_CONTRACT_VERSION,
+            "pipeline_id": pipeline_id,
+            "command": cap.command.join(" "),
+            "status": cap.status,
+            "capture": {
+                "chars_raw": cap.stats.system_output_len_raw,
+                "chars_clipped": cap.stats.system_output_len_clipped,
+                "clipped": cap.stats.clipped,
+            },
+            "steps": rows,
+        });
+        match serde_json::to_string_pretty(&doc) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return print_runtime_error("pipeline", &format!("render failure: {e}")),
+        }
+    }
+    if failed { failure_exit() } else { EXIT_OK }
+}
+
+/// `pipeline run <command> --steps a,b`: capture the command once and feed
+/// the same processed output to each step, logging every step's run under one
+/// `pipeline_id`. A failed step is reported and the remaining steps still run.
+pub fn cmd_pipeline(args: &[String], execute_task: ExecuteTaskFn) -> i32 {
+    match args.first().map(String::as_str) {
+        Some("run") => run_pipeline(&args[1..], execute_task),
+        _ => print_usage_error("pipeline", PIPELINE_USAGE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn run_args_split_a_quoted_command_and_validate_steps() {
+        let a = parse_run_args(&argv(&["git log -5 --oneline", "--steps", "next, fix-run"]))
+            .expect("parse");
+        assert_eq!(a.command, argv(&["git", "log", "-5", "--oneline"]));
+        assert_eq!(a.steps, ["next", "fix-run"]);
+        assert!(!a.json_out);
+
+        let a = parse_run_args(&argv(&["--json", "--steps", "cxo", "--", "ls", "--json"]))
+            .expect("parse after --");
+        assert_eq!(a.command, argv(&["ls", "--json"]));
+        assert!(a.json_out);
+
+        assert!(parse_run_args(&argv(&["ls"])).is_err());
+        assert!(
+            parse_run_args(&argv(&["ls", "--steps", "next,bogus"]))
+                .err()
+                .is_some_and(|e| e.contains("unknown step 'bogus'"))
+        );
+        assert!(parse_run_args(&argv(&["ls", "--steps", "next,next"])).is_err());
+        assert!(parse_run_args(&argv(&["--steps", "next"])).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellSpec {
+    pub program: String,
+    pub args: Vec<String>,
+    pub source: &'static str,
+}
+
+pub fn os_name() -> &'static str {
+    env::consts::OS
+}
+
+pub fn os_family() -> &'static str {
+    env::consts::FAMILY
+}
+
+fn executable_candidates(bin: &str, windows: bool, pathext: Option<&str>) -> Vec<String> {
+    if !windows || Path::new(bin).extension().is_some() {
+        return vec![bin.to_string()];
+    }
+    let mut out = vec![bin.to_string()];
+    for ext in pathext.unwrap_or(DEFAULT_PATHEXT).split(';') {
+        let ext = ext.trim();
+        if !ext.is_empty() {
+            out.push(format!("{bin}{}", ext.to_ascii_lowercase()));
+        }
+    }
+    out
+}
+
+/// Resolve `bin` against PATH (honouring PATHEXT on Windows) without spawning a shell.
+pub fn find_in_path(bin: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    let pathext = env::var("PATHEXT").ok();
+    let names = executable_candidates(bin, cfg!(windows), pathext.as_deref());
+    env::split_paths(&path).find_map(|dir| {
+        names
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+pub fn bin_in_path(bin: &str) -> bool {
+    find_in_path(bin).is_some()
+}
+
+fn default_shell_args(program: &str) -> Vec<String> {
+    let stem = Path::new(program)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(program)
+        .to_ascii_lowercase();
+    let args: &[&str] = match stem.as_str() {
+        "cmd" => &["/C"],
+        "powershell" | "pwsh" => &["-NoProfile", "-Command"],
+        "bash" | "zsh" => &["-lc"],
+        _ => &["-c"],
+    };
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+fn parse_shell_spec(raw: &str) -> Option<ShellSpec> {
+    let mut words = shell_words::split(raw).ok()?.into_iter();
+    let program = words.next().filter(|s| !s.trim().is_empty())?;
+    let rest: Vec<String> = words.collect();
+    let args = if rest.is_empty() {
+        default_shell_args(&program)
+    } else {
+        rest
+    };
+    Some(ShellSpec {
+        program,
+        args,
+        source: "CX_SHELL",
+    })
+}
+
+/// Shell used to run suggested commands: `CX_SHELL`, else `cmd` on Windows,
+/// else `bash` when installed, else `sh`.
+pub fn resolve_shell() -> ShellSpec {
+    if let Some(spec) = env::var("CX_SHELL")
+        .ok()
+        .and_then(|raw| parse_shell_spec(&raw))
+    {
+        return spec;
+    }
+    let program = if cfg!(windows) {
+        "cmd"
+    } else if bin_in_path("bash") {
+        "bash"
+    } else {
+        "sh"
+    };
+    ShellSpec {
+        program: program.to_string(),
+        args: default_shell_args(program),
+        source: "default",
+    }
+}
+
+pub fn shell_command(script: &str) -> Command {
+    let spec = resolve_shell();
+    let mut cmd = Command::new(&spec.program);
+    cmd.args(&spec.args).arg(script);
+    cmd
+}
+
+pub fn print_platform_capabilities() {
+    let shell = resolve_shell();
+    let clipboard: Vec<&str> = crate::clipboard::detect_providers()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    let clipboard_mode = if crate::clipboard::forced_provider_name().is_some() {
+        "CX_CLIPBOARD_PROVIDER"
+    } else {
+        "auto"
+    };
+    println!("== platform ==");
+    println!("os: {} ({})", os_name(), os_family());
+    println!(
+        "shell: {} {} (source: {})",
+        shell.program,
+        shell.args.join(" "),
+        shell.source
+    );
+    println!(
+        "clipboard_provider: {} (source: {clipboard_mode})",
+        clipboard.first().copied().unwrap_or("<none found>")
+    );
+    if clipboard.len() > 1 {
+        println!("clipboard_fallbacks: {}", clipboard[1..].join(", "));
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_candidates_expand_pathext() {
+        let names = executable_candidates("git", true, Some(".EXE;.CMD"));
+        assert_eq!(names, vec!["git", "git.exe", "git.cmd"]);
+        assert_eq!(
+            executable_candidates("git.exe", true, None),
+            vec!["git.exe"]
+        );
+        assert_eq!(
+            executable_candidates("git", false, Some(".EXE")),
+            vec!["git"]
+        );
+    }
+
+    #[test]
+    fn shell_spec_infers_invocation_args() {
+        let spec = parse_shell_spec("pwsh").expect("spec");
+        assert_eq!(spec.args, vec!["-NoProfile", "-Command"]);
+        let spec = parse_shell_spec("/bin/sh").expect("spec");
+        assert_eq!(spec.args, vec!["-c"]);
+        let spec = parse_shell_spec("zsh -fc").expect("spec");
+        assert_eq!(spec.program, "zsh");
+        assert_eq!(spec.args, vec!["-fc"]);
+        assert!(parse_shell_spec("   ").is_none());
+    }
+}
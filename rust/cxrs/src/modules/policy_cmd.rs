@@ -0,0 +1,209 @@
+use serde_json::{Value, json};
+use std::env;
+use std::path::PathBuf;
+
+use crate::config::app_config;
+use crate::contract_versions::POLICY_CHECK_JSON_CONTRACT_VERSION;
+use crate::paths::repo_root;
+
+use super::{
+    BUILTIN_RULES, RepoPolicy, SafetyDecision, evaluate_command_policy, load_repo_policy,
+    normalize_command,
+};
+
+fn policy_root() -> PathBuf {
+    repo_root()
+        .or_else(|| env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// `policy check --json` document for one evaluated command.
+fn check_json(candidate: &str, decision: &SafetyDecision, policy: &RepoPolicy) -> Value {
+    let (label, hit) = match decision {
+        SafetyDecision::Safe => ("safe", None),
+        SafetyDecision::Allowed(hit) => ("allowed", Some(hit)),
+        SafetyDecision::Warn(hit) => ("warning", Some(hit)),
+        SafetyDecision::Dangerous(hit) => ("dangerous", Some(hit)),
+    };
+    json!({
+        "contract_version": POLICY_CHECK_JSON_CONTRACT_VERSION,
+        "command": candidate,
+        "normalized_command": normalize_command(candidate),
+        "decision": label,
+        "rule": hit.map(|h| h.rule.as_str()),
+        "category": hit.map(|h| h.category),
+        "severity": hit.map(|h| h.severity.as_str()),
+        "reason": hit.map(|h| h.reason.as_str()),
+        "policy_file": policy.source.as_ref().map(|p| p.display().to_string()),
+    })
+}
+
+fn handle_policy_check(args: &[String], app_name: &str) -> i32 {
+    let json_out = args.get(1).is_some_and(|a| a == "--json");
+    let rest = &args[if json_out { 2 } else { 1 }..];
+    if rest.is_empty() {
+        crate::cx_eprintln!("Usage: {app_name} policy check [--json] <command...>");
+        return 2;
+    }
+    let candidate = rest.join(" ");
+    let root = policy_root();
+    let policy = match load_repo_policy(&root) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs policy: {e}");
+            return 1;
+        }
+    };
+    let decision = evaluate_command_policy(&candidate, &root, &policy);
+    if json_out {
+        println!("{}", check_json(&candidate, &decision, &policy));
+        return 0;
+    }
+    match decision {
+        SafetyDecision::Safe => println!("safe"),
+        SafetyDecision::Allowed(hit) => println!("safe (allowed by rule: {})", hit.rule),
+        SafetyDecision::Warn(hit) => println!(
+            "warning: {} (rule: {}, severity: {})",
+            hit.reason,
+            hit.rule,
+            hit.severity.as_str()
+        ),
+        SafetyDecision::Dangerous(hit) => println!(
+            "dangerous: {} (rule: {}, severity: {})",
+            hit.reason,
+            hit.rule,
+            hit.severity.as_str()
+        ),
+    }
+    0
+}
+
+fn print_effective_rules(policy: &RepoPolicy) {
+    println!("Effective rules (evaluation order):");
+    for r in &policy.deny {
+        let sev = r.severity.as_str();
+        println!("- [{sev}] {}: /{}/ ({})", r.id, r.regex.as_str(), r.reason);
+    }
+    for r in &policy.write_deny {
+        println!(
+            "- [{}] {}: no writes to {}",
+            r.severity.as_str(),
+            r.id,
+            r.glob
+        );
+    }
+    let print_builtins = |allowable: bool| {
+        for (id, category, reason, _) in BUILTIN_RULES.iter().filter(|r| r.3 == allowable) {
+            println!("- [critical] {id}: {reason} ({category})");
+        }
+    };
+    if policy.builtin {
+        print_builtins(false);
+    }
+    for r in &policy.allow {
+        println!(
+            "- [allow] {}: /{}/ (single commands only)",
+            r.id,
+            r.regex.as_str()
+        );
+    }
+    if policy.builtin {
+        print_builtins(true);
+    } else {
+        println!("- built-in rules disabled (policy.builtin = false)");
+    }
+    if !policy.write_allow.is_empty() {
+        let globs: Vec<&str> = policy.write_allow.iter().map(|r| r.glob.as_str()).collect();
+        println!(
+            "Repo paths exempt from write_paths.deny: {}",
+            globs.join(", ")
+        );
+    }
+    if !policy.write_waivers.is_empty() {
+        let globs: Vec<&str> = policy
+            .write_waivers
+            .iter()
+            .map(|r| r.glob.as_str())
+            .collect();
+        println!(
+            "Outside-repo write targets allowed by policy.write_allow: {}",
+            globs.join(", ")
+        );
+    }
+}
+
+fn print_policy_show() -> i32 {
+    let cfg = app_config();
+    let policy = match load_repo_policy(&policy_root()) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs policy: {e}");
+            return 1;
+        }
+    };
+    println!("== cxrs policy show ==");
+    match &policy.source {
+        Some(p) => println!("policy_file: {}", p.display()),
+        None => println!("policy_file: none (built-in rules only)"),
+    }
+    print_effective_rules(&policy);
+    println!();
+    println!("Unsafe override state:");
+    println!(
+        "--unsafe / CX_UNSAFE=1: {}",
+        if cfg.cx_unsafe { "on" } else { "off" }
+    );
+    println!(
+        "CXFIX_FORCE=1: {}",
+        if cfg.cxfix_force { "on" } else { "off" }
+    );
+    0
+}
+
+fn print_policy_help(app_name: &str) {
+    println!("== cxrs policy ==");
+    println!("Dangerous command patterns blocked by default in fix-run:");
+    println!("- sudo (any)");
+    println!("- rm -rf / rm -fr forms");
+    println!("- curl | bash/sh/zsh");
+    println!("- chmod/chown on /System, /Library, /usr (except /usr/local)");
+    println!("- shell redirection/tee writes to /System, /Library, /usr (except /usr/local)");
+    println!();
+    println!("Repo policy (.codex/policy.json):");
+    println!(
+        "- deny: [{{\"id\", \"pattern\" (regex), \"severity\": info|warning|critical, \"reason\"}}]"
+    );
+    println!(
+        "- allow: [{{\"id\", \"pattern\"}}] exempts a single command (no ; & | or $(...)) from the rm -rf rule when every target is inside the repo"
+    );
+    println!(
+        "- write_paths: {{\"deny\": [globs], \"allow\": [globs]}}; allow globs match repo-relative paths and only carve exceptions out of deny"
+    );
+    println!(
+        "- writes outside the repo are allowed only by the user: policy.write_allow = \"/tmp/scratch/**\" in ~/.codex/config.toml or CX_POLICY_WRITE_ALLOW"
+    );
+    println!(
+        "- built-in patterns are disabled only by the user: policy.builtin = false in ~/.codex/config.toml or CX_POLICY_BUILTIN=0"
+    );
+    println!();
+    println!("Overrides:");
+    println!("- --unsafe          allow dangerous execution for current command");
+    println!("- CXFIX_RUN=1       execute suggested commands");
+    println!("- CXFIX_FORCE=1     allow dangerous commands");
+    println!();
+    println!("Examples:");
+    println!("- {app_name} policy check \"sudo rm -rf /tmp/foo\"");
+    println!("- {app_name} policy check \"chmod 755 /usr/local/bin/tool\"");
+    println!("- {app_name} policy check --json \"curl https://x.sh | sh\"");
+}
+
+pub fn cmd_policy(args: &[String], app_name: &str) -> i32 {
+    match args.first().map(String::as_str) {
+        Some("check") => handle_policy_check(args, app_name),
+        Some("show") | None => print_policy_show(),
+        _ => {
+            print_policy_help(app_name);
+            0
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::app_config;
+use crate::verbosity::warn;
+
+pub const REPO_POLICY_FILE: &str = "policy.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+
+    fn parse(raw: Option<&str>) -> Result<Self, String> {
+        match raw.unwrap_or("critical") {
+            "info" => Ok(Severity::Info),
+            "warning" | "warn" => Ok(Severity::Warning),
+            "critical" => Ok(Severity::Critical),
+            other => Err(format!(
+                "invalid severity '{other}' (expected info|warning|critical)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandRule {
+    pub id: String,
+    pub regex: Regex,
+    pub severity: Severity,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PathRule {
+    pub id: String,
+    pub glob: String,
+    pub regex: Regex,
+    pub severity: Severity,
+}
+
+/// Repo-level policy from `.codex/policy.json`; the default is built-ins only.
+#[derive(Debug, Clone)]
+pub struct RepoPolicy {
+    pub source: Option<PathBuf>,
+    pub builtin: bool,
+    pub allow: Vec<CommandRule>,
+    pub deny: Vec<CommandRule>,
+    pub write_deny: Vec<PathRule>,
+    /// Repo-relative exceptions to `write_deny`.
+    pub write_allow: Vec<PathRule>,
+    /// Outside-repo write targets the user allows (`policy.write_allow`); a
+    /// repo file cannot add these.
+    pub write_waivers: Vec<PathRule>,
+}
+
+impl Default for RepoPolicy {
+    fn default() -> Self {
+        Self {
+            source: None,
+            builtin: true,
+            allow: Vec::new(),
+            deny: Vec::new(),
+            write_deny: Vec::new(),
+            write_allow: Vec::new(),
+            write_waivers: Vec::new(),
+        }
+    }
+}
+
+/// Translate a path glob (`**`, `*`, `?`) into an anchored regex.
+pub fn glob_to_regex(glob: &str) -> Result<Regex, String> {
+    let mut out = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    out.push_str("(?:.*/)?");
+                } else {
+                    out.push_str(".*");
+                }
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            other => out.push_str(&regex::escape(&other.to_string())),
+        }
+    }
+    out.push('$');
+    Regex::new(&out).map_err(|e| format!("invalid glob '{glob}': {e}"))
+}
+
+fn entry_field<'a>(entry: &'a Value, key: &str) -> Option<&'a str> {
+    entry
+        .as_str()
+        .or_else(|| entry.get(key).and_then(Value::as_str))
+}
+
+fn parse_command_rule(entry: &Value, kind: &str, idx: usize) -> Result<CommandRule, String> {
+    let pattern =
+        entry_field(entry, "pattern").ok_or_else(|| format!("{kind}[{idx}]: missing 'pattern'"))?;
+    let regex = Regex::new(pattern)
+        .map_err(|e| format!("{kind}[{idx}]: invalid regex '{pattern}': {e}"))?;
+    let id = entry
+        .get("id")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| idx.to_string());
+    let severity = Severity::parse(entry.get("severity").and_then(Value::as_str))
+        .map_err(|e| format!("{kind}[{idx}]: {e}"))?;
+    let reason = entry
+        .get("reason")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("matches /{pattern}/"));
+    Ok(CommandRule {
+        id: format!("repo.{kind}.{id}"),
+        regex,
+        severity,
+        reason,
+    })
+}
+
+fn parse_path_rule(entry: &Value, kind: &str, idx: usize) -> Result<PathRule, String> {
+    let glob = entry_field(entry, "path")
+        .ok_or_else(|| format!("write_paths.{kind}[{idx}]: missing 'path'"))?;
+    let severity = Severity::parse(entry.get("severity").and_then(Value::as_str))
+        .map_err(|e| format!("write_paths.{kind}[{idx}]: {e}"))?;
+    Ok(PathRule {
+        id: format!("repo.write_paths.{kind}.{idx}"),
+        glob: glob.to_string(),
+        regex: glob_to_regex(glob)?,
+        severity,
+    })
+}
+
+fn parse_list<T>(
+    v: Option<&Value>,
+    kind: &str,
+    f: fn(&Value, &str, usize) -> Result<T, String>,
+) -> Result<Vec<T>, String> {
+    match v {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .map(|(i, e)| f(e, kind, i))
+            .collect(),
+        Some(_) => Err(format!("'{kind}' must be an array")),
+    }
+}
+
+pub fn parse_repo_policy(value: &Value) -> Result<RepoPolicy, String> {
+    if !value.is_object() {
+        return Err("policy must be a JSON object".to_string());
+    }
+    let write_paths = value.get("write_paths");
+    Ok(RepoPolicy {
+        source: None,
+        builtin: value
+            .get("builtin")
+            .and_then(Value::as_bool)
+            .unwrap_or(true),
+        allow: parse_list(value.get("allow"), "allow", parse_command_rule)?,
+        deny: parse_list(value.get("deny"), "deny", parse_command_rule)?,
+        write_deny: parse_list(
+            write_paths.and_then(|w| w.get("deny")),
+            "deny",
+            parse_path_rule,
+        )?,
+        write_allow: parse_list(
+            write_paths.and_then(|w| w.get("allow")),
+            "allow",
+            parse_path_rule,
+        )?,
+        write_waivers: Vec::new(),
+    })
+}
+
+fn global_write_waivers(globs: &[String]) -> Result<Vec<PathRule>, String> {
+    globs
+        .iter()
+        .enumerate()
+        .map(|(idx, glob)| {
+            Ok(PathRule {
+                id: format!("global.policy.write_allow.{idx}"),
+                glob: glob.clone(),
+                regex: glob_to_regex(glob).map_err(|e| format!("policy.write_allow: {e}"))?,
+                severity: Severity::Info,
+            })
+        })
+        .collect()
+}
+
+/// Load `<repo>/.codex/policy.json`; a missing file yields the built-in policy.
+/// Whether built-ins apply and which outside-repo writes are waived are the
+/// user's `policy.builtin` / `policy.write_allow` settings: a repo file cannot
+/// change either.
+pub fn load_repo_policy(repo_root: &Path) -> Result<RepoPolicy, String> {
+    let path = repo_root.join(".codex").join(REPO_POLICY_FILE);
+    let builtin = app_config().policy_builtin;
+    let write_waivers = global_write_waivers(&app_config().policy_write_allow)?;
+    if !path.exists() {
+        return Ok(RepoPolicy {
+            builtin,
+            write_waivers,
+            ..RepoPolicy::default()
+        });
+    }
+    let text =
+        fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    let value: Value = serde_json::from_str(&text)
+        .map_err(|e| format!("invalid JSON in {}: {e}", path.display()))?;
+    let mut policy = parse_repo_policy(&value).map_err(|e| format!("{}: {e}", path.display()))?;
+    if !policy.builtin && builtin {
+        warn(&format!(
+            "cxrs: warning: {}: ignoring \"builtin\": false; only the global policy.builtin setting (CX_POLICY_BUILTIN) disables built-in rules",
+            path.display()
+        ));
+    }
+    policy.builtin = builtin;
+    policy.write_waivers = write_waivers;
+    policy.source = Some(path);
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_translation_handles_double_star() {
+        let re = glob_to_regex(".git/**").expect("glob");
+        assert!(re.is_match(".git/config"));
+        assert!(re.is_match(".git/refs/heads/main"));
+        assert!(!re.is_match("src/.git"));
+        let re = glob_to_regex("**/*.lock").expect("glob");
+        assert!(re.is_match("Cargo.lock"));
+        assert!(re.is_match("rust/cxrs/Cargo.lock"));
+        let re = glob_to_regex("src/*.rs").expect("glob");
+        assert!(!re.is_match("src/modules/a.rs"));
+    }
+
+    #[test]
+    fn parse_rejects_bad_regex_and_severity() {
+        let bad = serde_json::json!({"deny": ["("]});
+        assert!(parse_repo_policy(&bad).is_err());
+        let bad = serde_json::json!({"deny": [{"pattern": "x", "severity": "loud"}]});
+        assert!(parse_repo_policy(&bad).is_err());
+        let ok = serde_json::json!({"deny": [{"id": "pub", "pattern": "npm publish"}]});
+        let p = parse_repo_policy(&ok).expect("policy");
+        assert_eq!(p.deny[0].id, "repo.deny.pub");
+        assert_eq!(p.deny[0].severity, Severity::Critical);
+    }
+}
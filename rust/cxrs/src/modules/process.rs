@@ -1,13 +1,19 @@
 use std::fmt;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::process::{Child, Command, ExitStatus, Output, Stdio};
 use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use wait_timeout::ChildExt;
 
 use crate::config::DEFAULT_CMD_TIMEOUT_SECS;
+use crate::config_layers::config_env;
+use crate::signals::shutdown_requested;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+type OutputRx = mpsc::Receiver<std::io::Result<Output>>;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TimeoutInfo {
@@ -18,6 +24,8 @@ pub struct TimeoutInfo {
 #[derive(Debug)]
 pub enum ProcessError {
     Timeout(TimeoutInfo),
+    /// Stopped by Ctrl-C/SIGTERM; carries the command label.
+    Interrupted(String),
     Message(String),
 }
 
@@ -25,7 +33,7 @@ impl ProcessError {
     pub fn timeout_info(&self) -> Option<&TimeoutInfo> {
         match self {
             Self::Timeout(info) => Some(info),
-            Self::Message(_) => None,
+            Self::Interrupted(_) | Self::Message(_) => None,
         }
     }
 }
@@ -36,6 +44,7 @@ impl fmt::Display for ProcessError {
             Self::Timeout(info) => {
                 write!(f, "{} timed out after {}s", info.label, info.timeout_secs)
             }
+            Self::Interrupted(label) => write!(f, "{label} interrupted"),
             Self::Message(msg) => write!(f, "{msg}"),
         }
     }
@@ -48,10 +57,20 @@ fn parse_env_secs(name: &str) -> Option<u64> {
         .map(|v| v.max(1))
 }
 
+/// `CX_LLM_TIMEOUT_SECS` (`llm.timeout_secs`, `0` = inherit), then the legacy
+/// `CX_TIMEOUT_LLM_SECS`. Only LLM labels may consult the config files: loading
+/// them runs `git` through this module.
+fn llm_timeout_secs() -> Option<u64> {
+    config_env("CX_LLM_TIMEOUT_SECS")
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .or_else(|| parse_env_secs("CX_TIMEOUT_LLM_SECS"))
+}
+
 fn timeout_secs_for_label(label: &str) -> u64 {
     let lower = label.to_ascii_lowercase();
-    if (lower.contains("codex") || lower.contains("ollama"))
-        && let Some(v) = parse_env_secs("CX_TIMEOUT_LLM_SECS")
+    if (lower.contains("codex") || lower.contains("ollama") || lower.contains("http"))
+        && let Some(v) = llm_timeout_secs()
     {
         return v;
     }
@@ -105,6 +124,49 @@ fn wait_child_status(child: &mut Child, label: &str) -> Result<ExitStatus, Proce
     }
 }
 
+fn stop_child(rx: &OutputRx, pid: u32) {
+    terminate_pid(pid);
+    if rx.recv_timeout(Duration::from_secs(2)).is_err() {
+        kill_pid(pid);
+    }
+}
+
+/// Wait for the output worker in short slices so a shutdown request (Ctrl-C
+/// under a `signals::ShutdownGuard`) stops the child instead of blocking until
+/// the timeout expires.
+fn wait_output(rx: &OutputRx, pid: u32, label: &str) -> Result<Output, ProcessError> {
+    let deadline = Instant::now() + timeout_duration(label);
+    loop {
+        if shutdown_requested() {
+            stop_child(rx, pid);
+            return Err(ProcessError::Interrupted(label.to_string()));
+        }
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            stop_child(rx, pid);
+            return Err(timeout_error(label));
+        }
+        match rx.recv_timeout(left.min(POLL_INTERVAL)) {
+            // The terminal delivers SIGINT to the child too; report the
+            // interrupt rather than the child's signal exit.
+            Ok(_) if shutdown_requested() => {
+                return Err(ProcessError::Interrupted(label.to_string()));
+            }
+            Ok(res) => {
+                return res.map_err(|e| {
+                    ProcessError::Message(format!("{label} read output failed: {e}"))
+                });
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(_) => {
+                return Err(ProcessError::Message(format!(
+                    "{label} output worker channel closed unexpectedly"
+                )));
+            }
+        }
+    }
+}
+
 pub fn run_command_status_with_timeout_meta(
     mut cmd: Command,
     label: &str,
@@ -132,25 +194,119 @@ pub fn run_command_output_with_timeout_meta(
     thread::spawn(move || {
         let _ = tx.send(child.wait_with_output());
     });
-    match rx.recv_timeout(timeout_duration(label)) {
-        Ok(res) => {
-            res.map_err(|e| ProcessError::Message(format!("{label} read output failed: {e}")))
-        }
-        Err(mpsc::RecvTimeoutError::Timeout) => {
-            terminate_pid(pid);
-            if rx.recv_timeout(Duration::from_secs(2)).is_err() {
-                kill_pid(pid);
+    wait_output(&rx, pid, label)
+}
+
+pub fn run_command_output_with_timeout(cmd: Command, label: &str) -> Result<Output, String> {
+    run_command_output_with_timeout_meta(cmd, label).map_err(|e| e.to_string())
+}
+
+/// Read `reader` to EOF, echoing each chunk to our stderr as it arrives when
+/// `tee` is set. A read error ends the stream; the bytes read so far are kept.
+fn drain(mut reader: impl Read, tee: bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => {
+                if tee {
+                    let mut err = std::io::stderr().lock();
+                    let _ = err.write_all(&chunk[..n]);
+                    let _ = err.flush();
+                }
+                out.extend_from_slice(&chunk[..n]);
             }
-            Err(timeout_error(label))
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(_) => break,
         }
-        Err(_) => Err(ProcessError::Message(format!(
-            "{label} output worker channel closed unexpectedly"
-        ))),
     }
+    out
 }
 
-pub fn run_command_output_with_timeout(cmd: Command, label: &str) -> Result<Output, String> {
-    run_command_output_with_timeout_meta(cmd, label).map_err(|e| e.to_string())
+/// Like [`run_command_output_with_timeout`], but the child's stdout and stderr
+/// are also passed through to our stderr live, keeping our stdout for the
+/// model's answer.
+pub fn run_command_tee_with_timeout(mut cmd: Command, label: &str) -> Result<Output, String> {
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("{label} spawn failed: {e}"))?;
+    let pid = child.id();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let err_worker = thread::spawn(move || stderr.map(|r| drain(r, true)).unwrap_or_default());
+        let stdout = stdout.map(|r| drain(r, true)).unwrap_or_default();
+        let stderr = err_worker.join().unwrap_or_default();
+        let _ = tx.send(child.wait().map(|status| Output {
+            status,
+            stdout,
+            stderr,
+        }));
+    });
+    wait_output(&rx, pid, label).map_err(|e| e.to_string())
+}
+
+/// Run `argv` on a pseudo-terminal so TTY-sensitive tools keep their
+/// interactive output. The terminal merges both streams into `stdout`, which
+/// is echoed to our stderr as it arrives when `tee` is set. `envs` are added
+/// to the child's environment only.
+#[cfg(unix)]
+pub fn run_command_in_pty_with_timeout(
+    argv: &[String],
+    label: &str,
+    tee: bool,
+    envs: &[(&str, &str)],
+) -> Result<Output, String> {
+    use portable_pty::{CommandBuilder, PtySize, native_pty_system};
+    use std::os::unix::process::ExitStatusExt;
+
+    let pair = native_pty_system()
+        .openpty(PtySize {
+            rows: 50,
+            cols: 200,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("{label} pty open failed: {e}"))?;
+    let mut builder = CommandBuilder::from_argv(argv.iter().map(Into::into).collect());
+    // portable-pty defaults to $HOME rather than the caller's directory.
+    if let Ok(cwd) = std::env::current_dir() {
+        builder.cwd(cwd);
+    }
+    if std::env::var_os("TERM").is_none() {
+        builder.env("TERM", "xterm-256color");
+    }
+    for (k, v) in envs {
+        builder.env(k, v);
+    }
+    let mut child = pair
+        .slave
+        .spawn_command(builder)
+        .map_err(|e| format!("{label} spawn failed: {e}"))?;
+    // Only the child may hold the slave end, so reads hit EOF when it exits.
+    drop(pair.slave);
+    let reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| format!("{label} pty read failed: {e}"))?;
+    let pid = child.process_id().unwrap_or_default();
+    let (tx, rx) = mpsc::channel();
+    let master = pair.master;
+    thread::spawn(move || {
+        let _master = master;
+        // Linux reports the hangup as EIO, which `drain` treats as EOF.
+        let buf = drain(reader, tee);
+        let res = child.wait().map(|s| Output {
+            status: ExitStatus::from_raw((s.exit_code() as i32) << 8),
+            stdout: buf,
+            stderr: Vec::new(),
+        });
+        let _ = tx.send(res);
+    });
+    wait_output(&rx, pid, label).map_err(|e| e.to_string())
 }
 
 pub fn run_command_with_stdin_output_with_timeout_meta(
@@ -175,21 +331,105 @@ pub fn run_command_with_stdin_output_with_timeout_meta(
     thread::spawn(move || {
         let _ = tx.send(child.wait_with_output());
     });
-    match rx.recv_timeout(timeout_duration(label)) {
-        Ok(res) => {
-            res.map_err(|e| ProcessError::Message(format!("{label} read output failed: {e}")))
+    wait_output(&rx, pid, label)
+}
+
+/// Like [`run_command_with_stdin_output_with_timeout_meta`], but hands stdout
+/// to `on_chunk` as it is read. The returned `Output` still carries the full
+/// stdout; timeouts and shutdown requests stop the child the same way.
+pub fn run_command_with_stdin_streaming_meta(
+    mut cmd: Command,
+    stdin_text: &str,
+    label: &str,
+    on_chunk: &mut dyn FnMut(&[u8]),
+) -> Result<Output, ProcessError> {
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| ProcessError::Message(format!("{label} spawn failed: {e}")))?;
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin
+            .write_all(stdin_text.as_bytes())
+            .map_err(|e| ProcessError::Message(format!("{label} failed writing stdin: {e}")))?;
+    }
+    let _ = child.stdin.take();
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| ProcessError::Message(format!("{label} stdout unavailable")))?;
+    let mut stderr_pipe = child.stderr.take();
+    let stderr_worker = thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(pipe) = stderr_pipe.as_mut() {
+            let _ = pipe.read_to_end(&mut buf);
         }
-        Err(mpsc::RecvTimeoutError::Timeout) => {
-            terminate_pid(pid);
-            if rx.recv_timeout(Duration::from_secs(2)).is_err() {
-                kill_pid(pid);
+        buf
+    });
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        while let Ok(n) = stdout.read(&mut buf) {
+            if n == 0 || tx.send(buf[..n].to_vec()).is_err() {
+                break;
             }
-            Err(timeout_error(label))
         }
-        Err(_) => Err(ProcessError::Message(format!(
-            "{label} output worker channel closed unexpectedly"
-        ))),
+    });
+
+    let pid = child.id();
+    let stop = |child: &mut Child| {
+        terminate_pid(pid);
+        if child
+            .wait_timeout(Duration::from_secs(2))
+            .ok()
+            .flatten()
+            .is_none()
+        {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    };
+    let deadline = Instant::now() + timeout_duration(label);
+    let mut collected = Vec::new();
+    loop {
+        if shutdown_requested() {
+            stop(&mut child);
+            return Err(ProcessError::Interrupted(label.to_string()));
+        }
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            stop(&mut child);
+            return Err(timeout_error(label));
+        }
+        match rx.recv_timeout(left.min(POLL_INTERVAL)) {
+            Ok(bytes) => {
+                on_chunk(&bytes);
+                collected.extend_from_slice(&bytes);
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
     }
+    let left = deadline.saturating_duration_since(Instant::now());
+    let status = match child
+        .wait_timeout(left.max(Duration::from_millis(1)))
+        .map_err(|e| ProcessError::Message(format!("{label} wait failed: {e}")))?
+    {
+        Some(status) => status,
+        None => {
+            stop(&mut child);
+            return Err(timeout_error(label));
+        }
+    };
+    if shutdown_requested() {
+        return Err(ProcessError::Interrupted(label.to_string()));
+    }
+    Ok(Output {
+        status,
+        stdout: collected,
+        stderr: stderr_worker.join().unwrap_or_default(),
+    })
 }
 
 pub fn run_command_with_stdin_output_with_timeout(
@@ -216,7 +456,7 @@ mod tests {
                 assert_eq!(info.label, "timeout-test");
                 assert_eq!(info.timeout_secs, 17);
             }
-            ProcessError::Message(msg) => panic!("expected timeout, got message: {msg}"),
+            other => panic!("expected timeout, got: {other}"),
         }
     }
 
@@ -0,0 +1,237 @@
+use serde_json::{Map, Value, json};
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::config::app_config;
+use crate::execmeta::utc_now_iso;
+
+/// Where pipeline progress goes (`CX_PROGRESS`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    Off,
+    /// One status line on stderr, redrawn in place.
+    Human,
+    /// One JSON object per event on stderr.
+    Json,
+}
+
+impl ProgressMode {
+    /// `auto` (default) is human text when stderr is a terminal, else off.
+    fn resolve(raw: &str, stderr_tty: bool) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "json" => ProgressMode::Json,
+            "human" | "1" => ProgressMode::Human,
+            "off" | "0" => ProgressMode::Off,
+            _ if stderr_tty => ProgressMode::Human,
+            _ => ProgressMode::Off,
+        }
+    }
+}
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+/// Minimum gap between `llm_tokens` events.
+const TOKEN_EVENT_GAP: Duration = Duration::from_millis(250);
+
+#[derive(Default)]
+struct Status {
+    /// A human status line is on screen and must be cleared before other stderr.
+    drawn: bool,
+    frame: usize,
+    tokens: u64,
+    last_tokens_event: Option<Instant>,
+}
+
+static MODE: OnceLock<ProgressMode> = OnceLock::new();
+static STATUS: Mutex<Option<Status>> = Mutex::new(None);
+
+pub fn mode() -> ProgressMode {
+    *MODE.get_or_init(|| ProgressMode::resolve(&app_config().progress, io::stderr().is_terminal()))
+}
+
+pub fn enabled() -> bool {
+    mode() != ProgressMode::Off
+}
+
+fn with_status<T>(f: impl FnOnce(&mut Status) -> T) -> T {
+    let mut guard = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(Status::default))
+}
+
+/// Erase a drawn human status line so the next stderr write starts clean.
+/// Reads the mode without resolving it: the config loader reports through
+/// `cx_eprintln!`, and nothing is drawn before the mode is known.
+pub fn clear_line() {
+    if MODE.get() != Some(&ProgressMode::Human) {
+        return;
+    }
+    with_status(|s| {
+        if s.drawn {
+            let _ = io::stderr().write_all(b"\r\x1b[2K");
+            s.drawn = false;
+        }
+    });
+}
+
+fn field_str(fields: &[(&str, Value)], key: &str) -> String {
+    match fields.iter().find(|(k, _)| *k == key).map(|(_, v)| v) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(v) => v.to_string(),
+    }
+}
+
+/// Status-line text for `event`; `None` clears the line instead.
+fn human_text(event: &str, fields: &[(&str, Value)]) -> Option<String> {
+    let f = |k: &str| field_str(fields, k);
+    Some(match event {
+        "capture_started" => format!("capture: running `{}`", f("cmd")),
+        "capture_finished" => format!(
+            "capture: `{}` exited {} ({} B stdout, {} B stderr, {} ms)",
+            f("cmd"),
+            f("status"),
+            f("stdout_bytes"),
+            f("stderr_bytes"),
+            f("duration_ms")
+        ),
+        "reduce_applied" => format!(
+            "reduce: {} {} -> {} chars",
+            f("reducer"),
+            f("chars_before"),
+            f("chars_after")
+        ),
+        "clip_applied" => format!(
+            "clip: {} {} -> {} chars",
+            f("strategy"),
+            f("chars_before"),
+            f("chars_after")
+        ),
+        "llm_started" => format!("llm: waiting for {} ({})", f("backend"), f("tool")),
+        "llm_tokens" => format!("llm: {} tokens", f("tokens")),
+        _ => return None,
+    })
+}
+
+/// Emit one progress event: a JSON line under `CX_PROGRESS=json`, or a redrawn
+/// status line in human mode. No-op when progress is off.
+pub fn emit(event: &str, fields: &[(&str, Value)]) {
+    match mode() {
+        ProgressMode::Off => {}
+        ProgressMode::Json => {
+            let mut obj = Map::new();
+            obj.insert("type".to_string(), Value::from("progress"));
+            obj.insert("event".to_string(), Value::from(event));
+            obj.insert("ts".to_string(), Value::from(utc_now_iso()));
+            for (k, v) in fields {
+                obj.insert((*k).to_string(), v.clone());
+            }
+            crate::cx_eprintln!("{}", Value::Object(obj));
+        }
+        ProgressMode::Human => {
+            let Some(text) = human_text(event, fields) else {
+                clear_line();
+                return;
+            };
+            with_status(|s| {
+                let frame = SPINNER[s.frame % SPINNER.len()];
+                s.frame += 1;
+                let mut err = io::stderr().lock();
+                let _ = write!(err, "\r\x1b[2K{frame} {text}");
+                let _ = err.flush();
+                s.drawn = true;
+            });
+        }
+    }
+}
+
+/// An LLM call in flight: `llm_started` now, `llm_finished` (with duration and
+/// streamed token count) when dropped, so early returns still close it.
+pub struct LlmSpan {
+    tool: String,
+    started: Instant,
+}
+
+pub fn llm_started(tool: &str, prompt: &str) -> LlmSpan {
+    with_status(|s| {
+        s.tokens = 0;
+        s.last_tokens_event = None;
+    });
+    emit(
+        "llm_started",
+        &[
+            ("tool", json!(tool)),
+            ("backend", json!(crate::runtime::llm_backend())),
+            ("model", json!(crate::runtime::llm_model())),
+            ("prompt_chars", json!(prompt.chars().count())),
+        ],
+    );
+    LlmSpan {
+        tool: tool.to_string(),
+        started: Instant::now(),
+    }
+}
+
+impl Drop for LlmSpan {
+    fn drop(&mut self) {
+        let tokens = with_status(|s| s.tokens);
+        emit(
+            "llm_finished",
+            &[
+                ("tool", json!(self.tool)),
+                (
+                    "duration_ms",
+                    json!(self.started.elapsed().as_millis() as u64),
+                ),
+                ("tokens", json!(tokens)),
+            ],
+        );
+    }
+}
+
+/// Count `n` more streamed tokens; emits `llm_tokens` at most every
+/// [`TOKEN_EVENT_GAP`]. `echoing` printers already show the text itself, so
+/// human mode leaves their output alone.
+pub fn tokens_streamed(n: usize, echoing: bool) {
+    if n == 0 || !enabled() {
+        return;
+    }
+    let due = with_status(|s| {
+        s.tokens += n as u64;
+        let due = s
+            .last_tokens_event
+            .is_none_or(|t| t.elapsed() >= TOKEN_EVENT_GAP);
+        if due {
+            s.last_tokens_event = Some(Instant::now());
+        }
+        due.then_some(s.tokens)
+    });
+    if let Some(total) = due
+        && !(echoing && mode() == ProgressMode::Human)
+    {
+        emit("llm_tokens", &[("tokens", json!(total))]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode_defaults_to_human_only_on_a_terminal() {
+        assert_eq!(ProgressMode::resolve("auto", true), ProgressMode::Human);
+        assert_eq!(ProgressMode::resolve("auto", false), ProgressMode::Off);
+        assert_eq!(ProgressMode::resolve("JSON", false), ProgressMode::Json);
+        assert_eq!(ProgressMode::resolve("off", true), ProgressMode::Off);
+        let text = human_text(
+            "clip_applied",
+            &[
+                ("strategy", json!("smart")),
+                ("chars_before", json!(900)),
+                ("chars_after", json!(300)),
+            ],
+        );
+        assert_eq!(text.as_deref(), Some("clip: smart 900 -> 300 chars"));
+        assert_eq!(human_text("llm_finished", &[]), None);
+    }
+}
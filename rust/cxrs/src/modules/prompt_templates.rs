@@ -0,0 +1,174 @@
+use std::fs;
+
+use crate::paths::resolve_prompt_template_dir;
+use crate::types::PromptTemplateStamp;
+use crate::util::sha256_hex;
+
+pub struct PromptTemplateSpec {
+    pub tool: &'static str,
+    pub required: &'static [&'static str],
+    pub optional: &'static [&'static str],
+    pub builtin: &'static str,
+}
+
+pub const PROMPT_TEMPLATES: &[PromptTemplateSpec] = &[
+    PromptTemplateSpec {
+        tool: "fix",
+        required: &["command", "status", "output"],
+        optional: &["schema"],
+        builtin: "You are my terminal debugging assistant.\nTask:\n1) Explain what happened (brief).\n2) If the command failed, diagnose likely cause(s).\n3) Propose the next 3 commands to run to confirm/fix.\n4) If it is a configuration issue, point to exact file/line patterns to check.\n\nCommand:\n{{command}}\n\nExit status: {{status}}\n\nOutput:\n{{output}}",
+    },
+    PromptTemplateSpec {
+        tool: "next",
+        required: &["command", "status", "output"],
+        optional: &["schema"],
+        builtin: "Based on the terminal command output below, propose the NEXT shell commands to run.\nReturn 1-6 commands in execution order.\n\nExecuted command:\n{{command}}\nExit status: {{status}}\n\nTERMINAL OUTPUT:\n{{output}}",
+    },
+    PromptTemplateSpec {
+        tool: "diffsum",
+        required: &["output"],
+        optional: &["format", "label", "schema"],
+        builtin: "Write a PR-ready summary of this diff.\nKeep bullets concise and actionable.\nPreferred PR summary format: {{format}}\n\n{{label}}:\n{{output}}",
+    },
+    PromptTemplateSpec {
+        tool: "commitjson",
+        required: &["output"],
+        optional: &["style", "schema", "stats", "scope"],
+        builtin: "Generate a commit object from this STAGED diff.\n{{style}}\n\nCHANGE SUMMARY:\n{{stats}}\nSuggested scope: {{scope}}\nUse the suggested scope for the `scope` field unless the diff clearly belongs elsewhere.\n\nSTAGED DIFF:\n{{output}}",
+    },
+];
+
+fn template_spec(tool: &str) -> Result<&'static PromptTemplateSpec, String> {
+    PROMPT_TEMPLATES
+        .iter()
+        .find(|t| t.tool == tool)
+        .ok_or_else(|| format!("no prompt template registered for '{tool}'"))
+}
+
+/// Split a template into literal text and `{{name}}` placeholder names.
+fn placeholders(template: &str) -> Vec<(usize, usize, String)> {
+    let mut out = Vec::new();
+    let mut from = 0usize;
+    while let Some(open) = template[from..].find("{{").map(|i| i + from) {
+        let Some(close) = template[open + 2..].find("}}").map(|i| i + open + 2) else {
+            break;
+        };
+        let name = template[open + 2..close].trim().to_string();
+        out.push((open, close + 2, name));
+        from = close + 2;
+    }
+    out
+}
+
+/// Distinct `{{name}}` placeholders in order of first use.
+pub(crate) fn placeholder_names(template: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for (_, _, name) in placeholders(template) {
+        if !out.contains(&name) {
+            out.push(name);
+        }
+    }
+    out
+}
+
+fn validate_template(spec: &PromptTemplateSpec, template: &str) -> Result<(), String> {
+    check_placeholders(template, spec.required, spec.optional)
+}
+
+/// Check that every required placeholder is present and no unknown ones are used.
+pub(crate) fn check_placeholders(
+    template: &str,
+    required: &[&str],
+    optional: &[&str],
+) -> Result<(), String> {
+    let used: Vec<String> = placeholders(template).into_iter().map(|p| p.2).collect();
+    if let Some(unknown) = used
+        .iter()
+        .find(|n| !required.contains(&n.as_str()) && !optional.contains(&n.as_str()))
+    {
+        let mut allowed: Vec<&str> = required.to_vec();
+        allowed.extend_from_slice(optional);
+        return Err(format!(
+            "unknown placeholder {{{{{unknown}}}}} (allowed: {})",
+            allowed.join(", ")
+        ));
+    }
+    if let Some(missing) = required.iter().find(|r| !used.iter().any(|n| n == *r)) {
+        return Err(format!("missing required placeholder {{{{{missing}}}}}"));
+    }
+    Ok(())
+}
+
+/// Substitute placeholders in one pass so values containing `{{..}}` stay literal.
+pub(crate) fn fill(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut last = 0usize;
+    for (start, end, name) in placeholders(template) {
+        out.push_str(&template[last..start]);
+        let value = vars.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+        out.push_str(value.unwrap_or(""));
+        last = end;
+    }
+    out.push_str(&template[last..]);
+    out
+}
+
+/// Render the prompt for `tool`, preferring `.codex/prompts/<tool>.tmpl` over the
+/// built-in text; the stamp identifies which template produced it.
+pub fn render_prompt(
+    tool: &str,
+    vars: &[(&str, &str)],
+) -> Result<(String, PromptTemplateStamp), String> {
+    let spec = template_spec(tool)?;
+    let path = resolve_prompt_template_dir()
+        .map(|d| d.join(format!("{tool}.tmpl")))
+        .filter(|p| p.is_file());
+    let (template, source) = match path {
+        Some(p) => {
+            let raw = fs::read_to_string(&p)
+                .map_err(|e| format!("failed to read {}: {e}", p.display()))?;
+            validate_template(spec, &raw)
+                .map_err(|e| format!("prompt template {}: {e}", p.display()))?;
+            (raw, p.display().to_string())
+        }
+        None => (spec.builtin.to_string(), "builtin".to_string()),
+    };
+    let stamp = PromptTemplateStamp {
+        name: tool.to_string(),
+        source,
+        sha256: sha256_hex(&template),
+    };
+    Ok((fill(&template, vars), stamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_validate_and_fill() {
+        for spec in PROMPT_TEMPLATES {
+            validate_template(spec, spec.builtin).expect(spec.tool);
+        }
+        let out = fill(
+            "cmd={{command}} st={{ status }} {{output}}",
+            &[
+                ("command", "ls"),
+                ("status", "0"),
+                ("output", "{{command}}"),
+            ],
+        );
+        assert_eq!(out, "cmd=ls st=0 {{command}}");
+    }
+
+    #[test]
+    fn validation_reports_missing_and_unknown_placeholders() {
+        let spec = template_spec("fix").unwrap();
+        let err = validate_template(spec, "{{command}} {{status}}").unwrap_err();
+        assert_eq!(err, "missing required placeholder {{output}}");
+        let err =
+            validate_template(spec, "{{command}} {{status}} {{output}} {{oops}}").unwrap_err();
+        assert!(err.starts_with("unknown placeholder {{oops}}"));
+        assert!(template_spec("nope").is_err());
+    }
+}
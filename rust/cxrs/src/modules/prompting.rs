@@ -1,59 +1,75 @@
+use serde_json::{Value, json};
 use std::collections::HashMap;
+use std::path::Path;
 
-use crate::logs::load_runs;
+use crate::analytics::print_json_value;
+use crate::contract_versions::PROMPTLINT_JSON_CONTRACT_VERSION;
+use crate::fanout_llm::{generate_subtasks, print_llm_fanout};
 use crate::paths::resolve_log_file;
+use crate::roles::{RoleDef, load_roles, role_names};
+use crate::run_filter::{RunFilter, load_filtered_runs};
+use crate::structured_cmds::ExecuteTaskFn;
 
 type ToolTokenMap = HashMap<String, (u64, u64)>;
 
-fn print_roles() -> i32 {
-    println!("== cxrs roles ==");
-    println!("architect   Define approach, boundaries, and tradeoffs.");
-    println!("implementer Apply focused code changes with minimal blast radius.");
-    println!("reviewer    Validate regressions, risks, and missing tests.");
-    println!("tester      Design and run deterministic checks.");
-    println!("doc         Produce concise operator-facing documentation.");
-    0
+fn load_roles_or_exit(cmd: &str) -> Result<Vec<RoleDef>, i32> {
+    load_roles().map_err(|e| {
+        crate::cx_eprintln!("cxrs {cmd}: {e}");
+        1
+    })
 }
 
-fn role_header(role: &str) -> Option<&'static str> {
-    match role {
-        "architect" => Some(
-            "Role: architect\nFocus: design and decomposition.\nDeliver: implementation plan, constraints, and acceptance checks.",
-        ),
-        "implementer" => Some(
-            "Role: implementer\nFocus: minimal cohesive code change.\nDeliver: patch summary and verification commands.",
-        ),
-        "reviewer" => Some(
-            "Role: reviewer\nFocus: bugs, regressions, and safety.\nDeliver: findings ordered by severity with file references.",
-        ),
-        "tester" => Some(
-            "Role: tester\nFocus: deterministic validation.\nDeliver: test matrix, observed results, and failure triage.",
-        ),
-        "doc" => Some(
-            "Role: doc\nFocus: user/operator clarity.\nDeliver: concise docs with examples and expected outputs.",
-        ),
-        _ => None,
-    }
+fn resolve_role(cmd: &str, name: &str) -> Result<RoleDef, i32> {
+    let roles = load_roles_or_exit(cmd)?;
+    roles
+        .iter()
+        .find(|r| r.name == name)
+        .cloned()
+        .ok_or_else(|| {
+            crate::cx_eprintln!(
+                "cxrs {cmd}: unknown role '{name}' (known: {})",
+                role_names(&roles)
+            );
+            2
+        })
 }
 
 pub fn cmd_roles(role: Option<&str>) -> i32 {
     if let Some(r) = role {
-        let Some(header) = role_header(r) else {
-            crate::cx_eprintln!("cxrs roles: unknown role '{r}'");
-            return 2;
+        return match resolve_role("roles", r) {
+            Ok(def) => {
+                println!("{}", def.header);
+                0
+            }
+            Err(code) => code,
         };
-        println!("{header}");
-        return 0;
     }
-    print_roles()
+    let roles = match load_roles_or_exit("roles") {
+        Ok(v) => v,
+        Err(code) => return code,
+    };
+    println!("== cxrs roles ==");
+    for r in &roles {
+        let origin = if r.source == "builtin" {
+            String::new()
+        } else {
+            format!(" [{}]", r.source)
+        };
+        println!("{:<11} {}{origin}", r.name, r.summary);
+    }
+    0
 }
 
-pub fn cmd_prompt(mode: &str, request: &str) -> i32 {
+pub fn cmd_prompt(mode: &str, role: Option<&str>, request: &str) -> i32 {
     let valid = ["implement", "fix", "test", "doc", "ops"];
     if !valid.contains(&mode) {
         crate::cx_eprintln!("cxrs prompt: invalid mode '{mode}' (use implement|fix|test|doc|ops)");
         return 2;
     }
+    let role = match role.map(|r| resolve_role("prompt", r)).transpose() {
+        Ok(v) => v,
+        Err(code) => return code,
+    };
     let mode_goal = match mode {
         "implement" => "Implement the requested behavior with minimal risk and clear verification.",
         "fix" => "Diagnose and fix the issue with root-cause focus and regression prevention.",
@@ -64,6 +80,10 @@ pub fn cmd_prompt(mode: &str, request: &str) -> i32 {
     };
     println!("You are working on the \"cx\" toolchain.");
     println!();
+    if let Some(r) = role {
+        println!("{}", r.header);
+        println!();
+    }
     println!("Context:");
     println!("- Repo canonical implementation is the source of truth.");
     println!("- Keep behavior deterministic and non-interactive.");
@@ -96,30 +116,98 @@ pub fn cmd_prompt(mode: &str, request: &str) -> i32 {
     0
 }
 
-pub fn cmd_fanout(objective: &str) -> i32 {
-    let tasks = [
-        (
-            "architect",
-            "Define minimal design and split objective into independent slices.",
-        ),
-        (
-            "implementer",
-            "Implement slice A with deterministic behavior and tests.",
-        ),
-        (
-            "implementer",
-            "Implement slice B with minimal shared-state coupling.",
-        ),
-        (
-            "reviewer",
-            "Audit for regressions, safety issues, and schema/pipeline risks.",
-        ),
-        (
-            "tester",
-            "Create execution checklist and validate outputs against expectations.",
-        ),
-        ("doc", "Update operator docs and examples for new behavior."),
-    ];
+const FANOUT_TASKS: [(&str, &str); 6] = [
+    (
+        "architect",
+        "Define minimal design and split objective into independent slices.",
+    ),
+    (
+        "implementer",
+        "Implement slice A with deterministic behavior and tests.",
+    ),
+    (
+        "implementer",
+        "Implement slice B with minimal shared-state coupling.",
+    ),
+    (
+        "reviewer",
+        "Audit for regressions, safety issues, and schema/pipeline risks.",
+    ),
+    (
+        "tester",
+        "Create execution checklist and validate outputs against expectations.",
+    ),
+    ("doc", "Update operator docs and examples for new behavior."),
+];
+
+/// `fanout [--llm | --role NAME...] <objective>`: `--llm` asks the backend to
+/// split the objective; with roles, emit one subtask per listed role (built-in
+/// or custom) instead of the default six.
+pub fn cmd_fanout(args: &[String], execute_task: ExecuteTaskFn) -> i32 {
+    let mut picked: Vec<RoleDef> = Vec::new();
+    let mut words: Vec<&str> = Vec::new();
+    let mut llm = false;
+    let mut i = 0usize;
+    while i < args.len() {
+        if args[i] == "--llm" {
+            llm = true;
+            i += 1;
+            continue;
+        }
+        if args[i] == "--role" {
+            let Some(name) = args.get(i + 1) else {
+                crate::cx_eprintln!("cxrs fanout: --role requires a value");
+                return 2;
+            };
+            match resolve_role("fanout", &name.to_lowercase()) {
+                Ok(def) => picked.push(def),
+                Err(code) => return code,
+            }
+            i += 2;
+            continue;
+        }
+        words.push(&args[i]);
+        i += 1;
+    }
+    if words.is_empty() {
+        crate::cx_eprintln!("cxrs fanout: missing objective");
+        return 2;
+    }
+    let objective = words.join(" ");
+    if llm {
+        if !picked.is_empty() {
+            crate::cx_eprintln!("cxrs fanout: --llm cannot be combined with --role");
+            return 2;
+        }
+        return match generate_subtasks(&objective, None, execute_task) {
+            Ok(subtasks) => {
+                print_llm_fanout(&objective, &subtasks);
+                0
+            }
+            Err(e) => {
+                crate::cx_eprintln!("cxrs fanout: {e}");
+                1
+            }
+        };
+    }
+    let tasks: Vec<(String, String)> = if picked.is_empty() {
+        FANOUT_TASKS
+            .iter()
+            .map(|(r, t)| (r.to_string(), t.to_string()))
+            .collect()
+    } else {
+        picked
+            .iter()
+            .map(|r| {
+                let goal = FANOUT_TASKS
+                    .iter()
+                    .find(|(name, _)| *name == r.name && r.source == "builtin")
+                    .map(|(_, t)| t.to_string())
+                    .unwrap_or_else(|| r.summary.clone());
+                (r.name.clone(), goal)
+            })
+            .collect()
+    };
     println!("== cxrs fanout ==");
     println!("objective: {objective}");
     println!();
@@ -134,24 +222,86 @@ pub fn cmd_fanout(objective: &str) -> i32 {
     0
 }
 
-pub fn cmd_promptlint(n: usize) -> i32 {
-    let (log_file, runs) = match load_promptlint_runs(n) {
+pub(crate) struct PromptlintReport {
+    top_eff: Vec<(String, u64)>,
+    drift_rows: Vec<(String, i64, u64, u64)>,
+    poor_cache: Vec<(String, u64)>,
+    pub(crate) recommendations: Vec<String>,
+}
+
+pub(crate) fn promptlint_report(runs: &[crate::types::RunEntry]) -> PromptlintReport {
+    let (tool_eff, tool_cache) = collect_promptlint_maps(runs);
+    let top_eff = top_effective_rows(&tool_eff);
+    let drift_rows = prompt_drift_rows(runs, &tool_eff);
+    let poor_cache = poor_cache_rows(&tool_cache);
+    let recommendations = promptlint_recommendations(&top_eff, &drift_rows, &poor_cache);
+    PromptlintReport {
+        top_eff,
+        drift_rows,
+        poor_cache,
+        recommendations,
+    }
+}
+
+fn print_promptlint_json(
+    n: usize,
+    runs: usize,
+    report: &PromptlintReport,
+    filter: &RunFilter,
+    log_file: &Path,
+) -> i32 {
+    let mut out = json!({
+        "contract_version": PROMPTLINT_JSON_CONTRACT_VERSION,
+        "window": n,
+        "runs": runs,
+        "top_token_heavy": report.top_eff.iter().map(|(tool, avg)| {
+            json!({"tool": tool, "avg_effective_input_tokens": avg})
+        }).collect::<Vec<Value>>(),
+        "prompt_drift": report.drift_rows.iter().map(|(tool, delta, first, second)| {
+            json!({"tool": tool, "delta": delta, "first_avg": first, "second_avg": second})
+        }).collect::<Vec<Value>>(),
+        "poor_cache": report.poor_cache.iter().map(|(tool, pct)| {
+            json!({"tool": tool, "cache_hit_pct": pct})
+        }).collect::<Vec<Value>>(),
+        "recommendations": report.recommendations,
+        "log_file": log_file.display().to_string(),
+    });
+    if let Some(f) = filter.to_json() {
+        out["filter"] = f;
+    }
+    print_json_value("cxrs promptlint", &out)
+}
+
+fn print_promptlint_header(n: usize, filter: &RunFilter) {
+    println!("== cxrs promptlint (last {n} runs) ==");
+    if !filter.is_empty() {
+        println!("Filter: {}", filter.label());
+    }
+}
+
+pub fn cmd_promptlint(n: usize, json_out: bool, filter: &RunFilter) -> i32 {
+    let (log_file, runs) = match load_promptlint_runs(n, filter) {
         Ok(v) => v,
         Err(code) => return code,
     };
+    let report = promptlint_report(&runs);
+    if json_out {
+        return print_promptlint_json(n, runs.len(), &report, filter, &log_file);
+    }
     if runs.is_empty() {
-        println!("== cxrs promptlint (last {n} runs) ==");
+        print_promptlint_header(n, filter);
         println!("No runs found.");
         println!("log_file: {}", log_file.display());
         return 0;
     }
+    let PromptlintReport {
+        top_eff,
+        drift_rows,
+        poor_cache,
+        recommendations,
+    } = report;
 
-    let (tool_eff, tool_cache) = collect_promptlint_maps(&runs);
-    let top_eff = top_effective_rows(&tool_eff);
-    let drift_rows = prompt_drift_rows(&runs, &tool_eff);
-    let poor_cache = poor_cache_rows(&tool_cache);
-
-    println!("== cxrs promptlint (last {n} runs) ==");
+    print_promptlint_header(n, filter);
     println!("Top token-heavy tools (avg effective_input_tokens):");
     if top_eff.is_empty() {
         println!("- n/a");
@@ -179,13 +329,14 @@ pub fn cmd_promptlint(n: usize) -> i32 {
         }
     }
 
-    print_promptlint_recommendations(&top_eff, &drift_rows, &poor_cache);
+    print_promptlint_recommendations(&recommendations);
     println!("log_file: {}", log_file.display());
     0
 }
 
 fn load_promptlint_runs(
     n: usize,
+    filter: &RunFilter,
 ) -> Result<(std::path::PathBuf, Vec<crate::types::RunEntry>), i32> {
     let Some(log_file) = resolve_log_file() else {
         crate::cx_eprintln!("cxrs: unable to resolve log file");
@@ -194,7 +345,7 @@ fn load_promptlint_runs(
     if !log_file.exists() {
         return Ok((log_file, Vec::new()));
     }
-    let runs = load_runs(&log_file, n).map_err(|e| {
+    let runs = load_filtered_runs(&log_file, n, filter).map_err(|e| {
         crate::cx_eprintln!("cxrs promptlint: {e}");
         1
     })?;
@@ -224,7 +375,7 @@ fn top_effective_rows(tool_eff: &ToolTokenMap) -> Vec<(String, u64)> {
         .iter()
         .map(|(tool, (sum, count))| (tool.clone(), if *count == 0 { 0 } else { sum / count }))
         .collect();
-    rows.sort_by(|a, b| b.1.cmp(&a.1));
+    rows.sort_by_key(|b| std::cmp::Reverse(b.1));
     rows.truncate(5);
     rows
 }
@@ -248,7 +399,7 @@ fn prompt_drift_rows(
             drift_rows.push((tool, s_avg as i64 - f_avg as i64, f_avg, s_avg));
         }
     }
-    drift_rows.sort_by(|a, b| b.1.cmp(&a.1));
+    drift_rows.sort_by_key(|b| std::cmp::Reverse(b.1));
     drift_rows.truncate(5);
     drift_rows
 }
@@ -279,43 +430,47 @@ fn poor_cache_rows(tool_cache: &ToolTokenMap) -> Vec<(String, u64)> {
             }
         })
         .collect();
-    rows.sort_by(|a, b| a.1.cmp(&b.1));
+    rows.sort_by_key(|a| a.1);
     rows.truncate(5);
     rows
 }
 
-fn print_promptlint_recommendations(
+fn promptlint_recommendations(
     top_eff: &[(String, u64)],
     drift_rows: &[(String, i64, u64, u64)],
     poor_cache: &[(String, u64)],
-) {
-    println!("Recommendations:");
-    let mut rec_count = 0usize;
+) -> Vec<String> {
+    let mut recs: Vec<String> = Vec::new();
     if let Some((tool, avg)) = top_eff.first()
         && *avg > 3000
     {
-        println!(
-            "- {tool} prompts are heavy ({avg}); reduce embedded context and enforce schema-only outputs."
-        );
-        rec_count += 1;
+        recs.push(format!(
+            "{tool} prompts are heavy ({avg}); reduce embedded context and enforce schema-only outputs."
+        ));
     }
     if let Some((tool, delta, _, _)) = drift_rows.first()
         && *delta > 300
     {
-        println!(
-            "- {tool} shows token drift (+{delta}); stabilize prompt templates and prompt_preview content."
-        );
-        rec_count += 1;
+        recs.push(format!(
+            "{tool} shows token drift (+{delta}); stabilize prompt templates and prompt_preview content."
+        ));
     }
     if let Some((tool, pct)) = poor_cache.first()
         && *pct < 40
     {
-        println!(
-            "- {tool} cache hit is low ({pct}%); reduce prompt variability and reuse stable instruction blocks."
-        );
-        rec_count += 1;
+        recs.push(format!(
+            "{tool} cache hit is low ({pct}%); reduce prompt variability and reuse stable instruction blocks."
+        ));
     }
-    if rec_count == 0 {
+    recs
+}
+
+fn print_promptlint_recommendations(recs: &[String]) {
+    println!("Recommendations:");
+    if recs.is_empty() {
         println!("- No major prompt issues detected in this window.");
     }
+    for rec in recs {
+        println!("- {rec}");
+    }
 }
@@ -1,9 +1,20 @@
+use crate::config::app_config;
+use crate::config_layers::config_env;
+use crate::error::{ExitKind, note_failure};
 use crate::llm::{
     LlmRunError, run_codex_jsonl, run_codex_plain, run_http_plain, run_http_raw, run_ollama_plain,
     wrap_agent_text_as_jsonl,
 };
-use crate::runtime::{llm_backend, resolve_ollama_model_for_run};
+use crate::mock_responses::{MOCK_DEFAULT_RESPONSE, mock_response_for_prompt};
+use crate::offline::is_offline;
+use crate::runtime::{
+    llm_backend, llm_model, ollama_model_preference, resolve_ollama_model_for_run,
+};
+use crate::secrets::resolve_secret_ref;
+use crate::stream::{StreamMode, StreamPrinter};
+use crate::verbosity::{Verbosity, diag};
 use std::env;
+use std::sync::Mutex;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProviderStatus {
@@ -63,10 +74,10 @@ pub fn selected_adapter_name() -> &'static str {
             return "http-curl";
         }
     }
-    if normalized_backend_name(&llm_backend()) == "ollama" {
-        "ollama-cli"
-    } else {
-        "codex-cli"
+    match llm_backend().as_str() {
+        "mock" => "mock",
+        "ollama" => "ollama-cli",
+        _ => "codex-cli",
     }
 }
 
@@ -186,17 +197,56 @@ pub trait ProviderAdapter {
     fn run_plain(&self, prompt: &str) -> Result<String, LlmRunError>;
     fn run_jsonl(&self, prompt: &str) -> Result<String, LlmRunError>;
     fn capabilities(&self) -> ProviderCapabilities;
+
+    /// `run_plain`, printing output through `out` as it arrives. Adapters
+    /// without a streaming transport print the whole response at the end.
+    fn run_plain_streaming(
+        &self,
+        prompt: &str,
+        out: &mut StreamPrinter,
+    ) -> Result<String, LlmRunError> {
+        let text = self.run_plain(prompt)?;
+        out.text(text.as_bytes());
+        Ok(text)
+    }
+
+    /// `run_jsonl`, printing output through `out` as it arrives.
+    fn run_jsonl_streaming(
+        &self,
+        prompt: &str,
+        out: &mut StreamPrinter,
+    ) -> Result<String, LlmRunError> {
+        let jsonl = self.run_jsonl(prompt)?;
+        out.jsonl(jsonl.as_bytes());
+        Ok(jsonl)
+    }
 }
 
 pub struct CodexCliAdapter;
 
 impl ProviderAdapter for CodexCliAdapter {
     fn run_plain(&self, prompt: &str) -> Result<String, LlmRunError> {
-        run_codex_plain(prompt)
+        run_codex_plain(prompt, None)
     }
 
     fn run_jsonl(&self, prompt: &str) -> Result<String, LlmRunError> {
-        run_codex_jsonl(prompt)
+        run_codex_jsonl(prompt, None)
+    }
+
+    fn run_plain_streaming(
+        &self,
+        prompt: &str,
+        out: &mut StreamPrinter,
+    ) -> Result<String, LlmRunError> {
+        run_codex_plain(prompt, Some(&mut |b: &[u8]| out.text(b)))
+    }
+
+    fn run_jsonl_streaming(
+        &self,
+        prompt: &str,
+        out: &mut StreamPrinter,
+    ) -> Result<String, LlmRunError> {
+        run_codex_jsonl(prompt, Some(&mut |b: &[u8]| out.jsonl(b)))
     }
 
     fn capabilities(&self) -> ProviderCapabilities {
@@ -217,7 +267,7 @@ impl OllamaCliAdapter {
 
 impl ProviderAdapter for OllamaCliAdapter {
     fn run_plain(&self, prompt: &str) -> Result<String, LlmRunError> {
-        run_ollama_plain(prompt, &self.model)
+        run_ollama_plain(prompt, &self.model, None)
     }
 
     fn run_jsonl(&self, prompt: &str) -> Result<String, LlmRunError> {
@@ -225,21 +275,47 @@ impl ProviderAdapter for OllamaCliAdapter {
         ollama_plain_to_jsonl(&text)
     }
 
+    fn run_plain_streaming(
+        &self,
+        prompt: &str,
+        out: &mut StreamPrinter,
+    ) -> Result<String, LlmRunError> {
+        run_ollama_plain(prompt, &self.model, Some(&mut |b: &[u8]| out.text(b)))
+    }
+
+    /// Tokens stream as agent text; raw JSONL (`cxj`) is only known once the
+    /// response is wrapped, so it prints at the end.
+    fn run_jsonl_streaming(
+        &self,
+        prompt: &str,
+        out: &mut StreamPrinter,
+    ) -> Result<String, LlmRunError> {
+        if out.mode() != StreamMode::AgentText {
+            let jsonl = self.run_jsonl(prompt)?;
+            out.jsonl(jsonl.as_bytes());
+            return Ok(jsonl);
+        }
+        let text = self.run_plain_streaming(prompt, out)?;
+        ollama_plain_to_jsonl(&text)
+    }
+
     fn capabilities(&self) -> ProviderCapabilities {
         capabilities_for_adapter("ollama-cli")
     }
 }
 
+/// Offline backend (`CX_LLM_BACKEND=mock` or `CX_PROVIDER_ADAPTER=mock`).
+/// `CX_MOCK_PLAIN_RESPONSE` fixes the answer; otherwise schema prompts get a
+/// fixture or a synthesized instance of their schema.
 pub struct MockAdapter {
-    plain_response: String,
+    plain_response: Option<String>,
     jsonl_response: Option<String>,
     error_message: Option<String>,
 }
 
 impl MockAdapter {
     fn new_from_env() -> Self {
-        let plain_response = env::var("CX_MOCK_PLAIN_RESPONSE")
-            .unwrap_or_else(|_| "{\"commands\":[\"echo mock\"]}".to_string());
+        let plain_response = env::var("CX_MOCK_PLAIN_RESPONSE").ok();
         let jsonl_response = env::var("CX_MOCK_JSONL_RESPONSE")
             .ok()
             .map(|s| s.trim().to_string())
@@ -257,11 +333,15 @@ impl MockAdapter {
 }
 
 impl ProviderAdapter for MockAdapter {
-    fn run_plain(&self, _prompt: &str) -> Result<String, LlmRunError> {
+    fn run_plain(&self, prompt: &str) -> Result<String, LlmRunError> {
         if let Some(err) = &self.error_message {
             return Err(LlmRunError::message(err.clone()));
         }
-        Ok(self.plain_response.clone())
+        Ok(self
+            .plain_response
+            .clone()
+            .or_else(|| mock_response_for_prompt(prompt))
+            .unwrap_or_else(|| MOCK_DEFAULT_RESPONSE.to_string()))
     }
 
     fn run_jsonl(&self, prompt: &str) -> Result<String, LlmRunError> {
@@ -414,10 +494,13 @@ impl HttpCurlAdapter {
                     "http-curl adapter requires CX_HTTP_PROVIDER_URL to be set".to_string(),
                 )
             })?;
-        let token = env::var("CX_HTTP_PROVIDER_TOKEN")
-            .ok()
+        // `secret:<name>` keeps the token itself out of env/config files.
+        let token = config_env("CX_HTTP_PROVIDER_TOKEN")
             .map(|v| v.trim().to_string())
-            .filter(|v| !v.is_empty());
+            .filter(|v| !v.is_empty())
+            .map(|v| resolve_secret_ref(&v))
+            .transpose()
+            .map_err(|e| LlmRunError::message(format!("http-curl adapter token: {e}")))?;
         let format = Self::parse_format_from_env();
         Ok(Self { url, token, format })
     }
@@ -451,7 +534,178 @@ impl ProviderAdapter for HttpCurlAdapter {
     }
 }
 
+/// Which backend served the last [`FailoverAdapter`] call, read when the run row is logged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FailoverOutcome {
+    /// `None` when every backend in the chain failed.
+    pub served: Option<String>,
+    pub model: Option<String>,
+    /// `<backend>: <error>` for each backend tried before `served`.
+    pub failures: Vec<String>,
+}
+
+static FAILOVER_OUTCOME: Mutex<Option<FailoverOutcome>> = Mutex::new(None);
+
+fn set_failover_outcome(outcome: FailoverOutcome) {
+    if let Ok(mut slot) = FAILOVER_OUTCOME.lock() {
+        *slot = Some(outcome);
+    }
+}
+
+pub fn failover_outcome() -> Option<FailoverOutcome> {
+    FAILOVER_OUTCOME.lock().ok().and_then(|slot| slot.clone())
+}
+
+pub fn clear_failover_outcome() {
+    if let Ok(mut slot) = FAILOVER_OUTCOME.lock() {
+        *slot = None;
+    }
+}
+
+/// Backends to try in order: the configured backend, then the rest of
+/// `preferences.backend_chain`. A single entry means no failover.
+pub fn failover_backends() -> Vec<String> {
+    let primary = normalized_backend_name(&llm_backend()).to_string();
+    let mut out = vec![primary.clone()];
+    out.extend(
+        app_config()
+            .backend_chain
+            .iter()
+            .filter(|b| **b != primary)
+            .cloned(),
+    );
+    out
+}
+
+/// Adapter and model for one chain entry. Only the configured backend may
+/// prompt for an Ollama model; fallbacks need it set already.
+fn adapter_for_backend(backend: &str) -> Result<(Box<dyn ProviderAdapter>, String), LlmRunError> {
+    if backend != "ollama" {
+        return Ok((Box::new(CodexCliAdapter), app_config().codex_model.clone()));
+    }
+    let model = if llm_backend() == "ollama" {
+        resolve_ollama_model_for_run().map_err(LlmRunError::message)?
+    } else {
+        ollama_model_preference()
+    };
+    if model.trim().is_empty() {
+        return Err(LlmRunError::message(
+            "ollama model is unset; set CX_OLLAMA_MODEL".to_string(),
+        ));
+    }
+    Ok((
+        Box::new(OllamaCliAdapter {
+            model: model.clone(),
+        }),
+        model,
+    ))
+}
+
+/// Tries each backend of the chain until one succeeds. Spawn failures,
+/// non-zero exits and timeouts fail over; Ctrl-C does not.
+pub struct FailoverAdapter {
+    backends: Vec<String>,
+}
+
+impl FailoverAdapter {
+    fn run_chain(
+        &self,
+        call: &mut dyn FnMut(&dyn ProviderAdapter) -> Result<String, LlmRunError>,
+    ) -> Result<String, LlmRunError> {
+        let mut failures: Vec<String> = Vec::new();
+        let mut last_err: Option<LlmRunError> = None;
+        for (idx, backend) in self.backends.iter().enumerate() {
+            let attempt = adapter_for_backend(backend)
+                .and_then(|(adapter, model)| call(adapter.as_ref()).map(|out| (out, model)));
+            match attempt {
+                Ok((out, model)) => {
+                    set_failover_outcome(FailoverOutcome {
+                        served: Some(backend.clone()),
+                        model: Some(model).filter(|m| !m.trim().is_empty()),
+                        failures,
+                    });
+                    return Ok(out);
+                }
+                Err(e) if e.interrupted => {
+                    set_failover_outcome(FailoverOutcome {
+                        failures,
+                        ..Default::default()
+                    });
+                    return Err(e);
+                }
+                Err(e) => {
+                    if let Some(next) = self.backends.get(idx + 1) {
+                        crate::cx_eprintln!(
+                            "cxrs: {backend} failed ({}); failing over to {next}",
+                            e.message
+                        );
+                    }
+                    failures.push(format!("{backend}: {}", e.message));
+                    last_err = Some(e);
+                }
+            }
+        }
+        let mut err =
+            last_err.unwrap_or_else(|| LlmRunError::message("no backend configured".to_string()));
+        err.message = format!("all backends failed: {}", failures.join("; "));
+        set_failover_outcome(FailoverOutcome {
+            failures,
+            ..Default::default()
+        });
+        Err(err)
+    }
+}
+
+impl ProviderAdapter for FailoverAdapter {
+    fn run_plain(&self, prompt: &str) -> Result<String, LlmRunError> {
+        self.run_chain(&mut |a| a.run_plain(prompt))
+    }
+
+    fn run_jsonl(&self, prompt: &str) -> Result<String, LlmRunError> {
+        self.run_chain(&mut |a| a.run_jsonl(prompt))
+    }
+
+    fn run_plain_streaming(
+        &self,
+        prompt: &str,
+        out: &mut StreamPrinter,
+    ) -> Result<String, LlmRunError> {
+        self.run_chain(&mut |a| a.run_plain_streaming(prompt, out))
+    }
+
+    fn run_jsonl_streaming(
+        &self,
+        prompt: &str,
+        out: &mut StreamPrinter,
+    ) -> Result<String, LlmRunError> {
+        self.run_chain(&mut |a| a.run_jsonl_streaming(prompt, out))
+    }
+
+    /// The primary backend's capabilities; fallbacks return the same shapes.
+    fn capabilities(&self) -> ProviderCapabilities {
+        capabilities_for_adapter(if self.backends[0] == "ollama" {
+            "ollama-cli"
+        } else {
+            "codex-cli"
+        })
+    }
+}
+
 pub fn resolve_provider_adapter() -> Result<Box<dyn ProviderAdapter>, LlmRunError> {
+    if is_offline() {
+        note_failure(ExitKind::Offline);
+        return Err(LlmRunError::offline());
+    }
+    diag(
+        Verbosity::Verbose,
+        "backend",
+        &[
+            ("adapter", selected_adapter_name().to_string()),
+            ("backend", llm_backend()),
+            ("model", llm_model()),
+            ("transport", selected_provider_transport().to_string()),
+        ],
+    );
     if let Some(v) = adapter_override() {
         if v == "mock" {
             return Ok(Box::new(MockAdapter::new_from_env()));
@@ -463,6 +717,13 @@ pub fn resolve_provider_adapter() -> Result<Box<dyn ProviderAdapter>, LlmRunErro
             return Ok(Box::new(HttpCurlAdapter::new_from_env()?));
         }
     }
+    if llm_backend() == "mock" {
+        return Ok(Box::new(MockAdapter::new_from_env()));
+    }
+    let backends = failover_backends();
+    if backends.len() > 1 {
+        return Ok(Box::new(FailoverAdapter { backends }));
+    }
     if normalized_backend_name(&llm_backend()) == "ollama" {
         return Ok(Box::new(OllamaCliAdapter::new()?));
     }
@@ -2,7 +2,7 @@ use chrono::Utc;
 use std::fs;
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::execmeta::utc_now_iso;
 use crate::paths::resolve_quarantine_dir;
@@ -53,14 +53,19 @@ pub fn quarantine_store_with_attempts(
         raw_response: raw.to_string(),
         raw_sha256: sha256_hex(raw),
         attempts,
+        resolved_ts: None,
+        resolved_note: None,
     };
-    let file = qdir.join(format!("{id}.json"));
-    let serialized = serde_json::to_string_pretty(&rec)
-        .map_err(|e| format!("failed to serialize quarantine record: {e}"))?;
-    fs::write(&file, serialized).map_err(|e| format!("failed to write {}: {e}", file.display()))?;
+    write_quarantine_record(&qdir.join(format!("{id}.json")), &rec)?;
     Ok(id)
 }
 
+pub(crate) fn write_quarantine_record(file: &Path, rec: &QuarantineRecord) -> Result<(), String> {
+    let serialized = serde_json::to_string_pretty(rec)
+        .map_err(|e| format!("failed to serialize quarantine record: {e}"))?;
+    fs::write(file, serialized).map_err(|e| format!("failed to write {}: {e}", file.display()))
+}
+
 #[allow(dead_code)]
 pub fn quarantine_store(
     tool: &str,
@@ -72,7 +77,7 @@ pub fn quarantine_store(
     quarantine_store_with_attempts(tool, reason, raw, schema, prompt, Vec::new())
 }
 
-fn quarantine_file_by_id(id: &str) -> Option<PathBuf> {
+pub(crate) fn quarantine_file_by_id(id: &str) -> Option<PathBuf> {
     let qdir = resolve_quarantine_dir()?;
     let path = qdir.join(format!("{id}.json"));
     if path.exists() { Some(path) } else { None }
@@ -88,8 +93,9 @@ pub fn read_quarantine_record(id: &str) -> Result<QuarantineRecord, String> {
     serde_json::from_str(&s).map_err(|e| format!("invalid quarantine JSON {}: {e}", path.display()))
 }
 
-fn read_quarantine_rows(qdir: &std::path::Path, n: usize) -> Vec<QuarantineRecord> {
-    let mut rows: Vec<QuarantineRecord> = Vec::new();
+/// Every parseable quarantine record in `qdir` with its file path (unsorted).
+pub(crate) fn scan_quarantine_dir(qdir: &Path) -> Vec<(PathBuf, QuarantineRecord)> {
+    let mut rows = Vec::new();
     let Ok(rd) = fs::read_dir(qdir) else {
         return rows;
     };
@@ -105,9 +111,17 @@ fn read_quarantine_rows(qdir: &std::path::Path, n: usize) -> Vec<QuarantineRecor
             continue;
         }
         if let Ok(rec) = serde_json::from_str::<QuarantineRecord>(&s) {
-            rows.push(rec);
+            rows.push((path, rec));
         }
     }
+    rows
+}
+
+fn read_quarantine_rows(qdir: &Path, n: usize) -> Vec<QuarantineRecord> {
+    let mut rows: Vec<QuarantineRecord> = scan_quarantine_dir(qdir)
+        .into_iter()
+        .map(|(_, rec)| rec)
+        .collect();
     rows.sort_by(|a, b| b.ts.cmp(&a.ts));
     if rows.len() > n {
         rows.truncate(n);
@@ -131,7 +145,15 @@ pub fn cmd_quarantine_list(n: usize) -> i32 {
     println!("== cxrs quarantine list ==");
     println!("entries: {}", rows.len());
     for rec in rows {
-        println!("- {} | {} | {} | {}", rec.id, rec.ts, rec.tool, rec.reason);
+        let status = if rec.resolved_ts.is_some() {
+            " | resolved"
+        } else {
+            ""
+        };
+        println!(
+            "- {} | {} | {} | {}{status}",
+            rec.id, rec.ts, rec.tool, rec.reason
+        );
     }
     println!("quarantine_dir: {}", qdir.display());
     0
@@ -0,0 +1,200 @@
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::logs::load_values;
+use crate::types::QuarantineRecord;
+use crate::util::sha256_hex;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditIssue {
+    /// Failure type, used for per-kind counts in the `ci validate` report.
+    pub kind: &'static str,
+    pub message: String,
+    /// `false` for findings that are only warnings outside `--strict`.
+    pub error: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct QuarantineAudit {
+    pub records: usize,
+    pub referenced_ids: usize,
+    pub issues: Vec<AuditIssue>,
+}
+
+impl QuarantineAudit {
+    fn push(&mut self, kind: &'static str, error: bool, message: String) {
+        self.issues.push(AuditIssue {
+            kind,
+            message,
+            error,
+        });
+    }
+}
+
+/// Contract problems in one parsed record; `stem` is the file name without `.json`.
+fn record_issues(stem: &str, rec: &QuarantineRecord) -> Vec<(&'static str, String)> {
+    let mut out = Vec::new();
+    let required = [
+        ("id", &rec.id),
+        ("ts", &rec.ts),
+        ("tool", &rec.tool),
+        ("reason", &rec.reason),
+        ("schema", &rec.schema),
+        ("prompt", &rec.prompt),
+        ("raw_sha256", &rec.raw_sha256),
+    ];
+    for (field, value) in required {
+        if value.trim().is_empty() {
+            out.push(("quarantine_missing_field", format!("missing {field}")));
+        }
+    }
+    if !rec.id.is_empty() && rec.id != stem {
+        out.push((
+            "quarantine_id_mismatch",
+            format!("id '{}' does not match file name", rec.id),
+        ));
+    }
+    if !rec.raw_sha256.is_empty() && rec.raw_sha256 != sha256_hex(&rec.raw_response) {
+        out.push((
+            "quarantine_hash_mismatch",
+            "raw_sha256 does not match raw_response".to_string(),
+        ));
+    }
+    if !rec.schema.trim().is_empty() && serde_json::from_str::<Value>(&rec.schema).is_err() {
+        out.push((
+            "quarantine_schema_invalid",
+            "schema is not valid JSON".to_string(),
+        ));
+    }
+    out
+}
+
+/// Non-empty `quarantine_id` values referenced from a JSONL log.
+fn referenced_ids(log: &Path, into: &mut BTreeSet<String>) {
+    if !log.is_file() {
+        return;
+    }
+    for row in load_values(log, 0).unwrap_or_default() {
+        if let Some(id) = row
+            .get("quarantine_id")
+            .and_then(Value::as_str)
+            .filter(|s| !s.trim().is_empty())
+        {
+            into.insert(id.to_string());
+        }
+    }
+}
+
+/// Validates every `<qdir>/*.json` record against the `QuarantineRecord`
+/// contract and cross-checks ids referenced from `logs`. References to missing
+/// records are errors only when `strict` (`quarantine rm/purge` leave them
+/// behind); records no log references are always warnings.
+pub fn audit_quarantine(qdir: &Path, logs: &[&Path], strict: bool) -> QuarantineAudit {
+    let mut audit = QuarantineAudit::default();
+    let mut on_disk: BTreeSet<String> = BTreeSet::new();
+    let mut paths: Vec<_> = fs::read_dir(qdir)
+        .map(|rd| {
+            rd.flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|v| v.to_str()) == Some("json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    for path in paths {
+        let Some(stem) = path
+            .file_stem()
+            .and_then(|v| v.to_str())
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        audit.records += 1;
+        on_disk.insert(stem.clone());
+        let label = path.display();
+        let value = match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<Value>(&s).map_err(|e| e.to_string()))
+        {
+            Ok(v) => v,
+            Err(e) => {
+                audit.push("quarantine_invalid_json", true, format!("{label}: {e}"));
+                continue;
+            }
+        };
+        let rec: QuarantineRecord = match serde_json::from_value(value) {
+            Ok(v) => v,
+            Err(e) => {
+                audit.push("quarantine_record_shape", true, format!("{label}: {e}"));
+                continue;
+            }
+        };
+        for (kind, msg) in record_issues(&stem, &rec) {
+            audit.push(kind, true, format!("{label}: {msg}"));
+        }
+    }
+
+    let mut refs = BTreeSet::new();
+    for log in logs {
+        referenced_ids(log, &mut refs);
+    }
+    audit.referenced_ids = refs.len();
+    for id in refs.difference(&on_disk) {
+        audit.push(
+            "quarantine_orphan_ref",
+            strict,
+            format!("quarantine_id {id} is referenced in logs but missing on disk"),
+        );
+    }
+    for id in on_disk.difference(&refs) {
+        audit.push(
+            "quarantine_unreferenced",
+            false,
+            format!("quarantine record {id} is not referenced by any log row"),
+        );
+    }
+    audit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_issues_flag_contract_breaks() {
+        let rec = QuarantineRecord {
+            id: "q1".to_string(),
+            ts: "2026-01-01T00:00:00Z".to_string(),
+            tool: "cxrs_next".to_string(),
+            reason: "invalid_json".to_string(),
+            schema: "{}".to_string(),
+            prompt: "p".to_string(),
+            raw_response: "raw".to_string(),
+            raw_sha256: sha256_hex("raw"),
+            ..Default::default()
+        };
+        assert!(record_issues("q1", &rec).is_empty());
+
+        let broken = QuarantineRecord {
+            schema: "not json".to_string(),
+            raw_sha256: "deadbeef".to_string(),
+            prompt: String::new(),
+            ..rec
+        };
+        let kinds: Vec<&str> = record_issues("q2", &broken)
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "quarantine_missing_field",
+                "quarantine_id_mismatch",
+                "quarantine_hash_mismatch",
+                "quarantine_schema_invalid",
+            ]
+        );
+    }
+}
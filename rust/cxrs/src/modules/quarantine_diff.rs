@@ -0,0 +1,498 @@
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use jsonschema::paths::{JSONPointer, PathChunk};
+use jsonschema::primitive_type::PrimitiveType;
+use jsonschema::{JSONSchema, ValidationError};
+use serde_json::{Map, Value, json};
+
+use crate::contract_versions::QUARANTINE_DIFF_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, print_runtime_error, print_usage_error};
+use crate::quarantine::read_quarantine_record;
+use crate::schema::{load_schema_for_tool, repair_json_text};
+use crate::types::QuarantineRecord;
+
+const USAGE: &str = "quarantine diff <id> [--json]";
+
+/// One failed constraint, straight from the jsonschema error.
+struct Violation {
+    pointer: String,
+    schema_path: String,
+    keyword: String,
+    message: String,
+}
+
+/// RFC 6902 operation proposed to make the response pass.
+struct PatchOp {
+    op: &'static str,
+    path: String,
+    value: Option<Value>,
+}
+
+impl PatchOp {
+    fn to_json(&self) -> Value {
+        let mut v = json!({"op": self.op, "path": self.path});
+        if let Some(value) = &self.value {
+            v["value"] = value.clone();
+        }
+        v
+    }
+}
+
+#[derive(Default)]
+struct SchemaDiff {
+    repair_steps: Vec<&'static str>,
+    parse_error: Option<String>,
+    violations: Vec<Violation>,
+    unexpected_keys: Vec<String>,
+    missing_keys: Vec<String>,
+    patch: Vec<PatchOp>,
+    /// Violations left once `patch` is applied; `None` without a patch.
+    remaining: Option<Vec<String>>,
+}
+
+fn escape_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn child_pointer(parent: &JSONPointer, key: &str) -> String {
+    format!("{parent}/{}", escape_token(key))
+}
+
+/// Schema node a keyword error came from: `schema_path` minus its keyword.
+fn schema_node<'a>(schema: &'a Value, path: &JSONPointer) -> Option<&'a Value> {
+    let chunks: Vec<&PathChunk> = path.iter().collect();
+    let mut node = schema;
+    for chunk in &chunks[..chunks.len().saturating_sub(1)] {
+        node = match chunk {
+            PathChunk::Property(p) => node.get(p.as_ref())?,
+            PathChunk::Keyword(k) => node.get(*k)?,
+            PathChunk::Index(i) => node.get(*i)?,
+        };
+    }
+    Some(node)
+}
+
+fn type_names(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Smallest value `schema` accepts: its default, const or first enum option,
+/// else an empty value of its type (objects get their required keys).
+fn placeholder(schema: &Value) -> Value {
+    if let Some(v) = schema.get("default").or_else(|| schema.get("const")) {
+        return v.clone();
+    }
+    if let Some(first) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|a| a.first())
+    {
+        return first.clone();
+    }
+    let ty = type_names(schema)
+        .into_iter()
+        .find(|t| *t != "null")
+        .unwrap_or("null");
+    match ty {
+        "string" => json!(""),
+        "array" => json!([]),
+        "boolean" => json!(false),
+        "integer" | "number" => schema.get("minimum").cloned().unwrap_or(json!(0)),
+        "object" => {
+            let mut obj = Map::new();
+            let props = schema.get("properties");
+            for name in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                let sub = props.and_then(|p| p.get(name)).unwrap_or(&Value::Null);
+                obj.insert(name.to_string(), placeholder(sub));
+            }
+            Value::Object(obj)
+        }
+        _ => Value::Null,
+    }
+}
+
+fn expected_types(kind: &TypeKind) -> Vec<PrimitiveType> {
+    match kind {
+        TypeKind::Single(t) => vec![*t],
+        TypeKind::Multiple(set) => set.into_iter().collect(),
+    }
+}
+
+/// `value` converted to the first expected type it converts to losslessly
+/// (`"3"` -> `3`, `5` -> `"5"`, `x` -> `[x]`), else the type's placeholder.
+fn coerce(value: &Value, kind: &TypeKind, node: Option<&Value>) -> Value {
+    for ty in expected_types(kind) {
+        let converted = match (ty, value) {
+            (PrimitiveType::String, Value::Number(_) | Value::Bool(_)) => {
+                Some(json!(value.to_string()))
+            }
+            (PrimitiveType::Array, v) => Some(json!([v])),
+            (PrimitiveType::Integer, Value::String(s)) => {
+                s.trim().parse::<i64>().ok().map(Value::from)
+            }
+            (PrimitiveType::Number, Value::String(s)) => {
+                s.trim().parse::<f64>().ok().map(Value::from)
+            }
+            (PrimitiveType::Boolean, Value::String(s)) => {
+                s.trim().parse::<bool>().ok().map(Value::from)
+            }
+            _ => None,
+        };
+        if let Some(v) = converted {
+            return v;
+        }
+    }
+    node.map(placeholder).unwrap_or(Value::Null)
+}
+
+/// Closest enum option: a case-insensitive match, else the first.
+fn nearest_option(value: &Value, options: &Value) -> Option<Value> {
+    let options = options.as_array()?;
+    let text = value.as_str().map(str::to_lowercase);
+    options
+        .iter()
+        .find(|o| text.is_some() && o.as_str().map(str::to_lowercase) == text)
+        .or_else(|| options.first())
+        .cloned()
+}
+
+/// The edit this error asks for, if it has a mechanical one.
+fn patch_for(err: &ValidationError<'_>, schema: &Value, diff: &mut SchemaDiff) {
+    let node = schema_node(schema, &err.schema_path);
+    let at = err.instance_path.to_string();
+    match &err.kind {
+        ValidationErrorKind::AdditionalProperties { unexpected } => {
+            for key in unexpected {
+                let path = child_pointer(&err.instance_path, key);
+                diff.unexpected_keys.push(path.clone());
+                diff.patch.push(PatchOp {
+                    op: "remove",
+                    path,
+                    value: None,
+                });
+            }
+        }
+        ValidationErrorKind::Required { property } => {
+            let Some(name) = property.as_str() else {
+                return;
+            };
+            let path = child_pointer(&err.instance_path, name);
+            let sub = node
+                .and_then(|n| n.get("properties"))
+                .and_then(|p| p.get(name))
+                .unwrap_or(&Value::Null);
+            diff.missing_keys.push(path.clone());
+            diff.patch.push(PatchOp {
+                op: "add",
+                path,
+                value: Some(placeholder(sub)),
+            });
+        }
+        ValidationErrorKind::Type { kind } => diff.patch.push(PatchOp {
+            op: "replace",
+            path: at,
+            value: Some(coerce(&err.instance, kind, node)),
+        }),
+        ValidationErrorKind::Enum { options } => {
+            if let Some(v) = nearest_option(&err.instance, options) {
+                diff.patch.push(PatchOp {
+                    op: "replace",
+                    path: at,
+                    value: Some(v),
+                });
+            }
+        }
+        ValidationErrorKind::Constant { expected_value } => diff.patch.push(PatchOp {
+            op: "replace",
+            path: at,
+            value: Some(expected_value.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn unescape_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+fn apply_op(doc: &mut Value, op: &PatchOp) -> Result<(), String> {
+    let (parent, last) = op.path.rsplit_once('/').unwrap_or(("", ""));
+    if op.op == "replace" || op.path.is_empty() {
+        let target = doc
+            .pointer_mut(&op.path)
+            .ok_or_else(|| format!("no value at {}", op.path))?;
+        *target = op.value.clone().unwrap_or(Value::Null);
+        return Ok(());
+    }
+    let obj = doc
+        .pointer_mut(parent)
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| format!("no object at {parent}"))?;
+    let key = unescape_token(last);
+    match op.op {
+        "remove" => {
+            obj.remove(&key);
+        }
+        _ => {
+            obj.insert(key, op.value.clone().unwrap_or(Value::Null));
+        }
+    }
+    Ok(())
+}
+
+fn violation_messages(compiled: &JSONSchema, instance: &Value) -> Vec<String> {
+    match compiled.validate(instance) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|e| format!("{}: {e}", display_pointer(&e.instance_path.to_string())))
+            .collect(),
+    }
+}
+
+fn display_pointer(p: &str) -> &str {
+    if p.is_empty() { "(root)" } else { p }
+}
+
+/// Validate the quarantined response and derive the minimal patch.
+fn diff_record(rec: &QuarantineRecord) -> Result<SchemaDiff, String> {
+    let schema: Value = match serde_json::from_str(&rec.schema) {
+        Ok(v) => v,
+        Err(_) => load_schema_for_tool(&rec.tool)
+            .map(|s| s.value)
+            .map_err(|e| format!("record has no usable schema: {e}"))?,
+    };
+    let compiled =
+        JSONSchema::compile(&schema).map_err(|e| format!("failed to compile schema: {e}"))?;
+    let mut diff = SchemaDiff::default();
+    let instance: Value = match serde_json::from_str(&rec.raw_response) {
+        Ok(v) => v,
+        Err(e) => match repair_json_text(&rec.raw_response)
+            .and_then(|(text, steps)| serde_json::from_str(&text).ok().map(|v| (v, steps)))
+        {
+            Some((v, steps)) => {
+                diff.repair_steps = steps;
+                v
+            }
+            None => {
+                diff.parse_error = Some(format!("invalid JSON: {e}"));
+                return Ok(diff);
+            }
+        },
+    };
+    if let Err(errors) = compiled.validate(&instance) {
+        for err in errors {
+            patch_for(&err, &schema, &mut diff);
+            let keyword = match err.schema_path.last() {
+                Some(PathChunk::Keyword(k)) => (*k).to_string(),
+                Some(PathChunk::Property(p)) => p.to_string(),
+                Some(PathChunk::Index(i)) => i.to_string(),
+                None => String::new(),
+            };
+            diff.violations.push(Violation {
+                pointer: err.instance_path.to_string(),
+                schema_path: err.schema_path.to_string(),
+                keyword,
+                message: err.to_string(),
+            });
+        }
+    }
+    if !diff.patch.is_empty() {
+        let mut patched = instance.clone();
+        let remaining = match diff
+            .patch
+            .iter()
+            .try_for_each(|op| apply_op(&mut patched, op))
+        {
+            Ok(()) => violation_messages(&compiled, &patched),
+            Err(e) => vec![e],
+        };
+        diff.remaining = Some(remaining);
+    }
+    Ok(diff)
+}
+
+fn diff_json(rec: &QuarantineRecord, diff: &SchemaDiff) -> Value {
+    let violations: Vec<Value> = diff
+        .violations
+        .iter()
+        .map(|v| {
+            json!({
+                "pointer": v.pointer,
+                "schema_path": v.schema_path,
+                "keyword": v.keyword,
+                "message": v.message,
+            })
+        })
+        .collect();
+    json!({
+        "contract_version": QUARANTINE_DIFF_JSON_CONTRACT_VERSION,
+        "id": rec.id,
+        "tool": rec.tool,
+        "reason": rec.reason,
+        "json_valid": diff.parse_error.is_none(),
+        "parse_error": diff.parse_error,
+        "repair_steps": diff.repair_steps,
+        "violations": violations,
+        "unexpected_keys": diff.unexpected_keys,
+        "missing_keys": diff.missing_keys,
+        "patch": diff.patch.iter().map(PatchOp::to_json).collect::<Vec<_>>(),
+        "passes_after_patch": diff.remaining.as_ref().map(Vec::is_empty),
+        "remaining": diff.remaining,
+    })
+}
+
+fn print_diff(rec: &QuarantineRecord, diff: &SchemaDiff) {
+    println!("== cxrs quarantine diff {} ==", rec.id);
+    println!("tool: {} | reason: {}", rec.tool, rec.reason);
+    if !diff.repair_steps.is_empty() {
+        println!(
+            "repaired before validation: {}",
+            diff.repair_steps.join(", ")
+        );
+    }
+    if let Some(e) = &diff.parse_error {
+        println!("response is not JSON: {e}");
+        println!("suggestion: the response must be a single JSON object matching the schema");
+        return;
+    }
+    if diff.violations.is_empty() {
+        println!("no violations: the response now passes its schema");
+        return;
+    }
+    println!("violations: {}", diff.violations.len());
+    for v in &diff.violations {
+        println!(
+            "- {} [{}] {}",
+            display_pointer(&v.pointer),
+            v.keyword,
+            v.message
+        );
+        println!("    schema: {}", display_pointer(&v.schema_path));
+    }
+    if !diff.unexpected_keys.is_empty() || !diff.missing_keys.is_empty() {
+        println!("keys:");
+        for k in &diff.unexpected_keys {
+            println!("  + {k}  (unexpected)");
+        }
+        for k in &diff.missing_keys {
+            println!("  - {k}  (missing)");
+        }
+    }
+    if diff.patch.is_empty() {
+        println!("no mechanical edit; fix the violations above by hand");
+        return;
+    }
+    println!("minimal edit (JSON Patch):");
+    for op in &diff.patch {
+        match &op.value {
+            Some(v) => println!("  {} {} {v}", op.op, op.path),
+            None => println!("  {} {}", op.op, op.path),
+        }
+    }
+    match diff.remaining.as_deref() {
+        Some([]) => println!("after edit: passes"),
+        Some(left) => println!("after edit: still fails: {}", left.join(" | ")),
+        None => {}
+    }
+}
+
+/// `quarantine diff <id> [--json]`: every schema violation of a quarantined
+/// response with its JSON Pointer, the unexpected/missing keys, and a JSON
+/// Patch that would make it pass.
+pub fn cmd_quarantine_diff(args: &[String]) -> i32 {
+    let mut json_out = false;
+    let mut id: Option<&str> = None;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json_out = true,
+            a if a.starts_with('-') || id.is_some() => {
+                return print_usage_error("quarantine", USAGE);
+            }
+            a => id = Some(a),
+        }
+    }
+    let Some(id) = id else {
+        return print_usage_error("quarantine", USAGE);
+    };
+    let rec = match read_quarantine_record(id) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("quarantine diff", &e),
+    };
+    let diff = match diff_record(&rec) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("quarantine diff", &e),
+    };
+    if json_out {
+        let doc = diff_json(&rec, &diff);
+        match serde_json::to_string_pretty(&doc) {
+            Ok(s) => println!("{s}"),
+            Err(e) => {
+                return print_runtime_error("quarantine diff", &format!("render failure: {e}"));
+            }
+        }
+    } else {
+        print_diff(&rec, &diff);
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(schema: Value, raw: &str) -> QuarantineRecord {
+        QuarantineRecord {
+            id: "q1".to_string(),
+            tool: "cxrs_next".to_string(),
+            schema: schema.to_string(),
+            raw_response: raw.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn violations_yield_a_patch_that_passes() {
+        let schema = json!({
+            "type": "object",
+            "required": ["commands", "mode"],
+            "additionalProperties": false,
+            "properties": {
+                "commands": {"type": "array", "items": {"type": "string"}},
+                "mode": {"enum": ["fast", "safe"]},
+                "count": {"type": "integer"}
+            }
+        });
+        let rec = record(
+            schema,
+            "```json\n{\"commands\": [\"ls\", 5], \"extra\": 1, \"count\": \"3\"}\n```",
+        );
+        let diff = diff_record(&rec).expect("diff");
+        assert_eq!(diff.repair_steps, ["strip_code_fence"]);
+        assert_eq!(diff.unexpected_keys, ["/extra"]);
+        assert_eq!(diff.missing_keys, ["/mode"]);
+        let pointers: Vec<&str> = diff.violations.iter().map(|v| v.pointer.as_str()).collect();
+        assert!(pointers.contains(&"/commands/1"), "{pointers:?}");
+        assert!(pointers.contains(&"/count"), "{pointers:?}");
+        let ops: Vec<Value> = diff.patch.iter().map(PatchOp::to_json).collect();
+        assert!(ops.contains(&json!({"op": "replace", "path": "/commands/1", "value": "5"})));
+        assert!(ops.contains(&json!({"op": "replace", "path": "/count", "value": 3})));
+        assert!(ops.contains(&json!({"op": "add", "path": "/mode", "value": "fast"})));
+        assert_eq!(diff.remaining.as_deref(), Some(&[][..]));
+    }
+
+    #[test]
+    fn non_json_responses_report_the_parse_error() {
+        let diff = diff_record(&record(json!({"type": "object"}), "sorry, I can't")).expect("diff");
+        assert!(diff.parse_error.is_some());
+        assert!(diff.violations.is_empty() && diff.patch.is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::analytics::parse_ts_epoch;
+use crate::contract_versions::QUARANTINE_STATS_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, EXIT_RUNTIME, format_error, print_runtime_error, print_usage_error};
+use crate::execmeta::utc_now_iso;
+use crate::paths::resolve_quarantine_dir;
+use crate::quarantine::{
+    quarantine_file_by_id, read_quarantine_record, scan_quarantine_dir, write_quarantine_record,
+};
+use crate::types::QuarantineRecord;
+
+/// Parse `30d`, `12h`, `45m` or `90s` into seconds.
+pub fn parse_age_secs(raw: &str) -> Option<i64> {
+    let raw = raw.trim();
+    let split = raw.len().checked_sub(1)?;
+    let (num, unit) = raw.split_at(split);
+    let n = num.parse::<i64>().ok().filter(|v| *v > 0)?;
+    let mult = match unit {
+        "d" => 86_400,
+        "h" => 3_600,
+        "m" => 60,
+        "s" => 1,
+        _ => return None,
+    };
+    n.checked_mul(mult)
+}
+
+/// Mark a quarantine entry resolved; resolving twice keeps the first timestamp.
+pub fn mark_quarantine_resolved(id: &str, note: &str) -> Result<QuarantineRecord, String> {
+    let path = quarantine_file_by_id(id).ok_or_else(|| format!("quarantine id not found: {id}"))?;
+    let mut rec = read_quarantine_record(id)?;
+    if rec.resolved_ts.is_none() {
+        rec.resolved_ts = Some(utc_now_iso());
+    }
+    rec.resolved_note = Some(note.to_string());
+    write_quarantine_record(&path, &rec)?;
+    Ok(rec)
+}
+
+pub fn cmd_quarantine_resolve(args: &[String]) -> i32 {
+    let Some(id) = args.first() else {
+        return print_usage_error("quarantine", "quarantine resolve <id> [note...]");
+    };
+    let note = if args.len() > 1 {
+        args[1..].join(" ")
+    } else {
+        "manual".to_string()
+    };
+    match mark_quarantine_resolved(id, &note) {
+        Ok(rec) => {
+            println!(
+                "resolved: {} ({})",
+                rec.id,
+                rec.resolved_ts.unwrap_or_default()
+            );
+            EXIT_OK
+        }
+        Err(e) => print_runtime_error("quarantine", &e),
+    }
+}
+
+pub fn cmd_quarantine_rm(id: &str) -> i32 {
+    let Some(path) = quarantine_file_by_id(id) else {
+        return print_runtime_error("quarantine", &format!("quarantine id not found: {id}"));
+    };
+    match fs::remove_file(&path) {
+        Ok(()) => {
+            println!("removed: {id}");
+            EXIT_OK
+        }
+        Err(e) => print_runtime_error(
+            "quarantine",
+            &format!("failed to remove {}: {e}", path.display()),
+        ),
+    }
+}
+
+struct PurgeArgs {
+    older_than_secs: Option<i64>,
+    resolved_only: bool,
+    dry_run: bool,
+}
+
+fn parse_purge_args(args: &[String]) -> Result<PurgeArgs, String> {
+    let mut out = PurgeArgs {
+        older_than_secs: None,
+        resolved_only: false,
+        dry_run: false,
+    };
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--older-than" => {
+                let raw = args.get(i + 1).ok_or("--older-than requires a value")?;
+                out.older_than_secs = Some(parse_age_secs(raw).ok_or_else(|| {
+                    format!("invalid --older-than '{raw}' (expected e.g. 30d, 12h, 45m)")
+                })?);
+                i += 1;
+            }
+            "--resolved" => out.resolved_only = true,
+            "--dry-run" => out.dry_run = true,
+            other => return Err(format!("unknown flag '{other}'")),
+        }
+        i += 1;
+    }
+    if out.older_than_secs.is_none() && !out.resolved_only {
+        return Err("purge requires --older-than <age> and/or --resolved".to_string());
+    }
+    Ok(out)
+}
+
+fn purge_matches(rec: &QuarantineRecord, args: &PurgeArgs, now: i64) -> bool {
+    if args.resolved_only && rec.resolved_ts.is_none() {
+        return false;
+    }
+    match args.older_than_secs {
+        // Entries with an unparseable timestamp are never aged out.
+        Some(age) => parse_ts_epoch(&rec.ts).is_some_and(|ts| now - ts >= age),
+        None => true,
+    }
+}
+
+pub fn cmd_quarantine_purge(args: &[String]) -> i32 {
+    let parsed = match parse_purge_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("quarantine", &e));
+            return print_usage_error(
+                "quarantine",
+                "quarantine purge [--older-than <Nd|Nh|Nm|Ns>] [--resolved] [--dry-run]",
+            );
+        }
+    };
+    let Some(qdir) = resolve_quarantine_dir() else {
+        return print_runtime_error("quarantine", "unable to resolve quarantine directory");
+    };
+    let now = chrono::Utc::now().timestamp();
+    let mut rows = scan_quarantine_dir(&qdir);
+    rows.sort_by(|a, b| a.1.ts.cmp(&b.1.ts));
+    let (mut purged, mut failed) = (0usize, 0usize);
+    for (path, rec) in rows.iter().filter(|(_, r)| purge_matches(r, &parsed, now)) {
+        if parsed.dry_run {
+            println!("would purge: {} | {} | {}", rec.id, rec.ts, rec.tool);
+            purged += 1;
+            continue;
+        }
+        match fs::remove_file(path) {
+            Ok(()) => purged += 1,
+            Err(e) => {
+                failed += 1;
+                crate::cx_eprintln!(
+                    "{}",
+                    format_error(
+                        "quarantine",
+                        &format!("failed to remove {}: {e}", path.display())
+                    )
+                );
+            }
+        }
+    }
+    let label = if parsed.dry_run {
+        "would_purge"
+    } else {
+        "purged"
+    };
+    println!("{label}: {purged}");
+    println!(
+        "remaining: {}",
+        rows.len() - if parsed.dry_run { 0 } else { purged }
+    );
+    println!("quarantine_dir: {}", qdir.display());
+    if failed > 0 { EXIT_RUNTIME } else { EXIT_OK }
+}
+
+#[derive(Default)]
+struct QuarantineStats {
+    total: usize,
+    resolved: usize,
+    by_tool: BTreeMap<String, usize>,
+    by_reason: BTreeMap<String, usize>,
+    by_day: BTreeMap<String, usize>,
+}
+
+fn collect_stats(rows: &[(std::path::PathBuf, QuarantineRecord)]) -> QuarantineStats {
+    let mut stats = QuarantineStats::default();
+    for (_, rec) in rows {
+        stats.total += 1;
+        if rec.resolved_ts.is_some() {
+            stats.resolved += 1;
+        }
+        *stats.by_tool.entry(rec.tool.clone()).or_default() += 1;
+        *stats.by_reason.entry(rec.reason.clone()).or_default() += 1;
+        let day = rec.ts.get(..10).unwrap_or("unknown").to_string();
+        *stats.by_day.entry(day).or_default() += 1;
+    }
+    stats
+}
+
+/// Sorted by count descending, then name, so the noisiest schemas come first.
+fn ranked(map: &BTreeMap<String, usize>) -> Vec<(&String, &usize)> {
+    let mut v: Vec<_> = map.iter().collect();
+    v.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    v
+}
+
+pub fn cmd_quarantine_stats(json_out: bool) -> i32 {
+    let Some(qdir) = resolve_quarantine_dir() else {
+        return print_runtime_error("quarantine", "unable to resolve quarantine directory");
+    };
+    let stats = collect_stats(&scan_quarantine_dir(&qdir));
+    if json_out {
+        let out = json!({
+            "contract_version": QUARANTINE_STATS_JSON_CONTRACT_VERSION,
+            "total": stats.total,
+            "open": stats.total - stats.resolved,
+            "resolved": stats.resolved,
+            "by_tool": stats.by_tool,
+            "by_reason": stats.by_reason,
+            "by_day": stats.by_day,
+            "quarantine_dir": qdir.display().to_string(),
+        });
+        return match serde_json::to_string_pretty(&out) {
+            Ok(s) => {
+                println!("{s}");
+                EXIT_OK
+            }
+            Err(e) => {
+                crate::cx_eprintln!("cxrs quarantine stats: failed to render JSON: {e}");
+                EXIT_RUNTIME
+            }
+        };
+    }
+    println!("== cxrs quarantine stats ==");
+    println!("total: {}", stats.total);
+    println!("open: {}", stats.total - stats.resolved);
+    println!("resolved: {}", stats.resolved);
+    println!("by_tool:");
+    for (tool, n) in ranked(&stats.by_tool) {
+        println!("- {tool}: {n}");
+    }
+    println!("by_reason:");
+    for (reason, n) in ranked(&stats.by_reason) {
+        println!("- {reason}: {n}");
+    }
+    println!("by_day:");
+    for (day, n) in &stats.by_day {
+        println!("- {day}: {n}");
+    }
+    println!("quarantine_dir: {}", qdir.display());
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn age_suffixes_convert_to_seconds() {
+        assert_eq!(parse_age_secs("30d"), Some(30 * 86_400));
+        assert_eq!(parse_age_secs("12h"), Some(12 * 3_600));
+        assert_eq!(parse_age_secs("45m"), Some(45 * 60));
+        assert_eq!(parse_age_secs("90s"), Some(90));
+        assert_eq!(parse_age_secs("0d"), None);
+        assert_eq!(parse_age_secs("30"), None);
+        assert_eq!(parse_age_secs("d"), None);
+        assert_eq!(parse_age_secs(""), None);
+    }
+
+    #[test]
+    fn purge_requires_a_filter() {
+        assert!(parse_purge_args(&[]).is_err());
+        assert!(parse_purge_args(&["--dry-run".to_string()]).is_err());
+        let args = parse_purge_args(&["--older-than".to_string(), "7d".to_string()]).unwrap();
+        let now = parse_ts_epoch("2026-03-10T00:00:00Z").unwrap();
+        let rec = |ts: &str| QuarantineRecord {
+            ts: ts.to_string(),
+            ..Default::default()
+        };
+        assert!(purge_matches(&rec("2026-03-01T00:00:00Z"), &args, now));
+        assert!(!purge_matches(&rec("2026-03-05T00:00:00Z"), &args, now));
+        assert!(!purge_matches(&rec("garbage"), &args, now));
+    }
+}
@@ -0,0 +1,88 @@
+use serde_json::{Map, Value, json};
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use crate::capture::{Redaction, load_redactor};
+use crate::error::{EXIT_OK, print_runtime_error, print_usage_error};
+use crate::paths::repo_root;
+
+const USAGE: &str = "redact-test [--json] [FILE|-]";
+
+fn read_input(path: Option<&str>) -> Result<String, String> {
+    match path {
+        None | Some("-") => {
+            let mut buf = String::new();
+            io::stdin()
+                .read_to_string(&mut buf)
+                .map_err(|e| format!("failed to read stdin: {e}"))?;
+            Ok(buf)
+        }
+        Some(p) => fs::read_to_string(p).map_err(|e| format!("cannot read {p}: {e}")),
+    }
+}
+
+fn counts_summary(redaction: &Redaction) -> String {
+    let parts: Vec<String> = redaction
+        .counts
+        .iter()
+        .map(|(name, n)| format!("{name}={n}"))
+        .collect();
+    if parts.is_empty() {
+        return "redactions_applied=0".to_string();
+    }
+    format!(
+        "redactions_applied={} ({})",
+        redaction.total(),
+        parts.join(", ")
+    )
+}
+
+/// `redact-test [--json] [FILE|-]`: preview what capture redaction would mask.
+/// The masked text goes to stdout and the per-rule counts to stderr, so the
+/// preview can be diffed against the original.
+pub fn cmd_redact_test(args: &[String]) -> i32 {
+    let mut json_out = false;
+    let mut path: Option<&str> = None;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json_out = true,
+            "-" => path = Some("-"),
+            a if a.starts_with('-') || path.is_some() => {
+                return print_usage_error("redact-test", USAGE);
+            }
+            a => path = Some(a),
+        }
+    }
+    let root = repo_root().unwrap_or_else(|| PathBuf::from("."));
+    let redactor = match load_redactor(&root) {
+        Ok(r) => r,
+        Err(e) => return print_runtime_error("redact-test", &e),
+    };
+    let input = match read_input(path) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("redact-test", &e),
+    };
+    let redaction = redactor.redact(&input);
+    if json_out {
+        let rules: Map<String, Value> = redaction
+            .counts
+            .iter()
+            .map(|(name, n)| (name.clone(), json!(n)))
+            .collect();
+        let source = redactor.source.as_ref().map(|p| p.display().to_string());
+        println!(
+            "{}",
+            json!({
+                "redactions_applied": redaction.total(),
+                "rules": rules,
+                "config": source,
+                "text": redaction.text,
+            })
+        );
+        return EXIT_OK;
+    }
+    print!("{}", redaction.text);
+    crate::cx_eprintln!("cxrs redact-test: {}", counts_summary(&redaction));
+    EXIT_OK
+}
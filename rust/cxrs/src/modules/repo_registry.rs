@@ -0,0 +1,185 @@
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::analytics::parse_ts_epoch;
+use crate::logs::load_runs;
+use crate::paths::{codex_root, resolve_log_file, resolve_repo_registry_file};
+use crate::run_filter::RunFilter;
+use crate::state::write_json_atomic;
+use crate::types::RunEntry;
+use crate::verbosity::warn;
+
+/// Registered `(repo_root, log_file)` pairs, in registration order.
+fn read_registry(path: &Path) -> Vec<(String, String)> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    serde_json::from_str::<Value>(&text)
+        .ok()
+        .and_then(|v| v.get("repos").and_then(Value::as_array).cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|r| {
+            let root = r.get("repo_root").and_then(Value::as_str)?;
+            let log = r.get("log_file").and_then(Value::as_str)?;
+            Some((root.to_string(), log.to_string()))
+        })
+        .collect()
+}
+
+/// Remember where a repo keeps its run log; only writes when the pair is new.
+/// `root` is the run's codex root, so each package log of a monorepo counts.
+/// Best-effort: a registry failure must never fail the run being logged.
+pub fn record_repo(root: &str, log_file: &Path) {
+    if root.is_empty() {
+        return;
+    }
+    let Some(path) = resolve_repo_registry_file() else {
+        return;
+    };
+    let log = log_file.display().to_string();
+    let mut repos = read_registry(&path);
+    if repos.iter().any(|(r, l)| r == root && *l == log) {
+        return;
+    }
+    repos.retain(|(r, _)| r != root);
+    repos.push((root.to_string(), log));
+    let rows: Vec<Value> = repos
+        .iter()
+        .map(|(r, l)| json!({"repo_root": r, "log_file": l}))
+        .collect();
+    if let Err(e) = write_json_atomic(&path, &json!({"version": 1, "repos": rows})) {
+        warn(&format!("cxrs: could not update repo registry: {e}"));
+    }
+}
+
+/// Registered repos plus the current one (older logs predate the registry).
+fn known_logs() -> Vec<(String, PathBuf)> {
+    let mut out: Vec<(String, PathBuf)> = resolve_repo_registry_file()
+        .map(|p| read_registry(&p))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(r, l)| (r, PathBuf::from(l)))
+        .collect();
+    if let (Some(root), Some(log)) = (codex_root(), resolve_log_file()) {
+        let root = root.display().to_string();
+        if !out.iter().any(|(r, _)| *r == root) {
+            out.push((root, log));
+        }
+    }
+    out
+}
+
+/// Runs from every known repo log, each attributed to its repo, merged by
+/// timestamp; `filter` applies before the last-`n` window (`0` keeps all).
+/// Missing logs are skipped; unreadable ones are warned about and skipped.
+pub fn load_all_repo_runs(n: usize, filter: &RunFilter) -> Vec<RunEntry> {
+    let mut all = Vec::new();
+    for (root, log) in known_logs() {
+        if !log.exists() {
+            continue;
+        }
+        match load_runs(&log, 0) {
+            Ok(runs) => all.extend(runs.into_iter().map(|mut r| {
+                if r.repo_root.as_deref().is_none_or(str::is_empty) {
+                    r.repo_root = Some(root.clone());
+                }
+                r
+            })),
+            Err(e) => warn(&format!("cxrs: skipping {}: {e}", log.display())),
+        }
+    }
+    all.sort_by_key(|r| r.ts.as_deref().and_then(parse_ts_epoch).unwrap_or(0));
+    filter.apply(all, n)
+}
+
+#[derive(Debug, Default)]
+pub struct RepoStats {
+    pub runs: u64,
+    pub duration_sum: u64,
+    pub effective_input_tokens: u64,
+    pub output_tokens: u64,
+    pub schema_checked: u64,
+    pub schema_failed: u64,
+}
+
+/// Per-repo totals for the `--all-repos` breakdown, busiest repo first.
+pub fn per_repo(runs: &[RunEntry]) -> Vec<(String, RepoStats)> {
+    let mut by: BTreeMap<String, RepoStats> = BTreeMap::new();
+    for r in runs {
+        let root = r.repo_root.clone().unwrap_or_else(|| "unknown".to_string());
+        let s = by.entry(root).or_default();
+        s.runs += 1;
+        s.duration_sum += r.duration_ms.unwrap_or(0);
+        s.effective_input_tokens += r.effective_input_tokens.unwrap_or(0);
+        s.output_tokens += r.output_tokens.unwrap_or(0);
+        if let Some(valid) = r.schema_valid {
+            s.schema_checked += 1;
+            s.schema_failed += u64::from(!valid);
+        }
+    }
+    let mut out: Vec<_> = by.into_iter().collect();
+    out.sort_by(|a, b| b.1.runs.cmp(&a.1.runs).then_with(|| a.0.cmp(&b.0)));
+    out
+}
+
+pub fn per_repo_json(runs: &[RunEntry]) -> Value {
+    Value::Array(
+        per_repo(runs)
+            .into_iter()
+            .map(|(root, s)| {
+                json!({
+                    "repo_root": root,
+                    "runs": s.runs,
+                    "avg_duration_ms": s.duration_sum.checked_div(s.runs).unwrap_or(0),
+                    "effective_input_tokens": s.effective_input_tokens,
+                    "output_tokens": s.output_tokens,
+                    "schema_failure_rate": (s.schema_checked > 0)
+                        .then(|| s.schema_failed as f64 / s.schema_checked as f64),
+                })
+            })
+            .collect(),
+    )
+}
+
+pub fn print_per_repo(runs: &[RunEntry]) {
+    println!("Per repo (runs | avg ms | eff tokens):");
+    for (root, s) in per_repo(runs) {
+        println!(
+            "  {root}: {} | {} | {}",
+            s.runs,
+            s.duration_sum.checked_div(s.runs).unwrap_or(0),
+            s.effective_input_tokens
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_repo_ranks_busiest_first() {
+        let run = |root: Option<&str>, ms| RunEntry {
+            repo_root: root.map(str::to_string),
+            duration_ms: Some(ms),
+            schema_valid: Some(ms > 10),
+            ..RunEntry::default()
+        };
+        let runs = [
+            run(Some("/a"), 10),
+            run(Some("/b"), 20),
+            run(Some("/b"), 40),
+            run(None, 5),
+        ];
+        let rows = per_repo(&runs);
+        let names: Vec<&str> = rows.iter().map(|(r, _)| r.as_str()).collect();
+        assert_eq!(names, ["/b", "/a", "unknown"]);
+        assert_eq!(rows[0].1.duration_sum / rows[0].1.runs, 30);
+        let v = per_repo_json(&runs);
+        assert_eq!(v[0]["schema_failure_rate"], 0.0);
+        assert_eq!(v[1]["schema_failure_rate"], 1.0);
+    }
+}
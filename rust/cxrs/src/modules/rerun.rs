@@ -0,0 +1,258 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::analytics_diff::find_run;
+use crate::contract_versions::RERUN_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, print_runtime_error, print_usage_error};
+use crate::paths::resolve_log_file;
+use crate::structured_cmds::ExecuteTaskFn;
+use crate::transcripts::load_transcript;
+use crate::types::{LlmOutputKind, TaskInput, TaskSpec};
+use crate::util::sha256_hex;
+
+const USAGE: &str = "rerun <execution_id|prefix> [--json]";
+/// Above this many line pairs the diff falls back to "all removed, all added".
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Original execution id while a rerun executes; its run-log row records it as
+/// `rerun_of` (read by `runlog.rs`).
+static RERUN_OF: Mutex<Option<String>> = Mutex::new(None);
+
+fn set_rerun_of(id: Option<&str>) {
+    if let Ok(mut cur) = RERUN_OF.lock() {
+        *cur = id.map(str::to_string);
+    }
+}
+
+/// Execution id being rerun, when the current run is a rerun.
+pub fn current_rerun_of() -> Option<String> {
+    RERUN_OF.lock().ok().and_then(|cur| cur.clone())
+}
+
+/// Output kind the original tool ran with; schema tools re-send their
+/// archived envelope and compare the raw reply.
+fn output_kind_for_tool(tool: &str) -> LlmOutputKind {
+    match tool {
+        "cx" | "cxol" => LlmOutputKind::Plain,
+        "cxj" => LlmOutputKind::Jsonl,
+        _ => LlmOutputKind::AgentText,
+    }
+}
+
+/// Line diff (LCS) as `' '`/`'-'`/`'+'` tagged lines, old first.
+fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<(char, &'a str)> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELLS {
+        let mut out: Vec<(char, &str)> = a.iter().map(|l| ('-', *l)).collect();
+        out.extend(b.iter().map(|l| ('+', *l)));
+        return out;
+    }
+    // lcs[i][j] = common subsequence length of a[i..] and b[j..].
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            out.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(('-', a[i]));
+            i += 1;
+        } else {
+            out.push(('+', b[j]));
+            j += 1;
+        }
+    }
+    out.extend(a[i..].iter().map(|l| ('-', *l)));
+    out.extend(b[j..].iter().map(|l| ('+', *l)));
+    out
+}
+
+/// The newest run-log row written for a rerun of `original`.
+fn latest_rerun(log_file: &Path, original: &str) -> Option<Value> {
+    let text = fs::read_to_string(log_file).ok()?;
+    text.lines()
+        .rev()
+        .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+        .find(|v| v.get("rerun_of").and_then(Value::as_str) == Some(original))
+}
+
+fn str_field(v: &Value, key: &str) -> Value {
+    v.get(key).cloned().unwrap_or(Value::Null)
+}
+
+/// `match` when the archived prompt hashes to the row's `prompt_sha256`,
+/// `redacted` when it differs because secrets were masked before archiving.
+fn prompt_check(row: &Value, transcript: &Value, prompt: &str) -> Result<&'static str, String> {
+    let Some(expected) = row.get("prompt_sha256").and_then(Value::as_str) else {
+        return Ok("unverified");
+    };
+    if sha256_hex(prompt) == expected {
+        return Ok("match");
+    }
+    if transcript
+        .get("redactions_applied")
+        .and_then(Value::as_u64)
+        .is_some_and(|n| n > 0)
+    {
+        return Ok("redacted");
+    }
+    Err("archived prompt does not match the run's prompt_sha256".to_string())
+}
+
+fn print_report(report: &Value, diff: &[(char, &str)]) {
+    let cell = |v: &Value| v.as_str().unwrap_or("n/a").to_string();
+    println!(
+        "== cxrs rerun {} -> {} ==",
+        cell(&report["rerun_of"]),
+        cell(&report["execution_id"])
+    );
+    println!("tool: {}", cell(&report["tool"]));
+    for key in ["llm_backend", "llm_model"] {
+        println!(
+            "{key}: {} -> {}",
+            cell(&report[key]["from"]),
+            cell(&report[key]["to"])
+        );
+    }
+    println!("prompt: {}", cell(&report["prompt_check"]));
+    if report["identical"] == true {
+        println!("response: identical");
+        return;
+    }
+    println!(
+        "response: differs (+{} -{} lines)",
+        report["lines_added"], report["lines_removed"]
+    );
+    println!("--- {}", cell(&report["rerun_of"]));
+    println!("+++ {}", cell(&report["execution_id"]));
+    for (tag, line) in diff {
+        println!("{tag}{line}");
+    }
+}
+
+/// `rerun <execution_id> [--json]`: send an archived prompt to the current
+/// backend as the same tool, log the run with `rerun_of`, and diff the replies.
+pub fn cmd_rerun(args: &[String], execute_task: ExecuteTaskFn) -> i32 {
+    let (id, json_out) = match args {
+        [id] if !id.starts_with('-') => (id, false),
+        [id, flag] | [flag, id] if flag == "--json" && !id.starts_with('-') => (id, true),
+        _ => return print_usage_error("rerun", USAGE),
+    };
+    let Some(log_file) = resolve_log_file() else {
+        return print_runtime_error("rerun", "unable to resolve log file");
+    };
+    let row = match find_run(&log_file, id) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("rerun", &e),
+    };
+    let resolved = row
+        .get("execution_id")
+        .and_then(Value::as_str)
+        .unwrap_or(id)
+        .to_string();
+    let id = resolved.as_str();
+    let transcript = match load_transcript(id) {
+        Ok(v) => v,
+        Err(e) => {
+            return print_runtime_error(
+                "rerun",
+                &format!("{e}; runs can be rerun when archived with CX_TRANSCRIPTS=1"),
+            );
+        }
+    };
+    let prompt = transcript
+        .get("prompt")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let original = transcript
+        .get("response")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    if prompt.trim().is_empty() {
+        return print_runtime_error("rerun", &format!("transcript for {id} has no prompt"));
+    }
+    let check = match prompt_check(&row, &transcript, &prompt) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("rerun", &e),
+    };
+    let tool = row
+        .get("tool")
+        .and_then(Value::as_str)
+        .unwrap_or("cx")
+        .to_string();
+    set_rerun_of(Some(id));
+    let result = execute_task(TaskSpec {
+        command_name: tool.clone(),
+        input: TaskInput::Prompt(prompt),
+        output_kind: output_kind_for_tool(&tool),
+        schema: None,
+        schema_task_input: None,
+        logging_enabled: true,
+        capture_override: None,
+        prompt_template: None,
+        stream: false,
+    });
+    set_rerun_of(None);
+    let result = match result {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("rerun", &e),
+    };
+    let new_row = latest_rerun(&log_file, id).unwrap_or(Value::Null);
+    let diff = line_diff(&original, &result.stdout);
+    let count = |tag: char| diff.iter().filter(|(t, _)| *t == tag).count();
+    let report = json!({
+        "contract_version": RERUN_JSON_CONTRACT_VERSION,
+        "rerun_of": id,
+        "execution_id": str_field(&new_row, "execution_id"),
+        "tool": tool,
+        "llm_backend": {"from": str_field(&row, "llm_backend"), "to": str_field(&new_row, "llm_backend")},
+        "llm_model": {"from": str_field(&row, "llm_model"), "to": str_field(&new_row, "llm_model")},
+        "prompt_check": check,
+        "identical": original == result.stdout,
+        "lines_added": count('+'),
+        "lines_removed": count('-'),
+        "original_response": original,
+        "response": result.stdout,
+    });
+    if json_out {
+        match serde_json::to_string_pretty(&report) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return print_runtime_error("rerun", &e.to_string()),
+        }
+    } else {
+        print_report(&report, &diff);
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_keeps_common_lines_and_orders_removals_first() {
+        let diff = line_diff("a\nb\nc\n", "a\nx\nc\nd\n");
+        assert_eq!(
+            diff,
+            vec![(' ', "a"), ('-', "b"), ('+', "x"), (' ', "c"), ('+', "d")]
+        );
+        assert!(line_diff("same\n", "same\n").iter().all(|(t, _)| *t == ' '));
+        assert_eq!(line_diff("", "new"), vec![('+', "new")]);
+    }
+}
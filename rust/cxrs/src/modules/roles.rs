@@ -0,0 +1,192 @@
+use serde_json::Value;
+use std::fs;
+
+use crate::paths::{resolve_roles_file, resolve_state_file};
+use crate::state::{read_state_value, value_at_path};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleDef {
+    pub name: String,
+    pub summary: String,
+    pub header: String,
+    /// `builtin`, `state` or the path of `.codex/roles.json`.
+    pub source: String,
+}
+
+const BUILTIN_ROLES: [(&str, &str, &str); 5] = [
+    (
+        "architect",
+        "Define approach, boundaries, and tradeoffs.",
+        "Role: architect\nFocus: design and decomposition.\nDeliver: implementation plan, constraints, and acceptance checks.",
+    ),
+    (
+        "implementer",
+        "Apply focused code changes with minimal blast radius.",
+        "Role: implementer\nFocus: minimal cohesive code change.\nDeliver: patch summary and verification commands.",
+    ),
+    (
+        "reviewer",
+        "Validate regressions, risks, and missing tests.",
+        "Role: reviewer\nFocus: bugs, regressions, and safety.\nDeliver: findings ordered by severity with file references.",
+    ),
+    (
+        "tester",
+        "Design and run deterministic checks.",
+        "Role: tester\nFocus: deterministic validation.\nDeliver: test matrix, observed results, and failure triage.",
+    ),
+    (
+        "doc",
+        "Produce concise operator-facing documentation.",
+        "Role: doc\nFocus: user/operator clarity.\nDeliver: concise docs with examples and expected outputs.",
+    ),
+];
+
+fn builtin_roles() -> Vec<RoleDef> {
+    BUILTIN_ROLES
+        .iter()
+        .map(|(name, summary, header)| RoleDef {
+            name: name.to_string(),
+            summary: summary.to_string(),
+            header: header.to_string(),
+            source: "builtin".to_string(),
+        })
+        .collect()
+}
+
+fn valid_role_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Parse a `{"<name>": {"header": "...", "summary": "..."}}` object; errors name `origin`.
+fn parse_role_map(map: &Value, origin: &str, source: &str) -> Result<Vec<RoleDef>, String> {
+    let obj = map.as_object().ok_or_else(|| {
+        format!("{origin}: roles must be an object of name -> {{header, summary}}")
+    })?;
+    let mut out = Vec::new();
+    for (name, def) in obj {
+        if !valid_role_name(name) {
+            return Err(format!(
+                "{origin}: invalid role name '{name}' (use lowercase letters, digits, '-' or '_')"
+            ));
+        }
+        let header = def
+            .get("header")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| format!("{origin}: role '{name}' needs a non-empty string 'header'"))?;
+        let summary = match def.get("summary") {
+            None | Some(Value::Null) => header.lines().next().unwrap_or("").to_string(),
+            Some(Value::String(s)) => s.trim().to_string(),
+            Some(_) => return Err(format!("{origin}: role '{name}' summary must be a string")),
+        };
+        out.push(RoleDef {
+            name: name.clone(),
+            summary,
+            header: header.to_string(),
+            source: source.to_string(),
+        });
+    }
+    Ok(out)
+}
+
+fn merge(into: &mut Vec<RoleDef>, defs: Vec<RoleDef>) {
+    for def in defs {
+        match into.iter_mut().find(|r| r.name == def.name) {
+            Some(existing) => *existing = def,
+            None => into.push(def),
+        }
+    }
+}
+
+/// Built-in roles overlaid with `.codex/roles.json`, then state `roles.<name>`
+/// (later sources win for the same name).
+pub fn load_roles() -> Result<Vec<RoleDef>, String> {
+    let mut roles = builtin_roles();
+    if let Some(path) = resolve_roles_file().filter(|p| p.is_file()) {
+        let origin = path.display().to_string();
+        let raw = fs::read_to_string(&path).map_err(|e| format!("cannot read {origin}: {e}"))?;
+        let value: Value =
+            serde_json::from_str(&raw).map_err(|e| format!("{origin}: invalid JSON: {e}"))?;
+        merge(&mut roles, parse_role_map(&value, &origin, &origin)?);
+    }
+    if let Some(state) = read_state_value()
+        && let Some(map) = value_at_path(&state, "roles")
+    {
+        let origin = resolve_state_file()
+            .map(|p| format!("{} (roles)", p.display()))
+            .unwrap_or_else(|| "state (roles)".to_string());
+        merge(&mut roles, parse_role_map(map, &origin, "state")?);
+    }
+    Ok(roles)
+}
+
+pub fn find_role(name: &str) -> Result<Option<RoleDef>, String> {
+    Ok(load_roles()?.into_iter().find(|r| r.name == name))
+}
+
+/// Pull one `--role NAME` out of prompt args; None when the flag has no value.
+pub fn split_role_flag(args: &[String]) -> Option<(Option<&str>, Vec<String>)> {
+    let mut role = None;
+    let mut rest = Vec::new();
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        if a == "--role" {
+            role = Some(it.next()?.as_str());
+        } else {
+            rest.push(a.clone());
+        }
+    }
+    Some((role, rest))
+}
+
+/// Comma-separated names for "unknown role" messages.
+pub fn role_names(roles: &[RoleDef]) -> String {
+    roles
+        .iter()
+        .map(|r| r.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn custom_roles_parse_and_override_builtins() {
+        let map = json!({
+            "security": {"header": "Role: security\nFocus: threats."},
+            "doc": {"header": "Role: doc v2", "summary": "Docs, but custom."}
+        });
+        let mut roles = builtin_roles();
+        merge(
+            &mut roles,
+            parse_role_map(&map, "roles.json", "roles.json").unwrap(),
+        );
+        assert_eq!(roles.len(), 6);
+        let doc = roles.iter().find(|r| r.name == "doc").unwrap();
+        assert_eq!(
+            (doc.summary.as_str(), doc.source.as_str()),
+            ("Docs, but custom.", "roles.json")
+        );
+        let sec = roles.last().unwrap();
+        assert_eq!(
+            (sec.name.as_str(), sec.summary.as_str()),
+            ("security", "Role: security")
+        );
+    }
+
+    #[test]
+    fn invalid_roles_name_their_origin() {
+        let err = parse_role_map(&json!({"Bad Name": {"header": "x"}}), "f.json", "f").unwrap_err();
+        assert!(err.starts_with("f.json: invalid role name 'Bad Name'"));
+        let err = parse_role_map(&json!({"ops": {"header": " "}}), "f.json", "f").unwrap_err();
+        assert_eq!(err, "f.json: role 'ops' needs a non-empty string 'header'");
+        assert!(parse_role_map(&json!(["ops"]), "f.json", "f").is_err());
+    }
+}
@@ -4,10 +4,14 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::command_names::{is_compat_name, is_native_name};
+use crate::contract_versions::WHERE_JSON_CONTRACT_VERSION;
 use crate::execmeta::toolchain_version_string;
-use crate::paths::{repo_root_hint, resolve_log_file, resolve_state_file};
+use crate::paths::{
+    repo_root, repo_root_hint, resolve_log_file, resolve_state_file, resolved_paths,
+};
 use crate::process::run_command_output_with_timeout;
 use crate::runtime::{llm_backend, llm_model};
+use crate::vcs::current_vcs;
 
 const ROUTE_NAMES: &[&str] = &[
     "help",
@@ -17,6 +21,7 @@ const ROUTE_NAMES: &[&str] = &[
     "logs",
     "telemetry",
     "ci",
+    "hook",
     "task",
     "diag",
     "scheduler",
@@ -24,6 +29,14 @@ const ROUTE_NAMES: &[&str] = &[
     "doctor",
     "state",
     "llm",
+    "model",
+    "workspace",
+    "init",
+    "gc",
+    "annotate",
+    "secret",
+    "changelog",
+    "config",
     "policy",
     "bench",
     "metrics",
@@ -39,9 +52,14 @@ const ROUTE_NAMES: &[&str] = &[
     "cxol",
     "cxcopy",
     "fix",
+    "followup",
+    "explain",
+    "custom",
+    "session",
     "budget",
     "log-tail",
     "health",
+    "selftest",
     "capture-status",
     "log-on",
     "log-off",
@@ -49,21 +67,32 @@ const ROUTE_NAMES: &[&str] = &[
     "alert-on",
     "alert-off",
     "chunk",
+    "pack",
     "cx-compat",
     "profile",
     "alert",
     "optimize",
     "worklog",
     "trace",
+    "transcript",
+    "diff-runs",
+    "trends",
+    "digest",
+    "watch",
     "next",
     "fix-run",
+    "pipeline",
     "diffsum",
     "diffsum-staged",
     "commitjson",
     "commitmsg",
+    "stage",
     "replay",
+    "rerun",
     "quarantine",
     "supports",
+    "exit-codes",
+    "redact-test",
     "cxversion",
     "cxdoctor",
     "cxwhere",
@@ -216,6 +245,7 @@ fn print_where_header(repo: &Path, app_version: &str) {
     println!("bash_lib: {bash_lib}");
     println!("bash_lib_sourceable: {bash_sourceable}");
     println!("repo_root: {repo_root}");
+    println!("vcs: {}", current_vcs().as_str());
     println!("log_file: {log_file}");
     println!("source: {source}");
     println!("state_file: {state_file}");
@@ -247,10 +277,67 @@ fn print_where_routes(repo: &Path, cmds: &[String]) {
     }
 }
 
-pub fn print_where(cmds: &[String], app_version: &str) -> i32 {
+fn route_json(repo: &Path, cmd: &str) -> Value {
+    if let Some(handler) = route_handler_for(cmd) {
+        return json!({"name": cmd, "route": "rust", "handler": handler});
+    }
+    let route = if bash_type_of_function(repo, cmd).is_some() {
+        "bash"
+    } else {
+        "unknown"
+    };
+    json!({"name": cmd, "route": route, "handler": Value::Null})
+}
+
+fn print_where_json(repo: &Path, cmds: &[String], app_version: &str) -> i32 {
+    let paths: Vec<Value> = resolved_paths()
+        .iter()
+        .map(|p| {
+            json!({
+                "name": p.name,
+                "kind": p.kind,
+                "path": p.path.as_ref().map(|v| v.display().to_string()),
+                "reason": p.reason,
+                "exists": p.exists(),
+                "writable": p.writable(),
+            })
+        })
+        .collect();
+    let model = llm_model();
+    let doc = json!({
+        "contract_version": WHERE_JSON_CONTRACT_VERSION,
+        "cxrs_path": env::current_exe().ok().map(|p| p.display().to_string()),
+        "cxrs_version": toolchain_version_string(app_version),
+        "repo_root": repo_root().map(|p| p.display().to_string()),
+        "vcs": current_vcs().as_str(),
+        "home": env::var("HOME").ok(),
+        "backend": llm_backend(),
+        "active_model": if model.is_empty() { Value::Null } else { json!(model) },
+        "paths": paths,
+        "routes": cmds.iter().map(|c| route_json(repo, c)).collect::<Vec<_>>(),
+    });
+    match serde_json::to_string_pretty(&doc) {
+        Ok(s) => {
+            println!("{s}");
+            0
+        }
+        Err(e) => {
+            crate::cx_eprintln!("cxrs where: failed to render json: {e}");
+            1
+        }
+    }
+}
+
+/// `where [--json] [cmd...]`.
+pub fn print_where(args: &[String], app_version: &str) -> i32 {
     let repo = repo_root_hint().unwrap_or_else(|| PathBuf::from("."));
+    let json_out = args.iter().any(|a| a == "--json");
+    let cmds: Vec<String> = args.iter().filter(|a| *a != "--json").cloned().collect();
+    if json_out {
+        return print_where_json(&repo, &cmds, app_version);
+    }
     print_where_header(&repo, app_version);
-    print_where_routes(&repo, cmds);
+    print_where_routes(&repo, &cmds);
     0
 }
 
@@ -0,0 +1,510 @@
+use chrono::{NaiveDate, Utc};
+use serde_json::{Value, json};
+use std::path::Path;
+
+use crate::analytics::parse_ts_epoch;
+use crate::logs::load_runs;
+use crate::paths::repo_root;
+use crate::quarantine_ops::parse_age_secs;
+use crate::repo_registry::load_all_repo_runs;
+use crate::types::RunEntry;
+
+/// Commands that accept the shared filter flags (`[filters]` in their usage).
+pub const RUN_FILTER_COMMANDS: &[&str] = &[
+    "metrics",
+    "profile",
+    "alert",
+    "optimize",
+    "promptlint",
+    "worklog",
+    "trends",
+    "digest",
+];
+
+/// Help rows for the shared filter flags.
+pub const RUN_FILTER_FLAGS: &[(&str, &str)] = &[
+    ("--tool NAME[,NAME]", "Only runs of these tools"),
+    ("--task ID", "Only runs logged under this task id"),
+    (
+        "--since WHEN",
+        "Runs at or after WHEN (2h, 7d, YYYY-MM-DD or RFC 3339)",
+    ),
+    (
+        "--until WHEN",
+        "Runs at or before WHEN (a bare date includes the whole day)",
+    ),
+    (
+        "--scope repo|global|all",
+        "Runs logged inside a project, outside one, or both (profile/metrics default to the current one)",
+    ),
+    ("--branch NAME", "Only runs logged on this git branch"),
+    (
+        "--path SUBDIR",
+        "Only runs started at or below this repo subdirectory",
+    ),
+];
+
+/// A `--since`/`--until` bound as given on the command line and resolved to
+/// epoch seconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeBound {
+    pub raw: String,
+    pub epoch: i64,
+}
+
+/// Which runs a report covers: those logged inside a project (`repo`),
+/// outside one (`global`), or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunScope {
+    Repo,
+    Global,
+    All,
+}
+
+impl RunScope {
+    fn parse(raw: &str) -> Result<Self, String> {
+        match raw {
+            "repo" => Ok(RunScope::Repo),
+            "global" => Ok(RunScope::Global),
+            "all" => Ok(RunScope::All),
+            _ => Err(format!(
+                "--scope: invalid value '{raw}' (expected repo, global or all)"
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RunScope::Repo => "repo",
+            RunScope::Global => "global",
+            RunScope::All => "all",
+        }
+    }
+
+    /// `repo` inside a project, `global` outside one.
+    pub fn current() -> Self {
+        if repo_root().is_some() {
+            RunScope::Repo
+        } else {
+            RunScope::Global
+        }
+    }
+
+    /// Rows without a `scope` field predate it and belong to whatever log
+    /// they sit in, so every scope keeps them.
+    fn includes(self, r: &RunEntry) -> bool {
+        match (self, r.scope.as_deref()) {
+            (RunScope::All, _) | (_, None) => true,
+            (want, Some(got)) => want.as_str() == got,
+        }
+    }
+}
+
+/// Row filter shared by the analytics commands and applied before the
+/// "last N" window. Every set field must match for a run to be kept.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RunFilter {
+    pub branch: Option<String>,
+    pub path: Option<String>,
+    /// Comma-separated tool names (`--tool cxdiffsum,cxo`).
+    pub tools: Vec<String>,
+    pub task: Option<String>,
+    pub since: Option<TimeBound>,
+    pub until: Option<TimeBound>,
+    /// Read every registered repo log instead of the current one (`--all-repos`).
+    /// A scope, not a row predicate, so it does not count in [`RunFilter::is_empty`].
+    pub all_repos: bool,
+    /// `--scope`; reported on its own (see [`ScopeNote`]) rather than in the
+    /// filter label, so it does not count in [`RunFilter::is_empty`] either.
+    pub scope: Option<RunScope>,
+}
+
+fn normalize_path_arg(raw: &str) -> String {
+    let trimmed = raw.trim().trim_end_matches('/');
+    let trimmed = trimmed.strip_prefix("./").unwrap_or(trimmed);
+    if trimmed.is_empty() || trimmed == "." {
+        String::new()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// `2h`/`30d` (relative to `now`), RFC 3339, or `YYYY-MM-DD`. A bare date
+/// means the start of that day for `--since` and its end for `--until`.
+fn parse_time_bound(flag: &str, raw: &str, now: i64) -> Result<TimeBound, String> {
+    let epoch = if let Some(secs) = parse_age_secs(raw) {
+        now - secs
+    } else if let Some(ts) = parse_ts_epoch(raw) {
+        ts
+    } else if let Ok(day) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        let start = day.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc().timestamp());
+        let start = start.ok_or_else(|| format!("{flag}: invalid date '{raw}'"))?;
+        if flag == "--until" {
+            start + 86_399
+        } else {
+            start
+        }
+    } else {
+        return Err(format!(
+            "{flag}: invalid time '{raw}' (expected e.g. 2h, 7d, YYYY-MM-DD or RFC 3339)"
+        ));
+    };
+    Ok(TimeBound {
+        raw: raw.to_string(),
+        epoch,
+    })
+}
+
+impl RunFilter {
+    pub fn is_empty(&self) -> bool {
+        self.branch.is_none()
+            && self.path.is_none()
+            && self.tools.is_empty()
+            && self.task.is_none()
+            && !self.is_dated()
+    }
+
+    pub fn is_dated(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    /// Consume `flag` (and its value) if it is a filter flag. Returns
+    /// `Ok(false)` for flags the caller should handle itself.
+    pub fn parse_flag(&mut self, flag: &str, value: Option<&String>) -> Result<bool, String> {
+        if !matches!(
+            flag,
+            "--branch" | "--path" | "--tool" | "--task" | "--since" | "--until" | "--scope"
+        ) {
+            return Ok(false);
+        }
+        let v = value
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty() && !v.starts_with("--"))
+            .ok_or_else(|| format!("{flag} requires a value"))?;
+        let now = Utc::now().timestamp();
+        match flag {
+            "--branch" => self.branch = Some(v.to_string()),
+            "--path" => self.path = Some(normalize_path_arg(v)),
+            "--tool" => self.tools.extend(
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string),
+            ),
+            "--task" => self.task = Some(v.to_string()),
+            "--scope" => self.scope = Some(RunScope::parse(v)?),
+            "--since" => self.since = Some(parse_time_bound(flag, v, now)?),
+            _ => self.until = Some(parse_time_bound(flag, v, now)?),
+        }
+        if let (Some(s), Some(u)) = (&self.since, &self.until)
+            && u.epoch < s.epoch
+        {
+            return Err(format!("--until {} is before --since {}", u.raw, s.raw));
+        }
+        Ok(true)
+    }
+
+    fn path_matches(&self, r: &RunEntry) -> bool {
+        let Some(want) = &self.path else {
+            return true;
+        };
+        let Some(cwd) = r.cwd.as_deref().filter(|c| !c.is_empty()) else {
+            return false;
+        };
+        let cwd = Path::new(cwd);
+        if Path::new(want).is_absolute() {
+            return cwd.starts_with(want);
+        }
+        let Some(root) = r.repo_root.as_deref().filter(|c| !c.is_empty()) else {
+            return false;
+        };
+        cwd.strip_prefix(root)
+            .is_ok_and(|rel| rel.starts_with(want))
+    }
+
+    fn time_matches(&self, r: &RunEntry) -> bool {
+        if !self.is_dated() {
+            return true;
+        }
+        let Some(t) = r.ts.as_deref().and_then(parse_ts_epoch) else {
+            return false;
+        };
+        self.since.as_ref().is_none_or(|s| t >= s.epoch)
+            && self.until.as_ref().is_none_or(|u| t <= u.epoch)
+    }
+
+    pub fn matches(&self, r: &RunEntry) -> bool {
+        let eq = |want: &Option<String>, got: &Option<String>| {
+            want.as_ref()
+                .is_none_or(|w| got.as_deref() == Some(w.as_str()))
+        };
+        eq(&self.branch, &r.git_branch)
+            && eq(&self.task, &r.task_id)
+            && (self.tools.is_empty() || r.tool.as_ref().is_some_and(|t| self.tools.contains(t)))
+            && self.path_matches(r)
+            && self.time_matches(r)
+            && self.scope.is_none_or(|s| s.includes(r))
+    }
+
+    /// `--scope`, else the current scope (`all` with `--all-repos`).
+    pub fn report_scope(&self) -> RunScope {
+        self.scope.unwrap_or_else(|| {
+            if self.all_repos {
+                RunScope::All
+            } else {
+                RunScope::current()
+            }
+        })
+    }
+
+    fn is_unscoped(&self) -> bool {
+        self.is_empty() && self.scope.is_none_or(|s| s == RunScope::All)
+    }
+
+    /// Keep matching runs, then the last `n` of them (`0` = all).
+    pub fn apply(&self, runs: Vec<RunEntry>, n: usize) -> Vec<RunEntry> {
+        let mut out: Vec<RunEntry> = if self.is_unscoped() {
+            runs
+        } else {
+            runs.into_iter().filter(|r| self.matches(r)).collect()
+        };
+        if n > 0 && out.len() > n {
+            out = out.split_off(out.len() - n);
+        }
+        out
+    }
+
+    /// `filter` object for JSON reports; `None` when no filter is active.
+    pub fn to_json(&self) -> Option<Value> {
+        (!self.is_empty()).then(|| {
+            json!({
+                "branch": self.branch,
+                "path": self.path,
+                "tool": (!self.tools.is_empty()).then(|| self.tools.join(",")),
+                "task": self.task,
+                "since": self.since.as_ref().map(|b| &b.raw),
+                "until": self.until.as_ref().map(|b| &b.raw),
+            })
+        })
+    }
+
+    /// One-line description for text reports (`branch=main tool=cxo`).
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(b) = &self.branch {
+            parts.push(format!("branch={b}"));
+        }
+        if let Some(p) = &self.path {
+            parts.push(format!(
+                "path={}",
+                if p.is_empty() { "." } else { p.as_str() }
+            ));
+        }
+        if !self.tools.is_empty() {
+            parts.push(format!("tool={}", self.tools.join(",")));
+        }
+        if let Some(t) = &self.task {
+            parts.push(format!("task={t}"));
+        }
+        if let Some(s) = &self.since {
+            parts.push(format!("since={}", s.raw));
+        }
+        if let Some(u) = &self.until {
+            parts.push(format!("until={}", u.raw));
+        }
+        parts.join(" ")
+    }
+}
+
+/// Pull the filter flags out of `args`, returning the filter and the rest.
+pub fn split_run_filter(args: &[String]) -> Result<(RunFilter, Vec<String>), String> {
+    let mut filter = RunFilter::default();
+    let mut rest = Vec::new();
+    let mut i = 0usize;
+    while i < args.len() {
+        if args[i] == "--all-repos" {
+            filter.all_repos = true;
+            i += 1;
+            continue;
+        }
+        if filter.parse_flag(&args[i], args.get(i + 1))? {
+            i += 2;
+            continue;
+        }
+        rest.push(args[i].clone());
+        i += 1;
+    }
+    Ok((filter, rest))
+}
+
+/// Window size plus `--json` in `args[idx..]`, accepted in either order
+/// (`profile 50 --json`).
+pub fn parse_window_json(args: &[String], idx: usize, default: usize) -> (usize, bool) {
+    let rest = args.get(idx..).unwrap_or_default();
+    let json_out = rest.iter().any(|v| v == "--json");
+    let n = rest
+        .iter()
+        .find_map(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(default);
+    (n, json_out)
+}
+
+/// Last `n` runs of `log_file` matching `filter` (`n == 0` keeps all), or of
+/// every registered repo log with `--all-repos`.
+pub fn load_filtered_runs(
+    log_file: &Path,
+    n: usize,
+    filter: &RunFilter,
+) -> Result<Vec<RunEntry>, String> {
+    if filter.all_repos {
+        return Ok(load_all_repo_runs(n, filter));
+    }
+    if filter.is_unscoped() {
+        return load_runs(log_file, n);
+    }
+    Ok(filter.apply(load_runs(log_file, 0)?, n))
+}
+
+/// The scope a report covered and how many runs matching the other filters
+/// it left out for being in another scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScopeNote {
+    pub scope: RunScope,
+    pub excluded: usize,
+}
+
+impl ScopeNote {
+    pub fn to_json(self, included: usize) -> Value {
+        json!({
+            "selected": self.scope.as_str(),
+            "included": included,
+            "excluded": self.excluded,
+        })
+    }
+
+    /// `repo (12 runs; 3 from other scopes excluded)`.
+    pub fn label(self, included: usize) -> String {
+        let mut out = format!("{} ({included} runs", self.scope.as_str());
+        if self.excluded > 0 {
+            out.push_str(&format!("; {} from other scopes excluded", self.excluded));
+        }
+        out.push(')');
+        out
+    }
+}
+
+/// Like [`load_filtered_runs`], for reports that default to the current
+/// scope (`all` with `--all-repos`) when `--scope` is not given.
+pub fn load_scoped_runs(
+    log_file: &Path,
+    n: usize,
+    filter: &RunFilter,
+) -> Result<(Vec<RunEntry>, ScopeNote), String> {
+    let scope = filter.report_scope();
+    let unscoped = RunFilter {
+        scope: None,
+        ..filter.clone()
+    };
+    let runs = load_filtered_runs(log_file, 0, &unscoped)?;
+    let total = runs.len();
+    let mut kept: Vec<RunEntry> = runs.into_iter().filter(|r| scope.includes(r)).collect();
+    let excluded = total - kept.len();
+    if n > 0 && kept.len() > n {
+        kept = kept.split_off(kept.len() - n);
+    }
+    Ok((kept, ScopeNote { scope, excluded }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn run(branch: &str, cwd: &str) -> RunEntry {
+        RunEntry {
+            git_branch: Some(branch.to_string()),
+            cwd: Some(cwd.to_string()),
+            repo_root: Some("/repo".to_string()),
+            ts: Some("2024-06-01T12:00:00Z".to_string()),
+            tool: Some("cxo".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn branch_and_path_filters_combine() {
+        let (filter, rest) =
+            split_run_filter(&args(&["20", "--branch", "feat", "--path", "./src/"])).unwrap();
+        assert_eq!(rest, vec!["20"]);
+        assert_eq!(filter.label(), "branch=feat path=src");
+        assert!(filter.matches(&run("feat", "/repo/src/modules")));
+        assert!(!filter.matches(&run("feat", "/repo/srcx")));
+        assert!(!filter.matches(&run("main", "/repo/src")));
+        assert!(split_run_filter(&args(&["--branch"])).is_err());
+
+        let picked = filter.apply(
+            vec![
+                run("feat", "/repo/src"),
+                run("main", "/repo/src"),
+                run("feat", "/repo/src/a"),
+            ],
+            1,
+        );
+        assert_eq!(picked[0].cwd.as_deref(), Some("/repo/src/a"));
+    }
+
+    #[test]
+    fn tool_task_and_time_window_filters() {
+        let (filter, _) = split_run_filter(&args(&[
+            "--tool",
+            "cxdiffsum,cxo",
+            "--since",
+            "2024-06-01",
+            "--until",
+            "2024-06-01",
+        ]))
+        .unwrap();
+        let r = run("main", "/repo");
+        assert!(filter.matches(&r));
+        let late = RunEntry {
+            ts: Some("2024-06-02T00:00:00Z".to_string()),
+            ..r.clone()
+        };
+        assert!(!filter.matches(&late));
+        let other = RunEntry {
+            tool: Some("cxj".to_string()),
+            ..r.clone()
+        };
+        assert!(!filter.matches(&other));
+
+        let (task, _) = split_run_filter(&args(&["--task", "task_007"])).unwrap();
+        assert!(!task.matches(&r));
+
+        let (scoped, _) = split_run_filter(&args(&["--scope", "global"])).unwrap();
+        assert!(scoped.is_empty());
+        let global = RunEntry {
+            scope: Some("global".to_string()),
+            ..r.clone()
+        };
+        assert!(scoped.matches(&global));
+        assert!(scoped.matches(&r), "rows without a scope match any scope");
+        assert!(!scoped.matches(&RunEntry {
+            scope: Some("repo".to_string()),
+            ..r.clone()
+        }));
+        assert!(split_run_filter(&args(&["--scope", "home"])).is_err());
+
+        let bound = parse_time_bound("--since", "2h", 10_000).unwrap();
+        assert_eq!(bound.epoch, 10_000 - 7_200);
+        assert!(split_run_filter(&args(&["--since", "yesterday"])).is_err());
+        assert!(
+            split_run_filter(&args(&["--since", "2024-06-02", "--until", "2024-06-01"]))
+                .unwrap_err()
+                .contains("before --since")
+        );
+    }
+}
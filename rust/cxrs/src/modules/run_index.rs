@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::util::sha256_hex;
+
+const INDEX_VERSION: u32 = 1;
+const AMBIGUOUS_SHOWN: usize = 5;
+
+/// Sidecar index of a run log (`runs.idx` beside `runs.jsonl`): the byte
+/// offset of every parseable row, oldest first, keyed by `execution_id`
+/// (empty for legacy rows without one). Appended rows are indexed
+/// incrementally; a rewritten or rotated log is detected through `head` and
+/// `log_bytes` and indexed again from the start.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RunIndex {
+    version: u32,
+    log_bytes: u64,
+    head: String,
+    entries: Vec<(String, u64)>,
+}
+
+/// A run found by execution id, with its recency position (1 = most recent)
+/// among the `available` rows of the log.
+#[derive(Debug)]
+pub struct IndexedRun {
+    pub row: Value,
+    pub recency: usize,
+    pub available: usize,
+}
+
+fn index_path(log_file: &Path) -> PathBuf {
+    log_file.with_extension("idx")
+}
+
+fn first_line_hash(log_file: &Path) -> String {
+    let Ok(file) = File::open(log_file) else {
+        return String::new();
+    };
+    let mut line = String::new();
+    let _ = BufReader::new(file).read_line(&mut line);
+    sha256_hex(&line)
+}
+
+fn load_index(path: &Path) -> RunIndex {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<RunIndex>(&s).ok())
+        .filter(|idx| idx.version == INDEX_VERSION)
+        .unwrap_or_default()
+}
+
+/// Index rows from `idx.log_bytes` to the end of the log.
+fn extend_index(log_file: &Path, idx: &mut RunIndex) -> Result<(), String> {
+    let file =
+        File::open(log_file).map_err(|e| format!("cannot open {}: {e}", log_file.display()))?;
+    let mut reader = BufReader::new(file);
+    reader
+        .seek(SeekFrom::Start(idx.log_bytes))
+        .map_err(|e| format!("seek failed on {}: {e}", log_file.display()))?;
+    let mut offset = idx.log_bytes;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let n = reader
+            .read_line(&mut line)
+            .map_err(|e| format!("cannot read {}: {e}", log_file.display()))?;
+        if n == 0 || !line.ends_with('\n') {
+            break;
+        }
+        if let Ok(v) = serde_json::from_str::<Value>(line.trim()) {
+            let id = v.get("execution_id").and_then(Value::as_str).unwrap_or("");
+            idx.entries.push((id.to_string(), offset));
+        }
+        offset += n as u64;
+    }
+    idx.log_bytes = offset;
+    Ok(())
+}
+
+fn save_index(path: &Path, idx: &RunIndex) {
+    let Ok(text) = serde_json::to_string(idx) else {
+        return;
+    };
+    let tmp = path.with_extension(format!("idx.tmp.{}", std::process::id()));
+    if fs::write(&tmp, text).is_ok() && fs::rename(&tmp, path).is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+}
+
+/// Load the sidecar index and bring it up to date with the log. The index is
+/// a cache: when it cannot be written, lookups still work from memory.
+fn refreshed_index(log_file: &Path, rebuild: bool) -> Result<RunIndex, String> {
+    let path = index_path(log_file);
+    let log_bytes = fs::metadata(log_file)
+        .map_err(|e| format!("cannot read {}: {e}", log_file.display()))?
+        .len();
+    let head = first_line_hash(log_file);
+    let mut idx = if rebuild {
+        RunIndex::default()
+    } else {
+        load_index(&path)
+    };
+    if idx.head != head || idx.log_bytes > log_bytes {
+        idx = RunIndex {
+            version: INDEX_VERSION,
+            head,
+            ..RunIndex::default()
+        };
+    }
+    if idx.log_bytes < log_bytes {
+        extend_index(log_file, &mut idx)?;
+        save_index(&path, &idx);
+    }
+    Ok(idx)
+}
+
+/// Position in `entries` of the run `query` names: an exact execution id, or a
+/// prefix shared by exactly one id. The newest row wins for a repeated id.
+fn resolve_position(entries: &[(String, u64)], query: &str) -> Result<usize, String> {
+    if let Some(pos) = entries.iter().rposition(|(id, _)| id == query) {
+        return Ok(pos);
+    }
+    let mut matches: Vec<(usize, &str)> = Vec::new();
+    for (pos, (id, _)) in entries.iter().enumerate().rev() {
+        if !id.is_empty() && id.starts_with(query) && !matches.iter().any(|(_, m)| *m == id) {
+            matches.push((pos, id));
+        }
+    }
+    match matches.as_slice() {
+        [] => Err(format!("execution_id '{query}' not found")),
+        [(pos, _)] => Ok(*pos),
+        many => {
+            let shown: Vec<&str> = many
+                .iter()
+                .take(AMBIGUOUS_SHOWN)
+                .map(|(_, id)| *id)
+                .collect();
+            let more = many.len().saturating_sub(AMBIGUOUS_SHOWN);
+            let tail = if more > 0 {
+                format!(", +{more} more")
+            } else {
+                String::new()
+            };
+            Err(format!(
+                "execution_id prefix '{query}' is ambiguous ({} runs: {}{tail})",
+                many.len(),
+                shown.join(", ")
+            ))
+        }
+    }
+}
+
+fn read_row_at(log_file: &Path, offset: u64) -> Option<Value> {
+    let mut reader = BufReader::new(File::open(log_file).ok()?);
+    reader.seek(SeekFrom::Start(offset)).ok()?;
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    serde_json::from_str(line.trim()).ok()
+}
+
+/// Find a run by execution id or unique id prefix through the sidecar index,
+/// reading only the matching row. A stale index is rebuilt once.
+pub fn find_indexed_run(log_file: &Path, query: &str) -> Result<IndexedRun, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("execution_id must not be empty".to_string());
+    }
+    for rebuild in [false, true] {
+        let idx = refreshed_index(log_file, rebuild)?;
+        let pos = resolve_position(&idx.entries, query)
+            .map_err(|e| format!("{e} in {}", log_file.display()))?;
+        let (id, offset) = &idx.entries[pos];
+        let Some(row) = read_row_at(log_file, *offset) else {
+            continue;
+        };
+        if row.get("execution_id").and_then(Value::as_str) == Some(id.as_str()) {
+            return Ok(IndexedRun {
+                row,
+                recency: idx.entries.len() - pos,
+                available: idx.entries.len(),
+            });
+        }
+    }
+    Err(format!(
+        "execution_id '{query}' not found in {}",
+        log_file.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn append(path: &Path, id: &str) {
+        let mut f = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .expect("open log");
+        writeln!(
+            f,
+            "{}",
+            serde_json::json!({"execution_id": id, "tool": "cxo"})
+        )
+        .expect("write");
+    }
+
+    #[test]
+    fn prefix_lookup_resolves_unique_and_reports_ambiguous() {
+        let entries: Vec<(String, u64)> = ["abc111", "abd222", "abc333", "", "abc111"]
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.to_string(), i as u64))
+            .collect();
+        assert_eq!(resolve_position(&entries, "abc111"), Ok(4));
+        assert_eq!(resolve_position(&entries, "abd"), Ok(1));
+        let err = resolve_position(&entries, "abc").unwrap_err();
+        assert!(err.contains("ambiguous (2 runs: abc111, abc333)"), "{err}");
+        assert!(resolve_position(&entries, "zzz").is_err());
+    }
+
+    #[test]
+    fn index_extends_on_append_and_rebuilds_after_rewrite() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let log = dir.path().join("runs.jsonl");
+        append(&log, "run_a");
+        append(&log, "run_b");
+        let found = find_indexed_run(&log, "run_a").expect("run_a");
+        assert_eq!((found.recency, found.available), (2, 2));
+        assert!(dir.path().join("runs.idx").exists());
+
+        append(&log, "run_c");
+        let found = find_indexed_run(&log, "run_c").expect("run_c");
+        assert_eq!((found.recency, found.available), (1, 3));
+
+        fs::remove_file(&log).expect("rotate");
+        append(&log, "run_z");
+        append(&log, "run_c");
+        let found = find_indexed_run(&log, "run_c").expect("run_c after rotation");
+        assert_eq!(found.row["execution_id"], "run_c");
+        assert_eq!((found.recency, found.available), (1, 2));
+        assert!(find_indexed_run(&log, "run_a").is_err());
+    }
+}
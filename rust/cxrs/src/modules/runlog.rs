@@ -1,27 +1,44 @@
 use serde_json::json;
 use std::env;
 
+use crate::alert_sinks::dispatch_run_alerts;
+use crate::codex_args::{codex_arg_value, render_codex_args, resolved_codex_args};
 use crate::config::app_config;
 use crate::execmeta::{is_schema_tool, make_execution_id, prompt_preview, utc_now_iso};
+use crate::gc::auto_gc;
 use crate::llm::effective_input_tokens;
 use crate::logs::{append_jsonl, validate_execution_log_row};
-use crate::paths::{repo_root, resolve_log_file, resolve_schema_fail_log_file};
+use crate::model_context::context_outcome;
+use crate::nesting::{nested_parent_id, take_pending_execution_id};
+use crate::paths::{codex_root, repo_root, resolve_log_file, resolve_schema_fail_log_file};
+use crate::pipeline::current_pipeline_id;
 use crate::provider_adapter::{
-    selected_adapter_name, selected_http_parser_mode_opt, selected_http_provider_format_opt,
-    selected_provider_status, selected_provider_transport,
+    FailoverOutcome, failover_outcome, selected_adapter_name, selected_http_parser_mode_opt,
+    selected_http_provider_format_opt, selected_provider_status, selected_provider_transport,
 };
 use crate::quarantine::quarantine_store_with_attempts;
+use crate::repo_registry::record_repo;
+use crate::rerun::current_rerun_of;
 use crate::runtime::{llm_backend, llm_model};
 use crate::schema::schema_name_for_tool;
 use crate::state::{current_task_id, current_task_parent_id};
-use crate::types::{CaptureStats, ExecutionLog, QuarantineAttempt, UsageStats};
+use crate::structured_fixrun_exec::FixDecision;
+use crate::token_estimate::{TokenizerKind, estimate_tokens};
+use crate::transcripts::archive_transcript;
+use crate::types::{
+    CaptureStats, ChunkCall, ExecutionLog, PromptTemplateStamp, QuarantineAttempt, UsageStats,
+};
 use crate::util::sha256_hex;
+use crate::vcs::{branch_head, current_project};
+use crate::verbosity::warn;
 
 pub struct RunLogInput<'a> {
     pub tool: &'a str,
     pub prompt: &'a str,
     pub prompt_raw: Option<&'a str>,
     pub prompt_filtered: Option<&'a str>,
+    /// Full model response, archived as a transcript when `CX_TRANSCRIPTS=1`.
+    pub response: Option<&'a str>,
     pub schema_prompt: Option<&'a str>,
     pub schema_raw: Option<&'a str>,
     pub schema_attempt: Option<u64>,
@@ -35,8 +52,14 @@ pub struct RunLogInput<'a> {
     pub schema_reason: Option<&'a str>,
     pub schema_name: Option<&'a str>,
     pub quarantine_id: Option<&'a str>,
+    /// Steps that turned an invalid schema reply into a valid one.
+    pub schema_repair: Option<&'a [&'a str]>,
     pub policy_blocked: Option<bool>,
     pub policy_reason: Option<&'a str>,
+    pub fix_decisions: Option<&'a serde_json::Value>,
+    pub prompt_template: Option<&'a PromptTemplateStamp>,
+    /// Clipboard provider `cxcopy` actually copied with.
+    pub clipboard_provider: Option<&'a str>,
 }
 
 pub struct TaskRunAllSummaryLogInput<'a> {
@@ -81,6 +104,43 @@ fn current_task_fields() -> (Option<String>, Option<String>) {
     )
 }
 
+/// Attribute a codex run to the extra options it ran with; a `--model` there
+/// is the model codex actually used.
+fn apply_codex_args(row: &mut ExecutionLog) {
+    if row.backend_used != "codex" {
+        return;
+    }
+    let Ok(args) = resolved_codex_args() else {
+        return;
+    };
+    if args.is_empty() {
+        return;
+    }
+    row.codex_profile = codex_arg_value(&args, "--profile").map(str::to_string);
+    if let Some(model) = codex_arg_value(&args, "--model") {
+        row.llm_model = Some(model.to_string());
+    }
+    row.codex_args = Some(render_codex_args(&args));
+}
+
+/// Record which backend of the failover chain served the run and why the
+/// ones before it failed; `backend_selected` keeps the configured backend.
+fn apply_failover(row: &mut ExecutionLog, outcome: FailoverOutcome) {
+    if let Some(served) = outcome.served {
+        if served != row.llm_backend {
+            row.route_reason = Some(format!("failover_to_{served}"));
+        }
+        row.adapter_type = Some(format!("{served}-cli"));
+        row.backend_used = served.clone();
+        row.llm_backend = served;
+        row.llm_model = outcome.model;
+    }
+    if !outcome.failures.is_empty() {
+        row.failover_count = Some(outcome.failures.len() as u64);
+        row.failover_reasons = Some(outcome.failures);
+    }
+}
+
 fn base_execution_log(
     tool: &str,
     ts: String,
@@ -145,15 +205,28 @@ fn base_execution_log(
         .ok()
         .and_then(|v| v.parse::<u64>().ok());
     let (task_id, task_parent_id) = current_task_fields();
+    let rerun_of = current_rerun_of();
+    let pipeline_id = current_pipeline_id();
+    let project = (!root.is_empty()).then(current_project).flatten();
+    let vcs = project.as_ref().map(|p| p.vcs);
+    let (git_branch, git_head) = vcs.map_or((None, None), branch_head);
     let mut row = ExecutionLog {
-        execution_id: make_execution_id(tool),
+        execution_id: take_pending_execution_id().unwrap_or_else(|| make_execution_id(tool)),
         timestamp: ts.clone(),
         ts,
         command: tool.to_string(),
         tool: tool.to_string(),
         cwd,
         scope,
+        scope_source: Some(project.map_or("none", |p| p.source).to_string()),
+        codex_root: (!root.is_empty())
+            .then(codex_root)
+            .flatten()
+            .map(|p| p.display().to_string()),
         repo_root: root,
+        git_branch,
+        git_head,
+        vcs: vcs.map(|v| v.as_str().to_string()),
         backend_used: backend.clone(),
         llm_backend: backend,
         llm_model: model_opt.clone(),
@@ -179,8 +252,15 @@ fn base_execution_log(
         retry_backoff_ms,
         task_id,
         task_parent_id,
+        rerun_of,
+        pipeline_id,
+        parent_execution_id: nested_parent_id(),
         ..Default::default()
     };
+    if let Some(outcome) = failover_outcome() {
+        apply_failover(&mut row, outcome);
+    }
+    apply_codex_args(&mut row);
     row.execution_mode = app_config().cx_mode.clone();
     row.schema_valid = true;
     row.schema_ok = true;
@@ -199,8 +279,15 @@ fn base_run_row(tool: &str, cwd: String, scope: String, root: String) -> Executi
 
 fn finalize_and_append_run(run_log: &std::path::Path, row: ExecutionLog) -> Result<(), String> {
     validate_execution_log_row(&row)?;
+    let root = row
+        .codex_root
+        .clone()
+        .unwrap_or_else(|| row.repo_root.clone());
     let value = serde_json::to_value(row).map_err(|e| format!("failed serialize run log: {e}"))?;
-    append_jsonl(run_log, &value)
+    append_jsonl(run_log, &value)?;
+    record_repo(&root, run_log);
+    auto_gc(run_log);
+    Ok(())
 }
 
 pub fn log_codex_run(input: RunLogInput<'_>) -> Result<(), String> {
@@ -222,7 +309,25 @@ pub fn log_codex_run(input: RunLogInput<'_>) -> Result<(), String> {
     row.schema_ok = input.schema_ok;
     row.schema_reason = input.schema_reason.map(|s| s.to_string());
     row.quarantine_id = input.quarantine_id.map(|s| s.to_string());
+    row.repair_applied = input.schema_name.map(|_| input.schema_repair.is_some());
+    row.repair_steps = input
+        .schema_repair
+        .map(|steps| steps.iter().map(|s| s.to_string()).collect());
     row.capture_provider = cap.capture_provider.clone();
+    row.reducer = cap.reducer.clone();
+    row.chunk_strategy = cap.chunk_strategy.clone();
+    row.chunk_count = cap.chunk_count;
+    row.redactions_applied = cap.redactions_applied;
+    row.stdout_len_raw = cap.stdout_len_raw;
+    row.stdout_len_clipped = cap.stdout_len_clipped;
+    row.stdout_lines_raw = cap.stdout_lines_raw;
+    row.stdout_lines_clipped = cap.stdout_lines_clipped;
+    row.stderr_len_raw = cap.stderr_len_raw;
+    row.stderr_len_clipped = cap.stderr_len_clipped;
+    row.stderr_lines_raw = cap.stderr_lines_raw;
+    row.stderr_lines_clipped = cap.stderr_lines_clipped;
+    row.ansi_sequences_stripped = cap.ansi_sequences_stripped;
+    row.binary_output = cap.binary_output;
     row.input_tokens = input_tokens;
     row.cached_input_tokens = cached;
     row.effective_input_tokens = effective;
@@ -238,6 +343,9 @@ pub fn log_codex_run(input: RunLogInput<'_>) -> Result<(), String> {
     row.budget_lines = cap.budget_lines;
     row.clip_mode = cap.clip_mode;
     row.clip_footer = cap.clip_footer;
+    row.system_output_tokens_raw = cap.system_output_tokens_raw;
+    row.system_output_tokens_clipped = cap.system_output_tokens_clipped;
+    row.budget_tokens = cap.budget_tokens;
     row.rtk_used = cap.rtk_used;
     row.prompt_sha256 = Some(sha256_hex(filtered_prompt));
     row.prompt_sha256_raw = Some(sha256_hex(raw_prompt));
@@ -245,6 +353,13 @@ pub fn log_codex_run(input: RunLogInput<'_>) -> Result<(), String> {
     row.prompt_len_raw = Some(raw_prompt.chars().count() as u64);
     row.prompt_len_filtered = Some(filtered_prompt.chars().count() as u64);
     row.prompt_filter_applied = Some(raw_prompt != filtered_prompt);
+    let tokenizer = TokenizerKind::for_backend(&row.llm_backend);
+    row.prompt_tokens_est = Some(estimate_tokens(filtered_prompt, tokenizer) as u64);
+    row.token_estimator = Some(tokenizer.as_str().to_string());
+    if let Some(outcome) = context_outcome() {
+        row.context_window = Some(outcome.window);
+        row.context_guard = Some(outcome.guard.to_string());
+    }
     row.schema_prompt_sha256 = input.schema_prompt.map(sha256_hex);
     row.schema_sha256 = input.schema_raw.map(sha256_hex);
     row.schema_attempt = input.schema_attempt;
@@ -254,8 +369,60 @@ pub fn log_codex_run(input: RunLogInput<'_>) -> Result<(), String> {
     row.prompt_preview = Some(prompt_preview(filtered_prompt, 180));
     row.policy_blocked = input.policy_blocked;
     row.policy_reason = input.policy_reason.map(|s| s.to_string());
+    row.fix_decisions = input.fix_decisions.cloned();
+    row.clipboard_provider = input.clipboard_provider.map(str::to_string);
+    if let Some(t) = input.prompt_template {
+        row.prompt_template = Some(t.name.clone());
+        row.prompt_template_source = Some(t.source.clone());
+        row.prompt_template_sha256 = Some(t.sha256.clone());
+    }
 
-    finalize_and_append_run(&run_log, row)
+    if let Some(response) = input.response
+        && let Err(e) = archive_transcript(&row, filtered_prompt, response)
+    {
+        warn(&format!("cxrs transcript: {e}"));
+    }
+    let alert_row = serde_json::to_value(&row).ok();
+    let parent_id = row.execution_id.clone();
+    finalize_and_append_run(&run_log, row)?;
+    if let Some(v) = alert_row {
+        dispatch_run_alerts(&v);
+    }
+    for call in &cap.chunk_calls {
+        log_chunk_call(&run_log, &parent_id, call)?;
+    }
+    Ok(())
+}
+
+/// One child row per map-reduce summarization call, attributed to the parent run.
+fn log_chunk_call(
+    run_log: &std::path::Path,
+    parent_id: &str,
+    call: &ChunkCall,
+) -> Result<(), String> {
+    let (cwd, root, scope) = cwd_scope_root();
+    let mut row = base_run_row("cxmapreduce_chunk", cwd, scope, root);
+    row.execution_id = format!("{parent_id}_chunk{}", call.index);
+    row.parent_execution_id = Some(parent_id.to_string());
+    row.chunk_index = Some(call.index);
+    row.chunk_strategy = Some("mapreduce".to_string());
+    row.command_label = Some("mapreduce_chunk".to_string());
+    row.duration_ms = Some(call.duration_ms);
+    row.schema_valid = call.error.is_none();
+    row.schema_ok = call.error.is_none();
+    row.schema_reason = call.error.clone();
+    row.input_tokens = call.usage.input_tokens;
+    row.cached_input_tokens = call.usage.cached_input_tokens;
+    row.effective_input_tokens =
+        effective_input_tokens(call.usage.input_tokens, call.usage.cached_input_tokens);
+    row.output_tokens = call.usage.output_tokens;
+    row.prompt_sha256 = Some(sha256_hex(&call.prompt));
+    row.prompt_len_raw = Some(call.prompt.chars().count() as u64);
+    let tokenizer = TokenizerKind::for_backend(&row.llm_backend);
+    row.prompt_tokens_est = Some(estimate_tokens(&call.prompt, tokenizer) as u64);
+    row.token_estimator = Some(tokenizer.as_str().to_string());
+    row.prompt_preview = Some(prompt_preview(&call.prompt, 180));
+    finalize_and_append_run(run_log, row)
 }
 
 pub fn log_task_run_all_summary(input: TaskRunAllSummaryLogInput<'_>) -> Result<(), String> {
@@ -278,6 +445,45 @@ pub fn log_task_run_all_summary(input: TaskRunAllSummaryLogInput<'_>) -> Result<
     finalize_and_append_run(&run_log, row)
 }
 
+/// One row per suggestion picked in `next --run`, attributed to the `next` run.
+pub fn log_next_exec(parent_id: &str, index: usize, decision: &FixDecision) -> Result<(), String> {
+    let run_log = resolve_log_file().ok_or_else(|| "unable to resolve run log file".to_string())?;
+    let (cwd, root, scope) = cwd_scope_root();
+    let mut row = base_run_row("cxnext_exec", cwd, scope, root);
+    row.execution_id = format!("{parent_id}_exec{index}");
+    row.parent_execution_id = Some(parent_id.to_string());
+    row.command_label = Some("next_exec".to_string());
+    row.duration_ms = decision.duration_ms;
+    row.prompt_sha256 = Some(sha256_hex(&decision.command));
+    row.prompt_preview = Some(prompt_preview(&decision.command, 180));
+    let blocked = decision.decision == "blocked";
+    row.policy_blocked = Some(blocked);
+    row.policy_reason = decision.rule.clone().filter(|_| blocked);
+    row.fix_decisions = Some(serde_json::Value::Array(vec![decision.to_json()]));
+    finalize_and_append_run(&run_log, row)
+}
+
+/// Non-LLM row for `pack`: bundle fingerprint plus the per-file manifest.
+pub fn log_pack_run(
+    bundle: &str,
+    manifest: &serde_json::Value,
+    duration_ms: u64,
+) -> Result<(), String> {
+    let run_log = resolve_log_file().ok_or_else(|| "unable to resolve run log file".to_string())?;
+    let (cwd, root, scope) = cwd_scope_root();
+    let mut row = base_run_row("cxpack", cwd, scope, root);
+    row.duration_ms = Some(duration_ms);
+    row.command_label = Some("pack".to_string());
+    row.prompt_sha256 = Some(sha256_hex(bundle));
+    row.prompt_len_raw = Some(bundle.chars().count() as u64);
+    let tokenizer = TokenizerKind::for_backend(&row.llm_backend);
+    row.prompt_tokens_est = Some(estimate_tokens(bundle, tokenizer) as u64);
+    row.token_estimator = Some(tokenizer.as_str().to_string());
+    row.prompt_preview = Some(prompt_preview(bundle, 180));
+    row.pack_manifest = Some(manifest.clone());
+    finalize_and_append_run(&run_log, row)
+}
+
 pub fn log_schema_failure(
     tool: &str,
     reason: &str,
@@ -303,7 +509,7 @@ pub fn log_schema_failure(
     let (cwd, root, scope) = cwd_scope_root();
     let mut row = base_run_row(tool, cwd, scope, root);
     row.schema_enforced = true;
-    row.schema_name = schema_name_for_tool(tool).map(|s| s.to_string());
+    row.schema_name = schema_name_for_tool(tool);
     row.schema_valid = false;
     row.schema_ok = false;
     row.schema_reason = Some(reason.to_string());
@@ -1,5 +1,11 @@
+use std::collections::BTreeSet;
 use std::env;
 
+use crate::alert_sinks::{base_alert_thresholds, configured_alert_sinks, load_alert_policy};
+use crate::capture::{REDUCERS, reducer_override_env};
+use crate::config::app_config;
+use crate::config_layers::config_env;
+
 pub fn cmd_log_off() -> i32 {
     println!("cx logging: OFF (process-local)");
     0
@@ -11,15 +17,32 @@ pub fn cmd_log_on() -> i32 {
 }
 
 pub fn cmd_alert_show() -> i32 {
-    let enabled = env::var("CXALERT_ENABLED").unwrap_or_else(|_| "1".to_string());
-    let max_ms = env::var("CXALERT_MAX_MS").unwrap_or_else(|_| "8000".to_string());
-    let max_eff = env::var("CXALERT_MAX_EFF_IN").unwrap_or_else(|_| "5000".to_string());
-    let max_out = env::var("CXALERT_MAX_OUT").unwrap_or_else(|_| "500".to_string());
+    let enabled = config_env("CXALERT_ENABLED").unwrap_or_else(|| "1".to_string());
+    let base = base_alert_thresholds();
+    let max_out = config_env("CXALERT_MAX_OUT").unwrap_or_else(|| "500".to_string());
     println!("cx alerts:");
     println!("enabled={enabled}");
-    println!("max_ms={max_ms}");
-    println!("max_eff_in={max_eff}");
+    println!("max_ms={}", base.max_ms);
+    println!("max_eff_in={}", base.max_eff_in);
     println!("max_out={max_out}");
+    match load_alert_policy() {
+        Ok(policy) if policy.overrides.is_empty() => println!("overrides=none"),
+        Ok(policy) => {
+            println!("overrides:");
+            for (tool, t) in &policy.overrides {
+                println!("  {tool}: max_ms={} max_eff_in={}", t.max_ms, t.max_eff_in);
+            }
+        }
+        Err(e) => println!("overrides=invalid ({e})"),
+    }
+    match configured_alert_sinks() {
+        Ok(sinks) if sinks.is_empty() => println!("sinks=none"),
+        Ok(sinks) => {
+            let labels: Vec<String> = sinks.iter().map(|s| s.label()).collect();
+            println!("sinks={}", labels.join(", "));
+        }
+        Err(e) => println!("sinks=invalid ({e})"),
+    }
     0
 }
 
@@ -37,10 +60,33 @@ pub fn cmd_capture_status() -> i32 {
     let provider = env::var("CX_CAPTURE_PROVIDER").unwrap_or_else(|_| "native".to_string());
     let native_reduce = env::var("CX_NATIVE_REDUCE").unwrap_or_else(|_| "1".to_string());
     let prefer_native = env::var("CX_CAPTURE_PREFER_NATIVE").unwrap_or_else(|_| "1".to_string());
-    println!("capture_provider: native");
+    println!("capture_provider: {}", app_config().capture_provider);
     println!("capture_provider_config: {provider}");
     println!("native_reduce: {native_reduce}");
     println!("capture_prefer_native: {prefer_native}");
     println!("external_capture_dependencies: none");
+    println!("reducers:");
+    for r in REDUCERS {
+        let prefixes: Vec<String> = r.prefixes.iter().map(|p| p.join(" ")).collect();
+        let overrides: Vec<String> = r
+            .prefixes
+            .iter()
+            .filter_map(|p| p.first())
+            .map(|c| reducer_override_env(c))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|k| env::var(&k).ok().map(|v| format!("{k}={v}")))
+            .collect();
+        if overrides.is_empty() {
+            println!("- {}: {}", r.name, prefixes.join(", "));
+        } else {
+            println!(
+                "- {}: {} ({})",
+                r.name,
+                prefixes.join(", "),
+                overrides.join(", ")
+            );
+        }
+    }
     0
 }
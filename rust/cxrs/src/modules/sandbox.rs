@@ -0,0 +1,214 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::app_config;
+use crate::config_layers::set_by_repo_config;
+use crate::platform::{bin_in_path, shell_command};
+
+/// Where `fix-run`/`next --run` execute approved suggestions (`CX_SANDBOX`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandboxMode {
+    None,
+    Container,
+    Nsjail,
+}
+
+impl SandboxMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "" | "none" | "off" => Some(Self::None),
+            "container" => Some(Self::Container),
+            "nsjail" => Some(Self::Nsjail),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Container => "container",
+            Self::Nsjail => "nsjail",
+        }
+    }
+}
+
+/// Writable mounts for the sandbox: `CX_SANDBOX_WRITABLE` entries resolved
+/// against the repo root and canonicalized, so `..` and symlinks cannot reach
+/// past it. Entries outside the repo are only accepted from a trusted source
+/// (env or global config), and never the filesystem root or an ancestor of the
+/// repo, which would undo its read-only mount. Entries that do not exist are
+/// skipped, since neither runtime can bind-mount a missing path.
+fn writable_paths(root: &Path, raw: &[String], trusted: bool) -> Result<Vec<PathBuf>, String> {
+    let canon_root = root
+        .canonicalize()
+        .map_err(|e| format!("cannot resolve repo root {}: {e}", root.display()))?;
+    let mut out = Vec::new();
+    for entry in raw.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let Ok(path) = root.join(entry).canonicalize() else {
+            continue;
+        };
+        if canon_root.starts_with(&path) {
+            return Err(format!(
+                "CX_SANDBOX_WRITABLE entry '{entry}' would make the whole repo writable"
+            ));
+        }
+        match path.strip_prefix(&canon_root) {
+            Ok(rel) => out.push(root.join(rel)),
+            Err(_) if trusted && Path::new(entry).is_absolute() => out.push(path),
+            Err(_) => {
+                return Err(format!(
+                    "CX_SANDBOX_WRITABLE entry '{entry}' resolves outside the repo ({})",
+                    path.display()
+                ));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Argument vector that runs `script` under `sh -c` in the sandbox: the repo is
+/// mounted read-only at its own path, `writable` paths read-write, with no
+/// network. `runtime` is `docker`/`podman` for containers, `nsjail` otherwise.
+fn sandbox_argv(
+    mode: SandboxMode,
+    runtime: &str,
+    image: &str,
+    root: &Path,
+    writable: &[PathBuf],
+    script: &str,
+) -> Vec<String> {
+    let root_s = root.display().to_string();
+    let mut argv = vec![runtime.to_string()];
+    let shell = match mode {
+        SandboxMode::None => return vec!["sh".into(), "-c".into(), script.to_string()],
+        SandboxMode::Container => {
+            argv.extend(["run", "--rm", "--network", "none"].map(String::from));
+            argv.extend(["-v".to_string(), format!("{root_s}:{root_s}:ro")]);
+            for w in writable {
+                let w = w.display();
+                argv.extend(["-v".to_string(), format!("{w}:{w}:rw")]);
+            }
+            argv.extend(["-w".to_string(), root_s, image.to_string()]);
+            "sh"
+        }
+        SandboxMode::Nsjail => {
+            argv.extend(
+                ["-Mo", "--quiet", "--disable_rlimits", "--time_limit", "0"].map(String::from),
+            );
+            argv.extend(["-R", "/", "-T", "/tmp", "-B", "/dev/null"].map(String::from));
+            for w in writable {
+                argv.extend(["-B".to_string(), w.display().to_string()]);
+            }
+            argv.extend(["--cwd".to_string(), root_s, "--".to_string()]);
+            "/bin/sh"
+        }
+    };
+    argv.extend([shell.to_string(), "-c".to_string(), script.to_string()]);
+    argv
+}
+
+fn container_runtime() -> Option<&'static str> {
+    ["docker", "podman"].into_iter().find(|b| bin_in_path(b))
+}
+
+/// Build the command that runs `script` under the configured sandbox, with a
+/// label for the run log (`none`, `container:docker`, `nsjail`). Fails closed
+/// when the mode is unknown or its runtime is not installed.
+pub fn sandboxed_command(script: &str, root: &Path) -> Result<(Command, String), String> {
+    let cfg = app_config();
+    let mode = SandboxMode::parse(&cfg.sandbox).ok_or_else(|| {
+        format!(
+            "unknown CX_SANDBOX={} (expected none, container or nsjail)",
+            cfg.sandbox
+        )
+    })?;
+    let runtime = match mode {
+        SandboxMode::None => return Ok((shell_command(script), "none".to_string())),
+        SandboxMode::Container => container_runtime()
+            .ok_or("CX_SANDBOX=container but neither docker nor podman is on PATH")?,
+        SandboxMode::Nsjail if bin_in_path("nsjail") => "nsjail",
+        SandboxMode::Nsjail => return Err("CX_SANDBOX=nsjail but nsjail is not on PATH".into()),
+    };
+    let trusted = !set_by_repo_config("CX_SANDBOX_WRITABLE");
+    let writable = writable_paths(root, &cfg.sandbox_writable, trusted)?;
+    let argv = sandbox_argv(mode, runtime, &cfg.sandbox_image, root, &writable, script);
+    let mut cmd = Command::new(&argv[0]);
+    cmd.args(&argv[1..]);
+    let label = match mode {
+        SandboxMode::Container => format!("container:{runtime}"),
+        _ => mode.as_str().to_string(),
+    };
+    Ok((cmd, label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn container_mounts_repo_ro_and_allowlist_rw() {
+        let root = Path::new("/work/repo");
+        let argv = sandbox_argv(
+            SandboxMode::Container,
+            "podman",
+            "alpine:3",
+            root,
+            &[root.join("target")],
+            "cargo build",
+        );
+        assert_eq!(
+            argv.join(" "),
+            "podman run --rm --network none -v /work/repo:/work/repo:ro \
+             -v /work/repo/target:/work/repo/target:rw -w /work/repo alpine:3 sh -c cargo build"
+        );
+    }
+
+    #[test]
+    fn nsjail_binds_root_read_only_and_runs_in_repo() {
+        let root = Path::new("/work/repo");
+        let argv = sandbox_argv(SandboxMode::Nsjail, "nsjail", "", root, &[], "ls");
+        let joined = argv.join(" ");
+        assert!(joined.starts_with("nsjail -Mo --quiet"), "{joined}");
+        assert!(joined.contains("-R / "), "{joined}");
+        assert!(
+            joined.ends_with("--cwd /work/repo -- /bin/sh -c ls"),
+            "{joined}"
+        );
+    }
+
+    #[test]
+    fn modes_parse_and_writable_paths_skip_missing() {
+        assert_eq!(
+            SandboxMode::parse("Container"),
+            Some(SandboxMode::Container)
+        );
+        assert_eq!(SandboxMode::parse(""), Some(SandboxMode::None));
+        assert_eq!(SandboxMode::parse("vm"), None);
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(dir.path().join("out")).expect("mkdir");
+        let raw = ["out".to_string(), "missing".to_string()];
+        let paths = writable_paths(dir.path(), &raw, false).expect("paths");
+        assert_eq!(paths, vec![dir.path().join("out")]);
+    }
+
+    #[test]
+    fn writable_paths_cannot_escape_the_repo() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path().join("repo");
+        std::fs::create_dir_all(root.join("out")).expect("mkdir");
+        std::fs::create_dir(dir.path().join("cache")).expect("mkdir");
+        let cache = dir.path().join("cache").canonicalize().expect("canon");
+        let cache_abs = cache.display().to_string();
+        for entry in ["/", "..", ".", "out/../.."] {
+            let err = writable_paths(&root, &[entry.to_string()], true).unwrap_err();
+            assert!(err.contains("whole repo writable"), "{entry}: {err}");
+        }
+        let err = writable_paths(&root, &["../cache".to_string()], true).unwrap_err();
+        assert!(err.contains("resolves outside the repo"), "{err}");
+        assert!(writable_paths(&root, std::slice::from_ref(&cache_abs), false).is_err());
+        assert_eq!(
+            writable_paths(&root, &[cache_abs, "out/".to_string()], true).expect("paths"),
+            vec![cache, root.join("out")]
+        );
+    }
+}
@@ -1,11 +1,12 @@
 use jsonschema::JSONSchema;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::sync::{Arc, Mutex};
 
 use crate::config::app_config;
-use crate::paths::resolve_schema_dir;
+use crate::custom_cmds::custom_schema_for_tool;
+use crate::paths::{resolve_schema_dir, resolve_schema_registry_file};
 use crate::types::{LoadedSchema, SCHEMA_COMPILED_CACHE};
 use crate::util::sha256_hex;
 
@@ -15,7 +16,7 @@ pub struct SchemaPromptEnvelope {
     pub prompt_sha256: String,
 }
 
-fn normalize_schema_name(name: &str) -> String {
+pub fn normalize_schema_name(name: &str) -> String {
     if name.ends_with(".schema.json") {
         name.to_string()
     } else {
@@ -81,7 +82,9 @@ pub fn list_schemas() -> Result<Vec<LoadedSchema>, String> {
     Ok(out)
 }
 
-pub fn schema_name_for_tool(tool: &str) -> Option<&'static str> {
+/// Built-in tool -> schema mapping; `.codex/schemas/registry.json` entries
+/// take precedence.
+fn builtin_schema_for_tool(tool: &str) -> Option<&'static str> {
     match tool {
         "cxrs_commitjson" | "cxcommitjson" | "commitjson" | "cxrs_commitmsg" | "cxcommitmsg"
         | "commitmsg" => Some("commitjson"),
@@ -93,10 +96,64 @@ pub fn schema_name_for_tool(tool: &str) -> Option<&'static str> {
         | "diffsum-staged" => Some("diffsum"),
         "cxrs_next" | "cxnext" | "next" => Some("next"),
         "cxrs_fix_run" | "cxfix_run" | "fix-run" => Some("fixrun"),
+        "cxrs_explain" | "explain" => Some("explain"),
+        "cxrs_fix" => Some("fix"),
+        "cxrs_stage" | "stage" => Some("stage"),
+        "cxrs_fanout" => Some("fanout"),
         _ => None,
     }
 }
 
+/// Tool -> schema file mappings from the registry file
+/// (`{"tools": {"<tool>": "<name>.schema.json"}}`); empty when it is absent.
+pub fn load_tool_schema_map() -> Result<BTreeMap<String, String>, String> {
+    let Some(path) = resolve_schema_registry_file() else {
+        return Ok(BTreeMap::new());
+    };
+    if !path.is_file() {
+        return Ok(BTreeMap::new());
+    }
+    let raw =
+        fs::read_to_string(&path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let value: Value = serde_json::from_str(&raw)
+        .map_err(|e| format!("invalid registry JSON {}: {e}", path.display()))?;
+    let Some(tools) = value.get("tools").and_then(Value::as_object) else {
+        return Err(format!(
+            "{}: expected an object at \"tools\"",
+            path.display()
+        ));
+    };
+    let mut out = BTreeMap::new();
+    for (tool, schema) in tools {
+        let schema = schema
+            .as_str()
+            .ok_or_else(|| format!("{}: tools.{tool} must be a schema name", path.display()))?;
+        out.insert(tool.clone(), normalize_schema_name(schema));
+    }
+    Ok(out)
+}
+
+/// Schema file name (`<name>.schema.json`) enforced for `tool`: registry
+/// entry, then a `custom` command definition, then the built-in mapping. An
+/// unreadable registry is skipped; `schema lint` reports it.
+pub fn schema_name_for_tool(tool: &str) -> Option<String> {
+    if let Some(name) = load_tool_schema_map().ok().and_then(|mut m| m.remove(tool)) {
+        return Some(name);
+    }
+    custom_schema_for_tool(tool)
+        .or_else(|| builtin_schema_for_tool(tool).map(normalize_schema_name))
+}
+
+pub fn is_registry_mapped_tool(tool: &str) -> bool {
+    load_tool_schema_map().is_ok_and(|m| m.contains_key(tool))
+}
+
+pub fn load_schema_for_tool(tool: &str) -> Result<LoadedSchema, String> {
+    let name =
+        schema_name_for_tool(tool).ok_or_else(|| format!("no schema mapped for tool '{tool}'"))?;
+    load_schema(&name)
+}
+
 pub fn build_strict_schema_prompt(schema: &str, task_input: &str) -> String {
     if app_config().schema_relaxed {
         return format!(
@@ -156,3 +213,83 @@ pub fn validate_schema_instance(schema: &LoadedSchema, raw: &str) -> Result<Valu
     }
     Ok(instance)
 }
+
+/// Byte length of the bracketed JSON value at the start of `s`, or `None`
+/// when it never closes (a truncated response).
+pub fn json_value_end(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_str = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if in_str {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_str = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_str = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Strip a markdown fence and any prose around the first balanced JSON value.
+/// Returns the candidate text and the steps taken, or `None` when there is
+/// nothing to repair.
+pub fn repair_json_text(raw: &str) -> Option<(String, Vec<&'static str>)> {
+    let mut steps = Vec::new();
+    let mut text = raw.trim();
+    if let Some(rest) = text.strip_prefix("```") {
+        let body = rest.split_once('\n').map_or("", |(_, b)| b);
+        text = body.rfind("```").map_or(body, |end| &body[..end]).trim();
+        steps.push("strip_code_fence");
+    }
+    let start = text.find(['{', '['])?;
+    if start > 0 {
+        steps.push("strip_leading_text");
+    }
+    let end = start + json_value_end(&text[start..])?;
+    if !text[end..].trim().is_empty() {
+        steps.push("strip_trailing_text");
+    }
+    if steps.is_empty() {
+        return None;
+    }
+    Some((text[start..end].to_string(), steps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repair_strips_fences_and_surrounding_prose() {
+        let fenced = "```json\n{\"a\": \"}\"}\n```";
+        assert_eq!(
+            repair_json_text(fenced),
+            Some(("{\"a\": \"}\"}".to_string(), vec!["strip_code_fence"]))
+        );
+        assert_eq!(
+            repair_json_text("Sure:\n[1, 2]\nHope this helps!"),
+            Some((
+                "[1, 2]".to_string(),
+                vec!["strip_leading_text", "strip_trailing_text"]
+            ))
+        );
+        assert_eq!(repair_json_text("{\"a\": 1}"), None);
+        assert_eq!(repair_json_text("{\"a\": [1,"), None);
+        assert_eq!(repair_json_text("no json here"), None);
+    }
+}
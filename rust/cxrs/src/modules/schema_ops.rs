@@ -1,18 +1,40 @@
 use jsonschema::JSONSchema;
 use serde_json::{Value, json};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::capture::budget_config_from_env;
 use crate::logs::validate_runs_jsonl_file;
-use crate::paths::{repo_root, resolve_log_file, resolve_schema_dir};
+use crate::paths::{
+    repo_root, resolve_log_file, resolve_quarantine_dir, resolve_schema_dir,
+    resolve_schema_fail_log_file,
+};
+use crate::quarantine_audit::audit_quarantine;
 use crate::schema::list_schemas;
+use crate::schema_registry::{
+    cmd_schema_add, cmd_schema_diff, cmd_schema_lint, cmd_schema_map, cmd_schema_show,
+    cmd_schema_validate,
+};
+use crate::state::write_json_atomic;
 
 pub fn cmd_schema(app_name: &str, args: &[String]) -> i32 {
     let sub = args.first().map(String::as_str).unwrap_or("list");
-    if sub != "list" {
-        crate::cx_eprintln!("Usage: {app_name} schema list [--json]");
-        return 2;
+    let rest = args.get(1..).unwrap_or(&[]);
+    match sub {
+        "list" => {}
+        "show" => return cmd_schema_show(rest),
+        "validate" => return cmd_schema_validate(rest),
+        "add" => return cmd_schema_add(rest),
+        "lint" => return cmd_schema_lint(rest),
+        "diff" => return cmd_schema_diff(rest),
+        "map" => return cmd_schema_map(rest),
+        _ => {
+            crate::cx_eprintln!(
+                "Usage: {app_name} schema list [--json] | show <name> | validate <name> [file] | add <name> --from <file> | lint | diff <a> <b> | map [<tool> <schema>]"
+            );
+            return 2;
+        }
     }
     let as_json = args.iter().any(|a| a == "--json");
     let Some(dir) = resolve_schema_dir() else {
@@ -62,47 +84,87 @@ struct CiArgs {
     strict: bool,
     legacy_ok: bool,
     json_out: bool,
+    report: Option<PathBuf>,
 }
 
 fn parse_ci_args(app_name: &str, args: &[String]) -> Result<CiArgs, i32> {
+    let usage = || {
+        crate::cx_eprintln!(
+            "Usage: {app_name} ci validate [--strict] [--legacy-ok] [--json] [--report FILE]"
+        );
+        2
+    };
     let sub = args.first().map(String::as_str).unwrap_or("validate");
     if sub != "validate" {
-        crate::cx_eprintln!("Usage: {app_name} ci validate [--strict] [--legacy-ok] [--json]");
-        return Err(2);
+        return Err(usage());
+    }
+    let mut report = None;
+    let mut it = args.iter().skip(1);
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--strict" | "--legacy-ok" | "--json" => {}
+            "--report" => match it.next() {
+                Some(p) => report = Some(PathBuf::from(p)),
+                None => return Err(usage()),
+            },
+            _ => return Err(usage()),
+        }
     }
     Ok(CiArgs {
         strict: args.iter().any(|a| a == "--strict"),
         legacy_ok: args.iter().any(|a| a == "--legacy-ok") || !args.iter().any(|a| a == "--strict"),
         json_out: args.iter().any(|a| a == "--json"),
+        report,
     })
 }
 
-fn validate_schema_file(path: &Path, errors: &mut Vec<String>) {
+/// Errors and warnings plus per-kind counts for the machine-readable report.
+#[derive(Default)]
+struct CiFindings {
+    errors: Vec<String>,
+    warnings: Vec<String>,
+    error_counts: BTreeMap<&'static str, usize>,
+    warning_counts: BTreeMap<&'static str, usize>,
+}
+
+impl CiFindings {
+    fn error(&mut self, kind: &'static str, msg: String) {
+        *self.error_counts.entry(kind).or_default() += 1;
+        self.errors.push(msg);
+    }
+
+    fn warn(&mut self, kind: &'static str, msg: String) {
+        *self.warning_counts.entry(kind).or_default() += 1;
+        self.warnings.push(msg);
+    }
+}
+
+fn validate_schema_file(path: &Path, findings: &mut CiFindings) {
     let parsed = fs::read_to_string(path)
         .ok()
         .and_then(|s| serde_json::from_str::<Value>(&s).ok());
     match parsed {
         Some(v) => {
             if let Err(e) = JSONSchema::compile(&v) {
-                errors.push(format!(
-                    "schema failed to compile ({}): {e}",
-                    path.display()
-                ));
+                findings.error(
+                    "schema_registry",
+                    format!("schema failed to compile ({}): {e}", path.display()),
+                );
             }
         }
-        None => errors.push(format!(
-            "schema unreadable/invalid json: {}",
-            path.display()
-        )),
+        None => findings.error(
+            "schema_registry",
+            format!("schema unreadable/invalid json: {}", path.display()),
+        ),
     }
 }
 
-fn check_required_schemas(schema_dir: &Path, errors: &mut Vec<String>) {
+fn check_required_schemas(schema_dir: &Path, findings: &mut CiFindings) {
     if !schema_dir.is_dir() {
-        errors.push(format!(
-            "missing schema registry dir: {}",
-            schema_dir.display()
-        ));
+        findings.error(
+            "schema_registry",
+            format!("missing schema registry dir: {}", schema_dir.display()),
+        );
         return;
     }
     let required = [
@@ -110,105 +172,167 @@ fn check_required_schemas(schema_dir: &Path, errors: &mut Vec<String>) {
         "diffsum.schema.json",
         "next.schema.json",
         "fixrun.schema.json",
+        "explain.schema.json",
+        "fix.schema.json",
+        "fanout.schema.json",
+        "stage.schema.json",
     ];
     for name in required {
         let p = schema_dir.join(name);
         if !p.is_file() {
-            errors.push(format!("missing schema: {}", p.display()));
+            findings.error(
+                "schema_registry",
+                format!("missing schema: {}", p.display()),
+            );
         } else {
-            validate_schema_file(&p, errors);
+            validate_schema_file(&p, findings);
         }
     }
 }
 
-fn validate_logs(
-    legacy_ok: bool,
-    errors: &mut Vec<String>,
-    warnings: &mut Vec<String>,
-) -> Option<PathBuf> {
+/// Missing or non-compiling required schemas, for `health`.
+pub fn required_schema_problems(schema_dir: &Path) -> Vec<String> {
+    let mut findings = CiFindings::default();
+    check_required_schemas(schema_dir, &mut findings);
+    findings.errors
+}
+
+fn validate_logs(legacy_ok: bool, findings: &mut CiFindings) -> Option<PathBuf> {
     let Some(log_file) = resolve_log_file() else {
-        errors.push("unable to resolve log file".to_string());
+        findings.error("log_file", "unable to resolve log file".to_string());
         return None;
     };
     if !log_file.exists() {
-        warnings.push(format!("no log file at {}", log_file.display()));
+        findings.warn("log_file", format!("no log file at {}", log_file.display()));
         return Some(log_file);
     }
     match validate_runs_jsonl_file(&log_file, legacy_ok) {
         Ok(outcome) => {
-            if !outcome.issues.is_empty() {
-                if outcome.legacy_ok && outcome.invalid_json_lines == 0 {
-                    warnings.extend(outcome.issues);
+            let as_warnings = outcome.legacy_ok && outcome.invalid_json_lines == 0;
+            for issue in outcome.issues {
+                if as_warnings {
+                    findings.warn("log_contract", issue);
                 } else {
-                    errors.extend(outcome.issues);
+                    findings.error("log_contract", issue);
                 }
             }
         }
-        Err(e) => errors.push(e),
+        Err(e) => findings.error("log_file", e),
     }
     Some(log_file)
 }
 
-fn validate_budget(
-    errors: &mut Vec<String>,
-    warnings: &mut Vec<String>,
-) -> crate::capture::BudgetConfig {
+fn validate_budget(findings: &mut CiFindings) -> crate::capture::BudgetConfig {
     let budget = budget_config_from_env();
     if budget.budget_chars == 0 {
-        errors.push("budget_chars must be > 0".to_string());
+        findings.error("budget", "budget_chars must be > 0".to_string());
     }
     if budget.budget_lines == 0 {
-        errors.push("budget_lines must be > 0".to_string());
+        findings.error("budget", "budget_lines must be > 0".to_string());
     }
-    if !matches!(budget.clip_mode.as_str(), "smart" | "head" | "tail") {
-        warnings.push(format!(
-            "clip_mode '{}' not recognized; expected smart|head|tail",
-            budget.clip_mode
-        ));
+    if !matches!(
+        budget.clip_mode.as_str(),
+        "smart" | "head" | "tail" | "error-window"
+    ) {
+        findings.warn(
+            "budget",
+            format!(
+                "clip_mode '{}' not recognized; expected smart|head|tail|error-window",
+                budget.clip_mode
+            ),
+        );
+    }
+    if let Err(e) = regex::Regex::new(&budget.error_regex) {
+        findings.warn(
+            "budget",
+            format!("error_regex is invalid ({e}); error-window uses the default pattern"),
+        );
     }
     budget
 }
 
-struct CiJsonOutput<'a> {
+/// Quarantine records and their cross-references with the run and
+/// schema-failure logs; returns `(records, referenced_ids)`.
+fn validate_quarantine(
+    qdir: &Path,
+    log_file: Option<&Path>,
+    strict: bool,
+    findings: &mut CiFindings,
+) -> (usize, usize) {
+    if qdir.exists() && !qdir.is_dir() {
+        let msg = format!(
+            "quarantine path exists but is not a dir: {}",
+            qdir.display()
+        );
+        if strict {
+            findings.error("quarantine_dir", msg);
+        } else {
+            findings.warn("quarantine_dir", msg);
+        }
+        return (0, 0);
+    }
+    let schema_fail_log = resolve_schema_fail_log_file();
+    let logs: Vec<&Path> = log_file
+        .into_iter()
+        .chain(schema_fail_log.as_deref())
+        .collect();
+    let audit = audit_quarantine(qdir, &logs, strict);
+    for issue in audit.issues {
+        if issue.error {
+            findings.error(issue.kind, issue.message);
+        } else {
+            findings.warn(issue.kind, issue.message);
+        }
+    }
+    (audit.records, audit.referenced_ids)
+}
+
+struct CiReport<'a> {
     ok: bool,
     args: &'a CiArgs,
     root: &'a Path,
     schema_dir: &'a Path,
+    qdir: &'a Path,
+    quarantine: (usize, usize),
     log_file: Option<PathBuf>,
     budget: &'a crate::capture::BudgetConfig,
-    warnings: Vec<String>,
-    errors: Vec<String>,
+    findings: &'a CiFindings,
 }
 
-fn print_ci_json(out: CiJsonOutput<'_>) -> i32 {
-    let CiJsonOutput {
+fn ci_report_json(report: &CiReport<'_>) -> Value {
+    let CiReport {
         ok,
         args,
         root,
         schema_dir,
+        qdir,
+        quarantine,
         log_file,
         budget,
-        warnings,
-        errors,
-    } = out;
-    let v = json!({
+        findings,
+    } = report;
+    json!({
         "ok": ok,
         "strict": args.strict,
         "legacy_ok": args.legacy_ok,
         "repo_root": root.display().to_string(),
         "schema_dir": schema_dir.display().to_string(),
-        "log_file": log_file.map(|p| p.display().to_string()),
+        "log_file": log_file.as_ref().map(|p| p.display().to_string()),
         "budget_chars": budget.budget_chars,
         "budget_lines": budget.budget_lines,
         "clip_mode": budget.clip_mode,
-        "warnings": warnings,
-        "errors": errors
-    });
-    println!(
-        "{}",
-        serde_json::to_string_pretty(&v).unwrap_or_else(|_| v.to_string())
-    );
-    if ok { 0 } else { 1 }
+        "quarantine": {
+            "dir": qdir.display().to_string(),
+            "records": quarantine.0,
+            "referenced_ids": quarantine.1,
+        },
+        "counts": {
+            "errors": findings.error_counts,
+            "warnings": findings.warning_counts,
+        },
+        "warnings": findings.warnings,
+        "errors": findings.errors
+    })
 }
 
 fn print_sample(label: &str, items: &[String], max: usize) {
@@ -221,40 +345,47 @@ fn print_sample(label: &str, items: &[String], max: usize) {
     }
 }
 
-fn print_ci_text(
-    ok: bool,
-    root: &Path,
-    schema_dir: &Path,
-    budget: &crate::capture::BudgetConfig,
-    warnings: &[String],
-    errors: &[String],
-) -> i32 {
+fn counts_line(counts: &BTreeMap<&'static str, usize>) -> String {
+    counts
+        .iter()
+        .map(|(k, n)| format!("{k}={n}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn print_ci_text(report: &CiReport<'_>) -> i32 {
+    let findings = report.findings;
     println!("== cxrs ci validate ==");
-    println!("repo_root: {}", root.display());
-    println!("schema_dir: {}", schema_dir.display());
-    if let Some(p) = resolve_log_file() {
-        println!("log_file: {}", p.display());
-    } else {
-        println!("log_file: <unresolved>");
+    println!("repo_root: {}", report.root.display());
+    println!("schema_dir: {}", report.schema_dir.display());
+    match &report.log_file {
+        Some(p) => println!("log_file: {}", p.display()),
+        None => println!("log_file: <unresolved>"),
     }
     println!(
         "budget: chars={} lines={} mode={}",
-        budget.budget_chars, budget.budget_lines, budget.clip_mode
+        report.budget.budget_chars, report.budget.budget_lines, report.budget.clip_mode
+    );
+    println!(
+        "quarantine: records={} referenced_ids={}",
+        report.quarantine.0, report.quarantine.1
     );
 
-    if warnings.is_empty() {
+    if findings.warnings.is_empty() {
         println!("warnings: 0");
     } else {
-        print_sample("warnings", warnings, 10);
+        print_sample("warnings", &findings.warnings, 10);
+        println!("warning_counts: {}", counts_line(&findings.warning_counts));
     }
 
-    if errors.is_empty() {
+    if findings.errors.is_empty() {
         println!("status: ok");
         return 0;
     }
-    print_sample("errors", errors, 20);
+    print_sample("errors", &findings.errors, 20);
+    println!("error_counts: {}", counts_line(&findings.error_counts));
     println!("status: fail");
-    if ok { 0 } else { 1 }
+    if report.ok { 0 } else { 1 }
 }
 
 pub fn cmd_ci(app_name: &str, args: &[String]) -> i32 {
@@ -268,36 +399,39 @@ pub fn cmd_ci(app_name: &str, args: &[String]) -> i32 {
         return 2;
     };
 
-    let mut errors: Vec<String> = Vec::new();
-    let mut warnings: Vec<String> = Vec::new();
+    let mut findings = CiFindings::default();
     let schema_dir = root.join(".codex").join("schemas");
+    let qdir = resolve_quarantine_dir().unwrap_or_else(|| root.join(".codex").join("quarantine"));
 
-    check_required_schemas(&schema_dir, &mut errors);
-    let log_file = validate_logs(parsed.legacy_ok, &mut errors, &mut warnings);
-    let budget = validate_budget(&mut errors, &mut warnings);
+    check_required_schemas(&schema_dir, &mut findings);
+    let log_file = validate_logs(parsed.legacy_ok, &mut findings);
+    let budget = validate_budget(&mut findings);
+    let quarantine = validate_quarantine(&qdir, log_file.as_deref(), parsed.strict, &mut findings);
 
-    if parsed.strict {
-        let qdir = root.join(".codex").join("quarantine");
-        if qdir.exists() && !qdir.is_dir() {
-            errors.push(format!(
-                "quarantine path exists but is not a dir: {}",
-                qdir.display()
-            ));
-        }
+    let report = CiReport {
+        ok: findings.errors.is_empty(),
+        args: &parsed,
+        root: &root,
+        schema_dir: &schema_dir,
+        qdir: &qdir,
+        quarantine,
+        log_file,
+        budget: &budget,
+        findings: &findings,
+    };
+    let v = ci_report_json(&report);
+    if let Some(path) = &parsed.report
+        && let Err(e) = write_json_atomic(path, &v)
+    {
+        crate::cx_eprintln!("cxrs ci validate: {e}");
+        return 1;
     }
-
-    let ok = errors.is_empty();
     if parsed.json_out {
-        return print_ci_json(CiJsonOutput {
-            ok,
-            args: &parsed,
-            root: &root,
-            schema_dir: &schema_dir,
-            log_file,
-            budget: &budget,
-            warnings,
-            errors,
-        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&v).unwrap_or_else(|_| v.to_string())
+        );
+        return if report.ok { 0 } else { 1 };
     }
-    print_ci_text(ok, &root, &schema_dir, &budget, &warnings, &errors)
+    print_ci_text(&report)
 }
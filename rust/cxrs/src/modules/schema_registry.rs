@@ -0,0 +1,517 @@
+use jsonschema::JSONSchema;
+use serde_json::{Map, Value, json};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::error::{
+    EXIT_OK, EXIT_RUNTIME, EXIT_SCHEMA, format_error, print_runtime_error, print_usage_error,
+};
+use crate::paths::{resolve_schema_dir, resolve_schema_registry_file};
+use crate::schema::{
+    list_schemas, load_schema, load_tool_schema_map, normalize_schema_name, schema_name_for_tool,
+    validate_schema_instance,
+};
+use crate::state::write_json_atomic;
+
+const SHOW_USAGE: &str = "schema show <name>";
+const VALIDATE_USAGE: &str = "schema validate <name> [file.json|-]";
+const ADD_USAGE: &str = "schema add <name> --from <file> [--force]";
+const LINT_USAGE: &str = "schema lint [--json]";
+const DIFF_USAGE: &str = "schema diff <name|file> <name|file> [--json]";
+const MAP_USAGE: &str = "schema map [<tool> <schema>|<tool> --unset] [--json]";
+const REGISTRY_FILE: &str = "registry.json";
+const DEFAULT_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+const ID_PREFIX: &str = "cx://schemas/";
+
+fn stem(name: &str) -> &str {
+    name.strip_suffix(".schema.json").unwrap_or(name)
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+/// `$id` must be `cx://schemas/<stem>.v<N>`.
+fn id_matches(id: &str, stem: &str) -> bool {
+    id.strip_prefix(ID_PREFIX)
+        .and_then(|rest| rest.strip_prefix(stem))
+        .and_then(|rest| rest.strip_prefix(".v"))
+        .is_some_and(|v| !v.is_empty() && v.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Rewrites `$id` to the registry form, keeping a matching version, and fills
+/// in `$schema` and `title` when missing.
+fn normalize_schema(mut value: Value, stem: &str) -> Result<Value, String> {
+    let obj = value
+        .as_object_mut()
+        .ok_or("schema must be a JSON object")?;
+    let keep = obj
+        .get("$id")
+        .and_then(Value::as_str)
+        .is_some_and(|id| id_matches(id, stem));
+    if !keep {
+        obj.insert("$id".into(), json!(format!("{ID_PREFIX}{stem}.v1")));
+    }
+    obj.entry("$schema")
+        .or_insert_with(|| json!(DEFAULT_DIALECT));
+    obj.entry("title")
+        .or_insert_with(|| json!(format!("cx {stem}")));
+    Ok(value)
+}
+
+/// Metadata and compile problems for one registry schema.
+fn lint_schema(name: &str, value: &Value) -> Vec<String> {
+    let mut issues = Vec::new();
+    let stem = stem(name);
+    match value.get("$id").and_then(Value::as_str) {
+        None => issues.push("missing $id".to_string()),
+        Some(id) if !id_matches(id, stem) => {
+            issues.push(format!("$id '{id}' should be {ID_PREFIX}{stem}.v<N>"))
+        }
+        Some(_) => {}
+    }
+    for key in ["$schema", "title"] {
+        if value.get(key).and_then(Value::as_str).is_none() {
+            issues.push(format!("missing {key}"));
+        }
+    }
+    if value.get("type").and_then(Value::as_str) != Some("object") {
+        issues.push("top-level type should be \"object\"".to_string());
+    }
+    if let Err(e) = JSONSchema::compile(value) {
+        issues.push(format!("failed to compile: {e}"));
+    }
+    issues
+}
+
+fn read_instance(path: Option<&str>) -> Result<String, String> {
+    match path {
+        None | Some("-") => {
+            let mut buf = String::new();
+            io::stdin()
+                .read_to_string(&mut buf)
+                .map_err(|e| format!("failed to read stdin: {e}"))?;
+            Ok(buf)
+        }
+        Some(p) => fs::read_to_string(p).map_err(|e| format!("cannot read {p}: {e}")),
+    }
+}
+
+fn read_json_file(path: &Path) -> Result<Value, String> {
+    let raw =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    serde_json::from_str(&raw).map_err(|e| format!("invalid JSON {}: {e}", path.display()))
+}
+
+/// A diff operand is a file when the path exists, otherwise a registry name.
+fn load_operand(arg: &str) -> Result<Value, String> {
+    let path = Path::new(arg);
+    if path.is_file() {
+        return read_json_file(path);
+    }
+    load_schema(arg).map(|s| s.value)
+}
+
+pub fn cmd_schema_show(args: &[String]) -> i32 {
+    let [name] = args else {
+        return print_usage_error("schema show", SHOW_USAGE);
+    };
+    let schema = match load_schema(name) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("schema show", &e),
+    };
+    println!(
+        "# {} [{}]",
+        schema.path.display(),
+        schema.id.as_deref().unwrap_or("<no $id>")
+    );
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema.value).unwrap_or_else(|_| schema.value.to_string())
+    );
+    EXIT_OK
+}
+
+/// `schema validate <name> [file]`: exit 0 when the instance (file or stdin)
+/// matches, 4 on a schema mismatch, like a failed structured run.
+pub fn cmd_schema_validate(args: &[String]) -> i32 {
+    let (name, file) = match args {
+        [name] => (name, None),
+        [name, file] => (name, Some(file.as_str())),
+        _ => return print_usage_error("schema validate", VALIDATE_USAGE),
+    };
+    let schema = match load_schema(name) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("schema validate", &e),
+    };
+    let raw = match read_instance(file) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("schema validate", &e),
+    };
+    match validate_schema_instance(&schema, &raw) {
+        Ok(_) => {
+            println!("valid: {}", schema.name);
+            EXIT_OK
+        }
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("schema validate", &e));
+            EXIT_SCHEMA
+        }
+    }
+}
+
+pub fn cmd_schema_add(args: &[String]) -> i32 {
+    let mut name: Option<&str> = None;
+    let mut from: Option<&str> = None;
+    let mut force = false;
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--from" => match it.next() {
+                Some(v) => from = Some(v),
+                None => return print_usage_error("schema add", ADD_USAGE),
+            },
+            "--force" => force = true,
+            a if a.starts_with('-') || name.is_some() => {
+                return print_usage_error("schema add", ADD_USAGE);
+            }
+            a => name = Some(a),
+        }
+    }
+    let (Some(name), Some(from)) = (name, from) else {
+        return print_usage_error("schema add", ADD_USAGE);
+    };
+    let stem = stem(name);
+    if !valid_name(stem) {
+        crate::cx_eprintln!(
+            "{}",
+            format_error(
+                "schema add",
+                &format!("invalid name '{name}' (expected [a-z0-9_-]+)")
+            )
+        );
+        return print_usage_error("schema add", ADD_USAGE);
+    }
+    let Some(dir) = resolve_schema_dir() else {
+        return print_runtime_error("schema add", "unable to resolve schema dir");
+    };
+    let dest = dir.join(format!("{stem}.schema.json"));
+    if dest.exists() && !force {
+        return print_runtime_error(
+            "schema add",
+            &format!("{} already exists (use --force to replace)", dest.display()),
+        );
+    }
+    let value = match read_json_file(Path::new(from)).and_then(|v| normalize_schema(v, stem)) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("schema add", &e),
+    };
+    if let Err(e) = JSONSchema::compile(&value) {
+        return print_runtime_error("schema add", &format!("schema failed to compile: {e}"));
+    }
+    if let Err(e) = fs::create_dir_all(&dir) {
+        return print_runtime_error(
+            "schema add",
+            &format!("failed to create {}: {e}", dir.display()),
+        );
+    }
+    if let Err(e) = write_json_atomic(&dest, &value) {
+        return print_runtime_error("schema add", &e);
+    }
+    println!(
+        "added: {} [{}]",
+        dest.display(),
+        value["$id"].as_str().unwrap_or_default()
+    );
+    EXIT_OK
+}
+
+/// Tool mappings in `registry.json` must parse and point at existing schemas.
+fn lint_tool_map(dir: &Path) -> Vec<String> {
+    match load_tool_schema_map() {
+        Ok(map) => map
+            .iter()
+            .filter(|(_, schema)| !dir.join(schema).is_file())
+            .map(|(tool, schema)| format!("tools.{tool} -> missing schema {schema}"))
+            .collect(),
+        Err(e) => vec![e],
+    }
+}
+
+/// `schema lint`: every registry schema must compile and carry `$schema`,
+/// `title`, an object `type` and a `$id` of `cx://schemas/<name>.v<N>`;
+/// `registry.json` tool mappings must resolve.
+pub fn cmd_schema_lint(args: &[String]) -> i32 {
+    let json_out = match args {
+        [] => false,
+        [flag] if flag == "--json" => true,
+        _ => return print_usage_error("schema lint", LINT_USAGE),
+    };
+    let schemas = match list_schemas() {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("schema lint", &e),
+    };
+    let Some(dir) = resolve_schema_dir() else {
+        return print_runtime_error("schema lint", "unable to resolve schema dir");
+    };
+    let map_issues = lint_tool_map(&dir);
+    let results: Vec<(String, Vec<String>)> = schemas
+        .iter()
+        .map(|s| (s.name.clone(), lint_schema(&s.name, &s.value)))
+        .collect();
+    let failing = results.iter().filter(|(_, i)| !i.is_empty()).count();
+    if json_out {
+        let rows: Vec<Value> = results
+            .iter()
+            .map(|(name, issues)| json!({"name": name, "ok": issues.is_empty(), "issues": issues}))
+            .collect();
+        println!(
+            "{}",
+            json!({
+                "ok": failing == 0 && map_issues.is_empty(),
+                "schema_count": rows.len(),
+                "schemas": rows,
+                "registry_issues": map_issues,
+            })
+        );
+    } else {
+        for (name, issues) in &results {
+            if issues.is_empty() {
+                println!("ok    {name}");
+            }
+            for issue in issues {
+                println!("FAIL  {name}: {issue}");
+            }
+        }
+        for issue in &map_issues {
+            println!("FAIL  {REGISTRY_FILE}: {issue}");
+        }
+        println!("schemas: {} failing: {failing}", results.len());
+    }
+    if failing == 0 && map_issues.is_empty() {
+        EXIT_OK
+    } else {
+        EXIT_RUNTIME
+    }
+}
+
+fn required_set(v: &Value) -> BTreeSet<String> {
+    v.get("required")
+        .and_then(Value::as_array)
+        .map(|a| {
+            a.iter()
+                .filter_map(Value::as_str)
+                .map(ToOwned::to_owned)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn properties(v: &Value) -> Map<String, Value> {
+    v.get("properties")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Top-level structural differences: `$id`, required fields and properties.
+fn diff_schemas(a: &Value, b: &Value) -> Value {
+    let (ra, rb) = (required_set(a), required_set(b));
+    let (pa, pb) = (properties(a), properties(b));
+    let added: Vec<&String> = pb.keys().filter(|k| !pa.contains_key(*k)).collect();
+    let removed: Vec<&String> = pa.keys().filter(|k| !pb.contains_key(*k)).collect();
+    let changed: Vec<&String> = pa
+        .iter()
+        .filter(|(k, v)| pb.get(*k).is_some_and(|other| other != *v))
+        .map(|(k, _)| k)
+        .collect();
+    json!({
+        "id": {"a": a.get("$id"), "b": b.get("$id")},
+        "required_added": rb.difference(&ra).collect::<Vec<_>>(),
+        "required_removed": ra.difference(&rb).collect::<Vec<_>>(),
+        "properties_added": added,
+        "properties_removed": removed,
+        "properties_changed": changed,
+        "additional_properties": {
+            "a": a.get("additionalProperties"),
+            "b": b.get("additionalProperties"),
+        },
+        "identical": a == b,
+    })
+}
+
+fn print_diff_list(label: &str, v: &Value) {
+    let items: Vec<&str> = v
+        .as_array()
+        .map(|a| a.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    if !items.is_empty() {
+        println!("{label}: {}", items.join(", "));
+    }
+}
+
+pub fn cmd_schema_diff(args: &[String]) -> i32 {
+    let json_out = args.iter().any(|a| a == "--json");
+    let operands: Vec<&String> = args.iter().filter(|a| *a != "--json").collect();
+    let [a_arg, b_arg] = operands.as_slice() else {
+        return print_usage_error("schema diff", DIFF_USAGE);
+    };
+    let (a, b) = match (load_operand(a_arg), load_operand(b_arg)) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(e), _) | (_, Err(e)) => return print_runtime_error("schema diff", &e),
+    };
+    let diff = diff_schemas(&a, &b);
+    if json_out {
+        println!("{diff}");
+        return EXIT_OK;
+    }
+    println!("== cxrs schema diff {a_arg} -> {b_arg} ==");
+    if diff["identical"] == true {
+        println!("identical");
+        return EXIT_OK;
+    }
+    if diff["id"]["a"] != diff["id"]["b"] {
+        println!("$id: {} -> {}", diff["id"]["a"], diff["id"]["b"]);
+    }
+    print_diff_list("required added", &diff["required_added"]);
+    print_diff_list("required removed", &diff["required_removed"]);
+    print_diff_list("properties added", &diff["properties_added"]);
+    print_diff_list("properties removed", &diff["properties_removed"]);
+    print_diff_list("properties changed", &diff["properties_changed"]);
+    let ap = &diff["additional_properties"];
+    if ap["a"] != ap["b"] {
+        println!("additionalProperties: {} -> {}", ap["a"], ap["b"]);
+    }
+    EXIT_OK
+}
+
+fn print_tool_map(json_out: bool) -> i32 {
+    let map = match load_tool_schema_map() {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("schema map", &e),
+    };
+    if json_out {
+        println!("{}", json!({ "tools": map }));
+        return EXIT_OK;
+    }
+    if map.is_empty() {
+        println!("no registry mappings (built-in tools use their default schemas)");
+    }
+    for (tool, schema) in &map {
+        println!("{tool} -> {schema}");
+    }
+    EXIT_OK
+}
+
+/// Read-modify-write of `registry.json`, keeping any unrelated top-level keys.
+fn update_tool_map(tool: &str, schema: Option<&str>) -> Result<(), String> {
+    let path =
+        resolve_schema_registry_file().ok_or_else(|| "unable to resolve schema dir".to_string())?;
+    let mut doc = if path.is_file() {
+        read_json_file(&path)?
+    } else {
+        json!({})
+    };
+    let obj = doc
+        .as_object_mut()
+        .ok_or_else(|| format!("{}: expected a JSON object", path.display()))?;
+    let tools = obj
+        .entry("tools")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or_else(|| format!("{}: expected an object at \"tools\"", path.display()))?;
+    match schema {
+        Some(s) => {
+            tools.insert(tool.to_string(), json!(s));
+        }
+        None => {
+            tools.remove(tool);
+        }
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    write_json_atomic(&path, &doc)
+}
+
+/// `schema map`: list registry tool mappings, or point `<tool>` at a
+/// registered schema (`--unset` restores the built-in default).
+pub fn cmd_schema_map(args: &[String]) -> i32 {
+    let json_out = args.iter().any(|a| a == "--json");
+    let rest: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|a| *a != "--json")
+        .collect();
+    match rest.as_slice() {
+        [] => print_tool_map(json_out),
+        [tool, "--unset"] => {
+            if let Err(e) = update_tool_map(tool, None) {
+                return print_runtime_error("schema map", &e);
+            }
+            let effective = schema_name_for_tool(tool).unwrap_or_else(|| "none".to_string());
+            println!("unmapped: {tool} (now {effective})");
+            EXIT_OK
+        }
+        [tool, schema] if !tool.starts_with('-') && !schema.starts_with('-') => {
+            let name = normalize_schema_name(schema);
+            if let Err(e) = load_schema(&name) {
+                return print_runtime_error("schema map", &e);
+            }
+            if let Err(e) = update_tool_map(tool, Some(&name)) {
+                return print_runtime_error("schema map", &e);
+            }
+            println!("mapped: {tool} -> {name}");
+            EXIT_OK
+        }
+        _ => print_usage_error("schema map", MAP_USAGE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_keeps_version_and_rewrites_foreign_ids() {
+        let v = normalize_schema(
+            json!({"$id": "cx://schemas/review.v3", "type": "object"}),
+            "review",
+        )
+        .expect("normalized");
+        assert_eq!(v["$id"], "cx://schemas/review.v3");
+        assert_eq!(v["$schema"], DEFAULT_DIALECT);
+        assert_eq!(v["title"], "cx review");
+
+        let v = normalize_schema(json!({"$id": "https://example.com/x"}), "review").expect("ok");
+        assert_eq!(v["$id"], "cx://schemas/review.v1");
+        assert!(normalize_schema(json!([1]), "review").is_err());
+    }
+
+    #[test]
+    fn lint_flags_missing_metadata_and_bad_ids() {
+        let good = json!({"$schema": DEFAULT_DIALECT, "$id": "cx://schemas/next.v1",
+                          "title": "cx next", "type": "object"});
+        assert!(lint_schema("next.schema.json", &good).is_empty());
+        let issues = lint_schema("next.schema.json", &json!({"$id": "cx://schemas/other.v1"}));
+        assert_eq!(issues.len(), 4, "{issues:?}");
+    }
+
+    #[test]
+    fn diff_reports_required_and_property_changes() {
+        let a = json!({"required": ["x"], "properties": {"x": {"type": "string"}, "y": {}}});
+        let b = json!({"required": ["x", "z"], "properties": {"x": {"type": "integer"}, "z": {}}});
+        let d = diff_schemas(&a, &b);
+        assert_eq!(d["required_added"], json!(["z"]));
+        assert_eq!(d["properties_added"], json!(["z"]));
+        assert_eq!(d["properties_removed"], json!(["y"]));
+        assert_eq!(d["properties_changed"], json!(["x"]));
+        assert_eq!(d["identical"], false);
+    }
+}
@@ -0,0 +1,396 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload, rand_core::RngCore};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::Utc;
+use serde_json::{Map, Value, json};
+use std::env;
+use std::fs;
+use std::io::{IsTerminal, Read};
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::{EXIT_OK, EXIT_RUNTIME, EXIT_USAGE, format_error, print_usage_error};
+use crate::paths::resolve_secrets_file;
+use crate::platform::{bin_in_path, os_name};
+use crate::process::{run_command_output_with_timeout, run_command_with_stdin_output_with_timeout};
+use crate::state::write_json_atomic;
+use crate::verbosity::warn;
+
+const SECRET_USAGE: &str = "secret <set <name> [value]|get <name>|rm <name>|list>";
+/// Prefix that makes a config/env value a reference into the secret store.
+const SECRET_REF_PREFIX: &str = "secret:";
+const PASSPHRASE_ENV: &str = "CX_SECRET_PASSPHRASE";
+const KEYCHAIN_SERVICE: &str = "cxrs-secrets";
+const KDF_ITERATIONS: u32 = 200_000;
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>, String> {
+    // Checked before slicing: a corrupted store or odd keychain output may hold
+    // multi-byte characters, and a slice through one would panic.
+    if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err("bad hex: non-hex character".to_string());
+    }
+    if !s.len().is_multiple_of(2) {
+        return Err("odd-length hex".to_string());
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|e| format!("bad hex: {e}")))
+        .collect()
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn passphrase() -> Option<String> {
+    env::var(PASSPHRASE_ENV).ok().filter(|v| !v.is_empty())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(passphrase.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+/// Key stored in the OS keychain (`security` on macOS, `secret-tool` elsewhere).
+fn keychain_lookup() -> Result<Option<[u8; 32]>, String> {
+    let mut cmd = if os_name() == "macos" {
+        let mut c = Command::new("security");
+        c.args(["find-generic-password", "-s", KEYCHAIN_SERVICE, "-w"]);
+        c
+    } else {
+        let mut c = Command::new("secret-tool");
+        c.args(["lookup", "service", KEYCHAIN_SERVICE]);
+        c
+    };
+    cmd.stdin(std::process::Stdio::null());
+    let out = run_command_output_with_timeout(cmd, "keychain lookup")?;
+    let hex = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    if !out.status.success() || hex.is_empty() {
+        return Ok(None);
+    }
+    let bytes = from_hex(&hex).map_err(|e| format!("keychain entry {KEYCHAIN_SERVICE}: {e}"))?;
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map(Some)
+        .map_err(|_| format!("keychain entry {KEYCHAIN_SERVICE} is not a 32-byte key"))
+}
+
+/// Command and stdin that store `hex` in the keychain. The key only travels on
+/// stdin, since argv is visible to every user through `ps`: macOS `security`
+/// reads the whole `add-generic-password` line in interactive mode (`-i`).
+fn keychain_store_command(hex: &str) -> (Command, String) {
+    if os_name() == "macos" {
+        let mut c = Command::new("security");
+        c.arg("-i");
+        let line = format!("add-generic-password -U -s {KEYCHAIN_SERVICE} -a cxrs -w {hex}\n");
+        (c, line)
+    } else {
+        let mut c = Command::new("secret-tool");
+        c.args([
+            "store",
+            "--label=cxrs secrets key",
+            "service",
+            KEYCHAIN_SERVICE,
+        ]);
+        (c, hex.to_string())
+    }
+}
+
+/// Store the key, then read it back: `security -i` reports a failed command on
+/// stderr but still exits 0.
+fn keychain_store(key: &[u8; 32]) -> Result<(), String> {
+    let (cmd, input) = keychain_store_command(&to_hex(key));
+    let out = run_command_with_stdin_output_with_timeout(cmd, &input, "keychain store")?;
+    if !out.status.success() || keychain_lookup()?.as_ref() != Some(key) {
+        return Err(format!(
+            "keychain store failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+fn keychain_available() -> bool {
+    bin_in_path(if os_name() == "macos" {
+        "security"
+    } else {
+        "secret-tool"
+    })
+}
+
+fn empty_store() -> Result<Value, String> {
+    if passphrase().is_some() {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        return Ok(json!({
+            "version": 1,
+            "key_source": "passphrase",
+            "kdf": {"algorithm": "pbkdf2-sha256", "salt": to_hex(&salt), "iterations": KDF_ITERATIONS},
+            "secrets": {},
+        }));
+    }
+    if keychain_available() {
+        return Ok(json!({"version": 1, "key_source": "keychain", "secrets": {}}));
+    }
+    Err(format!(
+        "no key source: set {PASSPHRASE_ENV} or install an OS keychain tool (secret-tool/security)"
+    ))
+}
+
+fn load_store(path: &Path) -> Result<Option<Value>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    serde_json::from_str::<Value>(&raw)
+        .map(Some)
+        .map_err(|e| format!("invalid JSON in {}: {e}", path.display()))
+}
+
+/// Encryption key for `store`; `create` lets a keychain store mint its key.
+fn store_key(store: &Value, create: bool) -> Result<[u8; 32], String> {
+    match store.get("key_source").and_then(Value::as_str) {
+        Some("passphrase") => {
+            let pass = passphrase().ok_or_else(|| {
+                format!("secret store is passphrase-protected; set {PASSPHRASE_ENV}")
+            })?;
+            let kdf = store.get("kdf").cloned().unwrap_or(Value::Null);
+            let salt = from_hex(kdf.get("salt").and_then(Value::as_str).unwrap_or_default())?;
+            let iterations = kdf
+                .get("iterations")
+                .and_then(Value::as_u64)
+                .and_then(|n| u32::try_from(n).ok())
+                .unwrap_or(KDF_ITERATIONS);
+            Ok(derive_key(&pass, &salt, iterations))
+        }
+        Some("keychain") => match keychain_lookup()? {
+            Some(key) => Ok(key),
+            None if create => {
+                let mut key = [0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                keychain_store(&key)?;
+                Ok(key)
+            }
+            None => Err(format!(
+                "keychain entry {KEYCHAIN_SERVICE} not found; secrets cannot be decrypted"
+            )),
+        },
+        other => Err(format!("unknown secret key_source: {other:?}")),
+    }
+}
+
+fn encrypt(key: &[u8; 32], name: &str, plaintext: &str) -> Result<Value, String> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext.as_bytes(),
+                aad: name.as_bytes(),
+            },
+        )
+        .map_err(|_| "encryption failed".to_string())?;
+    Ok(json!({
+        "nonce": to_hex(&nonce),
+        "ciphertext": to_hex(&ciphertext),
+        "updated_at": Utc::now().to_rfc3339(),
+    }))
+}
+
+fn decrypt(key: &[u8; 32], name: &str, entry: &Value) -> Result<String, String> {
+    let field = |k: &str| from_hex(entry.get(k).and_then(Value::as_str).unwrap_or_default());
+    let nonce = field("nonce")?;
+    if nonce.len() != 12 {
+        return Err(format!("secret '{name}' has a malformed nonce"));
+    }
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let plain = cipher
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &field("ciphertext")?,
+                aad: name.as_bytes(),
+            },
+        )
+        .map_err(|_| format!("cannot decrypt secret '{name}' (wrong passphrase or key?)"))?;
+    String::from_utf8(plain).map_err(|_| format!("secret '{name}' is not UTF-8"))
+}
+
+fn write_store(path: &Path, store: &Value) -> Result<(), String> {
+    write_json_atomic(path, store)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("chmod {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Decrypt one named secret from the store.
+pub fn read_secret(name: &str) -> Result<String, String> {
+    let path = resolve_secrets_file().ok_or_else(|| "cannot resolve $HOME".to_string())?;
+    let store = load_store(&path)?.ok_or_else(|| format!("secret '{name}' not found"))?;
+    let entry = store
+        .get("secrets")
+        .and_then(|s| s.get(name))
+        .ok_or_else(|| format!("secret '{name}' not found"))?;
+    decrypt(&store_key(&store, false)?, name, entry)
+}
+
+/// Resolve a `secret:<name>` reference; any other value is returned unchanged.
+pub fn resolve_secret_ref(value: &str) -> Result<String, String> {
+    match value.strip_prefix(SECRET_REF_PREFIX) {
+        Some(name) => read_secret(name.trim()),
+        None => Ok(value.to_string()),
+    }
+}
+
+fn secret_value(arg: Option<&String>) -> Result<String, String> {
+    if let Some(v) = arg {
+        warn(
+            "cxrs secret set: warning: a value on the command line is visible to `ps` and kept in shell history; omit it to read the value from stdin",
+        );
+        return Ok(v.clone());
+    }
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        crate::cx_eprintln!("Enter secret value, then Ctrl-D:");
+    }
+    let mut buf = String::new();
+    stdin
+        .read_to_string(&mut buf)
+        .map_err(|e| format!("cannot read value from stdin: {e}"))?;
+    let v = buf.trim_end_matches(['\r', '\n']).to_string();
+    if v.is_empty() {
+        return Err("empty secret value".to_string());
+    }
+    Ok(v)
+}
+
+fn secret_set(name: &str, value: Option<&String>) -> Result<String, String> {
+    let path = resolve_secrets_file().ok_or_else(|| "cannot resolve $HOME".to_string())?;
+    let value = secret_value(value)?;
+    let mut store = match load_store(&path)? {
+        Some(s) => s,
+        None => empty_store()?,
+    };
+    let key = store_key(&store, true)?;
+    // Refuse to mix keys: every existing entry must decrypt with this one.
+    if let Some((first, entry)) = store
+        .get("secrets")
+        .and_then(Value::as_object)
+        .and_then(|m| m.iter().next())
+    {
+        decrypt(&key, first, entry)?;
+    }
+    let entry = encrypt(&key, name, &value)?;
+    if !store.get("secrets").is_some_and(Value::is_object) {
+        store["secrets"] = Value::Object(Map::new());
+    }
+    store["secrets"][name] = entry;
+    write_store(&path, &store)?;
+    Ok(format!("stored secret '{name}' ({})", path.display()))
+}
+
+fn secret_rm(name: &str) -> Result<String, String> {
+    let path = resolve_secrets_file().ok_or_else(|| "cannot resolve $HOME".to_string())?;
+    let mut store = load_store(&path)?.ok_or_else(|| format!("secret '{name}' not found"))?;
+    let removed = store
+        .get_mut("secrets")
+        .and_then(Value::as_object_mut)
+        .and_then(|m| m.remove(name));
+    if removed.is_none() {
+        return Err(format!("secret '{name}' not found"));
+    }
+    write_store(&path, &store)?;
+    Ok(format!("removed secret '{name}'"))
+}
+
+fn secret_list() -> Result<Vec<String>, String> {
+    let Some(path) = resolve_secrets_file() else {
+        return Err("cannot resolve $HOME".to_string());
+    };
+    Ok(load_store(&path)?
+        .and_then(|s| s.get("secrets").and_then(Value::as_object).cloned())
+        .map(|m| m.keys().cloned().collect())
+        .unwrap_or_default())
+}
+
+pub fn cmd_secret(args: &[String]) -> i32 {
+    let op = args.first().map(String::as_str).unwrap_or("");
+    let name = args.get(1);
+    if let Some(n) = name
+        && op != "list"
+        && !valid_name(n)
+    {
+        crate::cx_eprintln!(
+            "{}",
+            format_error(
+                "secret",
+                &format!("invalid name '{n}' (use [A-Za-z0-9_.-])")
+            )
+        );
+        return EXIT_USAGE;
+    }
+    let res = match (op, name, args.len()) {
+        ("set", Some(n), 2 | 3) => secret_set(n, args.get(2)),
+        ("get", Some(n), 2) => read_secret(n),
+        ("rm", Some(n), 2) => secret_rm(n),
+        ("list", None, 1) => secret_list().map(|names| names.join("\n")),
+        _ => return print_usage_error("secret", SECRET_USAGE),
+    };
+    match res {
+        Ok(out) => {
+            if !out.is_empty() {
+                println!("{out}");
+            }
+            EXIT_OK
+        }
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error(&format!("secret {op}"), &e));
+            EXIT_RUNTIME
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ciphertext_is_bound_to_key_and_name() {
+        let key = derive_key("hunter2", b"0123456789abcdef", 1000);
+        let entry = encrypt(&key, "openai", "sk-test").unwrap();
+        assert!(!entry.to_string().contains("sk-test"));
+        assert_eq!(decrypt(&key, "openai", &entry).unwrap(), "sk-test");
+        assert!(decrypt(&key, "other", &entry).is_err());
+        let wrong = derive_key("hunter3", b"0123456789abcdef", 1000);
+        assert!(decrypt(&wrong, "openai", &entry).is_err());
+        assert_eq!(
+            from_hex(&to_hex(&[0, 171, 255])).unwrap(),
+            vec![0, 171, 255]
+        );
+        for bad in ["é0", "0é", "zz", "abc"] {
+            assert!(from_hex(bad).is_err(), "{bad}");
+        }
+        assert!(valid_name("openai.prod-key_1"));
+        assert!(!valid_name("a/b"));
+    }
+
+    #[test]
+    fn keychain_key_is_never_in_argv() {
+        let hex = to_hex(&[0xab; 32]);
+        let (cmd, input) = keychain_store_command(&hex);
+        assert!(cmd.get_args().all(|a| !a.to_string_lossy().contains(&hex)));
+        assert!(input.contains(&hex));
+    }
+}
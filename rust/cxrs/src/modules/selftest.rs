@@ -0,0 +1,461 @@
+use serde_json::{Value, json};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+use crate::diagnostics::{has_required_log_fields, last_appended_json_value};
+use crate::error::{EXIT_OK, EXIT_SCHEMA, format_error, print_runtime_error, print_usage_error};
+use crate::logs::{file_len, validate_runs_jsonl_file};
+use crate::paths::resolve_schema_dir;
+use crate::process::{run_command_output_with_timeout, run_command_status_with_timeout};
+
+const SELFTEST_USAGE: &str = "selftest [--json] [--keep]";
+const SELFTEST_FILE: &str = "selftest.txt";
+
+/// Fixtures for tools whose output is checked against the repo: `stage` keeps
+/// only paths present in the diff, `fanout` only known roles.
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "stage",
+        r#"{"groups":[{"subject":"chore: selftest change","rationale":"selftest","files":["selftest.txt"],"hunks":[]}]}"#,
+    ),
+    (
+        "fanout",
+        r#"{"subtasks":[{"role":"implementer","objective":"selftest objective","depends_on":[],"estimate":"1h"}]}"#,
+    ),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    /// Free-form output; no schema enforcement.
+    Plain,
+    /// Schema-valid structured output.
+    Valid,
+    /// Schema failure with a quarantine entry on disk.
+    Quarantined,
+}
+
+/// One stage: a `cxrs` invocation inside the scratch repo and the run-log
+/// row it must append.
+struct Stage {
+    name: &'static str,
+    args: &'static [&'static str],
+    env: &'static [(&'static str, &'static str)],
+    tool: &'static str,
+    expect: Expect,
+    exit: i32,
+}
+
+const STAGES: &[Stage] = &[
+    Stage {
+        name: "plain",
+        args: &["cxo", "echo", "selftest"],
+        env: &[],
+        tool: "cxo",
+        expect: Expect::Plain,
+        exit: EXIT_OK,
+    },
+    Stage {
+        name: "jsonl",
+        args: &["cxj", "echo", "selftest"],
+        env: &[],
+        tool: "cxj",
+        expect: Expect::Plain,
+        exit: EXIT_OK,
+    },
+    Stage {
+        name: "next",
+        args: &["next", "echo", "selftest"],
+        env: &[],
+        tool: "cxrs_next",
+        expect: Expect::Valid,
+        exit: EXIT_OK,
+    },
+    // `fix-run` passes the exit status of the failed command through.
+    Stage {
+        name: "fix-run",
+        args: &["fix-run", "false"],
+        env: &[],
+        tool: "cxrs_fix_run",
+        expect: Expect::Valid,
+        exit: 1,
+    },
+    Stage {
+        name: "diffsum",
+        args: &["diffsum"],
+        env: &[],
+        tool: "cxrs_diffsum",
+        expect: Expect::Valid,
+        exit: EXIT_OK,
+    },
+    Stage {
+        name: "diffsum-staged",
+        args: &["diffsum-staged"],
+        env: &[],
+        tool: "cxrs_diffsum_staged",
+        expect: Expect::Valid,
+        exit: EXIT_OK,
+    },
+    Stage {
+        name: "commitjson",
+        args: &["commitjson"],
+        env: &[],
+        tool: "cxrs_commitjson",
+        expect: Expect::Valid,
+        exit: EXIT_OK,
+    },
+    Stage {
+        name: "explain",
+        args: &["explain", SELFTEST_FILE],
+        env: &[],
+        tool: "cxrs_explain",
+        expect: Expect::Valid,
+        exit: EXIT_OK,
+    },
+    Stage {
+        name: "stage",
+        args: &["stage", "--json"],
+        env: &[],
+        tool: "cxrs_stage",
+        expect: Expect::Valid,
+        exit: EXIT_OK,
+    },
+    Stage {
+        name: "fanout",
+        args: &["fanout", "--llm", "selftest objective"],
+        env: &[],
+        tool: "cxrs_fanout",
+        expect: Expect::Valid,
+        exit: EXIT_OK,
+    },
+    Stage {
+        name: "quarantine",
+        args: &["next", "echo", "selftest"],
+        env: &[("CX_MOCK_PLAIN_RESPONSE", "not-json")],
+        tool: "cxrs_next",
+        expect: Expect::Quarantined,
+        exit: EXIT_SCHEMA,
+    },
+];
+
+struct StageResult {
+    name: &'static str,
+    command: String,
+    exit_code: i32,
+    duration_ms: u64,
+    problems: Vec<String>,
+}
+
+impl StageResult {
+    fn passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "command": self.command,
+            "status": if self.passed() { "pass" } else { "fail" },
+            "exit_code": self.exit_code,
+            "duration_ms": self.duration_ms,
+            "problems": self.problems,
+        })
+    }
+}
+
+/// Scratch git repo with one staged file that also has an unstaged edit, the
+/// schema registry and the fixtures. Lives under the system temp dir.
+fn setup_scratch_repo(schema_dir: &Path) -> Result<PathBuf, String> {
+    let ts = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0);
+    let root = env::temp_dir().join(format!("cxselftest-{}-{ts}", std::process::id()));
+    let codex = root.join(".codex");
+    let fixtures = codex.join("fixtures");
+    fs::create_dir_all(&fixtures).map_err(|e| format!("create {}: {e}", fixtures.display()))?;
+    let git = |args: &[&str]| -> Result<(), String> {
+        let mut cmd = Command::new("git");
+        cmd.args(args).current_dir(&root);
+        match run_command_status_with_timeout(cmd, "selftest git") {
+            Ok(s) if s.success() => Ok(()),
+            _ => Err(format!(
+                "git {} failed in {}",
+                args.join(" "),
+                root.display()
+            )),
+        }
+    };
+    git(&["init", "-q"])?;
+    let file = root.join(SELFTEST_FILE);
+    fs::write(&file, "cx selftest staged line\n").map_err(|e| format!("write file: {e}"))?;
+    git(&["add", SELFTEST_FILE])?;
+    fs::write(
+        &file,
+        "cx selftest staged line\ncx selftest unstaged line\n",
+    )
+    .map_err(|e| format!("write file: {e}"))?;
+
+    let schemas = codex.join("schemas");
+    fs::create_dir_all(&schemas).map_err(|e| format!("create {}: {e}", schemas.display()))?;
+    let entries =
+        fs::read_dir(schema_dir).map_err(|e| format!("read {}: {e}", schema_dir.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|v| v.to_str()) == Some("json")
+            && let Some(name) = path.file_name()
+        {
+            fs::copy(&path, schemas.join(name))
+                .map_err(|e| format!("copy {}: {e}", path.display()))?;
+        }
+    }
+    for (name, body) in FIXTURES {
+        fs::write(fixtures.join(format!("{name}.json")), body)
+            .map_err(|e| format!("write fixture {name}: {e}"))?;
+    }
+    Ok(root)
+}
+
+/// `cxrs` child pinned to the mock backend. Every `CX*` variable of the caller
+/// is dropped and HOME points into the scratch repo, so user config, state
+/// and offline mode cannot change the outcome.
+fn stage_command(exe: &Path, root: &Path, stage: &Stage) -> Command {
+    let mut cmd = Command::new(exe);
+    cmd.args(stage.args).current_dir(root);
+    for (key, _) in env::vars_os() {
+        if key.to_string_lossy().starts_with("CX") {
+            cmd.env_remove(key);
+        }
+    }
+    for key in ["XDG_STATE_HOME", "XDG_CONFIG_HOME", "XDG_CACHE_HOME"] {
+        cmd.env_remove(key);
+    }
+    cmd.env("HOME", root.join(".codex").join("home"))
+        .env("CX_LLM_BACKEND", "mock")
+        .env("CX_MOCK_FIXTURES", root.join(".codex").join("fixtures"));
+    for (k, v) in stage.env {
+        cmd.env(k, v);
+    }
+    cmd
+}
+
+/// Problems with the row a stage appended to the run log.
+fn check_row(root: &Path, stage: &Stage, exit_code: i32, row: Option<Value>) -> Vec<String> {
+    let mut problems = Vec::new();
+    if exit_code != stage.exit {
+        problems.push(format!("exit {exit_code} (expected {})", stage.exit));
+    }
+    let Some(row) = row else {
+        problems.push("no run-log row appended".to_string());
+        return problems;
+    };
+    if !has_required_log_fields(&row) {
+        problems.push("run-log row is missing required fields".to_string());
+    }
+    let field = |k: &str| row.get(k).cloned().unwrap_or(Value::Null);
+    if field("tool") != stage.tool {
+        problems.push(format!("tool {} (expected {})", field("tool"), stage.tool));
+    }
+    if field("backend_used") != "mock" {
+        problems.push(format!("backend_used {}", field("backend_used")));
+    }
+    let quarantine_id = row.get("quarantine_id").and_then(Value::as_str);
+    match stage.expect {
+        Expect::Plain if field("schema_enforced") == true => {
+            problems.push("schema enforced on a plain run".to_string());
+        }
+        Expect::Valid if field("schema_valid") != true => {
+            problems.push(format!("schema_valid {}", field("schema_valid")));
+        }
+        Expect::Quarantined => {
+            if field("schema_valid") != false {
+                problems.push(format!("schema_valid {}", field("schema_valid")));
+            }
+            match quarantine_id {
+                Some(id) => {
+                    let file = root
+                        .join(".codex")
+                        .join("quarantine")
+                        .join(format!("{id}.json"));
+                    if !file.is_file() {
+                        problems.push(format!("quarantine file missing: {}", file.display()));
+                    }
+                }
+                None => problems.push("no quarantine_id logged".to_string()),
+            }
+        }
+        _ => {}
+    }
+    if stage.expect != Expect::Quarantined && quarantine_id.is_some() {
+        problems.push("unexpected quarantine entry".to_string());
+    }
+    problems
+}
+
+fn run_stage(exe: &Path, root: &Path, stage: &Stage) -> StageResult {
+    let log_file = root.join(".codex").join("cxlogs").join("runs.jsonl");
+    let offset = if log_file.exists() {
+        file_len(&log_file)
+    } else {
+        0
+    };
+    let started = Instant::now();
+    let output = run_command_output_with_timeout(
+        stage_command(exe, root, stage),
+        &format!("selftest {}", stage.name),
+    );
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let command = format!("cxrs {}", stage.args.join(" "));
+    let (exit_code, problems) = match output {
+        Ok(out) => {
+            let code = out.status.code().unwrap_or(1);
+            let row = last_appended_json_value(&log_file, offset);
+            let mut problems = check_row(root, stage, code, row);
+            if !problems.is_empty() {
+                let stderr = String::from_utf8_lossy(&out.stderr);
+                if let Some(line) = stderr.lines().rev().find(|l| !l.trim().is_empty()) {
+                    problems.push(format!("stderr: {}", line.trim()));
+                }
+            }
+            (code, problems)
+        }
+        Err(e) => (1, vec![e]),
+    };
+    StageResult {
+        name: stage.name,
+        command,
+        exit_code,
+        duration_ms,
+        problems,
+    }
+}
+
+/// Whole-log contract check after every stage ran.
+fn log_contract_result(root: &Path) -> StageResult {
+    let log_file = root.join(".codex").join("cxlogs").join("runs.jsonl");
+    let problems = match validate_runs_jsonl_file(&log_file, false) {
+        Ok(o) if o.issues.is_empty() => Vec::new(),
+        Ok(o) => o.issues.into_iter().take(3).collect(),
+        Err(e) => vec![e],
+    };
+    StageResult {
+        name: "log-contract",
+        command: "logs validate".to_string(),
+        exit_code: i32::from(!problems.is_empty()),
+        duration_ms: 0,
+        problems,
+    }
+}
+
+fn print_text(root: &Path, results: &[StageResult], kept: bool) {
+    println!("== cxrs selftest ==");
+    println!("backend: mock (offline)");
+    for r in results {
+        let status = if r.passed() { "PASS" } else { "FAIL" };
+        println!(
+            "{status:<5} {:<16} {:>6}ms  {}",
+            r.name, r.duration_ms, r.command
+        );
+        for p in &r.problems {
+            println!("      - {p}");
+        }
+    }
+    let passed = results.iter().filter(|r| r.passed()).count();
+    println!();
+    println!("selftest: {passed}/{} stage(s) passed", results.len());
+    if kept {
+        println!("scratch repo kept: {}", root.display());
+    }
+}
+
+/// `selftest`: run every structured tool against the mock backend in a
+/// scratch repo and check each run-log row and quarantine entry. Exit 1 when
+/// any stage fails.
+pub fn cmd_selftest(args: &[String]) -> i32 {
+    let mut json_out = false;
+    let mut keep = false;
+    for a in args {
+        match a.as_str() {
+            "--json" => json_out = true,
+            "--keep" => keep = true,
+            other => {
+                crate::cx_eprintln!(
+                    "{}",
+                    format_error("selftest", &format!("unknown flag '{other}'"))
+                );
+                return print_usage_error("selftest", SELFTEST_USAGE);
+            }
+        }
+    }
+    let Some(schema_dir) = resolve_schema_dir().filter(|d| d.is_dir()) else {
+        return print_runtime_error("selftest", "no schema registry found (.codex/schemas)");
+    };
+    let exe = match env::current_exe() {
+        Ok(p) => p,
+        Err(e) => return print_runtime_error("selftest", &format!("cannot locate cxrs: {e}")),
+    };
+    let root = match setup_scratch_repo(&schema_dir) {
+        Ok(r) => r,
+        Err(e) => return print_runtime_error("selftest", &e),
+    };
+    let mut results: Vec<StageResult> = STAGES
+        .iter()
+        .map(|stage| run_stage(&exe, &root, stage))
+        .collect();
+    results.push(log_contract_result(&root));
+    let ok = results.iter().all(StageResult::passed);
+    let code = i32::from(!ok);
+    if json_out {
+        let doc = json!({
+            "backend": "mock",
+            "status": if ok { "pass" } else { "fail" },
+            "exit_code": code,
+            "passed": results.iter().filter(|r| r.passed()).count(),
+            "failed": results.iter().filter(|r| !r.passed()).count(),
+            "scratch_repo": keep.then(|| root.display().to_string()),
+            "stages": results.iter().map(StageResult::to_json).collect::<Vec<_>>(),
+        });
+        match serde_json::to_string_pretty(&doc) {
+            Ok(s) => println!("{s}"),
+            Err(e) => {
+                crate::cx_eprintln!(
+                    "{}",
+                    format_error("selftest", &format!("render failed: {e}"))
+                );
+                return 1;
+            }
+        }
+    } else {
+        print_text(&root, &results, keep);
+    }
+    if !keep {
+        let _ = fs::remove_dir_all(&root);
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(name: &str) -> &'static Stage {
+        STAGES.iter().find(|s| s.name == name).expect("stage")
+    }
+
+    #[test]
+    fn rows_are_checked_against_the_stage_expectation() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let row = json!({
+            "execution_id": "e1", "backend_used": "mock", "capture_provider": "native",
+            "execution_mode": "lean", "schema_enforced": true, "schema_valid": true,
+            "duration_ms": 3, "tool": "cxrs_next"
+        });
+        assert!(check_row(dir.path(), stage("next"), 0, Some(row.clone())).is_empty());
+
+        let problems = check_row(dir.path(), stage("quarantine"), 0, Some(row));
+        assert!(problems.contains(&"exit 0 (expected 4)".to_string()));
+        assert!(problems.contains(&"no quarantine_id logged".to_string()));
+
+        let problems = check_row(dir.path(), stage("plain"), 0, None);
+        assert_eq!(problems, vec!["no run-log row appended".to_string()]);
+    }
+}
@@ -0,0 +1,361 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+
+use crate::capture::load_redactor;
+use crate::config::app_config;
+use crate::error::{EXIT_OK, EXIT_USAGE, print_runtime_error, print_usage_error};
+use crate::execmeta::utc_now_iso;
+use crate::logs::append_jsonl;
+use crate::paths::{repo_root, resolve_sessions_dir};
+use crate::types::{ExecutionResult, LlmOutputKind, SessionTurn, TaskInput, TaskSpec};
+use crate::verbosity::warn;
+
+type TaskRunner = fn(TaskSpec) -> Result<ExecutionResult, String>;
+
+const SESSION_OFF_VALUES: &[&str] = &["off", "none", "0"];
+
+fn valid_session_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+        && !name.starts_with('.')
+}
+
+fn session_file(name: &str) -> Result<PathBuf, String> {
+    if !valid_session_name(name) {
+        return Err(format!(
+            "invalid session name '{name}' (allowed: letters, digits, '-', '_', '.')"
+        ));
+    }
+    let dir = resolve_sessions_dir().ok_or("unable to resolve sessions directory")?;
+    Ok(dir.join(format!("{name}.jsonl")))
+}
+
+/// Session named by `CX_SESSION`, or `None` when it is `off`.
+fn selected_session_name() -> Option<String> {
+    let name = app_config().session_name.clone();
+    if SESSION_OFF_VALUES.contains(&name.to_ascii_lowercase().as_str()) {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Session that turns are recorded to; recording is opt-in through
+/// `session.enabled` (`CX_SESSION_ENABLED=1`).
+pub fn active_session_name() -> Option<String> {
+    if !app_config().session_enabled {
+        return None;
+    }
+    selected_session_name()
+}
+
+pub fn load_session_turns(name: &str) -> Result<Vec<SessionTurn>, String> {
+    let path = session_file(name)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = fs::File::open(&path).map_err(|e| format!("cannot open {}: {e}", path.display()))?;
+    let mut out = Vec::new();
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let turn = serde_json::from_str::<SessionTurn>(&line)
+            .map_err(|e| format!("invalid session JSON {}:{}: {e}", path.display(), idx + 1))?;
+        out.push(turn);
+    }
+    Ok(out)
+}
+
+pub fn record_session_turn(
+    tool: &str,
+    command: Option<&[String]>,
+    result: &ExecutionResult,
+) -> Result<(), String> {
+    let Some(name) = active_session_name() else {
+        return Ok(());
+    };
+    append_session_turn(&name, tool, command, result)
+}
+
+/// Append one turn. Prompt, answer and command go through capture redaction
+/// first (unless `CX_REDACT=0`); an invalid redact config skips the turn
+/// rather than storing unmasked text.
+fn append_session_turn(
+    name: &str,
+    tool: &str,
+    command: Option<&[String]>,
+    result: &ExecutionResult,
+) -> Result<(), String> {
+    let mut turn = SessionTurn {
+        ts: utc_now_iso(),
+        tool: tool.to_string(),
+        command: command.map(|c| c.join(" ")),
+        prompt: result.prompt.clone(),
+        answer: result.stdout.trim_end().to_string(),
+        execution_id: Some(result.execution_id.clone()),
+    };
+    if app_config().redact {
+        let root = repo_root().unwrap_or_else(|| PathBuf::from("."));
+        let redactor = load_redactor(&root)?;
+        turn.prompt = redactor.redact(&turn.prompt).text;
+        turn.answer = redactor.redact(&turn.answer).text;
+        turn.command = turn.command.map(|c| redactor.redact(&c).text);
+    }
+    let value = serde_json::to_value(&turn).map_err(|e| format!("session serialize: {e}"))?;
+    append_jsonl(&session_file(name)?, &value)
+}
+
+fn render_turn_context(turn: &SessionTurn, idx: usize) -> String {
+    let label = match &turn.command {
+        Some(cmd) => format!("{}: {cmd}", turn.tool),
+        None => turn.tool.clone(),
+    };
+    format!(
+        "### Turn {idx} ({label})\nInput:\n{}\n\nAnswer:\n{}\n",
+        turn.prompt.trim_end(),
+        turn.answer.trim_end()
+    )
+}
+
+pub fn build_followup_prompt(turns: &[SessionTurn], question: &str, max_turns: usize) -> String {
+    let start = turns.len().saturating_sub(max_turns);
+    let mut out = String::from(
+        "You are continuing a terminal assistant session.\nUse the prior turns below as context and answer the follow-up question directly.\n\n",
+    );
+    for (i, turn) in turns[start..].iter().enumerate() {
+        out.push_str(&render_turn_context(turn, i + 1));
+        out.push('\n');
+    }
+    out.push_str("Follow-up question:\n");
+    out.push_str(question.trim());
+    out
+}
+
+/// `--session NAME` is only read before the question, so the question itself
+/// may contain `--session`.
+fn split_followup_args(args: &[String]) -> Result<(Option<String>, &[String]), String> {
+    match args {
+        [flag, rest @ ..] if flag == "--session" => {
+            let (name, question) = rest.split_first().ok_or("--session requires a value")?;
+            Ok((Some(name.clone()), question))
+        }
+        _ => Ok((None, args)),
+    }
+}
+
+fn parse_session_flag(args: &[String]) -> Result<(Option<String>, Vec<String>), String> {
+    let mut session = None;
+    let mut rest = Vec::new();
+    let mut i = 0usize;
+    while i < args.len() {
+        if args[i] == "--session" {
+            let v = args.get(i + 1).ok_or("--session requires a value")?;
+            session = Some(v.clone());
+            i += 2;
+            continue;
+        }
+        rest.push(args[i].clone());
+        i += 1;
+    }
+    Ok((session, rest))
+}
+
+pub fn cmd_followup(app_name: &str, args: &[String], run_task: TaskRunner) -> i32 {
+    let usage = format!("{app_name} followup [--session NAME] <question...>");
+    let (flag, rest) = match split_followup_args(args) {
+        Ok(v) => v,
+        Err(_) => return print_usage_error("followup", &usage),
+    };
+    if rest.is_empty() {
+        return print_usage_error("followup", &usage);
+    }
+    let Some(name) = flag.or_else(selected_session_name) else {
+        return print_runtime_error("followup", "sessions are disabled (CX_SESSION=off)");
+    };
+    let turns = match load_session_turns(&name) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("followup", &e),
+    };
+    if turns.is_empty() {
+        let hint = if app_config().session_enabled {
+            "run cxo/cx first"
+        } else {
+            "recording is off; set CX_SESSION_ENABLED=1 (session.enabled) and run cxo/cx first"
+        };
+        return print_runtime_error(
+            "followup",
+            &format!("session '{name}' has no prior turns; {hint}"),
+        );
+    }
+    let question = rest.join(" ");
+    let prompt = build_followup_prompt(&turns, &question, app_config().session_turns);
+    let result = match run_task(TaskSpec {
+        command_name: "followup".to_string(),
+        input: TaskInput::Prompt(prompt),
+        output_kind: LlmOutputKind::AgentText,
+        schema: None,
+        schema_task_input: None,
+        logging_enabled: true,
+        capture_override: None,
+        prompt_template: None,
+        stream: false,
+    }) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("followup", &e),
+    };
+    println!("{}", result.stdout);
+    let turn_result = ExecutionResult {
+        prompt: question,
+        ..result
+    };
+    if app_config().session_enabled
+        && let Err(e) = append_session_turn(&name, "followup", None, &turn_result)
+    {
+        warn(&format!(
+            "cxrs followup: warning: failed to record session turn: {e}"
+        ));
+    }
+    EXIT_OK
+}
+
+fn session_summary(path: &std::path::Path) -> Option<Value> {
+    let name = path.file_stem()?.to_str()?.to_string();
+    let turns = load_session_turns(&name).ok()?;
+    Some(json!({
+        "name": name,
+        "turns": turns.len(),
+        "last_ts": turns.last().map(|t| t.ts.clone()),
+        "path": path.display().to_string(),
+    }))
+}
+
+fn list_sessions() -> Vec<Value> {
+    let Some(dir) = resolve_sessions_dir() else {
+        return Vec::new();
+    };
+    let Ok(rd) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut rows: Vec<Value> = rd
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|v| v.to_str()) == Some("jsonl"))
+        .filter_map(|p| session_summary(&p))
+        .collect();
+    rows.sort_by(|a, b| {
+        let ka = a.get("name").and_then(Value::as_str).unwrap_or_default();
+        let kb = b.get("name").and_then(Value::as_str).unwrap_or_default();
+        ka.cmp(kb)
+    });
+    rows
+}
+
+fn print_json(cmd: &str, value: &Value) -> i32 {
+    match serde_json::to_string_pretty(value) {
+        Ok(s) => {
+            println!("{s}");
+            EXIT_OK
+        }
+        Err(e) => print_runtime_error(cmd, &format!("failed to render JSON: {e}")),
+    }
+}
+
+fn cmd_session_list(json_out: bool) -> i32 {
+    let rows = list_sessions();
+    let active = active_session_name();
+    if json_out {
+        return print_json("session list", &json!({"active": active, "sessions": rows}));
+    }
+    println!("== cxrs session list ==");
+    println!("active: {}", active.as_deref().unwrap_or("<off>"));
+    println!("sessions: {}", rows.len());
+    for row in rows {
+        println!(
+            "- {} | turns={} | last={}",
+            row.get("name").and_then(Value::as_str).unwrap_or_default(),
+            row.get("turns").and_then(Value::as_u64).unwrap_or(0),
+            row.get("last_ts").and_then(Value::as_str).unwrap_or("n/a")
+        );
+    }
+    EXIT_OK
+}
+
+fn cmd_session_show(name: &str, json_out: bool) -> i32 {
+    let turns = match load_session_turns(name) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("session show", &e),
+    };
+    if json_out {
+        return print_json("session show", &json!({"name": name, "turns": turns}));
+    }
+    println!("== cxrs session show ==");
+    println!("session: {name}");
+    println!("turns: {}", turns.len());
+    for (i, turn) in turns.iter().enumerate() {
+        let cmd = turn.command.as_deref().unwrap_or("-");
+        println!();
+        println!("[{}] {} | {} | {}", i + 1, turn.ts, turn.tool, cmd);
+        println!("{}", turn.answer);
+    }
+    EXIT_OK
+}
+
+fn cmd_session_clear(name: &str) -> i32 {
+    let path = match session_file(name) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("session clear", &e),
+    };
+    if !path.exists() {
+        println!("session '{name}' already empty");
+        return EXIT_OK;
+    }
+    match fs::remove_file(&path) {
+        Ok(()) => {
+            println!("cleared session '{name}'");
+            EXIT_OK
+        }
+        Err(e) => print_runtime_error(
+            "session clear",
+            &format!("failed to remove {}: {e}", path.display()),
+        ),
+    }
+}
+
+pub fn cmd_session(app_name: &str, args: &[String]) -> i32 {
+    let usage = format!("{app_name} session <list [--json]|show [NAME] [--json]|clear [NAME]>");
+    let json_out = args.iter().any(|a| a == "--json");
+    let (flag, rest) = match parse_session_flag(args) {
+        Ok(v) => v,
+        Err(_) => return print_usage_error("session", &usage),
+    };
+    let positional: Vec<&String> = rest.iter().filter(|a| *a != "--json").collect();
+    let sub = positional.first().map(|s| s.as_str()).unwrap_or("list");
+    let target = flag
+        .or_else(|| positional.get(1).map(|s| s.to_string()))
+        .or_else(selected_session_name);
+    match sub {
+        "list" => cmd_session_list(json_out),
+        "show" | "clear" => {
+            let Some(name) = target else {
+                return print_runtime_error("session", "sessions are disabled (CX_SESSION=off)");
+            };
+            if sub == "show" {
+                cmd_session_show(&name, json_out)
+            } else {
+                cmd_session_clear(&name)
+            }
+        }
+        other => {
+            crate::cx_eprintln!("{app_name}: unknown session subcommand '{other}'");
+            crate::cx_eprintln!("Usage: {usage}");
+            EXIT_USAGE
+        }
+    }
+}
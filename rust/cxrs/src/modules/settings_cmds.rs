@@ -1,18 +1,90 @@
 use serde_json::Value;
+use std::path::PathBuf;
 
-use crate::analytics::quota_probe_for_backend_days;
-use crate::runtime::{llm_backend, llm_model, ollama_model_preference};
+use crate::config_layers::{
+    SETTINGS, file_setting, global_config_file, parse_setting_value, repo_config_file,
+    resolve_setting, setting_by_key, unknown_setting, write_file_setting,
+};
+use crate::error::print_usage_error;
+use crate::paths::{global_state_file, repo_state_file};
 use crate::state::{
-    ensure_state_value, parse_cli_value, set_state_path, set_value_at_path, state_cache_clear,
-    value_at_path, write_json_atomic,
+    default_state_value, ensure_state_value, ensure_state_value_at, parse_cli_value,
+    read_state_layers, read_state_value, set_value_at_path, state_cache_clear, state_key_origins,
+    state_layer_files, value_at_path, write_json_atomic,
 };
+use crate::verbosity::warn;
+
+const STATE_USAGE: &str = "state <show [--merged [--origin]|--repo|--global]|get <key> [--repo|--global]|set <key> <value> [--repo|--global]>";
+
+fn state_usage_error(op: &str, e: &str) -> i32 {
+    crate::cx_eprintln!("cxrs state {op}: {e}");
+    print_usage_error("state", STATE_USAGE)
+}
+
+fn state_scope_file(scope: &str) -> Result<PathBuf, String> {
+    match scope {
+        "global" => global_state_file().ok_or_else(|| "cannot resolve $HOME".to_string()),
+        _ => repo_state_file()
+            .ok_or_else(|| "not inside a git repository (use --global)".to_string()),
+    }
+}
+
+/// The state file for `scope`, or the default write target (repo, else global).
+fn scoped_state_value(scope: Option<&str>) -> Result<(PathBuf, Value), String> {
+    match scope {
+        Some(scope) => {
+            let path = state_scope_file(scope)?;
+            let value = ensure_state_value_at(&path)?;
+            Ok((path, value))
+        }
+        None => ensure_state_value(),
+    }
+}
+
+fn print_state_origins() -> i32 {
+    let layers = read_state_layers();
+    for (key, (value, layer)) in state_key_origins(&layers) {
+        match value {
+            Value::String(s) => println!("{key} = {s}  # {layer}"),
+            v => println!("{key} = {v}  # {layer}"),
+        }
+    }
+    for (label, path) in state_layer_files() {
+        if path.is_file() {
+            println!("# {label}_file: {}", path.display());
+        } else {
+            println!("# {label}_file: {} (absent)", path.display());
+        }
+    }
+    0
+}
 
-pub fn cmd_state_show() -> i32 {
-    let (_state_file, state) = match ensure_state_value() {
+pub fn cmd_state_show(args: &[String]) -> i32 {
+    let (scope, rest) = match config_scope(args) {
         Ok(v) => v,
-        Err(e) => {
-            crate::cx_eprintln!("cxrs state show: {e}");
-            return 1;
+        Err(e) => return state_usage_error("show", &e),
+    };
+    let (merged, origin) = match rest.as_slice() {
+        [] => (false, false),
+        ["--merged"] => (true, false),
+        ["--merged", "--origin"] | ["--origin", "--merged"] => (true, true),
+        _ => return state_usage_error("show", "unexpected arguments"),
+    };
+    if merged && scope.is_some() {
+        return state_usage_error("show", "--merged cannot be combined with --repo/--global");
+    }
+    if origin {
+        return print_state_origins();
+    }
+    let state = if merged {
+        read_state_value().unwrap_or_else(default_state_value)
+    } else {
+        match scoped_state_value(scope) {
+            Ok((_state_file, v)) => v,
+            Err(e) => {
+                crate::cx_eprintln!("cxrs state show: {e}");
+                return 1;
+            }
         }
     };
     match serde_json::to_string_pretty(&state) {
@@ -27,14 +99,27 @@ pub fn cmd_state_show() -> i32 {
     }
 }
 
-pub fn cmd_state_get(key: &str) -> i32 {
-    let (state_file, state) = match ensure_state_value() {
+/// Without a scope, reads the merged view (repo over global).
+pub fn cmd_state_get(args: &[String]) -> i32 {
+    let (scope, rest) = match config_scope(args) {
+        Ok(v) => v,
+        Err(e) => return state_usage_error("get", &e),
+    };
+    let [key] = rest.as_slice() else {
+        return state_usage_error("get", "expected exactly one key");
+    };
+    let (state_file, mut state) = match scoped_state_value(scope) {
         Ok(v) => v,
         Err(e) => {
             crate::cx_eprintln!("cxrs state get: {e}");
             return 1;
         }
     };
+    if scope.is_none()
+        && let Some(merged) = read_state_value()
+    {
+        state = merged;
+    }
     let Some(v) = value_at_path(&state, key) else {
         crate::cx_eprintln!("cxrs state get: key not found: {key}");
         crate::cx_eprintln!("state_file: {}", state_file.display());
@@ -47,8 +132,15 @@ pub fn cmd_state_get(key: &str) -> i32 {
     0
 }
 
-pub fn cmd_state_set(key: &str, raw_value: &str) -> i32 {
-    let (state_file, mut state) = match ensure_state_value() {
+pub fn cmd_state_set(args: &[String]) -> i32 {
+    let (scope, rest) = match config_scope(args) {
+        Ok(v) => v,
+        Err(e) => return state_usage_error("set", &e),
+    };
+    let [key, raw_value] = rest.as_slice() else {
+        return state_usage_error("set", "expected <key> <value>");
+    };
+    let (state_file, mut state) = match scoped_state_value(scope) {
         Ok(v) => v,
         Err(e) => {
             crate::cx_eprintln!("cxrs state set: {e}");
@@ -64,235 +156,183 @@ pub fn cmd_state_set(key: &str, raw_value: &str) -> i32 {
         return 1;
     }
     state_cache_clear();
+    if scope == Some("global")
+        && let Some(repo) = read_state_layers()
+            .into_iter()
+            .find_map(|(label, v)| (label == "repo").then_some(v))
+        && value_at_path(&repo, key).is_some_and(|v| !v.is_null())
+    {
+        warn(&format!(
+            "cxrs state: note: {key} is also set in the repo state, which takes precedence"
+        ));
+    }
     println!("ok");
     0
 }
 
-fn print_llm_usage(app_name: &str) {
+fn print_config_usage(app_name: &str) {
     crate::cx_eprintln!(
-        "Usage: {app_name} llm <show|use <codex|ollama> [model]|unset <backend|model|all>|set-backend <codex|ollama>|set-model <model>|clear-model>"
+        "Usage: {app_name} config <show [--origin]|get <key> [--repo|--global]|set <key> <value> [--repo|--global]|unset <key> [--repo|--global]>"
     );
 }
 
-fn llm_show() -> i32 {
-    let backend = llm_backend();
-    let model = llm_model();
-    let ollama_pref = ollama_model_preference();
-    println!("llm_backend: {backend}");
-    println!(
-        "active_model: {}",
-        if model.is_empty() { "<unset>" } else { &model }
-    );
-    println!(
-        "ollama_model: {}",
-        if ollama_pref.is_empty() {
-            "<unset>"
+/// Effective settings after layering defaults < ~/.codex/config.toml <
+/// repo .codex/config.toml < state preferences < env.
+fn config_show(origin: bool) -> i32 {
+    for spec in SETTINGS {
+        let (value, source) = resolve_setting(spec);
+        if origin {
+            println!("{} = {value}  # {source}", spec.key);
         } else {
-            &ollama_pref
+            println!("{} = {value}", spec.key);
         }
-    );
+    }
+    if origin {
+        for (label, path) in [
+            ("global", global_config_file()),
+            ("repo", repo_config_file()),
+        ] {
+            match path {
+                Some(p) if p.is_file() => println!("# {label}_file: {}", p.display()),
+                Some(p) => println!("# {label}_file: {} (absent)", p.display()),
+                None => println!("# {label}_file: <unresolved>"),
+            }
+        }
+    }
     0
 }
 
-fn emit_quota_probe_notice(backend: &str, model: Option<&str>) {
-    let Ok(payload) = quota_probe_for_backend_days(30, backend, model) else {
-        crate::cx_eprintln!("quota_probe: unavailable");
-        return;
-    };
-    let backend = payload
-        .get("backend")
-        .and_then(Value::as_str)
-        .unwrap_or("unknown");
-    let service_kind = payload
-        .get("service_kind")
-        .and_then(Value::as_str)
-        .unwrap_or("unknown");
-    let source = payload
-        .get("quota_source")
-        .and_then(Value::as_str)
-        .unwrap_or("unknown");
-    let remaining = payload.get("quota_remaining_pct").and_then(Value::as_f64);
-
-    if service_kind == "local_unmetered" {
-        crate::cx_eprintln!(
-            "quota_probe: backend={} service_kind=local_unmetered (provider quota unavailable for local model)",
-            backend
-        );
-        return;
+/// Split `--repo`/`--global` out of config args; `None` scope means neither was given.
+fn config_scope(args: &[String]) -> Result<(Option<&'static str>, Vec<&str>), String> {
+    let mut scope = None;
+    let mut rest = Vec::new();
+    for a in args {
+        match a.as_str() {
+            "--repo" | "--global" => {
+                let s = if a == "--repo" { "repo" } else { "global" };
+                if scope.is_some_and(|prev| prev != s) {
+                    return Err("--repo and --global are mutually exclusive".to_string());
+                }
+                scope = Some(s);
+            }
+            other => rest.push(other),
+        }
     }
+    Ok((scope, rest))
+}
 
-    if let Some(rem) = remaining {
-        let remaining_pct = format!("{}%", (rem * 100.0).round() as i64);
-        crate::cx_eprintln!(
-            "quota_probe: backend={} remaining={} source={}",
-            backend,
-            remaining_pct,
-            source
-        );
-    } else {
-        crate::cx_eprintln!(
-            "quota_probe: backend={} remaining=unknown source={} (set quota total or refresh catalog)",
-            backend,
-            source
-        );
+fn scope_file(scope: &str) -> Result<PathBuf, String> {
+    match scope {
+        "global" => global_config_file().ok_or_else(|| "cannot resolve $HOME".to_string()),
+        _ => repo_config_file()
+            .ok_or_else(|| "not inside a git repository (use --global)".to_string()),
     }
 }
 
-fn llm_use(app_name: &str, args: &[String]) -> i32 {
-    let Some(target) = args.get(1).map(|s| s.to_lowercase()) else {
-        print_llm_usage(app_name);
+fn config_get(scope: Option<&str>, key: &str) -> i32 {
+    let Some(spec) = setting_by_key(key) else {
+        crate::cx_eprintln!("cxrs config get: {}", unknown_setting(key));
         return 2;
     };
-    if target != "codex" && target != "ollama" {
-        print_llm_usage(app_name);
-        return 2;
-    }
-    if let Err(e) = set_state_path("preferences.llm_backend", Value::String(target.clone())) {
-        crate::cx_eprintln!("cxrs llm use: {e}");
-        return 1;
-    }
-    if target == "ollama" {
-        if let Some(model) = args.get(2) {
-            let m = model.trim();
-            if m.is_empty() {
-                print_llm_usage(app_name);
-                return 2;
-            }
-            if let Err(e) = set_state_path("preferences.ollama_model", Value::String(m.to_string()))
-            {
-                crate::cx_eprintln!("cxrs llm use: {e}");
-                return 1;
-            }
-        }
-        println!("ok");
-        println!("llm_backend: ollama");
-        let pref = ollama_model_preference();
-        println!(
-            "ollama_model: {}",
-            if pref.is_empty() { "<unset>" } else { &pref }
-        );
-        state_cache_clear();
-        let model_opt = if pref.is_empty() {
-            None
-        } else {
-            Some(pref.as_str())
-        };
-        emit_quota_probe_notice("ollama", model_opt);
+    let Some(scope) = scope else {
+        println!("{}", resolve_setting(spec).0);
         return 0;
-    }
-    println!("ok");
-    println!("llm_backend: codex");
-    state_cache_clear();
-    emit_quota_probe_notice("codex", None);
-    0
-}
-
-fn llm_unset(app_name: &str, args: &[String]) -> i32 {
-    let target = args.get(1).map(String::as_str).unwrap_or("all");
-    match target {
-        "backend" => {
-            if let Err(e) = set_state_path("preferences.llm_backend", Value::Null) {
-                crate::cx_eprintln!("cxrs llm unset backend: {e}");
-                return 1;
-            }
-            println!("ok");
-            println!("llm_backend: <unset>");
-            0
+    };
+    let path = match scope_file(scope) {
+        Ok(p) => p,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs config get: {e}");
+            return 1;
         }
-        "model" => {
-            if let Err(e) = set_state_path("preferences.ollama_model", Value::Null) {
-                crate::cx_eprintln!("cxrs llm unset model: {e}");
-                return 1;
-            }
-            println!("ok");
-            println!("ollama_model: <unset>");
+    };
+    match file_setting(&path, spec) {
+        Ok(Some(v)) => {
+            println!("{v}");
             0
         }
-        "all" => {
-            if let Err(e) = set_state_path("preferences.llm_backend", Value::Null) {
-                crate::cx_eprintln!("cxrs llm unset all: {e}");
-                return 1;
-            }
-            if let Err(e) = set_state_path("preferences.ollama_model", Value::Null) {
-                crate::cx_eprintln!("cxrs llm unset all: {e}");
-                return 1;
-            }
-            println!("ok");
-            println!("llm_backend: <unset>");
-            println!("ollama_model: <unset>");
-            0
+        Ok(None) => {
+            crate::cx_eprintln!("cxrs config get: {key} is not set in {}", path.display());
+            1
         }
-        _ => {
-            print_llm_usage(app_name);
-            2
+        Err(e) => {
+            crate::cx_eprintln!("cxrs config get: {e}");
+            1
         }
     }
 }
 
-fn llm_set_backend(app_name: &str, args: &[String]) -> i32 {
-    let Some(v) = args.get(1).map(|s| s.to_lowercase()) else {
-        print_llm_usage(app_name);
+fn config_write(scope: Option<&str>, key: &str, raw: Option<&str>) -> i32 {
+    let op = if raw.is_some() { "set" } else { "unset" };
+    let Some(spec) = setting_by_key(key) else {
+        crate::cx_eprintln!("cxrs config {op}: {}", unknown_setting(key));
         return 2;
     };
-    if v != "codex" && v != "ollama" {
-        print_llm_usage(app_name);
-        return 2;
-    }
-    if let Err(e) = set_state_path("preferences.llm_backend", Value::String(v.clone())) {
-        crate::cx_eprintln!("cxrs llm set-backend: {e}");
-        return 1;
-    }
-    println!("ok");
-    println!("llm_backend: {v}");
-    state_cache_clear();
-    emit_quota_probe_notice(&v, None);
-    0
-}
-
-fn llm_set_model(app_name: &str, args: &[String]) -> i32 {
-    let Some(model) = args.get(1) else {
-        print_llm_usage(app_name);
-        return 2;
+    let value = match raw.map(|r| parse_setting_value(spec, r)).transpose() {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs config {op}: {e}");
+            return 2;
+        }
     };
-    if model.trim().is_empty() {
-        print_llm_usage(app_name);
+    let scope = scope.unwrap_or("repo");
+    if scope == "repo" && raw.is_some() && !spec.repo_overridable {
+        crate::cx_eprintln!(
+            "cxrs config {op}: {key} can only be set in the global config or {} (use --global)",
+            spec.env
+        );
         return 2;
     }
-    if let Err(e) = set_state_path(
-        "preferences.ollama_model",
-        Value::String(model.trim().to_string()),
-    ) {
-        crate::cx_eprintln!("cxrs llm set-model: {e}");
-        return 1;
+    let path = match scope_file(scope) {
+        Ok(p) => p,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs config {op}: {e}");
+            return 1;
+        }
+    };
+    match write_file_setting(&path, spec, value.clone()) {
+        Ok(true) => match value {
+            Some(v) => println!("{key} = {v} ({})", path.display()),
+            None => println!("unset {key} ({})", path.display()),
+        },
+        Ok(false) => println!("{key} was not set in {}", path.display()),
+        Err(e) => {
+            crate::cx_eprintln!("cxrs config {op}: {e}");
+            return 1;
+        }
     }
-    println!("ok");
-    println!("ollama_model: {}", model.trim());
-    state_cache_clear();
-    emit_quota_probe_notice("ollama", Some(model.trim()));
-    0
-}
-
-fn llm_clear_model() -> i32 {
-    if let Err(e) = set_state_path("preferences.ollama_model", Value::Null) {
-        crate::cx_eprintln!("cxrs llm clear-model: {e}");
-        return 1;
+    if std::env::var_os(spec.env).is_some_and(|v| !v.is_empty()) {
+        warn(&format!(
+            "cxrs config: note: {} is set in the environment and takes precedence",
+            spec.env
+        ));
     }
-    println!("ok");
-    println!("ollama_model: <unset>");
     0
 }
 
-pub fn cmd_llm(app_name: &str, args: &[String]) -> i32 {
-    match args.first().map(String::as_str).unwrap_or("show") {
-        "show" => llm_show(),
-        "use" => llm_use(app_name, args),
-        "unset" => llm_unset(app_name, args),
-        "set-backend" => llm_set_backend(app_name, args),
-        "set-model" => llm_set_model(app_name, args),
-        "clear-model" => llm_clear_model(),
-        other => {
-            crate::cx_eprintln!("{app_name} llm: unknown subcommand '{other}'");
-            print_llm_usage(app_name);
+pub fn cmd_config(app_name: &str, args: &[String]) -> i32 {
+    let (scope, rest) = match config_scope(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{app_name} config: {e}");
+            return 2;
+        }
+    };
+    match (
+        rest.first().copied().unwrap_or("show"),
+        &rest[rest.len().min(1)..],
+    ) {
+        ("show", []) if scope.is_none() => config_show(false),
+        ("show", ["--origin"]) if scope.is_none() => config_show(true),
+        ("get", [key]) => config_get(scope, key),
+        ("set", [key, value]) => config_write(scope, key, Some(value)),
+        ("unset", [key]) => config_write(scope, key, None),
+        ("show" | "get" | "set" | "unset", _) => {
+            print_config_usage(app_name);
+            2
+        }
+        (other, _) => {
+            crate::cx_eprintln!("{app_name} config: unknown subcommand '{other}'");
+            print_config_usage(app_name);
             2
         }
     }
@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn on_shutdown_signal(_sig: libc::c_int) {
+    SHUTDOWN.store(true, Ordering::SeqCst);
+}
+
+/// Route SIGINT/SIGTERM into a flag so long-running loops can exit cleanly.
+pub fn install_shutdown_handler() {
+    #[cfg(unix)]
+    {
+        let handler = on_shutdown_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only performs an atomic store, which is async-signal-safe.
+        unsafe {
+            libc::signal(libc::SIGINT, handler);
+            libc::signal(libc::SIGTERM, handler);
+        }
+    }
+}
+
+/// Routes SIGINT/SIGTERM into the shutdown flag while alive, then restores the
+/// previous handlers so prompts and blocking reads keep the default Ctrl-C.
+pub struct ShutdownGuard {
+    #[cfg(unix)]
+    previous: [libc::sighandler_t; 2],
+}
+
+impl ShutdownGuard {
+    pub fn install() -> Self {
+        #[cfg(unix)]
+        {
+            let handler = on_shutdown_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            // SAFETY: same handler as `install_shutdown_handler`; the previous
+            // dispositions are restored on drop.
+            let previous = unsafe {
+                [
+                    libc::signal(libc::SIGINT, handler),
+                    libc::signal(libc::SIGTERM, handler),
+                ]
+            };
+            Self { previous }
+        }
+        #[cfg(not(unix))]
+        Self {}
+    }
+}
+
+impl Drop for ShutdownGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        // SAFETY: restores the handlers returned by `signal` in `install`.
+        unsafe {
+            libc::signal(libc::SIGINT, self.previous[0]);
+            libc::signal(libc::SIGTERM, self.previous[1]);
+        }
+    }
+}
+
+pub fn shutdown_requested() -> bool {
+    SHUTDOWN.load(Ordering::SeqCst)
+}
+
+/// Sleep for `total`, waking early when shutdown is requested.
+/// Returns `false` when interrupted.
+pub fn sleep_unless_shutdown(total: Duration) -> bool {
+    let deadline = Instant::now() + total;
+    while Instant::now() < deadline {
+        if shutdown_requested() {
+            return false;
+        }
+        let left = deadline.saturating_duration_since(Instant::now());
+        std::thread::sleep(left.min(Duration::from_millis(100)));
+    }
+    !shutdown_requested()
+}
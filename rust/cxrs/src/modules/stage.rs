@@ -0,0 +1,539 @@
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crate::capture::{budget_config_from_env, clip_text_with_config};
+use crate::changelog::git_output;
+use crate::contract_versions::STAGE_JSON_CONTRACT_VERSION;
+use crate::error::{EXIT_OK, EXIT_USAGE, format_error, print_runtime_error, print_usage_error};
+use crate::paths::{repo_root, resolve_stage_plan_file};
+use crate::process::run_command_with_stdin_output_with_timeout;
+use crate::schema::load_schema_for_tool;
+use crate::structured_cmds::{ExecuteTaskFn, parse_schema_json, state_bool};
+use crate::structured_fixrun_exec::{ConfirmAnswer, ask_confirm, confirm_supported};
+use crate::types::{LlmOutputKind, TaskInput, TaskSpec};
+use crate::verbosity::warn;
+
+const STAGE_USAGE: &str = "stage [--json] | stage --apply N [--yes]";
+
+#[derive(Debug, PartialEq, Eq)]
+enum StageArgs {
+    Propose { json_out: bool },
+    Apply { group: usize, yes: bool },
+}
+
+fn parse_args(args: &[String]) -> Result<StageArgs, ()> {
+    let mut json_out = false;
+    let mut yes = false;
+    let mut group = None;
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--json" => json_out = true,
+            "--yes" => yes = true,
+            "--apply" => {
+                let n = it.next().and_then(|v| v.parse::<usize>().ok());
+                group = Some(n.filter(|n| *n > 0).ok_or(())?);
+            }
+            _ => return Err(()),
+        }
+    }
+    match group {
+        Some(group) if !json_out => Ok(StageArgs::Apply { group, yes }),
+        None if !yes => Ok(StageArgs::Propose { json_out }),
+        _ => Err(()),
+    }
+}
+
+/// Paths with unstaged or untracked changes, from `git status --porcelain -z`.
+/// Rename/copy entries carry their source path as an extra field, skipped here.
+fn unstaged_paths(porcelain: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut fields = porcelain.split('\0');
+    while let Some(entry) = fields.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (xy, path) = entry.split_at(3);
+        let mut codes = xy.chars();
+        let (x, y) = (codes.next().unwrap_or(' '), codes.next().unwrap_or(' '));
+        if matches!(x, 'R' | 'C') {
+            fields.next();
+        }
+        if y != ' ' || xy.starts_with("??") {
+            out.push(path.to_string());
+        }
+    }
+    out
+}
+
+/// `@@ -1,3 +1,4 @@ fn name` -> `@@ -1,3 +1,4 @@`; the trailing context
+/// varies with how the model copies it.
+fn hunk_key(header: &str) -> Option<String> {
+    let rest = header.trim().strip_prefix("@@")?;
+    let end = rest.find("@@")?;
+    Some(format!("@@{}@@", &rest[..end]))
+}
+
+struct FileDiff {
+    header: String,
+    hunks: Vec<(String, String)>,
+}
+
+/// Split a single-file `git diff` into its header and `(hunk key, hunk text)` pairs.
+fn split_hunks(diff: &str) -> FileDiff {
+    let mut header = String::new();
+    let mut hunks: Vec<(String, String)> = Vec::new();
+    for line in diff.split_inclusive('\n') {
+        if let Some(key) = line.starts_with("@@").then(|| hunk_key(line)).flatten() {
+            hunks.push((key, line.to_string()));
+        } else if let Some((_, text)) = hunks.last_mut() {
+            text.push_str(line);
+        } else {
+            header.push_str(line);
+        }
+    }
+    FileDiff { header, hunks }
+}
+
+fn quote_path(p: &str) -> String {
+    if !p.is_empty()
+        && p.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-/+@=:,".contains(c))
+    {
+        p.to_string()
+    } else {
+        format!("'{}'", p.replace('\'', r"'\''"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Group {
+    subject: String,
+    rationale: String,
+    files: Vec<String>,
+    hunks: Vec<(String, String)>,
+}
+
+impl Group {
+    fn hunk_files(&self) -> BTreeSet<&str> {
+        self.hunks.iter().map(|(f, _)| f.as_str()).collect()
+    }
+
+    fn to_json(&self) -> Value {
+        let hunks: Vec<Value> = self
+            .hunks
+            .iter()
+            .map(|(file, header)| json!({"file": file, "header": header}))
+            .collect();
+        json!({
+            "subject": self.subject,
+            "rationale": self.rationale,
+            "files": self.files,
+            "hunks": hunks,
+            "commands": self.commands(),
+        })
+    }
+
+    fn from_json(v: &Value) -> Self {
+        let s = |k: &str| v.get(k).and_then(Value::as_str).unwrap_or("").to_string();
+        let files = v
+            .get("files")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        let hunks = v
+            .get("hunks")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|h| {
+                let file = h.get("file").and_then(Value::as_str)?;
+                let header = h.get("header").and_then(Value::as_str)?;
+                Some((file.to_string(), header.to_string()))
+            })
+            .collect();
+        Group {
+            subject: s("subject"),
+            rationale: s("rationale"),
+            files,
+            hunks,
+        }
+    }
+
+    /// Shell commands that stage this group by hand.
+    fn commands(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        if !self.files.is_empty() {
+            let paths: Vec<String> = self.files.iter().map(|p| quote_path(p)).collect();
+            out.push(format!("git add -- {}", paths.join(" ")));
+        }
+        for file in self.hunk_files() {
+            let headers: Vec<&str> = self
+                .hunks
+                .iter()
+                .filter(|(f, _)| f == file)
+                .map(|(_, h)| h.as_str())
+                .collect();
+            out.push(format!(
+                "git add -p -- {}  # hunks: {}",
+                quote_path(file),
+                headers.join(", ")
+            ));
+        }
+        out
+    }
+}
+
+/// Keep only paths and hunks that exist in the worktree; a file listed whole
+/// takes precedence over its hunks, and each path lands in one group.
+fn sanitize_groups(v: &Value, changed: &[String], diff: &str) -> (Vec<Group>, Vec<String>) {
+    let known: BTreeSet<&str> = changed.iter().map(String::as_str).collect();
+    let mut hunks_by_file: Vec<(String, BTreeSet<String>)> = Vec::new();
+    for chunk in diff.split("\ndiff --git ") {
+        let Some(path) = chunk.lines().find_map(|l| {
+            l.strip_prefix("+++ b/")
+                .or_else(|| l.strip_prefix("--- a/"))
+        }) else {
+            continue;
+        };
+        let keys = split_hunks(chunk)
+            .hunks
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        hunks_by_file.push((path.to_string(), keys));
+    }
+    let mut whole: BTreeSet<String> = BTreeSet::new();
+    let mut groups = Vec::new();
+    for raw in v
+        .get("groups")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let mut g = Group::from_json(raw);
+        g.files.retain(|f| {
+            let ok = known.contains(f.as_str()) && !whole.contains(f);
+            if !known.contains(f.as_str()) {
+                warn(&format!("cxrs stage: dropping unknown path '{f}'"));
+            }
+            if ok {
+                whole.insert(f.clone());
+            }
+            ok
+        });
+        groups.push(g);
+    }
+    let mut hunk_seen: BTreeSet<(String, String)> = BTreeSet::new();
+    for g in &mut groups {
+        g.hunks = std::mem::take(&mut g.hunks)
+            .into_iter()
+            .filter_map(|(file, header)| {
+                let key = hunk_key(&header)?;
+                let exists = hunks_by_file
+                    .iter()
+                    .any(|(p, keys)| *p == file && keys.contains(&key));
+                if !exists {
+                    warn(&format!("cxrs stage: dropping unknown hunk {file} {key}"));
+                    return None;
+                }
+                if whole.contains(&file) || !hunk_seen.insert((file.clone(), key.clone())) {
+                    return None;
+                }
+                Some((file, key))
+            })
+            .collect();
+    }
+    groups.retain(|g| !g.files.is_empty() || !g.hunks.is_empty());
+    let touched: BTreeSet<&str> = groups
+        .iter()
+        .flat_map(|g| g.hunk_files())
+        .chain(whole.iter().map(String::as_str))
+        .collect();
+    let unassigned = changed
+        .iter()
+        .filter(|p| !touched.contains(p.as_str()))
+        .cloned()
+        .collect();
+    (groups, unassigned)
+}
+
+fn git_worktree_state() -> Result<(Vec<String>, String), String> {
+    let porcelain = git_output(&["status", "--porcelain=v1", "-z", "-uall"])?;
+    let diff = git_output(&["diff", "--no-color"])?;
+    Ok((unstaged_paths(&porcelain), diff))
+}
+
+fn print_plan(groups: &[Group], unassigned: &[String]) {
+    println!("== cxrs stage ==");
+    for (i, g) in groups.iter().enumerate() {
+        println!();
+        println!("[{}] {}", i + 1, g.subject);
+        if !g.rationale.trim().is_empty() {
+            println!("    why: {}", g.rationale.trim());
+        }
+        for c in g.commands() {
+            println!("    {c}");
+        }
+    }
+    if !unassigned.is_empty() {
+        println!();
+        println!("unassigned: {}", unassigned.join(", "));
+    }
+    println!();
+    println!("apply one: cxrs stage --apply N");
+}
+
+fn propose(json_out: bool, execute_task: ExecuteTaskFn) -> i32 {
+    let (changed, diff) = match git_worktree_state() {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("stage", &e),
+    };
+    if changed.is_empty() {
+        println!("Nothing to stage: no unstaged or untracked changes.");
+        return EXIT_OK;
+    }
+    let (clipped, capture_stats) = clip_text_with_config(&diff, &budget_config_from_env());
+    let schema = match load_schema_for_tool("cxrs_stage") {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("stage", &e),
+    };
+    let style = if state_bool("preferences.conventional_commits", true) {
+        "Conventional Commits (`type(scope): description`)"
+    } else {
+        "a short imperative sentence"
+    };
+    let task_input = format!(
+        "Split these uncommitted changes into logical commits.\n\
+Put every changed path in exactly one group, using the paths exactly as listed. \
+List a file under `files` to stage it whole, or under `hunks` with the exact \
+`@@ ... @@` header to stage part of it. Give each group a commit subject in \
+{style}, at most 72 characters, and a one-line rationale.\n\n\
+CHANGED PATHS (unstaged or untracked):\n{}\n\nGIT DIFF:\n{clipped}",
+        changed.join("\n")
+    );
+    let result = execute_task(TaskSpec {
+        command_name: "cxrs_stage".to_string(),
+        input: TaskInput::Prompt(task_input.clone()),
+        output_kind: LlmOutputKind::SchemaJson,
+        schema: Some(schema),
+        schema_task_input: Some(task_input),
+        logging_enabled: true,
+        capture_override: Some(capture_stats),
+        prompt_template: None,
+        stream: false,
+    })
+    .and_then(|r| parse_schema_json(&r));
+    let value = match result {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("stage", &e),
+    };
+    let (groups, unassigned) = sanitize_groups(&value, &changed, &diff);
+    if groups.is_empty() {
+        return print_runtime_error("stage", "no usable groups in the model response");
+    }
+    let plan_file = resolve_stage_plan_file();
+    let doc = json!({
+        "contract_version": STAGE_JSON_CONTRACT_VERSION,
+        "groups": groups.iter().map(Group::to_json).collect::<Vec<_>>(),
+        "unassigned": unassigned,
+        "plan_file": plan_file.as_ref().map(|p| p.display().to_string()),
+    });
+    if let Some(p) = &plan_file
+        && let Err(e) = write_plan(p, &doc)
+    {
+        warn(&format!("cxrs stage: could not save plan: {e}"));
+    }
+    if json_out {
+        match serde_json::to_string_pretty(&doc) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return print_runtime_error("stage", &format!("render failure: {e}")),
+        }
+    } else {
+        print_plan(&groups, &unassigned);
+    }
+    EXIT_OK
+}
+
+fn write_plan(path: &Path, doc: &Value) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(doc).map_err(|e| e.to_string())?;
+    fs::write(path, text + "\n").map_err(|e| e.to_string())
+}
+
+fn load_group(n: usize) -> Result<Group, String> {
+    let path = resolve_stage_plan_file().ok_or("not inside a git repository")?;
+    let text = fs::read_to_string(&path)
+        .map_err(|_| "no saved plan; run `cxrs stage` first".to_string())?;
+    let doc: Value = serde_json::from_str(&text)
+        .map_err(|e| format!("invalid plan file {}: {e}", path.display()))?;
+    let groups = doc
+        .get("groups")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    groups
+        .get(n - 1)
+        .map(Group::from_json)
+        .ok_or_else(|| format!("no group {n} in the saved plan ({} group(s))", groups.len()))
+}
+
+/// Header plus the selected hunks of `file`, for `git apply --cached`.
+fn partial_patch(file: &str, keys: &[&str]) -> Result<Option<String>, String> {
+    let diff = git_output(&["diff", "--no-color", "--", file])?;
+    let parsed = split_hunks(&diff);
+    let picked: Vec<&str> = parsed
+        .hunks
+        .iter()
+        .filter(|(k, _)| keys.contains(&k.as_str()))
+        .map(|(_, text)| text.as_str())
+        .collect();
+    if picked.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(format!("{}{}", parsed.header, picked.concat())))
+}
+
+fn apply_patch(root: &Path, patch: &str) -> Result<(), String> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(root).args(["apply", "--cached", "-"]);
+    let out = run_command_with_stdin_output_with_timeout(cmd, patch, "git apply")?;
+    if !out.status.success() {
+        return Err(format!(
+            "git apply failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+fn apply(n: usize, yes: bool) -> i32 {
+    let mut group = match load_group(n) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("stage", &e),
+    };
+    let Some(root) = repo_root() else {
+        return print_runtime_error("stage", "not inside a git repository");
+    };
+    let (changed, _) = match git_worktree_state() {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("stage", &e),
+    };
+    group.files.retain(|f| changed.contains(f));
+    let mut patches = Vec::new();
+    for file in group.hunk_files() {
+        let keys: Vec<&str> = group
+            .hunks
+            .iter()
+            .filter(|(f, _)| f == file)
+            .map(|(_, k)| k.as_str())
+            .collect();
+        match partial_patch(file, &keys) {
+            Ok(Some(p)) => patches.push(p),
+            Ok(None) => {}
+            Err(e) => return print_runtime_error("stage", &e),
+        }
+    }
+    if group.files.is_empty() && patches.is_empty() {
+        return print_runtime_error(
+            "stage",
+            &format!("group {n} has no pending changes; run `cxrs stage` again"),
+        );
+    }
+    if !yes {
+        if !confirm_supported() {
+            crate::cx_eprintln!(
+                "{}",
+                format_error(
+                    "stage",
+                    "--apply requires an interactive terminal to confirm (pass --yes)"
+                )
+            );
+            return EXIT_USAGE;
+        }
+        let answer = ask_confirm(&mut io::stdin().lock(), &group.commands().join(" && "));
+        if !matches!(answer, ConfirmAnswer::Yes | ConfirmAnswer::All) {
+            println!("Not staged.");
+            return EXIT_OK;
+        }
+    }
+    if !group.files.is_empty() {
+        let mut args = vec!["-C", root.to_str().unwrap_or("."), "add", "--"];
+        args.extend(group.files.iter().map(String::as_str));
+        if let Err(e) = git_output(&args) {
+            return print_runtime_error("stage", &e);
+        }
+    }
+    for p in &patches {
+        if let Err(e) = apply_patch(&root, p) {
+            return print_runtime_error("stage", &e);
+        }
+    }
+    println!("staged group {n}: {}", group.subject);
+    println!("next: git commit -m {}", quote_path(&group.subject));
+    EXIT_OK
+}
+
+/// `stage`: ask the model to split the worktree into commit-sized groups and
+/// print how to stage each; `stage --apply N` stages one from the saved plan.
+pub fn cmd_stage(args: &[String], execute_task: ExecuteTaskFn) -> i32 {
+    match parse_args(args) {
+        Ok(StageArgs::Propose { json_out }) => propose(json_out, execute_task),
+        Ok(StageArgs::Apply { group, yes }) => apply(group, yes),
+        Err(()) => print_usage_error("stage", STAGE_USAGE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porcelain_and_hunks_parse() {
+        let porcelain =
+            " M src/a.rs\0M  staged.rs\0R  new.rs\0old.rs\0RM moved.rs\0was.rs\0?? notes/x y.md\0";
+        assert_eq!(
+            unstaged_paths(porcelain),
+            vec!["src/a.rs", "moved.rs", "notes/x y.md"]
+        );
+        assert_eq!(
+            hunk_key("@@ -1,3 +1,4 @@ fn main() {").as_deref(),
+            Some("@@ -1,3 +1,4 @@")
+        );
+        assert_eq!(hunk_key("not a hunk"), None);
+        let diff =
+            "diff --git a/f b/f\n--- a/f\n+++ b/f\n@@ -1 +1 @@\n-a\n+b\n@@ -9 +9 @@ x\n-c\n+d\n";
+        let parsed = split_hunks(diff);
+        assert!(parsed.header.ends_with("+++ b/f\n"));
+        assert_eq!(parsed.hunks.len(), 2);
+        assert_eq!(parsed.hunks[1].0, "@@ -9 +9 @@");
+        assert_eq!(parsed.hunks[1].1, "@@ -9 +9 @@ x\n-c\n+d\n");
+        assert_eq!(quote_path("src/a.rs"), "src/a.rs");
+        assert_eq!(quote_path("it's.md"), r"'it'\''s.md'");
+    }
+
+    #[test]
+    fn apply_and_json_are_exclusive() {
+        let a = |v: &[&str]| parse_args(&v.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+        assert_eq!(
+            a(&["--apply", "2", "--yes"]),
+            Ok(StageArgs::Apply {
+                group: 2,
+                yes: true
+            })
+        );
+        assert_eq!(a(&["--json"]), Ok(StageArgs::Propose { json_out: true }));
+        assert!(a(&["--apply", "0"]).is_err());
+        assert!(a(&["--apply", "1", "--json"]).is_err());
+        assert!(a(&["--yes"]).is_err());
+    }
+}
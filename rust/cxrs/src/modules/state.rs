@@ -1,5 +1,6 @@
-use crate::paths::{ensure_parent_dir, resolve_state_file};
+use crate::paths::{ensure_parent_dir, global_state_file, repo_state_file, resolve_state_file};
 use serde_json::{Value, json};
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -23,13 +24,11 @@ pub fn read_state_value() -> Option<Value> {
     {
         return Some(v);
     }
-    let state_file = resolve_state_file()?;
-    if !state_file.exists() {
-        return None;
+    let mut layers = read_state_layers().into_iter().map(|(_, v)| v);
+    let mut parsed = layers.next()?;
+    for overlay in layers {
+        merge_state(&mut parsed, &overlay);
     }
-    let mut s = String::new();
-    File::open(state_file).ok()?.read_to_string(&mut s).ok()?;
-    let parsed = serde_json::from_str::<Value>(&s).ok()?;
     if std::env::var("CX_NO_CACHE").ok().as_deref() != Some("1")
         && let Ok(mut g) = STATE_CACHE.get_or_init(|| Mutex::new(None)).lock()
     {
@@ -38,7 +37,91 @@ pub fn read_state_value() -> Option<Value> {
     Some(parsed)
 }
 
-fn default_state_value() -> Value {
+/// State files, lowest precedence first: global `~/.codex/state.json`, then
+/// the repo `.codex/state.json` when inside a repo.
+pub fn state_layer_files() -> Vec<(&'static str, PathBuf)> {
+    let global = global_state_file();
+    let repo = repo_state_file().filter(|p| Some(p) != global.as_ref());
+    [("global", global), ("repo", repo)]
+        .into_iter()
+        .filter_map(|(label, path)| path.map(|p| (label, p)))
+        .collect()
+}
+
+/// Parsed state layers; missing or unparseable files are skipped.
+pub fn read_state_layers() -> Vec<(&'static str, Value)> {
+    state_layer_files()
+        .into_iter()
+        .filter_map(|(label, path)| {
+            let raw = fs::read_to_string(path).ok()?;
+            serde_json::from_str::<Value>(&raw).ok().map(|v| (label, v))
+        })
+        .collect()
+}
+
+/// Null leaves and empty objects are placeholders and never mask a lower layer.
+fn masks_lower_layer(v: &Value) -> bool {
+    !(v.is_null() || v.as_object().is_some_and(|o| o.is_empty()))
+}
+
+/// Deep-merge `overlay` into `base`, overlay winning except for placeholders.
+pub fn merge_state(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Object(b), Value::Object(o)) => {
+            for (k, v) in o {
+                match b.get_mut(k) {
+                    Some(slot) => merge_state(slot, v),
+                    None => {
+                        b.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+        }
+        (b, o) if masks_lower_layer(o) || b.is_null() => *b = o.clone(),
+        _ => {}
+    }
+}
+
+fn flatten_leaves(prefix: &str, v: &Value, out: &mut Vec<(String, Value)>) {
+    match v.as_object() {
+        Some(obj) if !obj.is_empty() => {
+            for (k, child) in obj {
+                let key = if prefix.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{prefix}.{k}")
+                };
+                flatten_leaves(&key, child, out);
+            }
+        }
+        _ => out.push((prefix.to_string(), v.clone())),
+    }
+}
+
+/// Merged leaf keys with the layer that supplied each value.
+pub fn state_key_origins(
+    layers: &[(&'static str, Value)],
+) -> BTreeMap<String, (Value, &'static str)> {
+    let mut out: BTreeMap<String, (Value, &'static str)> = BTreeMap::new();
+    for (label, layer) in layers {
+        let mut leaves = Vec::new();
+        flatten_leaves("", layer, &mut leaves);
+        for (key, leaf) in leaves {
+            let child = format!("{key}.");
+            if !masks_lower_layer(&leaf) {
+                if !out.keys().any(|k| *k == key || k.starts_with(&child)) {
+                    out.insert(key, (leaf, label));
+                }
+                continue;
+            }
+            out.retain(|k, _| !k.starts_with(&child) && !key.starts_with(&format!("{k}.")));
+            out.insert(key, (leaf, label));
+        }
+    }
+    out
+}
+
+pub(crate) fn default_state_value() -> Value {
     json!({
         "preferences": {
             "llm_backend": Value::Null,
@@ -58,20 +141,25 @@ fn default_state_value() -> Value {
 pub fn ensure_state_value() -> Result<(PathBuf, Value), String> {
     let state_file =
         resolve_state_file().ok_or_else(|| "unable to resolve state file".to_string())?;
+    let value = ensure_state_value_at(&state_file)?;
+    Ok((state_file, value))
+}
+
+/// Read one state layer, initializing it with the default state when absent.
+pub fn ensure_state_value_at(state_file: &Path) -> Result<Value, String> {
     if !state_file.exists() {
-        ensure_parent_dir(&state_file)?;
+        ensure_parent_dir(state_file)?;
         let initial = default_state_value();
-        write_json_atomic(&state_file, &initial)?;
-        return Ok((state_file, initial));
+        write_json_atomic(state_file, &initial)?;
+        return Ok(initial);
     }
     let mut s = String::new();
-    File::open(&state_file)
+    File::open(state_file)
         .map_err(|e| format!("cannot open {}: {e}", state_file.display()))?
         .read_to_string(&mut s)
         .map_err(|e| format!("cannot read {}: {e}", state_file.display()))?;
-    let value = serde_json::from_str::<Value>(&s)
-        .map_err(|e| format!("invalid JSON in {}: {e}", state_file.display()))?;
-    Ok((state_file, value))
+    serde_json::from_str::<Value>(&s)
+        .map_err(|e| format!("invalid JSON in {}: {e}", state_file.display()))
 }
 
 pub fn write_json_atomic(path: &Path, value: &Value) -> Result<(), String> {
@@ -203,4 +291,29 @@ mod tests {
         set_value_at_path(&mut v, "a.b.c", json!(7)).expect("set nested path");
         assert_eq!(value_at_path(&v, "a.b.c"), Some(&json!(7)));
     }
+
+    #[test]
+    fn repo_layer_overrides_global_except_placeholders() {
+        let global = json!({"preferences": {"llm_backend": "ollama", "ollama_model": "llama3"}, "alert_overrides": {"x": 1}});
+        let repo = json!({"preferences": {"llm_backend": "codex", "ollama_model": null}, "alert_overrides": {}});
+        let mut merged = global.clone();
+        merge_state(&mut merged, &repo);
+        assert_eq!(
+            value_at_path(&merged, "preferences.llm_backend"),
+            Some(&json!("codex"))
+        );
+        assert_eq!(
+            value_at_path(&merged, "preferences.ollama_model"),
+            Some(&json!("llama3"))
+        );
+        assert_eq!(value_at_path(&merged, "alert_overrides.x"), Some(&json!(1)));
+        let origins = state_key_origins(&[("global", global), ("repo", repo)]);
+        assert_eq!(origins["preferences.llm_backend"], (json!("codex"), "repo"));
+        assert_eq!(
+            origins["preferences.ollama_model"],
+            (json!("llama3"), "global")
+        );
+        assert_eq!(origins["alert_overrides.x"].1, "global");
+        assert!(!origins.contains_key("alert_overrides"));
+    }
 }
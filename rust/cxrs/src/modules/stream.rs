@@ -0,0 +1,172 @@
+use serde_json::Value;
+use std::io::{self, Write};
+
+use crate::progress;
+use crate::runtime::llm_backend;
+use crate::token_estimate::{TokenizerKind, estimate_tokens};
+use crate::types::LlmOutputKind;
+
+/// What a streamed run prints while the backend is still producing output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamMode {
+    /// Model text as-is (`cx`, `cxol`).
+    Text,
+    /// Raw backend JSONL events (`cxj`).
+    Jsonl,
+    /// Only `agent_message` text, one message per line (`cxo`).
+    AgentText,
+}
+
+impl StreamMode {
+    pub fn for_kind(kind: LlmOutputKind) -> Option<Self> {
+        match kind {
+            LlmOutputKind::Plain => Some(StreamMode::Text),
+            LlmOutputKind::Jsonl => Some(StreamMode::Jsonl),
+            LlmOutputKind::AgentText => Some(StreamMode::AgentText),
+            LlmOutputKind::SchemaJson => None,
+        }
+    }
+}
+
+/// Forwards backend output to stdout as it arrives. Adapters feed it either
+/// codex `--json` bytes ([`StreamPrinter::jsonl`]) or plain model text
+/// ([`StreamPrinter::text`]); the full transcript is still assembled by the
+/// caller for usage extraction and logging. Model text is also counted for
+/// `llm_tokens` progress events.
+pub struct StreamPrinter {
+    mode: StreamMode,
+    line: Vec<u8>,
+    ends_with_newline: bool,
+    /// `false` for a printer that only counts tokens for progress.
+    echo: bool,
+    tokenizer: TokenizerKind,
+}
+
+impl StreamPrinter {
+    pub fn new(mode: StreamMode) -> Self {
+        Self {
+            mode,
+            line: Vec::new(),
+            ends_with_newline: true,
+            echo: true,
+            tokenizer: TokenizerKind::for_backend(&llm_backend()),
+        }
+    }
+
+    /// A printer that prints nothing and only feeds progress token counts, for
+    /// runs without `--stream`.
+    pub fn counting(mode: StreamMode) -> Self {
+        Self {
+            echo: false,
+            ..Self::new(mode)
+        }
+    }
+
+    pub fn mode(&self) -> StreamMode {
+        self.mode
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if bytes.is_empty() || !self.echo {
+            return;
+        }
+        progress::clear_line();
+        let mut out = io::stdout().lock();
+        let _ = out.write_all(bytes);
+        let _ = out.flush();
+        self.ends_with_newline = bytes.ends_with(b"\n");
+    }
+
+    /// Plain model text (codex `exec -` stdout, ollama tokens).
+    pub fn text(&mut self, chunk: &[u8]) {
+        self.count(&String::from_utf8_lossy(chunk));
+        self.write(chunk);
+    }
+
+    fn count(&self, text: &str) {
+        if progress::enabled() {
+            progress::tokens_streamed(estimate_tokens(text, self.tokenizer), self.echo);
+        }
+    }
+
+    /// Bytes of codex `exec --json` output; in agent-text mode only completed
+    /// `agent_message` items are printed. Events are split into lines in
+    /// either mode so agent text can be counted.
+    pub fn jsonl(&mut self, chunk: &[u8]) {
+        if self.mode != StreamMode::AgentText {
+            self.write(chunk);
+        }
+        for &b in chunk {
+            if b == b'\n' {
+                let line = std::mem::take(&mut self.line);
+                self.agent_line(&line);
+            } else {
+                self.line.push(b);
+            }
+        }
+    }
+
+    fn agent_line(&mut self, line: &[u8]) {
+        if let Some(text) = agent_message_text(&String::from_utf8_lossy(line)) {
+            self.count(&text);
+            if self.mode == StreamMode::AgentText {
+                self.write(format!("{text}\n").as_bytes());
+            }
+        }
+    }
+
+    /// Flush a trailing partial event and end agent text with a newline, as the
+    /// non-streaming `cxo` output does.
+    pub fn finish(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        self.agent_line(&line);
+        if self.mode == StreamMode::AgentText && !self.ends_with_newline {
+            self.write(b"\n");
+        }
+    }
+}
+
+fn agent_message_text(line: &str) -> Option<String> {
+    let v: Value = serde_json::from_str(line.trim()).ok()?;
+    if v.get("type").and_then(Value::as_str) != Some("item.completed") {
+        return None;
+    }
+    let item = v.get("item")?;
+    if item.get("type").and_then(Value::as_str) != Some("agent_message") {
+        return None;
+    }
+    item.get("text").and_then(Value::as_str).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_text_is_pulled_from_completed_events_only() {
+        assert_eq!(
+            agent_message_text(
+                r#"{"type":"item.completed","item":{"type":"agent_message","text":"hi"}}"#
+            )
+            .as_deref(),
+            Some("hi")
+        );
+        assert_eq!(
+            agent_message_text(
+                r#"{"type":"item.completed","item":{"type":"reasoning","text":"x"}}"#
+            ),
+            None
+        );
+        assert_eq!(agent_message_text(r#"{"type":"turn.completed"}"#), None);
+        assert_eq!(agent_message_text("not json"), None);
+    }
+
+    #[test]
+    fn split_events_are_buffered_until_the_newline() {
+        let mut p = StreamPrinter::new(StreamMode::AgentText);
+        p.jsonl(br#"{"type":"item.completed","item":{"type":"agent_"#);
+        assert!(!p.line.is_empty());
+        p.jsonl(b"message\",\"text\":\"ok\"}}\n");
+        assert!(p.line.is_empty());
+    }
+}
@@ -1,16 +1,27 @@
 use serde_json::Value;
 
-use crate::capture::run_system_command_capture;
-use crate::error::{EXIT_OK, EXIT_RUNTIME, format_error};
-use crate::schema::load_schema;
+use crate::capture::{add_diff_focus, run_system_command_capture};
+use crate::changelog::record_commitjson;
+use crate::commit_context::{infer_scope, render_stats};
+use crate::commit_lint::{LintReport, lint_commit, lint_feedback, wrap_bullet};
+use crate::error::{EXIT_OK, EXIT_RUNTIME, failure_exit, format_error, print_usage_error};
+use crate::prompt_templates::render_prompt;
+use crate::schema::load_schema_for_tool;
 use crate::state::{read_state_value, value_at_path};
-use crate::types::{ExecutionResult, LlmOutputKind, TaskInput, TaskSpec};
+use crate::structured_next_run::run_next_suggestions;
+use crate::types::{
+    CaptureStats, ExecutionResult, LlmOutputKind, LoadedSchema, TaskInput, TaskSpec,
+};
+use crate::vcs::{VcsKind, commit_file_stats, current_vcs, diff_command, empty_diff_message};
+use crate::verbosity::{Verbosity, diag, warn};
+
+const NEXT_USAGE: &str = "next [--run [--unsafe]] <command> [args...]";
 
 pub type ExecuteTaskFn = fn(TaskSpec) -> Result<ExecutionResult, String>;
 pub use crate::structured_fixrun::cmd_fix_run;
 pub use crate::structured_replay::cmd_replay;
 
-fn parse_commands_array(raw: &str) -> Result<Vec<String>, String> {
+pub(crate) fn parse_commands_array(raw: &str) -> Result<Vec<String>, String> {
     let v: Value = serde_json::from_str(raw).map_err(|e| format!("invalid JSON: {e}"))?;
     let arr = v
         .get("commands")
@@ -28,7 +39,7 @@ fn parse_commands_array(raw: &str) -> Result<Vec<String>, String> {
     Ok(out)
 }
 
-fn render_bullets(value: Option<&Value>) -> Vec<String> {
+pub(crate) fn render_bullets(value: Option<&Value>) -> Vec<String> {
     match value {
         Some(Value::Array(items)) => items
             .iter()
@@ -46,7 +57,7 @@ fn render_bullets(value: Option<&Value>) -> Vec<String> {
     }
 }
 
-fn print_diffsum_human(v: &Value) {
+pub(crate) fn print_diffsum_human(v: &Value) {
     let title = v.get("title").and_then(Value::as_str).unwrap_or("");
     let summary = render_bullets(v.get("summary"));
     let risks = render_bullets(v.get("risk_edge_cases"));
@@ -82,7 +93,7 @@ fn print_diffsum_human(v: &Value) {
     }
 }
 
-fn state_bool(path: &str, default: bool) -> bool {
+pub(crate) fn state_bool(path: &str, default: bool) -> bool {
     read_state_value()
         .as_ref()
         .and_then(|v| value_at_path(v, path))
@@ -99,13 +110,17 @@ fn state_string(path: &str, default: &str) -> String {
         .to_string()
 }
 
-fn capture_git_diff(
+fn capture_vcs_diff(
+    tool: &str,
     cmd: &[String],
     empty_msg: &str,
 ) -> Result<(String, crate::types::CaptureStats), String> {
-    let (diff_out, status, capture_stats) = run_system_command_capture(cmd)?;
+    let (diff_out, status, capture_stats) = run_system_command_capture(tool, cmd)?;
     if status != 0 {
-        return Err(format!("git diff failed with status {status}"));
+        return Err(format!(
+            "{} failed with status {status}",
+            cmd[..2.min(cmd.len())].join(" ")
+        ));
     }
     if diff_out.trim().is_empty() {
         return Err(empty_msg.to_string());
@@ -113,7 +128,7 @@ fn capture_git_diff(
     Ok((diff_out, capture_stats))
 }
 
-fn parse_schema_json(result: &ExecutionResult) -> Result<Value, String> {
+pub(crate) fn parse_schema_json(result: &ExecutionResult) -> Result<Value, String> {
     if result.schema_valid == Some(false) {
         return Err(format!(
             "schema validation failed; quarantine_id={}; raw={}",
@@ -124,16 +139,15 @@ fn parse_schema_json(result: &ExecutionResult) -> Result<Value, String> {
     serde_json::from_str(&result.stdout).map_err(|e| format!("invalid JSON: {e}"))
 }
 
-fn generate_commitjson_value(execute_task: ExecuteTaskFn) -> Result<Value, String> {
-    let (diff_out, capture_stats) = capture_git_diff(
-        &[
-            "git".to_string(),
-            "diff".to_string(),
-            "--staged".to_string(),
-            "--no-color".to_string(),
-        ],
-        "no staged changes. run: git add -p",
-    )?;
+fn generate_commitjson_value(execute_task: ExecuteTaskFn) -> Result<(Value, LintReport), String> {
+    let vcs = current_vcs();
+    let empty_msg = if vcs == VcsKind::Git {
+        "no staged changes. run: git add -p"
+    } else {
+        empty_diff_message(vcs, true)
+    };
+    let (diff_out, capture_stats) =
+        capture_vcs_diff("cxrs_commitjson", &diff_command(vcs, true)?, empty_msg)?;
 
     let conventional = state_bool("preferences.conventional_commits", true);
     let style_hint = if conventional {
@@ -141,26 +155,69 @@ fn generate_commitjson_value(execute_task: ExecuteTaskFn) -> Result<Value, Strin
     } else {
         "Use concise imperative subject (non-conventional format)."
     };
-    let schema = load_schema("commitjson")?;
-    let task_input = format!(
-        "Generate a commit object from this STAGED diff.\n{style_hint}\n\nSTAGED DIFF:\n{diff_out}"
-    );
-    let result = execute_task(TaskSpec {
-        command_name: "cxrs_commitjson".to_string(),
-        input: TaskInput::Prompt(task_input.clone()),
-        output_kind: LlmOutputKind::SchemaJson,
-        schema: Some(schema.clone()),
-        schema_task_input: Some(task_input),
-        logging_enabled: true,
-        capture_override: Some(capture_stats),
-    })?;
-    let mut v = parse_schema_json(&result)?;
-    if v.get("scope").is_none()
-        && let Some(obj) = v.as_object_mut()
-    {
-        obj.insert("scope".to_string(), Value::Null);
+    let files = commit_file_stats(vcs)?;
+    let stats = render_stats(&files);
+    let inferred_scope = if conventional {
+        infer_scope(&files)
+    } else {
+        None
+    };
+    let scope_hint = inferred_scope
+        .clone()
+        .unwrap_or_else(|| "none (use null)".to_string());
+    let schema = load_schema_for_tool("cxrs_commitjson")?;
+    let schema_text = schema_text(&schema);
+    let (task_input, template) = render_prompt(
+        "commitjson",
+        &[
+            ("style", style_hint),
+            ("stats", &stats),
+            ("scope", &scope_hint),
+            ("output", &diff_out),
+            ("schema", &schema_text),
+        ],
+    )?;
+    let run = |input: String| -> Result<Value, String> {
+        let result = execute_task(TaskSpec {
+            command_name: "cxrs_commitjson".to_string(),
+            input: TaskInput::Prompt(input.clone()),
+            output_kind: LlmOutputKind::SchemaJson,
+            schema: Some(schema.clone()),
+            schema_task_input: Some(input),
+            logging_enabled: true,
+            capture_override: Some(capture_stats.clone()),
+            prompt_template: Some(template.clone()),
+            stream: false,
+        })?;
+        parse_schema_json(&result)
+    };
+    let mut v = run(task_input.clone())?;
+    let mut report = lint_commit(&mut v, conventional);
+    // One re-prompt for what auto-fixing cannot repair; keep the first
+    // answer if the retry itself fails.
+    if !report.issues.is_empty() {
+        diag(
+            Verbosity::Verbose,
+            "commit_lint_reprompt",
+            &[("issues", report.issues.len().to_string())],
+        );
+        if let Ok(mut retry) = run(format!("{task_input}{}", lint_feedback(&report.issues))) {
+            report = lint_commit(&mut retry, conventional);
+            v = retry;
+        }
     }
-    Ok(v)
+    // Fall back to the inferred scope when the model leaves it out.
+    let missing = v
+        .get("scope")
+        .is_none_or(|s| s.as_str().is_none_or(|s| s.trim().is_empty()));
+    if missing && let Some(obj) = v.as_object_mut() {
+        obj.insert(
+            "scope".to_string(),
+            inferred_scope.map_or(Value::Null, Value::String),
+        );
+    }
+    record_commitjson(&v);
+    Ok((v, report))
 }
 
 fn generate_diffsum_value(
@@ -168,33 +225,38 @@ fn generate_diffsum_value(
     staged: bool,
     execute_task: ExecuteTaskFn,
 ) -> Result<Value, String> {
-    let git_cmd = if staged {
-        vec![
-            "git".to_string(),
-            "diff".to_string(),
-            "--staged".to_string(),
-            "--no-color".to_string(),
-        ]
-    } else {
-        vec![
-            "git".to_string(),
-            "diff".to_string(),
-            "--no-color".to_string(),
-        ]
-    };
-    let empty_msg = if staged {
-        "no staged changes."
+    let vcs = current_vcs();
+    let diff_cmd = diff_command(vcs, staged)?;
+    let (diff_out, capture_stats) =
+        capture_vcs_diff(tool, &diff_cmd, empty_diff_message(vcs, staged))?;
+    let diff_label = if staged && vcs == VcsKind::Git {
+        "STAGED DIFF"
     } else {
-        "no unstaged changes."
+        "DIFF"
     };
-    let (diff_out, capture_stats) = capture_git_diff(&git_cmd, empty_msg)?;
+    diffsum_from_output(tool, diff_label, &diff_out, capture_stats, execute_task)
+}
 
+/// Summarize an already captured diff; `pipeline` feeds it the shared capture.
+pub(crate) fn diffsum_from_output(
+    tool: &str,
+    diff_label: &str,
+    diff_out: &str,
+    capture_stats: CaptureStats,
+    execute_task: ExecuteTaskFn,
+) -> Result<Value, String> {
     let pr_fmt = state_string("preferences.pr_summary_format", "standard");
-    let schema = load_schema("diffsum")?;
-    let diff_label = if staged { "STAGED DIFF" } else { "DIFF" };
-    let task_input = format!(
-        "Write a PR-ready summary of this diff.\nKeep bullets concise and actionable.\nPreferred PR summary format: {pr_fmt}\n\n{diff_label}:\n{diff_out}"
-    );
+    let schema = load_schema_for_tool(tool)?;
+    let schema_text = schema_text(&schema);
+    let (task_input, template) = render_prompt(
+        "diffsum",
+        &[
+            ("format", &pr_fmt),
+            ("label", diff_label),
+            ("output", diff_out),
+            ("schema", &schema_text),
+        ],
+    )?;
     let result = execute_task(TaskSpec {
         command_name: tool.to_string(),
         input: TaskInput::Prompt(task_input.clone()),
@@ -203,19 +265,45 @@ fn generate_diffsum_value(
         schema_task_input: Some(task_input),
         logging_enabled: true,
         capture_override: Some(capture_stats),
+        prompt_template: Some(template),
+        stream: false,
     })?;
     parse_schema_json(&result)
 }
 
-fn run_next_schema(command: &[String], execute_task: ExecuteTaskFn) -> Result<Value, String> {
-    let (captured, exit_status, capture_stats) = run_system_command_capture(command)?;
-    let schema = load_schema("next")?;
-    let task_input = format!(
-        "Based on the terminal command output below, propose the NEXT shell commands to run.\nReturn 1-6 commands in execution order.\n\nExecuted command:\n{}\nExit status: {}\n\nTERMINAL OUTPUT:\n{}",
-        command.join(" "),
-        exit_status,
-        captured
-    );
+/// Schema JSON offered to templates as the optional `{{schema}}` placeholder.
+pub(crate) fn schema_text(schema: &LoadedSchema) -> String {
+    serde_json::to_string_pretty(&schema.value).unwrap_or_default()
+}
+
+/// Returns the parsed suggestions and the execution id of the `next` run.
+fn run_next_schema(
+    command: &[String],
+    execute_task: ExecuteTaskFn,
+) -> Result<(Value, String), String> {
+    let (captured, exit_status, capture_stats) = run_system_command_capture("cxrs_next", command)?;
+    next_from_capture(command, &captured, exit_status, capture_stats, execute_task)
+}
+
+/// `next` over output captured elsewhere (`pipeline` shares one capture).
+pub(crate) fn next_from_capture(
+    command: &[String],
+    captured: &str,
+    exit_status: i32,
+    capture_stats: CaptureStats,
+    execute_task: ExecuteTaskFn,
+) -> Result<(Value, String), String> {
+    let schema = load_schema_for_tool("cxrs_next")?;
+    let schema_text = schema_text(&schema);
+    let (task_input, template) = render_prompt(
+        "next",
+        &[
+            ("command", &command.join(" ")),
+            ("status", &exit_status.to_string()),
+            ("output", captured),
+            ("schema", &schema_text),
+        ],
+    )?;
     let result = execute_task(TaskSpec {
         command_name: "cxrs_next".to_string(),
         input: TaskInput::Prompt(task_input.clone()),
@@ -224,81 +312,172 @@ fn run_next_schema(command: &[String], execute_task: ExecuteTaskFn) -> Result<Va
         schema_task_input: Some(task_input),
         logging_enabled: true,
         capture_override: Some(capture_stats),
+        prompt_template: Some(template),
+        stream: false,
     })?;
-    parse_schema_json(&result)
+    Ok((parse_schema_json(&result)?, result.execution_id))
 }
 
-pub fn cmd_next(command: &[String], execute_task: ExecuteTaskFn) -> i32 {
-    let schema_value = match run_next_schema(command, execute_task) {
+/// `--run` shows policy labels and offers a selection menu; `--unsafe` lifts the
+/// dangerous-command block for the picked suggestions.
+pub fn cmd_next(args: &[String], execute_task: ExecuteTaskFn) -> i32 {
+    let (mut run, mut unsafe_flag, mut idx) = (false, false, 0usize);
+    while let Some(flag) = args.get(idx) {
+        match flag.as_str() {
+            "--run" => run = true,
+            "--unsafe" => unsafe_flag = true,
+            _ => break,
+        }
+        idx += 1;
+    }
+    let command = &args[idx..];
+    if command.is_empty() || (unsafe_flag && !run) {
+        return print_usage_error("next", NEXT_USAGE);
+    }
+    let (schema_value, execution_id) = match run_next_schema(command, execute_task) {
         Ok(v) => v,
         Err(e) => {
             crate::cx_eprintln!("{}", format_error("next", &e));
-            return EXIT_RUNTIME;
+            return failure_exit();
         }
     };
     let commands = match parse_commands_array(&schema_value.to_string()) {
         Ok(v) => v,
         Err(reason) => {
             crate::cx_eprintln!("cxrs next: {reason}");
-            return EXIT_RUNTIME;
+            return failure_exit();
         }
     };
+    if run {
+        return run_next_suggestions(&commands, &execution_id, unsafe_flag);
+    }
     for cmd in commands {
         println!("{cmd}");
     }
     EXIT_OK
 }
 
-pub fn cmd_diffsum(staged: bool, execute_task: ExecuteTaskFn) -> i32 {
+/// Collect `--focus GLOB` flags (repeatable) for the diff reducer.
+fn parse_diffsum_focus(args: &[String]) -> Result<Vec<String>, String> {
+    let mut focus = Vec::new();
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--focus" => {
+                let glob = args.get(i + 1).ok_or("--focus requires a glob")?;
+                focus.push(glob.clone());
+                i += 1;
+            }
+            other => return Err(format!("unknown flag '{other}'")),
+        }
+        i += 1;
+    }
+    Ok(focus)
+}
+
+pub fn cmd_diffsum(staged: bool, args: &[String], execute_task: ExecuteTaskFn) -> i32 {
     let tool = if staged {
         "cxrs_diffsum_staged"
     } else {
         "cxrs_diffsum"
     };
+    let label = if staged { "diffsum-staged" } else { "diffsum" };
+    let focus = match parse_diffsum_focus(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error(label, &e));
+            return print_usage_error(label, &format!("{label} [--focus GLOB]..."));
+        }
+    };
+    add_diff_focus(focus);
     match generate_diffsum_value(tool, staged, execute_task) {
         Ok(v) => {
             print_diffsum_human(&v);
             EXIT_OK
         }
         Err(e) => {
-            crate::cx_eprintln!(
-                "cxrs {}: {e}",
-                if staged { "diffsum-staged" } else { "diffsum" }
-            );
-            EXIT_RUNTIME
+            crate::cx_eprintln!("cxrs {label}: {e}");
+            failure_exit()
         }
     }
 }
 
-pub fn cmd_commitjson(execute_task: ExecuteTaskFn) -> i32 {
-    match generate_commitjson_value(execute_task) {
-        Ok(v) => match serde_json::to_string_pretty(&v) {
-            Ok(s) => {
-                println!("{s}");
-                EXIT_OK
-            }
-            Err(e) => {
-                crate::cx_eprintln!(
-                    "{}",
-                    format_error("commitjson", &format!("render failure: {e}"))
-                );
-                EXIT_RUNTIME
-            }
-        },
+/// `--strict` is the only flag of `commitjson`/`commitmsg`.
+fn parse_commit_args(label: &str, args: &[String]) -> Result<bool, i32> {
+    match args {
+        [] => Ok(false),
+        [flag] if flag == "--strict" => Ok(true),
+        _ => Err(print_usage_error(label, &format!("{label} [--strict]"))),
+    }
+}
+
+/// Report what the linter fixed and what it left; `--strict` turns leftover
+/// violations into a failure.
+fn check_commit_lint(label: &str, report: &LintReport, strict: bool) -> Result<(), i32> {
+    if !report.fixed.is_empty() {
+        diag(
+            Verbosity::Verbose,
+            "commit_lint_fixed",
+            &[("rules", report.fixed.join(","))],
+        );
+    }
+    for i in &report.issues {
+        warn(&format!("cxrs {label}: lint {}: {}", i.rule, i.message));
+    }
+    if strict && !report.issues.is_empty() {
+        crate::cx_eprintln!(
+            "{}",
+            format_error(
+                label,
+                &format!(
+                    "commit message failed lint with {} issue(s) (--strict)",
+                    report.issues.len()
+                )
+            )
+        );
+        return Err(EXIT_RUNTIME);
+    }
+    Ok(())
+}
+
+fn generate_linted_commit(
+    label: &str,
+    args: &[String],
+    execute_task: ExecuteTaskFn,
+) -> Result<Value, i32> {
+    let strict = parse_commit_args(label, args)?;
+    let (v, report) = generate_commitjson_value(execute_task).map_err(|e| {
+        crate::cx_eprintln!("{}", format_error(label, &e));
+        failure_exit()
+    })?;
+    check_commit_lint(label, &report, strict)?;
+    Ok(v)
+}
+
+pub fn cmd_commitjson(args: &[String], execute_task: ExecuteTaskFn) -> i32 {
+    let v = match generate_linted_commit("commitjson", args, execute_task) {
+        Ok(v) => v,
+        Err(code) => return code,
+    };
+    match serde_json::to_string_pretty(&v) {
+        Ok(s) => {
+            println!("{s}");
+            EXIT_OK
+        }
         Err(e) => {
-            crate::cx_eprintln!("{}", format_error("commitjson", &e));
+            crate::cx_eprintln!(
+                "{}",
+                format_error("commitjson", &format!("render failure: {e}"))
+            );
             EXIT_RUNTIME
         }
     }
 }
 
-pub fn cmd_commitmsg(execute_task: ExecuteTaskFn) -> i32 {
-    let v = match generate_commitjson_value(execute_task) {
+pub fn cmd_commitmsg(args: &[String], execute_task: ExecuteTaskFn) -> i32 {
+    let v = match generate_linted_commit("commitmsg", args, execute_task) {
         Ok(v) => v,
-        Err(e) => {
-            crate::cx_eprintln!("{}", format_error("commitmsg", &e));
-            return EXIT_RUNTIME;
-        }
+        Err(code) => return code,
     };
     let subject = v
         .get("subject")
@@ -328,9 +507,9 @@ pub fn cmd_commitmsg(execute_task: ExecuteTaskFn) -> i32 {
 
     println!("{subject}");
     println!();
-    if !body_items.is_empty() {
-        for line in body_items {
-            println!("- {line}");
+    for item in body_items {
+        for line in wrap_bullet(&item) {
+            println!("{line}");
         }
     }
     if !test_items.is_empty() {
@@ -1,16 +1,14 @@
 use serde_json::Value;
-use std::env;
-use std::path::PathBuf;
-use std::process::Command;
+use std::io;
 
 use crate::capture::run_system_command_capture;
 use crate::config::app_config;
-use crate::error::{EXIT_OK, EXIT_RUNTIME, EXIT_USAGE, format_error};
-use crate::paths::repo_root;
-use crate::policy::{SafetyDecision, evaluate_command_safety};
-use crate::process::run_command_status_with_timeout;
+use crate::error::{EXIT_OK, EXIT_RUNTIME, EXIT_SCHEMA, EXIT_USAGE, failure_exit, format_error};
 use crate::runlog::{RunLogInput, log_codex_run};
-use crate::schema::load_schema;
+use crate::schema::load_schema_for_tool;
+use crate::structured_fixrun_exec::{
+    FixExecOutcome, FixRunMode, confirm_supported, execute_fix_commands,
+};
 use crate::types::{ExecutionResult, LlmOutputKind, TaskInput, TaskSpec};
 
 pub type ExecuteTaskFn = fn(TaskSpec) -> Result<ExecutionResult, String>;
@@ -24,14 +22,14 @@ struct FixRunCtx {
 }
 
 fn load_fix_schema_or_exit() -> Result<crate::types::LoadedSchema, i32> {
-    load_schema("fixrun").map_err(|e| {
+    load_schema_for_tool("cxrs_fix_run").map_err(|e| {
         crate::cx_eprintln!("{}", format_error("fix-run", &e));
         EXIT_RUNTIME
     })
 }
 
 fn capture_fix_context(cmdv: &[String]) -> Result<(String, i32, crate::types::CaptureStats), i32> {
-    run_system_command_capture(cmdv).map_err(|e| {
+    run_system_command_capture("cxrs_fix_run", cmdv).map_err(|e| {
         crate::cx_eprintln!("{}", format_error("fix-run", &e));
         EXIT_RUNTIME
     })
@@ -51,10 +49,12 @@ fn execute_fix_schema_task(
         schema_task_input: Some(task_input.to_string()),
         logging_enabled: false,
         capture_override: Some(capture_stats),
+        prompt_template: None,
+        stream: false,
     })
     .map_err(|e| {
         crate::cx_eprintln!("{}", format_error("fix-run", &e));
-        EXIT_RUNTIME
+        failure_exit()
     })
 }
 
@@ -91,6 +91,7 @@ fn log_schema_failure_and_exit(
         prompt: task_input,
         prompt_raw: None,
         prompt_filtered: None,
+        response: Some(&result.stdout),
         schema_prompt: None,
         schema_raw: None,
         schema_attempt: None,
@@ -104,8 +105,12 @@ fn log_schema_failure_and_exit(
         schema_reason: Some("schema_validation_failed"),
         schema_name: Some(schema_name),
         quarantine_id: result.quarantine_id.as_deref(),
+        schema_repair: None,
         policy_blocked: None,
         policy_reason: None,
+        fix_decisions: None,
+        prompt_template: None,
+        clipboard_provider: None,
     });
     if let Some(qid) = result.quarantine_id.as_deref() {
         crate::cx_eprintln!(
@@ -115,15 +120,17 @@ fn log_schema_failure_and_exit(
     }
     crate::cx_eprintln!("{}", format_error("fix-run", "raw response follows:"));
     crate::cx_eprintln!("{}", result.stdout);
-    Err(EXIT_RUNTIME)
+    Err(EXIT_SCHEMA)
 }
 
-fn log_fix_run(ctx: &FixRunCtx, policy_blocked: Option<bool>, policy_reason: Option<&str>) {
+fn log_fix_run(ctx: &FixRunCtx, outcome: Option<&FixExecOutcome>) {
+    let decisions = outcome.map(FixExecOutcome::decisions_json);
     let _ = log_codex_run(RunLogInput {
         tool: "cxrs_fix_run",
         prompt: &ctx.task_input,
         prompt_raw: None,
         prompt_filtered: None,
+        response: Some(&ctx.result.stdout),
         schema_prompt: None,
         schema_raw: None,
         schema_attempt: None,
@@ -137,8 +144,12 @@ fn log_fix_run(ctx: &FixRunCtx, policy_blocked: Option<bool>, policy_reason: Opt
         schema_reason: None,
         schema_name: Some(ctx.schema_name.as_str()),
         quarantine_id: None,
-        policy_blocked,
-        policy_reason,
+        schema_repair: None,
+        policy_blocked: outcome.map(|o| o.policy_blocked),
+        policy_reason: outcome.and_then(|o| o.policy_reason.as_deref()),
+        fix_decisions: decisions.as_ref(),
+        prompt_template: None,
+        clipboard_provider: None,
     });
 }
 
@@ -160,28 +171,65 @@ fn parse_commands_array(raw: &str) -> Result<Vec<String>, String> {
     Ok(out)
 }
 
-fn parse_fix_run_args(app_name: &str, command: &[String]) -> Result<(bool, Vec<String>), i32> {
+struct FixRunArgs {
+    unsafe_override: bool,
+    mode: FixRunMode,
+    cmdv: Vec<String>,
+}
+
+fn parse_fix_run_args(app_name: &str, command: &[String]) -> Result<FixRunArgs, i32> {
     let mut unsafe_override = false;
-    let mut cmdv = command.to_vec();
-    if cmdv.first().map(String::as_str) == Some("--unsafe") {
-        unsafe_override = true;
-        cmdv = cmdv.into_iter().skip(1).collect();
+    let mut dry_run = false;
+    let mut confirm = false;
+    let mut idx = 0usize;
+    while let Some(flag) = command.get(idx) {
+        match flag.as_str() {
+            "--unsafe" => unsafe_override = true,
+            "--dry-run" => dry_run = true,
+            "--confirm" => confirm = true,
+            _ => break,
+        }
+        idx += 1;
     }
-    if cmdv.is_empty() {
+    let cmdv = command[idx..].to_vec();
+    if cmdv.is_empty() || (dry_run && confirm) {
         crate::cx_eprintln!(
             "{}",
             format_error(
                 "fix-run",
-                &format!("Usage: {app_name} fix-run [--unsafe] <command> [args...]")
+                &format!(
+                    "Usage: {app_name} fix-run [--unsafe] [--dry-run|--confirm] <command> [args...]"
+                )
             )
         );
         return Err(EXIT_USAGE);
     }
-    Ok((unsafe_override, cmdv))
+    let mode = if dry_run {
+        FixRunMode::DryRun
+    } else if confirm {
+        FixRunMode::Confirm
+    } else {
+        FixRunMode::Run
+    };
+    Ok(FixRunArgs {
+        unsafe_override,
+        mode,
+        cmdv,
+    })
 }
 
 fn run_fix_analysis(cmdv: Vec<String>, execute_task: ExecuteTaskFn) -> Result<FixRunCtx, i32> {
     let (captured, exit_status, capture_stats) = capture_fix_context(&cmdv)?;
+    fix_analysis(&cmdv, &captured, exit_status, capture_stats, execute_task)
+}
+
+fn fix_analysis(
+    cmdv: &[String],
+    captured: &str,
+    exit_status: i32,
+    capture_stats: crate::types::CaptureStats,
+    execute_task: ExecuteTaskFn,
+) -> Result<FixRunCtx, i32> {
     let schema = load_fix_schema_or_exit()?;
     let task_input = format!(
         "You are my terminal debugging assistant.\nGiven the command, exit status, and output, provide concise remediation.\n\nCommand:\n{}\n\nExit status: {}\n\nOutput:\n{}",
@@ -202,7 +250,21 @@ fn run_fix_analysis(cmdv: Vec<String>, execute_task: ExecuteTaskFn) -> Result<Fi
     })
 }
 
-fn print_fix_suggestions(analysis: &str, commands: &[String]) {
+/// Remediation for output captured elsewhere (`pipeline`): returns the
+/// analysis and suggested commands and logs the run without executing them.
+pub(crate) fn fix_run_suggestions(
+    cmdv: &[String],
+    captured: &str,
+    exit_status: i32,
+    capture_stats: crate::types::CaptureStats,
+    execute_task: ExecuteTaskFn,
+) -> Result<(String, Vec<String>), i32> {
+    let ctx = fix_analysis(cmdv, captured, exit_status, capture_stats, execute_task)?;
+    log_fix_run(&ctx, None);
+    Ok((ctx.analysis, ctx.commands))
+}
+
+pub(crate) fn print_fix_suggestions(analysis: &str, commands: &[String]) {
     if !analysis.is_empty() {
         println!("Analysis:");
         println!("{analysis}");
@@ -216,81 +278,51 @@ fn print_fix_suggestions(analysis: &str, commands: &[String]) {
     println!("-------------------");
 }
 
-fn execute_fix_commands(
-    commands: &[String],
-    force: bool,
-    allow_unsafe: bool,
-) -> (bool, Option<String>) {
-    let mut policy_blocked = false;
-    let mut policy_reasons: Vec<String> = Vec::new();
-    for c in commands {
-        let root = repo_root()
-            .or_else(|| env::current_dir().ok())
-            .unwrap_or_else(|| PathBuf::from("."));
-        match evaluate_command_safety(c, &root) {
-            SafetyDecision::Safe => {}
-            SafetyDecision::Dangerous(reason) => {
-                if !(force || allow_unsafe) {
-                    policy_blocked = true;
-                    policy_reasons.push(reason.clone());
-                    crate::cx_eprintln!(
-                        "WARN blocked dangerous command ({reason}); use CXFIX_FORCE=1 or --unsafe: {c}"
-                    );
-                    continue;
-                }
-                crate::cx_eprintln!("WARN unsafe override active; executing: {c}");
-            }
-        }
-        println!("-> {c}");
-        let mut shell_cmd = Command::new("bash");
-        shell_cmd.args(["-lc", c]);
-        if let Err(e) = run_command_status_with_timeout(shell_cmd, "cxfix_run command") {
-            crate::cx_eprintln!(
-                "{}",
-                format_error("fix-run", &format!("failed to execute command: {e}"))
-            );
-        }
-    }
-    let reason = if policy_reasons.is_empty() {
-        None
+fn fix_run_exit(ctx: &FixRunCtx) -> i32 {
+    if ctx.exit_status == 0 {
+        EXIT_OK
     } else {
-        Some(policy_reasons.join("; "))
-    };
-    (policy_blocked, reason)
+        ctx.exit_status
+    }
 }
 
+/// `--dry-run` previews and `--confirm` prompts regardless of `CXFIX_RUN`;
+/// otherwise commands only execute when `CXFIX_RUN=1`.
 pub fn cmd_fix_run(app_name: &str, command: &[String], execute_task: ExecuteTaskFn) -> i32 {
-    let (unsafe_override, cmdv) = match parse_fix_run_args(app_name, command) {
+    let args = match parse_fix_run_args(app_name, command) {
         Ok(v) => v,
         Err(code) => return code,
     };
-    let ctx = match run_fix_analysis(cmdv, execute_task) {
+    if args.mode == FixRunMode::Confirm && !confirm_supported() {
+        crate::cx_eprintln!(
+            "{}",
+            format_error(
+                "fix-run",
+                "--confirm requires an interactive terminal (use --dry-run to preview)"
+            )
+        );
+        return EXIT_USAGE;
+    }
+    let ctx = match run_fix_analysis(args.cmdv, execute_task) {
         Ok(v) => v,
         Err(code) => return code,
     };
     print_fix_suggestions(&ctx.analysis, &ctx.commands);
 
     let cfg = app_config();
-    let should_run = cfg.cxfix_run;
-    let force = cfg.cxfix_force;
-    let unsafe_env = cfg.cx_unsafe;
-    let allow_unsafe = unsafe_override || unsafe_env;
-    if !should_run {
+    let allow_unsafe = args.unsafe_override || cfg.cx_unsafe || cfg.cxfix_force;
+    if args.mode == FixRunMode::Run && !cfg.cxfix_run {
         println!("Not running suggested commands (set CXFIX_RUN=1 to execute).");
-        log_fix_run(&ctx, None, None);
-        return if ctx.exit_status == 0 {
-            EXIT_OK
-        } else {
-            ctx.exit_status
-        };
+        log_fix_run(&ctx, None);
+        return fix_run_exit(&ctx);
     }
-    let (policy_blocked, policy_reason_joined) =
-        execute_fix_commands(&ctx.commands, force, allow_unsafe);
-    log_fix_run(&ctx, Some(policy_blocked), policy_reason_joined.as_deref());
-
-    if ctx.exit_status == 0 {
-        EXIT_OK
+    let outcome = if args.mode == FixRunMode::Confirm {
+        let stdin = io::stdin();
+        let mut input = stdin.lock();
+        execute_fix_commands(&ctx.commands, args.mode, allow_unsafe, Some(&mut input))
     } else {
-        ctx.exit_status
-    }
+        execute_fix_commands(&ctx.commands, args.mode, allow_unsafe, None)
+    };
+    log_fix_run(&ctx, Some(&outcome));
+    fix_run_exit(&ctx)
 }
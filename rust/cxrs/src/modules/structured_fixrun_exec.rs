@@ -0,0 +1,344 @@
+use serde_json::{Value, json};
+use std::env;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::error::format_error;
+use crate::paths::repo_root;
+use crate::policy::{SafetyDecision, evaluate_command_safety};
+use crate::process::run_command_status_with_timeout;
+use crate::sandbox::sandboxed_command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixRunMode {
+    Run,
+    DryRun,
+    Confirm,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmAnswer {
+    Yes,
+    No,
+    All,
+    Quit,
+}
+
+pub fn parse_confirm_answer(raw: &str) -> Option<ConfirmAnswer> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "y" | "yes" => Some(ConfirmAnswer::Yes),
+        "n" | "no" => Some(ConfirmAnswer::No),
+        "a" | "all" => Some(ConfirmAnswer::All),
+        "q" | "quit" => Some(ConfirmAnswer::Quit),
+        _ => None,
+    }
+}
+
+pub fn confirm_supported() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Ask on stderr until a valid answer arrives; EOF counts as quit.
+pub fn ask_confirm(input: &mut dyn BufRead, command: &str) -> ConfirmAnswer {
+    loop {
+        eprint!("Run `{command}`? [y]es/[n]o/[a]ll/[q]uit: ");
+        let _ = io::stderr().flush();
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) | Err(_) => return ConfirmAnswer::Quit,
+            Ok(_) => {}
+        }
+        if let Some(answer) = parse_confirm_answer(&line) {
+            return answer;
+        }
+        crate::cx_eprintln!("please answer y, n, a or q");
+    }
+}
+
+/// Outcome for one suggested command, recorded in the run log as `fix_decisions`.
+#[derive(Debug, Clone)]
+pub struct FixDecision {
+    pub command: String,
+    pub policy: &'static str,
+    pub rule: Option<String>,
+    pub decision: &'static str,
+    pub exit_code: Option<i32>,
+    pub duration_ms: Option<u64>,
+    /// Sandbox the command ran under (`none`, `container:docker`, `nsjail`).
+    pub sandbox: Option<String>,
+}
+
+impl FixDecision {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "command": self.command,
+            "policy": self.policy,
+            "rule": self.rule,
+            "decision": self.decision,
+            "exit_code": self.exit_code,
+            "duration_ms": self.duration_ms,
+            "sandbox": self.sandbox,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FixExecOutcome {
+    pub decisions: Vec<FixDecision>,
+    pub policy_blocked: bool,
+    pub policy_reason: Option<String>,
+}
+
+impl FixExecOutcome {
+    pub fn decisions_json(&self) -> Value {
+        Value::Array(self.decisions.iter().map(FixDecision::to_json).collect())
+    }
+}
+
+pub fn policy_label(decision: &SafetyDecision) -> (&'static str, Option<String>) {
+    match decision {
+        SafetyDecision::Safe => ("safe", None),
+        SafetyDecision::Allowed(hit) => ("allowed", Some(hit.rule.clone())),
+        SafetyDecision::Warn(hit) => ("warn", Some(hit.rule.clone())),
+        SafetyDecision::Dangerous(hit) => ("dangerous", Some(hit.rule.clone())),
+    }
+}
+
+fn policy_root() -> PathBuf {
+    repo_root()
+        .or_else(|| env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// How an approved command ran: exit code (if any), wall time and sandbox.
+type Executed = (Option<i32>, u64, String);
+
+/// Run one approved command under the configured sandbox. `Err` means the
+/// sandbox was requested but is unavailable, so nothing ran.
+fn run_fix_command(c: &str, root: &Path) -> Result<Executed, String> {
+    let (cmd, sandbox) = sandboxed_command(c, root)?;
+    if sandbox == "none" {
+        println!("-> {c}");
+    } else {
+        println!("-> [sandbox {sandbox}] {c}");
+    }
+    let started = Instant::now();
+    let code = match run_command_status_with_timeout(cmd, "cxfix_run command") {
+        Ok(status) => status.code(),
+        Err(e) => {
+            crate::cx_eprintln!(
+                "{}",
+                format_error("fix-run", &format!("failed to execute command: {e}"))
+            );
+            None
+        }
+    };
+    Ok((code, started.elapsed().as_millis() as u64, sandbox))
+}
+
+/// Run `c` and record the outcome; a missing sandbox runtime is recorded as
+/// `sandbox_unavailable` instead of falling back to the host.
+fn run_and_record(outcome: &mut FixExecOutcome, c: &str, safety: &SafetyDecision, root: &Path) {
+    match run_fix_command(c, root) {
+        Ok(executed) => push_decision(outcome, c, safety, "executed", Some(executed)),
+        Err(e) => {
+            crate::cx_eprintln!("WARN {e}; not executing: {c}");
+            push_decision(outcome, c, safety, "sandbox_unavailable", None);
+        }
+    }
+}
+
+/// Print policy warnings for `c`; false when a dangerous command is blocked.
+fn policy_gate(
+    c: &str,
+    decision: &SafetyDecision,
+    override_unsafe: bool,
+    outcome: &mut FixExecOutcome,
+) -> bool {
+    match decision {
+        SafetyDecision::Safe | SafetyDecision::Allowed(_) => true,
+        SafetyDecision::Warn(hit) => {
+            crate::cx_eprintln!(
+                "WARN policy {} ({}): {}; executing: {c}",
+                hit.severity.as_str(),
+                hit.rule,
+                hit.reason
+            );
+            true
+        }
+        SafetyDecision::Dangerous(hit) if !override_unsafe => {
+            outcome.policy_blocked = true;
+            let joined = format!("{} [{}]", hit.reason, hit.rule);
+            outcome.policy_reason = Some(match outcome.policy_reason.take() {
+                Some(prev) => format!("{prev}; {joined}"),
+                None => joined,
+            });
+            crate::cx_eprintln!(
+                "WARN blocked dangerous command ({}; rule {}); use CXFIX_FORCE=1 or --unsafe: {c}",
+                hit.reason,
+                hit.rule
+            );
+            false
+        }
+        SafetyDecision::Dangerous(_) => {
+            crate::cx_eprintln!("WARN unsafe override active; executing: {c}");
+            true
+        }
+    }
+}
+
+fn print_dry_run_line(c: &str, policy: &str, rule: Option<&str>, decision: &str) {
+    let rule = rule.map(|r| format!(" rule={r}")).unwrap_or_default();
+    println!("[dry-run] {decision} ({policy}{rule}): {c}");
+}
+
+fn push_decision(
+    outcome: &mut FixExecOutcome,
+    c: &str,
+    safety: &SafetyDecision,
+    decision: &'static str,
+    executed: Option<Executed>,
+) {
+    let (policy, rule) = policy_label(safety);
+    let (exit_code, duration_ms, sandbox) = match executed {
+        Some((code, ms, sandbox)) => (code, Some(ms), Some(sandbox)),
+        None => (None, None, None),
+    };
+    outcome.decisions.push(FixDecision {
+        command: c.to_string(),
+        policy,
+        rule,
+        decision,
+        exit_code,
+        duration_ms,
+        sandbox,
+    });
+}
+
+/// Walk the suggested commands under the policy gate. `confirm` is consulted
+/// before each runnable command when present; dry-run never executes anything.
+pub fn execute_fix_commands(
+    commands: &[String],
+    mode: FixRunMode,
+    override_unsafe: bool,
+    mut confirm: Option<&mut dyn BufRead>,
+) -> FixExecOutcome {
+    let root = policy_root();
+    let mut outcome = FixExecOutcome::default();
+    let (mut approve_all, mut quit) = (false, false);
+    for c in commands {
+        let safety = evaluate_command_safety(c, &root);
+        let blocked = matches!(safety, SafetyDecision::Dangerous(_)) && !override_unsafe;
+        if mode == FixRunMode::DryRun {
+            let decision = if blocked {
+                "would_block"
+            } else {
+                "would_execute"
+            };
+            let (policy, rule) = policy_label(&safety);
+            print_dry_run_line(c, policy, rule.as_deref(), decision);
+            push_decision(&mut outcome, c, &safety, decision, None);
+            continue;
+        }
+        if quit {
+            push_decision(&mut outcome, c, &safety, "skipped", None);
+            continue;
+        }
+        if !blocked
+            && !approve_all
+            && let Some(input) = confirm.as_deref_mut()
+        {
+            match ask_confirm(input, c) {
+                ConfirmAnswer::Yes => {}
+                ConfirmAnswer::All => approve_all = true,
+                ConfirmAnswer::No => {
+                    push_decision(&mut outcome, c, &safety, "declined", None);
+                    continue;
+                }
+                ConfirmAnswer::Quit => {
+                    quit = true;
+                    push_decision(&mut outcome, c, &safety, "skipped", None);
+                    continue;
+                }
+            }
+        }
+        if !policy_gate(c, &safety, override_unsafe, &mut outcome) {
+            push_decision(&mut outcome, c, &safety, "blocked", None);
+            continue;
+        }
+        run_and_record(&mut outcome, c, &safety, &root);
+    }
+    outcome
+}
+
+/// Run only the picked suggestions (0-based `selected`) under the same policy
+/// gate; every other suggestion is recorded as `not_selected`.
+pub fn execute_selected_commands(
+    commands: &[String],
+    selected: &[usize],
+    override_unsafe: bool,
+) -> FixExecOutcome {
+    let root = policy_root();
+    let mut outcome = FixExecOutcome::default();
+    for (i, c) in commands.iter().enumerate() {
+        let safety = evaluate_command_safety(c, &root);
+        if !selected.contains(&i) {
+            push_decision(&mut outcome, c, &safety, "not_selected", None);
+        } else if !policy_gate(c, &safety, override_unsafe, &mut outcome) {
+            push_decision(&mut outcome, c, &safety, "blocked", None);
+        } else {
+            run_and_record(&mut outcome, c, &safety, &root);
+        }
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn confirm_answers_accept_short_and_long_forms() {
+        assert_eq!(parse_confirm_answer("Y\n"), Some(ConfirmAnswer::Yes));
+        assert_eq!(parse_confirm_answer("no"), Some(ConfirmAnswer::No));
+        assert_eq!(parse_confirm_answer(" all "), Some(ConfirmAnswer::All));
+        assert_eq!(parse_confirm_answer("q"), Some(ConfirmAnswer::Quit));
+        assert_eq!(parse_confirm_answer("maybe"), None);
+    }
+
+    #[test]
+    fn confirm_flow_records_each_decision() {
+        let commands: Vec<String> = ["true", "false", "rm -rf /", "true", "true"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut answers = Cursor::new("huh\ny\nn\nq\n");
+        let outcome =
+            execute_fix_commands(&commands, FixRunMode::Confirm, false, Some(&mut answers));
+        let decisions: Vec<&str> = outcome.decisions.iter().map(|d| d.decision).collect();
+        assert_eq!(
+            decisions,
+            vec!["executed", "declined", "blocked", "skipped", "skipped"]
+        );
+        assert_eq!(outcome.decisions[0].exit_code, Some(0));
+        assert!(outcome.decisions[0].duration_ms.is_some());
+        assert_eq!(outcome.decisions[0].sandbox.as_deref(), Some("none"));
+        assert_eq!(outcome.decisions[1].duration_ms, None);
+        assert!(outcome.policy_blocked);
+    }
+
+    #[test]
+    fn selected_commands_skip_unpicked_and_gate_dangerous() {
+        let commands: Vec<String> = ["true", "false", "rm -rf /"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let outcome = execute_selected_commands(&commands, &[1, 2], false);
+        let decisions: Vec<&str> = outcome.decisions.iter().map(|d| d.decision).collect();
+        assert_eq!(decisions, vec!["not_selected", "executed", "blocked"]);
+        assert_eq!(outcome.decisions[1].exit_code, Some(1));
+        assert!(outcome.policy_blocked);
+    }
+}
@@ -0,0 +1,139 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use crate::config::app_config;
+use crate::error::{EXIT_OK, EXIT_POLICY, format_error};
+use crate::paths::repo_root;
+use crate::policy::evaluate_command_safety;
+use crate::runlog::log_next_exec;
+use crate::structured_fixrun_exec::{confirm_supported, execute_selected_commands, policy_label};
+use crate::verbosity::warn;
+
+/// Parse a menu answer such as `1,3`, `2-4` or `all` into sorted 0-based
+/// indexes; an empty answer (or `none`/`q`) selects nothing.
+pub fn parse_selection(raw: &str, count: usize) -> Result<Vec<usize>, String> {
+    let raw = raw.trim().to_ascii_lowercase();
+    match raw.as_str() {
+        "" | "none" | "n" | "q" | "quit" => return Ok(Vec::new()),
+        "all" | "a" => return Ok((0..count).collect()),
+        _ => {}
+    }
+    let pick = |s: &str| -> Result<usize, String> {
+        s.trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|n| (1..=count).contains(n))
+            .ok_or_else(|| format!("'{}' is not a suggestion number (1-{count})", s.trim()))
+    };
+    let mut out = Vec::new();
+    for part in raw.split(',').filter(|p| !p.trim().is_empty()) {
+        let (lo, hi) = match part.split_once('-') {
+            Some((a, b)) => (pick(a)?, pick(b)?),
+            None => {
+                let n = pick(part)?;
+                (n, n)
+            }
+        };
+        if hi < lo {
+            return Err(format!("invalid range '{}'", part.trim()));
+        }
+        out.extend((lo..=hi).map(|n| n - 1));
+    }
+    out.sort_unstable();
+    out.dedup();
+    Ok(out)
+}
+
+/// Ask on stderr until the selection parses; EOF selects nothing.
+pub fn ask_selection(input: &mut dyn BufRead, count: usize) -> Vec<usize> {
+    loop {
+        eprint!("Run which suggestions? [1-{count}, e.g. 1,3 or 2-4 | all | Enter for none]: ");
+        let _ = io::stderr().flush();
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) | Err(_) => return Vec::new(),
+            Ok(_) => {}
+        }
+        match parse_selection(&line, count) {
+            Ok(v) => return v,
+            Err(e) => crate::cx_eprintln!("{e}"),
+        }
+    }
+}
+
+fn print_menu(commands: &[String]) {
+    let root = repo_root()
+        .or_else(|| env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."));
+    for (i, c) in commands.iter().enumerate() {
+        let safety = evaluate_command_safety(c, &root);
+        let (policy, rule) = policy_label(&safety);
+        let rule = rule.map(|r| format!(" rule={r}")).unwrap_or_default();
+        println!("{:>2}) [{policy}{rule}] {c}", i + 1);
+    }
+}
+
+/// `next --run`: show each suggestion with its policy classification and, on a
+/// TTY, execute the picked ones through the fix-run policy gate. Each selected
+/// suggestion gets its own run-log row linked to the `next` run.
+pub fn run_next_suggestions(commands: &[String], parent_id: &str, unsafe_flag: bool) -> i32 {
+    print_menu(commands);
+    if commands.is_empty() {
+        return EXIT_OK;
+    }
+    if !confirm_supported() {
+        println!("Not running suggested commands (selection requires an interactive terminal).");
+        return EXIT_OK;
+    }
+    let selected = {
+        let stdin = io::stdin();
+        let mut input = stdin.lock();
+        ask_selection(&mut input, commands.len())
+    };
+    if selected.is_empty() {
+        println!("No suggestions selected.");
+        return EXIT_OK;
+    }
+    let cfg = app_config();
+    let allow_unsafe = unsafe_flag || cfg.cx_unsafe || cfg.cxfix_force;
+    let outcome = execute_selected_commands(commands, &selected, allow_unsafe);
+    for (i, decision) in outcome.decisions.iter().enumerate() {
+        if decision.decision == "not_selected" {
+            continue;
+        }
+        if let Err(e) = log_next_exec(parent_id, i + 1, decision) {
+            warn(&format_error("next", &format!("warning: {e}")));
+        }
+    }
+    if outcome.policy_blocked {
+        EXIT_POLICY
+    } else {
+        EXIT_OK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn selection_accepts_lists_ranges_and_keywords() {
+        assert_eq!(parse_selection("1,3", 4).unwrap(), vec![0, 2]);
+        assert_eq!(parse_selection(" 2-4 , 2 ", 4).unwrap(), vec![1, 2, 3]);
+        assert_eq!(parse_selection("ALL", 3).unwrap(), vec![0, 1, 2]);
+        assert!(parse_selection("\n", 3).unwrap().is_empty());
+        assert!(parse_selection("5", 3).is_err());
+        assert!(parse_selection("3-1", 3).is_err());
+        assert!(parse_selection("x", 3).is_err());
+    }
+
+    #[test]
+    fn selection_prompt_retries_then_stops_at_eof() {
+        let mut answers = Cursor::new("9\n2\n");
+        assert_eq!(ask_selection(&mut answers, 3), vec![1]);
+        let mut empty = Cursor::new("");
+        assert!(ask_selection(&mut empty, 3).is_empty());
+    }
+}
@@ -1,16 +1,25 @@
-use serde_json::Value;
+use serde_json::{Value, json};
 use std::path::PathBuf;
+use std::time::Instant;
 
-use crate::error::{EXIT_OK, EXIT_RUNTIME, format_error};
+use crate::contract_versions::REPLAY_JSON_CONTRACT_VERSION;
+use crate::error::{
+    EXIT_BACKEND, EXIT_OK, EXIT_RUNTIME, EXIT_SCHEMA, format_error, print_runtime_error,
+    print_usage_error,
+};
 use crate::llm::extract_agent_text;
-use crate::quarantine::read_quarantine_record;
-use crate::runlog::log_schema_failure;
-use crate::schema::{build_strict_schema_prompt, validate_schema_instance};
-use crate::types::LoadedSchema;
+use crate::paths::resolve_quarantine_dir;
+use crate::quarantine::{read_quarantine_record, scan_quarantine_dir};
+use crate::quarantine_ops::mark_quarantine_resolved;
+use crate::runlog::{RunLogInput, log_codex_run, log_schema_failure};
+use crate::schema::{build_strict_schema_prompt, schema_name_for_tool, validate_schema_instance};
+use crate::types::{LoadedSchema, QuarantineRecord};
 
 pub type JsonlRunner = fn(&str) -> Result<String, String>;
 
-fn log_replay_schema_failure(rec: &crate::types::QuarantineRecord, reason: &str, raw: &str) {
+pub const REPLAY_USAGE: &str = "replay <quarantine_id> [--apply] | replay --all [--tool NAME] [--reason REASON] [--include-resolved] [--apply] [--json]";
+
+fn log_replay_schema_failure(rec: &QuarantineRecord, reason: &str, raw: &str) {
     match log_schema_failure(
         &format!("{}_replay", rec.tool),
         reason,
@@ -30,14 +39,14 @@ fn log_replay_schema_failure(rec: &crate::types::QuarantineRecord, reason: &str,
     }
 }
 
-fn ensure_quarantine_payload(rec: &crate::types::QuarantineRecord) -> Result<(), String> {
+fn ensure_quarantine_payload(rec: &QuarantineRecord) -> Result<(), String> {
     if rec.schema.trim().is_empty() || rec.prompt.trim().is_empty() {
         return Err("quarantine entry is missing schema/prompt payload".to_string());
     }
     Ok(())
 }
 
-fn replay_schema_from_record(rec: &crate::types::QuarantineRecord) -> Result<LoadedSchema, String> {
+fn replay_schema_from_record(rec: &QuarantineRecord) -> Result<LoadedSchema, String> {
     let value: Value = serde_json::from_str(&rec.schema)
         .map_err(|e| format!("quarantine schema is invalid JSON: {e}"))?;
     Ok(LoadedSchema {
@@ -49,7 +58,7 @@ fn replay_schema_from_record(rec: &crate::types::QuarantineRecord) -> Result<Loa
 }
 
 fn replay_raw_response(
-    rec: &crate::types::QuarantineRecord,
+    rec: &QuarantineRecord,
     run_llm_jsonl: JsonlRunner,
 ) -> Result<String, String> {
     let full_prompt = build_strict_schema_prompt(&rec.schema, &rec.prompt);
@@ -57,7 +66,7 @@ fn replay_raw_response(
     Ok(extract_agent_text(&jsonl).unwrap_or_default())
 }
 
-fn validate_replay_response(rec: &crate::types::QuarantineRecord, raw: &str) -> Result<(), String> {
+fn validate_replay_response(rec: &QuarantineRecord, raw: &str) -> Result<(), String> {
     if raw.trim().is_empty() {
         return Err("empty_agent_message".to_string());
     }
@@ -68,7 +77,101 @@ fn validate_replay_response(rec: &crate::types::QuarantineRecord, raw: &str) ->
     validate_schema_instance(&schema, raw).map(|_| ())
 }
 
-pub fn cmd_replay(id: &str, run_llm_jsonl: JsonlRunner) -> i32 {
+struct ReplayArgs {
+    id: Option<String>,
+    all: bool,
+    apply: bool,
+    json_out: bool,
+    include_resolved: bool,
+    tool: Option<String>,
+    reason: Option<String>,
+}
+
+fn parse_replay_args(args: &[String]) -> Result<ReplayArgs, String> {
+    let mut out = ReplayArgs {
+        id: None,
+        all: false,
+        apply: false,
+        json_out: false,
+        include_resolved: false,
+        tool: None,
+        reason: None,
+    };
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--all" => out.all = true,
+            "--apply" => out.apply = true,
+            "--json" => out.json_out = true,
+            "--include-resolved" => out.include_resolved = true,
+            "--tool" => {
+                out.tool = Some(args.get(i + 1).ok_or("--tool requires a value")?.clone());
+                i += 1;
+            }
+            "--reason" => {
+                out.reason = Some(args.get(i + 1).ok_or("--reason requires a value")?.clone());
+                i += 1;
+            }
+            other if other.starts_with("--") => return Err(format!("unknown flag '{other}'")),
+            id if out.id.is_none() => out.id = Some(id.to_string()),
+            extra => return Err(format!("unexpected argument '{extra}'")),
+        }
+        i += 1;
+    }
+    if out.all == out.id.is_some() {
+        return Err("expected either <quarantine_id> or --all".to_string());
+    }
+    if !out.all && (out.tool.is_some() || out.reason.is_some() || out.json_out) {
+        return Err("--tool/--reason/--json only apply to --all".to_string());
+    }
+    Ok(out)
+}
+
+/// Re-emit the corrected run-log row and resolve the quarantine entry.
+fn apply_replay(rec: &QuarantineRecord, raw: &str, duration_ms: u64) -> Result<(), String> {
+    let schema_name = schema_name_for_tool(&rec.tool);
+    log_codex_run(RunLogInput {
+        tool: &rec.tool,
+        prompt: &rec.prompt,
+        prompt_raw: None,
+        prompt_filtered: None,
+        response: Some(raw),
+        schema_prompt: Some(&rec.prompt),
+        schema_raw: Some(raw),
+        schema_attempt: None,
+        timed_out: None,
+        timeout_secs: None,
+        command_label: Some("replay"),
+        duration_ms,
+        usage: None,
+        capture: None,
+        schema_ok: true,
+        schema_reason: None,
+        schema_name: schema_name.as_deref(),
+        quarantine_id: Some(&rec.id),
+        schema_repair: None,
+        policy_blocked: None,
+        policy_reason: None,
+        fix_decisions: None,
+        prompt_template: None,
+        clipboard_provider: None,
+    })?;
+    mark_quarantine_resolved(&rec.id, "replay --apply").map(|_| ())
+}
+
+/// Replay one record; returns the validated raw response or the failure reason.
+fn replay_record(
+    rec: &QuarantineRecord,
+    run_llm_jsonl: JsonlRunner,
+) -> Result<(String, u64), String> {
+    ensure_quarantine_payload(rec)?;
+    let started = Instant::now();
+    let raw = replay_raw_response(rec, run_llm_jsonl)?;
+    validate_replay_response(rec, &raw)?;
+    Ok((raw, started.elapsed().as_millis() as u64))
+}
+
+fn replay_one(id: &str, apply: bool, run_llm_jsonl: JsonlRunner) -> i32 {
     let rec = match read_quarantine_record(id) {
         Ok(v) => v,
         Err(e) => {
@@ -82,11 +185,12 @@ pub fn cmd_replay(id: &str, run_llm_jsonl: JsonlRunner) -> i32 {
         return EXIT_RUNTIME;
     }
 
+    let started = Instant::now();
     let raw = match replay_raw_response(&rec, run_llm_jsonl) {
         Ok(v) => v,
         Err(e) => {
             crate::cx_eprintln!("{}", format_error("replay", &e));
-            return EXIT_RUNTIME;
+            return EXIT_BACKEND;
         }
     };
     if let Err(reason) = validate_replay_response(&rec, &raw) {
@@ -96,9 +200,101 @@ pub fn cmd_replay(id: &str, run_llm_jsonl: JsonlRunner) -> i32 {
             crate::cx_eprintln!("{raw}");
         }
         crate::cx_eprintln!("{}", format_error("replay", &reason));
-        return EXIT_RUNTIME;
+        return EXIT_SCHEMA;
     }
 
+    if apply && let Err(e) = apply_replay(&rec, &raw, started.elapsed().as_millis() as u64) {
+        crate::cx_eprintln!("{}", format_error("replay", &format!("apply failed: {e}")));
+        return EXIT_RUNTIME;
+    }
     println!("{raw}");
     EXIT_OK
 }
+
+fn replay_candidates(args: &ReplayArgs) -> Result<Vec<QuarantineRecord>, String> {
+    let qdir = resolve_quarantine_dir()
+        .ok_or_else(|| "unable to resolve quarantine directory".to_string())?;
+    let mut rows: Vec<QuarantineRecord> = scan_quarantine_dir(&qdir)
+        .into_iter()
+        .map(|(_, rec)| rec)
+        .filter(|rec| args.include_resolved || rec.resolved_ts.is_none())
+        .filter(|rec| args.tool.as_deref().is_none_or(|t| rec.tool == t))
+        .filter(|rec| args.reason.as_deref().is_none_or(|r| rec.reason == r))
+        .collect();
+    rows.sort_by(|a, b| a.ts.cmp(&b.ts));
+    Ok(rows)
+}
+
+/// Batch replay: failures are reported in the summary, not re-quarantined.
+fn replay_all(args: &ReplayArgs, run_llm_jsonl: JsonlRunner) -> i32 {
+    let rows = match replay_candidates(args) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("replay", &e),
+    };
+    let (mut passed, mut applied) = (0usize, 0usize);
+    let mut results = Vec::new();
+    for rec in &rows {
+        let (status, reason) = match replay_record(rec, run_llm_jsonl) {
+            Ok((raw, duration_ms)) => {
+                passed += 1;
+                match args.apply.then(|| apply_replay(rec, &raw, duration_ms)) {
+                    Some(Err(e)) => ("pass", Some(format!("apply failed: {e}"))),
+                    Some(Ok(())) => {
+                        applied += 1;
+                        ("applied", None)
+                    }
+                    None => ("pass", None),
+                }
+            }
+            Err(e) => ("fail", Some(e)),
+        };
+        if !args.json_out {
+            match reason.as_deref() {
+                Some(r) => println!("- {status} {} ({}): {r}", rec.id, rec.tool),
+                None => println!("- {status} {} ({})", rec.id, rec.tool),
+            }
+        }
+        results.push(json!({
+            "id": rec.id,
+            "tool": rec.tool,
+            "status": status,
+            "reason": reason,
+        }));
+    }
+    let failed = rows.len() - passed;
+    if args.json_out {
+        let out = json!({
+            "contract_version": REPLAY_JSON_CONTRACT_VERSION,
+            "replayed": rows.len(),
+            "passed": passed,
+            "failed": failed,
+            "applied": applied,
+            "results": results,
+        });
+        match serde_json::to_string_pretty(&out) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return print_runtime_error("replay", &format!("failed to render JSON: {e}")),
+        }
+    } else {
+        println!("== cxrs replay summary ==");
+        println!("replayed: {}", rows.len());
+        println!("passed: {passed}");
+        println!("failed: {failed}");
+        println!("applied: {applied}");
+    }
+    if failed > 0 { EXIT_RUNTIME } else { EXIT_OK }
+}
+
+pub fn cmd_replay(args: &[String], run_llm_jsonl: JsonlRunner) -> i32 {
+    let parsed = match parse_replay_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{}", format_error("replay", &e));
+            return print_usage_error("replay", REPLAY_USAGE);
+        }
+    };
+    match parsed.id.as_deref() {
+        Some(id) => replay_one(id, parsed.apply, run_llm_jsonl),
+        None => replay_all(&parsed, run_llm_jsonl),
+    }
+}
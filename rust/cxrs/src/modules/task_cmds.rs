@@ -9,18 +9,23 @@ use std::time::{Duration, Instant};
 use crate::cmdctx::CmdCtx;
 use crate::config::app_config;
 use crate::paths::resolve_log_file;
-use crate::process::{run_command_output_with_timeout, run_command_status_with_timeout};
+use crate::platform::bin_in_path;
+use crate::process::run_command_output_with_timeout;
 use crate::state::{current_task_id, set_state_path};
+use crate::task_watch::cmd_task_watch;
 use crate::taskrun::{TaskRunError, TaskRunner};
-use crate::tasks::set_task_status;
+use crate::tasks::{
+    cmd_task_claim, cmd_task_export, cmd_task_import, cmd_task_reap, cmd_task_template,
+    set_task_outcome, set_task_status,
+};
 use crate::tasks_plan::build_task_run_plan;
 use crate::types::TaskRecord;
 
 pub struct TaskCmdDeps {
     pub cmd_task_add: fn(&str, &[String]) -> i32,
-    pub cmd_task_list: fn(Option<&str>) -> i32,
-    pub cmd_task_show: fn(&str) -> i32,
-    pub cmd_task_fanout: fn(&str, &str, Option<&str>) -> i32,
+    pub cmd_task_list: fn(Option<&str>, Option<&str>, bool) -> i32,
+    pub cmd_task_show: fn(&str, bool) -> i32,
+    pub cmd_task_fanout: fn(&str, &str, Option<&str>, bool) -> i32,
     pub read_tasks: fn() -> Result<Vec<TaskRecord>, String>,
     pub run_task_by_id: TaskRunByIdFn,
     pub make_task_runner: fn() -> TaskRunner,
@@ -51,9 +56,12 @@ pub fn cmd_task_set_status(id: &str, new_status: &str) -> i32 {
 }
 
 fn handle_list(app_name: &str, args: &[String], deps: &TaskCmdDeps) -> i32 {
-    let usage =
-        format!("Usage: {app_name} task list [--status pending|in_progress|complete|failed]");
+    let usage = format!(
+        "Usage: {app_name} task list [--status pending|in_progress|complete|failed] [--assignee NAME] [--verbose]"
+    );
     let mut status_filter: Option<&str> = None;
+    let mut assignee: Option<&str> = None;
+    let mut verbose = false;
     let mut i = 1usize;
     while i < args.len() {
         match args[i].as_str() {
@@ -69,13 +77,25 @@ fn handle_list(app_name: &str, args: &[String], deps: &TaskCmdDeps) -> i32 {
                 status_filter = Some(v);
                 i += 2;
             }
+            "--assignee" => {
+                let Some(v) = args.get(i + 1).map(String::as_str) else {
+                    crate::cx_eprintln!("{usage}");
+                    return 2;
+                };
+                assignee = Some(v);
+                i += 2;
+            }
+            "--verbose" | "-v" => {
+                verbose = true;
+                i += 1;
+            }
             other => {
                 crate::cx_eprintln!("cxrs task list: unknown flag '{other}'");
                 return 2;
             }
         }
     }
-    (deps.cmd_task_list)(status_filter)
+    (deps.cmd_task_list)(status_filter, assignee, verbose)
 }
 
 fn require_id(app_name: &str, args: &[String], cmd: &str) -> Result<String, i32> {
@@ -85,6 +105,24 @@ fn require_id(app_name: &str, args: &[String], cmd: &str) -> Result<String, i32>
     })
 }
 
+fn handle_show(app_name: &str, args: &[String], deps: &TaskCmdDeps) -> i32 {
+    let id = match require_id(app_name, args, "show") {
+        Ok(id) => id,
+        Err(code) => return code,
+    };
+    let mut result = false;
+    for flag in &args[2..] {
+        match flag.as_str() {
+            "--result" => result = true,
+            other => {
+                crate::cx_eprintln!("cxrs task show: unknown flag '{other}'");
+                return 2;
+            }
+        }
+    }
+    (deps.cmd_task_show)(&id, result)
+}
+
 fn handle_fanout(app_name: &str, args: &[String], deps: &TaskCmdDeps) -> i32 {
     if args.len() < 2 {
         crate::cx_eprintln!("Usage: {app_name} task fanout <objective>");
@@ -92,12 +130,18 @@ fn handle_fanout(app_name: &str, args: &[String], deps: &TaskCmdDeps) -> i32 {
     }
     let mut objective_parts: Vec<String> = Vec::new();
     let mut from: Option<&str> = None;
+    let mut llm = false;
     let mut i = 1usize;
     while i < args.len() {
+        if args[i] == "--llm" {
+            llm = true;
+            i += 1;
+            continue;
+        }
         if args[i] == "--from" {
             let Some(v) = args.get(i + 1).map(String::as_str) else {
                 crate::cx_eprintln!(
-                    "Usage: {app_name} task fanout <objective> [--from staged-diff|worktree|log|file:PATH]"
+                    "Usage: {app_name} task fanout <objective> [--llm] [--from staged-diff|worktree|log|file:PATH]"
                 );
                 return 2;
             };
@@ -108,7 +152,7 @@ fn handle_fanout(app_name: &str, args: &[String], deps: &TaskCmdDeps) -> i32 {
         objective_parts.push(args[i].clone());
         i += 1;
     }
-    (deps.cmd_task_fanout)(app_name, &objective_parts.join(" "), from)
+    (deps.cmd_task_fanout)(app_name, &objective_parts.join(" "), from, llm)
 }
 
 fn parse_task_run_overrides(
@@ -364,7 +408,7 @@ fn should_retry(failure: FailureClass, attempt: u32, retry_max: u32) -> bool {
     matches!(failure, FailureClass::Retryable) && attempt <= retry_max
 }
 
-fn run_task_managed_subprocess(
+pub(crate) fn run_task_managed_subprocess(
     id: String,
     backend: String,
     queue_ms: u64,
@@ -672,11 +716,7 @@ fn backend_available(name: &str) -> bool {
     if disabled {
         return false;
     }
-    let mut cmd = Command::new("bash");
-    cmd.args(["-lc", &format!("command -v {name} >/dev/null 2>&1")]);
-    run_command_status_with_timeout(cmd, "command -v")
-        .ok()
-        .is_some_and(|s| s.success())
+    bin_in_path(name)
 }
 
 fn available_pool(pool: &[String]) -> Vec<String> {
@@ -820,6 +860,16 @@ fn parse_run_all_options(app_name: &str, args: &[String]) -> Result<RunAllOption
     })
 }
 
+/// Backend for a worker running `task` outside `run-all`: the task's own backend
+/// when available, else the configured default, if its binary is on PATH.
+pub(crate) fn worker_backend(task: &TaskRecord, index: usize) -> Option<String> {
+    let pool = default_backend_pool();
+    fallback_backend(
+        choose_backend_for_task(Some(task), &pool, index),
+        &available_pool(&pool),
+    )
+}
+
 fn choose_backend_for_task(
     task: Option<&TaskRecord>,
     pool: &[String],
@@ -989,12 +1039,12 @@ fn run_schedule_parallel(
                 Ok((code, execution_id)) => {
                     if code == 0 {
                         summary.record_success();
-                        let _ = set_task_status_quiet(&done.id, "complete");
+                        let _ = set_task_outcome(&done.id, "complete", execution_id.as_deref());
                     } else {
                         summary.record_failure(
                             classify_failure_for_execution(execution_id.as_deref()).class,
                         );
-                        let _ = set_task_status_quiet(&done.id, "failed");
+                        let _ = set_task_outcome(&done.id, "failed", execution_id.as_deref());
                         crate::cx_eprintln!("cxrs task run-all: task failed: {}", done.id);
                     }
                 }
@@ -1103,14 +1153,10 @@ pub fn handler(ctx: &CmdCtx, args: &[String], deps: &TaskCmdDeps) -> i32 {
     match sub {
         "add" => (deps.cmd_task_add)(app_name, &args[1..]),
         "list" => handle_list(app_name, args, deps),
-        "show" => match require_id(app_name, args, "show") {
-            Ok(id) => (deps.cmd_task_show)(&id),
-            Err(code) => code,
-        },
-        "claim" => match require_id(app_name, args, "claim") {
-            Ok(id) => cmd_task_set_status(&id, "in_progress"),
-            Err(code) => code,
-        },
+        "show" => handle_show(app_name, args, deps),
+        "claim" => cmd_task_claim(&args[1..]),
+        "reap" => cmd_task_reap(&args[1..]),
+        "watch" => cmd_task_watch(&args[1..]),
         "complete" => match require_id(app_name, args, "complete") {
             Ok(id) => cmd_task_set_status(&id, "complete"),
             Err(code) => code,
@@ -1120,12 +1166,15 @@ pub fn handler(ctx: &CmdCtx, args: &[String], deps: &TaskCmdDeps) -> i32 {
             Err(code) => code,
         },
         "fanout" => handle_fanout(app_name, args, deps),
+        "template" => cmd_task_template(&args[1..]),
+        "export" => cmd_task_export(&args[1..]),
+        "import" => cmd_task_import(&args[1..]),
         "run-plan" => handle_run_plan(app_name, args, deps),
         "run" => handle_run(app_name, args, deps),
         "run-all" => handle_run_all(app_name, args, deps),
         _ => {
             crate::cx_eprintln!(
-                "Usage: {app_name} task <add|list|show|claim|complete|fail|fanout|run-plan|run|run-all> ..."
+                "Usage: {app_name} task <add|list|show|claim|reap|complete|fail|fanout|template|export|import|run-plan|run|run-all|watch> ..."
             );
             2
         }
@@ -1154,6 +1203,11 @@ mod tests {
             resource_keys: Vec::new(),
             max_retries: None,
             timeout_secs: None,
+            estimate: None,
+            result_ref: None,
+            execution_id: None,
+            assignee: None,
+            claimed_at: None,
             status: "pending".to_string(),
             created_at: "2026-01-01T00:00:00Z".to_string(),
             updated_at: "2026-01-01T00:00:00Z".to_string(),
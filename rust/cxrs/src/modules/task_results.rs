@@ -0,0 +1,126 @@
+use std::fs;
+
+use crate::paths::{ensure_parent_dir, resolve_task_results_dir};
+use crate::types::TaskRecord;
+
+const OUTPUT_HEADING: &str = "## Output";
+const SUMMARY_CHARS: usize = 100;
+
+/// Repo-relative location stored in `TaskRecord.result_ref`.
+pub fn task_result_ref(id: &str) -> String {
+    format!(".codex/task_results/{id}.md")
+}
+
+/// One finished `task run`, as persisted to `.codex/task_results/<id>.md`.
+pub struct TaskResult<'a> {
+    pub task: &'a TaskRecord,
+    pub status_code: i32,
+    pub execution_id: Option<&'a str>,
+    /// Model text for prompt-run tasks; `None` when a dispatched command printed
+    /// its own output.
+    pub output: Option<&'a str>,
+    pub finished_at: &'a str,
+}
+
+fn render_task_result(r: &TaskResult) -> String {
+    let status = if r.status_code == 0 {
+        "complete".to_string()
+    } else {
+        format!("failed (exit {})", r.status_code)
+    };
+    let mut out = format!(
+        "# {}: {}\n\n- role: {}\n- status: {status}\n- execution_id: {}\n- finished_at: {}\n\n{OUTPUT_HEADING}\n\n",
+        r.task.id,
+        r.task.objective,
+        r.task.role,
+        r.execution_id.unwrap_or("-"),
+        r.finished_at,
+    );
+    match (r.output.map(str::trim).filter(|s| !s.is_empty()), r.execution_id) {
+        (Some(text), _) => out.push_str(text),
+        (None, Some(eid)) => out.push_str(&format!(
+            "_Output was printed by the dispatched command; see `cx transcript show {eid}` if transcripts are enabled._"
+        )),
+        (None, None) => out.push_str("_No output captured._"),
+    }
+    out.push('\n');
+    out
+}
+
+/// Write the result file and return its `result_ref`.
+pub fn write_task_result(r: &TaskResult) -> Result<String, String> {
+    let path = resolve_task_results_dir()?.join(format!("{}.md", r.task.id));
+    ensure_parent_dir(&path)?;
+    fs::write(&path, render_task_result(r))
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    Ok(task_result_ref(&r.task.id))
+}
+
+pub fn read_task_result(id: &str) -> Result<Option<String>, String> {
+    let path = resolve_task_results_dir()?.join(format!("{id}.md"));
+    if !path.is_file() {
+        return Ok(None);
+    }
+    fs::read_to_string(&path)
+        .map(Some)
+        .map_err(|e| format!("cannot read {}: {e}", path.display()))
+}
+
+/// First non-empty output line of a result file, clipped for one-line listings.
+pub fn summarize_task_result(text: &str) -> Option<String> {
+    let body = text.split_once(OUTPUT_HEADING)?.1;
+    let line = body.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let mut summary: String = line.chars().take(SUMMARY_CHARS).collect();
+    if line.chars().count() > SUMMARY_CHARS {
+        summary.push_str("...");
+    }
+    Some(summary)
+}
+
+/// Summary for task `id`, if a result file exists and has output.
+pub fn task_result_summary(id: &str) -> Option<String> {
+    read_task_result(id)
+        .ok()
+        .flatten()
+        .and_then(|t| summarize_task_result(&t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendered_result_summarizes_first_output_line() {
+        let task: TaskRecord = serde_json::from_value(serde_json::json!({
+            "id": "task_001", "parent_id": null, "role": "implementer",
+            "objective": "Add login", "context_ref": "", "status": "pending",
+            "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        let text = render_task_result(&TaskResult {
+            task: &task,
+            status_code: 0,
+            execution_id: Some("e1"),
+            output: Some("\nAdded the login form.\nNext: tests."),
+            finished_at: "2026-01-01T00:00:00Z",
+        });
+        assert!(text.starts_with("# task_001: Add login\n"));
+        assert!(text.contains("- execution_id: e1\n"));
+        assert_eq!(
+            summarize_task_result(&text).as_deref(),
+            Some("Added the login form.")
+        );
+
+        let dispatched = render_task_result(&TaskResult {
+            task: &task,
+            status_code: 4,
+            execution_id: Some("e2"),
+            output: None,
+            finished_at: "",
+        });
+        assert!(dispatched.contains("- status: failed (exit 4)"));
+        assert!(
+            summarize_task_result(&dispatched).is_some_and(|s| s.contains("cx transcript show e2"))
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::error::{EXIT_OK, EXIT_RUNTIME, print_runtime_error, print_usage_error};
+use crate::signals::{ShutdownGuard, shutdown_requested, sleep_unless_shutdown};
+use crate::task_cmds::{run_task_managed_subprocess, worker_backend};
+use crate::tasks::{claim_task, read_tasks, release_claim, set_task_outcome};
+use crate::tasks_plan::effective_dependencies;
+use crate::types::TaskRecord;
+
+pub const TASK_WATCH_USAGE: &str =
+    "task watch [--role ROLE] [--as NAME] [--max-workers N] [--interval SECS] [--once]";
+
+const DEFAULT_WATCH_INTERVAL_MS: u64 = 2000;
+
+struct WatchOptions {
+    role: Option<String>,
+    assignee: String,
+    max_workers: usize,
+    interval: Duration,
+    once: bool,
+}
+
+fn parse_interval(raw: &str) -> Option<Duration> {
+    let secs = raw.trim().parse::<f64>().ok()?;
+    (secs.is_finite() && secs > 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+fn parse_watch_options(args: &[String]) -> Option<WatchOptions> {
+    let mut out = WatchOptions {
+        role: None,
+        assignee: format!("watch-{}", std::process::id()),
+        max_workers: 1,
+        interval: Duration::from_millis(DEFAULT_WATCH_INTERVAL_MS),
+        once: false,
+    };
+    let mut i = 0usize;
+    while i < args.len() {
+        let value = args.get(i + 1).map(|v| v.trim());
+        match args[i].as_str() {
+            "--role" => out.role = Some(value.filter(|v| !v.is_empty())?.to_string()),
+            "--as" => out.assignee = value.filter(|v| !v.is_empty())?.to_string(),
+            "--max-workers" => {
+                out.max_workers = value?.parse::<usize>().ok().filter(|n| *n >= 1)?;
+            }
+            "--interval" => out.interval = parse_interval(value?)?,
+            "--once" => {
+                out.once = true;
+                i += 1;
+                continue;
+            }
+            _ => return None,
+        }
+        i += 2;
+    }
+    Some(out)
+}
+
+/// Pending tasks for `role` whose dependencies are all complete, in file order.
+fn ready_tasks<'a>(
+    tasks: &'a [TaskRecord],
+    role: Option<&str>,
+    running: &HashSet<String>,
+) -> Vec<&'a TaskRecord> {
+    let complete: HashSet<&str> = tasks
+        .iter()
+        .filter(|t| t.status == "complete")
+        .map(|t| t.id.as_str())
+        .collect();
+    tasks
+        .iter()
+        .filter(|t| t.status == "pending" && !running.contains(&t.id))
+        .filter(|t| role.is_none_or(|r| t.role == r))
+        .filter(|t| {
+            effective_dependencies(t)
+                .iter()
+                .all(|d| complete.contains(d.as_str()))
+        })
+        .collect()
+}
+
+struct Worker {
+    id: String,
+    join: JoinHandle<Result<(i32, Option<String>), String>>,
+}
+
+#[derive(Default)]
+struct WatchSummary {
+    complete: usize,
+    failed: usize,
+}
+
+fn finish(worker: Worker, summary: &mut WatchSummary) {
+    let id = worker.id;
+    let (status, execution_id) = match worker.join.join() {
+        Ok(Ok((0, execution_id))) => ("complete", execution_id),
+        Ok(Ok((_, execution_id))) => ("failed", execution_id),
+        Ok(Err(e)) => {
+            crate::cx_eprintln!("cxrs task watch: critical error for {id}: {e}");
+            ("failed", None)
+        }
+        Err(_) => {
+            crate::cx_eprintln!("cxrs task watch: worker thread panicked for {id}");
+            ("failed", None)
+        }
+    };
+    if status == "complete" {
+        summary.complete += 1;
+    } else {
+        summary.failed += 1;
+    }
+    if let Err(e) = set_task_outcome(&id, status, execution_id.as_deref()) {
+        crate::cx_eprintln!("cxrs task watch: cannot record outcome for {id}: {e}");
+    }
+    match execution_id {
+        Some(eid) => println!("{id}: {status} (execution_id: {eid})"),
+        None => println!("{id}: {status}"),
+    }
+}
+
+/// Claim and start ready tasks until `max_workers` are running. Returns how
+/// many were started; a task another worker claimed first is skipped.
+fn launch_ready(
+    opts: &WatchOptions,
+    active: &mut Vec<Worker>,
+    launched: &mut usize,
+) -> Result<usize, String> {
+    let tasks = read_tasks()?;
+    let running: HashSet<String> = active.iter().map(|w| w.id.clone()).collect();
+    let mut started = 0usize;
+    for task in ready_tasks(&tasks, opts.role.as_deref(), &running) {
+        if active.len() >= opts.max_workers {
+            break;
+        }
+        if claim_task(&task.id, Some(&opts.assignee)).is_err() {
+            continue;
+        }
+        let Some(backend) = worker_backend(task, *launched) else {
+            let _ = release_claim(&task.id);
+            return Err("no available backend (install codex or ollama)".to_string());
+        };
+        *launched += 1;
+        println!(
+            "{}: in_progress (assignee: {}, backend: {backend})",
+            task.id, opts.assignee
+        );
+        let id = task.id.clone();
+        let worker_id = format!(
+            "{}-w{}",
+            opts.assignee,
+            (*launched - 1) % opts.max_workers + 1
+        );
+        let parent_id = task.parent_id.clone();
+        let max_retries = task.max_retries.unwrap_or(0);
+        let join = thread::spawn(move || {
+            run_task_managed_subprocess(id, backend, 0, worker_id, parent_id, max_retries)
+        });
+        active.push(Worker {
+            id: task.id.clone(),
+            join,
+        });
+        started += 1;
+    }
+    Ok(started)
+}
+
+/// `task watch`: a long-running worker that claims ready pending tasks as they
+/// appear in `tasks.json` and runs them like `task run-all` workers do.
+pub fn cmd_task_watch(args: &[String]) -> i32 {
+    let Some(opts) = parse_watch_options(args) else {
+        return print_usage_error("task watch", TASK_WATCH_USAGE);
+    };
+    let _guard = ShutdownGuard::install();
+    println!("== cxrs task watch ==");
+    println!(
+        "assignee: {} | role: {} | max_workers: {} | interval: {:.1}s",
+        opts.assignee,
+        opts.role.as_deref().unwrap_or("any"),
+        opts.max_workers,
+        opts.interval.as_secs_f64()
+    );
+    let mut active: Vec<Worker> = Vec::new();
+    let mut summary = WatchSummary::default();
+    let mut launched = 0usize;
+    let mut fatal: Option<String> = None;
+    loop {
+        let (done, running): (Vec<Worker>, Vec<Worker>) =
+            active.into_iter().partition(|w| w.join.is_finished());
+        active = running;
+        for worker in done {
+            finish(worker, &mut summary);
+        }
+        if shutdown_requested() {
+            break;
+        }
+        let started = match launch_ready(&opts, &mut active, &mut launched) {
+            Ok(n) => n,
+            Err(e) => {
+                fatal = Some(e);
+                break;
+            }
+        };
+        if opts.once && started == 0 && active.is_empty() {
+            break;
+        }
+        let tick = if active.is_empty() {
+            opts.interval
+        } else {
+            opts.interval.min(Duration::from_millis(250))
+        };
+        if !sleep_unless_shutdown(tick) {
+            break;
+        }
+    }
+    if !active.is_empty() {
+        println!("waiting for {} running task(s)...", active.len());
+    }
+    for worker in active {
+        finish(worker, &mut summary);
+    }
+    println!("== cxrs task watch stopped ==");
+    println!(
+        "task watch summary: complete={}, failed={}",
+        summary.complete, summary.failed
+    );
+    if let Some(e) = fatal {
+        return print_runtime_error("task watch", &e);
+    }
+    if summary.failed > 0 {
+        EXIT_RUNTIME
+    } else {
+        EXIT_OK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn task(id: &str, role: &str, status: &str, depends_on: &[&str]) -> TaskRecord {
+        serde_json::from_value(json!({
+            "id": id, "parent_id": null, "role": role, "objective": "x",
+            "context_ref": "", "status": status, "depends_on": depends_on,
+            "created_at": "t0", "updated_at": "t0"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn ready_tasks_filter_role_dependencies_and_running() {
+        let tasks = vec![
+            task("task_001", "implementer", "complete", &[]),
+            task("task_002", "implementer", "pending", &["task_001"]),
+            task("task_003", "tester", "pending", &[]),
+            task("task_004", "implementer", "pending", &["task_002"]),
+            task("task_005", "implementer", "pending", &[]),
+        ];
+        let ids = |v: Vec<&TaskRecord>| v.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+        let none = HashSet::new();
+        assert_eq!(
+            ids(ready_tasks(&tasks, None, &none)),
+            vec!["task_002", "task_003", "task_005"]
+        );
+        assert_eq!(
+            ids(ready_tasks(&tasks, Some("tester"), &none)),
+            vec!["task_003"]
+        );
+        let running = HashSet::from(["task_002".to_string()]);
+        assert_eq!(
+            ids(ready_tasks(&tasks, Some("implementer"), &running)),
+            vec!["task_005"]
+        );
+    }
+
+    #[test]
+    fn watch_options_reject_bad_values() {
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let opts = parse_watch_options(&args(&["--role", "tester", "--as", "w1", "--once"]))
+            .expect("options");
+        assert_eq!(opts.role.as_deref(), Some("tester"));
+        assert_eq!(opts.assignee, "w1");
+        assert!(opts.once);
+        assert!(parse_watch_options(&args(&["--max-workers", "0"])).is_none());
+        assert!(parse_watch_options(&args(&["--interval", "soon"])).is_none());
+        assert!(parse_watch_options(&args(&["--role"])).is_none());
+    }
+}
@@ -10,6 +10,7 @@ use std::process::Command;
 use crate::logs::file_len;
 use crate::paths::resolve_log_file;
 use crate::runlog::{RunLogInput, log_codex_run};
+use crate::task_results::{TaskResult, write_task_result};
 use crate::types::{ExecutionResult, LlmOutputKind, TaskInput, TaskRecord, TaskSpec};
 
 #[derive(Debug, Clone)]
@@ -25,16 +26,19 @@ impl fmt::Display for TaskRunError {
     }
 }
 
+/// Apply a change to one task under the tasks lock.
+pub type UpdateTaskFn = fn(&str, &mut dyn FnMut(&mut TaskRecord)) -> Result<(), String>;
+
 pub struct TaskRunner {
     pub read_tasks: fn() -> Result<Vec<TaskRecord>, String>,
-    pub write_tasks: fn(&[TaskRecord]) -> Result<(), String>,
+    pub update_task: UpdateTaskFn,
     pub current_task_id: fn() -> Option<String>,
     pub current_task_parent_id: fn() -> Option<String>,
     pub set_state_path: fn(&str, Value) -> Result<(), String>,
     pub utc_now_iso: fn() -> String,
-    pub cmd_commitjson: fn() -> i32,
-    pub cmd_commitmsg: fn() -> i32,
-    pub cmd_diffsum: fn(bool) -> i32,
+    pub cmd_commitjson: fn(&[String]) -> i32,
+    pub cmd_commitmsg: fn(&[String]) -> i32,
+    pub cmd_diffsum: fn(bool, &[String]) -> i32,
     pub cmd_next: fn(&[String]) -> i32,
     pub cmd_fix_run: fn(&[String]) -> i32,
     pub cmd_fix: fn(&[String]) -> i32,
@@ -49,9 +53,13 @@ struct ReplicaOutcome {
     index: u32,
     status_code: i32,
     execution_id: Option<String>,
+    output: Option<String>,
     error: Option<String>,
 }
 
+/// (exit status, execution_id, captured model output).
+type ObjectiveRun = (i32, Option<String>, Option<String>);
+
 fn parse_words(input: &str) -> Vec<String> {
     match shell_words::split(input) {
         Ok(v) => v,
@@ -119,7 +127,7 @@ fn run_task_prompt(
     mode_override: Option<&str>,
     backend_override: Option<&str>,
     model_override: Option<&str>,
-) -> Result<(i32, Option<String>), String> {
+) -> Result<ObjectiveRun, String> {
     let prev_mode = env::var("CX_MODE").ok();
     let prev_backend = env::var("CX_LLM_BACKEND").ok();
     let prev_ollama_model = env::var("CX_OLLAMA_MODEL").ok();
@@ -141,13 +149,15 @@ fn run_task_prompt(
         schema_task_input: None,
         logging_enabled: true,
         capture_override: None,
+        prompt_template: None,
+        stream: false,
     });
     set_optional_env("CX_MODE", prev_mode);
     set_optional_env("CX_LLM_BACKEND", prev_backend);
     set_optional_env("CX_OLLAMA_MODEL", prev_ollama_model);
     let res = exec_result?;
     println!("{}", res.stdout);
-    Ok((0, Some(res.execution_id)))
+    Ok((0, Some(res.execution_id), Some(res.stdout)))
 }
 
 fn run_objective_subprocess(
@@ -219,7 +229,7 @@ fn dispatch_task_command(
     task: &TaskRecord,
     mode_override: Option<&str>,
     backend_override: Option<&str>,
-) -> Result<(i32, Option<String>), String> {
+) -> Result<ObjectiveRun, String> {
     let Some(cmd0) = words.first().map(String::as_str) else {
         return run_task_prompt(runner, task, mode_override, backend_override, None);
     };
@@ -236,16 +246,16 @@ fn dispatch_task_command(
                     backend_override,
                     model_override.as_deref(),
                 )?;
-                return Ok((code, None));
+                return Ok((code, None, None));
             }
             _ => {}
         }
     }
     let status = match cmd0 {
-        "cxcommitjson" | "commitjson" => (runner.cmd_commitjson)(),
-        "cxcommitmsg" | "commitmsg" => (runner.cmd_commitmsg)(),
-        "cxdiffsum" | "diffsum" => (runner.cmd_diffsum)(false),
-        "cxdiffsum_staged" | "diffsum-staged" => (runner.cmd_diffsum)(true),
+        "cxcommitjson" | "commitjson" => (runner.cmd_commitjson)(&args),
+        "cxcommitmsg" | "commitmsg" => (runner.cmd_commitmsg)(&args),
+        "cxdiffsum" | "diffsum" => (runner.cmd_diffsum)(false, &args),
+        "cxdiffsum_staged" | "diffsum-staged" => (runner.cmd_diffsum)(true, &args),
         "cxnext" | "next" => command_status_or_usage(runner.cmd_next, &args),
         "cxfix_run" | "fix-run" => command_status_or_usage(runner.cmd_fix_run, &args),
         "cxfix" | "fix" => command_status_or_usage(runner.cmd_fix, &args),
@@ -262,7 +272,7 @@ fn dispatch_task_command(
             );
         }
     };
-    Ok((status, None))
+    Ok((status, None, None))
 }
 
 fn run_task_objective(
@@ -270,18 +280,18 @@ fn run_task_objective(
     task: &TaskRecord,
     mode_override: Option<&str>,
     backend_override: Option<&str>,
-) -> Result<(i32, Option<String>), String> {
+) -> Result<ObjectiveRun, String> {
     let log_cursor = capture_log_cursor();
     let words = parse_words(&task.objective);
-    let (status, execution_id) =
+    let (status, execution_id, output) =
         dispatch_task_command(runner, &words, task, mode_override, backend_override)?;
     if execution_id.is_some() {
-        return Ok((status, execution_id));
+        return Ok((status, execution_id, output));
     }
     let recovered = log_cursor
         .as_ref()
         .and_then(|(p, offset)| recover_execution_id_from_log(p, *offset));
-    Ok((status, recovered))
+    Ok((status, recovered, output))
 }
 
 fn normalize_converge_mode(raw: &str) -> String {
@@ -307,6 +317,7 @@ fn select_winner(mode: &str, outcomes: &[ReplicaOutcome]) -> ReplicaOutcome {
             index: 1,
             status_code: 1,
             execution_id: None,
+            output: None,
             error: Some("no replica outcomes".to_string()),
         };
     }
@@ -439,6 +450,8 @@ fn judge_winner_with_model(
         schema_task_input: Some(prompt),
         logging_enabled: true,
         capture_override: None,
+        prompt_template: None,
+        stream: false,
     });
     set_optional_env("CX_MODE", prev_mode);
     set_optional_env("CX_LLM_BACKEND", prev_backend);
@@ -472,16 +485,18 @@ fn run_replica(
     set_optional_env("CX_TASK_CONVERGE_MODE", Some(converge_mode.to_string()));
     set_optional_env("CX_TASK_CONVERGE_WINNER", None);
     match run_task_objective(runner, task, mode_override, backend_override) {
-        Ok((code, execution_id)) => ReplicaOutcome {
+        Ok((code, execution_id, output)) => ReplicaOutcome {
             index: replica_index,
             status_code: code,
             execution_id,
+            output,
             error: None,
         },
         Err(e) => ReplicaOutcome {
             index: replica_index,
             status_code: 1,
             execution_id: None,
+            output: None,
             error: Some(e),
         },
     }
@@ -551,6 +566,7 @@ fn log_convergence_summary(
         prompt: &task.objective,
         prompt_raw: None,
         prompt_filtered: None,
+        response: None,
         schema_prompt: None,
         schema_raw: None,
         schema_attempt: None,
@@ -564,8 +580,12 @@ fn log_convergence_summary(
         schema_reason: None,
         schema_name: None,
         quarantine_id: None,
+        schema_repair: None,
         policy_blocked: None,
         policy_reason: None,
+        fix_decisions: None,
+        prompt_template: None,
+        clipboard_provider: None,
     });
     set_optional_env("CX_TASK_CONVERGE_VOTES", prev_votes);
 }
@@ -600,19 +620,27 @@ fn finalize_task_status(
     runner: &TaskRunner,
     id: &str,
     status_code: i32,
+    execution_id: Option<String>,
+    result_ref: Option<String>,
 ) -> Result<(), TaskRunError> {
-    let mut tasks = (runner.read_tasks)().map_err(TaskRunError::Critical)?;
-    let idx = tasks
-        .iter()
-        .position(|t| t.id == id)
-        .ok_or_else(|| TaskRunError::Critical(format!("cxrs task run: task disappeared: {id}")))?;
-    tasks[idx].status = if status_code == 0 {
-        "complete".to_string()
+    let status = if status_code == 0 {
+        "complete"
     } else {
-        "failed".to_string()
+        "failed"
     };
-    tasks[idx].updated_at = (runner.utc_now_iso)();
-    (runner.write_tasks)(&tasks).map_err(TaskRunError::Critical)?;
+    let now = (runner.utc_now_iso)();
+    let (mut execution_id, mut result_ref) = (execution_id, result_ref);
+    (runner.update_task)(id, &mut |task| {
+        task.status = status.to_string();
+        if let Some(eid) = execution_id.take() {
+            task.execution_id = Some(eid);
+        }
+        if let Some(r) = result_ref.take() {
+            task.result_ref = Some(r);
+        }
+        task.updated_at = now.clone();
+    })
+    .map_err(TaskRunError::Critical)?;
     if (runner.current_task_id)().as_deref() == Some(id) {
         let _ = (runner.set_state_path)("runtime.current_task_id", Value::Null);
     }
@@ -635,9 +663,14 @@ pub fn run_task_by_id(
         return Ok((0, None));
     }
     if !managed_by_parent {
+        let now = (runner.utc_now_iso)();
         tasks[idx].status = "in_progress".to_string();
-        tasks[idx].updated_at = (runner.utc_now_iso)();
-        (runner.write_tasks)(&tasks).map_err(TaskRunError::Critical)?;
+        tasks[idx].updated_at = now.clone();
+        (runner.update_task)(id, &mut |task| {
+            task.status = "in_progress".to_string();
+            task.updated_at = now.clone();
+        })
+        .map_err(TaskRunError::Critical)?;
     }
     let prev_task_id = if managed_by_parent {
         None
@@ -737,9 +770,23 @@ pub fn run_task_by_id(
     let status_code = winner.status_code;
     let execution_id = winner.execution_id.clone();
     let objective_err = winner.error.clone();
+    let finished_at = (runner.utc_now_iso)();
+    let result_ref = match write_task_result(&TaskResult {
+        task: &tasks[idx],
+        status_code,
+        execution_id: execution_id.as_deref(),
+        output: winner.output.as_deref(),
+        finished_at: &finished_at,
+    }) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task run: result not saved for {id}: {e}");
+            None
+        }
+    };
 
     if !managed_by_parent {
-        finalize_task_status(runner, id, status_code)?;
+        finalize_task_status(runner, id, status_code, execution_id.clone(), result_ref)?;
     }
     if let Some(e) = objective_err {
         crate::cx_eprintln!("cxrs task run: objective failed for {id}: {e}");
@@ -756,6 +803,7 @@ mod tests {
             index,
             status_code,
             execution_id: None,
+            output: None,
             error: None,
         }
     }
@@ -2,20 +2,28 @@ use std::fs::File;
 use std::io::Read;
 
 use crate::execmeta::utc_now_iso;
+use crate::notes::{notes_for, notes_json, read_notes};
 use crate::paths::resolve_tasks_file;
+use crate::roles::{find_role, load_roles, role_names};
 use crate::state::write_json_atomic;
+use crate::task_results::{read_task_result, task_result_ref, task_result_summary};
 use crate::types::TaskRecord;
 
+#[path = "tasks_claim.rs"]
+mod tasks_claim;
+pub use tasks_claim::{claim_task, cmd_task_claim, cmd_task_reap, release_claim, update_tasks};
+
 #[path = "tasks_fanout.rs"]
 mod tasks_fanout;
 pub use tasks_fanout::cmd_task_fanout;
 
-pub fn task_role_valid(role: &str) -> bool {
-    matches!(
-        role,
-        "architect" | "implementer" | "reviewer" | "tester" | "doc"
-    )
-}
+#[path = "tasks_io.rs"]
+mod tasks_io;
+pub use tasks_io::{cmd_task_export, cmd_task_import};
+
+#[path = "tasks_template.rs"]
+mod tasks_template;
+pub use tasks_template::cmd_task_template;
 
 pub fn read_tasks() -> Result<Vec<TaskRecord>, String> {
     let path = resolve_tasks_file()?;
@@ -357,9 +365,18 @@ fn parse_task_add_args(app_name: &str, args: &[String]) -> Result<AddArgs, i32>
         max_retries,
         timeout_secs,
     ) = parse_add_flags(args, i)?;
-    if !task_role_valid(&role) {
-        crate::cx_eprintln!("cxrs task add: invalid role '{role}'");
-        return Err(2);
+    // Built-in roles plus custom ones from `.codex/roles.json` / state `roles.<name>`.
+    match find_role(&role) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            let known = load_roles().map(|r| role_names(&r)).unwrap_or_default();
+            crate::cx_eprintln!("cxrs task add: invalid role '{role}' (known: {known})");
+            return Err(2);
+        }
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task add: {e}");
+            return Err(1);
+        }
     }
     Ok(AddArgs {
         objective,
@@ -386,45 +403,50 @@ pub fn cmd_task_add(app_name: &str, args: &[String]) -> i32 {
         Err(code) => return code,
     };
 
-    let mut tasks = match read_tasks() {
-        Ok(v) => v,
+    let added = update_tasks(|tasks| {
+        let id = next_task_id(tasks);
+        let now = utc_now_iso();
+        tasks.push(TaskRecord {
+            id: id.clone(),
+            parent_id: parsed.parent_id,
+            role: parsed.role,
+            objective: parsed.objective,
+            context_ref: parsed.context_ref,
+            backend: parsed.backend,
+            model: parsed.model,
+            profile: parsed.profile,
+            converge: parsed.converge,
+            replicas: parsed.replicas,
+            max_concurrency: parsed.max_concurrency,
+            run_mode: parsed.run_mode,
+            depends_on: parsed.depends_on,
+            resource_keys: parsed.resource_keys,
+            max_retries: parsed.max_retries,
+            timeout_secs: parsed.timeout_secs,
+            estimate: None,
+            result_ref: None,
+            execution_id: None,
+            assignee: None,
+            claimed_at: None,
+            status: "pending".to_string(),
+            created_at: now.clone(),
+            updated_at: now,
+        });
+        Ok(id)
+    });
+    match added {
+        Ok(id) => {
+            println!("{id}");
+            0
+        }
         Err(e) => {
-            crate::cx_eprintln!("{e}");
-            return 1;
+            crate::cx_eprintln!("cxrs task add: {e}");
+            1
         }
-    };
-    let id = next_task_id(&tasks);
-    let now = utc_now_iso();
-    tasks.push(TaskRecord {
-        id: id.clone(),
-        parent_id: parsed.parent_id,
-        role: parsed.role,
-        objective: parsed.objective,
-        context_ref: parsed.context_ref,
-        backend: parsed.backend,
-        model: parsed.model,
-        profile: parsed.profile,
-        converge: parsed.converge,
-        replicas: parsed.replicas,
-        max_concurrency: parsed.max_concurrency,
-        run_mode: parsed.run_mode,
-        depends_on: parsed.depends_on,
-        resource_keys: parsed.resource_keys,
-        max_retries: parsed.max_retries,
-        timeout_secs: parsed.timeout_secs,
-        status: "pending".to_string(),
-        created_at: now.clone(),
-        updated_at: now,
-    });
-    if let Err(e) = write_tasks(&tasks) {
-        crate::cx_eprintln!("cxrs task add: {e}");
-        return 1;
     }
-    println!("{id}");
-    0
 }
 
-pub fn cmd_task_list(status_filter: Option<&str>) -> i32 {
+pub fn cmd_task_list(status_filter: Option<&str>, assignee: Option<&str>, verbose: bool) -> i32 {
     let tasks = match read_tasks() {
         Ok(v) => v,
         Err(e) => {
@@ -432,30 +454,35 @@ pub fn cmd_task_list(status_filter: Option<&str>) -> i32 {
             return 1;
         }
     };
-    let filtered: Vec<TaskRecord> = match status_filter {
-        Some(s) => tasks.into_iter().filter(|t| t.status == s).collect(),
-        None => tasks,
-    };
+    let filtered: Vec<TaskRecord> = tasks
+        .into_iter()
+        .filter(|t| status_filter.is_none_or(|s| t.status == s))
+        .filter(|t| assignee.is_none_or(|a| t.assignee.as_deref() == Some(a)))
+        .collect();
     if filtered.is_empty() {
         println!("No tasks.");
         return 0;
     }
-    println!("id | role | status | parent_id | objective");
-    println!("---|---|---|---|---");
+    println!("id | role | status | assignee | parent_id | objective");
+    println!("---|---|---|---|---|---");
     for t in filtered {
         println!(
-            "{} | {} | {} | {} | {}",
+            "{} | {} | {} | {} | {} | {}",
             t.id,
             t.role,
             t.status,
-            t.parent_id.unwrap_or_else(|| "-".to_string()),
+            t.assignee.as_deref().unwrap_or("-"),
+            t.parent_id.as_deref().unwrap_or("-"),
             t.objective
         );
+        if verbose && let Some(summary) = task_result_summary(&t.id) {
+            println!("  result: {summary}");
+        }
     }
     0
 }
 
-pub fn cmd_task_show(id: &str) -> i32 {
+pub fn cmd_task_show(id: &str, result: bool) -> i32 {
     let tasks = match read_tasks() {
         Ok(v) => v,
         Err(e) => {
@@ -467,7 +494,34 @@ pub fn cmd_task_show(id: &str) -> i32 {
         crate::cx_eprintln!("cxrs task show: task not found: {id}");
         return 1;
     };
-    match serde_json::to_string_pretty(&task) {
+    if result {
+        return match read_task_result(id) {
+            Ok(Some(text)) => {
+                print!("{text}");
+                0
+            }
+            Ok(None) => {
+                crate::cx_eprintln!("cxrs task show: no result recorded for {id} (run it first)");
+                1
+            }
+            Err(e) => {
+                crate::cx_eprintln!("cxrs task show: {e}");
+                1
+            }
+        };
+    }
+    let mut value = match serde_json::to_value(&task) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task show: render failed: {e}");
+            return 1;
+        }
+    };
+    let notes = notes_for(&read_notes(), "task", id);
+    if !notes.is_empty() {
+        value["notes"] = notes_json(&notes);
+    }
+    match serde_json::to_string_pretty(&value) {
         Ok(s) => {
             println!("{s}");
             0
@@ -479,12 +533,40 @@ pub fn cmd_task_show(id: &str) -> i32 {
     }
 }
 
+/// Set the final status of a run-all worker's task, recording its execution id
+/// and the result file the worker wrote.
+pub fn set_task_outcome(
+    id: &str,
+    new_status: &str,
+    execution_id: Option<&str>,
+) -> Result<(), String> {
+    let has_result = read_task_result(id).ok().flatten().is_some();
+    update_task(id, &mut |task| {
+        task.status = new_status.to_string();
+        if let Some(eid) = execution_id {
+            task.execution_id = Some(eid.to_string());
+        }
+        if has_result {
+            task.result_ref = Some(task_result_ref(id));
+        }
+        task.updated_at = utc_now_iso();
+    })
+}
+
 pub fn set_task_status(id: &str, new_status: &str) -> Result<(), String> {
-    let mut tasks = read_tasks()?;
-    let Some(task) = tasks.iter_mut().find(|t| t.id == id) else {
-        return Err(format!("cxrs task: task not found: {id}"));
-    };
-    task.status = new_status.to_string();
-    task.updated_at = utc_now_iso();
-    write_tasks(&tasks)
+    update_task(id, &mut |task| {
+        task.status = new_status.to_string();
+        task.updated_at = utc_now_iso();
+    })
+}
+
+/// Apply `f` to task `id` under the tasks lock (see [`update_tasks`]).
+pub fn update_task(id: &str, f: &mut dyn FnMut(&mut TaskRecord)) -> Result<(), String> {
+    update_tasks(|tasks| {
+        let Some(task) = tasks.iter_mut().find(|t| t.id == id) else {
+            return Err(format!("cxrs task: task not found: {id}"));
+        };
+        f(task);
+        Ok(())
+    })
 }
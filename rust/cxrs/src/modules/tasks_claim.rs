@@ -0,0 +1,270 @@
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::analytics::parse_ts_epoch;
+use crate::error::{EXIT_OK, print_runtime_error, print_usage_error};
+use crate::execmeta::utc_now_iso;
+use crate::paths::resolve_tasks_file;
+use crate::quarantine_ops::parse_age_secs;
+use crate::state::set_state_path;
+use crate::types::TaskRecord;
+
+use super::{read_tasks, write_tasks};
+
+pub const TASK_CLAIM_USAGE: &str = "task claim <id> [--as NAME]";
+pub const TASK_REAP_USAGE: &str = "task reap --older-than <age> [--dry-run]";
+
+/// How long to wait for another writer to release `tasks.json`.
+const LOCK_WAIT: Duration = Duration::from_secs(5);
+/// A lock older than this was left behind by a crashed process.
+const LOCK_STALE: Duration = Duration::from_secs(30);
+
+/// Run `f` while holding `tasks.json.lock`, so a read-check-write of the task
+/// list is a compare-and-swap between concurrent agents.
+pub fn with_tasks_lock<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let lock = resolve_tasks_file()?.with_extension("json.lock");
+    if let Some(parent) = lock.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("cannot create {}: {e}", parent.display()))?;
+    }
+    let started = Instant::now();
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(&lock) {
+            Ok(_) => break,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                if lock_is_stale(&lock) {
+                    let _ = fs::remove_file(&lock);
+                    continue;
+                }
+                if started.elapsed() >= LOCK_WAIT {
+                    return Err(format!("timed out waiting for {}", lock.display()));
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => return Err(format!("cannot create {}: {e}", lock.display())),
+        }
+    }
+    let out = f();
+    let _ = fs::remove_file(&lock);
+    out
+}
+
+/// Read-modify-write `tasks.json` under [`with_tasks_lock`]. The list is only
+/// written back when `f` succeeds; every writer goes through here so none can
+/// overwrite a concurrent claim.
+pub fn update_tasks<T>(
+    f: impl FnOnce(&mut Vec<TaskRecord>) -> Result<T, String>,
+) -> Result<T, String> {
+    with_tasks_lock(|| {
+        let mut tasks = read_tasks()?;
+        let out = f(&mut tasks)?;
+        write_tasks(&tasks)?;
+        Ok(out)
+    })
+}
+
+fn lock_is_stale(lock: &Path) -> bool {
+    fs::metadata(lock)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+        .is_some_and(|age| age > LOCK_STALE)
+}
+
+/// Claim `id` for `assignee`: refuses when the task is finished or already in
+/// progress, unless the caller names the same assignee, in which case the claim
+/// is refreshed. Unnamed claims never match each other.
+fn try_claim(
+    tasks: &mut [TaskRecord],
+    id: &str,
+    assignee: Option<&str>,
+    now: &str,
+) -> Result<(), String> {
+    let Some(task) = tasks.iter_mut().find(|t| t.id == id) else {
+        return Err(format!("task not found: {id}"));
+    };
+    if matches!(task.status.as_str(), "complete" | "failed") {
+        return Err(format!("{id} is already {}", task.status));
+    }
+    if task.status == "in_progress" && (assignee.is_none() || task.assignee.as_deref() != assignee)
+    {
+        let holder = task.assignee.as_deref().unwrap_or("an unnamed claim");
+        let since = task.claimed_at.as_deref().unwrap_or(&task.updated_at);
+        return Err(format!("{id} is already claimed by {holder} since {since}"));
+    }
+    task.status = "in_progress".to_string();
+    task.assignee = assignee.map(str::to_string);
+    task.claimed_at = Some(now.to_string());
+    task.updated_at = now.to_string();
+    Ok(())
+}
+
+/// Atomically claim `id` for `assignee` (see [`try_claim`]).
+pub fn claim_task(id: &str, assignee: Option<&str>) -> Result<(), String> {
+    update_tasks(|tasks| try_claim(tasks, id, assignee, &utc_now_iso()))
+}
+
+fn release(task: &mut TaskRecord, now: &str) {
+    task.status = "pending".to_string();
+    task.assignee = None;
+    task.claimed_at = None;
+    task.updated_at = now.to_string();
+}
+
+/// Put a claimed task back to `pending` so another worker can take it.
+pub fn release_claim(id: &str) -> Result<(), String> {
+    update_tasks(|tasks| {
+        let Some(task) = tasks.iter_mut().find(|t| t.id == id) else {
+            return Err(format!("task not found: {id}"));
+        };
+        release(task, &utc_now_iso());
+        Ok(())
+    })
+}
+
+/// `task claim <id> [--as NAME]`
+pub fn cmd_task_claim(args: &[String]) -> i32 {
+    let Some(id) = args.first() else {
+        return print_usage_error("task claim", TASK_CLAIM_USAGE);
+    };
+    let assignee = match &args[1..] {
+        [] => None,
+        [flag, name] if flag == "--as" && !name.trim().is_empty() => Some(name.trim()),
+        _ => return print_usage_error("task claim", TASK_CLAIM_USAGE),
+    };
+    if let Err(e) = claim_task(id, assignee) {
+        return print_runtime_error("task claim", &e);
+    }
+    let _ = set_state_path("runtime.current_task_id", Value::String(id.clone()));
+    match assignee {
+        Some(name) => println!("{id}: in_progress (assignee: {name})"),
+        None => println!("{id}: in_progress"),
+    }
+    EXIT_OK
+}
+
+/// In-progress tasks whose claim is at least `max_age_secs` old at `now`.
+fn stale_claims(tasks: &[TaskRecord], max_age_secs: i64, now: i64) -> Vec<usize> {
+    tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.status == "in_progress")
+        .filter(|(_, t)| {
+            let since = t.claimed_at.as_deref().unwrap_or(&t.updated_at);
+            parse_ts_epoch(since).is_some_and(|ts| now - ts >= max_age_secs)
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// `task reap --older-than <age> [--dry-run]`: put stale claims back to pending.
+pub fn cmd_task_reap(args: &[String]) -> i32 {
+    let mut max_age: Option<i64> = None;
+    let mut dry_run = false;
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--older-than" => {
+                let Some(secs) = args.get(i + 1).and_then(|v| parse_age_secs(v)) else {
+                    return print_usage_error("task reap", TASK_REAP_USAGE);
+                };
+                max_age = Some(secs);
+                i += 1;
+            }
+            "--dry-run" => dry_run = true,
+            _ => return print_usage_error("task reap", TASK_REAP_USAGE),
+        }
+        i += 1;
+    }
+    let Some(max_age) = max_age else {
+        return print_usage_error("task reap", TASK_REAP_USAGE);
+    };
+    let reaped = with_tasks_lock(|| {
+        let mut tasks = read_tasks()?;
+        let now = utc_now_iso();
+        let now_epoch = parse_ts_epoch(&now).unwrap_or_default();
+        let stale = stale_claims(&tasks, max_age, now_epoch);
+        let mut lines = Vec::new();
+        for idx in &stale {
+            let t = &mut tasks[*idx];
+            lines.push(format!(
+                "{}: claim by {} since {} released",
+                t.id,
+                t.assignee.as_deref().unwrap_or("-"),
+                t.claimed_at.as_deref().unwrap_or(&t.updated_at)
+            ));
+            release(t, &now);
+        }
+        if !dry_run && !stale.is_empty() {
+            write_tasks(&tasks)?;
+        }
+        Ok(lines)
+    });
+    let lines = match reaped {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("task reap", &e),
+    };
+    if lines.is_empty() {
+        println!("No stale claims.");
+    }
+    for line in lines {
+        if dry_run {
+            println!("would release {line}");
+        } else {
+            println!("{line}");
+        }
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn task(id: &str, status: &str, assignee: Option<&str>, claimed_at: &str) -> TaskRecord {
+        serde_json::from_value(json!({
+            "id": id, "parent_id": null, "role": "implementer", "objective": "x",
+            "context_ref": "", "status": status, "assignee": assignee,
+            "claimed_at": claimed_at, "created_at": "t0", "updated_at": claimed_at
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn claims_refuse_other_holders_and_finished_tasks() {
+        let now = "2026-01-01T00:00:00Z";
+        let mut tasks = vec![
+            task("task_001", "pending", None, now),
+            task("task_002", "in_progress", Some("agent-a"), now),
+            task("task_003", "complete", None, now),
+            task("task_004", "pending", None, now),
+        ];
+        assert!(try_claim(&mut tasks, "task_001", Some("agent-b"), now).is_ok());
+        assert_eq!(tasks[0].assignee.as_deref(), Some("agent-b"));
+        let err = try_claim(&mut tasks, "task_002", Some("agent-b"), now).unwrap_err();
+        assert!(err.contains("already claimed by agent-a"), "{err}");
+        assert!(try_claim(&mut tasks, "task_002", Some("agent-a"), now).is_ok());
+        assert!(try_claim(&mut tasks, "task_003", Some("agent-a"), now).is_err());
+        assert!(try_claim(&mut tasks, "task_404", None, now).is_err());
+        assert!(try_claim(&mut tasks, "task_004", None, now).is_ok());
+        let err = try_claim(&mut tasks, "task_004", None, now).unwrap_err();
+        assert!(err.contains("already claimed by an unnamed claim"), "{err}");
+        assert!(try_claim(&mut tasks, "task_004", Some("agent-a"), now).is_err());
+        assert!(try_claim(&mut tasks, "task_002", None, now).is_err());
+    }
+
+    #[test]
+    fn stale_claims_are_in_progress_and_old_enough() {
+        let tasks = vec![
+            task("task_001", "in_progress", Some("a"), "2026-01-01T00:00:00Z"),
+            task("task_002", "in_progress", Some("b"), "2026-01-01T02:30:00Z"),
+            task("task_003", "pending", None, "2026-01-01T00:00:00Z"),
+        ];
+        let now = parse_ts_epoch("2026-01-01T03:00:00Z").unwrap();
+        assert_eq!(stale_claims(&tasks, 2 * 3_600, now), vec![0]);
+    }
+}
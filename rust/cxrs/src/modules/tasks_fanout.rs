@@ -4,10 +4,12 @@ use std::process::Command;
 use crate::capture::chunk_text_by_budget;
 use crate::config::app_config;
 use crate::execmeta::utc_now_iso;
+use crate::fanout_llm::{LlmSubtask, generate_subtasks};
 use crate::process::run_command_output_with_timeout;
+use crate::structured_cmds::ExecuteTaskFn;
 use crate::types::TaskRecord;
 
-use super::{next_task_id, read_tasks, write_tasks};
+use super::{next_task_id, update_tasks};
 
 fn collect_source_text(source: &str) -> Result<String, i32> {
     let out = match source {
@@ -91,6 +93,11 @@ fn make_subtask(
         },
         max_retries: None,
         timeout_secs: None,
+        estimate: None,
+        result_ref: None,
+        execution_id: None,
+        assignee: None,
+        claimed_at: None,
         status: "pending".to_string(),
         created_at: utc_now_iso(),
         updated_at: utc_now_iso(),
@@ -134,6 +141,11 @@ fn ensure_min_created(
             resource_keys: vec!["repo:read".to_string()],
             max_retries: None,
             timeout_secs: None,
+            estimate: None,
+            result_ref: None,
+            execution_id: None,
+            assignee: None,
+            claimed_at: None,
             status: "pending".to_string(),
             created_at: utc_now_iso(),
             updated_at: utc_now_iso(),
@@ -162,6 +174,11 @@ fn add_fanout_parent(tasks: &mut Vec<TaskRecord>, obj: &str) -> String {
         resource_keys: vec!["repo:write".to_string()],
         max_retries: None,
         timeout_secs: None,
+        estimate: None,
+        result_ref: None,
+        execution_id: None,
+        assignee: None,
+        claimed_at: None,
         status: "pending".to_string(),
         created_at: now.clone(),
         updated_at: now,
@@ -199,7 +216,53 @@ fn create_fanout_children(
     created
 }
 
-fn print_fanout_table(parent_id: &str, created: Vec<TaskRecord>) {
+/// Materialize model-proposed subtasks; sibling `depends_on` indexes become task ids.
+fn create_llm_children(
+    tasks: &mut Vec<TaskRecord>,
+    parent_id: &str,
+    subtasks: &[LlmSubtask],
+) -> Vec<TaskRecord> {
+    let mut created: Vec<TaskRecord> = Vec::new();
+    for sub in subtasks {
+        let mut depends_on = vec![parent_id.to_string()];
+        depends_on.extend(sub.depends_on.iter().map(|d| created[d - 1].id.clone()));
+        let now = utc_now_iso();
+        let rec = TaskRecord {
+            id: next_task_id(tasks),
+            parent_id: Some(parent_id.to_string()),
+            role: sub.role.clone(),
+            objective: sub.objective.clone(),
+            context_ref: "fanout_llm".to_string(),
+            backend: "auto".to_string(),
+            model: None,
+            profile: "balanced".to_string(),
+            converge: "none".to_string(),
+            replicas: 1,
+            max_concurrency: None,
+            run_mode: "parallel".to_string(),
+            depends_on,
+            resource_keys: match sub.role.as_str() {
+                "implementer" => vec!["repo:write".to_string()],
+                _ => vec!["repo:read".to_string()],
+            },
+            max_retries: None,
+            timeout_secs: None,
+            estimate: Some(sub.estimate.clone()),
+            result_ref: None,
+            execution_id: None,
+            assignee: None,
+            claimed_at: None,
+            status: "pending".to_string(),
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        tasks.push(rec.clone());
+        created.push(rec);
+    }
+    created
+}
+
+pub(super) fn print_fanout_table(parent_id: &str, created: Vec<TaskRecord>) {
     println!("parent: {parent_id}");
     println!("id | role | status | context_ref | objective");
     println!("---|---|---|---|---");
@@ -211,21 +274,38 @@ fn print_fanout_table(parent_id: &str, created: Vec<TaskRecord>) {
     }
 }
 
-pub fn cmd_task_fanout(app_name: &str, objective: &str, from: Option<&str>) -> i32 {
+/// `task fanout`: split `objective` into child tasks, by diff chunk or (with
+/// `llm`) by asking the backend for a schema-validated subtask list.
+pub fn cmd_task_fanout(
+    app_name: &str,
+    objective: &str,
+    from: Option<&str>,
+    llm: Option<ExecuteTaskFn>,
+) -> i32 {
     let obj = objective.trim();
     if obj.is_empty() {
         crate::cx_eprintln!("Usage: {app_name} task fanout <objective>");
         return 2;
     }
-    let mut tasks = match read_tasks() {
-        Ok(v) => v,
-        Err(e) => {
-            crate::cx_eprintln!("{e}");
-            return 1;
-        }
-    };
+    if let Some(execute_task) = llm {
+        let context = match from.map(collect_source_text).transpose() {
+            Ok(v) => v,
+            Err(code) => return code,
+        };
+        let subtasks = match generate_subtasks(obj, context.as_deref(), execute_task) {
+            Ok(v) => v,
+            Err(e) => {
+                crate::cx_eprintln!("cxrs task fanout: {e}");
+                return 1;
+            }
+        };
+        return write_fanout(|tasks| {
+            let parent_id = add_fanout_parent(tasks, obj);
+            let created = create_llm_children(tasks, &parent_id, &subtasks);
+            (parent_id, created)
+        });
+    }
 
-    let parent_id = add_fanout_parent(&mut tasks, obj);
     let source = from.unwrap_or("worktree");
     let diff = match collect_source_text(source) {
         Ok(v) => v,
@@ -237,18 +317,29 @@ pub fn cmd_task_fanout(app_name: &str, objective: &str, from: Option<&str>) -> i
         chunk_text_by_budget(&diff, app_config().budget_chars)
     };
 
-    let created = create_fanout_children(
-        &mut tasks,
-        &parent_id,
-        obj,
-        !chunks.is_empty(),
-        chunks.len().clamp(1, 6),
-    );
+    write_fanout(|tasks| {
+        let parent_id = add_fanout_parent(tasks, obj);
+        let created = create_fanout_children(
+            tasks,
+            &parent_id,
+            obj,
+            !chunks.is_empty(),
+            chunks.len().clamp(1, 6),
+        );
+        (parent_id, created)
+    })
+}
 
-    if let Err(e) = write_tasks(&tasks) {
-        crate::cx_eprintln!("cxrs task fanout: {e}");
-        return 1;
+/// Add a fanout parent and its children under the tasks lock, then print them.
+fn write_fanout(add: impl FnOnce(&mut Vec<TaskRecord>) -> (String, Vec<TaskRecord>)) -> i32 {
+    match update_tasks(|tasks| Ok(add(tasks))) {
+        Ok((parent_id, created)) => {
+            print_fanout_table(&parent_id, created);
+            0
+        }
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task fanout: {e}");
+            1
+        }
     }
-    print_fanout_table(&parent_id, created);
-    0
 }
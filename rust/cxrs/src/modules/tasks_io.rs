@@ -0,0 +1,503 @@
+use serde_json::{Map, Value, json};
+use std::fs;
+
+use crate::execmeta::utc_now_iso;
+use crate::types::TaskRecord;
+
+use super::{read_tasks, update_tasks};
+
+const TASK_STATUSES: &[&str] = &["pending", "in_progress", "complete", "failed"];
+const EXPORT_USAGE: &str = "task export [--format md|json]";
+const IMPORT_USAGE: &str = "task import <file.md|file.json> [--format md|json] [--dry-run]";
+
+/// One `- [ ] ...` line of a Markdown checklist.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ChecklistItem {
+    id: Option<String>,
+    role: Option<String>,
+    status: Option<String>,
+    checked: bool,
+    objective: String,
+    /// Index of the enclosing item, from indentation.
+    parent: Option<usize>,
+    depends_on: Vec<String>,
+}
+
+fn checklist_line(t: &TaskRecord, depth: usize) -> String {
+    let mut line = format!(
+        "{}- [{}] {} ({}, {}): {}",
+        "  ".repeat(depth),
+        if t.status == "complete" { "x" } else { " " },
+        t.id,
+        t.role,
+        t.status,
+        t.objective
+    );
+    let deps: Vec<&str> = t
+        .depends_on
+        .iter()
+        .map(String::as_str)
+        .filter(|d| Some(*d) != t.parent_id.as_deref())
+        .collect();
+    if !deps.is_empty() {
+        line.push_str(&format!(" [depends: {}]", deps.join(", ")));
+    }
+    line
+}
+
+/// Nested checklist: children are indented under their parent, in file order.
+pub fn export_markdown(tasks: &[TaskRecord]) -> String {
+    fn walk(tasks: &[TaskRecord], t: &TaskRecord, depth: usize, out: &mut Vec<String>) {
+        out.push(checklist_line(t, depth));
+        for child in tasks
+            .iter()
+            .filter(|c| c.parent_id.as_deref() == Some(t.id.as_str()))
+        {
+            walk(tasks, child, depth + 1, out);
+        }
+    }
+    let mut lines = Vec::new();
+    for root in tasks.iter().filter(|t| {
+        t.parent_id
+            .as_deref()
+            .is_none_or(|p| !tasks.iter().any(|o| o.id == p))
+    }) {
+        walk(tasks, root, 0, &mut lines);
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+fn looks_like_task_id(s: &str) -> bool {
+    s.strip_prefix("task_")
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// `[ ] task_001 (role, status): objective [depends: a, b]`; every part but
+/// the checkbox and objective is optional.
+fn parse_checklist_body(body: &str) -> Option<ChecklistItem> {
+    let (mark, mut rest) = (body.get(..3)?, body.get(3..)?.trim());
+    let checked = match mark {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let mut item = ChecklistItem {
+        checked,
+        ..Default::default()
+    };
+    if let Some((head, tail)) = rest.split_once(' ')
+        && looks_like_task_id(head.trim_end_matches(':'))
+    {
+        item.id = Some(head.trim_end_matches(':').to_string());
+        rest = tail.trim();
+    } else if looks_like_task_id(rest.trim_end_matches(':')) {
+        item.id = Some(rest.trim_end_matches(':').to_string());
+        rest = "";
+    }
+    if let Some(meta) = rest.strip_prefix('(')
+        && let Some((inner, tail)) = meta.split_once("):")
+    {
+        let mut parts = inner.split(',').map(str::trim);
+        item.role = parts.next().filter(|s| !s.is_empty()).map(str::to_string);
+        item.status = parts.next().filter(|s| !s.is_empty()).map(str::to_string);
+        rest = tail.trim();
+    }
+    if let Some(open) = rest.rfind("[depends:")
+        && rest.ends_with(']')
+    {
+        item.depends_on = rest[open + 9..rest.len() - 1]
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+        rest = rest[..open].trim();
+    }
+    item.objective = rest.to_string();
+    Some(item)
+}
+
+fn parse_checklist(text: &str) -> Result<Vec<ChecklistItem>, String> {
+    let mut items: Vec<ChecklistItem> = Vec::new();
+    // (indent, item index) of the open ancestors.
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let indent = line.len() - line.trim_start().len();
+        let Some(body) = line
+            .trim_start()
+            .strip_prefix("- ")
+            .or_else(|| line.trim_start().strip_prefix("* "))
+        else {
+            continue;
+        };
+        let Some(mut item) = parse_checklist_body(body) else {
+            continue;
+        };
+        if item.objective.is_empty() && item.id.is_none() {
+            return Err(format!("line {}: checklist item has no objective", n + 1));
+        }
+        while stack.last().is_some_and(|(i, _)| *i >= indent) {
+            stack.pop();
+        }
+        item.parent = stack.last().map(|(_, idx)| *idx);
+        stack.push((indent, items.len()));
+        items.push(item);
+    }
+    Ok(items)
+}
+
+fn max_task_num<'a>(ids: impl Iterator<Item = &'a str>) -> u64 {
+    ids.filter_map(|id| id.strip_prefix("task_")?.parse::<u64>().ok())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Turn checklist items into partial task objects, allocating ids for items
+/// that have none.
+fn checklist_to_values(items: &[ChecklistItem], existing: &[TaskRecord]) -> Vec<Value> {
+    let mut next = max_task_num(
+        existing
+            .iter()
+            .map(|t| t.id.as_str())
+            .chain(items.iter().filter_map(|i| i.id.as_deref())),
+    );
+    let ids: Vec<String> = items
+        .iter()
+        .map(|i| {
+            i.id.clone().unwrap_or_else(|| {
+                next += 1;
+                format!("task_{next:03}")
+            })
+        })
+        .collect();
+    items
+        .iter()
+        .zip(&ids)
+        .map(|(item, id)| {
+            let current = existing.iter().find(|t| &t.id == id);
+            let status = item.status.clone().unwrap_or_else(|| match current {
+                _ if item.checked => "complete".to_string(),
+                Some(t) if t.status != "complete" => t.status.clone(),
+                _ => "pending".to_string(),
+            });
+            let parent_id = item.parent.map(|p| ids[p].clone());
+            let mut depends_on = item.depends_on.clone();
+            if let Some(p) = &parent_id
+                && current.is_none_or(|t| t.depends_on.contains(p))
+                && !depends_on.contains(p)
+            {
+                depends_on.insert(0, p.clone());
+            }
+            let mut v = json!({"id": id, "status": status});
+            // Un-nested items and missing `[depends: ...]` keep an existing
+            // task's parent and dependencies.
+            if parent_id.is_some() || current.is_none() {
+                v["parent_id"] = json!(parent_id);
+            }
+            if !depends_on.is_empty() || current.is_none() {
+                v["depends_on"] = json!(depends_on);
+            }
+            if !item.objective.is_empty() {
+                v["objective"] = json!(item.objective);
+            }
+            if let Some(role) = &item.role {
+                v["role"] = json!(role);
+            }
+            v
+        })
+        .collect()
+}
+
+fn new_task_value(id: &str, now: &str) -> Value {
+    json!({
+        "id": id,
+        "parent_id": null,
+        "role": "implementer",
+        "objective": "",
+        "context_ref": "",
+        "status": "pending",
+        "created_at": now,
+        "updated_at": now,
+    })
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+}
+
+/// Merge partial task objects into `tasks`: known ids get only the given fields
+/// overwritten, unknown or missing ids become new tasks. Tasks not mentioned
+/// are left alone.
+pub fn merge_task_values(
+    tasks: &mut Vec<TaskRecord>,
+    items: Vec<Value>,
+) -> Result<ImportSummary, String> {
+    let now = utc_now_iso();
+    let mut summary = ImportSummary::default();
+    let mut next = max_task_num(tasks.iter().map(|t| t.id.as_str()));
+    for (n, item) in items.into_iter().enumerate() {
+        let Value::Object(fields) = item else {
+            return Err(format!("item {}: expected a JSON object", n + 1));
+        };
+        let id = match fields.get("id").and_then(Value::as_str) {
+            Some(id) if !id.trim().is_empty() => id.trim().to_string(),
+            _ => {
+                next += 1;
+                format!("task_{next:03}")
+            }
+        };
+        next = next.max(max_task_num(std::iter::once(id.as_str())));
+        if let Some(status) = fields.get("status").and_then(Value::as_str)
+            && !TASK_STATUSES.contains(&status)
+        {
+            return Err(format!("{id}: invalid status '{status}'"));
+        }
+        let pos = tasks.iter().position(|t| t.id == id);
+        let mut base = match pos {
+            Some(i) => serde_json::to_value(&tasks[i])
+                .map_err(|e| format!("failed to encode {id}: {e}"))?,
+            None => new_task_value(&id, &now),
+        };
+        let changed = base
+            .as_object_mut()
+            .is_some_and(|m| overlay(m, fields, &id));
+        if pos.is_none()
+            && base["objective"]
+                .as_str()
+                .is_none_or(|s| s.trim().is_empty())
+        {
+            return Err(format!("{id}: new task needs an objective"));
+        }
+        let mut rec: TaskRecord =
+            serde_json::from_value(base).map_err(|e| format!("{id}: invalid task: {e}"))?;
+        match pos {
+            Some(i) => {
+                if changed {
+                    rec.updated_at = now.clone();
+                    tasks[i] = rec;
+                    summary.updated.push(id);
+                }
+            }
+            None => {
+                tasks.push(rec);
+                summary.created.push(id);
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Copy `fields` onto `base`, keeping `id`/`created_at`; true if anything changed.
+fn overlay(base: &mut Map<String, Value>, fields: Map<String, Value>, id: &str) -> bool {
+    let mut changed = false;
+    for (k, v) in fields {
+        if matches!(k.as_str(), "id" | "created_at" | "updated_at") {
+            continue;
+        }
+        if base.get(&k) != Some(&v) {
+            base.insert(k, v);
+            changed = true;
+        }
+    }
+    base.insert("id".to_string(), json!(id));
+    changed
+}
+
+fn parse_import(raw: &str, format: &str, existing: &[TaskRecord]) -> Result<Vec<Value>, String> {
+    if format == "json" {
+        let v: Value = serde_json::from_str(raw).map_err(|e| format!("invalid JSON: {e}"))?;
+        return match v {
+            Value::Array(items) => Ok(items),
+            Value::Object(mut m) => match m.remove("tasks") {
+                Some(Value::Array(items)) => Ok(items),
+                _ => Err("expected a JSON array of tasks or {\"tasks\": [...]}".to_string()),
+            },
+            _ => Err("expected a JSON array of tasks".to_string()),
+        };
+    }
+    Ok(checklist_to_values(&parse_checklist(raw)?, existing))
+}
+
+fn parse_format(args: &[String], usage: &str) -> Result<(Option<String>, Vec<String>), String> {
+    let mut format: Option<String> = None;
+    let mut rest = Vec::new();
+    let mut i = 0usize;
+    while i < args.len() {
+        if args[i] == "--format" {
+            let v = args
+                .get(i + 1)
+                .map(|v| v.trim().to_lowercase())
+                .filter(|v| matches!(v.as_str(), "md" | "json"))
+                .ok_or_else(|| format!("Usage: cx {usage}"))?;
+            format = Some(v);
+            i += 2;
+            continue;
+        }
+        rest.push(args[i].clone());
+        i += 1;
+    }
+    Ok((format, rest))
+}
+
+/// `task export [--format md|json]`: print the task graph to stdout.
+pub fn cmd_task_export(args: &[String]) -> i32 {
+    let format = match parse_format(args, EXPORT_USAGE) {
+        Ok((f, rest)) if rest.is_empty() => f.unwrap_or_else(|| "md".to_string()),
+        Ok((_, rest)) => {
+            crate::cx_eprintln!("cxrs task export: unknown argument '{}'", rest[0]);
+            return 2;
+        }
+        Err(e) => {
+            crate::cx_eprintln!("{e}");
+            return 2;
+        }
+    };
+    let tasks = match read_tasks() {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{e}");
+            return 1;
+        }
+    };
+    if format == "json" {
+        return match serde_json::to_string_pretty(&tasks) {
+            Ok(s) => {
+                println!("{s}");
+                0
+            }
+            Err(e) => {
+                crate::cx_eprintln!("cxrs task export: render failed: {e}");
+                1
+            }
+        };
+    }
+    print!("{}", export_markdown(&tasks));
+    0
+}
+
+/// `task import <file>`: merge a Markdown checklist or JSON task list into
+/// `.codex/tasks.json`.
+pub fn cmd_task_import(args: &[String]) -> i32 {
+    let (format, rest) = match parse_format(args, IMPORT_USAGE) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{e}");
+            return 2;
+        }
+    };
+    let dry_run = rest.iter().any(|a| a == "--dry-run");
+    let files: Vec<&String> = rest.iter().filter(|a| *a != "--dry-run").collect();
+    let [file] = files.as_slice() else {
+        crate::cx_eprintln!("Usage: cx {IMPORT_USAGE}");
+        return 2;
+    };
+    if file.starts_with("--") {
+        crate::cx_eprintln!("cxrs task import: unknown flag '{file}'");
+        return 2;
+    }
+    let format = format.unwrap_or_else(|| {
+        if file.ends_with(".json") {
+            "json"
+        } else {
+            "md"
+        }
+        .to_string()
+    });
+    let raw = match fs::read_to_string(file) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task import: cannot read {file}: {e}");
+            return 1;
+        }
+    };
+    let merge = |tasks: &mut Vec<TaskRecord>| {
+        parse_import(&raw, &format, tasks).and_then(|items| merge_task_values(tasks, items))
+    };
+    let merged = if dry_run {
+        read_tasks().and_then(|mut tasks| merge(&mut tasks))
+    } else {
+        update_tasks(merge)
+    };
+    let summary = match merged {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task import: {e}");
+            return 1;
+        }
+    };
+    println!(
+        "{}created: {} ({})",
+        if dry_run { "dry-run " } else { "" },
+        summary.created.len(),
+        summary.created.join(", ")
+    );
+    println!(
+        "{}updated: {} ({})",
+        if dry_run { "dry-run " } else { "" },
+        summary.updated.len(),
+        summary.updated.join(", ")
+    );
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, parent: Option<&str>, status: &str) -> TaskRecord {
+        serde_json::from_value(json!({
+            "id": id, "parent_id": parent, "role": "implementer",
+            "objective": format!("do {id}"), "context_ref": "", "status": status,
+            "depends_on": parent.map(|p| vec![p]).unwrap_or_default(),
+            "created_at": "t0", "updated_at": "t0"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn markdown_round_trips_ids_nesting_and_status() {
+        let mut extra = task("task_003", Some("task_001"), "pending");
+        extra.depends_on.push("task_002".to_string());
+        let tasks = vec![
+            task("task_001", None, "complete"),
+            task("task_002", Some("task_001"), "in_progress"),
+            extra,
+        ];
+        let md = export_markdown(&tasks);
+        assert_eq!(
+            md,
+            "- [x] task_001 (implementer, complete): do task_001\n  \
+             - [ ] task_002 (implementer, in_progress): do task_002\n  \
+             - [ ] task_003 (implementer, pending): do task_003 [depends: task_002]\n"
+        );
+        let mut merged = tasks.clone();
+        let items = parse_import(&md, "md", &merged).unwrap();
+        let summary = merge_task_values(&mut merged, items).unwrap();
+        assert_eq!(summary, ImportSummary::default());
+        assert_eq!(merged[2].depends_on, vec!["task_001", "task_002"]);
+    }
+
+    #[test]
+    fn checklist_without_ids_creates_and_merges_tasks() {
+        let mut tasks = vec![task("task_004", None, "in_progress")];
+        let md =
+            "# Release\n\n- [ ] Ship it\n  - [x] Write notes\n- [ ] task_004: edited objective\n";
+        let items = parse_import(md, "md", &tasks).unwrap();
+        let summary = merge_task_values(&mut tasks, items).unwrap();
+        assert_eq!(summary.created, vec!["task_005", "task_006"]);
+        assert_eq!(summary.updated, vec!["task_004"]);
+        assert_eq!(tasks[0].status, "in_progress");
+        assert_eq!(tasks[0].objective, "edited objective");
+        assert_eq!(tasks[0].created_at, "t0");
+        assert_eq!(tasks[2].parent_id.as_deref(), Some("task_005"));
+        assert_eq!(tasks[2].status, "complete");
+
+        let bad = vec![json!({"id": "task_009", "objective": "x", "status": "done"})];
+        assert!(merge_task_values(&mut tasks, bad).is_err());
+        assert!(merge_task_values(&mut tasks, vec![json!({"role": "tester"})]).is_err());
+    }
+}
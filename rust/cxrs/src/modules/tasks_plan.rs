@@ -32,7 +32,8 @@ fn normalize_run_mode(task: &TaskRecord) -> &str {
     }
 }
 
-fn effective_dependencies(task: &TaskRecord) -> Vec<String> {
+/// Explicit `depends_on`, else the parent task.
+pub fn effective_dependencies(task: &TaskRecord) -> Vec<String> {
     if !task.depends_on.is_empty() {
         return task.depends_on.clone();
     }
@@ -265,6 +266,11 @@ mod tests {
             resource_keys: resource_keys.iter().map(|v| (*v).to_string()).collect(),
             max_retries: None,
             timeout_secs: None,
+            estimate: None,
+            result_ref: None,
+            execution_id: None,
+            assignee: None,
+            claimed_at: None,
             status: status.to_string(),
             created_at: "2026-01-01T00:00:00Z".to_string(),
             updated_at: "2026-01-01T00:00:00Z".to_string(),
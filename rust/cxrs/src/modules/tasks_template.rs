@@ -0,0 +1,561 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+
+use crate::execmeta::utc_now_iso;
+use crate::paths::resolve_task_templates_dir;
+use crate::prompt_templates::{fill, placeholder_names};
+use crate::state::write_json_atomic;
+use crate::types::TaskRecord;
+
+use super::tasks_fanout::print_fanout_table;
+use super::{next_task_id, read_tasks, update_tasks};
+
+/// One task of a saved subtree. `key` is local to the template (`t1`, `t2`, ...);
+/// `parent` and `depends_on` refer to other keys.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TemplateTask {
+    pub key: String,
+    #[serde(default)]
+    pub parent: Option<String>,
+    pub role: String,
+    pub objective: String,
+    #[serde(default)]
+    pub context_ref: String,
+    #[serde(default = "default_auto")]
+    pub backend: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default = "default_balanced")]
+    pub profile: String,
+    #[serde(default = "default_none")]
+    pub converge: String,
+    #[serde(default = "default_replicas")]
+    pub replicas: u32,
+    #[serde(default)]
+    pub max_concurrency: Option<u32>,
+    #[serde(default = "default_sequential")]
+    pub run_mode: String,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub resource_keys: Vec<String>,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<String>,
+}
+
+/// `.codex/task_templates/<name>.json`: a task subtree whose objectives and
+/// context refs may use `{{param}}` placeholders.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaskTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub params: Vec<String>,
+    pub tasks: Vec<TemplateTask>,
+}
+
+fn default_auto() -> String {
+    "auto".to_string()
+}
+
+fn default_balanced() -> String {
+    "balanced".to_string()
+}
+
+fn default_none() -> String {
+    "none".to_string()
+}
+
+fn default_replicas() -> u32 {
+    1
+}
+
+fn default_sequential() -> String {
+    "sequential".to_string()
+}
+
+const TEMPLATE_USAGE: &str = "task template <list | save <name> --from <parent_id> [--param key=VALUE]... [--force] | apply <name> --param objective=\"...\" [--param key=VALUE]...>";
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn parse_param(raw: &str) -> Result<(String, String), String> {
+    let (k, v) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("--param expects key=VALUE, got '{raw}'"))?;
+    let k = k.trim();
+    if !valid_name(k) {
+        return Err(format!("invalid param name '{k}'"));
+    }
+    Ok((k.to_string(), v.to_string()))
+}
+
+/// Parent first, then descendants in tasks-file order.
+fn collect_subtree<'a>(tasks: &'a [TaskRecord], root_id: &str) -> Vec<&'a TaskRecord> {
+    let mut ids: Vec<&str> = vec![root_id];
+    loop {
+        let before = ids.len();
+        for t in tasks {
+            if !ids.contains(&t.id.as_str())
+                && t.parent_id.as_deref().is_some_and(|p| ids.contains(&p))
+            {
+                ids.push(&t.id);
+            }
+        }
+        if ids.len() == before {
+            break;
+        }
+    }
+    tasks
+        .iter()
+        .filter(|t| t.id == root_id)
+        .chain(
+            tasks
+                .iter()
+                .filter(|t| t.id != root_id && ids.contains(&t.id.as_str())),
+        )
+        .collect()
+}
+
+/// Replace each literal param value with its `{{key}}` placeholder.
+fn parameterize(text: &str, params: &[(String, String)]) -> String {
+    params
+        .iter()
+        .filter(|(_, v)| !v.is_empty())
+        .fold(text.to_string(), |acc, (k, v)| {
+            acc.replace(v.as_str(), &format!("{{{{{k}}}}}"))
+        })
+}
+
+/// Build a template from the subtree rooted at `root_id`. The root objective
+/// becomes `{{objective}}`; extra `params` map literal values to placeholders.
+/// Dependencies on tasks outside the subtree are dropped.
+pub fn template_from_subtree(
+    name: &str,
+    tasks: &[TaskRecord],
+    root_id: &str,
+    extra: &[(String, String)],
+) -> Result<TaskTemplate, String> {
+    let subtree = collect_subtree(tasks, root_id);
+    let root = subtree
+        .first()
+        .ok_or_else(|| format!("task not found: {root_id}"))?;
+    let mut params = vec![("objective".to_string(), root.objective.clone())];
+    params.extend(extra.iter().filter(|(k, _)| k != "objective").cloned());
+    let keys: HashMap<&str, String> = subtree
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), format!("t{}", i + 1)))
+        .collect();
+    let to_key = |id: &str| keys.get(id).cloned();
+    let out: Vec<TemplateTask> = subtree
+        .iter()
+        .map(|t| TemplateTask {
+            key: keys[t.id.as_str()].clone(),
+            parent: t.parent_id.as_deref().and_then(to_key),
+            role: t.role.clone(),
+            objective: parameterize(&t.objective, &params),
+            context_ref: parameterize(&t.context_ref, &params),
+            backend: t.backend.clone(),
+            model: t.model.clone(),
+            profile: t.profile.clone(),
+            converge: t.converge.clone(),
+            replicas: t.replicas,
+            max_concurrency: t.max_concurrency,
+            run_mode: t.run_mode.clone(),
+            depends_on: t.depends_on.iter().filter_map(|d| to_key(d)).collect(),
+            resource_keys: t.resource_keys.clone(),
+            max_retries: t.max_retries,
+            timeout_secs: t.timeout_secs,
+            estimate: t.estimate.clone(),
+        })
+        .collect();
+    let mut names: Vec<String> = Vec::new();
+    for t in &out {
+        for n in placeholder_names(&t.objective)
+            .into_iter()
+            .chain(placeholder_names(&t.context_ref))
+        {
+            if !names.contains(&n) {
+                names.push(n);
+            }
+        }
+    }
+    Ok(TaskTemplate {
+        name: name.to_string(),
+        source: Some(root_id.to_string()),
+        params: names,
+        tasks: out,
+    })
+}
+
+/// Instantiate `template` into `tasks` with fresh ids; returns the created records.
+pub fn instantiate_template(
+    template: &TaskTemplate,
+    tasks: &mut Vec<TaskRecord>,
+    values: &[(String, String)],
+) -> Result<Vec<TaskRecord>, String> {
+    if let Some(missing) = template
+        .params
+        .iter()
+        .find(|p| !values.iter().any(|(k, _)| k == *p))
+    {
+        return Err(format!("missing --param {missing}=..."));
+    }
+    if let Some((unknown, _)) = values.iter().find(|(k, _)| !template.params.contains(k)) {
+        return Err(format!(
+            "unknown param '{unknown}' (template params: {})",
+            template.params.join(", ")
+        ));
+    }
+    let vars: Vec<(&str, &str)> = values
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    let mut ids: HashMap<&str, String> = HashMap::new();
+    let mut created: Vec<TaskRecord> = Vec::new();
+    for t in &template.tasks {
+        let id = next_task_id(tasks);
+        ids.insert(t.key.as_str(), id.clone());
+        let resolve = |key: &str| {
+            ids.get(key)
+                .cloned()
+                .ok_or_else(|| format!("task {} references unknown key '{key}'", t.key))
+        };
+        let parent_id = t.parent.as_deref().map(resolve).transpose()?;
+        let depends_on = t
+            .depends_on
+            .iter()
+            .map(|d| resolve(d))
+            .collect::<Result<Vec<_>, _>>()?;
+        let now = utc_now_iso();
+        let rec = TaskRecord {
+            id,
+            parent_id,
+            role: t.role.clone(),
+            objective: fill(&t.objective, &vars),
+            context_ref: fill(&t.context_ref, &vars),
+            backend: t.backend.clone(),
+            model: t.model.clone(),
+            profile: t.profile.clone(),
+            converge: t.converge.clone(),
+            replicas: t.replicas,
+            max_concurrency: t.max_concurrency,
+            run_mode: t.run_mode.clone(),
+            depends_on,
+            resource_keys: t.resource_keys.clone(),
+            max_retries: t.max_retries,
+            timeout_secs: t.timeout_secs,
+            estimate: t.estimate.clone(),
+            result_ref: None,
+            execution_id: None,
+            assignee: None,
+            claimed_at: None,
+            status: "pending".to_string(),
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        tasks.push(rec.clone());
+        created.push(rec);
+    }
+    Ok(created)
+}
+
+fn load_template(name: &str) -> Result<TaskTemplate, String> {
+    let path = resolve_task_templates_dir()?.join(format!("{name}.json"));
+    if !path.is_file() {
+        return Err(format!("template not found: {name}"));
+    }
+    let raw =
+        fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    serde_json::from_str(&raw).map_err(|e| format!("invalid JSON in {}: {e}", path.display()))
+}
+
+#[derive(Default)]
+struct TemplateArgs {
+    from: Option<String>,
+    params: Vec<(String, String)>,
+    force: bool,
+}
+
+fn parse_template_args(args: &[String]) -> Result<TemplateArgs, String> {
+    let mut out = TemplateArgs::default();
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--from" | "--param" => {
+                let v = args
+                    .get(i + 1)
+                    .ok_or_else(|| format!("{} requires a value", args[i]))?;
+                if args[i] == "--from" {
+                    out.from = Some(v.clone());
+                } else {
+                    let (k, val) = parse_param(v)?;
+                    out.params.retain(|(pk, _)| *pk != k);
+                    out.params.push((k, val));
+                }
+                i += 2;
+            }
+            "--force" => {
+                out.force = true;
+                i += 1;
+            }
+            other => return Err(format!("unknown flag '{other}'")),
+        }
+    }
+    Ok(out)
+}
+
+fn cmd_template_list() -> i32 {
+    let dir = match resolve_task_templates_dir() {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{e}");
+            return 1;
+        }
+    };
+    let mut names: Vec<String> = fs::read_dir(&dir)
+        .map(|rd| {
+            rd.flatten()
+                .filter_map(|e| {
+                    let p = e.path();
+                    (p.extension().and_then(|v| v.to_str()) == Some("json"))
+                        .then(|| p.file_stem()?.to_str().map(str::to_string))
+                        .flatten()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    if names.is_empty() {
+        println!("No task templates.");
+        return 0;
+    }
+    println!("name | tasks | params | source");
+    println!("---|---|---|---");
+    for name in names {
+        match load_template(&name) {
+            Ok(t) => println!(
+                "{name} | {} | {} | {}",
+                t.tasks.len(),
+                t.params.join(","),
+                t.source.as_deref().unwrap_or("-")
+            ),
+            Err(e) => println!("{name} | - | - | error: {e}"),
+        }
+    }
+    0
+}
+
+fn cmd_template_save(name: &str, args: &[String]) -> i32 {
+    let TemplateArgs {
+        from,
+        params,
+        force,
+    } = match parse_template_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task template save: {e}");
+            return 2;
+        }
+    };
+    let Some(from) = from else {
+        crate::cx_eprintln!("Usage: cx {TEMPLATE_USAGE}");
+        return 2;
+    };
+    let tasks = match read_tasks() {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("{e}");
+            return 1;
+        }
+    };
+    let template = match template_from_subtree(name, &tasks, &from, &params) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task template save: {e}");
+            return 1;
+        }
+    };
+    let path = match resolve_task_templates_dir() {
+        Ok(d) => d.join(format!("{name}.json")),
+        Err(e) => {
+            crate::cx_eprintln!("{e}");
+            return 1;
+        }
+    };
+    if path.exists() && !force {
+        crate::cx_eprintln!(
+            "cxrs task template save: {} exists (use --force to replace)",
+            path.display()
+        );
+        return 1;
+    }
+    let written = serde_json::to_value(&template)
+        .map_err(|e| format!("failed to encode template: {e}"))
+        .and_then(|v| write_json_atomic(&path, &v));
+    if let Err(e) = written {
+        crate::cx_eprintln!("cxrs task template save: {e}");
+        return 1;
+    }
+    println!(
+        "saved task template '{name}' ({} tasks, params: {}) -> {}",
+        template.tasks.len(),
+        template.params.join(","),
+        path.display()
+    );
+    0
+}
+
+fn cmd_template_apply(name: &str, args: &[String]) -> i32 {
+    let params = match parse_template_args(args) {
+        Ok(TemplateArgs {
+            from: None,
+            params,
+            force: false,
+        }) => params,
+        Ok(_) => {
+            crate::cx_eprintln!("Usage: cx {TEMPLATE_USAGE}");
+            return 2;
+        }
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task template apply: {e}");
+            return 2;
+        }
+    };
+    let template = match load_template(name) {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task template apply: {e}");
+            return 1;
+        }
+    };
+    let mut invalid = false;
+    let applied = update_tasks(|tasks| {
+        instantiate_template(&template, tasks, &params).inspect_err(|_| invalid = true)
+    });
+    let mut created = match applied {
+        Ok(v) => v,
+        Err(e) => {
+            crate::cx_eprintln!("cxrs task template apply: {e}");
+            return if invalid { 2 } else { 1 };
+        }
+    };
+    if created.is_empty() {
+        println!("template '{name}' has no tasks");
+        return 0;
+    }
+    let root = created.remove(0);
+    print_fanout_table(&root.id, created);
+    0
+}
+
+/// `task template list|save|apply`.
+pub fn cmd_task_template(args: &[String]) -> i32 {
+    let sub = args.first().map(String::as_str).unwrap_or("list");
+    if sub == "list" {
+        return cmd_template_list();
+    }
+    let Some(name) = args.get(1).filter(|n| valid_name(n)) else {
+        crate::cx_eprintln!("Usage: cx {TEMPLATE_USAGE}");
+        return 2;
+    };
+    match sub {
+        "save" => cmd_template_save(name, &args[2..]),
+        "apply" => cmd_template_apply(name, &args[2..]),
+        _ => {
+            crate::cx_eprintln!("Usage: cx {TEMPLATE_USAGE}");
+            2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, parent: Option<&str>, objective: &str, deps: &[&str]) -> TaskRecord {
+        TaskRecord {
+            id: id.to_string(),
+            parent_id: parent.map(str::to_string),
+            role: "implementer".to_string(),
+            objective: objective.to_string(),
+            context_ref: String::new(),
+            backend: "auto".to_string(),
+            model: None,
+            profile: "balanced".to_string(),
+            converge: "none".to_string(),
+            replicas: 1,
+            max_concurrency: None,
+            run_mode: "parallel".to_string(),
+            depends_on: deps.iter().map(|d| d.to_string()).collect(),
+            resource_keys: Vec::new(),
+            max_retries: None,
+            timeout_secs: None,
+            estimate: None,
+            result_ref: None,
+            execution_id: None,
+            assignee: None,
+            claimed_at: None,
+            status: "complete".to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn save_then_apply_round_trips_subtree_with_fresh_ids() {
+        let tasks = vec![
+            task("task_001", None, "Add login", &[]),
+            task(
+                "task_002",
+                Some("task_001"),
+                "Implement: Add login in auth",
+                &["task_001"],
+            ),
+            task("task_003", None, "Unrelated", &[]),
+            task(
+                "task_004",
+                Some("task_002"),
+                "Test Add login",
+                &["task_002", "task_003"],
+            ),
+        ];
+        let extra = vec![("area".to_string(), "auth".to_string())];
+        let tpl = template_from_subtree("feature", &tasks, "task_001", &extra).unwrap();
+        assert_eq!(tpl.params, vec!["objective", "area"]);
+        assert_eq!(tpl.tasks.len(), 3);
+        assert_eq!(
+            tpl.tasks[1].objective,
+            "Implement: {{objective}} in {{area}}"
+        );
+        assert_eq!(tpl.tasks[2].parent.as_deref(), Some("t2"));
+        assert_eq!(tpl.tasks[2].depends_on, vec!["t2"]);
+
+        let mut current = tasks.clone();
+        let values = vec![
+            ("objective".to_string(), "Add SSO".to_string()),
+            ("area".to_string(), "idp".to_string()),
+        ];
+        let created = instantiate_template(&tpl, &mut current, &values).unwrap();
+        let ids: Vec<&str> = created.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["task_005", "task_006", "task_007"]);
+        assert_eq!(created[1].objective, "Implement: Add SSO in idp");
+        assert_eq!(created[2].depends_on, vec!["task_006"]);
+        assert!(created.iter().all(|t| t.status == "pending"));
+
+        let err = instantiate_template(&tpl, &mut current, &values[..1]).unwrap_err();
+        assert_eq!(err, "missing --param area=...");
+        assert!(template_from_subtree("x", &tasks, "task_999", &[]).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+/// Tokenizer family used for local token estimates.
+///
+/// No vocabulary is shipped, so counts are approximations: codex models use a
+/// cl100k-style pre-tokenizer (words, digit groups, punctuation runs) with a
+/// per-piece cost, Ollama models use a character-ratio heuristic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenizerKind {
+    Codex,
+    Ollama,
+}
+
+impl TokenizerKind {
+    pub fn for_backend(backend: &str) -> Self {
+        if backend.eq_ignore_ascii_case("ollama") {
+            TokenizerKind::Ollama
+        } else {
+            TokenizerKind::Codex
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TokenizerKind::Codex => "bpe-approx",
+            TokenizerKind::Ollama => "char-ratio",
+        }
+    }
+}
+
+/// Common English words up to six letters are usually a single BPE token;
+/// longer or rarer words split roughly every six characters.
+fn word_cost(len: usize) -> usize {
+    len.div_ceil(6).max(1)
+}
+
+fn estimate_bpe(text: &str) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = 0usize;
+    let mut i = 0usize;
+    while i < chars.len() {
+        let c = chars[i];
+        // A single leading space is merged into the following word/punctuation piece.
+        let start = if c == ' ' && chars.get(i + 1).is_some_and(|n| !n.is_whitespace()) {
+            i + 1
+        } else {
+            i
+        };
+        let head = chars[start];
+        let mut j = start + 1;
+        if head.is_ascii_alphabetic() {
+            while j < chars.len() && chars[j].is_ascii_alphabetic() {
+                j += 1;
+            }
+            tokens += word_cost(j - start);
+        } else if head.is_ascii_digit() {
+            while j < chars.len() && chars[j].is_ascii_digit() {
+                j += 1;
+            }
+            tokens += (j - start).div_ceil(3);
+        } else if head == '\n' || head == '\r' {
+            while j < chars.len() && (chars[j] == '\n' || chars[j] == '\r') {
+                j += 1;
+            }
+            tokens += 1;
+        } else if head.is_whitespace() {
+            while j < chars.len() && chars[j].is_whitespace() && chars[j] != '\n' {
+                j += 1;
+            }
+            tokens += 1;
+        } else if head.is_ascii_punctuation() {
+            while j < chars.len() && chars[j].is_ascii_punctuation() {
+                j += 1;
+            }
+            tokens += (j - start).div_ceil(2);
+        } else {
+            // Non-ASCII: roughly one token per character.
+            tokens += 1;
+        }
+        i = j;
+    }
+    tokens
+}
+
+fn estimate_char_ratio(text: &str) -> usize {
+    let (mut ascii, mut other) = (0usize, 0usize);
+    for c in text.chars() {
+        if c.is_ascii() {
+            ascii += 1;
+        } else {
+            other += 1;
+        }
+    }
+    // SentencePiece vocabularies average ~3.5 ASCII chars per token.
+    (ascii * 2).div_ceil(7) + other
+}
+
+pub fn estimate_tokens(text: &str, kind: TokenizerKind) -> usize {
+    match kind {
+        TokenizerKind::Codex => estimate_bpe(text),
+        TokenizerKind::Ollama => estimate_char_ratio(text),
+    }
+}
+
+/// Longest head (or tail) of `text` whose estimate fits within `budget` tokens.
+pub fn clip_to_token_budget(text: &str, budget: usize, kind: TokenizerKind, tail: bool) -> String {
+    if estimate_tokens(text, kind) <= budget {
+        return text.to_string();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let take = |n: usize| -> String {
+        if tail {
+            chars[chars.len() - n..].iter().collect()
+        } else {
+            chars[..n].iter().collect()
+        }
+    };
+    let (mut lo, mut hi) = (0usize, chars.len());
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if estimate_tokens(&take(mid), kind) <= budget {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    take(lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bpe_estimate_tracks_word_and_symbol_pieces() {
+        let kind = TokenizerKind::Codex;
+        assert_eq!(estimate_tokens("", kind), 0);
+        assert_eq!(estimate_tokens("hello world", kind), 2);
+        assert_eq!(estimate_tokens("fn main() {}", kind), 4);
+        assert_eq!(estimate_tokens("1234567", kind), 3);
+        assert_eq!(estimate_tokens("internationalization", kind), 4);
+        assert_eq!(estimate_tokens("a\n\nb", kind), 3);
+    }
+
+    #[test]
+    fn ollama_estimate_uses_char_ratio() {
+        let kind = TokenizerKind::Ollama;
+        assert_eq!(estimate_tokens("abcdefg", kind), 2);
+        assert_eq!(estimate_tokens("日本", kind), 2);
+        assert_eq!(TokenizerKind::for_backend("OLLAMA"), kind);
+    }
+
+    #[test]
+    fn clip_respects_budget_and_direction() {
+        let kind = TokenizerKind::Codex;
+        let text = "one two three four five six";
+        let head = clip_to_token_budget(text, 3, kind, false);
+        assert_eq!(head, "one two three");
+        let tail = clip_to_token_budget(text, 2, kind, true);
+        assert_eq!(tail, " five six");
+        assert!(estimate_tokens(&tail, kind) <= 2);
+        assert_eq!(clip_to_token_budget(text, 100, kind, false), text);
+    }
+}
@@ -0,0 +1,206 @@
+use serde_json::{Value, json};
+
+use crate::quarantine::read_quarantine_record;
+use crate::tasks::read_tasks;
+use crate::transcripts::load_transcript;
+use crate::types::{RunEntry, TaskRecord};
+
+const RAW_SNIPPET_CHARS: usize = 240;
+
+/// Records a run points at: its schema outcome, quarantined response, archived
+/// transcript and the task (with its parents) it ran for. Missing links stay empty.
+pub struct TraceLinks {
+    pub schema: Option<Value>,
+    pub quarantine: Option<Value>,
+    pub transcript: Option<Value>,
+    pub task_chain: Vec<TaskRecord>,
+}
+
+fn snippet(text: &str) -> String {
+    let mut out: String = text.chars().take(RAW_SNIPPET_CHARS).collect();
+    if text.chars().count() > RAW_SNIPPET_CHARS {
+        out.push_str("...");
+    }
+    out
+}
+
+fn schema_link(run: &RunEntry) -> Option<Value> {
+    if run.schema_enforced != Some(true) && run.schema_valid.is_none() {
+        return None;
+    }
+    Some(json!({
+        "valid": run.schema_valid,
+        "reason": run.schema_reason,
+        "attempt": run.schema_attempt,
+        "repair_applied": run.repair_applied,
+    }))
+}
+
+fn quarantine_link(id: &str) -> Value {
+    match read_quarantine_record(id) {
+        Ok(rec) => json!({
+            "id": id,
+            "reason": rec.reason,
+            "schema": rec.schema,
+            "attempts": rec.attempts.len(),
+            "raw_response_chars": rec.raw_response.chars().count(),
+            "raw_response_snippet": snippet(&rec.raw_response),
+        }),
+        Err(e) => json!({ "id": id, "error": e }),
+    }
+}
+
+fn transcript_link(execution_id: &str) -> Option<Value> {
+    let doc = load_transcript(execution_id).ok()?;
+    let chars = |k: &str| {
+        doc.get(k)
+            .and_then(Value::as_str)
+            .map(|s| s.chars().count())
+    };
+    Some(json!({
+        "prompt_chars": chars("prompt"),
+        "response_chars": chars("response"),
+        "redactions_applied": doc.get("redactions_applied").cloned().unwrap_or(Value::Null),
+    }))
+}
+
+/// `task_id` and its ancestors, root first. Stops at a missing parent or a cycle.
+fn task_chain(tasks: &[TaskRecord], task_id: &str) -> Vec<TaskRecord> {
+    let mut chain: Vec<TaskRecord> = Vec::new();
+    let mut next = Some(task_id.to_string());
+    while let Some(id) = next {
+        if chain.iter().any(|t| t.id == id) {
+            break;
+        }
+        let Some(task) = tasks.iter().find(|t| t.id == id) else {
+            break;
+        };
+        next = task.parent_id.clone();
+        chain.push(task.clone());
+    }
+    chain.reverse();
+    chain
+}
+
+impl TraceLinks {
+    pub fn resolve(run: &RunEntry) -> Self {
+        let task_chain = run
+            .task_id
+            .as_deref()
+            .map(|id| task_chain(&read_tasks().unwrap_or_default(), id))
+            .unwrap_or_default();
+        Self {
+            schema: schema_link(run),
+            quarantine: run.quarantine_id.as_deref().map(quarantine_link),
+            transcript: run.execution_id.as_deref().and_then(transcript_link),
+            task_chain,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "schema": self.schema,
+            "quarantine": self.quarantine,
+            "transcript": self.transcript,
+            "task_chain": self.task_chain.iter().map(|t| json!({
+                "id": t.id,
+                "parent_id": t.parent_id,
+                "role": t.role,
+                "status": t.status,
+                "objective": t.objective,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn print(&self) {
+        let text = |v: &Value, k: &str| match v.get(k) {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Null) | None => "n/a".to_string(),
+            Some(other) => other.to_string(),
+        };
+        if let Some(s) = &self.schema {
+            println!(
+                "schema: valid={} attempt={} reason={}",
+                text(s, "valid"),
+                text(s, "attempt"),
+                text(s, "reason")
+            );
+        }
+        if let Some(q) = &self.quarantine {
+            println!("quarantine: {}", text(q, "id"));
+            if q.get("error").is_some() {
+                println!("  error: {}", text(q, "error"));
+            } else {
+                println!("  reason: {}", text(q, "reason"));
+                println!(
+                    "  raw_response ({} chars): {}",
+                    text(q, "raw_response_chars"),
+                    text(q, "raw_response_snippet")
+                );
+            }
+        }
+        if let Some(t) = &self.transcript {
+            println!(
+                "transcript: prompt_chars={} response_chars={}",
+                text(t, "prompt_chars"),
+                text(t, "response_chars")
+            );
+        }
+        if !self.task_chain.is_empty() {
+            println!("task_chain:");
+            for (depth, task) in self.task_chain.iter().enumerate() {
+                println!(
+                    "  {}{} [{}] {}: {}",
+                    "  ".repeat(depth),
+                    task.id,
+                    task.status,
+                    task.role,
+                    task.objective
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, parent: Option<&str>) -> TaskRecord {
+        serde_json::from_value(json!({
+            "id": id, "parent_id": parent, "role": "implementer", "objective": "x",
+            "context_ref": "", "status": "pending", "created_at": "t0", "updated_at": "t0"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn task_chain_walks_root_first_and_stops_on_cycles() {
+        let tasks = vec![
+            task("task_001", None),
+            task("task_002", Some("task_001")),
+            task("task_003", Some("task_002")),
+            task("task_004", Some("task_005")),
+            task("task_005", Some("task_004")),
+        ];
+        let ids = |v: Vec<TaskRecord>| v.into_iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(task_chain(&tasks, "task_003")),
+            vec!["task_001", "task_002", "task_003"]
+        );
+        assert_eq!(
+            ids(task_chain(&tasks, "task_004")),
+            vec!["task_005", "task_004"]
+        );
+        assert!(task_chain(&tasks, "task_404").is_empty());
+    }
+
+    #[test]
+    fn snippet_marks_truncation() {
+        assert_eq!(snippet("short"), "short");
+        let long = "é".repeat(RAW_SNIPPET_CHARS + 5);
+        let s = snippet(&long);
+        assert!(s.ends_with("..."));
+        assert_eq!(s.chars().count(), RAW_SNIPPET_CHARS + 3);
+    }
+}
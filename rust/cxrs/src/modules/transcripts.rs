@@ -0,0 +1,191 @@
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::capture::load_redactor;
+use crate::config::app_config;
+use crate::error::{EXIT_OK, print_runtime_error, print_usage_error};
+use crate::gc::{dir_files, plan_removals};
+use crate::paths::{repo_root, resolve_transcripts_dir};
+use crate::types::ExecutionLog;
+
+pub(crate) const SUFFIX: &str = ".json.gz";
+
+fn transcript_path(dir: &Path, execution_id: &str) -> Result<PathBuf, String> {
+    let valid = !execution_id.is_empty()
+        && execution_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!("invalid execution_id '{execution_id}'"));
+    }
+    Ok(dir.join(format!("{execution_id}{SUFFIX}")))
+}
+
+/// Write `<execution_id>.json.gz` with the full prompt and response, then
+/// apply retention. Both texts go through capture redaction first (unless
+/// `CX_REDACT=0`); an invalid redact config skips archiving rather than
+/// storing unmasked text.
+pub fn archive_transcript(row: &ExecutionLog, prompt: &str, response: &str) -> Result<(), String> {
+    let cfg = app_config();
+    if !cfg.transcripts {
+        return Ok(());
+    }
+    let dir = resolve_transcripts_dir().ok_or("unable to resolve transcripts dir")?;
+    let (prompt, response, redactions) = if cfg.redact {
+        let root = repo_root().unwrap_or_else(|| PathBuf::from("."));
+        let redactor = load_redactor(&root)?;
+        let p = redactor.redact(prompt);
+        let r = redactor.redact(response);
+        let n = p.total() + r.total();
+        (p.text, r.text, Some(n))
+    } else {
+        (prompt.to_string(), response.to_string(), None)
+    };
+    let doc = json!({
+        "execution_id": row.execution_id,
+        "timestamp": row.timestamp,
+        "tool": row.tool,
+        "llm_backend": row.llm_backend,
+        "llm_model": row.llm_model,
+        "redactions_applied": redactions,
+        "prompt": prompt,
+        "response": response,
+    });
+    fs::create_dir_all(&dir).map_err(|e| format!("failed to create {}: {e}", dir.display()))?;
+    let path = transcript_path(&dir, &row.execution_id)?;
+    let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+    enc.write_all(doc.to_string().as_bytes())
+        .and_then(|_| enc.finish())
+        .and_then(|bytes| fs::write(&path, bytes))
+        .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    prune_transcripts(
+        &dir,
+        cfg.transcripts_keep_days,
+        cfg.transcripts_max_bytes as u64,
+    )?;
+    Ok(())
+}
+
+/// Drop transcripts older than `keep_days` (`0` = no age limit), then the
+/// oldest remaining ones until the directory fits in `max_bytes` (`0` = no cap).
+/// Returns how many files were removed.
+pub fn prune_transcripts(dir: &Path, keep_days: usize, max_bytes: u64) -> Result<usize, String> {
+    fs::read_dir(dir).map_err(|e| format!("cannot read {}: {e}", dir.display()))?;
+    let mut files = dir_files(dir, |n| n.ends_with(SUFFIX));
+    files.sort_by_key(|f| f.since);
+    let total = files.iter().map(|f| f.bytes).sum();
+    let plan = plan_removals(&files, total, keep_days, max_bytes, SystemTime::now());
+    for (i, _) in &plan {
+        let path = &files[*i].path;
+        fs::remove_file(path).map_err(|e| format!("failed to remove {}: {e}", path.display()))?;
+    }
+    Ok(plan.len())
+}
+
+pub fn load_transcript(execution_id: &str) -> Result<Value, String> {
+    let dir = resolve_transcripts_dir().ok_or("unable to resolve transcripts dir")?;
+    let path = transcript_path(&dir, execution_id)?;
+    if !path.exists() {
+        return Err(format!("no transcript for {execution_id}"));
+    }
+    let file = fs::File::open(&path).map_err(|e| format!("cannot open {}: {e}", path.display()))?;
+    let mut text = String::new();
+    GzDecoder::new(file)
+        .read_to_string(&mut text)
+        .map_err(|e| format!("cannot decompress {}: {e}", path.display()))?;
+    serde_json::from_str(&text).map_err(|e| format!("invalid JSON in {}: {e}", path.display()))
+}
+
+fn print_transcript(v: &Value) {
+    let field = |k: &str| match v.get(k) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => "n/a".to_string(),
+        Some(other) => other.to_string(),
+    };
+    for key in [
+        "execution_id",
+        "timestamp",
+        "tool",
+        "llm_backend",
+        "llm_model",
+        "redactions_applied",
+    ] {
+        println!("{key}: {}", field(key));
+    }
+    println!("\n--- prompt ---\n{}", field("prompt"));
+    println!("--- response ---\n{}", field("response"));
+}
+
+pub fn cmd_transcript(app_name: &str, args: &[String]) -> i32 {
+    let usage = format!("{app_name} transcript show <execution_id> [--json]");
+    let (id, json_out) = match args {
+        [sub, id] if sub == "show" => (id, false),
+        [sub, id, flag] if sub == "show" && flag == "--json" => (id, true),
+        _ => return print_usage_error("transcript", &usage),
+    };
+    let v = match load_transcript(id) {
+        Ok(v) => v,
+        Err(e) => return print_runtime_error("transcript show", &e),
+    };
+    if json_out {
+        match serde_json::to_string_pretty(&v) {
+            Ok(s) => println!("{s}"),
+            Err(e) => return print_runtime_error("transcript show", &e.to_string()),
+        }
+    } else {
+        print_transcript(&v);
+    }
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn touch(dir: &Path, name: &str, bytes: usize, age_days: u64) {
+        let path = dir.join(name);
+        fs::write(&path, vec![b'x'; bytes]).expect("write");
+        let when = SystemTime::now() - Duration::from_secs(age_days * 86_400 + 60);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(when))
+            .expect("mtime");
+    }
+
+    #[test]
+    fn prune_applies_age_then_size_oldest_first() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        touch(dir.path(), "a.json.gz", 100, 30);
+        touch(dir.path(), "b.json.gz", 100, 3);
+        touch(dir.path(), "c.json.gz", 100, 2);
+        touch(dir.path(), "d.json.gz", 100, 1);
+        touch(dir.path(), "keep.txt", 1000, 90);
+        assert_eq!(prune_transcripts(dir.path(), 14, 250).expect("prune"), 2);
+        let mut left: Vec<String> = fs::read_dir(dir.path())
+            .expect("read")
+            .map(|e| e.expect("entry").file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["c.json.gz", "d.json.gz", "keep.txt"]);
+        assert_eq!(prune_transcripts(dir.path(), 0, 0).expect("prune"), 0);
+    }
+
+    #[test]
+    fn ids_cannot_escape_the_transcripts_dir() {
+        let dir = Path::new("/tmp/t");
+        assert!(transcript_path(dir, "../state").is_err());
+        assert!(transcript_path(dir, "").is_err());
+        assert_eq!(
+            transcript_path(dir, "20240101T000000Z_cxo_1").expect("id"),
+            dir.join("20240101T000000Z_cxo_1.json.gz")
+        );
+    }
+}
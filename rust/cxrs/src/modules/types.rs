@@ -30,8 +30,18 @@ pub struct RunEntry {
     #[serde(default)]
     pub scope: Option<String>,
     #[serde(default)]
+    pub scope_source: Option<String>,
+    #[serde(default)]
     pub repo_root: Option<String>,
     #[serde(default)]
+    pub codex_root: Option<String>,
+    #[serde(default)]
+    pub git_branch: Option<String>,
+    #[serde(default)]
+    pub git_head: Option<String>,
+    #[serde(default)]
+    pub vcs: Option<String>,
+    #[serde(default)]
     pub prompt_sha256: Option<String>,
     #[serde(default)]
     pub prompt_sha256_raw: Option<String>,
@@ -44,8 +54,22 @@ pub struct RunEntry {
     #[serde(default)]
     pub prompt_filter_applied: Option<bool>,
     #[serde(default)]
+    pub prompt_tokens_est: Option<u64>,
+    #[serde(default)]
+    pub token_estimator: Option<String>,
+    #[serde(default)]
+    pub context_window: Option<u64>,
+    #[serde(default)]
+    pub context_guard: Option<String>,
+    #[serde(default)]
     pub schema_prompt_sha256: Option<String>,
     #[serde(default)]
+    pub prompt_template: Option<String>,
+    #[serde(default)]
+    pub prompt_template_source: Option<String>,
+    #[serde(default)]
+    pub prompt_template_sha256: Option<String>,
+    #[serde(default)]
     pub schema_sha256: Option<String>,
     #[serde(default)]
     pub schema_attempt: Option<u64>,
@@ -80,10 +104,50 @@ pub struct RunEntry {
     #[serde(default)]
     pub clip_footer: Option<bool>,
     #[serde(default)]
+    pub system_output_tokens_raw: Option<u64>,
+    #[serde(default)]
+    pub system_output_tokens_clipped: Option<u64>,
+    #[serde(default)]
+    pub budget_tokens: Option<u64>,
+    #[serde(default)]
     pub rtk_used: Option<bool>,
     #[serde(default)]
     pub capture_provider: Option<String>,
     #[serde(default)]
+    pub reducer: Option<String>,
+    #[serde(default)]
+    pub chunk_strategy: Option<String>,
+    #[serde(default)]
+    pub chunk_count: Option<u64>,
+    #[serde(default)]
+    pub redactions_applied: Option<u64>,
+    #[serde(default)]
+    pub stdout_len_raw: Option<u64>,
+    #[serde(default)]
+    pub stdout_len_clipped: Option<u64>,
+    #[serde(default)]
+    pub stdout_lines_raw: Option<u64>,
+    #[serde(default)]
+    pub stdout_lines_clipped: Option<u64>,
+    #[serde(default)]
+    pub stderr_len_raw: Option<u64>,
+    #[serde(default)]
+    pub stderr_len_clipped: Option<u64>,
+    #[serde(default)]
+    pub stderr_lines_raw: Option<u64>,
+    #[serde(default)]
+    pub stderr_lines_clipped: Option<u64>,
+    #[serde(default)]
+    pub ansi_sequences_stripped: Option<u64>,
+    #[serde(default)]
+    pub binary_output: Option<bool>,
+    #[serde(default)]
+    pub execution_id: Option<String>,
+    #[serde(default)]
+    pub parent_execution_id: Option<String>,
+    #[serde(default)]
+    pub chunk_index: Option<u64>,
+    #[serde(default)]
     pub llm_backend: Option<String>,
     #[serde(default)]
     pub llm_model: Option<String>,
@@ -112,14 +176,34 @@ pub struct RunEntry {
     #[serde(default)]
     pub task_parent_id: Option<String>,
     #[serde(default)]
+    pub rerun_of: Option<String>,
+    #[serde(default)]
+    pub pipeline_id: Option<String>,
+    #[serde(default)]
+    pub failover_count: Option<u64>,
+    #[serde(default)]
+    pub codex_args: Option<Vec<String>>,
+    #[serde(default)]
+    pub codex_profile: Option<String>,
+    #[serde(default)]
     pub schema_enforced: Option<bool>,
     #[serde(default)]
     pub schema_valid: Option<bool>,
     #[serde(default)]
+    pub schema_reason: Option<String>,
+    #[serde(default)]
+    pub quarantine_id: Option<String>,
+    #[serde(default)]
+    pub repair_applied: Option<bool>,
+    #[serde(default)]
     pub policy_blocked: Option<bool>,
     #[serde(default)]
     pub policy_reason: Option<String>,
     #[serde(default)]
+    pub fix_decisions: Option<Value>,
+    #[serde(default)]
+    pub pack_manifest: Option<Value>,
+    #[serde(default)]
     pub retry_attempt: Option<u32>,
     #[serde(default)]
     pub retry_max: Option<u32>,
@@ -151,6 +235,10 @@ pub struct QuarantineRecord {
     pub raw_sha256: String,
     #[serde(default)]
     pub attempts: Vec<QuarantineAttempt>,
+    #[serde(default)]
+    pub resolved_ts: Option<String>,
+    #[serde(default)]
+    pub resolved_note: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -167,6 +255,22 @@ pub struct QuarantineAttempt {
     pub raw_sha256: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct SessionTurn {
+    #[serde(default)]
+    pub ts: String,
+    #[serde(default)]
+    pub tool: String,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub prompt: String,
+    #[serde(default)]
+    pub answer: String,
+    #[serde(default)]
+    pub execution_id: Option<String>,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct CaptureStats {
     pub system_output_len_raw: Option<u64>,
@@ -180,8 +284,48 @@ pub struct CaptureStats {
     pub budget_lines: Option<u64>,
     pub clip_mode: Option<String>,
     pub clip_footer: Option<bool>,
+    pub system_output_tokens_raw: Option<u64>,
+    pub system_output_tokens_clipped: Option<u64>,
+    pub budget_tokens: Option<u64>,
     pub rtk_used: Option<bool>,
     pub capture_provider: Option<String>,
+    pub reducer: Option<String>,
+    pub chunk_strategy: Option<String>,
+    pub chunk_count: Option<u64>,
+    /// Secrets masked before the capture reached the prompt; `None` when redaction is off.
+    pub redactions_applied: Option<u64>,
+    /// Per-stream figures for system-command captures (stderr is clipped first).
+    pub stdout_len_raw: Option<u64>,
+    pub stdout_len_clipped: Option<u64>,
+    pub stdout_lines_raw: Option<u64>,
+    pub stdout_lines_clipped: Option<u64>,
+    pub stderr_len_raw: Option<u64>,
+    pub stderr_len_clipped: Option<u64>,
+    pub stderr_lines_raw: Option<u64>,
+    pub stderr_lines_clipped: Option<u64>,
+    /// Escape sequences removed from the capture before prompting.
+    pub ansi_sequences_stripped: Option<u64>,
+    /// Output looked binary and was replaced with a size/type notice.
+    pub binary_output: Option<bool>,
+    /// Map-reduce summarization sub-calls, logged as child rows of the parent run.
+    pub chunk_calls: Vec<ChunkCall>,
+}
+
+/// Which prompt template rendered a task prompt (built-in or `.codex/prompts` override).
+#[derive(Debug, Clone)]
+pub struct PromptTemplateStamp {
+    pub name: String,
+    pub source: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ChunkCall {
+    pub index: u64,
+    pub prompt: String,
+    pub usage: UsageStats,
+    pub duration_ms: u64,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -214,12 +358,17 @@ pub struct TaskSpec {
     pub schema_task_input: Option<String>,
     pub logging_enabled: bool,
     pub capture_override: Option<CaptureStats>,
+    pub prompt_template: Option<PromptTemplateStamp>,
+    /// Print backend output as it arrives (`--stream`); the caller then skips
+    /// printing `ExecutionResult::stdout`.
+    pub stream: bool,
 }
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct ExecutionResult {
     pub stdout: String,
+    pub prompt: String,
     pub stderr: String,
     pub duration_ms: u64,
     pub schema_valid: Option<bool>,
@@ -247,7 +396,17 @@ pub struct ExecutionLog {
     pub tool: String,
     pub cwd: String,
     pub scope: String,
+    /// How the scope was decided: `env`, `marker`, `git`, `home` (the project
+    /// root is `$HOME`, so repo and global runs share a log) or `none` (global).
+    pub scope_source: Option<String>,
     pub repo_root: String,
+    /// Dir whose `.codex` served the run: `repo_root`, or a package below it
+    /// under `runtime.codex_root=nearest`; `None` for global runs.
+    pub codex_root: Option<String>,
+    pub git_branch: Option<String>,
+    pub git_head: Option<String>,
+    /// `git`, `jj`, `hg` or `none` (a `.codex`/`CX_PROJECT_ROOT` directory).
+    pub vcs: Option<String>,
     pub backend_used: String,
     pub llm_backend: String,
     pub llm_model: Option<String>,
@@ -260,6 +419,14 @@ pub struct ExecutionLog {
     pub model_selected: Option<String>,
     pub route_policy: Option<String>,
     pub route_reason: Option<String>,
+    /// Extra `codex exec` options the run was invoked with (codex runs only).
+    pub codex_args: Option<Vec<String>>,
+    /// `--profile` from `codex_args`, for attributing runs to a codex profile.
+    pub codex_profile: Option<String>,
+    /// Backends of `preferences.backend_chain` that failed before one served the run.
+    pub failover_count: Option<u64>,
+    /// `<backend>: <error>` per failed backend, in chain order.
+    pub failover_reasons: Option<Vec<String>>,
     pub worker_id: Option<String>,
     pub replica_index: Option<u32>,
     pub replica_count: Option<u32>,
@@ -268,6 +435,23 @@ pub struct ExecutionLog {
     pub converge_votes: Option<Value>,
     pub queue_ms: Option<u64>,
     pub capture_provider: Option<String>,
+    pub reducer: Option<String>,
+    pub chunk_strategy: Option<String>,
+    pub chunk_count: Option<u64>,
+    pub redactions_applied: Option<u64>,
+    pub stdout_len_raw: Option<u64>,
+    pub stdout_len_clipped: Option<u64>,
+    pub stdout_lines_raw: Option<u64>,
+    pub stdout_lines_clipped: Option<u64>,
+    pub stderr_len_raw: Option<u64>,
+    pub stderr_len_clipped: Option<u64>,
+    pub stderr_lines_raw: Option<u64>,
+    pub stderr_lines_clipped: Option<u64>,
+    pub ansi_sequences_stripped: Option<u64>,
+    pub binary_output: Option<bool>,
+    pub parent_execution_id: Option<String>,
+    pub chunk_index: Option<u64>,
+    pub clipboard_provider: Option<String>,
     pub execution_mode: String,
     pub duration_ms: Option<u64>,
     pub schema_enforced: bool,
@@ -276,8 +460,16 @@ pub struct ExecutionLog {
     pub schema_ok: bool,
     pub schema_reason: Option<String>,
     pub quarantine_id: Option<String>,
+    /// Schema runs only: whether the reply validated after a local repair.
+    pub repair_applied: Option<bool>,
+    /// `strip_code_fence`, `strip_leading_text`, `strip_trailing_text`.
+    pub repair_steps: Option<Vec<String>>,
     pub task_id: Option<String>,
     pub task_parent_id: Option<String>,
+    /// Execution this row re-ran (`rerun <execution_id>`).
+    pub rerun_of: Option<String>,
+    /// `pipeline run` that produced this row; its steps share one capture.
+    pub pipeline_id: Option<String>,
     pub input_tokens: Option<u64>,
     pub cached_input_tokens: Option<u64>,
     pub effective_input_tokens: Option<u64>,
@@ -293,6 +485,9 @@ pub struct ExecutionLog {
     pub budget_lines: Option<u64>,
     pub clip_mode: Option<String>,
     pub clip_footer: Option<bool>,
+    pub system_output_tokens_raw: Option<u64>,
+    pub system_output_tokens_clipped: Option<u64>,
+    pub budget_tokens: Option<u64>,
     pub rtk_used: Option<bool>,
     pub prompt_sha256: Option<String>,
     pub prompt_sha256_raw: Option<String>,
@@ -300,6 +495,15 @@ pub struct ExecutionLog {
     pub prompt_len_raw: Option<u64>,
     pub prompt_len_filtered: Option<u64>,
     pub prompt_filter_applied: Option<bool>,
+    pub prompt_tokens_est: Option<u64>,
+    pub token_estimator: Option<String>,
+    /// Context window of the model the prompt was sized against.
+    pub context_window: Option<u64>,
+    /// `fit`, `shrunk` (clipped to the window) or `blocked` (refused before dispatch).
+    pub context_guard: Option<String>,
+    pub prompt_template: Option<String>,
+    pub prompt_template_source: Option<String>,
+    pub prompt_template_sha256: Option<String>,
     pub schema_prompt_sha256: Option<String>,
     pub schema_sha256: Option<String>,
     pub schema_attempt: Option<u64>,
@@ -309,6 +513,8 @@ pub struct ExecutionLog {
     pub prompt_preview: Option<String>,
     pub policy_blocked: Option<bool>,
     pub policy_reason: Option<String>,
+    pub fix_decisions: Option<Value>,
+    pub pack_manifest: Option<Value>,
     pub retry_attempt: Option<u32>,
     pub retry_max: Option<u32>,
     pub retry_reason: Option<String>,
@@ -353,6 +559,21 @@ pub struct TaskRecord {
     pub max_retries: Option<u32>,
     #[serde(default)]
     pub timeout_secs: Option<u64>,
+    /// Rough size hint (e.g. `2h`) from `task fanout --llm`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<String>,
+    /// Repo-relative path of the last run's result file (`.codex/task_results/<id>.md`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_ref: Option<String>,
+    /// Run-log `execution_id` of the last run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_id: Option<String>,
+    /// Agent or person holding the claim (`task claim --as NAME`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    /// When the current claim was taken; `task reap` releases old ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claimed_at: Option<String>,
     pub status: String,
     pub created_at: String,
     pub updated_at: String,
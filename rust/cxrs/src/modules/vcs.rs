@@ -0,0 +1,351 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+#[cfg(not(test))]
+use std::sync::OnceLock;
+
+use crate::commit_context::{FileStat, staged_file_stats};
+use crate::paths::home_dir;
+use crate::process::run_command_output_with_timeout;
+
+/// Version control a project lives in. `Plain` is a directory marked only by
+/// `.codex` or `CX_PROJECT_ROOT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcsKind {
+    Git,
+    Jj,
+    Hg,
+    Plain,
+}
+
+impl VcsKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VcsKind::Git => "git",
+            VcsKind::Jj => "jj",
+            VcsKind::Hg => "hg",
+            VcsKind::Plain => "none",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    pub root: PathBuf,
+    pub vcs: VcsKind,
+    /// How the root was found: `env` (`CX_PROJECT_ROOT`), `marker`, `git`
+    /// (`rev-parse` without a marker in the walk) or `home` when it resolves to
+    /// `$HOME`, whose logs the global scope shares.
+    pub source: &'static str,
+}
+
+/// `dir` is the home directory, also through symlinks.
+fn is_home(dir: &Path, home: Option<&Path>) -> bool {
+    let Some(home) = home else {
+        return false;
+    };
+    dir == home
+        || matches!(
+            (dir.canonicalize(), home.canonicalize()),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+/// VCS whose metadata sits directly in `dir`; jj wins in colocated jj+git
+/// checkouts. `~/.codex` is the global store, not a project marker.
+fn marker_vcs(dir: &Path, home: Option<&Path>) -> Option<VcsKind> {
+    if dir.join(".jj").is_dir() {
+        Some(VcsKind::Jj)
+    } else if dir.join(".git").exists() {
+        Some(VcsKind::Git)
+    } else if dir.join(".hg").is_dir() {
+        Some(VcsKind::Hg)
+    } else if dir.join(".codex").is_dir() && !is_home(dir, home) {
+        Some(VcsKind::Plain)
+    } else {
+        None
+    }
+}
+
+/// Nearest checkout enclosing `cwd` (itself included). A `.codex`-only dir is
+/// the project when no checkout encloses it, or only `$HOME`'s does; inside a
+/// monorepo it is a package `.codex` (see [`crate::paths::codex_root`]).
+pub fn detect_project_from(cwd: &Path, home: Option<&Path>) -> Option<Project> {
+    let mut plain: Option<&Path> = None;
+    for dir in cwd.ancestors() {
+        match marker_vcs(dir, home) {
+            Some(VcsKind::Plain) => {
+                plain.get_or_insert(dir);
+            }
+            Some(_) if plain.is_some() && is_home(dir, home) => break,
+            Some(vcs) => {
+                return Some(Project {
+                    root: dir.to_path_buf(),
+                    vcs,
+                    source: "marker",
+                });
+            }
+            None => {}
+        }
+    }
+    plain.map(|dir| Project {
+        root: dir.to_path_buf(),
+        vcs: VcsKind::Plain,
+        source: "marker",
+    })
+}
+
+fn git_toplevel() -> Option<PathBuf> {
+    let mut cmd = Command::new("git");
+    cmd.args(["rev-parse", "--show-toplevel"]);
+    let out = run_command_output_with_timeout(cmd, "git rev-parse --show-toplevel").ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let s = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    (!s.is_empty()).then(|| PathBuf::from(s))
+}
+
+/// `CX_PROJECT_ROOT`, else the nearest `.jj`/`.git`/`.hg`/`.codex` marker.
+/// Git roots come from `git rev-parse` (worktrees, `GIT_DIR`), which is also
+/// the last resort when no marker is found.
+fn detect_project() -> Option<Project> {
+    let home = home_dir();
+    if let Some(root) = env::var_os("CX_PROJECT_ROOT")
+        .map(PathBuf::from)
+        .filter(|p| !p.as_os_str().is_empty() && p.is_dir())
+    {
+        let vcs = marker_vcs(&root, None).unwrap_or(VcsKind::Plain);
+        return Some(Project {
+            root,
+            vcs,
+            source: "env",
+        });
+    }
+    let found = env::current_dir()
+        .ok()
+        .and_then(|cwd| detect_project_from(&cwd, home.as_deref()));
+    let project = match found {
+        Some(p) if p.vcs == VcsKind::Git => Project {
+            root: git_toplevel().unwrap_or(p.root),
+            ..p
+        },
+        Some(p) => p,
+        None => Project {
+            root: git_toplevel()?,
+            vcs: VcsKind::Git,
+            source: "git",
+        },
+    };
+    if is_home(&project.root, home.as_deref()) {
+        return Some(Project {
+            source: "home",
+            ..project
+        });
+    }
+    Some(project)
+}
+
+pub fn current_project() -> Option<Project> {
+    #[cfg(test)]
+    {
+        detect_project()
+    }
+    #[cfg(not(test))]
+    {
+        static CACHED: OnceLock<Option<Project>> = OnceLock::new();
+        if env::var("CX_NO_CACHE").ok().as_deref() == Some("1") {
+            return detect_project();
+        }
+        CACHED.get_or_init(detect_project).clone()
+    }
+}
+
+/// VCS of the current project; `Plain` outside any project.
+pub fn current_vcs() -> VcsKind {
+    current_project().map_or(VcsKind::Plain, |p| p.vcs)
+}
+
+fn command_line(program: &str, args: &[&str]) -> Option<String> {
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    let label = format!("{program} {}", args.join(" "));
+    let out = run_command_output_with_timeout(cmd, &label).ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let s = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    (!s.is_empty()).then_some(s)
+}
+
+/// Branch (bookmark for jj) and short revision of the working copy, recorded
+/// on every run-log row; `None` where the VCS has no answer.
+pub fn branch_head(vcs: VcsKind) -> (Option<String>, Option<String>) {
+    match vcs {
+        VcsKind::Git => (
+            command_line("git", &["symbolic-ref", "--short", "-q", "HEAD"]),
+            command_line("git", &["rev-parse", "--short", "HEAD"]),
+        ),
+        VcsKind::Jj => (
+            command_line(
+                "jj",
+                &[
+                    "log",
+                    "-r",
+                    "@",
+                    "--no-graph",
+                    "-T",
+                    "bookmarks.join(\",\")",
+                ],
+            ),
+            command_line(
+                "jj",
+                &["log", "-r", "@", "--no-graph", "-T", "commit_id.short()"],
+            ),
+        ),
+        VcsKind::Hg => (
+            command_line("hg", &["branch"]),
+            command_line("hg", &["id", "-i"]).map(|id| id.trim_end_matches('+').to_string()),
+        ),
+        VcsKind::Plain => (None, None),
+    }
+}
+
+/// Command printing, as a git-style patch, the change the next commit would
+/// record. Only git has a staging area; jj and hg commit the whole working
+/// copy, so `staged` does not change their command.
+pub fn diff_command(vcs: VcsKind, staged: bool) -> Result<Vec<String>, String> {
+    let words: &[&str] = match (vcs, staged) {
+        (VcsKind::Git, true) => &["git", "diff", "--staged", "--no-color"],
+        (VcsKind::Git, false) => &["git", "diff", "--no-color"],
+        (VcsKind::Jj, _) => &["jj", "diff", "--git", "--color=never"],
+        (VcsKind::Hg, _) => &["hg", "diff", "--git", "--color", "never"],
+        (VcsKind::Plain, _) => return Err(no_vcs_message()),
+    };
+    Ok(words.iter().map(|w| w.to_string()).collect())
+}
+
+pub fn no_vcs_message() -> String {
+    let root = current_project()
+        .map(|p| p.root.display().to_string())
+        .unwrap_or_else(|| ".".to_string());
+    format!(
+        "no version control at {root}: this needs a git, jj or hg checkout (CX_PROJECT_ROOT can point at one)"
+    )
+}
+
+/// What to tell the user when the diff is empty.
+pub fn empty_diff_message(vcs: VcsKind, staged: bool) -> &'static str {
+    match (vcs, staged) {
+        (VcsKind::Git, true) => "no staged changes.",
+        (VcsKind::Git, false) => "no unstaged changes.",
+        (VcsKind::Jj, _) => "no changes in the working-copy commit.",
+        _ => "no uncommitted changes.",
+    }
+}
+
+/// Per-file line counts of a git-style patch, for VCSs without `--numstat`.
+pub fn file_stats_from_patch(patch: &str) -> Vec<FileStat> {
+    let mut out: Vec<FileStat> = Vec::new();
+    for line in patch.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git a/") {
+            let path = rest
+                .split_once(" b/")
+                .map_or(rest, |(_, new)| new)
+                .to_string();
+            out.push(FileStat {
+                path,
+                renamed_from: None,
+                added: Some(0),
+                removed: Some(0),
+            });
+            continue;
+        }
+        let Some(file) = out.last_mut() else {
+            continue;
+        };
+        if let Some(old) = line.strip_prefix("rename from ") {
+            file.renamed_from = Some(old.to_string());
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.added = None;
+            file.removed = None;
+        } else if line.starts_with('+') && !line.starts_with("+++ ") {
+            file.added = file.added.map(|n| n + 1);
+        } else if line.starts_with('-') && !line.starts_with("--- ") {
+            file.removed = file.removed.map(|n| n + 1);
+        }
+    }
+    out
+}
+
+/// Files in the change the next commit would record: `git diff --staged
+/// --numstat` for git, counted from the patch for jj and hg.
+pub fn commit_file_stats(vcs: VcsKind) -> Result<Vec<FileStat>, String> {
+    if vcs == VcsKind::Git {
+        return staged_file_stats();
+    }
+    let words = diff_command(vcs, true)?;
+    let mut cmd = Command::new(&words[0]);
+    cmd.args(&words[1..]);
+    let label = words.join(" ");
+    let out = run_command_output_with_timeout(cmd, &label)?;
+    if !out.status.success() {
+        return Err(format!(
+            "{label} failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(file_stats_from_patch(&String::from_utf8_lossy(&out.stdout)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn nearest_marker_picks_the_project_and_its_vcs() {
+        let base = env::temp_dir().join(format!("cxrs-vcs-{}", std::process::id()));
+        let nested = base.join("jj").join("sub").join("dir");
+        fs::create_dir_all(&nested).expect("dirs");
+        fs::create_dir_all(base.join("jj").join(".jj")).expect("jj marker");
+        fs::create_dir_all(base.join("jj").join(".git")).expect("colocated git");
+        fs::create_dir_all(base.join("plain").join(".codex")).expect("codex marker");
+        fs::create_dir_all(base.join("hg").join(".hg")).expect("hg marker");
+
+        let p = detect_project_from(&nested, None).expect("jj project");
+        assert_eq!((p.root, p.vcs), (base.join("jj"), VcsKind::Jj));
+        let p = detect_project_from(&base.join("hg"), None).expect("hg project");
+        assert_eq!(p.vcs, VcsKind::Hg);
+        let p = detect_project_from(&base.join("plain"), None).expect("plain project");
+        assert_eq!(p.vcs, VcsKind::Plain);
+        let package = base.join("jj").join("sub");
+        fs::create_dir_all(package.join(".codex")).expect("package codex");
+        let p = detect_project_from(&nested, None).expect("monorepo project");
+        assert_eq!(
+            (p.root, p.vcs),
+            (base.join("jj"), VcsKind::Jj),
+            "a package .codex does not hide the checkout"
+        );
+        let home = base.join("plain");
+        assert!(
+            detect_project_from(&home, Some(&home)).is_none_or(|p| p.root != home),
+            "~/.codex is not a project marker"
+        );
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn patch_stats_count_lines_renames_and_binaries() {
+        let patch = "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1,2 +1,2 @@\n-old\n+new\n+more\n diff --git a/x b/y\ndiff --git a/old.md b/new.md\nrename from old.md\nrename to new.md\ndiff --git a/logo.png b/logo.png\nBinary files a/logo.png and b/logo.png differ\n";
+        let stats = file_stats_from_patch(patch);
+        assert_eq!(stats.len(), 3);
+        assert_eq!(
+            (stats[0].path.as_str(), stats[0].added, stats[0].removed),
+            ("src/a.rs", Some(2), Some(1))
+        );
+        assert_eq!(stats[1].renamed_from.as_deref(), Some("old.md"));
+        assert_eq!(stats[1].path, "new.md");
+        assert_eq!(stats[2].added, None);
+    }
+}
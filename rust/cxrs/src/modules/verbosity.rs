@@ -0,0 +1,149 @@
+use serde_json::{Map, Value};
+use std::env;
+use std::sync::OnceLock;
+
+/// Stderr diagnostics level, set by `-q`/`-v`/`-vv` (`CX_VERBOSITY` 0..3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+    Debug,
+}
+
+impl Verbosity {
+    fn from_env(raw: Option<&str>) -> Self {
+        match raw.map(str::trim) {
+            Some("0") => Verbosity::Quiet,
+            Some("2") => Verbosity::Verbose,
+            Some("3") => Verbosity::Debug,
+            _ => Verbosity::Normal,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Verbosity::Quiet => "quiet",
+            Verbosity::Normal => "warn",
+            Verbosity::Verbose => "verbose",
+            Verbosity::Debug => "debug",
+        }
+    }
+}
+
+struct DiagSettings {
+    level: Verbosity,
+    json: bool,
+}
+
+static SETTINGS: OnceLock<DiagSettings> = OnceLock::new();
+
+/// Read straight from the env (not `config.toml`): the config loader itself
+/// reports through here, so these must not depend on it.
+fn settings() -> &'static DiagSettings {
+    SETTINGS.get_or_init(|| DiagSettings {
+        level: Verbosity::from_env(env::var("CX_VERBOSITY").ok().as_deref()),
+        json: env::var("CX_LOG_FORMAT")
+            .map(|v| v.trim().eq_ignore_ascii_case("json"))
+            .unwrap_or(false),
+    })
+}
+
+fn text_value(v: &str) -> String {
+    if !v.is_empty() && !v.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') {
+        return v.to_string();
+    }
+    Value::String(v.to_string()).to_string()
+}
+
+/// One diagnostic line: `cxrs: level=L event=E k=v ...`, or a JSON object with
+/// the same keys when `json` is set.
+fn format_line(level: Verbosity, event: &str, fields: &[(&str, String)], json: bool) -> String {
+    if json {
+        let mut obj = Map::new();
+        obj.insert("level".to_string(), Value::from(level.label()));
+        obj.insert("event".to_string(), Value::from(event));
+        for (k, v) in fields {
+            obj.insert((*k).to_string(), Value::from(v.as_str()));
+        }
+        return Value::Object(obj).to_string();
+    }
+    let mut out = format!("cxrs: level={} event={}", level.label(), text_value(event));
+    for (k, v) in fields {
+        out.push_str(&format!(" {k}={}", text_value(v)));
+    }
+    out
+}
+
+/// Emit a structured stderr diagnostic when the current verbosity is at least
+/// `level`. Never writes to stdout.
+pub fn diag(level: Verbosity, event: &str, fields: &[(&str, String)]) {
+    let s = settings();
+    if s.level >= level {
+        crate::cx_eprintln!("{}", format_line(level, event, fields, s.json));
+    }
+}
+
+/// Non-essential warning: suppressed by `-q`, structured under `CX_LOG_FORMAT=json`.
+pub fn warn(message: &str) {
+    let s = settings();
+    if s.level < Verbosity::Normal {
+        return;
+    }
+    if s.json {
+        let line = format_line(
+            Verbosity::Normal,
+            "warning",
+            &[("message", message.to_string())],
+            true,
+        );
+        crate::cx_eprintln!("{line}");
+    } else {
+        crate::cx_eprintln!("{message}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_lines_quote_values_that_need_it() {
+        let line = format_line(
+            Verbosity::Verbose,
+            "clip",
+            &[
+                ("mode", "smart".to_string()),
+                ("cmd", "git status".to_string()),
+                ("model", String::new()),
+            ],
+            false,
+        );
+        assert_eq!(
+            line,
+            "cxrs: level=verbose event=clip mode=smart cmd=\"git status\" model=\"\""
+        );
+    }
+
+    #[test]
+    fn json_lines_carry_level_event_and_fields() {
+        let line = format_line(
+            Verbosity::Debug,
+            "config",
+            &[("path", "/x y".to_string())],
+            true,
+        );
+        let v: Value = serde_json::from_str(&line).expect("json");
+        assert_eq!(v["level"], "debug");
+        assert_eq!(v["event"], "config");
+        assert_eq!(v["path"], "/x y");
+    }
+
+    #[test]
+    fn env_levels_default_to_normal() {
+        assert_eq!(Verbosity::from_env(Some("0")), Verbosity::Quiet);
+        assert_eq!(Verbosity::from_env(Some("3")), Verbosity::Debug);
+        assert_eq!(Verbosity::from_env(Some("junk")), Verbosity::Normal);
+        assert_eq!(Verbosity::from_env(None), Verbosity::Normal);
+    }
+}
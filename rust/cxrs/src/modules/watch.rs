@@ -0,0 +1,243 @@
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::alert_sinks::{
+    AlertSink, alert_events_for_row, alert_policy, configured_alert_sinks, dispatch_alerts,
+};
+use crate::config::DEFAULT_RUN_WINDOW;
+use crate::error::{EXIT_OK, print_runtime_error, print_usage_error};
+use crate::logs::file_len;
+use crate::paths::resolve_log_file;
+use crate::signals::{install_shutdown_handler, shutdown_requested, sleep_unless_shutdown};
+use crate::verbosity::warn;
+
+const DEFAULT_WATCH_INTERVAL_MS: u64 = 2000;
+
+struct WatchArgs {
+    interval: Duration,
+    dispatch: bool,
+    from_start: bool,
+    once: bool,
+}
+
+fn parse_interval(raw: &str) -> Option<Duration> {
+    let secs = raw.trim().parse::<f64>().ok()?;
+    (secs.is_finite() && secs > 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+fn parse_watch_args(args: &[String]) -> Result<WatchArgs, String> {
+    let mut out = WatchArgs {
+        interval: Duration::from_millis(DEFAULT_WATCH_INTERVAL_MS),
+        dispatch: false,
+        from_start: false,
+        once: false,
+    };
+    let mut i = 0usize;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--interval" => {
+                let raw = args.get(i + 1).ok_or("--interval requires a value")?;
+                out.interval = parse_interval(raw)
+                    .ok_or_else(|| format!("invalid --interval '{raw}' (seconds > 0)"))?;
+                i += 1;
+            }
+            "--dispatch" => out.dispatch = true,
+            "--from-start" => out.from_start = true,
+            "--once" => out.once = true,
+            other => return Err(format!("unknown flag '{other}'")),
+        }
+        i += 1;
+    }
+    Ok(out)
+}
+
+/// Rolling aggregates over the most recent rows seen by the watcher.
+#[derive(Default)]
+struct WatchStats {
+    seen: u64,
+    alerts: u64,
+    window: VecDeque<(u64, u64, u64, u64)>,
+}
+
+impl WatchStats {
+    fn push(&mut self, row: &Value) {
+        let field = |k: &str| row.get(k).and_then(Value::as_u64).unwrap_or(0);
+        self.seen += 1;
+        self.window.push_back((
+            field("duration_ms"),
+            field("effective_input_tokens"),
+            field("input_tokens"),
+            field("cached_input_tokens"),
+        ));
+        while self.window.len() > DEFAULT_RUN_WINDOW {
+            self.window.pop_front();
+        }
+    }
+
+    fn status_line(&self) -> String {
+        let n = self.window.len() as u64;
+        let sum = |f: fn(&(u64, u64, u64, u64)) -> u64| self.window.iter().map(f).sum::<u64>();
+        let (ms, eff, input, cached) = (sum(|r| r.0), sum(|r| r.1), sum(|r| r.2), sum(|r| r.3));
+        let cache = if input == 0 {
+            "n/a".to_string()
+        } else {
+            format!(
+                "{}%",
+                ((cached as f64 / input as f64) * 100.0).round() as u64
+            )
+        };
+        format!(
+            "[watch] runs={} alerts={} avg_ms={} avg_eff={} cache_hit={} (last {n})",
+            self.seen,
+            self.alerts,
+            ms.checked_div(n).unwrap_or(0),
+            eff.checked_div(n).unwrap_or(0),
+            cache
+        )
+    }
+}
+
+/// Parse complete JSONL lines appended after `offset`, advancing it past them.
+/// A trailing partial line is left for the next poll; truncation resets to 0.
+fn read_appended_rows(path: &Path, offset: &mut u64) -> Result<Vec<Value>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    if file_len(path) < *offset {
+        *offset = 0;
+    }
+    let mut file = File::open(path).map_err(|e| format!("cannot open {}: {e}", path.display()))?;
+    file.seek(SeekFrom::Start(*offset))
+        .map_err(|e| format!("seek failed on {}: {e}", path.display()))?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)
+        .map_err(|e| format!("read failed on {}: {e}", path.display()))?;
+    let Some(end) = buf.iter().rposition(|b| *b == b'\n') else {
+        return Ok(Vec::new());
+    };
+    *offset += (end + 1) as u64;
+    Ok(String::from_utf8_lossy(&buf[..end])
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+        .collect())
+}
+
+fn process_rows(rows: &[Value], stats: &mut WatchStats, sinks: &[AlertSink]) {
+    if rows.is_empty() {
+        return;
+    }
+    // Re-read per batch so `alert_overrides` edits apply without a restart.
+    let policy = alert_policy();
+    for row in rows {
+        stats.push(row);
+        let t = policy.for_row(row);
+        let events = alert_events_for_row(row, t.max_ms, t.max_eff_in);
+        stats.alerts += events.len() as u64;
+        for event in &events {
+            let ts = row
+                .get("timestamp")
+                .and_then(Value::as_str)
+                .unwrap_or("n/a");
+            let msg = event.get("message").and_then(Value::as_str).unwrap_or("");
+            println!("{ts} ALERT {msg}");
+        }
+        for failure in dispatch_alerts(sinks, &events) {
+            warn(&format!("cxrs watch: warning: sink {failure}"));
+        }
+    }
+    println!("{}", stats.status_line());
+}
+
+fn print_watch_header(log_file: &Path, args: &WatchArgs, sinks: &[AlertSink]) {
+    let policy = alert_policy();
+    let (max_ms, max_eff) = (policy.base.max_ms, policy.base.max_eff_in);
+    let labels: Vec<String> = sinks.iter().map(AlertSink::label).collect();
+    println!("== cxrs watch ==");
+    println!("log_file: {}", log_file.display());
+    println!(
+        "interval: {:.1}s | thresholds: max_ms={max_ms}, max_eff_in={max_eff}",
+        args.interval.as_secs_f64()
+    );
+    println!("overrides: {}", policy.overrides_label());
+    println!(
+        "dispatch: {}",
+        if args.dispatch {
+            labels.join(", ")
+        } else {
+            "off".to_string()
+        }
+    );
+}
+
+pub fn cmd_watch(app_name: &str, args: &[String]) -> i32 {
+    let usage = format!("{app_name} watch [--interval SECS] [--dispatch] [--from-start] [--once]");
+    let parsed = match parse_watch_args(args) {
+        Ok(v) => v,
+        Err(_) => return print_usage_error("watch", &usage),
+    };
+    let Some(log_file) = resolve_log_file() else {
+        return print_runtime_error("watch", "unable to resolve log file");
+    };
+    let sinks = if parsed.dispatch {
+        match configured_alert_sinks() {
+            Ok(v) => v,
+            Err(e) => return print_runtime_error("watch", &e),
+        }
+    } else {
+        Vec::new()
+    };
+    install_shutdown_handler();
+    print_watch_header(&log_file, &parsed, &sinks);
+    let mut offset = if parsed.from_start {
+        0
+    } else {
+        file_len(&log_file)
+    };
+    let mut stats = WatchStats::default();
+    loop {
+        match read_appended_rows(&log_file, &mut offset) {
+            Ok(rows) => process_rows(&rows, &mut stats, &sinks),
+            Err(e) => warn(&format!("cxrs watch: warning: {e}")),
+        }
+        if parsed.once || shutdown_requested() || !sleep_unless_shutdown(parsed.interval) {
+            break;
+        }
+    }
+    println!("== cxrs watch stopped ==");
+    println!("{}", stats.status_line());
+    EXIT_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_lines_wait_for_next_poll() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("runs.jsonl");
+        std::fs::write(&path, "{\"tool\":\"a\"}\n{\"tool\":").expect("write");
+        let mut offset = 0u64;
+        let rows = read_appended_rows(&path, &mut offset).expect("rows");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(offset, 13);
+        std::fs::write(&path, "{\"tool\":\"a\"}\n{\"tool\":\"b\"}\n").expect("write");
+        let rows = read_appended_rows(&path, &mut offset).expect("rows");
+        assert_eq!(rows[0]["tool"], "b");
+        std::fs::write(&path, "{\"tool\":\"c\"}\n").expect("truncate");
+        let rows = read_appended_rows(&path, &mut offset).expect("rows");
+        assert_eq!(rows[0]["tool"], "c");
+    }
+
+    #[test]
+    fn interval_accepts_fractional_seconds() {
+        assert_eq!(parse_interval("0.5"), Some(Duration::from_millis(500)));
+        assert!(parse_interval("0").is_none());
+        assert!(parse_interval("soon").is_none());
+    }
+}
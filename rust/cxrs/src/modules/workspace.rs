@@ -0,0 +1,308 @@
+use chrono::Utc;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{format_error, print_usage_error};
+use crate::paths::resolve_codex_dir;
+use crate::process::run_command_output_with_timeout;
+
+const WORKSPACE_USAGE: &str =
+    "workspace <info [--json]|backup [--out FILE.tar.gz]|restore <FILE> [--force]>";
+
+fn fail(sub: &str, e: &str) -> i32 {
+    crate::cx_eprintln!("{}", format_error(&format!("workspace {sub}"), e));
+    1
+}
+
+/// `(bytes, files)` under `path` (a file counts as itself).
+fn tree_size(path: &Path) -> (u64, u64) {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return (0, 0);
+    };
+    if !meta.is_dir() {
+        return (meta.len(), 1);
+    }
+    let mut total = (0, 0);
+    for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+        let (b, f) = tree_size(&entry.path());
+        total.0 += b;
+        total.1 += f;
+    }
+    total
+}
+
+pub(crate) fn human_bytes(n: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut v = n as f64;
+    let mut unit = 0;
+    while v >= 1024.0 && unit + 1 < UNITS.len() {
+        v /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{n} B")
+    } else {
+        format!("{v:.1} {}", UNITS[unit])
+    }
+}
+
+/// Per-entry sizes of the workspace; loose top-level files are grouped as `(files)`.
+fn workspace_sizes(dir: &Path) -> BTreeMap<String, (u64, u64)> {
+    let mut out: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        let key = if path.is_dir() {
+            format!("{}/", entry.file_name().to_string_lossy())
+        } else {
+            "(files)".to_string()
+        };
+        let (b, f) = tree_size(&path);
+        let slot = out.entry(key).or_default();
+        slot.0 += b;
+        slot.1 += f;
+    }
+    out
+}
+
+fn cmd_workspace_info(args: &[String]) -> i32 {
+    let json_out = match args {
+        [] => false,
+        [flag] if flag == "--json" => true,
+        _ => return print_usage_error("workspace", WORKSPACE_USAGE),
+    };
+    let Some(dir) = resolve_codex_dir() else {
+        return fail("info", "unable to resolve .codex dir");
+    };
+    let sizes = if dir.is_dir() {
+        workspace_sizes(&dir)
+    } else {
+        BTreeMap::new()
+    };
+    let total_bytes: u64 = sizes.values().map(|v| v.0).sum();
+    let total_files: u64 = sizes.values().map(|v| v.1).sum();
+    if json_out {
+        let entries: Vec<Value> = sizes
+            .iter()
+            .map(|(k, (b, f))| json!({"path": k, "bytes": b, "files": f}))
+            .collect();
+        let doc = json!({
+            "dir": dir.display().to_string(),
+            "exists": dir.is_dir(),
+            "bytes": total_bytes,
+            "files": total_files,
+            "entries": entries,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&doc).unwrap_or_else(|_| doc.to_string())
+        );
+        return 0;
+    }
+    println!("workspace: {}", dir.display());
+    if !dir.is_dir() {
+        println!("(missing; run `cxrs doctor --fix` to create it)");
+        return 0;
+    }
+    for (k, (b, f)) in &sizes {
+        println!("  {k:<22} {:>10}  {f} file(s)", human_bytes(*b));
+    }
+    println!(
+        "  {:<22} {:>10}  {total_files} file(s)",
+        "total",
+        human_bytes(total_bytes)
+    );
+    0
+}
+
+fn run_tar(args: &[&str], cwd: &Path, label: &str) -> Result<String, String> {
+    let mut cmd = Command::new("tar");
+    cmd.args(args).current_dir(cwd);
+    let out = run_command_output_with_timeout(cmd, label)?;
+    if !out.status.success() {
+        return Err(format!(
+            "{label} failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+fn cmd_workspace_backup(args: &[String]) -> i32 {
+    let out = match args {
+        [] => PathBuf::from(format!(
+            "cx-workspace-{}.tar.gz",
+            Utc::now().format("%Y%m%dT%H%M%SZ")
+        )),
+        [flag, path] if flag == "--out" => PathBuf::from(path),
+        _ => return print_usage_error("workspace", WORKSPACE_USAGE),
+    };
+    let Some(dir) = resolve_codex_dir().filter(|d| d.is_dir()) else {
+        return fail("backup", "no .codex workspace to back up");
+    };
+    let Some(parent) = dir.parent() else {
+        return fail("backup", "workspace has no parent dir");
+    };
+    let out = match std::env::current_dir() {
+        Ok(cwd) => cwd.join(out),
+        Err(e) => return fail("backup", &format!("current dir: {e}")),
+    };
+    if out.starts_with(&dir) {
+        return fail("backup", "--out must be outside the .codex dir");
+    }
+    let out_str = out.display().to_string();
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| ".codex".to_string());
+    if let Err(e) = run_tar(&["-czf", &out_str, &name], parent, "tar -czf") {
+        return fail("backup", &e);
+    }
+    let (bytes, files) = tree_size(&dir);
+    println!(
+        "backed up {} ({files} file(s), {}) -> {}",
+        dir.display(),
+        human_bytes(bytes),
+        out.display()
+    );
+    0
+}
+
+/// Archive members must share one top-level workspace dir (`.codex/`, or
+/// another name under CX_HOME/XDG layouts) with no absolute paths or `..`.
+fn validate_members(listing: &str) -> Result<usize, String> {
+    let mut n = 0usize;
+    let mut top_dir: Option<std::ffi::OsString> = None;
+    for member in listing.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let p = Path::new(member.strip_prefix("./").unwrap_or(member));
+        let mut comps = p.components();
+        let top_ok = match comps.next() {
+            Some(Component::Normal(c)) => {
+                top_dir.get_or_insert_with(|| c.to_os_string()).as_os_str() == c
+            }
+            _ => false,
+        };
+        if !top_ok || comps.any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(format!(
+                "refusing archive member outside the workspace dir: {member}"
+            ));
+        }
+        n += 1;
+    }
+    if n == 0 {
+        return Err("archive is empty".to_string());
+    }
+    Ok(n)
+}
+
+fn cmd_workspace_restore(args: &[String]) -> i32 {
+    let (file, force) = match args {
+        [f] => (f, false),
+        [f, flag] | [flag, f] if flag == "--force" => (f, true),
+        _ => return print_usage_error("workspace", WORKSPACE_USAGE),
+    };
+    let archive = match fs::canonicalize(file) {
+        Ok(p) => p,
+        Err(e) => return fail("restore", &format!("cannot read {file}: {e}")),
+    };
+    let Some(dir) = resolve_codex_dir() else {
+        return fail("restore", "unable to resolve .codex dir");
+    };
+    let Some(parent) = dir.parent().map(Path::to_path_buf) else {
+        return fail("restore", "workspace has no parent dir");
+    };
+    if let Err(e) = fs::create_dir_all(&parent) {
+        return fail(
+            "restore",
+            &format!("cannot create {}: {e}", parent.display()),
+        );
+    }
+    let archive_str = archive.display().to_string();
+    let members = match run_tar(&["-tzf", &archive_str], &parent, "tar -tzf")
+        .and_then(|l| validate_members(&l))
+    {
+        Ok(n) => n,
+        Err(e) => return fail("restore", &e),
+    };
+    let occupied = fs::read_dir(&dir).is_ok_and(|mut d| d.next().is_some());
+    let mut moved: Option<PathBuf> = None;
+    if occupied {
+        if !force {
+            return fail(
+                "restore",
+                &format!(
+                    "{} is not empty; pass --force to move it aside first",
+                    dir.display()
+                ),
+            );
+        }
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        let aside = parent.join(format!(
+            "{name}.bak-{}",
+            Utc::now().format("%Y%m%dT%H%M%SZ")
+        ));
+        if let Err(e) = fs::rename(&dir, &aside) {
+            return fail("restore", &format!("cannot move {}: {e}", dir.display()));
+        }
+        moved = Some(aside);
+    }
+    // The archived dir name may differ from the target (e.g. `.codex` -> XDG `cx`).
+    let dir_str = dir.display().to_string();
+    let extracted = fs::create_dir_all(&dir)
+        .map_err(|e| format!("cannot create {dir_str}: {e}"))
+        .and_then(|_| {
+            run_tar(
+                &["-xzf", &archive_str, "--strip-components=1", "-C", &dir_str],
+                &parent,
+                "tar -xzf",
+            )
+        });
+    if let Err(e) = extracted {
+        if let Some(aside) = &moved {
+            let _ = fs::remove_dir_all(&dir);
+            let _ = fs::rename(aside, &dir);
+        }
+        return fail("restore", &e);
+    }
+    println!(
+        "restored {members} entr(ies) from {} into {}",
+        archive.display(),
+        dir.display()
+    );
+    if let Some(aside) = moved {
+        println!("previous workspace moved to {}", aside.display());
+    }
+    0
+}
+
+pub fn cmd_workspace(args: &[String]) -> i32 {
+    let rest = args.get(1..).unwrap_or(&[]);
+    match args.first().map(String::as_str).unwrap_or("info") {
+        "info" => cmd_workspace_info(rest),
+        "backup" => cmd_workspace_backup(rest),
+        "restore" => cmd_workspace_restore(rest),
+        _ => print_usage_error("workspace", WORKSPACE_USAGE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_members_must_stay_in_codex_dir() {
+        assert_eq!(
+            validate_members(".codex/\n.codex/state.json\n./.codex/cxlogs/runs.jsonl\n"),
+            Ok(3)
+        );
+        assert!(validate_members(".codex/../etc/passwd\n").is_err());
+        assert!(validate_members("/etc/passwd\n").is_err());
+        assert_eq!(validate_members("cx/\ncx/state.json\n"), Ok(2));
+        assert!(validate_members(".codex/a\nother/file\n").is_err());
+        assert!(validate_members("\n").is_err());
+        assert_eq!(human_bytes(512), "512 B");
+        assert_eq!(human_bytes(1536), "1.5 KiB");
+    }
+}
@@ -123,7 +123,7 @@ fn http_stub_fails_fast_logs_transport() {
     );
     assert_eq!(
         out.status.code(),
-        Some(1),
+        Some(5),
         "expected failure; stdout={} stderr={}",
         stdout_str(&out),
         stderr_str(&out)
@@ -169,7 +169,7 @@ fn http_curl_requires_url_logs_experimental_status() {
     );
     assert_eq!(
         out.status.code(),
-        Some(1),
+        Some(5),
         "expected failure; stdout={} stderr={}",
         stdout_str(&out),
         stderr_str(&out)
@@ -294,3 +294,51 @@ fn http_curl_hits_server_with_auth_prompt() {
         req.body
     );
 }
+
+#[test]
+fn http_curl_token_can_reference_a_secret() {
+    if std::process::Command::new("curl")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        return;
+    }
+    let repo = TempRepo::new("cxrs-it");
+    let pass = ("CX_SECRET_PASSPHRASE", "pw");
+    let out = repo.run_with_env(&["secret", "set", "provider", "token-from-store"], &[pass]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let (url, captured, handle) = run_fixture_http_server_once(r#"{"text":"fixture-http-ok"}"#);
+    let out = repo.run_with_env(
+        &["cxo", "echo", "http-live"],
+        &[
+            ("CX_PROVIDER_ADAPTER", "http-curl"),
+            ("CX_HTTP_PROVIDER_URL", &url),
+            ("CX_HTTP_PROVIDER_TOKEN", "secret:provider"),
+            pass,
+        ],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    handle.join().expect("fixture join");
+    let req = captured
+        .lock()
+        .expect("fixture lock")
+        .clone()
+        .expect("captured request");
+    assert_eq!(
+        req.authorization.as_deref(),
+        Some("Bearer token-from-store")
+    );
+
+    let out = repo.run_with_env(
+        &["cxo", "echo", "http-live"],
+        &[
+            ("CX_PROVIDER_ADAPTER", "http-curl"),
+            ("CX_HTTP_PROVIDER_URL", &url),
+            ("CX_HTTP_PROVIDER_TOKEN", "secret:missing"),
+            pass,
+        ],
+    );
+    assert!(!out.status.success());
+    assert!(stderr_str(&out).contains("secret 'missing' not found"));
+}
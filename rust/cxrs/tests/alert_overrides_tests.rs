@@ -0,0 +1,73 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+use std::fs;
+
+fn seed_runs(repo: &TempRepo) {
+    write_runs_log_rows(
+        repo,
+        &[
+            json!({"execution_id":"ao1","timestamp":"2026-01-01T00:00:00Z","tool":"cxo",
+                   "duration_ms":20000,"input_tokens":100,"effective_input_tokens":100}),
+            json!({"execution_id":"ao2","timestamp":"2026-01-01T00:00:05Z","tool":"cxdiffsum",
+                   "duration_ms":20000,"input_tokens":100,"effective_input_tokens":100}),
+        ],
+    );
+}
+
+fn write_overrides(repo: &TempRepo, overrides: Value) {
+    let path = repo.state_file();
+    fs::create_dir_all(path.parent().expect("state parent")).expect("mkdir state");
+    let state = json!({"alert_overrides": overrides});
+    fs::write(&path, state.to_string()).expect("write state");
+}
+
+fn alert_json(repo: &TempRepo) -> Value {
+    let out = repo.run(&["alert", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    serde_json::from_str(&stdout_str(&out)).expect("alert json")
+}
+
+#[test]
+fn tool_overrides_apply_to_alert_and_optimize() {
+    let repo = TempRepo::new("cxrs-alert-overrides");
+    seed_runs(&repo);
+    assert_eq!(alert_json(&repo)["slow_violations"], 2);
+
+    write_overrides(&repo, json!({"cxdiffsum": {"max_ms": 30000}}));
+    let v = alert_json(&repo);
+    assert_eq!(v["slow_violations"], 1);
+    assert_eq!(
+        v["thresholds"]["overrides"]["cxdiffsum"],
+        json!({"max_ms": 30000, "max_eff_in": 8000})
+    );
+
+    let out = repo.run(&["optimize", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let report: Value = serde_json::from_str(&stdout_str(&out)).expect("optimize json");
+    assert_eq!(report["scoreboard"]["alerts"], 1);
+
+    let show = stdout_str(&repo.run(&["alert-show"]));
+    assert!(show.contains("max_ms=12000\n"), "{show}");
+    assert!(
+        show.contains("  cxdiffsum: max_ms=30000 max_eff_in=8000"),
+        "{show}"
+    );
+}
+
+#[test]
+fn invalid_overrides_warn_and_use_globals() {
+    let repo = TempRepo::new("cxrs-alert-overrides");
+    seed_runs(&repo);
+    write_overrides(&repo, json!({"cxo": {"max_secs": 5}}));
+
+    let out = repo.run(&["alert", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stderr_str(&out).contains("unknown key 'max_secs'"));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("alert json");
+    assert_eq!(v["slow_violations"], 2);
+
+    let show = stdout_str(&repo.run(&["alert-show"]));
+    assert!(show.contains("overrides=invalid ("), "{show}");
+}
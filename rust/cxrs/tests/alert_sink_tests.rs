@@ -0,0 +1,92 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+const MOCK_CODEX: &str = r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+printf '%s\n' '{"type":"turn.completed","usage":{"input_tokens":900,"cached_input_tokens":0,"output_tokens":4}}'
+"#;
+
+fn write_sinks(repo: &TempRepo, sinks: Value) {
+    write_sinks_at(&repo.home, sinks);
+}
+
+fn write_sinks_at(base: &Path, sinks: Value) {
+    let state = serde_json::json!({"alerts": {"sinks": sinks}});
+    let path = base.join(".codex/state.json");
+    fs::create_dir_all(path.parent().expect("state parent")).expect("mkdir state");
+    fs::write(&path, serde_json::to_string(&state).expect("state json")).expect("write state");
+}
+
+#[test]
+fn run_alerts_go_to_file_and_webhook() {
+    let repo = TempRepo::new("cxrs-alert-sink");
+    repo.write_mock_codex(MOCK_CODEX);
+    let posted = repo.root.join("webhook.json");
+    repo.write_mock(
+        "curl",
+        &format!("#!/usr/bin/env bash\ncat > \"{}\"\n", posted.display()),
+    );
+    write_sinks(
+        &repo,
+        serde_json::json!(["file", {"type": "webhook", "url": "https://hooks.example/t"}]),
+    );
+
+    let out = repo.run_with_env(&["cxo", "echo", "hi"], &[("CXALERT_MAX_EFF_IN", "100")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+
+    let alerts = parse_jsonl(&repo.root.join(".codex/cxlogs/alerts.jsonl"));
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(
+        alerts[0].get("rule").and_then(Value::as_str),
+        Some("max_eff_in")
+    );
+    assert_eq!(alerts[0].get("tool").and_then(Value::as_str), Some("cxo"));
+
+    let body: Value =
+        serde_json::from_str(&fs::read_to_string(&posted).expect("webhook body")).expect("json");
+    assert!(
+        body.get("text")
+            .and_then(Value::as_str)
+            .is_some_and(|t| t.contains("max_eff_in exceeded"))
+    );
+}
+
+#[test]
+fn alerts_disabled_or_under_threshold_skip_sinks() {
+    let repo = TempRepo::new("cxrs-alert-sink");
+    repo.write_mock_codex(MOCK_CODEX);
+    write_sinks(&repo, serde_json::json!(["file"]));
+
+    let out = repo.run_with_env(
+        &["cxo", "echo", "hi"],
+        &[("CXALERT_MAX_EFF_IN", "100"), ("CXALERT_ENABLED", "0")],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(!repo.root.join(".codex/cxlogs/alerts.jsonl").exists());
+
+    let show = repo.run(&["alert-show"]);
+    assert!(stdout_str(&show).contains("sinks=file("));
+}
+
+#[test]
+fn repo_state_cannot_choose_alert_sinks() {
+    let repo = TempRepo::new("cxrs-alert-sink");
+    repo.write_mock_codex(MOCK_CODEX);
+    let target = repo.home.join("planted.jsonl");
+    write_sinks_at(
+        &repo.root,
+        serde_json::json!([{"type": "file", "path": target.display().to_string()}]),
+    );
+
+    let out = repo.run_with_env(&["cxo", "echo", "hi"], &[("CXALERT_MAX_EFF_IN", "100")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stderr_str(&out).contains("ignoring alerts.sinks in repo"));
+    assert!(!target.exists());
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{TempRepo, read_json, stderr_str, stdout_str};
+use serde_json::Value;
+
+const MOCK: &str = r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+"#;
+
+fn json_out(repo: &TempRepo, args: &[&str], home: &str) -> Value {
+    let out = repo.run_with_env(args, &[("HOME", home)]);
+    assert!(out.status.success(), "{args:?}: {}", stderr_str(&out));
+    serde_json::from_str(&stdout_str(&out)).expect("json")
+}
+
+fn repo_runs(v: &Value, repo: &TempRepo) -> u64 {
+    let name = repo
+        .root
+        .file_name()
+        .and_then(|n| n.to_str())
+        .expect("name");
+    v["by_repo"]
+        .as_array()
+        .expect("by_repo")
+        .iter()
+        .find(|r| r["repo_root"].as_str().unwrap_or("").ends_with(name))
+        .and_then(|r| r["runs"].as_u64())
+        .unwrap_or(0)
+}
+
+#[test]
+fn all_repos_merges_logs_per_repo() {
+    let a = TempRepo::new("cxrs-allrepos");
+    let b = TempRepo::new("cxrs-allrepos");
+    let home = a.home.to_string_lossy().to_string();
+    for repo in [&a, &b] {
+        repo.write_mock_codex(MOCK);
+    }
+    for (repo, n) in [(&a, 2), (&b, 1)] {
+        for _ in 0..n {
+            let out = repo.run_with_env(&["cxo", "echo", "hi"], &[("HOME", &home)]);
+            assert!(out.status.success(), "stderr={}", stderr_str(&out));
+        }
+    }
+    let registry = read_json(&a.home.join(".codex").join("repos.json"));
+    assert_eq!(registry["repos"].as_array().map(Vec::len), Some(2));
+
+    let local = json_out(&a, &["metrics"], &home);
+    assert_eq!(local["runs"], 2);
+    assert!(local.get("by_repo").is_none());
+
+    let all = json_out(&a, &["metrics", "--all-repos"], &home);
+    assert_eq!(all["runs"], 3);
+    assert_eq!((repo_runs(&all, &a), repo_runs(&all, &b)), (2, 1));
+
+    let profile = json_out(&b, &["profile", "--all-repos", "--json"], &home);
+    assert_eq!(profile["runs"], 3);
+    assert_eq!(repo_runs(&profile, &b), 1);
+
+    let trends = json_out(&a, &["trends", "--all-repos", "--json"], &home);
+    assert_eq!(trends["runs"], 3);
+    assert_eq!(trends["by_repo"].as_array().map(Vec::len), Some(2));
+
+    let out = a.run_with_env(&["trends", "--all-repos"], &[("HOME", &home)]);
+    let text = stdout_str(&out);
+    assert!(text.contains("all repos"), "{text}");
+    assert!(
+        text.contains("Per repo (runs | avg ms | eff tokens):"),
+        "{text}"
+    );
+}
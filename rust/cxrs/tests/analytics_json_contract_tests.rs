@@ -0,0 +1,327 @@
+mod common;
+
+use common::*;
+use serde_json::Value;
+
+fn seed_runs(repo: &TempRepo) {
+    let rows = vec![
+        serde_json::json!({
+            "execution_id":"ajc1","timestamp":"2026-01-01T00:00:00Z","ts":"2026-01-01T00:00:00Z",
+            "command":"cxo","tool":"cxo","backend_used":"codex","duration_ms":1200,
+            "input_tokens":4000,"cached_input_tokens":100,"effective_input_tokens":3900,
+            "output_tokens":120,"prompt_sha256":"abc"
+        }),
+        serde_json::json!({
+            "execution_id":"ajc2","timestamp":"2026-01-01T00:00:05Z","ts":"2026-01-01T00:00:05Z",
+            "command":"cxj","tool":"cxj","backend_used":"codex","duration_ms":15000,
+            "input_tokens":9000,"cached_input_tokens":0,"effective_input_tokens":9000,
+            "output_tokens":300,"prompt_sha256":"def"
+        }),
+    ];
+    write_runs_log_rows(repo, &rows);
+}
+
+fn run_json(repo: &TempRepo, args: &[&str]) -> Value {
+    let out = repo.run(args);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    serde_json::from_str(&stdout_str(&out)).expect("json payload")
+}
+
+#[test]
+fn analytics_json_match_contract_fixture() {
+    let repo = TempRepo::new("cxrs-it");
+    seed_runs(&repo);
+    let fixture = load_fixture_json("analytics_json_contract.json");
+
+    for (cmd, key) in [
+        ("profile", "profile_keys"),
+        ("alert", "alert_keys"),
+        ("worklog", "worklog_keys"),
+        ("promptlint", "promptlint_keys"),
+    ] {
+        let payload = run_json(&repo, &[cmd, "10", "--json"]);
+        assert_has_keys(&payload, &fixture_keys(&fixture, key), cmd);
+        assert_eq!(
+            payload.get("contract_version").and_then(Value::as_str),
+            Some(format!("{cmd}.v1").as_str())
+        );
+        assert_eq!(payload.get("runs").and_then(Value::as_u64), Some(2));
+    }
+
+    let alert = run_json(&repo, &["alert", "--json"]);
+    assert_eq!(
+        alert.get("slow_violations").and_then(Value::as_u64),
+        Some(1)
+    );
+    assert_eq!(
+        alert.get("token_violations").and_then(Value::as_u64),
+        Some(1)
+    );
+}
+
+#[test]
+fn trace_json_matches_contract_fixture() {
+    let repo = TempRepo::new("cxrs-it");
+    seed_runs(&repo);
+    let fixture = load_fixture_json("analytics_json_contract.json");
+
+    let payload = run_json(&repo, &["trace", "--json", "2"]);
+    assert_has_keys(&payload, &fixture_keys(&fixture, "trace_keys"), "trace");
+    let run = payload.get("run").expect("run");
+    assert_has_keys(run, &fixture_keys(&fixture, "trace_run_keys"), "trace.run");
+    assert_eq!(run.get("tool").and_then(Value::as_str), Some("cxo"));
+}
+
+#[test]
+fn bench_json_matches_contract_fixture() {
+    let repo = TempRepo::new("cxrs-it");
+    let fixture = load_fixture_json("analytics_json_contract.json");
+
+    let payload = run_json(&repo, &["bench", "2", "--json", "--", "true"]);
+    assert_has_keys(&payload, &fixture_keys(&fixture, "bench_keys"), "bench");
+    assert_eq!(payload.get("runs").and_then(Value::as_u64), Some(2));
+    assert_eq!(payload.get("failures").and_then(Value::as_u64), Some(0));
+}
+
+#[test]
+fn analytics_json_empty_log_is_valid() {
+    let repo = TempRepo::new("cxrs-it");
+    for cmd in ["profile", "alert", "worklog", "promptlint"] {
+        let payload = run_json(&repo, &[cmd, "--json"]);
+        assert_eq!(
+            payload.get("runs").and_then(Value::as_u64),
+            Some(0),
+            "{cmd}"
+        );
+    }
+}
+
+#[test]
+fn bench_llm_compares_variants_and_checks_baseline() {
+    let repo = TempRepo::new("cxrs-it");
+    let out = repo.run(&[
+        "bench",
+        "2",
+        "--llm",
+        "--warmup",
+        "1",
+        "--compare",
+        "--json",
+        "--",
+        "true",
+        "--",
+        "false",
+    ]);
+    assert_eq!(out.status.code(), Some(1), "stderr={}", stderr_str(&out));
+    let payload: Value = serde_json::from_str(&stdout_str(&out)).expect("bench-llm json");
+    assert_eq!(payload["contract_version"], "bench-llm.v1");
+    assert_eq!(payload["warmup"], 1);
+    let variants = payload["variants"].as_array().expect("variants");
+    assert_eq!(variants.len(), 2);
+    assert_eq!(variants[0]["label"], "true");
+    assert_eq!(variants[0]["runs"], 2);
+    assert_eq!(variants[0]["failures"], 0);
+    assert_eq!(variants[1]["failures"], 2);
+    for key in ["avg", "stddev", "p50", "p90", "p99"] {
+        assert!(variants[0]["duration_ms"].get(key).is_some(), "{key}");
+    }
+    assert_eq!(payload["comparison"][0]["against"], "true");
+
+    let baseline = repo.root.join("bench.json");
+    std::fs::write(&baseline, serde_json::to_string(&payload).unwrap()).unwrap();
+    let out = repo.run(&[
+        "bench",
+        "1",
+        "--llm",
+        "--warmup",
+        "0",
+        "--baseline",
+        baseline.to_str().unwrap(),
+        "--max-regress",
+        "100000",
+        "--",
+        "true",
+    ]);
+    assert_eq!(out.status.code(), Some(0), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("[true]"), "{text}");
+    assert!(text.contains("baseline "), "{text}");
+
+    let out = repo.run(&["bench", "2", "--llm", "--compare", "--", "true"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("--compare needs two commands"));
+}
+
+#[test]
+fn bench_suite_gates_and_writes_next_baseline() {
+    let repo = TempRepo::new("cxrs-it");
+    let suite = repo.root.join("bench-suite.json");
+    std::fs::write(
+        &suite,
+        r#"{"commands":[{"label":"nap","command":["sleep","0.05"]},{"command":["true"]}]}"#,
+    )
+    .unwrap();
+    let baseline = repo.root.join("bench-baseline.json");
+    std::fs::write(
+        &baseline,
+        r#"{"variants":[{"label":"nap","duration_ms":{"avg":1.0,"p50":1}}]}"#,
+    )
+    .unwrap();
+    let next = repo.root.join("bench-next.json");
+    let out = repo.run(&[
+        "bench",
+        "2",
+        "--json",
+        "--suite",
+        suite.to_str().unwrap(),
+        "--baseline",
+        baseline.to_str().unwrap(),
+        "--max-regression",
+        "20%",
+        "--write-baseline",
+        next.to_str().unwrap(),
+    ]);
+    assert_eq!(out.status.code(), Some(1), "stderr={}", stderr_str(&out));
+    let payload: Value = serde_json::from_str(&stdout_str(&out)).expect("bench json");
+    assert_eq!(payload["contract_version"], "bench-llm.v1");
+    assert!(payload["comparison"].is_null());
+    assert_eq!(payload["regressions"], serde_json::json!(["nap"]));
+    assert_eq!(
+        payload["regression_details"][0]["metric"],
+        "duration_ms.p50"
+    );
+    assert!(payload["baseline"]["variants"][1]["against"].is_null());
+    assert_eq!(payload["baseline_written"], next.to_str().unwrap());
+
+    let written: Value =
+        serde_json::from_str(&std::fs::read_to_string(&next).unwrap()).expect("next baseline");
+    assert_eq!(written["variants"][1]["label"], "true");
+    let out = repo.run(&[
+        "bench",
+        "1",
+        "--suite",
+        suite.to_str().unwrap(),
+        "--baseline",
+        next.to_str().unwrap(),
+        "--max-regression",
+        "100000",
+    ]);
+    assert_eq!(out.status.code(), Some(0), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("baseline "));
+
+    let out = repo.run(&[
+        "bench",
+        "1",
+        "--suite",
+        suite.to_str().unwrap(),
+        "--",
+        "true",
+    ]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn trace_follows_quarantine_transcript_task_links() {
+    let repo = TempRepo::new("cxrs-it");
+    repo.write_mock_codex(
+        "#!/usr/bin/env bash\ncat >/dev/null\nprintf '%s\\n' '{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"text\":\"ok\"}}'\n",
+    );
+    let out = repo.run_with_env(&["cxo", "echo", "hi"], &[("CX_TRANSCRIPTS", "1")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let exec_id = parse_jsonl(&repo.runs_log())[0]["execution_id"]
+        .as_str()
+        .expect("execution_id")
+        .to_string();
+    let payload = run_json(&repo, &["trace", "--json"]);
+    let transcript = &payload["links"]["transcript"];
+    assert!(
+        transcript["prompt_chars"].as_u64().unwrap_or(0) > 0,
+        "{payload}"
+    );
+    assert_eq!(transcript["response_chars"], 2);
+    assert!(payload["links"]["quarantine"].is_null());
+    let text = stdout_str(&repo.run(&["trace"]));
+    assert!(text.contains(&format!("execution_id: {exec_id}")), "{text}");
+    assert!(text.contains("transcript: prompt_chars="), "{text}");
+
+    std::fs::create_dir_all(repo.quarantine_dir()).expect("quarantine dir");
+    std::fs::write(
+        repo.quarantine_file("q_trace_1"),
+        r#"{"id":"q_trace_1","tool":"cxcommitjson","reason":"missing required key: subject","raw_response":"{\"body\":[]}"}"#,
+    )
+    .expect("write quarantine");
+    std::fs::write(
+        repo.tasks_file(),
+        r#"[{"id":"task_001","parent_id":null,"role":"architect","objective":"ship it","context_ref":"","status":"in_progress","created_at":"t0","updated_at":"t0"},
+            {"id":"task_002","parent_id":"task_001","role":"implementer","objective":"write commit","context_ref":"","status":"failed","created_at":"t0","updated_at":"t0"}]"#,
+    )
+    .expect("write tasks");
+    write_runs_log_row(
+        &repo,
+        &serde_json::json!({
+            "execution_id": "trace_q", "ts": "2026-01-01T00:00:00Z", "tool": "cxcommitjson",
+            "schema_enforced": true, "schema_valid": false,
+            "schema_reason": "missing required key: subject",
+            "quarantine_id": "q_trace_1", "task_id": "task_002"
+        }),
+    );
+    let payload = run_json(&repo, &["trace", "--json"]);
+    let links = &payload["links"];
+    assert_eq!(links["schema"]["valid"], false);
+    assert_eq!(
+        links["quarantine"]["reason"],
+        "missing required key: subject"
+    );
+    assert_eq!(links["quarantine"]["raw_response_snippet"], "{\"body\":[]}");
+    let chain: Vec<&str> = links["task_chain"]
+        .as_array()
+        .expect("task_chain")
+        .iter()
+        .filter_map(|t| t["id"].as_str())
+        .collect();
+    assert_eq!(chain, vec!["task_001", "task_002"]);
+
+    let out = repo.run(&["trace"]);
+    let text = stdout_str(&out);
+    assert!(text.contains("quarantine: q_trace_1"), "{text}");
+    assert!(
+        text.contains("raw_response (11 chars): {\"body\":[]}"),
+        "{text}"
+    );
+    assert!(
+        text.contains("    task_002 [failed] implementer: write commit"),
+        "{text}"
+    );
+}
+
+#[test]
+fn trace_accepts_execution_id_and_unique_prefix() {
+    let repo = TempRepo::new("cxrs-it");
+    seed_runs(&repo);
+
+    let payload = run_json(&repo, &["trace", "ajc1", "--json"]);
+    assert_eq!(payload["run"]["execution_id"], "ajc1");
+    assert_eq!(payload["index"], 2);
+    assert_eq!(payload["available"], 2);
+
+    let out = repo.run(&["trace", "ajc"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(
+        stderr_str(&out).contains("prefix 'ajc' is ambiguous (2 runs: ajc2, ajc1)"),
+        "{}",
+        stderr_str(&out)
+    );
+
+    write_runs_log_rows(
+        &repo,
+        &[
+            serde_json::json!({"execution_id":"ajc1","ts":"2026-01-01T00:00:00Z","tool":"cxo"}),
+            serde_json::json!({"execution_id":"zz_new_run","ts":"2026-01-02T00:00:00Z","tool":"cxj"}),
+        ],
+    );
+    let out = repo.run(&["trace", "zz_"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("execution_id: zz_new_run"));
+    assert!(stdout_str(&out).contains("== cxrs trace (run #1 most recent) =="));
+    assert!(repo.runs_log().with_extension("idx").exists());
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+
+#[test]
+fn notes_appear_in_trace_tasks_and_worklog() {
+    let repo = TempRepo::new("cxrs-annotate");
+    repo.write_mock_codex(
+        "#!/usr/bin/env bash\ncat >/dev/null\nprintf '%s\\n' '{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"text\":\"ok\"}}'\n",
+    );
+    assert!(repo.run(&["cxo", "echo", "hi"]).status.success());
+    let runs = parse_jsonl(&repo.runs_log());
+    let exec_id = runs[0]["execution_id"].as_str().expect("execution_id");
+    let add = repo.run(&["task", "add", "investigate deploy", "--role", "implementer"]);
+    assert!(add.status.success(), "stderr={}", stderr_str(&add));
+    let task_id = stdout_str(&add).trim().to_string();
+
+    let out = repo.run(&["annotate", "run", exec_id, "this was the bad deploy"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let out = repo.run(&["annotate", "task", &task_id, "rollback", "done"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+
+    let out = repo.run(&["trace"]);
+    let text = stdout_str(&out);
+    assert!(text.contains(&format!("execution_id: {exec_id}")), "{text}");
+    assert!(text.contains("] this was the bad deploy"), "{text}");
+    let out = repo.run(&["trace", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("trace json");
+    assert_eq!(v["notes"][0]["note"], "this was the bad deploy");
+
+    let out = repo.run(&["task", "show", &task_id]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("task json");
+    assert_eq!(v["notes"][0]["note"], "rollback done");
+
+    let out = repo.run(&["worklog"]);
+    let text = stdout_str(&out);
+    assert!(text.contains("## Notes"), "{text}");
+    assert!(
+        text.contains(&format!("- run {exec_id} (")),
+        "run notes in the window are exported: {text}"
+    );
+    let out = repo.run(&["worklog", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("worklog json");
+    assert_eq!(v["notes"][0]["kind"], "run");
+    assert_eq!(v["notes"][0]["target"], exec_id);
+
+    let out = repo.run(&["annotate", "run", "nope", "x"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("run not found: nope"));
+    let out = repo.run(&["annotate", "task", &task_id]);
+    assert_eq!(out.status.code(), Some(2));
+}
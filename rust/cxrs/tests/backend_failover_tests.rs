@@ -0,0 +1,94 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::{Value, json};
+use std::fs;
+
+fn set_chain(repo: &TempRepo) {
+    let state = json!({
+        "preferences": {"backend_chain": ["codex", "ollama"], "ollama_model": "llama3"}
+    });
+    fs::write(repo.state_file(), state.to_string()).expect("write state");
+}
+
+fn mock_local_model(repo: &TempRepo) {
+    repo.write_mock(
+        "ollama",
+        "#!/usr/bin/env bash\ncat >/dev/null\nprintf 'served locally\\n'\n",
+    );
+}
+
+#[test]
+fn failing_codex_falls_over_to_ollama() {
+    let repo = TempRepo::new("cxrs-failover");
+    set_chain(&repo);
+    repo.write_mock_codex("#!/usr/bin/env bash\ncat >/dev/null\necho 'rate limited' >&2\nexit 1\n");
+    mock_local_model(&repo);
+
+    let out = repo.run(&["cx", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("served locally"));
+    assert!(
+        stderr_str(&out).contains("codex failed (codex exited with status"),
+        "{}",
+        stderr_str(&out)
+    );
+    assert!(stderr_str(&out).contains("failing over to ollama"));
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let row = rows.last().expect("row");
+    assert_eq!(row["llm_backend"], "ollama");
+    assert_eq!(row["backend_used"], "ollama");
+    assert_eq!(row["backend_selected"], "codex");
+    assert_eq!(row["llm_model"], "llama3");
+    assert_eq!(row["adapter_type"], "ollama-cli");
+    assert_eq!(row["route_reason"], "failover_to_ollama");
+    assert_eq!(row["failover_count"], 1);
+    let reasons = row["failover_reasons"].as_array().expect("reasons");
+    assert!(reasons[0].as_str().unwrap_or("").starts_with("codex: "));
+
+    let out = repo.run(&["optimize", "10", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    let bf = &v["scoreboard"]["backend_failover"];
+    assert_eq!(bf["runs_with_failover"], 1);
+    assert_eq!(bf["failovers"], 1);
+    assert!(
+        v["anomalies"]
+            .as_array()
+            .expect("anomalies")
+            .iter()
+            .any(|a| a
+                .as_str()
+                .unwrap_or("")
+                .contains("Backend failover frequent"))
+    );
+}
+
+#[test]
+fn failover_skips_healthy_and_reports_exhausted() {
+    let repo = TempRepo::new("cxrs-failover");
+    set_chain(&repo);
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"from codex"}}'
+"#,
+    );
+    mock_local_model(&repo);
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let rows = parse_jsonl(&repo.runs_log());
+    let row = rows.last().expect("row");
+    assert_eq!(row["llm_backend"], "codex");
+    assert_eq!(row["route_reason"], "codex_selected");
+    assert!(row.get("failover_count").is_none_or(Value::is_null));
+
+    repo.write_mock_codex("#!/usr/bin/env bash\ncat >/dev/null\nexit 3\n");
+    repo.write_mock("ollama", "#!/usr/bin/env bash\ncat >/dev/null\nexit 4\n");
+    let out = repo.run(&["cx", "echo", "hi"]);
+    assert!(!out.status.success());
+    let err = stderr_str(&out);
+    assert!(err.contains("all backends failed: codex: "), "{err}");
+    assert!(err.contains("; ollama: ollama exited"), "{err}");
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, read_json, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+
+const MOCK_CODEX: &str = r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+"#;
+
+fn capture_row(i: u64, tool: &str, chars: u64) -> Value {
+    json!({"execution_id": format!("bs{i}"), "timestamp": "2026-01-01T00:00:00Z",
+           "ts": "2026-01-01T00:00:00Z", "tool": tool, "system_output_len_raw": chars,
+           "system_output_lines_raw": chars / 50, "system_output_tokens_raw": chars / 4,
+           "clipped": chars > 12_000})
+}
+
+#[test]
+fn budget_suggest_reports_and_applies_per_tool() {
+    let repo = TempRepo::new("cxrs-budget-suggest");
+    let mut rows: Vec<Value> = (1..=10).map(|i| capture_row(i, "cxo", i * 2_000)).collect();
+    rows.push(capture_row(11, "cxj", 500));
+    write_runs_log_rows(&repo, &rows);
+
+    let out = repo.run(&["budget", "suggest", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["contract_version"], "budget-suggest.v1");
+    let tools = v["tools"].as_array().expect("tools");
+    let cxj = tools.iter().find(|t| t["tool"] == "cxj").expect("cxj");
+    assert!(cxj["suggested"].is_null());
+    let cxo = tools.iter().find(|t| t["tool"] == "cxo").expect("cxo");
+    assert_eq!(cxo["clipped_runs"], 4);
+    assert_eq!(
+        cxo["suggested"],
+        json!({"budget_chars": 20_000, "budget_lines": 400})
+    );
+    assert_eq!(cxo["current"]["budget_chars"], 12_000);
+
+    let out = repo.run(&["budget", "suggest", "--coverage", "50", "--apply"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("insufficient data"), "{text}");
+    assert!(text.contains("applied: 1 tool(s)"), "{text}");
+    let state = read_json(&repo.state_file());
+    assert_eq!(
+        state["budget_overrides"]["cxo"],
+        json!({"budget_chars": 10_000, "budget_lines": 200})
+    );
+    let budget = stdout_str(&repo.run(&["budget"]));
+    assert!(
+        budget.contains("budget_overrides: cxo(chars=10000,lines=200)"),
+        "{budget}"
+    );
+
+    repo.write_mock_codex(MOCK_CODEX);
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let last = parse_jsonl(&repo.runs_log()).pop().expect("row");
+    assert_eq!(last["budget_chars"], 10_000);
+    assert_eq!(last["budget_lines"], 200);
+
+    let out = repo.run(&["budget", "bogus"]);
+    assert_eq!(out.status.code(), Some(2));
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{TempRepo, mock_recorder, parse_jsonl};
+use serde_json::Value;
+use std::fs;
+
+fn last_row(repo: &TempRepo) -> Value {
+    parse_jsonl(&repo.runs_log()).pop().expect("run row")
+}
+
+#[test]
+fn colored_output_is_stripped_and_counted() {
+    let repo = TempRepo::new("cxrs-ansi");
+    let prompts = mock_recorder(&repo, "ok");
+    fs::write(
+        repo.root.join("color.sh"),
+        "printf '\\033[32mok\\033[0m build\\n\\033[1;31merror\\033[0m: boom\\n'\n",
+    )
+    .expect("write script");
+
+    let out = repo.run(&["cxo", "bash", "color.sh"]);
+    assert!(out.status.success(), "{}", common::stderr_str(&out));
+    let prompt = fs::read_to_string(&prompts).expect("prompt");
+    assert!(prompt.contains("ok build\nerror: boom"), "{prompt:?}");
+    assert!(!prompt.contains('\x1b'), "{prompt:?}");
+    let row = last_row(&repo);
+    assert_eq!(row["ansi_sequences_stripped"], 4);
+    assert_eq!(row["binary_output"], false);
+}
+
+#[test]
+fn binary_output_becomes_a_notice() {
+    let repo = TempRepo::new("cxrs-binary");
+    let prompts = mock_recorder(&repo, "ok");
+    let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR".to_vec();
+    png.extend(std::iter::repeat_n(0u8, 1000));
+    fs::write(repo.root.join("image.png"), &png).expect("write png");
+
+    let out = repo.run(&["cxo", "cat", "image.png"]);
+    assert!(out.status.success(), "{}", common::stderr_str(&out));
+    let prompt = fs::read_to_string(&prompts).expect("prompt");
+    assert!(
+        prompt.contains("[cx] binary output omitted: 1016 bytes (PNG image)"),
+        "{prompt:?}"
+    );
+    assert!(!prompt.contains("IHDR"), "{prompt:?}");
+    let row = last_row(&repo);
+    assert_eq!(row["binary_output"], true);
+    assert_eq!(row["ansi_sequences_stripped"], 0);
+}
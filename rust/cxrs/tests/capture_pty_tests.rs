@@ -0,0 +1,51 @@
+#![cfg(unix)]
+mod common;
+
+use common::{TempRepo, parse_jsonl};
+use serde_json::Value;
+use std::fs;
+
+#[test]
+fn pty_provider_keeps_tty_and_strips_ansi() {
+    let repo = TempRepo::new("cxrs-pty");
+    let prompts = repo.root.join("prompt.txt");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat > "{}"
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"ok"}}}}'
+"#,
+        prompts.display()
+    ));
+    fs::write(
+        repo.root.join("tty.sh"),
+        "if [ -t 1 ]; then echo 'stdout is a tty'; else echo 'stdout is a pipe'; fi\nprintf '\\033[31mred alert\\033[0m\\n'\necho 'to stderr' >&2\n",
+    )
+    .expect("write script");
+
+    let out = repo.run_with_env(
+        &["cxo", "bash", "tty.sh"],
+        &[("CX_CAPTURE_PROVIDER", "pty")],
+    );
+    assert!(out.status.success(), "{}", common::stderr_str(&out));
+    let prompt = fs::read_to_string(&prompts).expect("prompt");
+    assert!(prompt.contains("stdout is a tty"), "{prompt}");
+    assert!(prompt.contains("red alert"), "{prompt}");
+    assert!(prompt.contains("to stderr"), "{prompt}");
+    assert!(!prompt.contains('\x1b'), "{prompt:?}");
+    assert!(!prompt.contains('\r'), "{prompt:?}");
+    let row = parse_jsonl(&repo.runs_log()).pop().expect("run row");
+    assert_eq!(
+        row.get("capture_provider").and_then(Value::as_str),
+        Some("pty")
+    );
+
+    let out = repo.run_with_env(&["cxo", "bash", "tty.sh"], &[]);
+    assert!(out.status.success());
+    let prompt = fs::read_to_string(&prompts).expect("prompt");
+    assert!(prompt.contains("stdout is a pipe"), "{prompt}");
+    let row = parse_jsonl(&repo.runs_log()).pop().expect("run row");
+    assert_eq!(
+        row.get("capture_provider").and_then(Value::as_str),
+        Some("native")
+    );
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str};
+use serde_json::Value;
+use std::fs;
+
+#[test]
+fn stderr_keeps_budget_share_under_noisy_stdout() {
+    let repo = TempRepo::new("cxrs-streams");
+    let prompts = repo.root.join("prompts.log");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat >> "{}"
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"ok"}}}}'
+"#,
+        prompts.display()
+    ));
+    fs::write(
+        repo.root.join("noisy.sh"),
+        "for i in $(seq 1 200); do echo \"progress $i\"; done\necho 'fatal: disk full' >&2\n",
+    )
+    .expect("write script");
+
+    let out = repo.run_with_env(
+        &["cxo", "bash", "noisy.sh"],
+        &[
+            ("CX_CONTEXT_BUDGET_LINES", "40"),
+            ("CX_CONTEXT_CLIP_MODE", "head"),
+        ],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let prompt = fs::read_to_string(&prompts).expect("prompt");
+    assert!(prompt.contains("[stdout]\nprogress 1\n"), "{prompt}");
+    assert!(prompt.contains("[stderr]\nfatal: disk full"), "{prompt}");
+    assert!(!prompt.contains("progress 200"), "{prompt}");
+
+    let row = parse_jsonl(&repo.runs_log()).pop().expect("run row");
+    let field = |k: &str| row.get(k).and_then(Value::as_u64);
+    assert_eq!(field("stdout_lines_raw"), Some(200));
+    assert_eq!(field("stdout_lines_clipped"), Some(39));
+    assert_eq!(field("stderr_lines_raw"), Some(1));
+    assert_eq!(field("stderr_lines_clipped"), Some(1));
+    assert_eq!(field("stderr_len_raw"), Some(17));
+}
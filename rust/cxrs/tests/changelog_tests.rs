@@ -0,0 +1,91 @@
+mod common;
+
+use common::{TempRepo, git, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+fn commit(repo: &TempRepo, file: &str, msg: &str) {
+    fs::write(repo.root.join(file), msg).expect("write file");
+    git(repo, &["add", file]);
+    git(
+        repo,
+        &[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "-qm",
+            msg,
+        ],
+    );
+}
+
+#[test]
+fn changelog_groups_and_prefers_recorded_json() {
+    let repo = TempRepo::new("cxrs-changelog");
+    commit(&repo, "base.txt", "chore: initial");
+    git(&repo, &["tag", "v1.0.0"]);
+    commit(&repo, "api.rs", "feat(api)!: drop v1 routes");
+    commit(&repo, "bug.rs", "fix: handle empty input");
+    commit(&repo, "misc.txt", "tweak things");
+
+    // A commitjson run on the staged tree is reused for the commit it becomes.
+    fs::write(repo.root.join("billing.rs"), "fn charge() {}\n").expect("write billing");
+    git(&repo, &["add", "billing.rs"]);
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"{\"subject\":\"feat: add billing charge\",\"body\":[\"adds charge()\"],\"breaking\":false,\"scope\":\"billing\",\"tests\":[]}"}}'
+"#,
+    );
+    let out = repo.run(&["commitjson"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    git(
+        &repo,
+        &[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "-qm",
+            "wip",
+        ],
+    );
+
+    let out = repo.run(&["changelog", "--from", "v1.0.0", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("changelog json");
+    assert_eq!(v["contract_version"], "changelog.v1");
+    assert_eq!(v["commits"], 4);
+    let types: Vec<&str> = v["sections"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(types, ["feat", "fix", "other"]);
+    let feats = v["sections"][0]["entries"].as_array().unwrap();
+    let billing = feats
+        .iter()
+        .find(|e| e["source"] == "commitjson")
+        .expect("commitjson-sourced entry");
+    assert_eq!(billing["subject"], "add billing charge");
+    assert_eq!(billing["scope"], "billing");
+    assert_eq!(billing["body"][0], "adds charge()");
+    let md = v["markdown"].as_str().unwrap();
+    assert!(md.starts_with("## Changes v1.0.0..HEAD\n"), "{md}");
+    assert!(md.contains("### Breaking Changes\n\n- **api:** drop v1 routes ("));
+    assert!(md.contains("### Bug Fixes\n\n- handle empty input ("));
+    assert!(md.contains("### Other Changes\n\n- tweak things ("));
+    assert!(!md.contains("initial"));
+
+    let out = repo.run(&["changelog", "--from", "v1.0.0"]);
+    assert_eq!(stdout_str(&out), md);
+
+    let out = repo.run(&["changelog", "--to", "HEAD"]);
+    assert_eq!(out.status.code(), Some(2));
+    let out = repo.run(&["changelog", "--from", "no-such-tag"]);
+    assert_eq!(out.status.code(), Some(1));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, read_json, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+use std::fs;
+
+#[test]
+fn ci_validate_audits_quarantine_and_writes_report() {
+    let repo = TempRepo::new("cxrs-ci-quarantine");
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"not json"}}'
+"#,
+    );
+    let out = repo.run(&["next", "echo", "hi"]);
+    assert_eq!(out.status.code(), Some(4), "stderr={}", stderr_str(&out));
+    let mut rows = parse_jsonl(&repo.runs_log());
+    let kept = rows
+        .last()
+        .and_then(|r| r["quarantine_id"].as_str())
+        .expect("quarantine id")
+        .to_string();
+
+    let mut tampered = read_json(&repo.quarantine_file(&kept));
+    tampered["id"] = json!("tampered");
+    tampered["raw_response"] = json!("edited");
+    fs::write(repo.quarantine_file("tampered"), tampered.to_string()).expect("write record");
+    let mut orphan = rows.last().cloned().expect("row");
+    orphan["quarantine_id"] = json!("gone");
+    rows.push(orphan);
+    write_runs_log_rows(&repo, &rows);
+
+    let report = repo.root.join("out/ci.json");
+    let out = repo.run(&[
+        "ci",
+        "validate",
+        "--json",
+        "--report",
+        &report.to_string_lossy(),
+    ]);
+    assert_eq!(out.status.code(), Some(1), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(read_json(&report), v);
+    assert_eq!(v["quarantine"]["records"], 2);
+    assert_eq!(v["quarantine"]["referenced_ids"], 2);
+    assert_eq!(v["counts"]["errors"]["quarantine_hash_mismatch"], 1);
+    assert_eq!(v["counts"]["warnings"]["quarantine_orphan_ref"], 1);
+    assert_eq!(v["counts"]["warnings"]["quarantine_unreferenced"], 1);
+    assert!(
+        v["errors"]
+            .as_array()
+            .is_some_and(|e| e.iter().all(|m| !m.as_str().unwrap_or("").contains(&kept)))
+    );
+
+    let out = repo.run(&["ci", "validate", "--strict", "--legacy-ok"]);
+    assert_eq!(out.status.code(), Some(1));
+    let text = stdout_str(&out);
+    assert!(
+        text.contains("quarantine: records=2 referenced_ids=2"),
+        "{text}"
+    );
+    assert!(text.contains("quarantine_orphan_ref=1"), "{text}");
+    assert!(
+        text.contains("quarantine_id gone is referenced in logs but missing on disk"),
+        "{text}"
+    );
+
+    let out = repo.run(&["ci", "validate", "--report"]);
+    assert_eq!(out.status.code(), Some(2));
+}
@@ -53,7 +53,7 @@ exit 22
     );
     assert_eq!(
         out.status.code(),
-        Some(1),
+        Some(5),
         "expected failure; stdout={} stderr={}",
         stdout_str(&out),
         stderr_str(&out)
@@ -85,7 +85,7 @@ exit 7
     );
     assert_eq!(
         out.status.code(),
-        Some(1),
+        Some(5),
         "expected failure; stdout={} stderr={}",
         stdout_str(&out),
         stderr_str(&out)
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{TempRepo, mock_codex_agent_text, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+#[test]
+fn forced_clipboard_provider_is_used_and_logged() {
+    let repo = TempRepo::new("cxrs-clip");
+    repo.write_mock_codex(&mock_codex_agent_text("copied text"));
+    let sink = repo.root.join("clip.txt");
+    repo.write_mock(
+        "xsel",
+        &format!("#!/usr/bin/env bash\ncat > \"{}\"\n", sink.display()),
+    );
+
+    let out = repo.run_with_env(
+        &["cxcopy", "echo", "hello"],
+        &[("CX_CLIPBOARD_PROVIDER", "xsel")],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("Copied to clipboard (xsel)"));
+    assert_eq!(fs::read_to_string(&sink).expect("clip sink"), "copied text");
+
+    let runs = parse_jsonl(&repo.runs_log());
+    let last = runs.last().expect("run row");
+    assert_eq!(
+        last.get("clipboard_provider").and_then(Value::as_str),
+        Some("xsel")
+    );
+}
+
+#[test]
+fn log_names_the_provider_used_after_fallback() {
+    let repo = TempRepo::new("cxrs-clip");
+    repo.write_mock_codex(&mock_codex_agent_text("copied text"));
+    for broken in ["pbcopy", "wl-copy", "xclip"] {
+        repo.write_mock(broken, "#!/usr/bin/env bash\ncat >/dev/null\nexit 1\n");
+    }
+    let sink = repo.root.join("clip.txt");
+    repo.write_mock(
+        "xsel",
+        &format!("#!/usr/bin/env bash\ncat > \"{}\"\n", sink.display()),
+    );
+
+    let out = repo.run_with_env(&["cxcopy", "echo", "hello"], &[("WAYLAND_DISPLAY", "wl-0")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("Copied to clipboard (xsel)"));
+    let runs = parse_jsonl(&repo.runs_log());
+    let last = runs.last().expect("run row");
+    assert_eq!(last["tool"], "cxcopy");
+    assert_eq!(last["clipboard_provider"], "xsel");
+}
+
+#[test]
+fn unknown_clipboard_provider_is_rejected() {
+    let repo = TempRepo::new("cxrs-clip");
+    repo.write_mock_codex(&mock_codex_agent_text("copied text"));
+    let out = repo.run_with_env(
+        &["cxcopy", "echo", "hello"],
+        &[("CX_CLIPBOARD_PROVIDER", "carrier-pigeon")],
+    );
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("unknown CX_CLIPBOARD_PROVIDER"));
+}
+
+#[test]
+fn cxcopy_copies_raw_capture_jsonl_or_markdown() {
+    let repo = TempRepo::new("cxrs-clip");
+    repo.write_mock_codex(&mock_codex_agent_text("copied text"));
+    let sink = repo.root.join("clip.txt");
+    repo.write_mock(
+        "xsel",
+        &format!("#!/usr/bin/env bash\ncat > \"{}\"\n", sink.display()),
+    );
+    let copy = |flag: &str| {
+        let out = repo.run_with_env(
+            &["cxcopy", flag, "echo", "hello"],
+            &[("CX_CLIPBOARD_PROVIDER", "xsel")],
+        );
+        assert!(out.status.success(), "stderr={}", stderr_str(&out));
+        (
+            stdout_str(&out),
+            fs::read_to_string(&sink).expect("clip sink"),
+        )
+    };
+
+    let (msg, raw) = copy("--raw");
+    assert!(msg.contains("Copied captured output to clipboard (xsel)"));
+    assert_eq!(raw.trim_end(), "hello");
+
+    let (_, jsonl) = copy("--jsonl");
+    let event: Value = serde_json::from_str(jsonl.lines().next().expect("event")).expect("json");
+    assert_eq!(event["item"]["text"], "copied text");
+
+    let (_, md) = copy("--both");
+    for section in [
+        "## Command\n\n```sh\necho hello\n```",
+        "## Captured output\n\n```text\nhello\n```",
+        "## Response\n\ncopied text\n",
+        "## JSONL transcript\n\n```jsonl\n{",
+    ] {
+        assert!(md.contains(section), "missing {section:?} in {md}");
+    }
+
+    let out = repo.run(&["cxcopy", "--raw"]);
+    assert_eq!(out.status.code(), Some(2));
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::{TempRepo, git, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+#[test]
+fn commitjson_prompt_has_stats_and_scope() {
+    let repo = TempRepo::new("cxrs-commitctx");
+    let src = repo.root.join("src");
+    fs::create_dir_all(&src).expect("mkdir src");
+    fs::create_dir_all(repo.root.join("tests")).expect("mkdir tests");
+    fs::write(src.join("billing.rs"), "fn charge() {}\n").expect("write src");
+    fs::write(
+        repo.root.join("tests").join("billing_tests.rs"),
+        "#[test]\nfn t() {}\n",
+    )
+    .expect("write test");
+    git(&repo, &["add", "src", "tests"]);
+
+    let prompt_file = repo.root.join("prompt.txt");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat > {}
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"{{\"subject\":\"add billing\",\"body\":[],\"breaking\":false,\"scope\":null,\"tests\":[]}}"}}}}'
+"#,
+        prompt_file.display()
+    ));
+
+    let out = repo.run(&["commitjson"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let prompt = fs::read_to_string(&prompt_file).expect("captured prompt");
+    assert!(prompt.contains("Files changed: 2 (+3 -0)"), "{prompt}");
+    assert!(prompt.contains("- src/billing.rs [Rust] +1 -0"), "{prompt}");
+    assert!(prompt.contains("Suggested scope: billing"), "{prompt}");
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("commitjson output");
+    assert_eq!(v["scope"], "billing");
+}
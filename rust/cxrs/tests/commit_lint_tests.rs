@@ -0,0 +1,96 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+use std::process::Command;
+
+fn stage_file(repo: &TempRepo) {
+    fs::write(repo.root.join("billing.rs"), "fn charge() {}\n").expect("write src");
+    let out = Command::new("git")
+        .args(["add", "billing.rs"])
+        .current_dir(&repo.root)
+        .env("HOME", &repo.home)
+        .output()
+        .expect("git add");
+    assert!(out.status.success());
+}
+
+/// Each call appends its prompt to `prompts.txt` and replies with `commit`.
+fn mock_commit(repo: &TempRepo, commit: &str) {
+    let text = serde_json::to_string(commit).expect("quote");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat >> prompts.txt
+echo '---' >> prompts.txt
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":{text}}}}}'
+"#
+    ));
+}
+
+fn prompt_count(repo: &TempRepo) -> usize {
+    fs::read_to_string(repo.root.join("prompts.txt"))
+        .unwrap_or_default()
+        .matches("\n---\n")
+        .count()
+}
+
+#[test]
+fn safe_violations_are_fixed_without_reprompt() {
+    let repo = TempRepo::new("cxrs-commit-lint");
+    stage_file(&repo);
+    mock_commit(
+        &repo,
+        r#"{"subject":"feat(billing): Added charge support.","body":["adds charge()"],"breaking":false,"scope":"billing","tests":[]}"#,
+    );
+    let out = repo.run(&["commitjson", "--strict"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["subject"], "feat(billing): add charge support");
+    assert_eq!(prompt_count(&repo), 1);
+}
+
+#[test]
+fn unfixable_lint_reprompts_then_warns_or_fails() {
+    let repo = TempRepo::new("cxrs-commit-lint");
+    stage_file(&repo);
+    mock_commit(
+        &repo,
+        &format!(
+            r#"{{"subject":"Streamlined billing","body":["{}"],"breaking":false,"scope":null,"tests":[]}}"#,
+            "word ".repeat(25).trim()
+        ),
+    );
+
+    let out = repo.run(&["commitmsg"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(prompt_count(&repo), 2);
+    let prompts = fs::read_to_string(repo.root.join("prompts.txt")).expect("prompts");
+    assert!(
+        prompts.contains("- conventional_format: subject is not"),
+        "{prompts}"
+    );
+    assert!(prompts.contains(
+        "- imperative_mood: start the subject with an imperative verb, not 'streamlined'"
+    ));
+    let err = stderr_str(&out);
+    assert!(
+        err.contains("cxrs commitmsg: lint conventional_format:"),
+        "{err}"
+    );
+    assert!(
+        err.contains("cxrs commitmsg: lint imperative_mood:"),
+        "{err}"
+    );
+    let text = stdout_str(&out);
+    let body: Vec<&str> = text.lines().skip(2).collect();
+    assert!(body.len() > 1, "{text}");
+    assert!(body[0].starts_with("- word") && body[1].starts_with("  word"));
+    assert!(body.iter().all(|l| l.chars().count() <= 72), "{text}");
+
+    let out = repo.run(&["commitjson", "--strict"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stdout_str(&out).is_empty());
+    assert!(stderr_str(&out).contains("failed lint with 2 issue(s) (--strict)"));
+    assert_eq!(repo.run(&["commitjson", "--bogus"]).status.code(), Some(2));
+}
@@ -140,12 +140,22 @@ impl TempRepo {
     }
 
     pub fn run_with_env(&self, args: &[&str], envs: &[(&str, &str)]) -> Output {
+        self.run_in(&self.root, args, envs)
+    }
+
+    /// Like [`TempRepo::run_with_env`], from `dir` (a package of a monorepo).
+    pub fn run_in(&self, dir: &Path, args: &[&str], envs: &[(&str, &str)]) -> Output {
         let path = format!("{}:{}", self.mock_bin.display(), self.original_path);
         let mut cmd = Command::new(env!("CARGO_BIN_EXE_cxrs"));
         cmd.args(args)
-            .current_dir(&self.root)
+            .current_dir(dir)
             .env("HOME", &self.home)
-            .env("PATH", path);
+            .env("PATH", path)
+            .env_remove("CX_HOME")
+            .env_remove("CX_CODEX_ROOT")
+            .env_remove("XDG_STATE_HOME")
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("XDG_CACHE_HOME");
         for (k, v) in envs {
             cmd.env(k, v);
         }
@@ -203,6 +213,42 @@ pub fn stderr_str(out: &Output) -> String {
     String::from_utf8_lossy(&out.stderr).to_string()
 }
 
+/// Run git in `repo` and return its stdout; panics when git fails.
+pub fn git(repo: &TempRepo, args: &[&str]) -> String {
+    let out = Command::new(git_bin())
+        .args(args)
+        .current_dir(&repo.root)
+        .env("HOME", &repo.home)
+        .output()
+        .expect("run git");
+    assert!(out.status.success(), "git {args:?} failed");
+    String::from_utf8_lossy(&out.stdout).to_string()
+}
+
+/// Mock `codex` script that ignores the prompt and replies with `text`.
+pub fn mock_codex_agent_text(text: &str) -> String {
+    format!(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":{text:?}}}}}'
+"#
+    )
+}
+
+/// Install a mock `codex` that saves each prompt to the returned file and
+/// replies with `text`.
+pub fn mock_recorder(repo: &TempRepo, text: &str) -> PathBuf {
+    let prompt = repo.root.join("prompt.txt");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat > "{}"
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":{text:?}}}}}'
+"#,
+        prompt.display()
+    ));
+    prompt
+}
+
 pub fn read_json(path: &Path) -> Value {
     let text = fs::read_to_string(path).expect("read json");
     serde_json::from_str::<Value>(&text).expect("parse json")
@@ -0,0 +1,205 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+use std::fs;
+
+fn write_config(dir: &std::path::Path, body: &str) {
+    fs::create_dir_all(dir.join(".codex")).expect("mkdir .codex");
+    fs::write(dir.join(".codex").join("config.toml"), body).expect("write config");
+}
+
+#[test]
+fn env_beats_repo_config_beats_global() {
+    let repo = TempRepo::new("cxrs-config");
+    write_config(
+        &repo.home,
+        "[capture]\nbudget_chars = 20000\nclip_mode = \"tail\"\n[alert]\nmax_ms = 5000\n",
+    );
+    write_config(&repo.root, "[capture]\nbudget_chars = 30000\n");
+
+    let out = repo.run(&["config", "show", "--origin"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    let repo_file = repo.root.join(".codex/config.toml");
+    let home_file = repo.home.join(".codex/config.toml");
+    assert!(text.contains(&format!(
+        "capture.budget_chars = 30000  # {}",
+        repo_file.display()
+    )));
+    assert!(text.contains(&format!(
+        "capture.clip_mode = tail  # {}",
+        home_file.display()
+    )));
+    assert!(text.contains("capture.budget_lines = 300  # default"));
+    assert!(text.contains(&format!("# repo_file: {}", repo_file.display())));
+
+    let out = repo.run(&["budget"]);
+    assert!(stdout_str(&out).contains("CX_CONTEXT_BUDGET_CHARS=30000"));
+    assert!(stdout_str(&out).contains("CX_CONTEXT_CLIP_MODE=tail"));
+
+    let out = repo.run_with_env(
+        &["config", "show", "--origin"],
+        &[("CX_CONTEXT_BUDGET_CHARS", "999")],
+    );
+    assert!(stdout_str(&out).contains("capture.budget_chars = 999  # env:CX_CONTEXT_BUDGET_CHARS"));
+    let out = repo.run(&["config", "show"]);
+    assert!(stdout_str(&out).contains("alert.max_ms = 5000\n"));
+}
+
+#[test]
+fn invalid_config_file_is_ignored_with_warning() {
+    let repo = TempRepo::new("cxrs-config");
+    write_config(&repo.root, "[capture]\nbudget = 1\n");
+    let out = repo.run(&["budget"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stderr_str(&out).contains("unknown setting 'capture.budget'"));
+    assert!(stdout_str(&out).contains("CX_CONTEXT_BUDGET_CHARS=12000"));
+
+    let out = repo.run(&["config", "bogus"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn config_set_get_unset_edit_scoped_file() {
+    let repo = TempRepo::new("cxrs-config");
+    let out = repo.run(&["config", "set", "capture.budget_chars", "20000"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let repo_file = repo.root.join(".codex/config.toml");
+    let raw = fs::read_to_string(&repo_file).expect("repo config");
+    assert!(raw.contains("[capture]\nbudget_chars = 20000"));
+
+    let out = repo.run(&["config", "set", "llm.backend", "ollama", "--global"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let out = repo.run(&["config", "set", "capture.clip_footer", "off", "--global"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let raw = fs::read_to_string(repo.home.join(".codex/config.toml")).expect("global config");
+    assert!(raw.contains("clip_footer = false"));
+    assert!(raw.contains("backend = \"ollama\""));
+
+    let out = repo.run(&["config", "get", "capture.budget_chars"]);
+    assert_eq!(stdout_str(&out).trim(), "20000");
+    let out = repo.run(&["config", "get", "llm.backend", "--repo"]);
+    assert_eq!(out.status.code(), Some(1));
+    let out = repo.run(&["config", "get", "llm.backend"]);
+    assert_eq!(stdout_str(&out).trim(), "ollama");
+
+    let out = repo.run(&["config", "unset", "capture.budget_chars"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(fs::read_to_string(&repo_file).expect("repo config"), "");
+    let out = repo.run(&["config", "get", "capture.budget_chars"]);
+    assert_eq!(stdout_str(&out).trim(), "12000");
+}
+
+#[test]
+fn config_set_rejects_unknown_keys_bad_values() {
+    let repo = TempRepo::new("cxrs-config");
+    let out = repo.run(&["config", "set", "capture.budget_char", "1"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("did you mean: capture.budget_chars"));
+
+    let out = repo.run(&["config", "set", "capture.budget_chars", "lots"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("capture.budget_chars: expected a non-negative integer"));
+
+    let out = repo.run(&["config", "set", "llm.backend", "gpt"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(!repo.root.join(".codex/config.toml").exists());
+
+    let out = repo.run(&["config", "set", "alert.max_ms", "1", "--repo", "--global"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn repo_config_cannot_set_global_only_keys() {
+    let repo = TempRepo::new("cxrs-config");
+    write_config(&repo.home, "[sandbox]\nmode = \"container\"\n");
+    write_config(
+        &repo.root,
+        "[digest]\nsendmail = \"/tmp/evil\"\n[sandbox]\nmode = \"none\"\nimage = \"evil:latest\"\n[capture]\nbudget_chars = 30000\n",
+    );
+    let out = repo.run(&["config", "show", "--origin"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(
+        text.contains("digest.sendmail = sendmail  # default"),
+        "{text}"
+    );
+    assert!(text.contains(&format!(
+        "sandbox.mode = container  # {}",
+        repo.home.join(".codex/config.toml").display()
+    )));
+    assert!(text.contains("capture.budget_chars = 30000"));
+    let err = stderr_str(&out);
+    assert!(
+        err.contains("digest.sendmail can only be set in the global config or CX_DIGEST_SENDMAIL")
+    );
+    assert!(err.contains("sandbox.mode can only be set in the global config or CX_SANDBOX"));
+    assert!(text.contains("sandbox.image = debian:stable-slim  # default"));
+    assert!(err.contains("sandbox.image can only be set in the global config"));
+
+    let out = repo.run(&["config", "set", "sandbox.writable", "/"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("(use --global)"));
+    let out = repo.run(&["config", "set", "sandbox.writable", "out", "--global"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+}
+
+#[test]
+fn repo_state_overrides_global_state_per_key() {
+    let repo = TempRepo::new("cxrs-state-layers");
+    let set = |args: &[&str]| {
+        let out = repo.run(args);
+        assert!(out.status.success(), "stderr={}", stderr_str(&out));
+        out
+    };
+    set(&[
+        "state",
+        "set",
+        "preferences.llm_backend",
+        "ollama",
+        "--global",
+    ]);
+    set(&[
+        "state",
+        "set",
+        "preferences.ollama_model",
+        "llama3",
+        "--global",
+    ]);
+    let out = set(&["state", "get", "preferences.llm_backend"]);
+    assert_eq!(stdout_str(&out), "ollama\n");
+
+    set(&["state", "set", "preferences.llm_backend", "codex", "--repo"]);
+    let out = set(&["state", "get", "preferences.llm_backend"]);
+    assert_eq!(stdout_str(&out), "codex\n");
+    let out = set(&["state", "get", "preferences.llm_backend", "--global"]);
+    assert_eq!(stdout_str(&out), "ollama\n");
+
+    let out = set(&["state", "show", "--merged", "--origin"]);
+    let text = stdout_str(&out);
+    assert!(
+        text.contains("preferences.llm_backend = codex  # repo"),
+        "{text}"
+    );
+    assert!(
+        text.contains("preferences.ollama_model = llama3  # global"),
+        "{text}"
+    );
+    assert!(text.contains(&format!(
+        "# global_file: {}",
+        repo.home.join(".codex/state.json").display()
+    )));
+
+    let out = repo.run(&[
+        "state",
+        "set",
+        "preferences.llm_backend",
+        "ollama",
+        "--global",
+    ]);
+    assert!(stderr_str(&out).contains("also set in the repo state"));
+    let out = repo.run(&["state", "show", "--merged", "--repo"]);
+    assert_eq!(out.status.code(), Some(2));
+    let out = repo.run(&["state", "get", "a", "--repo", "--global"]);
+    assert_eq!(out.status.code(), Some(2));
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+const SUMMARY_SCHEMA: &str = r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "$id": "cx://schemas/lint-summary.v1",
+  "title": "cx lint-summary",
+  "type": "object",
+  "required": ["warnings"],
+  "properties": {"warnings": {"type": "integer"}}
+}"#;
+
+const COMMANDS: &str = r#"{"commands": {"lint-summary": {
+  "description": "Count lint warnings",
+  "schema": "lint-summary",
+  "prompt": "Count the warnings.\nCommand: {{command}} (exit {{status}})\n{{output}}",
+  "command": ["echo", "default-output"]
+}}}"#;
+
+/// Mock backend that records the prompt and answers with `$MOCK_ANSWER`.
+const MOCK_CODEX: &str = r#"#!/usr/bin/env bash
+cat > "$PROMPT_OUT"
+printf '{"type":"item.completed","item":{"type":"agent_message","text":"%s"}}\n' "$MOCK_ANSWER"
+"#;
+
+fn setup(repo: &TempRepo) {
+    fs::write(
+        repo.root.join(".codex/schemas/lint-summary.schema.json"),
+        SUMMARY_SCHEMA,
+    )
+    .expect("write schema");
+    fs::write(repo.root.join(".codex/commands.json"), COMMANDS).expect("write commands");
+    repo.write_mock_codex(MOCK_CODEX);
+}
+
+#[test]
+fn custom_command_renders_enforces_and_logs() {
+    let repo = TempRepo::new("cxrs-custom");
+    setup(&repo);
+    let prompt_out = repo.root.join("prompt.txt");
+    let prompt_arg = prompt_out.to_string_lossy().to_string();
+
+    let out = repo.run_with_env(
+        &["custom", "lint-summary", "--", "echo", "warning: unused"],
+        &[
+            ("PROMPT_OUT", &prompt_arg),
+            ("MOCK_ANSWER", r#"{\"warnings\":1}"#),
+        ],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["warnings"], 1);
+    let prompt = fs::read_to_string(&prompt_out).expect("prompt");
+    assert!(
+        prompt.contains("Command: echo warning: unused (exit 0)"),
+        "{prompt}"
+    );
+    assert!(prompt.contains("warning: unused\n"), "{prompt}");
+
+    let last = parse_jsonl(&repo.runs_log()).pop().expect("row");
+    assert_eq!(last["tool"], "cxrs_custom_lint-summary");
+    assert_eq!(last["schema_enforced"], true);
+    assert_eq!(last["schema_name"], "lint-summary.schema.json");
+    assert_eq!(last["prompt_template"], "custom:lint-summary");
+
+    let out = repo.run_with_env(
+        &["custom", "lint-summary"],
+        &[
+            ("PROMPT_OUT", &prompt_arg),
+            ("MOCK_ANSWER", r#"{\"warnings\":\"many\"}"#),
+        ],
+    );
+    assert_eq!(out.status.code(), Some(4), "stderr={}", stderr_str(&out));
+    assert!(
+        fs::read_to_string(&prompt_out)
+            .expect("prompt")
+            .contains("default-output")
+    );
+    let last = parse_jsonl(&repo.runs_log()).pop().expect("row");
+    assert_eq!(last["schema_valid"], false);
+    assert!(
+        last["quarantine_id"]
+            .as_str()
+            .is_some_and(|q| !q.is_empty())
+    );
+
+    let list = stdout_str(&repo.run(&["custom", "list"]));
+    assert!(
+        list.contains("- lint-summary -> lint-summary.schema.json [command: echo default-output]"),
+        "{list}"
+    );
+    let out = repo.run(&["custom", "nope"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("known: lint-summary"));
+}
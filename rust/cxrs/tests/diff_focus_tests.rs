@@ -0,0 +1,45 @@
+mod common;
+
+use common::{TempRepo, git, stderr_str};
+use std::fs;
+
+#[test]
+fn diffsum_focus_puts_matching_files_first() {
+    let repo = TempRepo::new("cxrs-difffocus");
+    fs::write(repo.root.join("alpha.txt"), "one\n").expect("write alpha");
+    fs::write(repo.root.join("zeta.rs"), "fn a() {}\n").expect("write zeta");
+    git(&repo, &["add", "alpha.txt", "zeta.rs"]);
+    git(
+        &repo,
+        &[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "-qm",
+            "base",
+        ],
+    );
+    fs::write(repo.root.join("zeta.rs"), "fn b() {}\n").expect("edit zeta");
+    fs::write(repo.root.join("alpha.txt"), "two\n").expect("edit alpha");
+
+    let prompt_file = repo.root.join("prompt.txt");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat > {}
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"{{\"title\":\"t\",\"summary\":[\"s\"],\"risk_edge_cases\":[],\"suggested_tests\":[]}}"}}}}'
+"#,
+        prompt_file.display()
+    ));
+
+    let out = repo.run(&["diffsum", "--focus", "*.rs"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let prompt = fs::read_to_string(&prompt_file).expect("captured prompt");
+    let zeta = prompt.find("+++ b/zeta.rs").expect("zeta in prompt");
+    let alpha = prompt.find("+++ b/alpha.txt").expect("alpha in prompt");
+    assert!(zeta < alpha, "focused file should lead the diff: {prompt}");
+
+    let bad = repo.run(&["diffsum", "--focus"]);
+    assert_eq!(bad.status.code(), Some(2));
+}
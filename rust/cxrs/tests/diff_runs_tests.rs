@@ -0,0 +1,80 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+
+#[test]
+fn diff_runs_reports_deltas_and_hash_equality() {
+    let repo = TempRepo::new("cxrs-diff-runs");
+    write_runs_log_rows(
+        &repo,
+        &[
+            json!({"execution_id": "run_a", "tool": "cxo", "llm_backend": "codex", "duration_ms": 1000,
+                   "input_tokens": 400, "system_output_len_clipped": 900, "prompt_sha256": "abc"}),
+            json!({"execution_id": "run_b", "tool": "cxo", "llm_backend": "ollama", "duration_ms": 1500,
+                   "input_tokens": 400, "system_output_len_clipped": 1200, "prompt_sha256": "abc"}),
+        ],
+    );
+
+    let out = repo.run(&["diff-runs", "run_a", "run_b"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.starts_with("== cxrs diff-runs run_a -> run_b ==\n"));
+    assert!(text.contains("* duration_ms"), "{text}");
+    assert!(text.contains("+500 (+50.0%)"));
+    assert!(text.contains("  input_tokens"));
+    assert!(text.contains("* llm_backend"));
+    assert!(text.contains("prompt_sha256: same\n"));
+    assert!(text.contains("transcripts: n/a"));
+    assert!(text.contains("changed: 3 of 5 fields"));
+
+    let out = repo.run(&["diff-runs", "run_a", "run_b", "--changed"]);
+    assert!(!stdout_str(&out).contains("input_tokens"));
+
+    let out = repo.run(&["diff-runs", "run_a", "run_b", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["contract_version"], "diff-runs.v1");
+    assert_eq!(v["changed"], 3);
+    assert_eq!(v["prompt_hash_equal"], true);
+
+    let out = repo.run(&["diff-runs", "run_a", "nope"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("execution_id 'nope' not found"));
+    let out = repo.run(&["diff-runs", "run_a"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn diff_runs_compares_archived_transcripts() {
+    let repo = TempRepo::new("cxrs-diff-runs");
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+"#,
+    );
+    let envs = [("CX_TRANSCRIPTS", "1")];
+    for cmd in [["cxo", "echo", "one"], ["cxo", "echo", "three"]] {
+        let out = repo.run_with_env(&cmd, &envs);
+        assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    }
+    let ids: Vec<String> = parse_jsonl(&repo.runs_log())
+        .iter()
+        .filter_map(|r| {
+            r.get("execution_id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .collect();
+    let out = repo.run(&["diff-runs", &ids[0], &ids[1], "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["prompt_hash_equal"], false);
+    assert_eq!(v["transcripts"]["prompt_equal"], false);
+    assert_eq!(v["transcripts"]["response_equal"], true);
+    let chars = &v["transcripts"]["prompt_chars"];
+    assert_eq!(
+        chars[1].as_u64().unwrap_or(0) - chars[0].as_u64().unwrap_or(0),
+        2
+    );
+}
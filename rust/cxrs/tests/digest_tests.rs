@@ -0,0 +1,118 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{TempRepo, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+use std::fs;
+
+fn row(id: &str, days_ago: i64, tool: &str, duration_ms: u64, schema_valid: bool) -> Value {
+    let ts = (Utc::now() - Duration::days(days_ago)).to_rfc3339();
+    json!({"execution_id": id, "timestamp": ts, "ts": ts, "tool": tool,
+           "duration_ms": duration_ms, "effective_input_tokens": 500,
+           "schema_valid": schema_valid, "schema_reason": "invalid JSON: eof"})
+}
+
+fn seed(repo: &TempRepo) {
+    write_runs_log_rows(
+        repo,
+        &[
+            row("p1", 9, "cxdiffsum", 1000, true),
+            row("p2", 8, "cxo", 400, true),
+            row("c1", 2, "cxdiffsum", 2000, true),
+            row("c2", 1, "cxdiffsum", 2000, false),
+            row("c3", 1, "cxo", 400, true),
+        ],
+    );
+    let qdir = repo.quarantine_dir();
+    fs::create_dir_all(&qdir).expect("quarantine dir");
+    let rec = json!({"id": "q_recent", "ts": Utc::now().to_rfc3339(), "tool": "cxdiffsum",
+                     "reason": "invalid JSON: eof", "raw_response": "{\"title\":"});
+    fs::write(qdir.join("q_recent.json"), rec.to_string()).expect("write quarantine");
+}
+
+#[test]
+fn digest_compares_periods_and_lists_regressions() {
+    let repo = TempRepo::new("cxrs-digest");
+    seed(&repo);
+
+    let out = repo.run(&["digest", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("digest json");
+    assert_eq!(v["contract_version"], "digest.v1");
+    let runs = &v["totals"][0];
+    assert_eq!(
+        (runs["metric"].as_str(), runs["current"].as_u64()),
+        (Some("runs"), Some(3))
+    );
+    assert_eq!(runs["previous"], 2);
+    assert_eq!(runs["change_pct"], 50);
+    assert_eq!(v["regressions"][0]["tool"], "cxdiffsum");
+    assert_eq!(v["regressions"][0]["change_pct"], 100);
+    assert_eq!(v["failure_clusters"][0]["tool"], "cxdiffsum");
+    assert_eq!(v["quarantines"]["notable"][0]["id"], "q_recent");
+
+    let md = stdout_str(&repo.run(&["digest"]));
+    assert!(md.starts_with("# cx digest: "), "{md}");
+    assert!(md.contains("| runs | 3 | 2 | +50% |"), "{md}");
+    assert!(
+        md.contains("- cxdiffsum avg_duration_ms: 1000 -> 2000 (+100%)"),
+        "{md}"
+    );
+    assert!(md.contains("`q_recent`"), "{md}");
+}
+
+#[test]
+fn digest_send_mails_markdown_or_needs_sink() {
+    let repo = TempRepo::new("cxrs-digest-send");
+    seed(&repo);
+    let mail = repo.root.join("mail.txt");
+    repo.write_mock(
+        "fake-sendmail",
+        &format!("#!/usr/bin/env bash\ncat > {}\n", mail.display()),
+    );
+
+    let out = repo.run_with_env(
+        &["digest", "--send"],
+        &[
+            ("CX_DIGEST_EMAIL", "team@example.com"),
+            ("CX_DIGEST_SENDMAIL", "fake-sendmail"),
+        ],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let sent = fs::read_to_string(&mail).expect("mail");
+    assert!(
+        sent.starts_with("To: team@example.com\nSubject: cx digest: "),
+        "{sent}"
+    );
+    assert!(sent.contains("## Top regressions"), "{sent}");
+
+    let out = repo.run(&["digest", "--send"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("no digest sink configured"));
+}
+
+#[test]
+fn repo_config_cannot_pick_the_sendmail_program() {
+    let repo = TempRepo::new("cxrs-digest-sendmail");
+    seed(&repo);
+    let ran = repo.root.join("ran.txt");
+    repo.write_mock(
+        "fake-sendmail",
+        &format!("#!/usr/bin/env bash\ntouch {}\n", ran.display()),
+    );
+    fs::write(
+        repo.root.join(".codex").join("config.toml"),
+        "[digest]\nsendmail = \"fake-sendmail\"\n",
+    )
+    .expect("repo config");
+
+    let out = repo.run_with_env(
+        &["digest", "--send"],
+        &[
+            ("CX_DIGEST_EMAIL", "team@example.com"),
+            ("CX_DIGEST_SENDMAIL", ""),
+        ],
+    );
+    assert!(stderr_str(&out).contains("digest.sendmail can only be set in the global config"));
+    assert!(!ran.exists());
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::*;
+use std::fs;
+
+#[test]
+fn doctor_fix_repairs_workspace_and_reports_changes() {
+    let repo = TempRepo::new("cxrs-doctor-fix");
+    let codex = repo.root.join(".codex");
+    fs::create_dir_all(&codex).unwrap();
+    fs::write(codex.join("state.json"), "{broken").unwrap();
+
+    let out = repo.run_with_env(&["doctor", "--fix"], &[("XDG_DATA_HOME", "")]);
+    assert_eq!(out.status.code(), Some(0), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("fixed: created directory"), "{text}");
+    assert!(text.contains("cxlogs"), "{text}");
+    assert!(text.contains("fixed: regenerated corrupt"), "{text}");
+    assert!(text.contains("fixed: initialized"), "{text}");
+    assert!(text.contains("fixed: installed bash completions"), "{text}");
+
+    let state: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(codex.join("state.json")).unwrap()).unwrap();
+    assert!(state.get("preferences").is_some());
+    let backups = fs::read_dir(&codex)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .starts_with("state.json.corrupt-")
+        })
+        .count();
+    assert_eq!(backups, 1);
+    assert!(codex.join("config.toml").is_file());
+    let completion = repo
+        .home
+        .join(".local/share/bash-completion/completions/cxrs");
+    assert!(
+        fs::read_to_string(completion)
+            .unwrap()
+            .contains("complete -o default -F _cxrs cxrs")
+    );
+
+    let out = repo.run_with_env(&["doctor", "--fix"], &[("XDG_DATA_HOME", "")]);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(stdout_str(&out).contains("nothing to fix"));
+
+    let out = repo.run(&["doctor", "--bogus"]);
+    assert_eq!(out.status.code(), Some(2));
+}
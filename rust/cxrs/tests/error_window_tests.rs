@@ -0,0 +1,54 @@
+mod common;
+
+use common::{TempRepo, mock_recorder, parse_jsonl};
+use serde_json::Value;
+use std::fs;
+
+fn last_clip_mode(repo: &TempRepo) -> Option<String> {
+    parse_jsonl(&repo.runs_log()).pop().and_then(|r| {
+        r.get("clip_mode")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    })
+}
+
+#[test]
+fn failing_command_keeps_window_around_first_error() {
+    let repo = TempRepo::new("cxrs-errwin");
+    let prompts = mock_recorder(&repo, "ok");
+    fs::write(
+        repo.root.join("build.sh"),
+        "for i in $(seq 1 100); do echo \"step $i\"; done\necho 'oops: missing symbol foo'\nfor i in $(seq 101 300); do echo \"step $i\"; done\nexit 3\n",
+    )
+    .expect("write script");
+    let env = [
+        ("CX_CONTEXT_BUDGET_LINES", "40"),
+        ("CX_CONTEXT_ERROR_REGEX", "^oops:"),
+    ];
+
+    let out = repo.run_with_env(&["cxo", "bash", "build.sh"], &env);
+    assert_eq!(out.status.code(), Some(3));
+    let prompt = fs::read_to_string(&prompts).expect("prompt");
+    assert!(
+        prompt.contains("[cx] command `bash build.sh` exited with status 3"),
+        "{prompt}"
+    );
+    assert!(prompt.contains("step 91\n"), "{prompt}");
+    assert!(prompt.contains("oops: missing symbol foo\n"), "{prompt}");
+    assert!(prompt.contains("step 129"), "{prompt}");
+    assert!(!prompt.contains("step 1\n"), "{prompt}");
+    assert!(!prompt.contains("step 300"), "{prompt}");
+    assert_eq!(last_clip_mode(&repo).as_deref(), Some("error-window"));
+
+    // Successful runs keep the smart head/tail choice and no failure framing.
+    fs::write(
+        repo.root.join("ok.sh"),
+        "for i in $(seq 1 300); do echo \"step $i\"; done\n",
+    )
+    .expect("write script");
+    let out = repo.run_with_env(&["cxo", "bash", "ok.sh"], &env);
+    assert!(out.status.success());
+    let prompt = fs::read_to_string(&prompts).expect("prompt");
+    assert!(!prompt.contains("exited with status"), "{prompt}");
+    assert_eq!(last_clip_mode(&repo).as_deref(), Some("head"));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+use serde_json::Value;
+
+#[test]
+fn exit_codes_lists_the_contract() {
+    let repo = TempRepo::new("cxrs-exit-codes");
+    let out = repo.run(&["exit-codes"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("4    schema_failure   "));
+    assert!(text.contains("*    command_status   "));
+
+    let out = repo.run(&["exit-codes", "--json"]);
+    let rows: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    let code_of = |name: &str| {
+        rows.as_array()
+            .and_then(|r| r.iter().find(|v| v["name"] == name))
+            .map(|v| v["code"].clone())
+    };
+    assert_eq!(code_of("usage_error"), Some(Value::from(2)));
+    assert_eq!(code_of("policy_blocked"), Some(Value::from(3)));
+    assert_eq!(code_of("backend_failure"), Some(Value::from(5)));
+    assert_eq!(code_of("command_status"), Some(Value::Null));
+
+    assert_eq!(repo.run(&["exit-codes", "--yaml"]).status.code(), Some(2));
+}
+
+#[test]
+fn failures_exit_with_their_class() {
+    let repo = TempRepo::new("cxrs-exit-codes");
+    repo.write_mock_codex("#!/usr/bin/env bash\ncat >/dev/null\necho boom >&2\nexit 7\n");
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert_eq!(out.status.code(), Some(5), "stderr={}", stderr_str(&out));
+
+    let out = repo.run_with_env(
+        &["next", "echo", "hi"],
+        &[
+            ("CX_PROVIDER_ADAPTER", "mock"),
+            ("CX_MOCK_PLAIN_RESPONSE", "not-json"),
+        ],
+    );
+    assert_eq!(out.status.code(), Some(4), "stderr={}", stderr_str(&out));
+
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+"#,
+    );
+    let out = repo.run(&["cxo", "sh", "-c", "exit 9"]);
+    assert_eq!(out.status.code(), Some(9), "stderr={}", stderr_str(&out));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+fn mock_explainer(repo: &TempRepo) -> std::path::PathBuf {
+    let prompt_file = repo.root.join("prompt.txt");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat > {}
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"{{\"purpose\":\"Adds numbers\",\"key_functions\":[{{\"name\":\"add\",\"summary\":\"sums two ints\"}}],\"risks\":[\"overflow\"],\"related_files\":[\"util.rs\"]}}"}}}}'
+"#,
+        prompt_file.display()
+    ));
+    prompt_file
+}
+
+#[test]
+fn explain_reads_line_range_and_renders_sections() {
+    let repo = TempRepo::new("cxrs-explain");
+    let prompt_file = mock_explainer(&repo);
+    let src = (1..=10)
+        .map(|i| format!("fn f{i}() {{}}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(repo.root.join("lib.rs"), src).expect("write lib");
+    fs::write(repo.root.join("util.rs"), "").expect("write util");
+
+    let out = repo.run(&["explain", "lib.rs:3-4"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("File: lib.rs:3-4"));
+    assert!(text.contains("Purpose: Adds numbers"));
+    assert!(text.contains("- add: sums two ints"));
+    assert!(text.contains("Related files:\n- util.rs"));
+
+    let prompt = fs::read_to_string(&prompt_file).expect("prompt");
+    assert!(prompt.contains("    3 | fn f3() {}\n    4 | fn f4() {}"));
+    assert!(!prompt.contains("fn f5()"));
+    assert!(prompt.contains("util.rs"));
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let last = rows.last().expect("run row");
+    assert_eq!(
+        last.get("tool").and_then(Value::as_str),
+        Some("cxrs_explain")
+    );
+    assert_eq!(
+        last.get("schema_name").and_then(Value::as_str),
+        Some("explain.schema.json")
+    );
+}
+
+#[test]
+fn explain_json_and_errors() {
+    let repo = TempRepo::new("cxrs-explain");
+    mock_explainer(&repo);
+    fs::write(repo.root.join("a.txt"), "one\ntwo\n").expect("write a");
+
+    let out = repo.run(&["explain", "a.txt", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(
+        v.get("purpose").and_then(Value::as_str),
+        Some("Adds numbers")
+    );
+
+    assert_eq!(repo.run(&["explain", "a.txt:5-2"]).status.code(), Some(2));
+    assert_eq!(repo.run(&["explain", "a.txt:9"]).status.code(), Some(1));
+    assert_eq!(repo.run(&["explain", "missing.rs"]).status.code(), Some(1));
+    assert_eq!(repo.run(&["explain"]).status.code(), Some(2));
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+fn mock_planner(repo: &TempRepo, subtasks: &str) -> std::path::PathBuf {
+    let prompt_file = repo.root.join("prompt.txt");
+    let escaped = subtasks.replace('"', "\\\"");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat > {}
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"{escaped}"}}}}'
+"#,
+        prompt_file.display()
+    ));
+    prompt_file
+}
+
+const PLAN: &str = r#"{"subtasks":[{"role":"architect","objective":"Sketch the cache layer","depends_on":[],"estimate":"1h"},{"role":"implementer","objective":"Add the cache","depends_on":[1],"estimate":"4h"},{"role":"tester","objective":"Cover cache eviction","depends_on":[2],"estimate":"2h"}]}"#;
+
+#[test]
+fn fanout_llm_prints_schema_validated_subtasks() {
+    let repo = TempRepo::new("cxrs-fanout-llm");
+    let prompt_file = mock_planner(&repo, PLAN);
+
+    let out = repo.run(&["fanout", "--llm", "add", "a", "cache"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("== cxrs fanout (llm) =="));
+    assert!(text.contains(
+        "### Subtask 2/3 [implementer]\nGoal: Add the cache\nDepends on: 1\nEstimate: 4h"
+    ));
+
+    let prompt = fs::read_to_string(&prompt_file).expect("prompt");
+    assert!(prompt.contains("OBJECTIVE:\nadd a cache"));
+    assert!(prompt.contains("- reviewer: "));
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let last = rows.last().expect("run row");
+    assert_eq!(
+        last.get("tool").and_then(Value::as_str),
+        Some("cxrs_fanout")
+    );
+    assert_eq!(
+        last.get("schema_name").and_then(Value::as_str),
+        Some("fanout.schema.json")
+    );
+}
+
+#[test]
+fn fanout_llm_materializes_dependent_tasks() {
+    let repo = TempRepo::new("cxrs-fanout-llm");
+    mock_planner(&repo, PLAN);
+
+    let out = repo.run(&["task", "fanout", "add a cache", "--llm"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("parent: task_001"));
+
+    let raw = fs::read_to_string(repo.root.join(".codex/tasks.json")).expect("tasks");
+    let tasks: Vec<Value> = serde_json::from_str(&raw).expect("tasks json");
+    assert_eq!(tasks.len(), 4);
+    let deps = |i: usize| -> Vec<&str> {
+        tasks[i]["depends_on"]
+            .as_array()
+            .expect("deps")
+            .iter()
+            .filter_map(Value::as_str)
+            .collect()
+    };
+    assert_eq!(deps(1), vec!["task_001"]);
+    assert_eq!(deps(2), vec!["task_001", "task_002"]);
+    assert_eq!(deps(3), vec!["task_001", "task_003"]);
+    assert_eq!(tasks[2]["estimate"], Value::from("4h"));
+    assert_eq!(tasks[2]["resource_keys"][0], Value::from("repo:write"));
+    assert!(tasks[0].get("estimate").is_none());
+}
+
+#[test]
+fn fanout_llm_rejects_bad_roles_forward_deps() {
+    let repo = TempRepo::new("cxrs-fanout-llm");
+    mock_planner(
+        &repo,
+        r#"{"subtasks":[{"role":"wizard","objective":"x","depends_on":[],"estimate":"1h"}]}"#,
+    );
+    let out = repo.run(&["fanout", "--llm", "do", "it"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("subtask 1: unknown role 'wizard'"));
+
+    mock_planner(
+        &repo,
+        r#"{"subtasks":[{"role":"tester","objective":"x","depends_on":[2],"estimate":"1h"},{"role":"doc","objective":"y","depends_on":[],"estimate":"1h"}]}"#,
+    );
+    let out = repo.run(&["task", "fanout", "do it", "--llm"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("must reference an earlier subtask"));
+    assert!(!repo.root.join(".codex/tasks.json").exists());
+
+    let out = repo.run(&["fanout", "--llm", "--role", "doc", "x"]);
+    assert_eq!(out.status.code(), Some(2));
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+fn mock_fixer(repo: &TempRepo, text: &str) -> std::path::PathBuf {
+    let prompt_file = repo.root.join("prompt.txt");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat > {}
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"{text}"}}}}'
+"#,
+        prompt_file.display()
+    ));
+    prompt_file
+}
+
+#[test]
+fn fix_json_prints_object_and_keeps_status() {
+    let repo = TempRepo::new("cxrs-fix-json");
+    let prompt_file = mock_fixer(
+        &repo,
+        r#"{\"diagnosis\":\"missing file\",\"probable_causes\":[\"typo in path\"],\"next_commands\":[\"ls\"],\"config_files_to_check\":[]}"#,
+    );
+
+    let out = repo.run(&["fix", "--json", "sh", "-c", "echo no such file >&2; exit 3"]);
+    assert_eq!(out.status.code(), Some(3), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("fix json");
+    assert_eq!(v["diagnosis"], "missing file");
+    assert_eq!(v["next_commands"][0], "ls");
+
+    let prompt = fs::read_to_string(&prompt_file).expect("prompt");
+    assert!(prompt.contains("no such file"), "{prompt}");
+    assert!(prompt.contains("config_files_to_check"), "{prompt}");
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let row = rows.last().expect("run row");
+    assert_eq!(row["tool"], "cxrs_fix");
+    assert_eq!(row["schema_name"], "fix.schema.json");
+    assert_eq!(row["schema_valid"], true);
+}
+
+#[test]
+fn fix_json_fails_on_schema_violations() {
+    let repo = TempRepo::new("cxrs-fix-json-invalid");
+    mock_fixer(&repo, r#"{\"diagnosis\":\"missing file\"}"#);
+
+    let out = repo.run(&["fix", "--json", "true"]);
+    assert_eq!(out.status.code(), Some(4));
+    assert!(
+        stderr_str(&out).contains("schema validation failed"),
+        "{}",
+        stderr_str(&out)
+    );
+
+    let out = repo.run(&["fix", "true"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("missing file"));
+    let rows = parse_jsonl(&repo.runs_log());
+    assert_eq!(rows.last().expect("run row")["tool"], "cxfix");
+
+    assert_eq!(repo.run(&["fix", "--json"]).status.code(), Some(2));
+}
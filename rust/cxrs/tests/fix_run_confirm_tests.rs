@@ -0,0 +1,152 @@
+mod common;
+
+use common::{TempRepo, mock_codex_agent_text, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+
+#[test]
+fn dry_run_previews_and_logs_decisions() {
+    let repo = TempRepo::new("cxrs-fixconfirm");
+    let marker = repo.root.join("ran.txt");
+    let fix_json = format!(
+        r#"{{"analysis":"try these","commands":["touch {}","rm -rf /"]}}"#,
+        marker.display()
+    );
+    repo.write_mock_codex(&mock_codex_agent_text(&fix_json));
+
+    let out = repo.run_with_env(
+        &["fix-run", "--dry-run", "echo", "hi"],
+        &[("CXFIX_RUN", "1")],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let stdout = stdout_str(&out);
+    assert!(stdout.contains("[dry-run] would_execute (safe): touch"));
+    assert!(stdout.contains("[dry-run] would_block (dangerous rule=builtin.rm_rf): rm -rf /"));
+    assert!(!marker.exists(), "dry-run must not execute commands");
+
+    let runs = parse_jsonl(&repo.runs_log());
+    let decisions = runs
+        .last()
+        .and_then(|r| r.get("fix_decisions"))
+        .and_then(Value::as_array)
+        .cloned()
+        .expect("fix_decisions array");
+    assert_eq!(decisions.len(), 2);
+    assert_eq!(decisions[0]["decision"], "would_execute");
+    assert_eq!(decisions[1]["decision"], "would_block");
+    assert_eq!(decisions[1]["rule"], "builtin.rm_rf");
+}
+
+#[test]
+fn confirm_requires_interactive_terminal() {
+    let repo = TempRepo::new("cxrs-fixconfirm");
+    repo.write_mock_codex(&mock_codex_agent_text(
+        r#"{"analysis":"x","commands":["true"]}"#,
+    ));
+    let out = repo.run(&["fix-run", "--confirm", "echo", "hi"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("--confirm requires an interactive terminal"));
+
+    let out = repo.run(&["fix-run", "--confirm", "--dry-run", "echo", "hi"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn executed_remediations_show_in_trace() {
+    let repo = TempRepo::new("cxrs-fixconfirm");
+    repo.write_mock_codex(&mock_codex_agent_text(
+        r#"{"analysis":"x","commands":["true","exit 3","rm -rf /"]}"#,
+    ));
+    let out = repo.run_with_env(&["fix-run", "echo", "hi"], &[("CXFIX_RUN", "1")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+
+    let out = repo.run(&["trace", "--remediations", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["contract_version"], "remediations.v1");
+    let rows = v["remediations"].as_array().expect("remediations");
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["decision"], "executed");
+    assert_eq!(rows[0]["exit_code"], 0);
+    assert!(rows[0]["duration_ms"].is_u64());
+    assert_eq!(rows[1]["exit_code"], 3);
+    assert_eq!(rows[2]["decision"], "blocked");
+    assert!(rows[2]["duration_ms"].is_null());
+
+    let out = repo.run(&["trace", "--remediations"]);
+    let stdout = stdout_str(&out);
+    assert!(stdout.contains("- executed (safe) exit=3 duration_ms="));
+    assert!(
+        stdout.contains("- blocked (dangerous rule=builtin.rm_rf) exit=- duration_ms=-: rm -rf /")
+    );
+}
+
+#[test]
+fn next_run_labels_suggestions_off_tty() {
+    let repo = TempRepo::new("cxrs-fixconfirm");
+    let marker = repo.root.join("ran.txt");
+    let next_json = format!(
+        r#"{{"commands":["touch {}","rm -rf /"]}}"#,
+        marker.display()
+    );
+    repo.write_mock_codex(&mock_codex_agent_text(&next_json));
+
+    let out = repo.run(&["next", "--run", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let stdout = stdout_str(&out);
+    assert!(stdout.contains(" 1) [safe] touch"));
+    assert!(stdout.contains(" 2) [dangerous rule=builtin.rm_rf] rm -rf /"));
+    assert!(stdout.contains("selection requires an interactive terminal"));
+    assert!(
+        !marker.exists(),
+        "next --run must not execute without a TTY"
+    );
+    let runs = parse_jsonl(&repo.runs_log());
+    assert!(runs.iter().all(|r| r["tool"] != "cxnext_exec"));
+
+    let out = repo.run(&["next", "--unsafe", "echo", "hi"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn sandboxed_fix_run_mounts_repo_read_only() {
+    let repo = TempRepo::new("cxrs-fixconfirm");
+    repo.write_mock_codex(&mock_codex_agent_text(
+        r#"{"analysis":"x","commands":["true"]}"#,
+    ));
+    let args_file = repo.root.join("nsjail-args.txt");
+    repo.write_mock(
+        "nsjail",
+        &format!(
+            "#!/usr/bin/env bash\nprintf '%s\\n' \"$@\" > {:?}\nwhile [ \"$1\" != \"--\" ]; do shift; done\nshift\nexec \"$@\"\n",
+            args_file.display().to_string()
+        ),
+    );
+    let out = repo.run_with_env(
+        &["fix-run", "echo", "hi"],
+        &[("CXFIX_RUN", "1"), ("CX_SANDBOX", "nsjail")],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("-> [sandbox nsjail] true"));
+    let args = std::fs::read_to_string(&args_file).expect("nsjail args");
+    assert!(args.contains("-R\n/\n"), "{args}");
+    assert!(
+        args.contains(&format!("--cwd\n{}\n", repo.root.display())),
+        "{args}"
+    );
+
+    let runs = parse_jsonl(&repo.runs_log());
+    let decision = &runs.last().expect("run row")["fix_decisions"][0];
+    assert_eq!(decision["decision"], "executed");
+    assert_eq!(decision["sandbox"], "nsjail");
+    assert_eq!(decision["exit_code"], 0);
+
+    let out = repo.run_with_env(
+        &["fix-run", "echo", "hi"],
+        &[("CXFIX_RUN", "1"), ("CX_SANDBOX", "vm")],
+    );
+    assert!(stderr_str(&out).contains("unknown CX_SANDBOX=vm"));
+    let runs = parse_jsonl(&repo.runs_log());
+    let decision = &runs.last().expect("run row")["fix_decisions"][0];
+    assert_eq!(decision["decision"], "sandbox_unavailable");
+    assert!(decision["sandbox"].is_null());
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+fn write_aged(path: &Path, bytes: usize, days: u64) {
+    fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+    fs::write(path, vec![b'x'; bytes]).expect("write");
+    let when = SystemTime::now() - Duration::from_secs(days * 86_400);
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|f| f.set_modified(when))
+        .expect("mtime");
+}
+
+fn seed_quarantine(repo: &TempRepo, id: &str, resolved_ts: Option<&str>) {
+    fs::create_dir_all(repo.quarantine_dir()).expect("mkdir quarantine");
+    let rec = json!({
+        "id": id,
+        "ts": "2020-01-01T00:00:00Z",
+        "tool": "cxcommitjson",
+        "reason": "invalid_json",
+        "resolved_ts": resolved_ts,
+    });
+    fs::write(repo.quarantine_file(id), rec.to_string()).expect("write quarantine");
+}
+
+fn removed(v: &Value, store: &str) -> Vec<String> {
+    let stores = v["stores"].as_array().expect("stores");
+    let s = stores.iter().find(|s| s["store"] == store).expect("store");
+    s["removed"]
+        .as_array()
+        .expect("removed")
+        .iter()
+        .map(|r| {
+            let path = r["path"].as_str().unwrap_or("");
+            Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[test]
+fn gc_lists_then_prunes_by_age_size() {
+    let repo = TempRepo::new("cxrs-gc");
+    let logs = repo.runs_log().parent().expect("log dir").to_path_buf();
+    write_aged(&repo.runs_log(), 10, 90);
+    write_aged(&logs.join("runs.jsonl.bak.1"), 10, 90);
+    write_aged(&logs.join("runs.jsonl.bak.2"), 10, 1);
+    let cache = repo.home.join(".codex").join("cache");
+    write_aged(&cache.join("a.bin"), 600, 3);
+    write_aged(&cache.join("nested").join("b.bin"), 600, 1);
+    seed_quarantine(&repo, "q_old", Some("2020-01-02T00:00:00Z"));
+    seed_quarantine(&repo, "q_open", None);
+    let sessions = repo.root.join(".codex").join("sessions");
+    write_aged(&sessions.join("stale.jsonl"), 10, 90);
+    write_aged(&sessions.join("default.jsonl"), 10, 1);
+    let envs = [("CX_GC_CACHE_MAX_BYTES", "1000")];
+
+    let out = repo.run_with_env(&["gc", "--dry-run", "--json"], &envs);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["contract_version"], "gc.v1");
+    assert_eq!(v["dry_run"], true);
+    assert_eq!(removed(&v, "logs"), ["runs.jsonl.bak.1"]);
+    assert_eq!(removed(&v, "cache"), ["a.bin"]);
+    assert_eq!(removed(&v, "quarantine"), ["q_old.json"]);
+    assert_eq!(removed(&v, "sessions"), ["stale.jsonl"]);
+    assert!(
+        logs.join("runs.jsonl.bak.1").exists(),
+        "dry run deletes nothing"
+    );
+
+    let out = repo.run_with_env(&["gc"], &envs);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("cache: 2 file(s)"), "{text}");
+    assert!(text.contains("[size]"), "{text}");
+    assert!(!logs.join("runs.jsonl.bak.1").exists());
+    assert!(logs.join("runs.jsonl.bak.2").exists());
+    assert!(repo.runs_log().exists(), "live log is never pruned");
+    assert!(!cache.join("a.bin").exists());
+    assert!(cache.join("nested").join("b.bin").exists());
+    assert!(!repo.quarantine_file("q_old").exists());
+    assert!(repo.quarantine_file("q_open").exists(), "open records stay");
+    assert!(!sessions.join("stale.jsonl").exists());
+    assert!(sessions.join("default.jsonl").exists());
+
+    let out = repo.run(&["gc", "--store", "nope"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn auto_gc_runs_once_per_interval() {
+    let repo = TempRepo::new("cxrs-gc");
+    repo.write_mock_codex(
+        "#!/usr/bin/env bash\ncat >/dev/null\nprintf '%s\\n' '{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"text\":\"ok\"}}'\n",
+    );
+    let logs = repo.runs_log().parent().expect("log dir").to_path_buf();
+    let old = logs.join("runs.jsonl.bak.1");
+    write_aged(&old, 10, 90);
+
+    let out = repo.run_with_env(&["cxo", "echo", "hi"], &[("CX_GC_AUTO", "0")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(old.exists(), "CX_GC_AUTO=0 disables auto-gc");
+
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(!old.exists(), "auto-gc prunes after a logged run");
+
+    write_aged(&old, 10, 90);
+    assert!(repo.run(&["cxo", "echo", "hi"]).status.success());
+    assert!(old.exists(), "a fresh stamp skips the scan");
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+
+const MOCK_CODEX: &str = r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+"#;
+
+#[test]
+fn global_flags_override_settings() {
+    let repo = TempRepo::new("cxrs-global-flags");
+    let out = repo.run_with_env(
+        &["--backend", "ollama", "config", "show", "--origin"],
+        &[("CX_LLM_BACKEND", "codex")],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("llm.backend = ollama  # env:CX_LLM_BACKEND"));
+
+    repo.write_mock_codex(MOCK_CODEX);
+    let out = repo.run(&["--mode=deterministic", "cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let rows = parse_jsonl(&repo.runs_log());
+    assert_eq!(
+        rows.last()
+            .and_then(|r| r.get("execution_mode"))
+            .and_then(Value::as_str),
+        Some("deterministic")
+    );
+}
+
+#[test]
+fn global_flag_errors_exit_with_usage_code() {
+    let repo = TempRepo::new("cxrs-global-flags");
+    let out = repo.run(&["--backend", "gpt", "version"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("--backend: llm.backend: expected one of codex|ollama|mock"));
+
+    let out = repo.run(&["--timeout"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("--timeout requires SECS"));
+
+    let out = repo.run(&["--bogus", "version"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("unknown command '--bogus'"));
+
+    let out = repo.run(&["cx", "cxversion"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+}
+
+#[test]
+fn codex_args_are_appended_allowlisted_and_logged() {
+    let repo = TempRepo::new("cxrs-codex-args");
+    let argv_file = repo.root.join("codex-argv.txt");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' "$*" > '{}'
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"ok"}}}}'
+"#,
+        argv_file.display()
+    ));
+    let out = repo.run_with_env(
+        &["--codex-arg", "-m o4-mini", "cxo", "echo", "hi"],
+        &[("CX_CODEX_ARGS", "--profile work -m gpt-5")],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let argv = std::fs::read_to_string(&argv_file).expect("codex argv");
+    assert_eq!(argv.trim(), "exec --profile work --model o4-mini --json -");
+    let rows = parse_jsonl(&repo.runs_log());
+    let last = rows.last().expect("run row");
+    assert_eq!(last["codex_profile"], "work");
+    assert_eq!(last["llm_model"], "o4-mini");
+    assert_eq!(
+        last["codex_args"],
+        serde_json::json!(["--profile", "work", "--model", "o4-mini"])
+    );
+
+    let out = repo.run(&[
+        "--codex-arg=--dangerously-bypass-approvals-and-sandbox",
+        "cxo",
+        "ls",
+    ]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("is not an allowed codex argument"));
+
+    let out = repo.run_with_env(&["cxo", "echo", "hi"], &[("CX_CODEX_ARGS", "--cd /")]);
+    assert_eq!(out.status.code(), Some(5), "stderr={}", stderr_str(&out));
+    assert!(stderr_str(&out).contains("CX_CODEX_ARGS: '--cd' is not an allowed codex argument"));
+}
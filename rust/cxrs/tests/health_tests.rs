@@ -0,0 +1,78 @@
+mod common;
+
+use common::*;
+use serde_json::Value;
+use std::fs;
+
+fn check<'a>(payload: &'a Value, name: &str) -> &'a Value {
+    payload["checks"]
+        .as_array()
+        .and_then(|c| c.iter().find(|c| c["name"] == name))
+        .unwrap_or_else(|| panic!("missing check {name}: {payload}"))
+}
+
+#[test]
+fn health_no_llm_checks_local_setup_only() {
+    let repo = TempRepo::new("cxrs-health");
+    let out = repo.run_with_env(
+        &["health", "--no-llm", "--json"],
+        &[("CX_LLM_BACKEND", "codex")],
+    );
+    let payload: Value = serde_json::from_str(&stdout_str(&out)).expect("health json");
+    assert_eq!(payload["contract_version"], "health.v1");
+    assert_eq!(payload["no_llm"], true);
+    assert_eq!(check(&payload, "schema_registry")["status"], "pass");
+    assert_eq!(check(&payload, "log_contract")["status"], "warn");
+    for name in ["llm_version", "llm_json", "cxo"] {
+        assert_eq!(check(&payload, name)["status"], "skip", "{name}");
+    }
+    assert_eq!(
+        out.status.code().map(i64::from),
+        payload["exit_code"].as_i64()
+    );
+
+    fs::remove_file(repo.root.join(".codex/schemas/next.schema.json")).unwrap();
+    let out = repo.run(&["health", "--no-llm", "--json"]);
+    let payload: Value = serde_json::from_str(&stdout_str(&out)).expect("health json");
+    assert_eq!(check(&payload, "schema_registry")["status"], "fail");
+    assert_eq!(
+        check(&payload, "schema_registry")["class"],
+        "schema_failure"
+    );
+    assert_eq!(payload["status"], "fail");
+
+    let out = repo.run(&["health", "--bogus"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn health_runs_llm_probes_with_mock_backend() {
+    let repo = TempRepo::new("cxrs-health");
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+if [ "${1:-}" = "--version" ]; then echo "codex 9.9.9"; exit 0; fi
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"clean tree"}}'
+"#,
+    );
+    let out = repo.run(&["health"]);
+    let text = stdout_str(&out);
+    assert!(text.contains("== cxrs health =="), "{text}");
+    assert!(text.contains("codex 9.9.9"), "{text}");
+    assert!(text.contains("PASS  llm_json"), "{text}");
+    assert!(text.contains("PASS  cxo"), "{text}");
+
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+if [ "${1:-}" = "--version" ]; then echo "codex 9.9.9"; exit 0; fi
+exit 3
+"#,
+    );
+    let out = repo.run(&["health", "--json"]);
+    let payload: Value = serde_json::from_str(&stdout_str(&out)).expect("health json");
+    assert_eq!(check(&payload, "llm_json")["status"], "fail");
+    assert_eq!(check(&payload, "llm_json")["class"], "backend_failure");
+    if check(&payload, "binaries")["status"] != "fail" {
+        assert_eq!(out.status.code(), Some(5));
+    }
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+
+#[test]
+fn command_help_matches_flag_and_compat_forms() {
+    let repo = TempRepo::new("cxrs-help");
+    let out = repo.run(&["help", "diffsum"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.starts_with("cxrs diffsum - Summarize unstaged diff"));
+    assert!(text.contains("  cxrs diffsum [--focus GLOB]...\n"));
+    assert!(text.contains("Environment:\n  CX_DIFF_FOCUS\n"));
+    assert!(text.contains("State keys:\n  preferences.llm_backend\n"));
+
+    for args in [
+        vec!["diffsum", "--help"],
+        vec!["diffsum", "-h"],
+        vec!["cx", "cxdiffsum", "--help"],
+    ] {
+        let out = repo.run(&args);
+        assert!(out.status.success(), "{args:?}");
+        assert_eq!(stdout_str(&out), text, "{args:?}");
+    }
+
+    let out = repo.run(&["help", "quarantine"]);
+    let text = stdout_str(&out);
+    assert!(text.starts_with("cxrs quarantine\n"));
+    assert!(
+        text.contains("  cxrs quarantine list [N]\n      Show recent quarantine entries (default ")
+    );
+
+    let out = repo.run(&["help", "task", "run"]);
+    assert!(stdout_str(&out).contains("  cxrs task run <id> "));
+    let out = repo.run(&["task", "--help"]);
+    assert!(stdout_str(&out).starts_with("cx help task"));
+}
+
+#[test]
+fn help_all_emits_markdown_reference() {
+    let repo = TempRepo::new("cxrs-help");
+    let out = repo.run(&["help", "--all", "--format", "md"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let md = stdout_str(&out);
+    assert!(md.starts_with("# cxrs command reference\n"));
+    assert!(md.contains("## worklog\n"));
+    assert!(
+        md.contains("| `--since WHEN` | Runs at or after WHEN (2h, 7d, YYYY-MM-DD or RFC 3339) |")
+    );
+    assert!(md.contains("### Examples\n\n```sh\ncxrs worklog 100\n"));
+    assert!(md.contains("## task run-all\n"));
+    assert_eq!(md.matches("## diffsum\n").count(), 1);
+
+    let out = repo.run(&["help", "--all"]);
+    assert!(stdout_str(&out).contains("cxrs logs\n\nUsage:\n"));
+}
+
+#[test]
+fn help_rejects_unknown_topics_and_formats() {
+    let repo = TempRepo::new("cxrs-help");
+    let out = repo.run(&["help", "nope"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("cxrs help: unknown command 'nope'"));
+
+    let out = repo.run(&["help", "--all", "--format", "pdf"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("Usage: help"));
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{TempRepo, git, stderr_str, stdout_str};
+use std::fs;
+use std::process::Command;
+
+fn git_push(repo: &TempRepo, envs: &[(&str, &str)]) -> std::process::Output {
+    let mut cmd = Command::new("git");
+    cmd.args(["push", "-q", "origin", "HEAD:refs/heads/main"])
+        .current_dir(&repo.root)
+        .env("HOME", &repo.home)
+        .env_remove("CX_HOME");
+    for (k, v) in envs {
+        cmd.env(k, v);
+    }
+    cmd.output().expect("git push")
+}
+
+/// A repo with one commit and a bare `origin` to push to.
+fn pushable_repo() -> TempRepo {
+    let repo = TempRepo::new("cxrs-hook");
+    let remote = repo.home.join("remote.git");
+    git(&repo, &["init", "-q", "--bare", &remote.to_string_lossy()]);
+    git(
+        &repo,
+        &["remote", "add", "origin", &remote.to_string_lossy()],
+    );
+    fs::write(repo.root.join("a.rs"), "fn main() {}\n").expect("write a.rs");
+    git(&repo, &["add", "a.rs"]);
+    git(
+        &repo,
+        &[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "-qm",
+            "base",
+        ],
+    );
+    repo
+}
+
+#[test]
+fn pre_push_hook_blocks_on_schema_failures() {
+    let repo = pushable_repo();
+    let out = repo.run(&["hook", "install", "--type", "pre-push", "--logs"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let hook = repo.root.join(".git/hooks/pre-push");
+    let script = fs::read_to_string(&hook).expect("hook script");
+    assert!(script.contains("hook run pre-push --logs"), "{script}");
+    assert!(git_push(&repo, &[]).status.success(), "clean repo pushes");
+
+    repo.write_mock_codex("#!/usr/bin/env bash\ncat >/dev/null\necho 'not json'\n");
+    let out = repo.run(&["explain", "a.rs"]);
+    assert!(!out.status.success());
+    let out = repo.run(&["hook", "run", "pre-push"]);
+    assert_eq!(out.status.code(), Some(1), "stdout={}", stdout_str(&out));
+    assert!(stdout_str(&out).contains("schema_failure_rate: 100.0%"));
+    assert!(
+        stderr_str(&out).contains("exceeds 20% (hooks.max_schema_fail_pct)"),
+        "{}",
+        stderr_str(&out)
+    );
+    let out = git_push(&repo, &[]);
+    assert!(!out.status.success(), "push should be blocked");
+    assert!(
+        stderr_str(&out).contains("push blocked"),
+        "{}",
+        stderr_str(&out)
+    );
+    let out = git_push(&repo, &[("CX_HOOK_MAX_SCHEMA_FAIL_PCT", "0")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+}
+
+#[test]
+fn install_keeps_foreign_hooks_unless_forced() {
+    let repo = TempRepo::new("cxrs-hook");
+    let hook = repo.root.join(".git/hooks/pre-push");
+    fs::create_dir_all(hook.parent().expect("hooks dir")).expect("mkdir hooks");
+    fs::write(&hook, "#!/bin/sh\nexit 0\n").expect("write foreign hook");
+
+    let out = repo.run(&["hook", "install", "--type", "pre-push"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("--force"));
+    assert!(
+        repo.run(&["hook", "uninstall", "--type", "pre-push"])
+            .status
+            .code()
+            == Some(1)
+    );
+    assert!(
+        repo.run(&["hook", "install", "--type", "pre-push", "--force"])
+            .status
+            .success()
+    );
+    assert!(
+        repo.run(&["hook", "install", "--type", "pre-push"])
+            .status
+            .success(),
+        "reinstalling a managed hook needs no --force"
+    );
+    assert!(
+        repo.run(&["hook", "uninstall", "--type", "pre-push"])
+            .status
+            .success()
+    );
+    assert!(!hook.exists());
+    assert_eq!(
+        repo.run(&["hook", "install", "--type", "pre-commit"])
+            .status
+            .code(),
+        Some(2)
+    );
+}
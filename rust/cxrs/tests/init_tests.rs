@@ -0,0 +1,44 @@
+mod common;
+
+use common::{TempRepo, read_json, stderr_str, stdout_str};
+use std::fs;
+
+#[test]
+fn init_scaffolds_codex_and_is_rerunnable() {
+    let repo = TempRepo::new("cxrs-init");
+    fs::remove_dir_all(repo.root.join(".codex")).expect("drop copied schemas");
+    fs::write(repo.root.join(".gitignore"), "target/").expect("gitignore");
+
+    let out = repo.run(&["init", "--gitignore"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let codex = repo.root.join(".codex");
+    for sub in ["cxlogs", "quarantine", "schemas", "prompts"] {
+        assert!(codex.join(sub).is_dir(), "missing .codex/{sub}");
+    }
+    let schemas = fs::read_dir(codex.join("schemas"))
+        .expect("schemas")
+        .count();
+    assert_eq!(schemas, 8);
+    assert!(read_json(&repo.state_file()).get("preferences").is_some());
+    assert!(codex.join("config.toml").is_file());
+    assert_eq!(
+        fs::read_to_string(repo.root.join(".gitignore")).expect("gitignore"),
+        "target/\n# cx: local run logs and quarantine; schemas are committed\n.codex/cxlogs/\n.codex/quarantine/\n"
+    );
+    assert!(stdout_str(&out).contains("next steps:"));
+
+    let out = repo.run(&["schema", "list"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+
+    fs::write(codex.join("schemas").join("next.schema.json"), "{}").expect("local edit");
+    let out = repo.run(&["init", "--gitignore"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("already initialized"));
+    assert_eq!(
+        fs::read_to_string(codex.join("schemas").join("next.schema.json")).expect("schema"),
+        "{}"
+    );
+
+    let out = repo.run(&["init", "--bogus"]);
+    assert_eq!(out.status.code(), Some(2));
+}
@@ -140,3 +140,112 @@ fn ollama_without_model_fails_noninteractive_clear() {
         "expected unset-model guidance in stderr; got: {err}"
     );
 }
+
+const OLLAMA_API_CURL: &str = r#"#!/usr/bin/env bash
+url="${@: -1}"
+body="$(cat 2>/dev/null || true)"
+printf '%s %s\n' "$url" "$body" >> "$CURL_LOG"
+case "$url" in
+  */api/tags)
+    echo '{"models":[{"name":"tiny:1b","size":1288490188,"details":{"family":"llama","parameter_size":"1.2B","quantization_level":"Q8_0"}}]}' ;;
+  */api/show)
+    echo '{"details":{"family":"llama","parameter_size":"1.2B","quantization_level":"Q8_0"},"model_info":{"llama.context_length":131072},"parameters":"num_ctx 256"}' ;;
+  */api/pull)
+    printf '%s\n' '{"status":"pulling manifest"}' '{"status":"downloading","total":2048,"completed":2048}' '{"status":"success"}' ;;
+esac
+"#;
+
+#[test]
+fn llm_models_pull_inspect_use_ollama_api() {
+    let repo = TempRepo::new("cxrs-llm-ollama-api");
+    repo.write_mock("curl", OLLAMA_API_CURL);
+    repo.write_mock("ollama", "#!/usr/bin/env bash\ncat >/dev/null\necho ok\n");
+    let log = repo.root.join("curl.log");
+    let log_s = log.to_str().expect("utf8");
+    let env = [
+        ("CURL_LOG", log_s),
+        ("OLLAMA_HOST", "127.0.0.1:9"),
+        ("CX_OLLAMA_MODEL", "tiny:1b"),
+    ];
+
+    let out = repo.run_with_env(&["llm", "models", "--json"], &env);
+    assert!(out.status.success(), "{}", stderr_str(&out));
+    let doc: Value = serde_json::from_str(&stdout_str(&out)).expect("models json");
+    assert_eq!(doc["host"], "http://127.0.0.1:9");
+    assert_eq!(doc["models"][0]["name"], "tiny:1b");
+    assert_eq!(doc["models"][0]["quantization"], "Q8_0");
+
+    let out = repo.run_with_env(&["llm", "pull", "tiny:1b"], &env);
+    assert!(out.status.success(), "{}", stderr_str(&out));
+    assert!(
+        stderr_str(&out).contains("downloading 100%"),
+        "{}",
+        stderr_str(&out)
+    );
+    assert!(stdout_str(&out).contains("context_window: 256 (num_ctx)"));
+    let calls = std::fs::read_to_string(&log).expect("curl log");
+    assert!(
+        calls.contains("http://127.0.0.1:9/api/pull {\"model\":\"tiny:1b\""),
+        "{calls}"
+    );
+
+    let out = repo.run_with_env(&["llm", "inspect", "--json"], &env);
+    let info: Value = serde_json::from_str(&stdout_str(&out)).expect("inspect json");
+    assert_eq!(info["context_length"], 131072);
+    assert_eq!(info["prompt_budget_tokens"], 192);
+
+    let out = repo.run_with_env(
+        &["cxo", "echo", "hi"],
+        &[("CX_LLM_BACKEND", "ollama"), env[0], env[1], env[2]],
+    );
+    assert!(out.status.success(), "{}", stderr_str(&out));
+    let rows = common::parse_jsonl(&repo.runs_log());
+    assert_eq!(rows.last().expect("row")["budget_tokens"], 192);
+}
+
+#[test]
+fn oversized_prompts_are_shrunk_or_refused() {
+    let repo = TempRepo::new("cxrs-context-guard");
+    let env = [
+        ("CX_LLM_BACKEND", "mock"),
+        ("CX_CONTEXT_WINDOWS", "mock=200"),
+    ];
+
+    let out = repo.run_with_env(&["cxo", "seq", "1", "3000"], &env);
+    assert_eq!(out.status.code(), Some(0), "stderr={}", stderr_str(&out));
+    assert!(
+        stderr_str(&out).contains("prompt exceeds model context ("),
+        "stderr={}",
+        stderr_str(&out)
+    );
+    let rows = common::parse_jsonl(&repo.runs_log());
+    let last = rows.last().expect("run row");
+    assert_eq!(last["context_window"], 200);
+    assert_eq!(last["context_guard"], "shrunk");
+    assert!(last["prompt_tokens_est"].as_u64().expect("estimate") <= 200);
+
+    let out = repo.run_with_env(
+        &["cxo", "seq", "1", "3000"],
+        &[
+            ("CX_LLM_BACKEND", "mock"),
+            ("CX_CONTEXT_WINDOWS", "mock=200"),
+            ("CX_CONTEXT_GUARD", "fail"),
+        ],
+    );
+    assert_eq!(out.status.code(), Some(5), "stderr={}", stderr_str(&out));
+    let err = stderr_str(&out);
+    assert!(err.contains("prompt exceeds model context ("), "{err}");
+    assert!(err.contains(" > 200 tokens)"), "{err}");
+    let rows = common::parse_jsonl(&repo.runs_log());
+    let last = rows.last().expect("blocked row");
+    assert_eq!(last["context_guard"], "blocked");
+    assert!(
+        last["schema_reason"]
+            .as_str()
+            .is_some_and(|r| r.starts_with("prompt exceeds model context")),
+        "{last}"
+    );
+
+    let out = repo.run_with_env(&["llm", "show"], &env);
+    assert!(stdout_str(&out).contains("context_window: 200 (config)"));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str};
+use serde_json::Value;
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+const SLOW_CODEX: &str = "#!/usr/bin/env bash\ncat >/dev/null\nexec sleep 5\n";
+
+fn last_run(repo: &TempRepo) -> Value {
+    parse_jsonl(&repo.runs_log())
+        .into_iter()
+        .last()
+        .expect("last run row")
+}
+
+#[test]
+fn llm_timeout_kills_backend_and_exits_124() {
+    let repo = TempRepo::new("cxrs-llm-timeout");
+    repo.write_mock_codex(SLOW_CODEX);
+
+    let started = Instant::now();
+    let out = repo.run(&["cxo", "--timeout", "1", "echo", "hi"]);
+    assert_eq!(out.status.code(), Some(124), "stderr={}", stderr_str(&out));
+    assert!(started.elapsed() < Duration::from_secs(4));
+    assert!(stderr_str(&out).contains("timed out after 1s"));
+    let row = last_run(&repo);
+    assert_eq!(row["schema_reason"], "timeout");
+    assert_eq!(row["timed_out"], true);
+    assert_eq!(row["timeout_secs"], 1);
+
+    let out = repo.run_with_env(
+        &["cxo", "echo", "hi"],
+        &[("CX_LLM_TIMEOUT_SECS", "1"), ("CX_TIMEOUT_LLM_SECS", "9")],
+    );
+    assert_eq!(out.status.code(), Some(124));
+    assert_eq!(last_run(&repo)["timeout_secs"], 1);
+
+    let out = repo.run(&["cxo", "--timeout", "soon", "echo", "hi"]);
+    assert_eq!(out.status.code(), Some(2));
+    let help = repo.run(&["help", "next"]);
+    assert!(
+        String::from_utf8_lossy(&help.stdout).contains("  --timeout SECS  LLM backend timeout")
+    );
+}
+
+#[test]
+fn sigint_kills_backend_and_logs_interrupt() {
+    let repo = TempRepo::new("cxrs-llm-timeout");
+    repo.write_mock_codex(SLOW_CODEX);
+    let path = format!(
+        "{}:{}",
+        repo.mock_bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let child = Command::new(env!("CARGO_BIN_EXE_cxrs"))
+        .args(["cxo", "echo", "hi"])
+        .current_dir(&repo.root)
+        .env("HOME", &repo.home)
+        .env("PATH", path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn cxo");
+    sleep(Duration::from_millis(500));
+    let killed = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .expect("kill");
+    assert!(killed.success());
+
+    let out = child.wait_with_output().expect("wait cxo");
+    assert_eq!(out.status.code(), Some(130), "stderr={}", stderr_str(&out));
+    assert!(stderr_str(&out).contains("interrupted"));
+    assert_eq!(last_run(&repo)["schema_reason"], "interrupted");
+}
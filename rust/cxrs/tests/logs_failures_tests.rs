@@ -0,0 +1,85 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+use std::fs;
+
+fn quarantine(repo: &TempRepo, id: &str, raw: &str) {
+    fs::create_dir_all(repo.quarantine_dir()).expect("mkdir quarantine");
+    let rec = json!({"id": id, "ts": "2026-03-01T00:00:00Z", "tool": "cxrs_next",
+                     "reason": "invalid JSON", "raw_response": raw});
+    fs::write(repo.quarantine_file(id), rec.to_string()).expect("write record");
+}
+
+fn fail_row(i: usize, tool: &str, reason: &str, qid: &str) -> Value {
+    json!({"execution_id": format!("f{i}"), "ts": format!("2026-03-0{i}T10:00:00Z"),
+           "tool": tool, "schema_enforced": true, "schema_valid": false,
+           "schema_ok": false, "schema_reason": reason, "quarantine_id": qid})
+}
+
+fn ok_row(i: usize) -> Value {
+    json!({"execution_id": format!("ok{i}"), "ts": format!("2026-03-0{i}T09:00:00Z"),
+           "tool": "cxrs_next", "schema_enforced": true, "schema_valid": true})
+}
+
+#[test]
+fn logs_failures_clusters_and_links_quarantine() {
+    let repo = TempRepo::new("cxrs-logs-failures");
+    quarantine(&repo, "q1", "```json\n{\"commands\":[]}\n```");
+    quarantine(&repo, "q2", "```json\n{\"commands\":[\"ls\"]}\n```");
+    quarantine(&repo, "q3", "```\n{}\n```");
+    quarantine(&repo, "q4", "{\"commands\": [\"ls\"");
+    let bad_json = "invalid JSON: expected value at line 1 column 1";
+    write_runs_log_rows(
+        &repo,
+        &[
+            fail_row(1, "cxrs_next", "invalid JSON: trailing characters", "q4"),
+            ok_row(2),
+            fail_row(3, "cxrs_next", bad_json, "q1"),
+            ok_row(4),
+            fail_row(5, "cxrs_next", bad_json, "q2"),
+            fail_row(6, "cxrs_next", bad_json, "q3"),
+        ],
+    );
+
+    let out = repo.run(&["logs", "failures", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["contract_version"], "logs-failures.v1");
+    assert_eq!(v["window"], 6);
+    assert_eq!(v["schema_failures"], 4);
+    let top = &v["clusters"][0];
+    assert_eq!(top["tool"], "cxrs_next");
+    assert_eq!(top["reason"], "invalid JSON");
+    assert_eq!(top["fingerprint"], "markdown fence wrapped JSON");
+    assert_eq!(top["count"], 3);
+    assert_eq!(top["trend"], "rising");
+    assert_eq!(top["quarantine_ids"], json!(["q3", "q2", "q1"]));
+    assert_eq!(top["last_ts"], "2026-03-06T10:00:00Z");
+    assert_eq!(v["clusters"][1]["fingerprint"], "truncated");
+    assert_eq!(v["clusters"][1]["trend"], "falling");
+
+    let out = repo.run(&["logs", "failures", "3"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("window: 3 run(s)"), "{text}");
+    assert!(
+        text.contains("- 2x cxrs_next | invalid JSON | markdown fence wrapped JSON | trend:"),
+        "{text}"
+    );
+    assert!(text.contains("  quarantine: q3, q2"), "{text}");
+    assert!(text.contains("fix_first: cxrs_next (markdown fence wrapped JSON)"));
+    assert!(text.contains("next: cxrs quarantine show q3"));
+}
+
+#[test]
+fn logs_failures_handles_empty_logs_bad_args() {
+    let repo = TempRepo::new("cxrs-logs-failures");
+    let out = repo.run(&["logs", "failures"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("clusters: 0"));
+    assert_eq!(
+        repo.run(&["logs", "failures", "nope"]).status.code(),
+        Some(2)
+    );
+}
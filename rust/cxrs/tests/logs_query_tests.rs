@@ -0,0 +1,104 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+
+fn seed(repo: &TempRepo) {
+    write_runs_log_rows(
+        repo,
+        &[
+            json!({"execution_id": "a", "ts": "2026-03-01T10:00:00Z", "tool": "cxdiffsum",
+                   "duration_ms": 15000, "schema_ok": false, "llm_backend": "codex"}),
+            json!({"execution_id": "b", "ts": "2026-03-01T11:00:00Z", "tool": "cxdiffsum",
+                   "duration_ms": 800, "schema_ok": true, "llm_backend": "codex"}),
+            json!({"execution_id": "c", "timestamp": "2026-03-02T09:00:00Z", "tool": "cxo",
+                   "duration_ms": 22000, "llm_backend": "ollama"}),
+            json!({"execution_id": "d", "ts": "2026-03-03T09:00:00Z", "tool": "cxdiffsum",
+                   "duration_ms": 31000, "schema_ok": false, "llm_backend": "ollama"}),
+        ],
+    );
+}
+
+fn query_json(repo: &TempRepo, args: &[&str]) -> Value {
+    let mut argv = vec!["logs", "query"];
+    argv.extend_from_slice(args);
+    argv.push("--json");
+    let out = repo.run(&argv);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    serde_json::from_str(&stdout_str(&out)).expect("json")
+}
+
+fn ids(v: &Value) -> Vec<String> {
+    v["rows"]
+        .as_array()
+        .expect("rows")
+        .iter()
+        .map(|r| r["execution_id"].as_str().unwrap_or("").to_string())
+        .collect()
+}
+
+#[test]
+fn logs_query_filters_rows_and_projects_fields() {
+    let repo = TempRepo::new("cxrs-logs-query");
+    seed(&repo);
+
+    let v = query_json(
+        &repo,
+        &["tool=cxdiffsum AND duration_ms>10000 AND schema_ok=false"],
+    );
+    assert_eq!(v["contract_version"], "logs-query.v1");
+    assert_eq!(v["scanned"], 4);
+    assert_eq!(v["matched"], 2);
+    assert_eq!(ids(&v), vec!["a", "d"]);
+
+    let v = query_json(
+        &repo,
+        &[
+            "llm_backend=ollama OR ts<2026-03-01T10:30:00Z",
+            "--last",
+            "2",
+            "--fields",
+            "execution_id,ts",
+        ],
+    );
+    assert_eq!(v["matched"], 3);
+    assert_eq!(
+        v["rows"],
+        json!([
+            {"execution_id": "c", "ts": "2026-03-02T09:00:00Z"},
+            {"execution_id": "d", "ts": "2026-03-03T09:00:00Z"}
+        ])
+    );
+
+    let out = repo.run(&["logs", "query", "NOT schema_ok=true AND duration_ms>=20000"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    let mut lines = text.lines();
+    let header = lines.next().expect("header");
+    assert!(header.starts_with("ts"), "{text}");
+    assert!(header.ends_with("schema_ok  duration_ms"), "{text}");
+    assert!(text.contains("2026-03-02T09:00:00Z  c "), "{text}");
+    assert!(
+        text.ends_with("2 of 4 row(s) matched; showing 2\n"),
+        "{text}"
+    );
+}
+
+#[test]
+fn logs_query_rejects_bad_filters_and_fields() {
+    let repo = TempRepo::new("cxrs-logs-query");
+    seed(&repo);
+    let out = repo.run(&["logs", "query", "duration>10"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(
+        stderr_str(&out).contains("unknown field 'duration' (did you mean"),
+        "{}",
+        stderr_str(&out)
+    );
+    let out = repo.run(&["logs", "query", "tool=cxo AND"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("expected a field name"));
+    assert_eq!(repo.run(&["logs", "query"]).status.code(), Some(2));
+    let out = repo.run(&["logs", "query", "tool=cxo", "--fields", "nope"]);
+    assert_eq!(out.status.code(), Some(2));
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+fn mock_summarizer(repo: &TempRepo, fail_chunks: bool) -> std::path::PathBuf {
+    let prompts = repo.root.join("prompts.log");
+    let fail = if fail_chunks { "exit 3" } else { ":" };
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+prompt="$(cat)"
+printf '%s\n===\n' "$prompt" >> "{}"
+if printf '%s' "$prompt" | grep -q '^--- chunk '; then
+  {fail}
+  printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"- lines summarized"}}}}'
+else
+  printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"final answer"}}}}'
+fi
+printf '%s\n' '{{"type":"turn.completed","usage":{{"input_tokens":20,"cached_input_tokens":0,"output_tokens":4}}}}'
+"#,
+        prompts.display()
+    ));
+    prompts
+}
+
+#[test]
+fn mapreduce_summarizes_chunks_and_logs_child_rows() {
+    let repo = TempRepo::new("cxrs-mapreduce");
+    let prompts = mock_summarizer(&repo, false);
+    let envs = [
+        ("CX_CHUNK_STRATEGY", "mapreduce"),
+        ("CX_CONTEXT_BUDGET_CHARS", "400"),
+        ("CX_CHUNK_MAX", "3"),
+    ];
+    let out = repo.run_with_env(&["cxo", "seq", "1", "400"], &envs);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("final answer"));
+
+    let log = fs::read_to_string(&prompts).expect("prompts");
+    assert_eq!(log.matches("--- chunk ").count(), 3, "{log}");
+    assert!(log.contains("[cx] mapreduce: 3 chunk summaries of"));
+    assert!(log.contains("## chunk 3/3\n- lines summarized"));
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let parent = rows
+        .iter()
+        .find(|r| r.get("tool").and_then(Value::as_str) == Some("cxo"))
+        .expect("parent row");
+    assert_eq!(
+        parent.get("chunk_strategy").and_then(Value::as_str),
+        Some("mapreduce")
+    );
+    assert_eq!(parent.get("chunk_count").and_then(Value::as_u64), Some(3));
+    let parent_id = parent.get("execution_id").and_then(Value::as_str);
+    let children: Vec<&Value> = rows
+        .iter()
+        .filter(|r| r.get("parent_execution_id").and_then(Value::as_str) == parent_id)
+        .collect();
+    assert_eq!(children.len(), 3);
+    assert_eq!(
+        children[2].get("chunk_index").and_then(Value::as_u64),
+        Some(3)
+    );
+    assert_eq!(
+        children[0].get("input_tokens").and_then(Value::as_u64),
+        Some(20)
+    );
+    assert_eq!(
+        children[0].get("tool").and_then(Value::as_str),
+        Some("cxmapreduce_chunk")
+    );
+}
+
+#[test]
+fn mapreduce_failure_falls_back_to_clipping() {
+    let repo = TempRepo::new("cxrs-mapreduce");
+    let prompts = mock_summarizer(&repo, true);
+    let envs = [
+        ("CX_CHUNK_STRATEGY", "mapreduce"),
+        ("CX_CONTEXT_BUDGET_CHARS", "400"),
+    ];
+    let out = repo.run_with_env(&["cxo", "seq", "1", "400"], &envs);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stderr_str(&out).contains("falling back to clipping"));
+    let log = fs::read_to_string(&prompts).expect("prompts");
+    assert!(log.contains("[cx] output clipped"));
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let parent = rows
+        .iter()
+        .find(|r| r.get("tool").and_then(Value::as_str) == Some("cxo"))
+        .expect("parent row");
+    assert_eq!(
+        parent.get("chunk_strategy").and_then(Value::as_str),
+        Some("mapreduce_fallback")
+    );
+    let child = rows
+        .iter()
+        .find(|r| r.get("tool").and_then(Value::as_str) == Some("cxmapreduce_chunk"))
+        .expect("failed chunk row");
+    assert_eq!(child.get("schema_ok").and_then(Value::as_bool), Some(false));
+}
+
+#[test]
+fn clip_strategy_is_default() {
+    let repo = TempRepo::new("cxrs-mapreduce");
+    let prompts = mock_summarizer(&repo, false);
+    let out = repo.run_with_env(
+        &["cxo", "seq", "1", "400"],
+        &[("CX_CONTEXT_BUDGET_CHARS", "400")],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let log = fs::read_to_string(&prompts).expect("prompts");
+    assert!(!log.contains("--- chunk "));
+    let rows = parse_jsonl(&repo.runs_log());
+    assert_eq!(
+        rows.last()
+            .and_then(|r| r.get("chunk_strategy"))
+            .and_then(Value::as_str),
+        Some("clip")
+    );
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+
+const MARKDOWN: &str =
+    "# Result\\n| a | b |\\n|---|---|\\n| 1 | 2 |\\n```rust\\nfn main() {}\\n```";
+
+#[test]
+fn piped_output_stays_unrendered_even_when_enabled() {
+    let repo = TempRepo::new("cxrs-md");
+    repo.write_mock_codex(&format!(
+        "#!/usr/bin/env bash\ncat >/dev/null\nprintf '%s\\n' '{{\"type\":\"item.completed\",\"item\":{{\"type\":\"agent_message\",\"text\":\"{MARKDOWN}\"}}}}'\n"
+    ));
+    let expected = format!("{}\n", MARKDOWN.replace("\\n", "\n"));
+    for args in [
+        &["cxo", "echo", "hi"][..],
+        &["cxo", "--plain", "echo", "hi"][..],
+    ] {
+        let out = repo.run_with_env(args, &[("CX_RENDER_MARKDOWN", "1")]);
+        assert!(out.status.success(), "stderr={}", stderr_str(&out));
+        assert_eq!(stdout_str(&out), expected, "{args:?}");
+    }
+}
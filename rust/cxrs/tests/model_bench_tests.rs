@@ -0,0 +1,66 @@
+mod common;
+
+use common::*;
+use serde_json::Value;
+use std::fs;
+
+const MOCK_CODEX: &str = r#"#!/usr/bin/env bash
+cat >/dev/null
+if [ "$CX_MODEL" = "good" ]; then
+  text='{\"commands\":[\"cargo test\"]}'
+else
+  text='not json'
+fi
+printf '%s\n' "{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"text\":\"$text\"}}"
+printf '%s\n' '{"type":"turn.completed","usage":{"input_tokens":120,"cached_input_tokens":20,"output_tokens":8}}'
+"#;
+
+#[test]
+fn model_bench_ranks_by_schema_pass_rate() {
+    let repo = TempRepo::new("cxrs-model-bench");
+    repo.write_mock_codex(MOCK_CODEX);
+    let suite = repo.root.join("prompts");
+    fs::create_dir_all(&suite).unwrap();
+    fs::write(
+        suite.join("a_next.md"),
+        "schema: next\nList the next shell commands as JSON.\n",
+    )
+    .unwrap();
+    fs::write(suite.join("b_free.txt"), "Say hello.\n").unwrap();
+    fs::write(suite.join("ignored.json"), "{}").unwrap();
+
+    let out = repo.run(&[
+        "model",
+        "bench",
+        "prompts",
+        "--models",
+        "codex:bad,codex:good",
+        "--runs",
+        "2",
+        "--json",
+    ]);
+    assert_eq!(out.status.code(), Some(0), "stderr={}", stderr_str(&out));
+    let payload: Value = serde_json::from_str(&stdout_str(&out)).expect("model-bench json");
+    assert_eq!(payload["contract_version"], "model-bench.v1");
+    assert_eq!(payload["prompts"].as_array().map(Vec::len), Some(2));
+    assert_eq!(payload["prompts"][0]["schema"], "next");
+    let models = payload["models"].as_array().expect("models");
+    assert_eq!(models[0]["label"], "codex:good");
+    assert_eq!(models[0]["rank"], 1);
+    assert_eq!(models[0]["runs"], 4);
+    assert_eq!(models[0]["schema_checked"], 2);
+    assert_eq!(models[0]["schema_pass_rate"], 1.0);
+    assert_eq!(models[0]["effective_input_tokens_avg"], 100);
+    assert_eq!(models[1]["label"], "codex:bad");
+    assert_eq!(models[1]["schema_pass_rate"], 0.0);
+
+    let out = repo.run(&["model", "bench", "prompts/b_free.txt", "--models", "codex"]);
+    assert_eq!(out.status.code(), Some(0), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("rank"), "{text}");
+    assert!(text.contains("codex"), "{text}");
+
+    let out = repo.run(&["model", "bench", "prompts"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("--models"));
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::{TempRepo, mock_codex_agent_text, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+/// A git monorepo whose `packages/app` has its own `.codex` but no schemas.
+fn monorepo() -> (TempRepo, std::path::PathBuf) {
+    let repo = TempRepo::new("cxrs-monorepo");
+    let package = repo.root.join("packages").join("app");
+    fs::create_dir_all(package.join(".codex")).expect("package codex");
+    repo.write_mock_codex(&mock_codex_agent_text(r#"{"commands":["make test"]}"#));
+    (repo, package)
+}
+
+#[test]
+fn package_logs_nearest_codex_shares_root_schemas() {
+    let (repo, package) = monorepo();
+
+    let out = repo.run_in(&package, &["next", "echo", "hi"], &[]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(stdout_str(&out).trim(), "make test");
+    let rows = parse_jsonl(&package.join(".codex").join("cxlogs").join("runs.jsonl"));
+    assert_eq!(rows.len(), 1);
+    let row = &rows[0];
+    assert_eq!(row["codex_root"], package.display().to_string());
+    assert_eq!(row["repo_root"], repo.root.display().to_string());
+    assert_eq!(row["vcs"], "git");
+    assert!(!repo.runs_log().exists());
+
+    let out = repo.run_in(&package, &["where", "--json"], &[]);
+    let doc: Value = serde_json::from_str(&stdout_str(&out)).expect("where json");
+    let entry = |name: &str| {
+        doc["paths"]
+            .as_array()
+            .and_then(|a| a.iter().find(|p| p["name"] == name))
+            .cloned()
+            .unwrap_or(Value::Null)
+    };
+    assert_eq!(entry("log_file")["reason"], "nearest");
+    assert_eq!(entry("schema_dir")["reason"], "repo_root");
+    assert_eq!(
+        entry("schema_dir")["path"],
+        repo.root
+            .join(".codex")
+            .join("schemas")
+            .display()
+            .to_string()
+    );
+}
+
+#[test]
+fn shared_codex_root_logs_packages_at_root() {
+    let (repo, package) = monorepo();
+
+    let out = repo.run_in(
+        &package,
+        &["next", "echo", "hi"],
+        &[("CX_CODEX_ROOT", "shared")],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let rows = parse_jsonl(&repo.runs_log());
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["codex_root"], repo.root.display().to_string());
+    assert!(!package.join(".codex").join("cxlogs").exists());
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::{TempRepo, mock_recorder, parse_jsonl, stderr_str};
+use std::fs;
+
+const CXRS: &str = env!("CARGO_BIN_EXE_cxrs");
+
+#[test]
+fn nested_cx_is_refused_unless_allowed() {
+    let repo = TempRepo::new("cxrs-nesting");
+    mock_recorder(&repo, "looks fine");
+
+    let out = repo.run(&["cxo", CXRS, "cxo", "echo", "hi"]);
+    assert_eq!(out.status.code(), Some(3));
+    assert!(
+        stderr_str(&out).contains("runs cx cxo inside cx"),
+        "{}",
+        stderr_str(&out)
+    );
+    assert!(!repo.runs_log().exists() || parse_jsonl(&repo.runs_log()).is_empty());
+
+    let out = repo.run(&["--allow-nested", "cxo", CXRS, "cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let rows = parse_jsonl(&repo.runs_log());
+    assert_eq!(rows.len(), 2);
+    let (inner, outer) = (&rows[0], &rows[1]);
+    assert_eq!(inner["parent_execution_id"], outer["execution_id"]);
+    assert!(outer["parent_execution_id"].is_null());
+}
+
+#[test]
+fn in_flight_marker_catches_cx_behind_shell() {
+    let repo = TempRepo::new("cxrs-nesting-shell");
+    let prompt_file = mock_recorder(&repo, "looks fine");
+
+    let script = format!("{CXRS} next echo hi");
+    let out = repo.run(&["cxo", "sh", "-c", &script]);
+    // The wrapped shell exits with the nested refusal, which cxo passes through.
+    assert_eq!(out.status.code(), Some(3), "stderr={}", stderr_str(&out));
+    let prompt = fs::read_to_string(&prompt_file).expect("prompt");
+    assert!(prompt.contains("started inside cx run"), "{prompt}");
+    let rows = parse_jsonl(&repo.runs_log());
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["tool"], "cxo");
+}
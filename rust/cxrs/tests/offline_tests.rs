@@ -0,0 +1,97 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+
+fn mock_codex_marking_calls(repo: &TempRepo) {
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+touch codex-called
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+"#,
+    );
+}
+
+#[test]
+fn offline_mode_blocks_llm_commands_up_front() {
+    let repo = TempRepo::new("cxrs-offline");
+    mock_codex_marking_calls(&repo);
+    let marker = repo.root.join("codex-called");
+
+    let out = repo.run(&["--offline", "cxo", "touch", "captured"]);
+    assert_eq!(out.status.code(), Some(6), "stderr={}", stderr_str(&out));
+    assert!(
+        stderr_str(&out).contains("cxrs cxo: offline mode is on"),
+        "{}",
+        stderr_str(&out)
+    );
+    assert!(!repo.root.join("captured").exists(), "command was captured");
+    let out = repo.run_with_env(&["next", "echo", "hi"], &[("CX_OFFLINE", "1")]);
+    assert_eq!(out.status.code(), Some(6));
+    assert!(!marker.exists(), "backend was called");
+    assert!(!repo.runs_log().exists() || parse_jsonl(&repo.runs_log()).is_empty());
+
+    // Help and local commands keep working.
+    assert!(repo.run(&["--offline", "cxo", "--help"]).status.success());
+    for args in [
+        &["--offline", "logs", "stats"][..],
+        &["--offline", "metrics", "10"],
+        &["--offline", "policy", "show"],
+        &["--offline", "task", "list"],
+    ] {
+        let out = repo.run(args);
+        assert!(out.status.success(), "{args:?}: {}", stderr_str(&out));
+    }
+    let out = repo.run(&["--offline", "exit-codes", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert!(
+        v.to_string().contains("offline_blocked"),
+        "{}",
+        stdout_str(&out)
+    );
+
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(marker.exists());
+}
+
+#[test]
+fn llm_tasks_hit_the_adapter_guard() {
+    let repo = TempRepo::new("cxrs-offline");
+    mock_codex_marking_calls(&repo);
+    let add = repo.run(&["task", "add", "cxo echo hi", "--role", "implementer"]);
+    assert!(add.status.success(), "stderr={}", stderr_str(&add));
+    let id = stdout_str(&add).trim().to_string();
+
+    let out = repo.run_with_env(&["task", "run", &id], &[("CX_OFFLINE", "1")]);
+    assert!(!out.status.success());
+    assert!(
+        stderr_str(&out).contains("offline mode is on"),
+        "{}",
+        stderr_str(&out)
+    );
+    assert!(!repo.root.join("codex-called").exists());
+}
+
+#[test]
+fn doctor_and_health_report_offline_mode() {
+    let repo = TempRepo::new("cxrs-offline");
+    mock_codex_marking_calls(&repo);
+
+    let out = repo.run(&["--offline", "health", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("health json");
+    assert_eq!(v["offline"], true);
+    for c in v["checks"].as_array().expect("checks") {
+        if ["llm_version", "llm_json", "cxo"].contains(&c["name"].as_str().unwrap_or("")) {
+            assert_eq!(c["status"], "skip");
+            assert_eq!(c["detail"], "offline mode");
+        }
+    }
+
+    let out = repo.run_with_env(&["doctor"], &[("CX_OFFLINE", "1")]);
+    let text = stdout_str(&out);
+    assert!(text.contains("offline mode: on"), "{text}");
+    assert!(!text.contains("== llm json pipeline"), "{text}");
+    assert!(!repo.root.join("codex-called").exists());
+}
@@ -106,3 +106,45 @@ fn optimize_actions_strict_gate_is_deterministic() {
         stderr_str(&crit)
     );
 }
+
+#[test]
+fn optimize_reports_bench_latency_regressions() {
+    let repo = TempRepo::new("cxrs-it");
+    let bench = repo.run(&["bench", "2", "--", "true"]);
+    assert!(bench.status.success(), "stderr={}", stderr_str(&bench));
+    let history_file = repo.root.join(".codex").join("bench.jsonl");
+    let rows = parse_jsonl(&history_file);
+    assert_eq!(rows.len(), 1, "{rows:?}");
+    assert_eq!(rows[0]["source"], "bench");
+    assert_eq!(rows[0]["label"], "true");
+
+    let old = serde_json::json!({
+        "ts": "2026-01-01T00:00:00Z", "source": "bench", "label": "cxdiffsum",
+        "runs": 5, "failures": 0, "duration_ms": {"p90": 1000}
+    });
+    let new = serde_json::json!({
+        "ts": "2026-01-08T00:00:00Z", "source": "bench", "label": "cxdiffsum",
+        "runs": 5, "failures": 0, "duration_ms": {"p90": 1400}
+    });
+    std::fs::write(&history_file, format!("{old}\n{new}\n")).expect("write bench history");
+
+    let out = repo.run(&["optimize", "10", "--json", "--actions"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let payload: Value = serde_json::from_str(&stdout_str(&out)).expect("optimize json");
+    let regression = &payload["bench"]["latency_regressions"][0];
+    assert_eq!(regression["label"], "cxdiffsum");
+    assert_eq!(regression["delta_pct"], 40.0);
+    let recs = payload["recommendations"].to_string();
+    assert!(
+        recs.contains("cxdiffsum p90 latency regressed 40%"),
+        "{recs}"
+    );
+    let actions = payload["actions"].as_array().expect("actions");
+    assert!(
+        actions
+            .iter()
+            .any(|a| a["id"] == "bench_latency_regression"
+                && a["command"] == "cx bench 5 -- cxdiffsum"),
+        "{actions:?}"
+    );
+}
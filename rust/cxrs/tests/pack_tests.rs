@@ -0,0 +1,96 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+fn write_tree(repo: &TempRepo) {
+    fs::create_dir_all(repo.root.join("src/nested")).expect("mkdir");
+    fs::write(repo.root.join("src/a.rs"), "fn a() {}\n").expect("write a");
+    fs::write(repo.root.join("src/nested/b.rs"), "fn b() {}\n").expect("write b");
+    fs::write(repo.root.join("notes.md"), "has ``` fence\n").expect("write notes");
+    fs::write(repo.root.join("blob.bin"), [0u8, 1, 2, 3]).expect("write bin");
+}
+
+#[test]
+fn pack_bundles_globs_and_logs_manifest() {
+    let repo = TempRepo::new("cxrs-pack");
+    write_tree(&repo);
+
+    let out = repo.run(&["pack", "src/**/*.rs", "notes.md", "blob.bin"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.starts_with("# cx pack (4 files)"));
+    assert!(text.contains("## src/a.rs\n```rust\nfn a() {}\n```"));
+    assert!(text.contains("## src/nested/b.rs"));
+    assert!(text.contains("## notes.md\n````markdown\nhas ``` fence\n````"));
+    assert!(text.contains("## blob.bin\n[cx] skipped: binary"));
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let last = rows.last().expect("run row");
+    assert_eq!(last.get("tool").and_then(Value::as_str), Some("cxpack"));
+    let manifest = last.get("pack_manifest").expect("manifest");
+    assert_eq!(manifest.get("file_count").and_then(Value::as_u64), Some(4));
+    assert_eq!(
+        manifest.get("bytes_dropped").and_then(Value::as_u64),
+        Some(4)
+    );
+    let files = manifest
+        .get("files")
+        .and_then(Value::as_array)
+        .expect("files");
+    assert_eq!(
+        files[3].get("skipped").and_then(Value::as_str),
+        Some("binary")
+    );
+}
+
+#[test]
+fn pack_truncates_to_budget_and_chunks() {
+    let repo = TempRepo::new("cxrs-pack");
+    let big = (1..=200)
+        .map(|i| format!("line {i}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(repo.root.join("big.txt"), &big).expect("write big");
+    fs::write(repo.root.join("small.txt"), "tiny\n").expect("write small");
+
+    let out = repo.run_with_env(
+        &["pack", "big.txt", "small.txt"],
+        &[("CX_CONTEXT_BUDGET_CHARS", "300")],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("tiny"));
+    assert!(text.contains("[cx] truncated big.txt: kept "));
+    assert!(!text.contains("line 200"));
+    let rows = parse_jsonl(&repo.runs_log());
+    let files = rows.last().unwrap()["pack_manifest"]["files"]
+        .as_array()
+        .cloned()
+        .expect("files");
+    assert_eq!(files[0]["truncated"], Value::Bool(true));
+    assert_eq!(files[1]["truncated"], Value::Bool(false));
+
+    let out = repo.run_with_env(
+        &["pack", "--chunk", "big.txt"],
+        &[("CX_CONTEXT_BUDGET_CHARS", "300")],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("----- cx chunk 1/"));
+    assert!(text.contains("----- cx chunk 2/"));
+}
+
+#[test]
+fn pack_rejects_missing_paths_and_empty_args() {
+    let repo = TempRepo::new("cxrs-pack");
+    let out = repo.run(&["pack"]);
+    assert_eq!(out.status.code(), Some(2));
+    let out = repo.run(&["pack", "nope.rs"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("no such file or directory: nope.rs"));
+    let out = repo.run(&["pack", "**/*.zig"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("no files matched"));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+
+/// Ten fast `cxo` runs plus one `cxj` run with a long tail.
+fn seed_runs(repo: &TempRepo) {
+    let mut rows: Vec<Value> = (1..=10)
+        .map(|i| {
+            json!({"execution_id": format!("pc{i}"), "timestamp": "2026-01-01T00:00:00Z",
+                   "tool": "cxo", "duration_ms": i * 100, "effective_input_tokens": 1000})
+        })
+        .collect();
+    let tail = json!({"execution_id": "pc11", "timestamp": "2026-01-01T00:01:00Z",
+                      "tool": "cxj", "duration_ms": 9000, "effective_input_tokens": 7000});
+    rows.push(tail);
+    write_runs_log_rows(repo, &rows);
+}
+
+fn run_json(repo: &TempRepo, args: &[&str]) -> Value {
+    let out = repo.run(args);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    serde_json::from_str(&stdout_str(&out)).expect("json")
+}
+
+#[test]
+fn metrics_and_profile_report_percentiles() {
+    let repo = TempRepo::new("cxrs-percentiles");
+    seed_runs(&repo);
+
+    assert!(run_json(&repo, &["metrics"]).get("percentiles").is_none());
+    let v = run_json(&repo, &["metrics", "--percentiles", "50"]);
+    assert_eq!(v["runs"], 11);
+    assert_eq!(
+        v["percentiles"]["duration_ms"],
+        json!({"p50": 600, "p90": 1000, "p99": 9000})
+    );
+    assert_eq!(
+        v["percentiles"]["by_tool"]["cxo"]["duration_ms"],
+        json!({"p50": 500, "p90": 900, "p99": 1000})
+    );
+
+    let v = run_json(&repo, &["profile", "--json", "--percentiles"]);
+    assert_eq!(v["percentiles"]["effective_input_tokens"]["p99"], 7000);
+
+    let out = repo.run(&["profile", "--percentiles"]);
+    let text = stdout_str(&out);
+    assert!(
+        text.contains("Duration p50/p90/p99: 600/1000/9000ms"),
+        "{text}"
+    );
+    assert!(
+        text.contains("  cxj: 9000/9000/9000 | 7000/7000/7000"),
+        "{text}"
+    );
+}
+
+#[test]
+fn optimize_ranks_tools_by_p90() {
+    let repo = TempRepo::new("cxrs-percentiles");
+    seed_runs(&repo);
+    let v = run_json(&repo, &["optimize", "--json"]);
+    let sb = &v["scoreboard"];
+    assert_eq!(sb["top_p90_duration_ms"][0], json!(["cxj", 9000]));
+    assert_eq!(sb["top_p90_duration_ms"][1], json!(["cxo", 900]));
+    let anomalies = v["anomalies"].to_string();
+    assert!(
+        anomalies.contains("cxj p90_duration_ms=9000"),
+        "{anomalies}"
+    );
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+/// Answers by prompt: fix-run and next get their schema objects, anything else prose.
+fn mock_steps(repo: &TempRepo) {
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+prompt="$(cat)"
+case "$prompt" in
+  *'"analysis"'*) text='{\"analysis\":\"flaky build\",\"commands\":[\"make clean\"]}' ;;
+  *'"commands"'*) text='{\"commands\":[\"make test\"]}' ;;
+  *) text='The build failed with boom.' ;;
+esac
+printf '%s\n' "{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"text\":\"$text\"}}"
+"#,
+    );
+}
+
+#[test]
+fn pipeline_captures_once_and_links_every_step() {
+    let repo = TempRepo::new("cxrs-pipeline");
+    mock_steps(&repo);
+    let counter = repo.root.join("runs.txt");
+    let script = format!("echo run >> {}; echo boom >&2; exit 1", counter.display());
+
+    let out = repo.run(&[
+        "pipeline",
+        "run",
+        "--steps",
+        "cxo,next,fix-run",
+        "--json",
+        "--",
+        "sh",
+        "-c",
+        &script,
+    ]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(fs::read_to_string(&counter).expect("counter"), "run\n");
+    let doc: Value = serde_json::from_str(&stdout_str(&out)).expect("pipeline json");
+    assert_eq!(doc["contract_version"], "pipeline.v1");
+    assert_eq!(doc["status"], 1);
+    let steps = doc["steps"].as_array().expect("steps");
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[0]["result"], "The build failed with boom.");
+    assert_eq!(steps[1]["result"]["commands"][0], "make test");
+    assert_eq!(steps[2]["result"]["commands"][0], "make clean");
+
+    let id = doc["pipeline_id"].as_str().expect("pipeline id");
+    let rows = parse_jsonl(&repo.runs_log());
+    let tools: Vec<&str> = rows
+        .iter()
+        .filter(|r| r["pipeline_id"] == id)
+        .filter_map(|r| r["tool"].as_str())
+        .collect();
+    assert_eq!(tools, ["cxo", "cxrs_next", "cxrs_fix_run"]);
+}
+
+#[test]
+fn pipeline_reports_failed_and_unknown_steps() {
+    let repo = TempRepo::new("cxrs-pipeline-errors");
+    mock_steps(&repo);
+
+    let out = repo.run(&["pipeline", "run", "true", "--steps", "diffsum,fix"]);
+    assert_eq!(out.status.code(), Some(1));
+    let text = stdout_str(&out);
+    assert!(
+        text.contains("== fix ==\nThe build failed with boom."),
+        "{text}"
+    );
+    assert!(
+        stderr_str(&out).contains("diffsum: the captured output is empty"),
+        "{}",
+        stderr_str(&out)
+    );
+
+    let out = repo.run(&["pipeline", "run", "true", "--steps", "next,deploy"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("unknown step 'deploy'"));
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+use serde_json::Value;
+
+const MOCK: &str = r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"all good here"}}'
+"#;
+
+fn progress_events(stderr: &str) -> Vec<Value> {
+    stderr
+        .lines()
+        .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+        .filter(|v| v["type"] == "progress")
+        .collect()
+}
+
+#[test]
+fn json_progress_reports_capture_and_llm_phases() {
+    let repo = TempRepo::new("cxrs-progress");
+    repo.write_mock_codex(MOCK);
+
+    let out = repo.run_with_env(&["cxo", "echo", "hi"], &[("CX_PROGRESS", "json")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(stdout_str(&out), "all good here\n");
+    let events = progress_events(&stderr_str(&out));
+    let names: Vec<&str> = events.iter().filter_map(|e| e["event"].as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "capture_started",
+            "capture_finished",
+            "llm_started",
+            "llm_tokens",
+            "llm_finished"
+        ],
+        "{}",
+        stderr_str(&out)
+    );
+    assert_eq!(events[0]["cmd"], "echo hi");
+    assert_eq!(events[1]["status"], 0);
+    assert_eq!(events[1]["stdout_bytes"], 3);
+    assert_eq!(events[2]["tool"], "cxo");
+    let tokens = events[4]["tokens"].as_u64().expect("tokens");
+    assert!(tokens > 0);
+    assert_eq!(events[3]["tokens"].as_u64(), Some(tokens));
+    assert!(events.iter().all(|e| e["ts"].is_string()));
+
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert!(out.status.success());
+    assert!(
+        progress_events(&stderr_str(&out)).is_empty(),
+        "auto mode stays quiet off a terminal"
+    );
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+fn mock_next(repo: &TempRepo) -> std::path::PathBuf {
+    let prompt_file = repo.root.join("prompt.txt");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat > {}
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"{{\"commands\":[\"echo ok\"]}}"}}}}'
+"#,
+        prompt_file.display()
+    ));
+    prompt_file
+}
+
+#[test]
+fn template_override_is_rendered_and_hashed() {
+    let repo = TempRepo::new("cxrs-prompt-tmpl");
+    let prompt_file = mock_next(&repo);
+
+    let out = repo.run(&["next", "echo", "hello"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let rows = parse_jsonl(&repo.runs_log());
+    let builtin = rows.last().expect("row");
+    assert_eq!(builtin["prompt_template"], "next");
+    assert_eq!(builtin["prompt_template_source"], "builtin");
+    let builtin_sha = builtin["prompt_template_sha256"]
+        .as_str()
+        .expect("sha")
+        .to_string();
+
+    let dir = repo.root.join(".codex").join("prompts");
+    fs::create_dir_all(&dir).expect("mkdir prompts");
+    fs::write(
+        dir.join("next.tmpl"),
+        "CUSTOM NEXT for `{{ command }}` (exit {{status}})\n{{output}}\n",
+    )
+    .expect("write template");
+
+    let out = repo.run(&["next", "echo", "hello"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(stdout_str(&out).trim(), "echo ok");
+    let prompt = fs::read_to_string(&prompt_file).expect("prompt");
+    assert!(prompt.contains("CUSTOM NEXT for `echo hello` (exit 0)\nhello"));
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let custom = rows.last().expect("row");
+    assert!(
+        custom["prompt_template_source"]
+            .as_str()
+            .is_some_and(|s| s.ends_with("next.tmpl"))
+    );
+    let sha = custom.get("prompt_template_sha256").and_then(Value::as_str);
+    assert!(sha.is_some_and(|s| s != builtin_sha));
+}
+
+#[test]
+fn invalid_template_is_rejected_with_its_path() {
+    let repo = TempRepo::new("cxrs-prompt-tmpl");
+    mock_next(&repo);
+    let dir = repo.root.join(".codex").join("prompts");
+    fs::create_dir_all(&dir).expect("mkdir prompts");
+    fs::write(dir.join("next.tmpl"), "{{command}} {{output}} {{bogus}}").expect("write");
+
+    let out = repo.run(&["next", "echo", "hello"]);
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = stderr_str(&out);
+    assert!(
+        stderr.contains("next.tmpl: unknown placeholder {{bogus}}"),
+        "{stderr}"
+    );
+
+    fs::write(dir.join("next.tmpl"), "{{command}} {{output}}").expect("write");
+    let out = repo.run(&["next", "echo", "hello"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("missing required placeholder {{status}}"));
+}
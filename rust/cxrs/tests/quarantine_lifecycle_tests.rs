@@ -0,0 +1,168 @@
+mod common;
+
+use common::{TempRepo, read_json, stderr_str, stdout_str};
+use serde_json::{Value, json};
+use std::fs;
+
+fn seed_entry(repo: &TempRepo, id: &str, ts: &str, tool: &str, reason: &str) {
+    fs::create_dir_all(repo.quarantine_dir()).expect("create quarantine dir");
+    let rec = json!({
+        "id": id,
+        "ts": ts,
+        "tool": tool,
+        "reason": reason,
+        "schema": "{}",
+        "prompt": "p",
+        "raw_response": "not-json"
+    });
+    fs::write(repo.quarantine_file(id), rec.to_string()).expect("write quarantine entry");
+}
+
+fn seed_entries(repo: &TempRepo) {
+    seed_entry(
+        repo,
+        "old_a",
+        "2020-01-01T00:00:00Z",
+        "cxcommitjson",
+        "invalid_json",
+    );
+    seed_entry(
+        repo,
+        "old_b",
+        "2020-01-02T00:00:00Z",
+        "cxnext",
+        "schema_validation_failed",
+    );
+    seed_entry(
+        repo,
+        "new_c",
+        "2999-01-01T00:00:00Z",
+        "cxcommitjson",
+        "invalid_json",
+    );
+}
+
+#[test]
+fn resolve_and_rm_update_entries() {
+    let repo = TempRepo::new("cxrs-qlife");
+    seed_entries(&repo);
+
+    let out = repo.run(&["quarantine", "resolve", "old_a", "fixed", "schema"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let rec = read_json(&repo.quarantine_file("old_a"));
+    assert!(rec["resolved_ts"].is_string());
+    assert_eq!(rec["resolved_note"], "fixed schema");
+
+    let list = stdout_str(&repo.run(&["quarantine", "list"]));
+    assert!(list.contains("old_a | 2020-01-01T00:00:00Z | cxcommitjson | invalid_json | resolved"));
+
+    let out = repo.run(&["quarantine", "rm", "old_b"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(!repo.quarantine_file("old_b").exists());
+
+    let out = repo.run(&["quarantine", "rm", "missing"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("quarantine id not found: missing"));
+}
+
+#[test]
+fn purge_removes_only_aged_entries() {
+    let repo = TempRepo::new("cxrs-qlife");
+    seed_entries(&repo);
+
+    let out = repo.run(&["quarantine", "purge"]);
+    assert_eq!(out.status.code(), Some(2));
+
+    let out = repo.run(&["quarantine", "purge", "--older-than", "30d", "--dry-run"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("would_purge: 2"));
+    assert!(repo.quarantine_file("old_a").exists());
+
+    let out = repo.run(&["quarantine", "purge", "--older-than", "30d"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("purged: 2"));
+    assert!(!repo.quarantine_file("old_a").exists());
+    assert!(!repo.quarantine_file("old_b").exists());
+    assert!(repo.quarantine_file("new_c").exists());
+}
+
+#[test]
+fn stats_groups_by_tool_reason_and_day() {
+    let repo = TempRepo::new("cxrs-qlife");
+    seed_entries(&repo);
+    assert!(
+        repo.run(&["quarantine", "resolve", "new_c"])
+            .status
+            .success()
+    );
+
+    let out = repo.run(&["quarantine", "stats", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["contract_version"], "quarantine-stats.v1");
+    assert_eq!(v["total"], 3);
+    assert_eq!(v["open"], 2);
+    assert_eq!(v["resolved"], 1);
+    assert_eq!(v["by_tool"]["cxcommitjson"], 2);
+    assert_eq!(v["by_reason"]["schema_validation_failed"], 1);
+    assert_eq!(v["by_day"]["2020-01-01"], 1);
+
+    let text = stdout_str(&repo.run(&["quarantine", "stats"]));
+    assert!(text.contains("by_tool:\n- cxcommitjson: 2\n- cxnext: 1"));
+}
+
+#[test]
+fn diff_points_at_violations_and_suggests_patch() {
+    let repo = TempRepo::new("cxrs-qdiff");
+    fs::create_dir_all(repo.quarantine_dir()).expect("create quarantine dir");
+    let schema =
+        fs::read_to_string(repo.root.join(".codex/schemas/next.schema.json")).expect("next schema");
+    let rec = json!({
+        "id": "q_next",
+        "ts": "2026-03-01T10:15:00Z",
+        "tool": "cxrs_next",
+        "reason": "schema_validation_failed",
+        "schema": schema,
+        "prompt": "p",
+        "raw_response": "{\"commands\": \"cargo test\", \"why\": \"flaky\"}"
+    });
+    fs::write(repo.quarantine_file("q_next"), rec.to_string()).expect("write entry");
+
+    let out = repo.run(&["quarantine", "diff", "q_next"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("- /commands [type]"), "{text}");
+    assert!(text.contains("  + /why  (unexpected)"), "{text}");
+    assert!(
+        text.contains("  replace /commands [\"cargo test\"]"),
+        "{text}"
+    );
+    assert!(text.contains("after edit: passes"), "{text}");
+
+    let out = repo.run(&["quarantine", "diff", "q_next", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("diff json");
+    assert_eq!(v["contract_version"], "quarantine-diff.v1");
+    assert_eq!(v["unexpected_keys"], json!(["/why"]));
+    assert_eq!(v["passes_after_patch"], true);
+    assert_eq!(
+        v["patch"],
+        json!([
+            {"op": "replace", "path": "/commands", "value": ["cargo test"]},
+            {"op": "remove", "path": "/why"}
+        ])
+    );
+
+    seed_entry(
+        &repo,
+        "q_prose",
+        "2026-03-01T10:16:00Z",
+        "cxrs_next",
+        "invalid_json",
+    );
+    let out = repo.run(&["quarantine", "diff", "q_prose", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("diff json");
+    assert_eq!(v["json_valid"], false);
+
+    let out = repo.run(&["quarantine", "diff"]);
+    assert_eq!(out.status.code(), Some(2));
+}
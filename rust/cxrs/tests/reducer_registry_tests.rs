@@ -0,0 +1,76 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+
+fn last_reducer(repo: &TempRepo) -> Option<String> {
+    parse_jsonl(&repo.runs_log())
+        .last()
+        .and_then(|row| row.get("reducer"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+#[test]
+fn run_log_records_reducer_and_env_override() {
+    let repo = TempRepo::new("cxrs-reducers");
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+"#,
+    );
+
+    let out = repo.run(&["cxo", "git", "status"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(last_reducer(&repo).as_deref(), Some("git-status"));
+
+    let out = repo.run(&["cxo", "echo", "plain"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(last_reducer(&repo).as_deref(), Some("none"));
+
+    let out = repo.run_with_env(&["cxo", "git", "status"], &[("CX_REDUCER_GIT", "off")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(last_reducer(&repo).as_deref(), Some("off"));
+
+    let out = repo.run_with_env(&["cxo", "echo", "a"], &[("CX_REDUCER_ECHO", "grep")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(last_reducer(&repo).as_deref(), Some("grep"));
+
+    let out = repo.run_with_env(&["cxo", "echo", "a"], &[("CX_REDUCER_ECHO", "bogus")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stderr_str(&out).contains("unknown reducer 'bogus' in CX_REDUCER_ECHO"));
+    assert_eq!(last_reducer(&repo).as_deref(), Some("none"));
+}
+
+#[test]
+fn capture_status_lists_registry() {
+    let repo = TempRepo::new("cxrs-reducers");
+    let out = repo.run_with_env(&["capture-status"], &[("CX_REDUCER_KUBECTL", "off")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("reducers:"));
+    assert!(text.contains("- pytest: pytest, python -m pytest, python3 -m pytest"));
+    assert!(text.contains("(CX_REDUCER_KUBECTL=off)"));
+}
+
+#[test]
+fn state_preference_selects_reducer() {
+    let repo = TempRepo::new("cxrs-reducers");
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+"#,
+    );
+    let set = repo.run(&["state", "set", "preferences.reducers.echo", "listing"]);
+    assert!(set.status.success(), "stderr={}", stderr_str(&set));
+
+    let out = repo.run(&["cxo", "echo", "a"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(last_reducer(&repo).as_deref(), Some("listing"));
+
+    let out = repo.run_with_env(&["cxo", "echo", "a"], &[("CX_REDUCER_ECHO", "auto")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(last_reducer(&repo).as_deref(), Some("none"));
+}
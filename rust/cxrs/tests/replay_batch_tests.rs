@@ -0,0 +1,112 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, read_json, stderr_str, stdout_str};
+use serde_json::{Value, json};
+use std::fs;
+
+fn seed(repo: &TempRepo, id: &str, ts: &str, tool: &str, schema: &str) {
+    let q = json!({
+        "id": id,
+        "ts": ts,
+        "tool": tool,
+        "reason": "invalid_json",
+        "schema": schema,
+        "prompt": "Command: git status --short\nOutput: M src/main.rs",
+        "raw_response": "not-json",
+        "attempts": []
+    });
+    fs::create_dir_all(repo.quarantine_dir()).expect("create quarantine dir");
+    fs::write(repo.quarantine_file(id), q.to_string()).expect("write quarantine fixture");
+}
+
+fn setup() -> TempRepo {
+    let repo = TempRepo::new("cxrs-replay");
+    repo.write_mock(
+        "codex",
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"{\"commands\":[\"echo ok\"]}"}}'
+"#,
+    );
+    let next_schema = fs::read_to_string(
+        repo.root
+            .join(".codex")
+            .join("schemas")
+            .join("next.schema.json"),
+    )
+    .expect("read next schema");
+    seed(
+        &repo,
+        "q_pass",
+        "2026-01-01T00:00:00Z",
+        "next",
+        &next_schema,
+    );
+    seed(
+        &repo,
+        "q_fail_entry",
+        "2026-01-02T00:00:00Z",
+        "commitjson",
+        r#"{"type":"object","required":["subject"]}"#,
+    );
+    repo
+}
+
+#[test]
+fn replay_all_reports_summary_without_mutating() {
+    let repo = setup();
+    let out = repo.run(&["replay", "--all", "--json"]);
+    assert_eq!(out.status.code(), Some(1), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["contract_version"], "replay.v1");
+    assert_eq!(v["replayed"], 2);
+    assert_eq!(v["passed"], 1);
+    assert_eq!(v["failed"], 1);
+    assert_eq!(v["applied"], 0);
+    assert!(read_json(&repo.quarantine_file("q_pass"))["resolved_ts"].is_null());
+    // Batch failures are summarized, not re-quarantined.
+    assert_eq!(fs::read_dir(repo.quarantine_dir()).unwrap().count(), 2);
+
+    let out = repo.run(&["replay", "--all", "--tool", "next"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("passed: 1\nfailed: 0"));
+}
+
+#[test]
+fn replay_apply_resolves_and_reemits_run_row() {
+    let repo = setup();
+    let out = repo.run(&["replay", "--all", "--apply"]);
+    assert_eq!(out.status.code(), Some(1));
+    let stdout = stdout_str(&out);
+    assert!(stdout.contains("- applied q_pass (next)"));
+    assert!(stdout.contains("- fail q_fail_entry (commitjson)"));
+    assert!(stdout.contains("applied: 1"));
+
+    assert!(read_json(&repo.quarantine_file("q_pass"))["resolved_ts"].is_string());
+    let runs = parse_jsonl(&repo.runs_log());
+    let row = runs.last().expect("re-emitted run row");
+    assert_eq!(row["tool"], "next");
+    assert_eq!(row["schema_valid"], true);
+    assert_eq!(row["quarantine_id"], "q_pass");
+
+    // Resolved entries are skipped on the next sweep.
+    let out = repo.run(&["replay", "--all", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["replayed"], 1);
+}
+
+#[test]
+fn replay_rejects_ambiguous_args() {
+    let repo = setup();
+    assert_eq!(
+        repo.run(&["replay", "q_pass", "--all"]).status.code(),
+        Some(2)
+    );
+    assert_eq!(
+        repo.run(&["replay", "q_pass", "--tool", "next"])
+            .status
+            .code(),
+        Some(2)
+    );
+    assert_eq!(repo.run(&["replay"]).status.code(), Some(2));
+}
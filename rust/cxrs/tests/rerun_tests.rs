@@ -0,0 +1,79 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+
+fn mock_answer(repo: &TempRepo, text: &str) {
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":"{text}"}}}}'
+"#
+    ));
+}
+
+#[test]
+fn rerun_resends_prompt_and_diffs_replies() {
+    let repo = TempRepo::new("cxrs-rerun");
+    mock_answer(&repo, "cause: missing dep\\nfix: add serde");
+    let out = repo.run_with_env(&["cxo", "echo", "build failed"], &[("CX_TRANSCRIPTS", "1")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let rows = parse_jsonl(&repo.runs_log());
+    let original = rows.last().expect("row");
+    let id = original["execution_id"].as_str().expect("id").to_string();
+
+    mock_answer(&repo, "cause: missing dep\\nfix: add serde_json");
+    let out = repo.run(&["rerun", &id]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(
+        text.starts_with(&format!("== cxrs rerun {id} -> ")),
+        "{text}"
+    );
+    assert!(text.contains("tool: cxo\n"), "{text}");
+    assert!(text.contains("prompt: match\n"), "{text}");
+    assert!(text.contains("response: differs (+1 -1 lines)"), "{text}");
+    assert!(
+        text.contains(" cause: missing dep\n-fix: add serde\n+fix: add serde_json\n"),
+        "{text}"
+    );
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let rerun = rows.last().expect("rerun row");
+    assert_eq!(rerun["rerun_of"], id.as_str());
+    assert_eq!(rerun["tool"], "cxo");
+    assert_eq!(rerun["prompt_sha256"], original["prompt_sha256"]);
+    assert!(text.contains(rerun["execution_id"].as_str().expect("new id")));
+
+    let out = repo.run(&["rerun", &id, "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["contract_version"], "rerun.v1");
+    assert_eq!(v["identical"], false);
+    assert_eq!(v["response"], "cause: missing dep\nfix: add serde_json");
+    assert_eq!(v["original_response"], "cause: missing dep\nfix: add serde");
+}
+
+#[test]
+fn rerun_needs_an_archived_transcript() {
+    let repo = TempRepo::new("cxrs-rerun");
+    mock_answer(&repo, "ok");
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let rows = parse_jsonl(&repo.runs_log());
+    let id = rows.last().expect("row")["execution_id"]
+        .as_str()
+        .expect("id")
+        .to_string();
+
+    let out = repo.run(&["rerun", &id]);
+    assert!(!out.status.success());
+    assert!(
+        stderr_str(&out).contains("CX_TRANSCRIPTS=1"),
+        "{}",
+        stderr_str(&out)
+    );
+    let out = repo.run(&["rerun", "nope"]);
+    assert!(stderr_str(&out).contains("not found"));
+    assert_eq!(repo.run(&["rerun"]).status.code(), Some(2));
+    assert_eq!(parse_jsonl(&repo.runs_log()).len(), rows.len());
+}
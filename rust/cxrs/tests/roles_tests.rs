@@ -0,0 +1,78 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+use std::fs;
+
+fn write_roles(repo: &TempRepo, body: &str) {
+    let dir = repo.root.join(".codex");
+    fs::create_dir_all(&dir).expect("mkdir .codex");
+    fs::write(dir.join("roles.json"), body).expect("write roles.json");
+}
+
+#[test]
+fn custom_roles_reach_prompt_and_fanout() {
+    let repo = TempRepo::new("cxrs-roles");
+    write_roles(
+        &repo,
+        r#"{"security": {"header": "Role: security\nFocus: threat review.", "summary": "Look for exploitable flaws."}}"#,
+    );
+    let set = repo.run(&["state", "set", "roles.perf.header", "Role: perf"]);
+    assert!(set.status.success(), "stderr={}", stderr_str(&set));
+
+    let out = repo.run(&["roles"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("architect   Define approach"));
+    assert!(text.contains("security    Look for exploitable flaws. ["));
+    assert!(text.contains("perf        Role: perf [state]"));
+
+    let out = repo.run(&["roles", "security"]);
+    assert_eq!(stdout_str(&out), "Role: security\nFocus: threat review.\n");
+
+    let out = repo.run(&[
+        "prompt",
+        "implement",
+        "--role",
+        "security",
+        "harden",
+        "login",
+    ]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("Role: security\nFocus: threat review."));
+    assert!(text.contains("- User request: harden login"));
+
+    let out = repo.run(&[
+        "fanout", "--role", "security", "--role", "reviewer", "ship", "it",
+    ]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("objective: ship it"));
+    assert!(text.contains("### Subtask 1/2 [security]\nGoal: Look for exploitable flaws."));
+    assert!(text.contains("### Subtask 2/2 [reviewer]\nGoal: Audit for regressions"));
+
+    let out = repo.run(&["task", "add", "audit auth", "--role", "perf"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+}
+
+#[test]
+fn unknown_and_invalid_roles_are_reported() {
+    let repo = TempRepo::new("cxrs-roles");
+    let out = repo.run(&["prompt", "fix", "--role", "wizard", "x"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("unknown role 'wizard' (known: architect,"));
+
+    let out = repo.run(&["task", "add", "x", "--role", "wizard"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("invalid role 'wizard'"));
+
+    write_roles(&repo, r#"{"ops": {"summary": "no header"}}"#);
+    let out = repo.run(&["roles"]);
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = stderr_str(&out);
+    assert!(stderr.contains("roles.json: role 'ops' needs a non-empty string 'header'"));
+
+    let out = repo.run(&["task", "add", "x", "--role", "ops"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("roles.json"));
+}
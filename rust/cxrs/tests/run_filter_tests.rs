@@ -0,0 +1,190 @@
+mod common;
+
+use chrono::Utc;
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+use std::process::Command;
+
+fn row(repo: &TempRepo, id: &str, branch: &str, subdir: &str) -> Value {
+    let ts = Utc::now().to_rfc3339();
+    let root = repo.root.display().to_string();
+    json!({"execution_id": id, "timestamp": ts, "ts": ts, "tool": "cxo", "duration_ms": 100,
+           "effective_input_tokens": 50, "repo_root": root,
+           "cwd": format!("{root}/{subdir}"), "git_branch": branch})
+}
+
+fn json_out(repo: &TempRepo, args: &[&str]) -> Value {
+    let out = repo.run(args);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    serde_json::from_str(&stdout_str(&out)).expect("json")
+}
+
+#[test]
+fn analytics_filter_by_branch_and_path() {
+    let repo = TempRepo::new("cxrs-run-filter");
+    let status = Command::new("git")
+        .args(["checkout", "-q", "-b", "feature/login"])
+        .current_dir(&repo.root)
+        .status()
+        .expect("git checkout");
+    assert!(status.success());
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+"#,
+    );
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let last = parse_jsonl(&repo.runs_log()).pop().expect("row");
+    assert_eq!(last["git_branch"], "feature/login");
+    assert_eq!(last["git_head"], Value::Null);
+    assert_eq!(last["scope"], "repo");
+    assert_eq!(last["scope_source"], "marker");
+
+    write_runs_log_rows(
+        &repo,
+        &[
+            row(&repo, "a", "main", "src"),
+            row(&repo, "b", "feature/login", "src/auth"),
+            row(&repo, "c", "feature/login", "docs"),
+        ],
+    );
+    let v = json_out(&repo, &["metrics", "--branch", "feature/login"]);
+    assert_eq!(v["runs"], 2);
+    assert_eq!(v["filter"]["branch"], "feature/login");
+    assert_eq!(v["filter"]["path"], Value::Null);
+
+    let v = json_out(
+        &repo,
+        &[
+            "profile",
+            "--json",
+            "--branch",
+            "feature/login",
+            "--path",
+            "./src/",
+        ],
+    );
+    assert_eq!(v["runs"], 1);
+    assert_eq!(v["filter"]["path"], "src");
+
+    let v = json_out(&repo, &["worklog", "--json", "--path", "docs"]);
+    assert_eq!(v["runs"], 1);
+    assert_eq!(v["entries"][0]["tool"], "cxo");
+
+    let v = json_out(&repo, &["trends", "--json", "--branch", "main"]);
+    assert_eq!(v["runs"], 1);
+
+    let text = stdout_str(&repo.run(&["profile", "--path", "src"]));
+    assert!(text.contains("Filter: path=src"), "{text}");
+    assert!(text.contains("Runs: 2"), "{text}");
+
+    let out = repo.run(&["metrics", "--branch"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("--branch requires a value"));
+}
+
+#[test]
+fn analytics_filter_by_tool_task_and_window() {
+    let repo = TempRepo::new("cxrs-run-filter-window");
+    let stamp = |ts: &str, id: &str, tool: &str, task: Option<&str>| {
+        json!({"execution_id": id, "timestamp": ts, "ts": ts, "tool": tool,
+               "duration_ms": 100, "effective_input_tokens": 50, "task_id": task})
+    };
+    write_runs_log_rows(
+        &repo,
+        &[
+            stamp("2024-06-01T09:00:00Z", "a", "cxo", None),
+            stamp("2024-06-02T09:00:00Z", "b", "cxdiffsum", Some("task_001")),
+            stamp("2024-06-03T09:00:00Z", "c", "cxo", Some("task_001")),
+            stamp("2024-06-04T09:00:00Z", "d", "cxcommitjson", None),
+        ],
+    );
+
+    let v = json_out(&repo, &["metrics", "--tool", "cxo,cxdiffsum"]);
+    assert_eq!(v["runs"], 3);
+    assert_eq!(v["filter"]["tool"], "cxo,cxdiffsum");
+
+    let v = json_out(&repo, &["alert", "--json", "--task", "task_001"]);
+    assert_eq!(v["runs"], 2);
+    assert_eq!(v["filter"]["task"], "task_001");
+
+    let v = json_out(
+        &repo,
+        &[
+            "optimize",
+            "--json",
+            "--since",
+            "2024-06-02",
+            "--until",
+            "2024-06-03",
+        ],
+    );
+    assert_eq!(v["runs"], 2);
+    assert_eq!(v["filter"]["since"], "2024-06-02");
+
+    let v = json_out(&repo, &["promptlint", "--json", "--tool", "cxcommitjson"]);
+    assert_eq!(v["filter"]["tool"], "cxcommitjson");
+
+    let v = json_out(
+        &repo,
+        &[
+            "worklog",
+            "--json",
+            "--since",
+            "2024-06-03",
+            "--tool",
+            "cxo",
+        ],
+    );
+    assert_eq!(v["runs"], 1);
+    assert_eq!(v["since"], "2024-06-03");
+
+    let out = repo.run(&["alert", "--since", "yesterday"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("--since: invalid time 'yesterday'"));
+}
+
+#[test]
+fn profile_and_metrics_default_to_current_scope() {
+    let repo = TempRepo::new("cxrs-run-filter-scope");
+    let scoped = |id: &str, scope: Option<&str>| {
+        let mut v = row(&repo, id, "main", "");
+        if let Some(scope) = scope {
+            v["scope"] = json!(scope);
+        }
+        v
+    };
+    write_runs_log_rows(
+        &repo,
+        &[
+            scoped("a", Some("repo")),
+            scoped("b", Some("global")),
+            scoped("c", Some("global")),
+            scoped("d", None),
+        ],
+    );
+
+    let v = json_out(&repo, &["metrics"]);
+    assert_eq!(v["runs"], 2);
+    assert_eq!(v["scope"]["selected"], "repo");
+    assert_eq!(v["scope"]["excluded"], 2);
+    assert_eq!(v["filter"], Value::Null);
+
+    let v = json_out(&repo, &["metrics", "--scope", "global"]);
+    assert_eq!(v["runs"], 3);
+    let v = json_out(&repo, &["profile", "--json", "--scope", "all"]);
+    assert_eq!(v["runs"], 4);
+    assert_eq!(v["scope"]["excluded"], 0);
+
+    let text = stdout_str(&repo.run(&["profile"]));
+    assert!(
+        text.contains("Scope: repo (2 runs; 2 from other scopes excluded)"),
+        "{text}"
+    );
+
+    let out = repo.run(&["metrics", "--scope", "home"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("expected repo, global or all"));
+}
@@ -121,7 +121,7 @@ fn mock_schema_failure_creates_quarantine_logs() {
     );
     assert_eq!(
         out.status.code(),
-        Some(1),
+        Some(4),
         "expected schema failure; stdout={} stderr={}",
         stdout_str(&out),
         stderr_str(&out)
@@ -269,7 +269,7 @@ printf '%s\n' '{"content":[{"unexpected":"shape"}]}'
     );
     assert_eq!(
         out.status.code(),
-        Some(1),
+        Some(5),
         "expected failure; stdout={} stderr={}",
         stdout_str(&out),
         stderr_str(&out)
@@ -301,7 +301,7 @@ printf '%s\n' '{not-json'
     );
     assert_eq!(
         out.status.code(),
-        Some(1),
+        Some(5),
         "expected failure; stdout={} stderr={}",
         stdout_str(&out),
         stderr_str(&out)
@@ -363,7 +363,7 @@ printf '%s\n' '{"unexpected":"shape"}'
     );
     assert_eq!(
         out.status.code(),
-        Some(1),
+        Some(5),
         "expected failure; stdout={} stderr={}",
         stdout_str(&out),
         stderr_str(&out)
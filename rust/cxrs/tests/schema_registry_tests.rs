@@ -0,0 +1,121 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, read_json, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+use std::fs;
+
+#[test]
+fn schema_add_validate_lint_and_diff() {
+    let repo = TempRepo::new("cxrs-schema-registry");
+
+    let out = repo.run(&["schema", "lint"]);
+    assert!(out.status.success(), "{}", stdout_str(&out));
+    assert!(stdout_str(&out).contains("schemas: 8 failing: 0"));
+
+    let draft = repo.root.join("review.json");
+    fs::write(
+        &draft,
+        r#"{"$id": "https://example.com/review", "type": "object",
+            "required": ["verdict"], "properties": {"verdict": {"type": "string"}}}"#,
+    )
+    .expect("write draft");
+    let draft_arg = draft.to_string_lossy().to_string();
+    let out = repo.run(&["schema", "add", "review", "--from", &draft_arg]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let stored = read_json(&repo.root.join(".codex/schemas/review.schema.json"));
+    assert_eq!(stored["$id"], "cx://schemas/review.v1");
+    assert_eq!(stored["title"], "cx review");
+    let out = repo.run(&["schema", "add", "review", "--from", &draft_arg]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("--force"));
+
+    let show = stdout_str(&repo.run(&["schema", "show", "review"]));
+    assert!(show.contains("[cx://schemas/review.v1]"), "{show}");
+
+    let good = repo.root.join("good.json");
+    fs::write(&good, r#"{"verdict": "ship"}"#).expect("write instance");
+    let bad = repo.root.join("bad.json");
+    fs::write(&bad, r#"{"verdict": 3}"#).expect("write instance");
+    let out = repo.run(&["schema", "validate", "review", &good.to_string_lossy()]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let out = repo.run(&["schema", "validate", "review", &bad.to_string_lossy()]);
+    assert_eq!(out.status.code(), Some(4));
+    assert!(stderr_str(&out).contains("schema_validation_failed"));
+
+    let out = repo.run(&["schema", "diff", "next", "review", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let diff: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(diff["required_added"], json!(["verdict"]));
+    assert_eq!(diff["properties_removed"], json!(["commands"]));
+
+    fs::write(
+        repo.root.join(".codex/schemas/loose.schema.json"),
+        r#"{"type": "array"}"#,
+    )
+    .expect("write loose schema");
+    let out = repo.run(&["schema", "lint", "--json"]);
+    assert_eq!(out.status.code(), Some(1));
+    let lint: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    let loose = lint["schemas"]
+        .as_array()
+        .and_then(|a| a.iter().find(|s| s["name"] == "loose.schema.json"))
+        .expect("loose row");
+    assert_eq!(loose["ok"], false);
+    assert_eq!(loose["issues"].as_array().map(Vec::len), Some(4));
+}
+
+#[test]
+fn registry_mapping_changes_enforced_schema() {
+    let repo = TempRepo::new("cxrs-schema-map");
+    let strict = repo.root.join("strict.json");
+    fs::write(
+        &strict,
+        r#"{"type": "object", "required": ["commands", "reason"],
+            "properties": {"commands": {"type": "array"}, "reason": {"type": "string"}}}"#,
+    )
+    .expect("write strict schema");
+    let out = repo.run(&[
+        "schema",
+        "add",
+        "next-strict",
+        "--from",
+        &strict.to_string_lossy(),
+    ]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+
+    let out = repo.run(&["schema", "map", "cxrs_next", "missing"]);
+    assert_eq!(out.status.code(), Some(1));
+    let out = repo.run(&["schema", "map", "cxrs_next", "next-strict"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let registry = read_json(&repo.root.join(".codex/schemas/registry.json"));
+    assert_eq!(registry["tools"]["cxrs_next"], "next-strict.schema.json");
+
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"{\"commands\":[\"ls\"]}"}}'
+"#,
+    );
+    let out = repo.run(&["next", "echo", "hi"]);
+    assert_eq!(out.status.code(), Some(4), "stderr={}", stderr_str(&out));
+    let last = parse_jsonl(&repo.runs_log()).pop().expect("row");
+    assert_eq!(last["schema_name"], "next-strict.schema.json");
+
+    let mut row = last.clone();
+    row["tool"] = json!("review");
+    row["schema_name"] = Value::Null;
+    write_runs_log_rows(&repo, &[row]);
+    let out = repo.run(&["schema", "map", "review", "next-strict"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&repo.run(&["logs", "validate"]));
+    assert!(
+        text.contains(
+            "'review' row missing schema_name (registry maps it to next-strict.schema.json)"
+        ),
+        "{text}"
+    );
+
+    let out = repo.run(&["schema", "map", "cxrs_next", "--unset"]);
+    assert!(stdout_str(&out).contains("now next.schema.json"));
+    assert!(repo.run(&["schema", "lint"]).status.success());
+}
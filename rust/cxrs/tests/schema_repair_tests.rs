@@ -0,0 +1,83 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+
+fn mock_codex_reply(repo: &TempRepo, text: &str) {
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+echo call >> codex-calls
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":{text:?}}}}}'
+"#
+    ));
+}
+
+fn codex_calls(repo: &TempRepo) -> usize {
+    std::fs::read_to_string(repo.root.join("codex-calls"))
+        .map(|s| s.lines().count())
+        .unwrap_or(0)
+}
+
+#[test]
+fn prose_wrapped_reply_is_repaired_without_retry() {
+    let repo = TempRepo::new("cxrs-schema-repair");
+    mock_codex_reply(
+        &repo,
+        "Here you go:\n{\"commands\":[\"cargo test\"]}\nLet me know if you need more.",
+    );
+
+    let out = repo.run(&["next", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(stdout_str(&out).trim(), "cargo test");
+    assert_eq!(codex_calls(&repo), 1, "repair should not spend a retry");
+    assert!(!repo.quarantine_dir().exists());
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let row = rows.last().expect("row");
+    assert_eq!(row["schema_valid"], true);
+    assert_eq!(row["repair_applied"], true);
+    let steps: Vec<&str> = row["repair_steps"]
+        .as_array()
+        .expect("steps")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    assert_eq!(steps, vec!["strip_leading_text", "strip_trailing_text"]);
+
+    let out = repo.run(&["logs", "query", "repair_applied=true", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["matched"], 1);
+}
+
+#[test]
+fn clean_and_unrepairable_replies_are_untouched() {
+    let repo = TempRepo::new("cxrs-schema-repair");
+    mock_codex_reply(&repo, "{\"commands\":[\"ls\"]}");
+    let out = repo.run(&["next", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let rows = parse_jsonl(&repo.runs_log());
+    assert_eq!(rows.last().expect("row")["repair_applied"], false);
+    assert!(
+        rows.last()
+            .expect("row")
+            .get("repair_steps")
+            .is_none_or(Value::is_null)
+    );
+
+    // Valid JSON after repair but wrong shape: still quarantined, original reason kept.
+    mock_codex_reply(&repo, "```json\n{\"other\":1}\n```");
+    let out = repo.run(&["next", "echo", "hi"]);
+    assert!(!out.status.success());
+    let rows = parse_jsonl(&repo.runs_log());
+    let row = rows.last().expect("row");
+    assert_eq!(row["schema_valid"], false);
+    assert!(
+        row["schema_reason"]
+            .as_str()
+            .unwrap_or("")
+            .starts_with("invalid JSON"),
+        "{row}"
+    );
+    assert!(repo.quarantine_dir().exists());
+}
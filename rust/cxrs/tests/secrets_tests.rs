@@ -0,0 +1,52 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+use std::fs;
+
+const PASS: (&str, &str) = ("CX_SECRET_PASSPHRASE", "correct horse");
+
+#[test]
+fn secrets_round_trip_encrypted_under_passphrase() {
+    let repo = TempRepo::new("cxrs-secret");
+    let out = repo.run_with_env(&["secret", "set", "provider", "sk-live-123"], &[PASS]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stderr_str(&out).contains("visible to `ps`"));
+
+    let store = repo.home.join(".codex/secrets.json");
+    let raw = fs::read_to_string(&store).expect("read secrets store");
+    assert!(!raw.contains("sk-live-123"), "{raw}");
+    assert!(raw.contains("\"key_source\": \"passphrase\""));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&store).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let out = repo.run_with_env(&["secret", "get", "provider"], &[PASS]);
+    assert_eq!(stdout_str(&out), "sk-live-123\n");
+    let out = repo.run_with_env(&["secret", "list"], &[]);
+    assert_eq!(stdout_str(&out), "provider\n");
+
+    let out = repo.run_with_env(
+        &["secret", "get", "provider"],
+        &[("CX_SECRET_PASSPHRASE", "wrong")],
+    );
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("cannot decrypt secret 'provider'"));
+    let out = repo.run(&["secret", "get", "provider"]);
+    assert!(stderr_str(&out).contains("set CX_SECRET_PASSPHRASE"));
+    let out = repo.run_with_env(
+        &["secret", "set", "other", "x"],
+        &[("CX_SECRET_PASSPHRASE", "wrong")],
+    );
+    assert_eq!(out.status.code(), Some(1));
+
+    let out = repo.run_with_env(&["secret", "rm", "provider"], &[PASS]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let out = repo.run_with_env(&["secret", "get", "provider"], &[PASS]);
+    assert!(stderr_str(&out).contains("secret 'provider' not found"));
+
+    assert_eq!(repo.run(&["secret", "get", "a/b"]).status.code(), Some(2));
+    assert_eq!(repo.run(&["secret", "bogus"]).status.code(), Some(2));
+}
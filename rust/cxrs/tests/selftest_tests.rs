@@ -0,0 +1,66 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl};
+use serde_json::Value;
+use std::fs;
+
+fn stdout_str(out: &std::process::Output) -> String {
+    String::from_utf8_lossy(&out.stdout).to_string()
+}
+
+#[test]
+fn selftest_passes_every_stage_offline() {
+    let repo = TempRepo::new("cxrs-selftest");
+    // A codex that would fail every call proves no stage reaches it.
+    repo.write_mock_codex("#!/usr/bin/env bash\nexit 9\n");
+    let out = repo.run(&["selftest", "--json"]);
+    assert_eq!(out.status.code(), Some(0), "{}", stdout_str(&out));
+    let doc: Value = serde_json::from_slice(&out.stdout).expect("selftest json");
+    assert_eq!(doc["status"], "pass");
+    let stages = doc["stages"].as_array().expect("stages");
+    let names: Vec<&str> = stages.iter().filter_map(|s| s["name"].as_str()).collect();
+    for want in [
+        "next",
+        "fix-run",
+        "commitjson",
+        "stage",
+        "quarantine",
+        "log-contract",
+    ] {
+        assert!(names.contains(&want), "missing stage {want}: {names:?}");
+    }
+    assert!(stages.iter().all(|s| s["status"] == "pass"), "{doc}");
+    assert!(
+        !repo.runs_log().exists(),
+        "selftest must not write to the caller's run log"
+    );
+}
+
+#[test]
+fn mock_backend_answers_from_fixtures_or_schema() {
+    let repo = TempRepo::new("cxrs-mock-backend");
+    let fixtures = repo.root.join("fixtures");
+    fs::create_dir_all(&fixtures).expect("fixtures dir");
+    fs::write(
+        fixtures.join("next.json"),
+        r#"{"commands":["git status --short"]}"#,
+    )
+    .expect("fixture");
+    let out = repo.run_with_env(
+        &["next", "echo", "hi"],
+        &[
+            ("CX_LLM_BACKEND", "mock"),
+            ("CX_MOCK_FIXTURES", fixtures.to_str().expect("utf8")),
+        ],
+    );
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(stdout_str(&out).trim(), "git status --short");
+
+    let out = repo.run_with_env(&["fix-run", "false"], &[("CX_LLM_BACKEND", "mock")]);
+    let text = stdout_str(&out);
+    assert!(text.contains("mock analysis"), "{text}");
+    let rows = parse_jsonl(&repo.runs_log());
+    let last = rows.last().expect("run row");
+    assert_eq!(last["backend_used"], "mock");
+    assert_eq!(last["schema_valid"], true);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{TempRepo, git, stderr_str, stdout_str};
+use serde_json::{Value, json};
+use std::fs;
+
+fn numbered(lines: usize, edit: &[usize]) -> String {
+    (1..=lines)
+        .map(|i| {
+            if edit.contains(&i) {
+                format!("line {i} changed\n")
+            } else {
+                format!("line {i}\n")
+            }
+        })
+        .collect()
+}
+
+/// Committed `a.rs`, then two separate edits to it and a new `c.txt`.
+fn dirty_repo(repo: &TempRepo) {
+    fs::write(repo.root.join(".gitignore"), ".codex/\n.git-template/\n").expect("gitignore");
+    fs::write(repo.root.join("a.rs"), numbered(20, &[])).expect("write a.rs");
+    git(repo, &["add", "-A"]);
+    git(
+        repo,
+        &[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "-qm",
+            "base",
+        ],
+    );
+    fs::write(repo.root.join("a.rs"), numbered(20, &[1, 20])).expect("edit a.rs");
+    fs::write(repo.root.join("c.txt"), "new\n").expect("write c.txt");
+}
+
+fn mock_plan(repo: &TempRepo) {
+    let plan = json!({"groups": [
+        {
+            "subject": "feat: add c and the head edit",
+            "rationale": "new file plus its caller",
+            "files": ["c.txt", "ghost.rs"],
+            "hunks": [{"file": "a.rs", "header": "@@ -1,4 +1,4 @@ line 1"}]
+        },
+        {
+            "subject": "fix: tail edit",
+            "rationale": "unrelated",
+            "files": [],
+            "hunks": [{"file": "a.rs", "header": "@@ -17,4 +17,4 @@"}]
+        }
+    ]});
+    let text = serde_json::to_string(&plan.to_string()).expect("quote");
+    repo.write_mock_codex(&format!(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{{"type":"item.completed","item":{{"type":"agent_message","text":{text}}}}}'
+"#
+    ));
+}
+
+#[test]
+fn stage_proposes_groups_and_applies_one() {
+    let repo = TempRepo::new("cxrs-stage");
+    dirty_repo(&repo);
+    mock_plan(&repo);
+
+    let out = repo.run(&["stage"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("[1] feat: add c and the head edit"), "{text}");
+    assert!(text.contains("git add -- c.txt\n"), "{text}");
+    assert!(
+        text.contains("git add -p -- a.rs  # hunks: @@ -1,4 +1,4 @@"),
+        "{text}"
+    );
+    assert!(text.contains("apply one: cxrs stage --apply N"), "{text}");
+    assert!(stderr_str(&out).contains("dropping unknown path 'ghost.rs'"));
+
+    let out = repo.run(&["stage", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["contract_version"], "stage.v1");
+    assert_eq!(v["groups"][1]["hunks"][0]["header"], "@@ -17,4 +17,4 @@");
+    assert_eq!(v["unassigned"], json!([]));
+
+    let out = repo.run(&["stage", "--apply", "1"]);
+    assert_eq!(out.status.code(), Some(2), "no terminal, no --yes");
+    let out = repo.run(&["stage", "--apply", "1", "--yes"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("staged group 1: feat: add c and the head edit"));
+    assert_eq!(
+        git(&repo, &["diff", "--cached", "--name-only"]),
+        "a.rs\nc.txt\n"
+    );
+    let cached = git(&repo, &["diff", "--cached", "--", "a.rs"]);
+    assert!(cached.contains("+line 1 changed"), "{cached}");
+    assert!(!cached.contains("+line 20 changed"), "{cached}");
+
+    let out = repo.run(&["stage", "--apply", "3", "--yes"]);
+    assert!(!out.status.success());
+    assert!(stderr_str(&out).contains("no group 3"));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+const SLOW_CODEX: &str = r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"first"}}'
+sleep 2
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"second"}}'
+printf '%s\n' '{"type":"turn.completed","usage":{"input_tokens":40,"cached_input_tokens":0,"output_tokens":5}}'
+"#;
+
+#[test]
+fn stream_prints_text_before_backend_exits() {
+    let repo = TempRepo::new("cxrs-stream");
+    repo.write_mock_codex(SLOW_CODEX);
+    let path = format!(
+        "{}:{}",
+        repo.mock_bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let started = Instant::now();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cxrs"))
+        .args(["cxo", "--stream", "echo", "hi"])
+        .current_dir(&repo.root)
+        .env("HOME", &repo.home)
+        .env("PATH", path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn cxo");
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout"));
+    let mut first = String::new();
+    stdout.read_line(&mut first).expect("first line");
+    assert_eq!(first, "first\n");
+    assert!(started.elapsed() < Duration::from_millis(1500));
+    let mut rest = String::new();
+    stdout.read_to_string(&mut rest).expect("rest");
+    assert_eq!(rest, "second\n");
+    assert!(child.wait().expect("wait").success());
+
+    let row = parse_jsonl(&repo.runs_log()).pop().expect("run row");
+    assert_eq!(row.get("input_tokens").and_then(Value::as_u64), Some(40));
+}
+
+#[test]
+fn without_stream_output_is_unchanged() {
+    let repo = TempRepo::new("cxrs-stream");
+    repo.write_mock_codex(SLOW_CODEX.replace("sleep 2", "").as_str());
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(stdout_str(&out), "second\n");
+
+    let out = repo.run_with_env(&["cxj", "echo", "hi"], &[("CX_STREAM", "1")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(stdout_str(&out).lines().count(), 3);
+
+    let out = repo.run_with_env(
+        &["cx", "cxo", "--stream", "echo", "hi"],
+        &[
+            ("CX_PROVIDER_ADAPTER", "mock"),
+            ("CX_MOCK_PLAIN_RESPONSE", "mocked"),
+        ],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(stdout_str(&out), "mocked\n");
+}
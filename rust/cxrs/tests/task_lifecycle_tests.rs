@@ -44,3 +44,339 @@ fn task_lifecycle_add_claim_complete() {
         .expect("task exists");
     assert_eq!(task.get("status").and_then(Value::as_str), Some("complete"));
 }
+
+#[test]
+fn task_claims_track_assignees_and_reap_stale() {
+    let repo = TempRepo::new("cxrs-it");
+    let add = repo.run(&["task", "add", "Fix flaky test", "--role", "tester"]);
+    let id = stdout_str(&add).trim().to_string();
+    let other = repo.run(&["task", "add", "Write docs", "--role", "implementer"]);
+    let other = stdout_str(&other).trim().to_string();
+
+    let claim = repo.run(&["task", "claim", &id, "--as", "agent-a"]);
+    assert!(claim.status.success(), "stderr={}", stderr_str(&claim));
+    assert!(stdout_str(&claim).contains("assignee: agent-a"));
+    let again = repo.run(&["task", "claim", &id, "--as", "agent-b"]);
+    assert_eq!(again.status.code(), Some(1));
+    assert!(
+        stderr_str(&again).contains("already claimed by agent-a"),
+        "{}",
+        stderr_str(&again)
+    );
+    assert!(
+        repo.run(&["task", "claim", &id, "--as", "agent-a"])
+            .status
+            .success()
+    );
+
+    let list = repo.run(&["task", "list", "--assignee", "agent-a"]);
+    let text = stdout_str(&list);
+    assert!(
+        text.contains(&format!("{id} | tester | in_progress | agent-a")),
+        "{text}"
+    );
+    assert!(!text.contains(&other), "{text}");
+
+    let fresh = repo.run(&["task", "reap", "--older-than", "2h"]);
+    assert!(stdout_str(&fresh).contains("No stale claims."));
+    let mut tasks = read_json(&repo.tasks_file());
+    for t in tasks.as_array_mut().expect("tasks array") {
+        if t["id"] == id.as_str() {
+            t["claimed_at"] = Value::from("2020-01-01T00:00:00Z");
+        }
+    }
+    std::fs::write(repo.tasks_file(), tasks.to_string()).expect("write tasks");
+    let dry = repo.run(&["task", "reap", "--older-than", "2h", "--dry-run"]);
+    assert!(stdout_str(&dry).contains(&format!("would release {id}: claim by agent-a")));
+    let reap = repo.run(&["task", "reap", "--older-than", "2h"]);
+    assert!(reap.status.success(), "stderr={}", stderr_str(&reap));
+    let tasks = read_json(&repo.tasks_file());
+    let task = tasks
+        .as_array()
+        .expect("tasks array")
+        .iter()
+        .find(|t| t["id"] == id.as_str())
+        .expect("task exists");
+    assert_eq!(task["status"], "pending");
+    assert!(task.get("assignee").is_none());
+    assert!(
+        repo.run(&["task", "claim", &id, "--as", "agent-b"])
+            .status
+            .success()
+    );
+}
+
+#[test]
+fn task_watch_once_runs_ready_role_tasks() {
+    let repo = TempRepo::new("cxrs-it");
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+printf '%s\n' '{"type":"turn.completed","usage":{"input_tokens":20,"cached_input_tokens":2,"output_tokens":5}}'
+"#,
+    );
+    let first = repo.run(&["task", "add", "cxo echo first", "--role", "implementer"]);
+    let first = stdout_str(&first).trim().to_string();
+    let tester = repo.run(&["task", "add", "cxo echo tests", "--role", "tester"]);
+    let tester = stdout_str(&tester).trim().to_string();
+    let second = repo.run(&["task", "add", "cxo echo second", "--role", "implementer"]);
+    let second = stdout_str(&second).trim().to_string();
+    let mut tasks = read_json(&repo.tasks_file());
+    for t in tasks.as_array_mut().expect("tasks array") {
+        if t["id"] == second.as_str() {
+            t["depends_on"] = serde_json::json!([first]);
+        }
+    }
+    std::fs::write(repo.tasks_file(), tasks.to_string()).expect("write tasks");
+
+    let out = repo.run(&[
+        "task",
+        "watch",
+        "--role",
+        "implementer",
+        "--as",
+        "worker-1",
+        "--interval",
+        "0.1",
+        "--once",
+    ]);
+    let text = stdout_str(&out);
+    assert!(
+        out.status.success(),
+        "stdout={text} stderr={}",
+        stderr_str(&out)
+    );
+    assert!(
+        text.contains("task watch summary: complete=2, failed=0"),
+        "{text}"
+    );
+
+    let tasks = read_json(&repo.tasks_file());
+    let by_id = |id: &str| {
+        tasks
+            .as_array()
+            .expect("tasks array")
+            .iter()
+            .find(|t| t["id"] == id)
+            .cloned()
+            .expect("task exists")
+    };
+    for id in [&first, &second] {
+        let task = by_id(id);
+        assert_eq!(task["status"], "complete", "{task}");
+        assert_eq!(task["assignee"], "worker-1", "{task}");
+    }
+    assert_eq!(by_id(&tester)["status"], "pending");
+}
+
+#[test]
+fn task_template_apply_instantiates_subtree() {
+    let repo = TempRepo::new("cxrs-it");
+    let add = |args: &[&str]| {
+        let out = repo.run(args);
+        assert!(out.status.success(), "stderr={}", stderr_str(&out));
+        stdout_str(&out).trim().to_string()
+    };
+    let parent = add(&["task", "add", "Add login", "--role", "architect"]);
+    let imp = add(&[
+        "task",
+        "add",
+        "Implement Add login",
+        "--parent",
+        &parent,
+        "--depends-on",
+        &parent,
+    ]);
+    add(&[
+        "task",
+        "add",
+        "Test Add login",
+        "--role",
+        "tester",
+        "--parent",
+        &parent,
+        "--depends-on",
+        &imp,
+    ]);
+
+    let out = repo.run(&["task", "template", "save", "feature", "--from", &parent]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let tpl = read_json(&repo.root.join(".codex/task_templates/feature.json"));
+    assert_eq!(tpl["params"], serde_json::json!(["objective"]));
+    assert_eq!(tpl["tasks"][2]["objective"], "Test {{objective}}");
+    assert_eq!(tpl["tasks"][2]["depends_on"], serde_json::json!(["t2"]));
+    let out = repo.run(&["task", "template", "save", "feature", "--from", &parent]);
+    assert!(stderr_str(&out).contains("--force"));
+
+    let out = repo.run(&[
+        "task",
+        "template",
+        "apply",
+        "feature",
+        "--param",
+        "objective=Add SSO",
+    ]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).starts_with("parent: task_004\n"));
+    let tasks = read_json(&repo.tasks_file());
+    let tasks = tasks.as_array().expect("tasks array");
+    assert_eq!(tasks.len(), 6);
+    assert_eq!(tasks[5]["objective"], "Test Add SSO");
+    assert_eq!(tasks[5]["parent_id"], "task_004");
+    assert_eq!(tasks[5]["depends_on"], serde_json::json!(["task_005"]));
+    assert_eq!(tasks[5]["status"], "pending");
+
+    let out = repo.run(&["task", "template", "apply", "feature"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("missing --param objective"));
+    let list = stdout_str(&repo.run(&["task", "template", "list"]));
+    assert!(
+        list.contains("feature | 3 | objective | task_001"),
+        "{list}"
+    );
+}
+
+#[test]
+fn task_run_result_reaches_show_and_worklog() {
+    let repo = TempRepo::new("cxrs-it");
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"Drafted the parser plan.\nNext: write tests."}}'
+"#,
+    );
+    let add = repo.run(&[
+        "task",
+        "add",
+        "Plan parser hardening",
+        "--role",
+        "architect",
+    ]);
+    let id = stdout_str(&add).trim().to_string();
+    let run = repo.run(&["task", "run", &id]);
+    assert!(run.status.success(), "stderr={}", stderr_str(&run));
+
+    let tasks = read_json(&repo.tasks_file());
+    let task = &tasks[0];
+    let result_ref = format!(".codex/task_results/{id}.md");
+    assert_eq!(task["result_ref"], result_ref.as_str());
+    let eid = task["execution_id"].as_str().expect("execution_id");
+    assert!(repo.root.join(&result_ref).is_file());
+
+    let show = stdout_str(&repo.run(&["task", "show", &id, "--result"]));
+    assert!(
+        show.starts_with(&format!("# {id}: Plan parser hardening\n")),
+        "{show}"
+    );
+    assert!(show.contains(&format!("- execution_id: {eid}\n")), "{show}");
+    assert!(show.contains("Next: write tests."), "{show}");
+
+    let list = stdout_str(&repo.run(&["task", "list", "--verbose"]));
+    assert!(
+        list.contains("  result: Drafted the parser plan.\n"),
+        "{list}"
+    );
+    assert!(!stdout_str(&repo.run(&["task", "list"])).contains("result:"));
+
+    let out = repo.run(&["worklog", "--json"]);
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["task_results"][0]["task_id"], id.as_str());
+    assert_eq!(v["task_results"][0]["status"], "complete");
+    assert_eq!(v["task_results"][0]["summary"], "Drafted the parser plan.");
+    let md = stdout_str(&repo.run(&["worklog"]));
+    assert!(
+        md.contains(&format!("- {id} (complete): Drafted the parser plan.")),
+        "{md}"
+    );
+
+    let add = repo.run(&["task", "add", "Not run yet"]);
+    let other = stdout_str(&add).trim().to_string();
+    let out = repo.run(&["task", "show", &other, "--result"]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("no result recorded"));
+}
+
+#[test]
+fn task_export_import_round_trips_and_merges() {
+    let repo = TempRepo::new("cxrs-it");
+    let parent = stdout_str(&repo.run(&["task", "add", "Ship release", "--role", "architect"]))
+        .trim()
+        .to_string();
+    repo.run(&[
+        "task",
+        "add",
+        "Write notes",
+        "--parent",
+        &parent,
+        "--role",
+        "doc",
+    ]);
+    repo.run(&["task", "complete", &parent]);
+
+    let md = stdout_str(&repo.run(&["task", "export"]));
+    assert_eq!(
+        md,
+        "- [x] task_001 (architect, complete): Ship release\n  - [ ] task_002 (doc, pending): Write notes\n"
+    );
+    let before = std::fs::read_to_string(repo.tasks_file()).expect("tasks");
+    let md_file = repo.root.join("tasks.md");
+    std::fs::write(&md_file, &md).expect("write md");
+    let out = repo.run(&["task", "import", &md_file.to_string_lossy()]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("created: 0"));
+    assert!(stdout_str(&out).contains("updated: 0"));
+    assert_eq!(
+        std::fs::read_to_string(repo.tasks_file()).expect("tasks"),
+        before
+    );
+
+    std::fs::write(
+        &md_file,
+        "## PR checklist\n- [x] task_002: Write release notes\n- [ ] Tag the release\n  - [ ] Publish binaries\n",
+    )
+    .expect("write md");
+    let out = repo.run(&["task", "import", &md_file.to_string_lossy(), "--dry-run"]);
+    assert!(stdout_str(&out).contains("dry-run created: 2 (task_003, task_004)"));
+    assert_eq!(
+        std::fs::read_to_string(repo.tasks_file()).expect("tasks"),
+        before
+    );
+    let out = repo.run(&["task", "import", &md_file.to_string_lossy()]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let tasks = read_json(&repo.tasks_file());
+    assert_eq!(tasks[1]["objective"], "Write release notes");
+    assert_eq!(tasks[1]["status"], "complete");
+    assert_eq!(tasks[1]["parent_id"], "task_001");
+    assert_eq!(tasks[1]["role"], "doc");
+    assert_eq!(tasks[3]["parent_id"], "task_003");
+
+    let json_out = stdout_str(&repo.run(&["task", "export", "--format", "json"]));
+    let json_file = repo.root.join("tasks.json");
+    std::fs::write(
+        &json_file,
+        json_out.replace("Publish binaries", "Publish artifacts"),
+    )
+    .expect("write json");
+    let out = repo.run(&["task", "import", &json_file.to_string_lossy()]);
+    assert!(
+        stdout_str(&out).contains("updated: 1 (task_004)"),
+        "{}",
+        stdout_str(&out)
+    );
+    assert_eq!(
+        read_json(&repo.tasks_file())[3]["objective"],
+        "Publish artifacts"
+    );
+
+    std::fs::write(&json_file, r#"[{"id":"task_001","status":"done"}]"#).expect("write json");
+    let out = repo.run(&["task", "import", &json_file.to_string_lossy()]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("invalid status 'done'"));
+    assert_eq!(
+        repo.run(&["task", "export", "--format", "csv"])
+            .status
+            .code(),
+        Some(2)
+    );
+}
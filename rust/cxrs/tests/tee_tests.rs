@@ -0,0 +1,55 @@
+mod common;
+
+use common::{TempRepo, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+const STAT_FIELDS: &[&str] = &[
+    "system_output_len_raw",
+    "system_output_len_clipped",
+    "stdout_len_raw",
+    "stderr_len_raw",
+    "clip_mode",
+];
+
+#[test]
+fn tee_shows_output_live_keeping_capture() {
+    let repo = TempRepo::new("cxrs-tee");
+    repo.write_mock_codex(
+        r#"#!/usr/bin/env bash
+cat > /dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"summary: build broke"}}'
+"#,
+    );
+    fs::write(
+        repo.root.join("build.sh"),
+        "echo 'compiling foo'\necho 'error: linker failed' >&2\nexit 7\n",
+    )
+    .expect("write script");
+
+    let teed = repo.run(&["cxo", "--tee", "bash", "build.sh"]);
+    assert_eq!(teed.status.code(), Some(7));
+    let err = stderr_str(&teed);
+    assert!(err.contains("compiling foo"), "{err}");
+    assert!(err.contains("error: linker failed"), "{err}");
+    // stdout stays reserved for the model's answer.
+    assert_eq!(stdout_str(&teed).trim(), "summary: build broke");
+
+    let quiet = repo.run(&["cxo", "bash", "build.sh"]);
+    assert_eq!(quiet.status.code(), Some(7));
+    assert!(!stderr_str(&quiet).contains("compiling foo"));
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let [.., teed_row, quiet_row] = rows.as_slice() else {
+        panic!("expected two run rows");
+    };
+    for k in STAT_FIELDS {
+        assert_eq!(teed_row.get(*k), quiet_row.get(*k), "{k}");
+        assert!(teed_row.get(*k).is_some_and(|v| !v.is_null()), "{k}");
+    }
+
+    let fix = repo.run_with_env(&["fix", "bash", "build.sh"], &[("CX_TEE", "1")]);
+    assert!(stderr_str(&fix).contains("compiling foo"));
+    let row = parse_jsonl(&repo.runs_log()).pop().expect("fix row");
+    assert_eq!(row.get("tool").and_then(Value::as_str), Some("cxfix"));
+}
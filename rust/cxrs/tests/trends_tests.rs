@@ -0,0 +1,50 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{TempRepo, stderr_str, stdout_str, write_runs_log_rows};
+use serde_json::{Value, json};
+
+fn row(id: &str, hours_ago: i64, schema_valid: bool) -> Value {
+    let ts = (Utc::now() - Duration::hours(hours_ago)).to_rfc3339();
+    json!({"execution_id": id, "timestamp": ts, "ts": ts, "tool": "cxo", "duration_ms": 400,
+           "input_tokens": 1000, "cached_input_tokens": 500, "effective_input_tokens": 500,
+           "output_tokens": 20, "schema_valid": schema_valid})
+}
+
+#[test]
+fn trends_buckets_recent_runs_skips_old() {
+    let repo = TempRepo::new("cxrs-trends");
+    write_runs_log_rows(
+        &repo,
+        &[
+            row("t1", 0, true),
+            row("t2", 0, false),
+            row("t3", 24 * 3, true),
+            row("t4", 24 * 60, true),
+        ],
+    );
+
+    let out = repo.run(&["trends", "--json"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["contract_version"], "trends.v1");
+    assert_eq!(v["bucket"], "day");
+    assert_eq!(v["runs"], 3);
+    let buckets = v["buckets"].as_array().expect("buckets");
+    assert_eq!(buckets.len(), 31);
+    let today = buckets.last().expect("today");
+    assert_eq!(today["runs"], 2);
+    assert_eq!(today["schema_failure_rate"], 0.5);
+    assert_eq!(today["cache_hit_rate"], 0.5);
+
+    let out = repo.run(&["trends", "--bucket", "hour", "--last", "6h"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("== cxrs trends (hour buckets, last 6h) =="));
+    assert!(text.contains("  runs         ▁▁▁▁▁▁█"), "{text}");
+    assert!(text.contains("total runs: 2"), "{text}");
+
+    let out = repo.run(&["trends", "--bucket", "week"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr_str(&out).contains("invalid --bucket 'week'"));
+}
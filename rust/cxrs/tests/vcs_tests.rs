@@ -0,0 +1,86 @@
+mod common;
+
+use common::{TempRepo, mock_recorder, parse_jsonl, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+const JJ_PATCH: &str = "diff --git a/src/billing.rs b/src/billing.rs
+--- a/src/billing.rs
++++ b/src/billing.rs
+@@ -1 +1,2 @@
+-fn charge() {}
++fn charge(amount: u64) {}
++fn refund() {}
+";
+
+fn jj_repo() -> TempRepo {
+    let repo = TempRepo::new("cxrs-vcs-jj");
+    fs::remove_dir_all(repo.root.join(".git")).expect("drop git dir");
+    fs::create_dir_all(repo.root.join(".jj")).expect("jj marker");
+    repo.write_mock(
+        "jj",
+        &format!(
+            r#"#!/usr/bin/env bash
+case "$1" in
+  diff) cat <<'EOF'
+{JJ_PATCH}EOF
+  ;;
+  log) case "$*" in *commit_id*) echo 1a2b3c4d ;; *) echo main ;; esac ;;
+esac
+"#
+        ),
+    );
+    repo
+}
+
+#[test]
+fn jj_diff_feeds_commitjson_and_run_log() {
+    let repo = jj_repo();
+    let prompt_file = mock_recorder(
+        &repo,
+        r#"{"subject":"add refunds","body":[],"breaking":false,"scope":null,"tests":[]}"#,
+    );
+
+    let out = repo.run(&["commitjson"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let prompt = fs::read_to_string(&prompt_file).expect("captured prompt");
+    assert!(prompt.contains("+fn refund() {}"), "{prompt}");
+    assert!(prompt.contains("- src/billing.rs [Rust] +2 -1"), "{prompt}");
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("commitjson output");
+    assert_eq!(v["scope"], "billing");
+
+    let rows = parse_jsonl(&repo.runs_log());
+    let row = rows.last().expect("run row");
+    assert_eq!(row["vcs"], "jj");
+    assert_eq!(row["git_branch"], "main");
+    assert_eq!(row["git_head"], "1a2b3c4d");
+
+    let out = repo.run(&["where", "--json"]);
+    let doc: Value = serde_json::from_str(&stdout_str(&out)).expect("where json");
+    assert_eq!(doc["vcs"], "jj");
+}
+
+#[test]
+fn plain_codex_dirs_refuse_diffsum_clearly() {
+    let repo = TempRepo::new("cxrs-vcs-plain");
+    fs::remove_dir_all(repo.root.join(".git")).expect("drop git dir");
+
+    let out = repo.run_with_env(
+        &["diffsum"],
+        &[("CX_PROJECT_ROOT", repo.root.to_str().expect("utf8 root"))],
+    );
+    assert!(!out.status.success());
+    let err = stderr_str(&out);
+    assert!(err.contains("no version control at"), "{err}");
+
+    let out = repo.run(&["where", "--json"]);
+    let doc: Value = serde_json::from_str(&stdout_str(&out)).expect("where json");
+    assert_eq!(doc["vcs"], "none");
+    assert_eq!(
+        doc["repo_root"]
+            .as_str()
+            .map(fs::canonicalize)
+            .and_then(Result::ok),
+        fs::canonicalize(&repo.root).ok()
+    );
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+use serde_json::Value;
+use std::fs;
+
+const MOCK_CODEX: &str = r#"#!/usr/bin/env bash
+cat >/dev/null
+printf '%s\n' '{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}'
+"#;
+
+#[test]
+fn verbose_diagnostics_go_to_stderr_only() {
+    let repo = TempRepo::new("cxrs-verbosity");
+    repo.write_mock_codex(MOCK_CODEX);
+    let out = repo.run(&["-v", "cxo", "echo", "hi"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert_eq!(stdout_str(&out), "ok\n");
+    let err = stderr_str(&out);
+    assert!(err.contains("cxrs: level=verbose event=capture cmd=\"echo hi\" provider=native"));
+    assert!(err.contains("event=clip mode="));
+    assert!(err.contains("event=backend adapter=codex-cli backend=codex"));
+    assert!(err.contains("event=timing cmd=cxo exit=0 duration_ms="));
+
+    let out = repo.run(&["cxo", "echo", "hi"]);
+    assert_eq!(stderr_str(&out), "");
+}
+
+#[test]
+fn quiet_hides_warnings_and_json_format_parses() {
+    let repo = TempRepo::new("cxrs-verbosity");
+    fs::create_dir_all(repo.root.join(".codex")).expect("mkdir");
+    fs::write(repo.root.join(".codex/config.toml"), "[capture]\nx = 1\n").expect("config");
+
+    let out = repo.run(&["budget"]);
+    assert!(stderr_str(&out).contains("ignoring config file"));
+    let out = repo.run(&["-q", "budget"]);
+    assert!(out.status.success());
+    assert_eq!(stderr_str(&out), "");
+
+    let out = repo.run_with_env(&["-vv", "version"], &[("CX_LOG_FORMAT", "json")]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let lines: Vec<Value> = stderr_str(&out)
+        .lines()
+        .map(|l| serde_json::from_str(l).expect("json diag line"))
+        .collect();
+    assert_eq!(lines[0]["event"], "warning");
+    assert!(
+        lines
+            .iter()
+            .any(|v| v["event"] == "timing" && v["cmd"] == "version")
+    );
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str, write_runs_log_rows};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::Duration;
+
+fn run_row(id: &str, duration_ms: u64) -> serde_json::Value {
+    serde_json::json!({
+        "execution_id": id, "timestamp": "2026-01-01T00:00:00Z", "command": "cxo",
+        "tool": "cxo", "backend_used": "codex", "duration_ms": duration_ms,
+        "input_tokens": 100, "cached_input_tokens": 50, "effective_input_tokens": 50
+    })
+}
+
+#[test]
+fn watch_once_reports_alerts_from_start() {
+    let repo = TempRepo::new("cxrs-watch");
+    write_runs_log_rows(&repo, &[run_row("w1", 100), run_row("w2", 20000)]);
+
+    let out = repo.run(&["watch", "--from-start", "--once"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let stdout = stdout_str(&out);
+    assert!(stdout.contains("ALERT cx alert: cxo max_ms exceeded (20000ms > 12000ms)"));
+    assert!(stdout.contains("runs=2 alerts=1 avg_ms=10050"), "{stdout}");
+    assert!(stdout.contains("cache_hit=50%"));
+}
+
+#[test]
+fn watch_tails_new_rows_until_sigterm() {
+    let repo = TempRepo::new("cxrs-watch");
+    write_runs_log_rows(&repo, &[run_row("old", 50000)]);
+
+    let child = Command::new(env!("CARGO_BIN_EXE_cxrs"))
+        .args(["watch", "--interval", "0.1"])
+        .current_dir(&repo.root)
+        .env("HOME", &repo.home)
+        .env("CXALERT_MAX_MS", "1000")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn watch");
+    sleep(Duration::from_millis(400));
+    let mut log = OpenOptions::new()
+        .append(true)
+        .open(repo.runs_log())
+        .expect("open runs log");
+    writeln!(log, "{}", run_row("new", 3000)).expect("append row");
+    sleep(Duration::from_millis(600));
+    let killed = Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .expect("kill");
+    assert!(killed.success());
+
+    let out = child.wait_with_output().expect("wait watch");
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let stdout = stdout_str(&out);
+    assert!(
+        stdout.contains("max_ms exceeded (3000ms > 1000ms)"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("50000ms"), "{stdout}");
+    assert!(stdout.contains("== cxrs watch stopped =="));
+    assert!(stdout.contains("runs=1 alerts=1"));
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{TempRepo, stderr_str, stdout_str};
+use serde_json::Value;
+
+fn path_entry<'a>(doc: &'a Value, name: &str) -> &'a Value {
+    doc["paths"]
+        .as_array()
+        .expect("paths array")
+        .iter()
+        .find(|p| p["name"] == name)
+        .unwrap_or_else(|| panic!("missing path entry {name}: {doc}"))
+}
+
+#[test]
+fn where_json_reports_reason_and_existence() {
+    let repo = TempRepo::new("cxrs-where");
+    let out = repo.run(&["where", "--json", "logs", "nope"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let doc: Value = serde_json::from_str(&stdout_str(&out)).expect("where json");
+    assert_eq!(doc["contract_version"], "where.v1");
+
+    let log = path_entry(&doc, "log_file");
+    assert_eq!(log["reason"], "repo_root");
+    assert_eq!(log["kind"], "file");
+    assert!(
+        log["path"]
+            .as_str()
+            .unwrap()
+            .ends_with(".codex/cxlogs/runs.jsonl")
+    );
+    assert_eq!(log["writable"], true);
+
+    let global = path_entry(&doc, "global_state_file");
+    assert_eq!(global["reason"], "home");
+    assert_eq!(
+        global["path"].as_str().unwrap(),
+        repo.home.join(".codex/state.json").display().to_string()
+    );
+    assert_eq!(global["exists"], false);
+    assert_eq!(path_entry(&doc, "schema_dir")["exists"], true);
+
+    assert_eq!(doc["routes"][0]["route"], "rust");
+    assert_eq!(doc["routes"][1]["route"], "unknown");
+}
+
+#[test]
+fn global_paths_prefer_cx_home_legacy_xdg() {
+    let repo = TempRepo::new("cxrs-xdg");
+    let xdg_state = repo.home.join("xdg-state");
+    let xdg_config = repo.home.join("xdg-config");
+    let xdg = [
+        ("XDG_STATE_HOME", xdg_state.to_str().unwrap()),
+        ("XDG_CONFIG_HOME", xdg_config.to_str().unwrap()),
+    ];
+    let out = repo.run_with_env(&["state", "set", "k", "1", "--global"], &xdg);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(xdg_state.join("cx/state.json").is_file());
+    let out = repo.run_with_env(
+        &["config", "set", "capture.budget_chars", "20000", "--global"],
+        &xdg,
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(xdg_config.join("cx/config.toml").is_file());
+    let out = repo.run_with_env(&["where", "--json"], &xdg);
+    let doc: Value = serde_json::from_str(&stdout_str(&out)).expect("where json");
+    assert_eq!(
+        path_entry(&doc, "global_state_file")["reason"],
+        "env:XDG_STATE_HOME"
+    );
+    assert_eq!(
+        path_entry(&doc, "global_config_file")["reason"],
+        "env:XDG_CONFIG_HOME"
+    );
+
+    let cx_home = repo.home.join("cxhome");
+    let out = repo.run_with_env(
+        &["state", "set", "k", "2", "--global"],
+        &[("CX_HOME", cx_home.to_str().unwrap())],
+    );
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    assert!(cx_home.join("state.json").is_file());
+
+    // An existing ~/.codex keeps precedence over XDG; doctor --fix explains the move.
+    std::fs::create_dir_all(repo.home.join(".codex")).unwrap();
+    let out = repo.run_with_env(&["state", "get", "k", "--global"], &xdg);
+    assert_eq!(out.status.code(), Some(1), "legacy dir starts empty");
+    let out = repo.run_with_env(&["doctor", "--fix"], &xdg);
+    let text = stdout_str(&out);
+    assert!(
+        text.contains("migrate: XDG base dirs are set but"),
+        "{text}"
+    );
+    assert!(
+        text.contains(&format!("{}/cx", xdg_state.display())),
+        "{text}"
+    );
+}
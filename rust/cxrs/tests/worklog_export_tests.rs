@@ -0,0 +1,85 @@
+mod common;
+
+use common::*;
+use serde_json::{Value, json};
+
+fn seed_week(repo: &TempRepo) {
+    let rows = vec![
+        json!({"execution_id":"w1","ts":"2024-05-31T09:00:00Z","tool":"cxo","duration_ms":100,"effective_input_tokens":10}),
+        json!({"execution_id":"w2","ts":"2024-06-01T09:00:00Z","tool":"cxo","duration_ms":200,"effective_input_tokens":20,"task_id":"task_001"}),
+        json!({"execution_id":"w3","ts":"2024-06-01T10:00:00Z","tool":"cxrs_next","duration_ms":300,"effective_input_tokens":30,
+               "schema_valid":false,"schema_reason":"invalid_json","quarantine_id":"q_123","task_id":"task_001"}),
+        json!({"execution_id":"w4","ts":"2024-06-03T09:00:00Z","tool":"cxdiffsum","duration_ms":400,"effective_input_tokens":40}),
+        json!({"execution_id":"w5","ts":"2024-06-08T09:00:00Z","tool":"cxo","duration_ms":500,"effective_input_tokens":50}),
+    ];
+    write_runs_log_rows(repo, &rows);
+}
+
+#[test]
+fn worklog_json_reports_range_rollups_failures() {
+    let repo = TempRepo::new("cxrs-worklog");
+    seed_week(&repo);
+    let out = repo.run(&[
+        "worklog",
+        "--since",
+        "2024-06-01",
+        "--until",
+        "2024-06-07",
+        "--format",
+        "json",
+        "--include-failures",
+    ]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let v: Value = serde_json::from_str(&stdout_str(&out)).expect("json");
+    assert_eq!(v["runs"], json!(3));
+    assert_eq!(v["since"], json!("2024-06-01"));
+    assert_eq!(v["window"], Value::Null);
+    assert_eq!(
+        v["by_day"],
+        json!([
+            {"day":"2024-06-01","runs":2,"duration_ms":500,"effective_input_tokens":50,"schema_failures":1},
+            {"day":"2024-06-03","runs":1,"duration_ms":400,"effective_input_tokens":40,"schema_failures":0}
+        ])
+    );
+    assert_eq!(v["by_task"][0]["task_id"], json!("task_001"));
+    assert_eq!(v["by_task"][0]["runs"], json!(2));
+    assert_eq!(v["schema_failures"][0]["quarantine_id"], json!("q_123"));
+}
+
+#[test]
+fn worklog_markdown_and_html_render_day_groups() {
+    let repo = TempRepo::new("cxrs-worklog");
+    seed_week(&repo);
+    let out = repo.run(&["worklog", "--since", "2024-06-01", "--include-failures"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let text = stdout_str(&out);
+    assert!(text.contains("Window: 2024-06-01 .. now"));
+    assert!(text.contains("## By Day"));
+    assert!(text.contains("| task_001 | 2 | 500 | 50 | 1 |"));
+    assert!(text.contains("### 2024-06-08"));
+    assert!(!text.contains("2024-05-31"));
+    assert!(text.contains("- 2024-06-01T10:00:00Z | cxrs_next | quarantine=q_123 | invalid_json"));
+
+    let out = repo.run(&["worklog", "2", "--format", "html"]);
+    assert!(out.status.success(), "stderr={}", stderr_str(&out));
+    let html = stdout_str(&out);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<h2>By Day</h2>"));
+    assert!(html.contains("<td>2024-06-08</td>"));
+    assert!(!html.contains("2024-06-01"));
+    assert!(!html.contains("Schema Failures</h2>"));
+}
+
+#[test]
+fn worklog_rejects_bad_dates_and_formats() {
+    let repo = TempRepo::new("cxrs-worklog");
+    for args in [
+        vec!["worklog", "--since", "June 1"],
+        vec!["worklog", "--format", "pdf"],
+        vec!["worklog", "--since", "2024-06-02", "--until", "2024-06-01"],
+    ] {
+        let out = repo.run(&args);
+        assert_eq!(out.status.code(), Some(2), "{args:?}");
+        assert!(stderr_str(&out).contains("Usage: worklog"));
+    }
+}
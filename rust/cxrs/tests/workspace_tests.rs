@@ -0,0 +1,54 @@
+mod common;
+
+use common::*;
+use serde_json::Value;
+use std::fs;
+
+#[test]
+fn workspace_backup_info_and_restore_round_trip() {
+    let repo = TempRepo::new("cxrs-workspace");
+    let codex = repo.root.join(".codex");
+    fs::write(
+        codex.join("state.json"),
+        r#"{"preferences":{"llm_backend":"ollama"}}"#,
+    )
+    .unwrap();
+
+    let out = repo.run(&["workspace", "info", "--json"]);
+    assert_eq!(out.status.code(), Some(0), "stderr={}", stderr_str(&out));
+    let info: Value = serde_json::from_str(&stdout_str(&out)).expect("info json");
+    let entries = info["entries"].as_array().expect("entries");
+    assert!(entries.iter().any(|e| e["path"] == "schemas/"));
+    assert!(
+        entries
+            .iter()
+            .any(|e| e["path"] == "(files)" && e["files"] == 1)
+    );
+    assert!(info["bytes"].as_u64().unwrap() > 0);
+
+    let archive = repo.home.join("snap.tar.gz");
+    let archive_arg = archive.to_str().unwrap();
+    let out = repo.run(&["workspace", "backup", "--out", archive_arg]);
+    assert_eq!(out.status.code(), Some(0), "stderr={}", stderr_str(&out));
+    assert!(archive.is_file());
+
+    fs::write(codex.join("state.json"), "{}").unwrap();
+    let out = repo.run(&["workspace", "restore", archive_arg]);
+    assert_eq!(out.status.code(), Some(1));
+    assert!(stderr_str(&out).contains("--force"));
+
+    let out = repo.run(&["workspace", "restore", archive_arg, "--force"]);
+    assert_eq!(out.status.code(), Some(0), "stderr={}", stderr_str(&out));
+    assert!(stdout_str(&out).contains("previous workspace moved to"));
+    let state = fs::read_to_string(codex.join("state.json")).unwrap();
+    assert!(state.contains("ollama"), "{state}");
+    let aside = fs::read_dir(&repo.root)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_name().to_string_lossy().starts_with(".codex.bak-"))
+        .expect("previous workspace kept");
+    assert_eq!(
+        fs::read_to_string(aside.path().join("state.json")).unwrap(),
+        "{}"
+    );
+}
@@ -5,9 +5,9 @@ ROOT="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
 
 out="$($ROOT/bin/cx schema list --json)"
 count="$(printf '%s' "$out" | jq -r '.file_count')"
-[[ "$count" -ge 4 ]]
+[[ "$count" -ge 5 ]]
 
-for name in commitjson.schema.json diffsum.schema.json next.schema.json fixrun.schema.json; do
+for name in commitjson.schema.json diffsum.schema.json next.schema.json fixrun.schema.json explain.schema.json fix.schema.json fanout.schema.json stage.schema.json; do
   printf '%s' "$out" | jq -e --arg n "$name" '.schemas[] | select(.name == $n)' >/dev/null
   [[ -f "$ROOT/.codex/schemas/$name" ]]
 done
@@ -16,5 +16,8 @@ $ROOT/bin/cx supports commitjson >/dev/null
 $ROOT/bin/cx supports diffsum >/dev/null
 $ROOT/bin/cx supports next >/dev/null
 $ROOT/bin/cx supports fix-run >/dev/null
+$ROOT/bin/cx supports explain >/dev/null
+$ROOT/bin/cx supports fanout >/dev/null
+$ROOT/bin/cx supports stage >/dev/null
 
 echo "schema_registry_ok"